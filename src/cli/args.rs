@@ -3,12 +3,13 @@
 use anyhow::{anyhow, Result};
 use clap::{Args, Parser, Subcommand};
 
-use super::{build_launch_args, resolve_config_path, LaunchProfile};
+use super::{build_launch_args, resolve_config_path, LaunchProfile, TransportMode};
 
 /// Parsed command intent from CLI.
 #[derive(Debug, Clone)]
 pub enum ParsedCommand {
     RunServer(LaunchProfile),
+    SelfCheck(LaunchProfile),
     Cli(CliCommand),
 }
 
@@ -21,13 +22,16 @@ pub enum CliCommand {
     /// Manage bundled Codex skills (install/remove).
     #[command(about = "Manage bundled Codex skills (install/remove)")]
     Skill(SkillArgs),
+    /// Run the server as a launchd agent or systemd user service.
+    #[command(about = "Run the server as a launchd agent or systemd user service")]
+    Service(ServiceArgs),
 }
 
 /// `config` command container.
 #[derive(Debug, Clone, Args)]
 #[command(
     about = "Generate Seiro MCP configuration",
-    long_about = "Generate Seiro MCP configuration.\n\nSubcommands:\n  mcp      Print a Codex MCP registration snippet.\n  project  Create a project-local seiro-mcp.toml.",
+    long_about = "Generate Seiro MCP configuration.\n\nSubcommands:\n  mcp       Print a Codex MCP registration snippet.\n  project   Create a project-local seiro-mcp.toml.\n  generate  Create seiro-mcp.toml from detected Xcode and project values.",
     after_help = "Hint: use `seiro-mcp config mcp` for Codex config and `seiro-mcp config project` in a project root."
 )]
 pub struct ConfigArgs {
@@ -42,6 +46,8 @@ pub enum ConfigCommand {
     Mcp,
     /// Create a project-local seiro-mcp.toml.
     Project(ConfigProjectArgs),
+    /// Create seiro-mcp.toml from the detected Xcode developer dir, workspace, and schemes.
+    Generate(ConfigGenerateArgs),
 }
 
 /// Arguments for `config project`.
@@ -52,6 +58,17 @@ pub struct ConfigProjectArgs {
     pub force: bool,
 }
 
+/// Arguments for `config generate`.
+#[derive(Debug, Clone, Args)]
+pub struct ConfigGenerateArgs {
+    /// Overwrite an existing seiro-mcp.toml.
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+    /// Write the detected values without prompting.
+    #[arg(long, default_value_t = false)]
+    pub yes: bool,
+}
+
 /// `skill` command container.
 #[derive(Debug, Clone, Args)]
 #[command(
@@ -93,6 +110,49 @@ pub struct SkillRemoveArgs {
     pub skill_name: String,
 }
 
+/// `service` command container.
+#[derive(Debug, Clone, Args)]
+#[command(
+    about = "Run the server as a launchd agent or systemd user service",
+    long_about = "Run the server as a launchd agent (macOS) or systemd user service (Linux) serving WebSocket sessions.\n\nSubcommands:\n  install    Write the service file for this binary and config, then start it.\n  uninstall  Stop the service and delete its file.\n  status     Report whether the service is installed and running.",
+    after_help = "Hint: use `seiro-mcp service install --dry-run` to preview without touching files. The config needs [auth] token or [[auth.tokens]]."
+)]
+pub struct ServiceArgs {
+    #[command(subcommand)]
+    pub command: ServiceCommand,
+}
+
+/// Service management subcommands.
+#[derive(Debug, Clone, Subcommand)]
+pub enum ServiceCommand {
+    /// Write the service file for this binary and config, then start it.
+    Install(ServiceInstallArgs),
+    /// Stop the service and delete its file.
+    Uninstall,
+    /// Report whether the service is installed and running.
+    Status,
+}
+
+/// Arguments for `service install`.
+#[derive(Debug, Clone, Args)]
+pub struct ServiceInstallArgs {
+    /// Config the service runs with; defaults to the global `--config`, `MCP_CONFIG_PATH`, or ./seiro-mcp.toml.
+    #[arg(long = "config")]
+    pub config: Option<std::path::PathBuf>,
+    /// `[profile.<name>]` section to apply; defaults to the global `--profile`.
+    #[arg(long = "profile")]
+    pub profile: Option<String>,
+    /// Extra environment for the service as KEY=VALUE; repeatable. PATH and RUST_LOG are copied when set.
+    #[arg(long = "env", value_name = "KEY=VALUE")]
+    pub env: Vec<String>,
+    /// Overwrite an existing service file.
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+    /// Show the planned service without touching files.
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
+
 /// Command-line arguments.
 #[derive(Debug, Clone, Parser)]
 #[command(
@@ -105,6 +165,15 @@ pub struct LaunchProfileArgs {
     /// Path to seiro-mcp.toml (overrides MCP_CONFIG_PATH).
     #[arg(long = "config")]
     pub config_override: Option<std::path::PathBuf>,
+    /// `[profile.<name>]` section of the config to apply (e.g. `ci`, `local`).
+    #[arg(long = "profile")]
+    pub config_profile: Option<String>,
+    /// `stdio` for a client that launches the server, or `websocket` to listen on `[server] host:port`.
+    #[arg(long = "transport", value_enum, default_value_t = TransportMode::Stdio)]
+    pub transport: TransportMode,
+    /// Check the config, tool router, sandbox, and xcodebuild, print a JSON report, and exit.
+    #[arg(long = "self-check", default_value_t = false)]
+    pub self_check: bool,
     /// Optional CLI command mode.
     #[command(subcommand)]
     pub command: Option<CliCommand>,
@@ -115,10 +184,13 @@ impl LaunchProfileArgs {
     pub fn build(self) -> Result<LaunchProfile> {
         let config_path = resolve_config_path(self.config_override)?;
 
-        let launch_args = build_launch_args(&config_path);
+        let launch_args =
+            build_launch_args(&config_path, self.config_profile.as_deref(), self.transport);
 
         Ok(LaunchProfile {
             config_path,
+            config_profile: self.config_profile,
+            transport: self.transport,
             launch_args,
         })
     }
@@ -126,10 +198,18 @@ impl LaunchProfileArgs {
     /// Parse CLI args into either server launch mode or utility command mode.
     pub fn into_command(self) -> Result<ParsedCommand> {
         match self.command {
-            Some(command) => {
+            Some(mut command) => {
                 validate_command(&command)?;
+                if let CliCommand::Service(ServiceArgs {
+                    command: ServiceCommand::Install(install),
+                }) = &mut command
+                {
+                    install.config = install.config.take().or(self.config_override);
+                    install.profile = install.profile.take().or(self.config_profile);
+                }
                 Ok(ParsedCommand::Cli(command))
             }
+            None if self.self_check => Ok(ParsedCommand::SelfCheck(self.build()?)),
             None => Ok(ParsedCommand::RunServer(self.build()?)),
         }
     }
@@ -140,7 +220,7 @@ fn validate_command(command: &CliCommand) -> Result<()> {
     use crate::cli::validate_skill_name_prefix;
 
     match command {
-        CliCommand::Config(_) => {}
+        CliCommand::Config(_) | CliCommand::Service(_) => {}
         CliCommand::Skill(skill) => match &skill.command {
             SkillCommand::Install(args) => {
                 if let Some(skill_name) = &args.skill_name {
@@ -0,0 +1,247 @@
+//! `config generate`: a project config filled in from the local machine.
+use std::{
+    fs,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{anyhow, Context, Result};
+
+use super::PROJECT_CONFIG_FILE;
+
+const DEFAULT_XCODE_PATH: &str = "/Applications/Xcode.app/Contents/Developer";
+const DEFAULT_DESTINATION: &str = "platform=visionOS Simulator,name=Apple Vision Pro";
+
+/// Values written into the generated config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedConfig {
+    pub xcode_path: String,
+    pub workspace_dir: PathBuf,
+    pub project_path: Option<PathBuf>,
+    pub schemes: Vec<String>,
+}
+
+impl DetectedConfig {
+    /// Detect values for `directory`: the active developer dir from `xcode-select -p`, the
+    /// directory itself as the allowed workspace, and its Xcode project with shared schemes.
+    pub fn detect(directory: &Path) -> Self {
+        let xcode_path = Command::new("xcode-select")
+            .arg("-p")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|path| !path.is_empty())
+            .unwrap_or_else(|| DEFAULT_XCODE_PATH.to_string());
+        let project_path = find_xcode_project(directory);
+        let schemes = project_path
+            .as_deref()
+            .map(shared_schemes)
+            .unwrap_or_default();
+        Self {
+            xcode_path,
+            workspace_dir: directory.to_path_buf(),
+            project_path,
+            schemes,
+        }
+    }
+
+    /// Ask for each value on `output`, keeping the detected one when the answer is empty.
+    pub fn confirm(mut self, input: &mut impl BufRead, output: &mut impl Write) -> Result<Self> {
+        if let Some(answer) = prompt(input, output, "Xcode developer dir", &self.xcode_path)? {
+            self.xcode_path = answer;
+        }
+        let workspace = self.workspace_dir.display().to_string();
+        if let Some(answer) = prompt(input, output, "Allowed workspace dir", &workspace)? {
+            self.workspace_dir = PathBuf::from(answer);
+        }
+        let schemes = self.schemes.join(", ");
+        if let Some(answer) = prompt(input, output, "Allowed schemes (comma separated)", &schemes)?
+        {
+            self.schemes = answer
+                .split(',')
+                .map(str::trim)
+                .filter(|scheme| !scheme.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        Ok(self)
+    }
+
+    /// Render the `[visionos]` section for these values.
+    pub fn render(&self) -> String {
+        let quote = |value: &str| toml::Value::String(value.to_string()).to_string();
+        let list = |values: &[String]| {
+            let items: Vec<_> = values.iter().map(|value| quote(value)).collect();
+            format!("[{}]", items.join(", "))
+        };
+        let mut rendered = String::from("[visionos]\n");
+        rendered.push_str(&format!(
+            "allowed_paths = {}\n",
+            list(&[self.workspace_dir.display().to_string()])
+        ));
+        rendered.push_str(&format!("allowed_schemes = {}\n", list(&self.schemes)));
+        if let Some(project_path) = &self.project_path {
+            rendered.push_str(&format!(
+                "default_project_path = {}\n",
+                quote(&project_path.display().to_string())
+            ));
+        }
+        rendered.push_str(&format!(
+            "default_destination = {}\n",
+            quote(DEFAULT_DESTINATION)
+        ));
+        rendered.push_str(&format!("xcode_path = {}\n", quote(&self.xcode_path)));
+        rendered
+    }
+}
+
+/// Write a detected config to `directory`, asking to confirm each value when `interactive`.
+pub fn generate_project_config_in_dir(
+    directory: &Path,
+    force: bool,
+    interactive: bool,
+) -> Result<String> {
+    let destination = directory.join(PROJECT_CONFIG_FILE);
+    if destination.exists() && !force {
+        return Err(anyhow!(
+            "{} already exists; re-run with --force to overwrite",
+            PROJECT_CONFIG_FILE
+        ));
+    }
+    let mut detected = DetectedConfig::detect(directory);
+    if interactive {
+        let stdin = std::io::stdin();
+        detected = detected.confirm(&mut stdin.lock(), &mut std::io::stderr())?;
+    }
+    fs::write(&destination, detected.render()).with_context(|| {
+        format!(
+            "failed to write project config to {}",
+            destination.to_string_lossy()
+        )
+    })?;
+    Ok(format!("created {PROJECT_CONFIG_FILE}"))
+}
+
+fn prompt(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    label: &str,
+    detected: &str,
+) -> Result<Option<String>> {
+    write!(output, "{label} [{detected}]: ").context("failed to write prompt")?;
+    output.flush().context("failed to write prompt")?;
+    let mut answer = String::new();
+    input
+        .read_line(&mut answer)
+        .context("failed to read answer")?;
+    let answer = answer.trim();
+    Ok((!answer.is_empty()).then(|| answer.to_string()))
+}
+
+/// First `*.xcodeproj` in `directory`, by name.
+fn find_xcode_project(directory: &Path) -> Option<PathBuf> {
+    let mut projects: Vec<_> = fs::read_dir(directory)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xcodeproj"))
+        .collect();
+    projects.sort();
+    projects.into_iter().next()
+}
+
+/// Scheme names shared in `project` (`xcshareddata/xcschemes/*.xcscheme`).
+fn shared_schemes(project: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(project.join("xcshareddata").join("xcschemes")) else {
+        return Vec::new();
+    };
+    let mut schemes: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xcscheme"))
+        .filter_map(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(str::to_string)
+        })
+        .collect();
+    schemes.sort();
+    schemes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::server::config::ServerConfig;
+
+    #[test]
+    fn generated_config_loads_with_detected_project_and_schemes() {
+        let temp = tempdir().expect("can create temporary directory");
+        let schemes_dir = temp
+            .path()
+            .join("VisionApp.xcodeproj")
+            .join("xcshareddata")
+            .join("xcschemes");
+        fs::create_dir_all(&schemes_dir).expect("can create schemes dir");
+        for scheme in ["VisionApp", "VisionAppTests"] {
+            fs::write(schemes_dir.join(format!("{scheme}.xcscheme")), "").expect("can write");
+        }
+
+        let result = generate_project_config_in_dir(temp.path(), false, false);
+
+        result.expect("config should be generated");
+        let config = ServerConfig::load_from_path(temp.path().join(PROJECT_CONFIG_FILE))
+            .expect("generated config should load");
+        assert_eq!(
+            config.visionos.allowed_paths,
+            vec![temp.path().to_path_buf()]
+        );
+        assert_eq!(
+            config.visionos.allowed_schemes,
+            vec!["VisionApp", "VisionAppTests"]
+        );
+        assert_eq!(
+            config.visionos.default_project_path,
+            Some(temp.path().join("VisionApp.xcodeproj"))
+        );
+    }
+
+    #[test]
+    fn confirm_keeps_detected_values_on_empty_answers() {
+        let detected = DetectedConfig {
+            xcode_path: DEFAULT_XCODE_PATH.into(),
+            workspace_dir: PathBuf::from("/Users/example/work"),
+            project_path: None,
+            schemes: vec!["VisionApp".into()],
+        };
+        let mut output = Vec::new();
+
+        let confirmed = detected
+            .clone()
+            .confirm(&mut Cursor::new("\n/tmp/work\nA, B\n"), &mut output)
+            .expect("answers are read");
+
+        assert_eq!(confirmed.xcode_path, detected.xcode_path);
+        assert_eq!(confirmed.workspace_dir, PathBuf::from("/tmp/work"));
+        assert_eq!(confirmed.schemes, vec!["A", "B"]);
+        assert!(String::from_utf8_lossy(&output).contains("[VisionApp]"));
+    }
+
+    #[test]
+    fn generate_preserves_existing_without_force() {
+        let temp = tempdir().expect("can create temporary directory");
+        fs::write(temp.path().join(PROJECT_CONFIG_FILE), "existing").expect("can write existing");
+
+        let result = generate_project_config_in_dir(temp.path(), false, false);
+
+        assert!(result.is_err());
+        assert_eq!(
+            fs::read_to_string(temp.path().join(PROJECT_CONFIG_FILE)).expect("can read config"),
+            "existing"
+        );
+    }
+}
@@ -1,5 +1,5 @@
 //! CLI entrypoint module structure.
-use std::{fs, path::Path};
+use std::{fs, io::IsTerminal, path::Path};
 
 use anyhow::{anyhow, Context, Result};
 use serde_json::json;
@@ -10,13 +10,16 @@ use crate::lib::fs::{
 };
 
 pub mod args;
+pub mod generate;
 pub mod profile;
+pub mod service;
 
 pub use args::{
-    CliCommand, ConfigArgs, ConfigCommand, ConfigProjectArgs, LaunchProfileArgs, ParsedCommand,
-    SkillArgs, SkillCommand, SkillInstallArgs, SkillRemoveArgs,
+    CliCommand, ConfigArgs, ConfigCommand, ConfigGenerateArgs, ConfigProjectArgs,
+    LaunchProfileArgs, ParsedCommand, ServiceArgs, ServiceCommand, ServiceInstallArgs, SkillArgs,
+    SkillCommand, SkillInstallArgs, SkillRemoveArgs,
 };
-pub use profile::{build_launch_args, resolve_config_path, LaunchProfile};
+pub use profile::{build_launch_args, resolve_config_path, LaunchProfile, TransportMode};
 
 /// Prefix reserved for Seiro-managed bundled skills.
 pub const SKILL_NAME_PREFIX: &str = "seiro-mcp-";
@@ -40,7 +43,7 @@ const BUNDLED_VISIONOS_SMALL_ICON_PATH: &str = "assets/seiro-mcp-logo-small.svg"
 const BUNDLED_VISIONOS_SMALL_ICON: &[u8] = include_bytes!(
     "../../.agents/skills/seiro-mcp-visionos-build-operator/assets/seiro-mcp-logo-small.svg"
 );
-const PROJECT_CONFIG_FILE: &str = "seiro-mcp.toml";
+pub(crate) const PROJECT_CONFIG_FILE: &str = "seiro-mcp.toml";
 const PROJECT_CONFIG_TEMPLATE: &str = r#"[visionos]
 allowed_paths = []
 allowed_schemes = []
@@ -62,6 +65,11 @@ pub fn execute_cli_command(command: CliCommand) -> Result<String> {
         CliCommand::Config(config) => match config.command {
             ConfigCommand::Mcp => render_mcp_config_snippet(),
             ConfigCommand::Project(args) => write_project_config(args.force),
+            ConfigCommand::Generate(args) => {
+                let cwd = std::env::current_dir().context("failed to obtain current directory")?;
+                let interactive = !args.yes && std::io::stdin().is_terminal();
+                generate::generate_project_config_in_dir(&cwd, args.force, interactive)
+            }
         },
         CliCommand::Skill(skill) => match skill.command {
             SkillCommand::Install(args) => {
@@ -86,6 +94,17 @@ pub fn execute_cli_command(command: CliCommand) -> Result<String> {
                 )
             }
         },
+        CliCommand::Service(service) => match service.command {
+            ServiceCommand::Install(args) => service::install_service(
+                args.config,
+                args.profile.as_deref(),
+                &args.env,
+                args.force,
+                args.dry_run,
+            ),
+            ServiceCommand::Uninstall => service::uninstall_service(),
+            ServiceCommand::Status => service::service_status(),
+        },
     }
 }
 
@@ -9,10 +9,31 @@ use anyhow::{Context, Result};
 const DEFAULT_CONFIG: &str = "seiro-mcp.toml";
 const MCP_CONFIG_ENV: &str = "MCP_CONFIG_PATH";
 
+/// How the server talks to MCP clients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TransportMode {
+    /// One client on stdin/stdout that launched the server as a child process.
+    #[default]
+    Stdio,
+    /// Token-authenticated WebSocket sessions on `[server] host:port`.
+    Websocket,
+}
+
+impl TransportMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TransportMode::Stdio => "stdio",
+            TransportMode::Websocket => "websocket",
+        }
+    }
+}
+
 /// Resolved launch profile.
 #[derive(Debug, Clone)]
 pub struct LaunchProfile {
     pub config_path: PathBuf,
+    pub config_profile: Option<String>,
+    pub transport: TransportMode,
     pub launch_args: Vec<String>,
 }
 
@@ -31,8 +52,19 @@ pub fn resolve_config_path(override_path: Option<PathBuf>) -> Result<PathBuf> {
 }
 
 /// Build launch arguments suitable for reproduction/logging.
-pub fn build_launch_args(config: &Path) -> Vec<String> {
-    vec![format!("--config={}", config.display())]
+pub fn build_launch_args(
+    config: &Path,
+    profile: Option<&str>,
+    transport: TransportMode,
+) -> Vec<String> {
+    let mut args = vec![format!("--config={}", config.display())];
+    if let Some(profile) = profile {
+        args.push(format!("--profile={profile}"));
+    }
+    if transport != TransportMode::Stdio {
+        args.push(format!("--transport={}", transport.as_str()));
+    }
+    args
 }
 
 #[cfg(test)]
@@ -52,4 +84,21 @@ mod tests {
             .expect("absolute override should resolve");
         assert_eq!(config, PathBuf::from("/tmp/custom.toml"));
     }
+
+    #[test]
+    fn launch_args_name_non_default_transport() {
+        let config = Path::new("/tmp/seiro-mcp.toml");
+        assert_eq!(
+            build_launch_args(config, None, TransportMode::Stdio),
+            ["--config=/tmp/seiro-mcp.toml"]
+        );
+        assert_eq!(
+            build_launch_args(config, Some("ci"), TransportMode::Websocket),
+            [
+                "--config=/tmp/seiro-mcp.toml",
+                "--profile=ci",
+                "--transport=websocket"
+            ]
+        );
+    }
 }
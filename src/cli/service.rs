@@ -0,0 +1,539 @@
+//! `service install|uninstall|status`: run the server as a per-user daemon, as a launchd agent on
+//! macOS or a systemd user unit elsewhere, serving WebSocket sessions.
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::json;
+
+use super::{build_launch_args, resolve_config_path, TransportMode};
+use crate::{lib::telemetry::REDACTED, server::config::ServerConfig};
+
+/// launchd label and systemd unit name.
+pub const SERVICE_LABEL: &str = "dev.seiro.mcp";
+const SYSTEMD_UNIT_NAME: &str = "seiro-mcp.service";
+/// Variables copied from the installing shell when set, so the daemon finds the same tools.
+const FORWARDED_ENV: &[&str] = &["PATH", "RUST_LOG"];
+
+/// Service manager on this host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceManager {
+    Launchd,
+    Systemd,
+}
+
+impl ServiceManager {
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            ServiceManager::Launchd
+        } else {
+            ServiceManager::Systemd
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ServiceManager::Launchd => "launchd",
+            ServiceManager::Systemd => "systemd",
+        }
+    }
+
+    /// `~/Library/LaunchAgents/<label>.plist` or `~/.config/systemd/user/seiro-mcp.service`.
+    pub fn unit_path(self, home: &Path) -> PathBuf {
+        match self {
+            ServiceManager::Launchd => home
+                .join("Library/LaunchAgents")
+                .join(format!("{SERVICE_LABEL}.plist")),
+            ServiceManager::Systemd => home.join(".config/systemd/user").join(SYSTEMD_UNIT_NAME),
+        }
+    }
+}
+
+/// What the daemon runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceDefinition {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    /// The config's directory, so relative paths in it resolve as they do interactively.
+    pub working_directory: PathBuf,
+    /// launchd only; systemd sends output to the journal.
+    pub log_path: PathBuf,
+}
+
+impl ServiceDefinition {
+    /// Run the current binary on `config` over WebSocket, with the forwarded variables and `env`
+    /// (`KEY=VALUE`) on top.
+    pub fn for_current_binary(
+        config: &Path,
+        profile: Option<&str>,
+        env_pairs: &[String],
+        home: &Path,
+    ) -> Result<Self> {
+        let program = env::current_exe().context("failed to resolve current executable path")?;
+        let mut variables = BTreeMap::new();
+        for name in FORWARDED_ENV {
+            if let Ok(value) = env::var(name) {
+                variables.insert(name.to_string(), value);
+            }
+        }
+        for pair in env_pairs {
+            let (name, value) = pair
+                .split_once('=')
+                .filter(|(name, _)| !name.trim().is_empty())
+                .ok_or_else(|| anyhow!("invalid --env `{pair}`: expected KEY=VALUE"))?;
+            variables.insert(name.trim().to_string(), value.to_string());
+        }
+        Ok(Self {
+            program,
+            args: build_launch_args(config, profile, TransportMode::Websocket),
+            env: variables,
+            working_directory: config
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from("/")),
+            log_path: home.join("Library/Logs/seiro-mcp.log"),
+        })
+    }
+
+    /// The same definition with every environment value masked, for showing a planned unit.
+    pub fn with_masked_env(&self) -> Self {
+        Self {
+            env: self
+                .env
+                .keys()
+                .map(|name| (name.clone(), REDACTED.to_string()))
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    /// File contents for `manager`.
+    pub fn render(&self, manager: ServiceManager) -> String {
+        match manager {
+            ServiceManager::Launchd => self.render_launchd_plist(),
+            ServiceManager::Systemd => self.render_systemd_unit(),
+        }
+    }
+
+    fn render_launchd_plist(&self) -> String {
+        let string = |value: &str| format!("<string>{}</string>", xml_escape(value));
+        let mut plist = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+             <plist version=\"1.0\">\n<dict>\n",
+        );
+        plist.push_str(&format!(
+            "  <key>Label</key>\n  {}\n",
+            string(SERVICE_LABEL)
+        ));
+        plist.push_str("  <key>ProgramArguments</key>\n  <array>\n");
+        plist.push_str(&format!(
+            "    {}\n",
+            string(&self.program.display().to_string())
+        ));
+        for arg in &self.args {
+            plist.push_str(&format!("    {}\n", string(arg)));
+        }
+        plist.push_str("  </array>\n");
+        if !self.env.is_empty() {
+            plist.push_str("  <key>EnvironmentVariables</key>\n  <dict>\n");
+            for (name, value) in &self.env {
+                plist.push_str(&format!(
+                    "    <key>{}</key>\n    {}\n",
+                    xml_escape(name),
+                    string(value)
+                ));
+            }
+            plist.push_str("  </dict>\n");
+        }
+        let log_path = string(&self.log_path.display().to_string());
+        plist.push_str(&format!(
+            "  <key>WorkingDirectory</key>\n  {}\n",
+            string(&self.working_directory.display().to_string())
+        ));
+        plist.push_str("  <key>RunAtLoad</key>\n  <true/>\n");
+        plist.push_str("  <key>KeepAlive</key>\n  <true/>\n");
+        plist.push_str(&format!("  <key>StandardOutPath</key>\n  {log_path}\n"));
+        plist.push_str(&format!("  <key>StandardErrorPath</key>\n  {log_path}\n"));
+        plist.push_str("</dict>\n</plist>\n");
+        plist
+    }
+
+    fn render_systemd_unit(&self) -> String {
+        let command = std::iter::once(self.program.display().to_string())
+            .chain(self.args.iter().cloned())
+            .map(|word| systemd_quote(&word))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut unit = format!(
+            "[Unit]\nDescription=Seiro MCP server\nAfter=network.target\n\n[Service]\nExecStart={command}\nWorkingDirectory={}\n",
+            systemd_quote(&self.working_directory.display().to_string())
+        );
+        for (name, value) in &self.env {
+            unit.push_str(&format!(
+                "Environment={}\n",
+                systemd_quote(&format!("{name}={value}"))
+            ));
+        }
+        unit.push_str("Restart=on-failure\nRestartSec=5\n\n[Install]\nWantedBy=default.target\n");
+        unit
+    }
+}
+
+/// Write the unit for the current binary and load it.
+pub fn install_service(
+    config: Option<PathBuf>,
+    profile: Option<&str>,
+    env_pairs: &[String],
+    force: bool,
+    dry_run: bool,
+) -> Result<String> {
+    let config = resolve_config_path(config)?;
+    let loaded = ServerConfig::load_with_profile(config.clone(), profile.map(str::to_string))
+        .with_context(|| format!("failed to load {}", config.display()))?;
+    if !loaded.auth.has_tokens() {
+        return Err(anyhow!(
+            "the service serves WebSocket sessions, which need [auth] token or [[auth.tokens]] in {}",
+            config.display()
+        ));
+    }
+    let home = home_dir()?;
+    let manager = ServiceManager::current();
+    let definition = ServiceDefinition::for_current_binary(&config, profile, env_pairs, &home)?;
+    let unit_path = manager.unit_path(&home);
+    let contents = definition.render(manager);
+    let status = write_unit(&unit_path, &contents, force, dry_run)?;
+    if status == "installed" {
+        if manager == ServiceManager::Launchd {
+            if let Some(parent) = definition.log_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        load(manager, &unit_path)?;
+    }
+    let message = match status {
+        "planned" => "dry-run: no files were modified",
+        "installed" => "service installed and started",
+        _ => "service file already exists; re-run with --force to overwrite",
+    };
+    let mut payload = json!({
+        "status": status,
+        "manager": manager.as_str(),
+        "label": SERVICE_LABEL,
+        "unit_path": unit_path.to_string_lossy(),
+        "program": definition.program.to_string_lossy(),
+        "args": definition.args,
+        "environment": definition.env.keys().collect::<Vec<_>>(),
+        "message": message
+    });
+    if status == "planned" {
+        // `--env` values may be secrets; the written unit has them, the preview does not.
+        payload["contents"] = json!(definition.with_masked_env().render(manager));
+    }
+    Ok(serde_json::to_string_pretty(&payload)?)
+}
+
+/// Stop the service and remove its unit.
+pub fn uninstall_service() -> Result<String> {
+    let manager = ServiceManager::current();
+    let unit_path = manager.unit_path(&home_dir()?);
+    let status = if unit_path.exists() {
+        unload(manager);
+        fs::remove_file(&unit_path)
+            .with_context(|| format!("failed to remove {}", unit_path.display()))?;
+        if manager == ServiceManager::Systemd {
+            let _ = systemctl(&["daemon-reload"]);
+        }
+        "removed"
+    } else {
+        "not_found"
+    };
+    let payload = json!({
+        "status": status,
+        "manager": manager.as_str(),
+        "label": SERVICE_LABEL,
+        "unit_path": unit_path.to_string_lossy(),
+        "message": if status == "removed" { "service stopped and removed" } else { "service not installed" }
+    });
+    Ok(serde_json::to_string_pretty(&payload)?)
+}
+
+/// Whether the unit is installed and the daemon is running.
+pub fn service_status() -> Result<String> {
+    let manager = ServiceManager::current();
+    let unit_path = manager.unit_path(&home_dir()?);
+    let installed = unit_path.exists();
+    let (running, pid) = if installed {
+        running_pid(manager)
+    } else {
+        (false, None)
+    };
+    let status = match (installed, running) {
+        (false, _) => "not_installed",
+        (true, true) => "running",
+        (true, false) => "stopped",
+    };
+    let payload = json!({
+        "status": status,
+        "manager": manager.as_str(),
+        "label": SERVICE_LABEL,
+        "unit_path": unit_path.to_string_lossy(),
+        "pid": pid
+    });
+    Ok(serde_json::to_string_pretty(&payload)?)
+}
+
+/// Write `contents` to `path`, returning `planned`, `installed`, or `skipped_existing`. The file
+/// carries `--env` values, so only the owner may read it.
+fn write_unit(path: &Path, contents: &str, force: bool, dry_run: bool) -> Result<&'static str> {
+    if path.exists() && !force {
+        return Ok("skipped_existing");
+    }
+    if dry_run {
+        return Ok("planned");
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let write = || -> std::io::Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        // `mode` only applies to new files; a unit replaced with --force is tightened too.
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        file.write_all(contents.as_bytes())
+    };
+    write().with_context(|| format!("failed to write {}", path.display()))?;
+    Ok("installed")
+}
+
+fn load(manager: ServiceManager, unit_path: &Path) -> Result<()> {
+    match manager {
+        ServiceManager::Launchd => {
+            // Replacing an installed agent needs the old one unloaded first.
+            unload(manager);
+            launchctl(&["bootstrap", &launchd_domain(), &unit_path.to_string_lossy()])
+        }
+        ServiceManager::Systemd => {
+            systemctl(&["daemon-reload"])?;
+            systemctl(&["enable", "--now", SYSTEMD_UNIT_NAME])?;
+            systemctl(&["restart", SYSTEMD_UNIT_NAME])
+        }
+    }
+}
+
+fn unload(manager: ServiceManager) {
+    let _ = match manager {
+        ServiceManager::Launchd => launchctl(&["bootout", &launchd_target()]),
+        ServiceManager::Systemd => systemctl(&["disable", "--now", SYSTEMD_UNIT_NAME]),
+    };
+}
+
+fn running_pid(manager: ServiceManager) -> (bool, Option<u32>) {
+    let output = match manager {
+        ServiceManager::Launchd => Command::new("launchctl")
+            .args(["print", &launchd_target()])
+            .output(),
+        ServiceManager::Systemd => Command::new("systemctl")
+            .args([
+                "--user",
+                "show",
+                SYSTEMD_UNIT_NAME,
+                "--property=MainPID",
+                "--value",
+            ])
+            .output(),
+    };
+    let Some(output) = output.ok().filter(|output| output.status.success()) else {
+        return (false, None);
+    };
+    let pid = parse_pid(manager, &String::from_utf8_lossy(&output.stdout));
+    (pid.is_some(), pid)
+}
+
+/// `pid = 123` from `launchctl print`, or the bare `MainPID` value (0 when stopped).
+fn parse_pid(manager: ServiceManager, stdout: &str) -> Option<u32> {
+    let value = match manager {
+        ServiceManager::Launchd => stdout
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("pid = "))?,
+        ServiceManager::Systemd => stdout.trim(),
+    };
+    value.trim().parse().ok().filter(|pid| *pid > 0)
+}
+
+fn launchd_domain() -> String {
+    format!("gui/{}", unsafe { libc::getuid() })
+}
+
+fn launchd_target() -> String {
+    format!("{}/{SERVICE_LABEL}", launchd_domain())
+}
+
+fn launchctl(args: &[&str]) -> Result<()> {
+    run("launchctl", args)
+}
+
+fn systemctl(args: &[&str]) -> Result<()> {
+    let args: Vec<&str> = std::iter::once("--user")
+        .chain(args.iter().copied())
+        .collect();
+    run("systemctl", &args)
+}
+
+fn run(program: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("failed to run {program}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "`{program} {}` failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr).trim()
+    ))
+}
+
+fn home_dir() -> Result<PathBuf> {
+    env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow!("HOME is unset"))
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Double-quote `word` for a systemd unit, escaping `\`, `"`, and the `%` specifier prefix.
+fn systemd_quote(word: &str) -> String {
+    format!(
+        "\"{}\"",
+        word.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('%', "%%")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn definition() -> ServiceDefinition {
+        ServiceDefinition {
+            program: PathBuf::from("/Users/example/.cargo/bin/seiro-mcp"),
+            args: build_launch_args(
+                Path::new("/Users/example/build host/seiro-mcp.toml"),
+                None,
+                TransportMode::Websocket,
+            ),
+            env: BTreeMap::from([
+                ("PATH".to_string(), "/usr/bin:/bin".to_string()),
+                ("RUST_LOG".to_string(), "info&debug".to_string()),
+            ]),
+            working_directory: PathBuf::from("/Users/example/build host"),
+            log_path: PathBuf::from("/Users/example/Library/Logs/seiro-mcp.log"),
+        }
+    }
+
+    #[test]
+    fn launchd_plist_runs_the_binary_over_websocket() {
+        let plist = definition().render(ServiceManager::Launchd);
+
+        assert!(plist.contains("<string>dev.seiro.mcp</string>"));
+        assert!(plist.contains("<string>/Users/example/.cargo/bin/seiro-mcp</string>"));
+        assert!(
+            plist.contains("<string>--config=/Users/example/build host/seiro-mcp.toml</string>")
+        );
+        assert!(plist.contains("<string>--transport=websocket</string>"));
+        assert!(plist.contains("<key>RUST_LOG</key>\n    <string>info&amp;debug</string>"));
+        assert!(plist.contains("<key>KeepAlive</key>\n  <true/>"));
+        assert!(plist.ends_with("</dict>\n</plist>\n"));
+    }
+
+    #[test]
+    fn systemd_unit_quotes_arguments_and_environment() {
+        let unit = definition().render(ServiceManager::Systemd);
+
+        assert!(unit.contains(
+            "ExecStart=\"/Users/example/.cargo/bin/seiro-mcp\" \"--config=/Users/example/build host/seiro-mcp.toml\" \"--transport=websocket\"\n"
+        ));
+        assert!(unit.contains("Environment=\"PATH=/usr/bin:/bin\"\n"));
+        assert!(unit.contains("WantedBy=default.target"));
+        assert_eq!(systemd_quote("50%"), "\"50%%\"");
+    }
+
+    #[test]
+    fn write_unit_respects_dry_run_and_force() {
+        let temp = tempdir().expect("can create temporary directory");
+        let path = temp.path().join("LaunchAgents/dev.seiro.mcp.plist");
+
+        assert_eq!(
+            write_unit(&path, "new", false, true).expect("dry run"),
+            "planned"
+        );
+        assert!(!path.exists());
+        assert_eq!(
+            write_unit(&path, "new", false, false).expect("write"),
+            "installed"
+        );
+        assert_eq!(
+            write_unit(&path, "newer", false, false).expect("existing"),
+            "skipped_existing"
+        );
+        assert_eq!(fs::read_to_string(&path).expect("unit"), "new");
+        assert_eq!(
+            fs::metadata(&path).expect("unit").permissions().mode() & 0o777,
+            0o600
+        );
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).expect("chmod");
+        assert_eq!(
+            write_unit(&path, "newer", true, false).expect("force"),
+            "installed"
+        );
+        assert_eq!(fs::read_to_string(&path).expect("unit"), "newer");
+        assert_eq!(
+            fs::metadata(&path).expect("unit").permissions().mode() & 0o777,
+            0o600
+        );
+    }
+
+    #[test]
+    fn planned_unit_masks_environment_values() {
+        let unit = definition()
+            .with_masked_env()
+            .render(ServiceManager::Systemd);
+
+        assert!(unit.contains("Environment=\"RUST_LOG=[redacted]\"\n"));
+        assert!(!unit.contains("info&debug"));
+    }
+
+    #[test]
+    fn pid_is_parsed_from_service_manager_output() {
+        let launchctl = "gui/501/dev.seiro.mcp = {\n\tstate = running\n\tpid = 4242\n}";
+        assert_eq!(parse_pid(ServiceManager::Launchd, launchctl), Some(4242));
+        assert_eq!(parse_pid(ServiceManager::Launchd, "state = waiting"), None);
+        assert_eq!(parse_pid(ServiceManager::Systemd, "1234\n"), Some(1234));
+        assert_eq!(parse_pid(ServiceManager::Systemd, "0\n"), None);
+    }
+}
@@ -0,0 +1,177 @@
+//! Size measurement for built `.app` bundles.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::lib::errors::ArtifactError;
+
+/// Size of the `.app` bundle produced by a build.
+#[derive(Debug, Clone, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct AppSizeReport {
+    /// Bundle directory name, e.g. `VisionApp.app`.
+    pub app_bundle: String,
+    /// Total size of regular files inside the bundle.
+    pub app_bundle_bytes: u64,
+    /// Size of `<App>.app/<App>`; `None` when the executable is missing.
+    pub main_binary_bytes: Option<u64>,
+    /// Present when `visionos.size_budgets` has an entry for the scheme.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<SizeBudgetStatus>,
+}
+
+/// Limits applied to a build and whether any of them was exceeded.
+#[derive(Debug, Clone, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct SizeBudgetStatus {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_app_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_binary_bytes: Option<u64>,
+    pub exceeded: bool,
+}
+
+impl AppSizeReport {
+    /// Compare the report against the given limits and record the result.
+    pub fn apply_budget(&mut self, max_app_bytes: Option<u64>, max_binary_bytes: Option<u64>) {
+        let app_over = max_app_bytes.is_some_and(|max| self.app_bundle_bytes > max);
+        let binary_over = max_binary_bytes
+            .zip(self.main_binary_bytes)
+            .is_some_and(|(max, bytes)| bytes > max);
+        self.budget = Some(SizeBudgetStatus {
+            max_app_bytes,
+            max_binary_bytes,
+            exceeded: app_over || binary_over,
+        });
+    }
+
+    pub fn budget_exceeded(&self) -> bool {
+        self.budget.as_ref().is_some_and(|budget| budget.exceeded)
+    }
+}
+
+/// Measure the first `.app` bundle in a build staging directory.
+///
+/// Looks in the staging directory itself and in `*.xcarchive/Products/Applications`.
+pub fn measure_app_bundle(staging_dir: &Path) -> Result<Option<AppSizeReport>, ArtifactError> {
+    let Some(app) = find_app_bundle(staging_dir)? else {
+        return Ok(None);
+    };
+    let app_bundle_bytes = directory_size(&app)?;
+    let main_binary_bytes = app
+        .file_stem()
+        .map(|stem| app.join(stem))
+        .and_then(|binary| fs::metadata(binary).ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len());
+    Ok(Some(AppSizeReport {
+        app_bundle: app
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        app_bundle_bytes,
+        main_binary_bytes,
+        budget: None,
+    }))
+}
+
+/// First `.app` bundle in a staging directory or in one of its `.xcarchive`s.
+pub fn find_app_bundle(staging_dir: &Path) -> Result<Option<PathBuf>, ArtifactError> {
+    let mut archives = Vec::new();
+    for path in sorted_entries(staging_dir)? {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("app") if path.is_dir() => return Ok(Some(path)),
+            Some("xcarchive") if path.is_dir() => archives.push(path),
+            _ => {}
+        }
+    }
+    for archive in archives {
+        let applications = archive.join("Products/Applications");
+        if !applications.is_dir() {
+            continue;
+        }
+        if let Some(app) = sorted_entries(&applications)?
+            .into_iter()
+            .find(|path| path.is_dir() && path.extension().is_some_and(|ext| ext == "app"))
+        {
+            return Ok(Some(app));
+        }
+    }
+    Ok(None)
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>, ArtifactError> {
+    let entries = fs::read_dir(dir).map_err(|source| ArtifactError::ReadDir {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|source| ArtifactError::ReadDir {
+            path: dir.to_path_buf(),
+            source,
+        })?;
+        paths.push(entry.path());
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Sum of regular file sizes below `dir`; symlinks are not followed.
+pub fn directory_size(dir: &Path) -> Result<u64, ArtifactError> {
+    let mut total = 0;
+    for path in sorted_entries(dir)? {
+        let metadata = fs::symlink_metadata(&path).map_err(|source| ArtifactError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        if metadata.is_dir() {
+            total += directory_size(&path)?;
+        } else if metadata.is_file() {
+            total += metadata.len();
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn measures_archived_app_and_applies_budget() {
+        let temp = tempdir().expect("can create temp directory");
+        let app = temp
+            .path()
+            .join("VisionApp.xcarchive/Products/Applications/VisionApp.app");
+        fs::create_dir_all(app.join("Assets")).expect("can create bundle");
+        fs::write(app.join("VisionApp"), vec![0u8; 300]).expect("can write binary");
+        fs::write(app.join("Assets/Assets.car"), vec![0u8; 200]).expect("can write assets");
+
+        let mut report = measure_app_bundle(temp.path())
+            .expect("can measure")
+            .expect("bundle is found");
+        assert_eq!(report.app_bundle, "VisionApp.app");
+        assert_eq!(report.app_bundle_bytes, 500);
+        assert_eq!(report.main_binary_bytes, Some(300));
+        assert!(!report.budget_exceeded());
+
+        report.apply_budget(Some(1_000), Some(400));
+        assert!(!report.budget_exceeded());
+        report.apply_budget(None, Some(250));
+        assert!(report.budget_exceeded());
+    }
+
+    #[test]
+    fn missing_bundle_yields_no_report() {
+        let temp = tempdir().expect("can create temp directory");
+        fs::write(temp.path().join("log.txt"), "no app").expect("can write file");
+
+        assert_eq!(measure_app_bundle(temp.path()).expect("can measure"), None);
+    }
+}
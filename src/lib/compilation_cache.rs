@@ -0,0 +1,149 @@
+//! Compiler caching for `xcodebuild` runs: Xcode's built-in compilation cache or an sccache
+//! compiler wrapper.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::process::Command;
+
+/// Which compiler cache `xcodebuild` uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompilationCacheMode {
+    /// Xcode's compilation cache (`COMPILATION_CACHE_ENABLE_CACHING=YES`).
+    Xcode,
+    /// sccache behind a wrapper script passed as the C and C++ compiler.
+    Sccache {
+        /// `sccache` binary, queried for hit statistics around each build.
+        sccache_path: PathBuf,
+        /// Script forwarding to the compiler through sccache, e.g. `exec sccache clang "$@"`.
+        compiler_wrapper: PathBuf,
+    },
+}
+
+/// Compiler cache applied to every `xcodebuild` run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompilationCache {
+    pub mode: CompilationCacheMode,
+    /// Extra environment for `xcodebuild` and the cache, e.g. `SCCACHE_DIR`.
+    pub env: BTreeMap<String, String>,
+}
+
+impl CompilationCache {
+    /// Build settings appended to the `xcodebuild` invocation.
+    pub fn build_settings(&self) -> Vec<String> {
+        match &self.mode {
+            CompilationCacheMode::Xcode => vec!["COMPILATION_CACHE_ENABLE_CACHING=YES".into()],
+            CompilationCacheMode::Sccache {
+                compiler_wrapper, ..
+            } => vec![
+                format!("CC={}", compiler_wrapper.display()),
+                format!("CXX={}", compiler_wrapper.display()),
+            ],
+        }
+    }
+
+    /// Current sccache counters; `None` for the Xcode cache or when sccache cannot be queried.
+    pub async fn snapshot(&self) -> Option<CompilationCacheStats> {
+        let CompilationCacheMode::Sccache { sccache_path, .. } = &self.mode else {
+            return None;
+        };
+        sccache_stats(sccache_path, &self.env).await
+    }
+}
+
+/// Compiler cache hits and misses.
+#[derive(Debug, Clone, Copy, Default, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct CompilationCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CompilationCacheStats {
+    /// Counts added since `before` was taken.
+    pub fn since(self, before: Self) -> Self {
+        Self {
+            hits: self.hits.saturating_sub(before.hits),
+            misses: self.misses.saturating_sub(before.misses),
+        }
+    }
+}
+
+/// Run `sccache --show-stats --stats-format=json` and total its per-language counts.
+async fn sccache_stats(
+    sccache_path: &Path,
+    env: &BTreeMap<String, String>,
+) -> Option<CompilationCacheStats> {
+    let output = Command::new(sccache_path)
+        .kill_on_drop(true)
+        .env_clear()
+        .envs(env)
+        .args(["--show-stats", "--stats-format=json"])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_sccache_stats(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Totals of `stats.cache_hits.counts` and `stats.cache_misses.counts` in sccache's JSON output.
+pub fn parse_sccache_stats(json: &str) -> Option<CompilationCacheStats> {
+    let value: Value = serde_json::from_str(json).ok()?;
+    let stats = value.get("stats")?;
+    let total = |key: &str| -> Option<u64> {
+        let counts = stats.get(key)?.get("counts")?.as_object()?;
+        Some(counts.values().filter_map(Value::as_u64).sum())
+    };
+    Some(CompilationCacheStats {
+        hits: total("cache_hits")?,
+        misses: total("cache_misses")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sccache_json_stats() {
+        let json = r#"{
+            "stats": {
+                "compile_requests": 12,
+                "cache_hits": {"counts": {"C/C++": 7, "Swift": 1}, "adv_counts": {}},
+                "cache_misses": {"counts": {"C/C++": 3}, "adv_counts": {}}
+            }
+        }"#;
+        let stats = parse_sccache_stats(json).expect("stats should parse");
+        assert_eq!(stats, CompilationCacheStats { hits: 8, misses: 3 });
+
+        let before = CompilationCacheStats { hits: 5, misses: 1 };
+        assert_eq!(
+            stats.since(before),
+            CompilationCacheStats { hits: 3, misses: 2 }
+        );
+        assert!(parse_sccache_stats("not json").is_none());
+    }
+
+    #[test]
+    fn sccache_wrapper_replaces_the_compilers() {
+        let cache = CompilationCache {
+            mode: CompilationCacheMode::Sccache {
+                sccache_path: PathBuf::from("/opt/homebrew/bin/sccache"),
+                compiler_wrapper: PathBuf::from("/opt/ci/sccache-clang"),
+            },
+            env: BTreeMap::new(),
+        };
+        assert_eq!(
+            cache.build_settings(),
+            vec!["CC=/opt/ci/sccache-clang", "CXX=/opt/ci/sccache-clang"]
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Physical device destinations and the UDID allowlist shared by the config and build requests.
+
+/// What an `xcodebuild -destination` value points at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DestinationTarget {
+    /// A simulator, or macOS itself.
+    Local,
+    /// `generic/platform=...`: builds for devices without installing on one.
+    GenericDevice,
+    /// A connected device, with the `id` given in the destination.
+    Device {
+        platform: String,
+        id: Option<String>,
+    },
+}
+
+/// Classify `destination` by its `platform` and `id` keys.
+pub fn destination_target(destination: &str) -> DestinationTarget {
+    let destination = destination.trim();
+    if destination.starts_with("generic/") {
+        return DestinationTarget::GenericDevice;
+    }
+    let mut platform = None;
+    let mut id = None;
+    for pair in destination.split(',') {
+        let Some((key, value)) = pair.split_once(['=', ':']) else {
+            continue;
+        };
+        match key.trim() {
+            "platform" => platform = Some(value.trim().to_string()),
+            "id" => id = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    match platform {
+        Some(platform) if !platform.ends_with("Simulator") && platform != "macOS" => {
+            DestinationTarget::Device { platform, id }
+        }
+        _ => DestinationTarget::Local,
+    }
+}
+
+/// Hardware UDIDs (`00008112-001A2C3E0123401E`) and CoreDevice identifiers (UUIDs) are hex
+/// digits and dashes.
+pub fn is_device_udid(value: &str) -> bool {
+    (20..=40).contains(&value.len())
+        && value.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+        && value.chars().any(|c| c.is_ascii_hexdigit())
+}
+
+/// True when `udid` is in `allowed`, ignoring case.
+pub fn is_allowed_device(udid: &str, allowed: &[String]) -> bool {
+    allowed
+        .iter()
+        .any(|entry| entry.eq_ignore_ascii_case(udid.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn destinations_are_classified_by_platform_and_id() {
+        assert_eq!(
+            destination_target("platform=visionOS Simulator,name=Apple Vision Pro"),
+            DestinationTarget::Local
+        );
+        assert_eq!(
+            destination_target("generic/platform=visionOS"),
+            DestinationTarget::GenericDevice
+        );
+        assert_eq!(
+            destination_target("platform=visionOS,id=00008112-001A2C3E0123401E"),
+            DestinationTarget::Device {
+                platform: "visionOS".into(),
+                id: Some("00008112-001A2C3E0123401E".into()),
+            }
+        );
+        assert_eq!(
+            destination_target("platform=visionOS,name=Studio Vision Pro"),
+            DestinationTarget::Device {
+                platform: "visionOS".into(),
+                id: None,
+            }
+        );
+        assert!(is_device_udid("00008112-001A2C3E0123401E"));
+        assert!(!is_device_udid("Studio Vision Pro"));
+        assert!(is_allowed_device(
+            "00008112-001a2c3e0123401e",
+            &["00008112-001A2C3E0123401E".into()]
+        ));
+    }
+}
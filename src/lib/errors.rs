@@ -2,11 +2,17 @@ use std::{io, path::PathBuf};
 
 use config::ConfigError as ConfigLoaderError;
 use rmcp::model::ErrorData;
+use schemars::JsonSchema;
 use serde::Serialize;
 use serde_json::{Map, Number, Value};
 use thiserror::Error;
 use zip::result::ZipError;
 
+use crate::lib::{
+    app_size::AppSizeReport, telemetry::redact_json, xcodebuild::BuildLogDiagnostic,
+    xcresult::XcresultDiagnostic,
+};
+
 /// Errors that can occur while loading or validating configuration files.
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -59,17 +65,32 @@ pub enum VisionOsBuildError {
     CommandFailed {
         exit_code: Option<i32>,
         message: String,
+        /// Issues read from the job's `.xcresult` bundle, when one was produced.
+        diagnostics: Vec<XcresultDiagnostic>,
+        /// First errors parsed from the `xcodebuild` output.
+        log_errors: Vec<BuildLogDiagnostic>,
     },
     #[error("visionOS build timed out after {duration_secs} seconds")]
     Timeout { duration_secs: u64 },
     #[error("Build was blocked by sandbox policy: {reason}")]
     SandboxViolated { reason: String },
+    #[error(
+        "Insufficient free space to start the build (available={available_bytes} bytes, required={required_bytes} bytes)"
+    )]
+    DiskInsufficient {
+        available_bytes: u64,
+        required_bytes: u64,
+    },
     #[error("Failed to process artifacts: {message}")]
     ArtifactFailure { message: String },
+    #[error("App size exceeds the configured budget ({} bytes)", report.app_bundle_bytes)]
+    SizeBudgetExceeded { report: AppSizeReport },
+    #[error("Post-build processing failed: {0}")]
+    PostProcessFailed(#[from] PostProcessError),
 }
 
 /// Failure reasons for sandbox policy validation.
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum SandboxPolicyError {
     #[error("Path is not allowed: {path}")]
     PathNotAllowed { path: PathBuf },
@@ -81,8 +102,25 @@ pub enum SandboxPolicyError {
     LicenseNotAccepted,
     #[error("DevToolsSecurity is disabled")]
     DevToolsSecurityDisabled,
+    #[error(
+        "xcode-select points at `{selected}`, not the configured developer directory `{expected}`"
+    )]
+    DeveloperDirMismatch {
+        selected: PathBuf,
+        expected: PathBuf,
+    },
+    #[error("Xcode Command Line Tools are not installed")]
+    CommandLineToolsMissing,
+    #[error("No available simulator runtime matches `{requested}`")]
+    RuntimeMissing { requested: String },
+    #[error("Package hosts unreachable: {hosts} ({proxy})")]
+    PackageHostsUnreachable { hosts: String, proxy: String },
     #[error("Insufficient free space for visionOS build (available={available_bytes} bytes)")]
     DiskInsufficient { available_bytes: u64 },
+    #[error("Code signing identity `{identity}` was not found in the keychain")]
+    SigningIdentityMissing { identity: String },
+    #[error("remediate was requested but visionos.allow_remediation is false")]
+    RemediationNotAllowed,
     #[error("Internal sandbox policy error: {message}")]
     Internal { message: String },
 }
@@ -122,6 +160,46 @@ pub enum ArtifactError {
     },
     #[error("Artifact source {path} is not a directory")]
     InvalidSource { path: PathBuf },
+    #[error("Symlink {path} points back into one of its parent directories")]
+    SymlinkLoop { path: PathBuf },
+    #[error("Object storage command failed for {uri}: {message}")]
+    ObjectStorage { uri: String, message: String },
+}
+
+/// Errors occurring while reading an `.xcresult` bundle.
+#[derive(Debug, Error)]
+pub enum XcresultError {
+    #[error("Failed to launch xcresulttool: {0}")]
+    Spawn(#[source] io::Error),
+    #[error("xcresulttool exited abnormally (exit={exit_code:?}): {stderr}")]
+    CommandFailed {
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    #[error("xcresulttool returned invalid JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+}
+
+/// Errors occurring while stripping or thinning built binaries.
+#[derive(Debug, Error)]
+pub enum PostProcessError {
+    #[error("Failed to launch {tool}: {source}")]
+    Spawn {
+        tool: &'static str,
+        #[source]
+        source: io::Error,
+    },
+    #[error("{tool} exited abnormally on {path} (exit={exit_code:?}): {stderr}")]
+    CommandFailed {
+        tool: &'static str,
+        path: PathBuf,
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    #[error("{path} contains none of the requested architectures (available: {available})")]
+    NoMatchingArchitecture { path: PathBuf, available: String },
+    #[error(transparent)]
+    Artifact(#[from] ArtifactError),
 }
 
 impl From<ArtifactError> for VisionOsBuildError {
@@ -160,7 +238,7 @@ impl ToolErrorDescriptor {
 }
 
 /// Sandbox state representation.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SandboxState {
     NotApplicable,
@@ -253,11 +331,16 @@ impl<'a> ToolErrorDescriptorBuilder<'a> {
             "sandbox_state".into(),
             Value::String(sandbox_state.as_str().into()),
         );
+        // Mask the caller-supplied fields as one map, so sensitive keys are caught at the top
+        // level as well as inside values.
+        let mut supplied = self.extra_fields;
         if let Some(details) = self.details {
-            data.insert("details".into(), details);
+            supplied.insert("details".into(), details);
         }
-        for (key, value) in self.extra_fields {
-            data.insert(key, value);
+        let mut supplied = Value::Object(supplied);
+        redact_json(&mut supplied);
+        if let Value::Object(supplied) = supplied {
+            data.extend(supplied);
         }
 
         Ok(ErrorData::invalid_params(
@@ -285,6 +368,51 @@ pub const MCP_CLIENT_REQUIRED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::
     "Launch through an MCP client such as `npx @modelcontextprotocol/inspector target/release/seiro-mcp`.",
 );
 
+/// Standard error when jobs were still running after the shutdown grace period.
+pub const SHUTDOWN_GRACE_EXPIRED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "SHUTDOWN_GRACE_EXPIRED",
+    "visionOS jobs were still running when the shutdown grace period ended",
+    "Raise server.shutdown_grace_secs or cancel long builds before stopping the server.",
+);
+
+/// A descriptor with the `retryable` and `sandbox_state` its call sites report, as listed by
+/// `list_error_codes`. Each module that declares descriptors lists them in a
+/// `pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry]`, registered in `MODULE_CATALOGS` of
+/// `server/runtime/error_catalog.rs`.
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorCatalogEntry {
+    pub descriptor: &'static ToolErrorDescriptor,
+    pub retryable: bool,
+    pub sandbox_state: SandboxState,
+}
+
+impl ErrorCatalogEntry {
+    pub const fn new(
+        descriptor: &'static ToolErrorDescriptor,
+        retryable: bool,
+        sandbox_state: SandboxState,
+    ) -> Self {
+        Self {
+            descriptor,
+            retryable,
+            sandbox_state,
+        }
+    }
+}
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(
+        &MCP_CLIENT_REQUIRED_ERROR,
+        true,
+        SandboxState::NotApplicable,
+    ),
+    ErrorCatalogEntry::new(
+        &SHUTDOWN_GRACE_EXPIRED_ERROR,
+        true,
+        SandboxState::NotApplicable,
+    ),
+];
+
 #[cfg(test)]
 mod tests {
     use rmcp::model::ErrorData;
@@ -327,6 +455,37 @@ mod tests {
         assert_eq!(data.get("job_id"), Some(&json!("1234")));
     }
 
+    #[test]
+    fn builder_masks_sensitive_details() {
+        let error = ToolErrorDescriptorBuilder::new(&BASE_DESCRIPTOR)
+            .retryable(false)
+            .sandbox_state(SandboxState::NoViolation)
+            .details(json!({ "env_overrides": { "API_TOKEN": "abc" }, "scheme": "VisionApp" }))
+            .build()
+            .expect("builder must succeed");
+
+        let data = extract_data(&error);
+        assert_eq!(
+            data.get("details"),
+            Some(&json!({ "env_overrides": { "API_TOKEN": "[redacted]" }, "scheme": "VisionApp" }))
+        );
+    }
+
+    #[test]
+    fn builder_masks_sensitive_context_field_keys() {
+        let error = ToolErrorDescriptorBuilder::new(&BASE_DESCRIPTOR)
+            .retryable(false)
+            .sandbox_state(SandboxState::NoViolation)
+            .with_context_field("token", json!("abc"))
+            .with_context_field("job_id", json!("1234"))
+            .build()
+            .expect("builder must succeed");
+
+        let data = extract_data(&error);
+        assert_eq!(data.get("token"), Some(&json!("[redacted]")));
+        assert_eq!(data.get("job_id"), Some(&json!("1234")));
+    }
+
     #[test]
     fn builder_fails_when_sandbox_state_missing() {
         let result = ToolErrorDescriptorBuilder::new(&BASE_DESCRIPTOR)
@@ -3,24 +3,159 @@
 use std::{
     env,
     fs::{self, File},
-    io::{self, Read, Write},
-    path::{Path, PathBuf},
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
 };
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use glob::{MatchOptions, Pattern, PatternError};
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 use zip::{write::FileOptions, CompressionMethod, ZipWriter};
 
 use crate::lib::errors::ArtifactError;
 
-/// Unix permission bits applied to generated ZIP entries.
+/// Unix permission bits for ZIP entries on platforms that do not report file modes.
 const ZIP_DIR_PERMISSIONS: u32 = 0o755;
+/// Unix permission bits applied to symlink entries (the file type bits are added by `zip`).
+const ZIP_SYMLINK_PERMISSIONS: u32 = 0o777;
+/// Read buffer used when streaming a file into a ZIP entry.
+const ZIP_COPY_BUFFER_BYTES: usize = 64 * 1024;
+/// Lockfile naming the server process that owns an artifact root.
+pub const ROOT_LOCK_FILE: &str = ".seiro-mcp.lock";
+/// Directory under an artifact root holding `<pid>/` roots of servers that found it locked.
+pub const INSTANCES_DIR: &str = "instances";
+/// Per-scheme build durations under an artifact root, kept across TTL cleanups.
+pub const DURATION_HISTORY_FILE: &str = "durations.json";
 /// Environment variable name for Codex home override.
 const CODEX_HOME_ENV: &str = "CODEX_HOME";
 /// Environment variable name for user home directory.
 const HOME_ENV: &str = "HOME";
 
+/// ZIP entry compression used for artifacts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ZipCompressionMethod {
+    #[default]
+    Deflate,
+    /// Store entries uncompressed; fastest for already-compressed assets.
+    Store,
+    /// Zstandard: close to deflate's size at a fraction of the time. Needs an unzip tool with
+    /// zstd support (method 93), such as `7z` or recent `bsdtar`.
+    Zstd,
+}
+
+impl ZipCompressionMethod {
+    /// Level used when the config names a method without a level.
+    pub const fn default_level(self) -> u8 {
+        match self {
+            Self::Deflate => ZipCompression::DEFAULT_LEVEL,
+            Self::Store => 0,
+            Self::Zstd => 3,
+        }
+    }
+
+    /// Accepted levels; `None` for `Store`, which takes none.
+    pub const fn level_range(self) -> Option<(u8, u8)> {
+        match self {
+            Self::Deflate => Some((0, 9)),
+            Self::Store => None,
+            Self::Zstd => Some((1, 22)),
+        }
+    }
+}
+
+/// Method and level applied by [`zip_directory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZipCompression {
+    pub method: ZipCompressionMethod,
+    /// Deflate level (0-9) or zstd level (1-22); ignored for `Store`.
+    pub level: u8,
+}
+
+impl ZipCompression {
+    pub const DEFAULT_LEVEL: u8 = 6;
+}
+
+impl Default for ZipCompression {
+    fn default() -> Self {
+        Self {
+            method: ZipCompressionMethod::Deflate,
+            level: Self::DEFAULT_LEVEL,
+        }
+    }
+}
+
+/// How [`zip_directory`] stores symbolic links.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Store links as symlink entries, which `.framework` bundles rely on
+    /// (`Versions/Current -> A`). Links that point outside the zipped directory are stored as
+    /// the files they point to instead.
+    #[default]
+    Preserve,
+    /// Store every link as the file or directory it points to.
+    Dereference,
+}
+
+/// Glob filters selecting the entries [`zip_directory`] packs.
+///
+/// A pattern without `/` matches an entry's name at any depth (`*.app`); one with `/` matches
+/// the entry's path relative to the zipped directory (`Debug-xros/*.app`). A matching directory
+/// brings in, or leaves out, everything below it. Exclusions win over inclusions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ZipFilter {
+    /// Entries to pack; everything when empty.
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl ZipFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, PatternError> {
+        let parse = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| Pattern::new(pattern))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(Self {
+            include: parse(include)?,
+            exclude: parse(exclude)?,
+        })
+    }
+
+    fn includes(&self, relative: &str) -> bool {
+        self.include.is_empty() || self.include.iter().any(|p| pattern_matches(p, relative))
+    }
+
+    fn excludes(&self, relative: &str) -> bool {
+        self.exclude.iter().any(|p| pattern_matches(p, relative))
+    }
+}
+
+fn pattern_matches(pattern: &Pattern, relative: &str) -> bool {
+    const OPTIONS: MatchOptions = MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    if pattern.as_str().contains('/') {
+        return pattern.matches_with(relative, OPTIONS);
+    }
+    let name = relative.rsplit('/').next().unwrap_or(relative);
+    pattern.matches_with(name, OPTIONS)
+}
+
+/// Settings for [`zip_directory`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ZipOptions {
+    pub compression: ZipCompression,
+    pub symlinks: SymlinkPolicy,
+    /// Zero every timestamp and reduce permissions to `0o755` / `0o644`, so identical trees
+    /// always produce byte-identical zips.
+    pub deterministic: bool,
+    pub filter: ZipFilter,
+}
+
 /// Skill file payload to install into Codex skill directory.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BundledSkillFile<'a> {
@@ -218,7 +353,8 @@ pub fn ensure_job_dir(base_dir: &Path, job_id: &Uuid) -> Result<PathBuf, Artifac
     Ok(job_dir)
 }
 
-/// Delete artifacts whose TTL has expired and return the removed paths.
+/// Delete artifacts whose TTL has expired and return the removed paths. The root lockfile, the
+/// duration history, and the `instances/` directory are left alone.
 pub fn cleanup_expired_entries(
     root: &Path,
     ttl: Duration,
@@ -237,6 +373,12 @@ pub fn cleanup_expired_entries(
             path: root.to_path_buf(),
             source,
         })?;
+        if [ROOT_LOCK_FILE, DURATION_HISTORY_FILE, INSTANCES_DIR]
+            .iter()
+            .any(|name| entry.file_name() == *name)
+        {
+            continue;
+        }
         let path = entry.path();
         let metadata = entry.metadata().map_err(|source| ArtifactError::Io {
             path: path.clone(),
@@ -286,8 +428,71 @@ pub fn compute_sha256(path: &Path) -> Result<String, ArtifactError> {
     Ok(format!("{:x}", hasher.finalize()))
 }
 
-/// Zip a directory tree, preserving empty directories as entries.
-pub fn zip_directory(source: &Path, destination: &Path) -> Result<(), ArtifactError> {
+/// Recursively copy a directory tree, keeping file permissions and modification times; symlinks
+/// are skipped.
+pub fn copy_directory(source: &Path, destination: &Path) -> Result<(), ArtifactError> {
+    if !source.is_dir() {
+        return Err(ArtifactError::InvalidSource {
+            path: source.to_path_buf(),
+        });
+    }
+    fs::create_dir_all(destination).map_err(|source_err| ArtifactError::CreateDir {
+        path: destination.to_path_buf(),
+        source: source_err,
+    })?;
+
+    let entries = fs::read_dir(source).map_err(|source_err| ArtifactError::ReadDir {
+        path: source.to_path_buf(),
+        source: source_err,
+    })?;
+    for entry in entries {
+        let entry = entry.map_err(|source_err| ArtifactError::ReadDir {
+            path: source.to_path_buf(),
+            source: source_err,
+        })?;
+        let file_type = entry.file_type().map_err(|source_err| ArtifactError::Io {
+            path: entry.path(),
+            source: source_err,
+        })?;
+        let target = destination.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_directory(&entry.path(), &target)?;
+        } else if file_type.is_file() {
+            fs::copy(entry.path(), &target).map_err(|source_err| ArtifactError::Io {
+                path: target.clone(),
+                source: source_err,
+            })?;
+            copy_modified_time(&entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Give `target` the modification time of `source`; `fs::copy` only carries permissions over.
+fn copy_modified_time(source: &Path, target: &Path) -> Result<(), ArtifactError> {
+    let modified = fs::metadata(source)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|source_err| ArtifactError::Io {
+            path: source.to_path_buf(),
+            source: source_err,
+        })?;
+    File::options()
+        .write(true)
+        .open(target)
+        .and_then(|file| file.set_modified(modified))
+        .map_err(|source_err| ArtifactError::Io {
+            path: target.to_path_buf(),
+            source: source_err,
+        })
+}
+
+/// Zip a directory tree, preserving empty directories as entries. With include patterns in
+/// `options.filter`, only matching entries are packed.
+pub fn zip_directory(
+    source: &Path,
+    destination: &Path,
+    options: ZipOptions,
+) -> Result<(), ArtifactError> {
     if !source.is_dir() {
         return Err(ArtifactError::InvalidSource {
             path: source.to_path_buf(),
@@ -299,11 +504,28 @@ pub fn zip_directory(source: &Path, destination: &Path) -> Result<(), ArtifactEr
         source: source_err,
     })?;
     let mut zip = ZipWriter::new(file);
-    let options = FileOptions::default()
-        .compression_method(CompressionMethod::Deflated)
-        .unix_permissions(ZIP_DIR_PERMISSIONS);
+    let compression = options.compression;
+    let file_options = match compression.method {
+        ZipCompressionMethod::Deflate => FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .compression_level(Some(i32::from(compression.level))),
+        ZipCompressionMethod::Store => {
+            FileOptions::default().compression_method(CompressionMethod::Stored)
+        }
+        ZipCompressionMethod::Zstd => FileOptions::default()
+            .compression_method(CompressionMethod::Zstd)
+            .compression_level(Some(i32::from(compression.level))),
+    }
+    .unix_permissions(ZIP_DIR_PERMISSIONS);
 
-    add_directory_to_zip(source, source, &mut zip, options)?;
+    let mut walker = ZipWalker {
+        base: source,
+        zip: &mut zip,
+        file_options,
+        options,
+        ancestors: Vec::new(),
+    };
+    walker.add_directory(source, false)?;
 
     zip.finish().map_err(|source| ArtifactError::Zip {
         path: destination.to_path_buf(),
@@ -312,81 +534,200 @@ pub fn zip_directory(source: &Path, destination: &Path) -> Result<(), ArtifactEr
     Ok(())
 }
 
-/// Walk and append directory entries into a ZIP archive.
-fn add_directory_to_zip(
-    base: &Path,
-    current: &Path,
-    zip: &mut ZipWriter<File>,
+/// `options` with the permission bits and modification time of the file or directory behind
+/// `metadata`, so extracted executables stay runnable and timestamps match the build output.
+/// `deterministic` keeps only the executable bit and uses the earliest ZIP timestamp instead.
+fn entry_options(
     options: FileOptions,
-) -> Result<(), ArtifactError> {
-    let entries = fs::read_dir(current).map_err(|source| ArtifactError::ReadDir {
-        path: current.to_path_buf(),
-        source,
-    })?;
+    metadata: &fs::Metadata,
+    deterministic: bool,
+) -> FileOptions {
+    #[cfg(unix)]
+    let options = {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = metadata.permissions().mode() & 0o7777;
+        let mode = if !deterministic {
+            mode
+        } else if metadata.is_dir() || mode & 0o111 != 0 {
+            0o755
+        } else {
+            0o644
+        };
+        options.unix_permissions(mode)
+    };
+    if deterministic {
+        return options.last_modified_time(zip::DateTime::default());
+    }
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| zip_date_time(DateTime::<Utc>::from(modified)));
+    match modified {
+        Some(modified) => options.last_modified_time(modified),
+        None => options,
+    }
+}
 
-    let mut is_empty = true;
-    for entry in entries {
-        let entry = entry.map_err(|source| ArtifactError::ReadDir {
+/// ZIP timestamps have two-second resolution and cover 1980-2107; others keep the default.
+fn zip_date_time(time: DateTime<Utc>) -> Option<zip::DateTime> {
+    zip::DateTime::from_date_and_time(
+        u16::try_from(time.year()).ok()?,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    )
+    .ok()
+}
+
+/// Walks a directory tree and appends its entries to a ZIP archive.
+struct ZipWalker<'a> {
+    base: &'a Path,
+    zip: &'a mut ZipWriter<File>,
+    file_options: FileOptions,
+    options: ZipOptions,
+    /// Canonical paths of the directories being walked, to stop dereferenced symlink loops.
+    ancestors: Vec<PathBuf>,
+}
+
+impl ZipWalker<'_> {
+    /// Add the entries below `current`; `included` when `current` matched an include pattern.
+    fn add_directory(&mut self, current: &Path, included: bool) -> Result<(), ArtifactError> {
+        let canonical = fs::canonicalize(current).map_err(|source| ArtifactError::Io {
             path: current.to_path_buf(),
             source,
         })?;
-        let path = entry.path();
-        let relative = path
-            .strip_prefix(base)
-            .unwrap_or(&path)
-            .to_string_lossy()
-            .replace('\\', "/");
+        if self.ancestors.contains(&canonical) {
+            return Err(ArtifactError::SymlinkLoop {
+                path: current.to_path_buf(),
+            });
+        }
+        self.ancestors.push(canonical);
 
-        if path.is_dir() {
-            is_empty = false;
-            let dir_name = format!("{relative}/");
-            zip.add_directory(dir_name, options)
-                .map_err(|source| ArtifactError::Zip {
-                    path: path.clone(),
-                    source,
-                })?;
-            add_directory_to_zip(base, &path, zip, options)?;
-        } else {
-            is_empty = false;
-            zip.start_file(relative, options)
-                .map_err(|source| ArtifactError::Zip {
-                    path: path.clone(),
-                    source,
-                })?;
-            let mut file = File::open(&path).map_err(|source| ArtifactError::Io {
-                path: path.clone(),
+        let mut entries = fs::read_dir(current)
+            .map_err(|source| ArtifactError::ReadDir {
+                path: current.to_path_buf(),
                 source,
-            })?;
-            let mut buffer = Vec::new();
-            file.read_to_end(&mut buffer)
-                .map_err(|source| ArtifactError::Io {
-                    path: path.clone(),
-                    source,
-                })?;
-            zip.write_all(&buffer).map_err(|source| ArtifactError::Io {
-                path: path.clone(),
+            })?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|source| ArtifactError::ReadDir {
+                path: current.to_path_buf(),
                 source,
             })?;
+        entries.sort();
+
+        for path in entries {
+            self.add_entry(&path, included)?;
         }
+
+        self.ancestors.pop();
+        Ok(())
     }
 
-    if is_empty {
-        let relative = current
-            .strip_prefix(base)
-            .unwrap_or(current)
-            .to_string_lossy()
-            .replace('\\', "/");
-        if !relative.is_empty() {
-            let dir_name = format!("{relative}/");
-            zip.add_directory(dir_name, options)
+    fn add_entry(&mut self, path: &Path, included: bool) -> Result<(), ArtifactError> {
+        let io_error = |source| ArtifactError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let relative = self.relative(path);
+        if self.options.filter.excludes(&relative) {
+            return Ok(());
+        }
+        let included = included || self.options.filter.includes(&relative);
+        let mut metadata = fs::symlink_metadata(path).map_err(io_error)?;
+        if !included && !metadata.is_dir() {
+            return Ok(());
+        }
+        if metadata.file_type().is_symlink() {
+            let target = fs::read_link(path).map_err(io_error)?;
+            if self.options.symlinks == SymlinkPolicy::Preserve
+                && self.link_stays_inside(path, &target)
+            {
+                let mut options = self.file_options.unix_permissions(ZIP_SYMLINK_PERMISSIONS);
+                if self.options.deterministic {
+                    options = options.last_modified_time(zip::DateTime::default());
+                }
+                return self
+                    .zip
+                    .add_symlink(
+                        self.relative(path),
+                        target.to_string_lossy().replace('\\', "/"),
+                        options,
+                    )
+                    .map_err(|source| ArtifactError::Zip {
+                        path: path.to_path_buf(),
+                        source,
+                    });
+            }
+            metadata = fs::metadata(path).map_err(io_error)?;
+        }
+
+        let options = entry_options(self.file_options, &metadata, self.options.deterministic);
+        if metadata.is_dir() {
+            // Directories outside the include patterns are only searched for matches.
+            if included {
+                let dir_name = format!("{}/", self.relative(path));
+                self.zip
+                    .add_directory(dir_name, options)
+                    .map_err(|source| ArtifactError::Zip {
+                        path: path.to_path_buf(),
+                        source,
+                    })?;
+            }
+            self.add_directory(path, included)
+        } else if metadata.is_file() {
+            self.zip
+                .start_file(self.relative(path), options)
                 .map_err(|source| ArtifactError::Zip {
-                    path: current.to_path_buf(),
+                    path: path.to_path_buf(),
                     source,
                 })?;
+            // Stream in chunks so multi-gigabyte binaries never sit in memory whole.
+            let file = File::open(path).map_err(io_error)?;
+            io::copy(
+                &mut io::BufReader::with_capacity(ZIP_COPY_BUFFER_BYTES, file),
+                self.zip,
+            )
+            .map(|_| ())
+            .map_err(io_error)
+        } else {
+            // Sockets, FIFOs, and device nodes have no place in a build artifact.
+            Ok(())
         }
     }
 
-    Ok(())
+    /// Whether a relative link target resolves inside the zipped directory.
+    fn link_stays_inside(&self, link: &Path, target: &Path) -> bool {
+        if target.is_absolute() {
+            return false;
+        }
+        let mut resolved: Vec<Component> = link
+            .parent()
+            .and_then(|parent| parent.strip_prefix(self.base).ok())
+            .map(|parent| parent.components().collect())
+            .unwrap_or_default();
+        for component in target.components() {
+            match component {
+                Component::ParentDir => {
+                    if resolved.pop().is_none() {
+                        return false;
+                    }
+                }
+                Component::CurDir => {}
+                other => resolved.push(other),
+            }
+        }
+        true
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(self.base)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
 }
 
 #[cfg(test)]
@@ -419,6 +760,8 @@ mod tests {
         let temp = tempdir().expect("can create temp directory");
         let old_job = temp.path().join("old-job");
         fs::create_dir_all(&old_job).expect("can create old job");
+        fs::write(temp.path().join(ROOT_LOCK_FILE), b"42\n").expect("can write lockfile");
+        fs::create_dir_all(temp.path().join(INSTANCES_DIR)).expect("can create instances dir");
 
         let ttl = Duration::minutes(5);
         let now = Utc::now() + Duration::minutes(10);
@@ -446,6 +789,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn copy_directory_copies_nested_files() {
+        let temp = tempdir().expect("can create temp directory");
+        let source = temp.path().join("App.dSYM");
+        let nested = source.join("Contents").join("Resources");
+        fs::create_dir_all(&nested).expect("can create source directory");
+        fs::write(nested.join("DWARF"), b"dwarf").expect("can write nested file");
+
+        let destination = temp.path().join("export").join("App.dSYM");
+        copy_directory(&source, &destination).expect("copy succeeds");
+
+        let copied = fs::read(destination.join("Contents/Resources/DWARF")).expect("copied file");
+        assert_eq!(copied, b"dwarf");
+        let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        assert_eq!(
+            modified(&destination.join("Contents/Resources/DWARF")),
+            modified(&nested.join("DWARF"))
+        );
+    }
+
     #[test]
     fn zip_directory_packs_all_files() {
         let temp = tempdir().expect("can create temp directory");
@@ -456,7 +819,8 @@ mod tests {
         fs::write(nested.join("child.txt"), b"child").expect("can write file to subdirectory");
 
         let destination = temp.path().join("artifacts.zip");
-        zip_directory(&source, &destination).expect("should successfully create zip");
+        zip_directory(&source, &destination, ZipOptions::default())
+            .expect("should successfully create zip");
 
         let archive_file = fs::File::open(&destination).expect("can open zip");
         let mut archive = ZipArchive::new(archive_file).expect("can extract zip");
@@ -482,6 +846,366 @@ mod tests {
         }
     }
 
+    #[test]
+    fn zip_directory_can_store_entries_uncompressed() {
+        let temp = tempdir().expect("can create temp directory");
+        let source = temp.path().join("source");
+        fs::create_dir_all(&source).expect("can create source directory");
+        fs::write(source.join("Assets.car"), vec![b'a'; 4_096]).expect("can write asset");
+
+        let destination = temp.path().join("artifacts.zip");
+        let compression = ZipCompression {
+            method: ZipCompressionMethod::Store,
+            level: ZipCompression::DEFAULT_LEVEL,
+        };
+        let options = ZipOptions {
+            compression,
+            ..ZipOptions::default()
+        };
+        zip_directory(&source, &destination, options).expect("should create zip");
+
+        let archive_file = fs::File::open(&destination).expect("can open zip");
+        let mut archive = ZipArchive::new(archive_file).expect("can extract zip");
+        let entry = archive.by_name("Assets.car").expect("asset entry exists");
+        assert_eq!(entry.compression(), CompressionMethod::Stored);
+        assert_eq!(entry.compressed_size(), 4_096);
+    }
+
+    #[test]
+    fn zip_directory_can_compress_entries_with_zstd() {
+        let temp = tempdir().expect("can create temp directory");
+        let source = temp.path().join("source");
+        fs::create_dir_all(&source).expect("can create source directory");
+        fs::write(source.join("Assets.car"), vec![b'a'; 4_096]).expect("can write asset");
+
+        let destination = temp.path().join("artifacts.zip");
+        let options = ZipOptions {
+            compression: ZipCompression {
+                method: ZipCompressionMethod::Zstd,
+                level: ZipCompressionMethod::Zstd.default_level(),
+            },
+            ..ZipOptions::default()
+        };
+        zip_directory(&source, &destination, options).expect("should create zip");
+
+        let archive_file = fs::File::open(&destination).expect("can open zip");
+        let mut archive = ZipArchive::new(archive_file).expect("can extract zip");
+        let mut entry = archive.by_name("Assets.car").expect("asset entry exists");
+        assert_eq!(entry.compression(), CompressionMethod::Zstd);
+        assert!(entry.compressed_size() < 4_096);
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents).expect("can read entry");
+        assert_eq!(contents, vec![b'a'; 4_096]);
+    }
+
+    #[test]
+    fn zip_directory_streams_large_files_intact() {
+        let temp = tempdir().expect("can create temp directory");
+        let source = temp.path().join("VisionApp.app");
+        fs::create_dir_all(&source).expect("can create source directory");
+        let binary = source.join("VisionApp");
+        // Several copy buffers plus a partial one, with content that differs between chunks.
+        let len = 3 * ZIP_COPY_BUFFER_BYTES * 64 + 12_345;
+        let mut state = 0x2545_f491_u32;
+        let payload: Vec<u8> = (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        fs::write(&binary, &payload).expect("can write binary");
+
+        let destination = temp.path().join("artifacts.zip");
+        let options = ZipOptions {
+            compression: ZipCompression {
+                method: ZipCompressionMethod::Store,
+                level: 0,
+            },
+            ..ZipOptions::default()
+        };
+        zip_directory(&source, &destination, options).expect("should create zip");
+
+        let archive_file = fs::File::open(&destination).expect("can open zip");
+        let mut archive = ZipArchive::new(archive_file).expect("can extract zip");
+        let mut entry = archive.by_name("VisionApp").expect("binary entry exists");
+        assert_eq!(entry.size(), len as u64);
+        let mut hasher = Sha256::new();
+        io::copy(&mut entry, &mut hasher).expect("can read entry");
+        assert_eq!(
+            format!("{:x}", hasher.finalize()),
+            compute_sha256(&binary).expect("can hash binary")
+        );
+    }
+
+    #[test]
+    fn zip_directory_filter_packs_only_matching_entries() {
+        let temp = tempdir().expect("can create temp directory");
+        let source = temp.path().join("staging");
+        let products = source.join("Debug-xros");
+        let app = products.join("VisionApp.app");
+        let dsym = products.join("VisionApp.app.dSYM");
+        let intermediates = source.join("Intermediates.noindex");
+        for dir in [&app, &dsym, &intermediates] {
+            fs::create_dir_all(dir).expect("can create directory");
+        }
+        fs::write(app.join("VisionApp"), b"binary").expect("can write binary");
+        fs::write(app.join("Info.plist.orig"), b"backup").expect("can write backup");
+        fs::write(dsym.join("Info.plist"), b"dsym").expect("can write dSYM");
+        fs::write(intermediates.join("main.o"), b"object").expect("can write object");
+
+        let destination = temp.path().join("artifacts.zip");
+        let options = ZipOptions {
+            filter: ZipFilter::new(&["*.app".into(), "*.dSYM".into()], &["*.orig".into()])
+                .expect("valid patterns"),
+            ..ZipOptions::default()
+        };
+        zip_directory(&source, &destination, options).expect("should create zip");
+
+        let archive_file = fs::File::open(&destination).expect("can open zip");
+        let archive = ZipArchive::new(archive_file).expect("can extract zip");
+        let mut names: Vec<_> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "Debug-xros/VisionApp.app.dSYM/",
+                "Debug-xros/VisionApp.app.dSYM/Info.plist",
+                "Debug-xros/VisionApp.app/",
+                "Debug-xros/VisionApp.app/VisionApp",
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn zip_directory_keeps_permissions_and_modification_times() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempdir().expect("can create temp directory");
+        let source = temp.path().join("VisionApp.app");
+        fs::create_dir_all(&source).expect("can create source directory");
+        let binary = source.join("VisionApp");
+        let plist = source.join("Info.plist");
+        fs::write(&binary, b"binary").expect("can write binary");
+        fs::write(&plist, b"plist").expect("can write plist");
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).expect("can chmod");
+        fs::set_permissions(&plist, fs::Permissions::from_mode(0o644)).expect("can chmod");
+        let modified = DateTime::parse_from_rfc3339("2024-03-05T06:07:08Z")
+            .expect("valid timestamp")
+            .with_timezone(&Utc);
+        File::options()
+            .write(true)
+            .open(&binary)
+            .and_then(|file| file.set_modified(modified.into()))
+            .expect("can set mtime");
+
+        let destination = temp.path().join("artifacts.zip");
+        zip_directory(&source, &destination, ZipOptions::default()).expect("should create zip");
+
+        let archive_file = fs::File::open(&destination).expect("can open zip");
+        let mut archive = ZipArchive::new(archive_file).expect("can extract zip");
+        {
+            let entry = archive.by_name("VisionApp").expect("binary entry exists");
+            assert_eq!(entry.unix_mode().map(|mode| mode & 0o777), Some(0o755));
+            let time = entry.last_modified();
+            assert_eq!(
+                (time.year(), time.month(), time.day()),
+                (2024, 3, 5),
+                "date is kept"
+            );
+            assert_eq!((time.hour(), time.minute(), time.second()), (6, 7, 8));
+        }
+        let entry = archive.by_name("Info.plist").expect("plist entry exists");
+        assert_eq!(entry.unix_mode().map(|mode| mode & 0o777), Some(0o644));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn deterministic_zips_are_byte_identical_for_identical_trees() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempdir().expect("can create temp directory");
+        let stage = |name: &str, modified: &str, mode: u32| {
+            let source = temp.path().join(name).join("VisionApp.app");
+            fs::create_dir_all(source.join("Base.lproj")).expect("can create source directory");
+            let binary = source.join("VisionApp");
+            fs::write(&binary, b"binary").expect("can write binary");
+            fs::write(source.join("Base.lproj/Main.strings"), b"strings")
+                .expect("can write strings");
+            fs::set_permissions(&binary, fs::Permissions::from_mode(mode)).expect("can chmod");
+            let modified = DateTime::parse_from_rfc3339(modified)
+                .expect("valid timestamp")
+                .with_timezone(&Utc);
+            File::options()
+                .write(true)
+                .open(&binary)
+                .and_then(|file| file.set_modified(modified.into()))
+                .expect("can set mtime");
+            source
+        };
+        let first = stage("first", "2024-03-05T06:07:08Z", 0o755);
+        let second = stage("second", "2025-11-12T13:14:16Z", 0o700);
+        let zip = |source: &Path, deterministic: bool| {
+            let destination = source.with_extension(format!("{deterministic}.zip"));
+            let options = ZipOptions {
+                deterministic,
+                ..ZipOptions::default()
+            };
+            zip_directory(source, &destination, options).expect("should create zip");
+            fs::read(destination).expect("can read zip")
+        };
+
+        assert_eq!(zip(&first, true), zip(&second, true));
+        assert_ne!(zip(&first, false), zip(&second, false));
+
+        let archive = zip(&first, true);
+        let mut archive = ZipArchive::new(io::Cursor::new(archive)).expect("can extract zip");
+        let entry = archive.by_name("VisionApp").expect("binary entry exists");
+        assert_eq!(entry.unix_mode().map(|mode| mode & 0o777), Some(0o755));
+        assert_eq!(entry.last_modified().year(), 1980);
+    }
+
+    /// Lay out `Kit.framework` the way Xcode does for a versioned macOS-style framework.
+    #[cfg(unix)]
+    fn framework_fixture(root: &Path) -> PathBuf {
+        use std::os::unix::fs::symlink;
+
+        let framework = root.join("Frameworks").join("Kit.framework");
+        let version = framework.join("Versions").join("A");
+        fs::create_dir_all(version.join("Resources")).expect("can create framework version");
+        fs::write(version.join("Kit"), b"kit binary").expect("can write framework binary");
+        fs::write(version.join("Resources/Info.plist"), b"plist").expect("can write plist");
+        symlink("A", framework.join("Versions/Current")).expect("can link current version");
+        symlink("Versions/Current/Kit", framework.join("Kit")).expect("can link binary");
+        symlink("Versions/Current/Resources", framework.join("Resources"))
+            .expect("can link resources");
+        framework
+    }
+
+    #[cfg(unix)]
+    fn symlink_target(archive: &mut ZipArchive<fs::File>, name: &str) -> Option<String> {
+        let mut entry = archive.by_name(name).expect("entry exists");
+        if entry.unix_mode()? & 0o170000 != 0o120000 {
+            return None;
+        }
+        let mut target = String::new();
+        entry
+            .read_to_string(&mut target)
+            .expect("can read link target");
+        Some(target)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn zip_directory_preserves_framework_symlinks() {
+        let temp = tempdir().expect("can create temp directory");
+        let source = temp.path().join("VisionApp.app");
+        framework_fixture(&source);
+        std::os::unix::fs::symlink(temp.path().join("outside.txt"), source.join("External"))
+            .expect("can link outside the bundle");
+        fs::write(temp.path().join("outside.txt"), b"outside").expect("can write outside file");
+
+        let destination = temp.path().join("artifacts.zip");
+        zip_directory(
+            &source,
+            &destination,
+            ZipOptions {
+                symlinks: SymlinkPolicy::Preserve,
+                ..ZipOptions::default()
+            },
+        )
+        .expect("should create zip");
+
+        let archive_file = fs::File::open(&destination).expect("can open zip");
+        let mut archive = ZipArchive::new(archive_file).expect("can extract zip");
+        let framework = "Frameworks/Kit.framework";
+        assert_eq!(
+            symlink_target(&mut archive, &format!("{framework}/Versions/Current")).as_deref(),
+            Some("A")
+        );
+        assert_eq!(
+            symlink_target(&mut archive, &format!("{framework}/Kit")).as_deref(),
+            Some("Versions/Current/Kit")
+        );
+        assert_eq!(
+            symlink_target(&mut archive, &format!("{framework}/Resources")).as_deref(),
+            Some("Versions/Current/Resources")
+        );
+        assert!(archive
+            .by_name(&format!("{framework}/Resources/Info.plist"))
+            .is_err());
+        // Links leaving the bundle cannot be restored on another machine, so their target is stored.
+        assert_eq!(symlink_target(&mut archive, "External"), None);
+        let mut external = String::new();
+        archive
+            .by_name("External")
+            .expect("external entry exists")
+            .read_to_string(&mut external)
+            .expect("can read external entry");
+        assert_eq!(external, "outside");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn zip_directory_can_dereference_symlinks() {
+        let temp = tempdir().expect("can create temp directory");
+        let source = temp.path().join("VisionApp.app");
+        framework_fixture(&source);
+
+        let destination = temp.path().join("artifacts.zip");
+        zip_directory(
+            &source,
+            &destination,
+            ZipOptions {
+                symlinks: SymlinkPolicy::Dereference,
+                ..ZipOptions::default()
+            },
+        )
+        .expect("should create zip");
+
+        let archive_file = fs::File::open(&destination).expect("can open zip");
+        let mut archive = ZipArchive::new(archive_file).expect("can extract zip");
+        let framework = "Frameworks/Kit.framework";
+        assert_eq!(
+            symlink_target(&mut archive, &format!("{framework}/Kit")),
+            None
+        );
+        let mut binary = String::new();
+        archive
+            .by_name(&format!("{framework}/Kit"))
+            .expect("binary entry exists")
+            .read_to_string(&mut binary)
+            .expect("can read binary entry");
+        assert_eq!(binary, "kit binary");
+        assert!(archive
+            .by_name(&format!("{framework}/Resources/Info.plist"))
+            .is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn zip_directory_rejects_dereferenced_symlink_loops() {
+        let temp = tempdir().expect("can create temp directory");
+        let source = temp.path().join("VisionApp.app");
+        let nested = source.join("Contents");
+        fs::create_dir_all(&nested).expect("can create source directory");
+        std::os::unix::fs::symlink("..", nested.join("Parent")).expect("can link parent");
+
+        let destination = temp.path().join("artifacts.zip");
+        let error = zip_directory(
+            &source,
+            &destination,
+            ZipOptions {
+                symlinks: SymlinkPolicy::Dereference,
+                ..ZipOptions::default()
+            },
+        )
+        .expect_err("loop is rejected");
+        assert!(matches!(error, ArtifactError::SymlinkLoop { .. }));
+    }
+
     #[test]
     fn resolve_codex_skills_root_prefers_codex_home() {
         let root = resolve_codex_skills_root_from(
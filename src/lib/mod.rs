@@ -1,8 +1,16 @@
 //! Shared library modules providing error types, file utilities, and telemetry initialization.
 
+pub mod app_size;
+pub mod compilation_cache;
+pub mod devices;
 pub mod errors;
 pub mod fs;
 pub mod paths;
+pub mod platform;
+pub mod postprocess;
+pub mod retry;
+pub mod signing;
 pub mod telemetry;
 pub mod visionos;
 pub mod xcodebuild;
+pub mod xcresult;
@@ -1,8 +1,16 @@
 //! Shared helpers reused across modules (e.g., path validation).
 
-use std::path::Path;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 /// Returns true if the path is non-empty and absolute.
 pub fn is_nonempty_absolute(path: &Path) -> bool {
     !path.as_os_str().is_empty() && path.is_absolute()
 }
+
+/// `path` with `..` and symlinks resolved, or unchanged when it does not exist.
+pub fn resolve_existing(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,79 @@
+//! Apple platforms the build, archive, and test tools can target.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Target platform of a build.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    #[default]
+    VisionOs,
+    Ios,
+    MacOs,
+    TvOs,
+    WatchOs,
+}
+
+impl Platform {
+    pub const ALL: [Platform; 5] = [
+        Self::VisionOs,
+        Self::Ios,
+        Self::MacOs,
+        Self::TvOs,
+        Self::WatchOs,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::VisionOs => "visionos",
+            Self::Ios => "ios",
+            Self::MacOs => "macos",
+            Self::TvOs => "tvos",
+            Self::WatchOs => "watchos",
+        }
+    }
+
+    /// Platform named by `value` (`visionos`, `ios`, `macos`, `tvos`, `watchos`).
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|platform| platform.as_str() == value)
+    }
+
+    /// `xcodebuild -destination` used when neither the request nor the config sets one.
+    pub fn default_destination(self) -> &'static str {
+        match self {
+            Self::VisionOs => "platform=visionOS Simulator,name=Apple Vision Pro",
+            Self::Ios => "platform=iOS Simulator,name=iPhone 16",
+            Self::MacOs => "platform=macOS",
+            Self::TvOs => "platform=tvOS Simulator,name=Apple TV",
+            Self::WatchOs => "platform=watchOS Simulator,name=Apple Watch Series 10 (46mm)",
+        }
+    }
+
+    /// SDKs checked by the sandbox tools when the config does not list any. Names match
+    /// `xcodebuild -showsdks` identifiers without the version suffix.
+    pub fn default_required_sdks(self) -> &'static [&'static str] {
+        match self {
+            Self::VisionOs => &["visionOS", "visionOS Simulator"],
+            Self::Ios => &["iphoneos", "iphonesimulator"],
+            Self::MacOs => &["macosx"],
+            Self::TvOs => &["appletvos", "appletvsimulator"],
+            Self::WatchOs => &["watchos", "watchsimulator"],
+        }
+    }
+}
@@ -0,0 +1,408 @@
+//! Post-build stripping and architecture thinning via `xcrun strip` and `xcrun lipo`, plus
+//! dSYM lookup and `xcrun atos` symbolication.
+
+use std::{
+    ffi::{OsStr, OsString},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use tokio::process::Command;
+
+use crate::lib::{
+    app_size,
+    errors::{ArtifactError, PostProcessError},
+    fs::{self as artifact_fs, ZipOptions},
+};
+
+/// Directory (next to `staging`) that receives dSYM bundles split out of a stripped build.
+pub const DSYM_DIR_NAME: &str = "dSYMs";
+
+/// Options requested for a build's binaries.
+#[derive(Debug, Clone, Copy)]
+pub struct PostProcessOptions<'a> {
+    /// Remove debug and local symbols with `strip -S -x`.
+    pub strip_symbols: bool,
+    /// Architectures to keep; empty keeps every slice.
+    pub architectures: &'a [String],
+}
+
+impl PostProcessOptions<'_> {
+    pub fn is_empty(&self) -> bool {
+        !self.strip_symbols && self.architectures.is_empty()
+    }
+}
+
+/// What was changed in the packaged app.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct PostProcessReport {
+    /// Binaries touched, relative to the `.app` bundle.
+    pub binaries: Vec<String>,
+    pub stripped: bool,
+    /// Architecture slices removed from at least one binary.
+    pub removed_architectures: Vec<String>,
+    /// Zip of the dSYM bundles moved out of the app artifact.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dsym_artifact_path: Option<String>,
+}
+
+/// Strip and/or thin the main executable and embedded frameworks of the app in `staging_dir`.
+///
+/// Returns `None` when no `.app` bundle was produced.
+pub async fn process_app_bundle(
+    xcrun_path: &Path,
+    developer_dir: &Path,
+    staging_dir: &Path,
+    options: PostProcessOptions<'_>,
+) -> Result<Option<PostProcessReport>, PostProcessError> {
+    let Some(app) = app_size::find_app_bundle(staging_dir)? else {
+        return Ok(None);
+    };
+    let mut report = PostProcessReport {
+        stripped: options.strip_symbols,
+        ..PostProcessReport::default()
+    };
+    for binary in app_binaries(&app) {
+        if !options.architectures.is_empty() {
+            let available = lipo_archs(xcrun_path, developer_dir, &binary).await?;
+            let removed = thin_binary(
+                xcrun_path,
+                developer_dir,
+                &binary,
+                &available,
+                options.architectures,
+            )
+            .await?;
+            for arch in removed {
+                if !report.removed_architectures.contains(&arch) {
+                    report.removed_architectures.push(arch);
+                }
+            }
+        }
+        if options.strip_symbols {
+            let args = [
+                OsStr::new("strip"),
+                OsStr::new("-S"),
+                OsStr::new("-x"),
+                binary.as_os_str(),
+            ];
+            run_xcrun(xcrun_path, developer_dir, "strip", &binary, &args).await?;
+        }
+        report.binaries.push(
+            binary
+                .strip_prefix(&app)
+                .unwrap_or(&binary)
+                .to_string_lossy()
+                .to_string(),
+        );
+    }
+    report.removed_architectures.sort();
+    Ok(Some(report))
+}
+
+/// Move top-level `*.dSYM` bundles out of `staging_dir` into `job_dir/dSYMs` and zip them.
+///
+/// Returns the zip path, or `None` when the build produced no dSYMs.
+pub fn split_dsyms(
+    staging_dir: &Path,
+    job_dir: &Path,
+    options: ZipOptions,
+) -> Result<Option<PathBuf>, PostProcessError> {
+    let dsyms: Vec<PathBuf> = fs::read_dir(staging_dir)
+        .map_err(|source| ArtifactError::ReadDir {
+            path: staging_dir.to_path_buf(),
+            source,
+        })?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path.extension().is_some_and(|ext| ext == "dSYM"))
+        .collect();
+    if dsyms.is_empty() {
+        return Ok(None);
+    }
+    let dsym_dir = job_dir.join(DSYM_DIR_NAME);
+    fs::create_dir_all(&dsym_dir).map_err(|source| ArtifactError::CreateDir {
+        path: dsym_dir.clone(),
+        source,
+    })?;
+    for dsym in dsyms {
+        let target = dsym_dir.join(dsym.file_name().unwrap_or_default());
+        fs::rename(&dsym, &target).map_err(|source| ArtifactError::Io {
+            path: dsym.clone(),
+            source,
+        })?;
+    }
+    let zip = job_dir.join("dsyms.zip");
+    artifact_fs::zip_directory(&dsym_dir, &zip, options)?;
+    Ok(Some(zip))
+}
+
+/// `*.dSYM` bundles directly inside `dir`, sorted.
+pub fn find_dsyms(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dsyms: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path.extension().is_some_and(|ext| ext == "dSYM"))
+        .collect();
+    dsyms.sort();
+    dsyms
+}
+
+/// Every dSYM a job kept: in `staging`, in the split `dSYMs` directory, and in the `dSYMs`
+/// folder of an `.xcarchive`.
+pub fn job_dsyms(job_dir: &Path) -> Vec<PathBuf> {
+    let staging_dir = job_dir.join("staging");
+    let mut dsyms = find_dsyms(&staging_dir);
+    dsyms.extend(find_dsyms(&job_dir.join(DSYM_DIR_NAME)));
+    if let Ok(entries) = fs::read_dir(&staging_dir) {
+        let mut archives: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "xcarchive"))
+            .collect();
+        archives.sort();
+        for archive in archives {
+            dsyms.extend(find_dsyms(&archive.join(DSYM_DIR_NAME)));
+        }
+    }
+    dsyms
+}
+
+/// DWARF files inside `dsym`, keyed by the name of the binary they describe.
+pub fn dwarf_files(dsym: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dsym.join("Contents/Resources/DWARF")) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_string();
+            Some((name, path))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Resolve `addresses` of a binary loaded at `load_address` with `xcrun atos`.
+///
+/// Returns one entry per address; `None` where atos could not find a symbol.
+pub async fn atos(
+    xcrun_path: &Path,
+    developer_dir: &Path,
+    dwarf: &Path,
+    arch: &str,
+    load_address: u64,
+    addresses: &[u64],
+) -> Result<Vec<Option<String>>, PostProcessError> {
+    let mut args: Vec<OsString> = vec![
+        "atos".into(),
+        "-o".into(),
+        dwarf.into(),
+        "-arch".into(),
+        arch.into(),
+        "-l".into(),
+        format!("{load_address:#x}").into(),
+    ];
+    args.extend(
+        addresses
+            .iter()
+            .map(|address| format!("{address:#x}").into()),
+    );
+    let stdout = run_xcrun(xcrun_path, developer_dir, "atos", dwarf, &args).await?;
+    let mut lines = stdout.lines();
+    // atos echoes the address back when it has no symbol for it.
+    Ok(addresses
+        .iter()
+        .map(|_| {
+            lines
+                .next()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with("0x"))
+                .map(str::to_string)
+        })
+        .collect())
+}
+
+/// Main executable plus `Frameworks/*.framework/<Name>` binaries that exist in the bundle.
+fn app_binaries(app: &Path) -> Vec<PathBuf> {
+    let mut binaries = Vec::new();
+    if let Some(stem) = app.file_stem() {
+        binaries.push(app.join(stem));
+    }
+    if let Ok(entries) = fs::read_dir(app.join("Frameworks")) {
+        let mut frameworks: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "framework"))
+            .filter_map(|framework| framework.file_stem().map(|stem| framework.join(stem)))
+            .collect();
+        frameworks.sort();
+        binaries.extend(frameworks);
+    }
+    binaries.retain(|binary| binary.is_file());
+    binaries
+}
+
+/// Keep only the `requested` slices of `binary`, returning the architectures that were removed.
+async fn thin_binary(
+    xcrun_path: &Path,
+    developer_dir: &Path,
+    binary: &Path,
+    available: &[String],
+    requested: &[String],
+) -> Result<Vec<String>, PostProcessError> {
+    let (keep, removed) = partition_architectures(available, requested);
+    if keep.is_empty() {
+        return Err(PostProcessError::NoMatchingArchitecture {
+            path: binary.to_path_buf(),
+            available: available.join(" "),
+        });
+    }
+    if removed.is_empty() {
+        return Ok(removed);
+    }
+    let flag = if keep.len() == 1 { "-thin" } else { "-extract" };
+    let mut args: Vec<OsString> = vec!["lipo".into(), binary.into()];
+    for arch in &keep {
+        args.push(flag.into());
+        args.push(arch.into());
+    }
+    args.push("-output".into());
+    args.push(binary.into());
+    run_xcrun(xcrun_path, developer_dir, "lipo", binary, &args).await?;
+    Ok(removed)
+}
+
+/// Split `available` slices into those to keep and those to remove.
+fn partition_architectures(
+    available: &[String],
+    requested: &[String],
+) -> (Vec<String>, Vec<String>) {
+    available
+        .iter()
+        .cloned()
+        .partition(|arch| requested.contains(arch))
+}
+
+async fn lipo_archs(
+    xcrun_path: &Path,
+    developer_dir: &Path,
+    binary: &Path,
+) -> Result<Vec<String>, PostProcessError> {
+    let args = [OsStr::new("lipo"), OsStr::new("-archs"), binary.as_os_str()];
+    let stdout = run_xcrun(xcrun_path, developer_dir, "lipo", binary, &args).await?;
+    Ok(stdout.split_whitespace().map(str::to_string).collect())
+}
+
+async fn run_xcrun<S: AsRef<OsStr>>(
+    xcrun_path: &Path,
+    developer_dir: &Path,
+    tool: &'static str,
+    binary: &Path,
+    args: &[S],
+) -> Result<String, PostProcessError> {
+    let output = Command::new(xcrun_path)
+        .kill_on_drop(true)
+        .env("DEVELOPER_DIR", developer_dir)
+        .args(args)
+        .output()
+        .await
+        .map_err(|source| PostProcessError::Spawn { tool, source })?;
+    if !output.status.success() {
+        return Err(PostProcessError::CommandFailed {
+            tool,
+            path: binary.to_path_buf(),
+            exit_code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn partition_keeps_requested_slices_in_binary_order() {
+        let available = vec!["x86_64".to_string(), "arm64".to_string()];
+        let (keep, removed) = partition_architectures(&available, &["arm64".to_string()]);
+        assert_eq!(keep, vec!["arm64".to_string()]);
+        assert_eq!(removed, vec!["x86_64".to_string()]);
+
+        let (keep, removed) = partition_architectures(&available, &["arm64e".to_string()]);
+        assert!(keep.is_empty());
+        assert_eq!(removed.len(), 2);
+    }
+
+    #[test]
+    fn app_binaries_include_embedded_frameworks() {
+        let temp = tempdir().expect("can create temp directory");
+        let app = temp.path().join("VisionApp.app");
+        fs::create_dir_all(app.join("Frameworks/Kit.framework")).expect("can create bundle");
+        fs::create_dir_all(app.join("Frameworks/Empty.framework")).expect("can create bundle");
+        fs::write(app.join("VisionApp"), "bin").expect("can write binary");
+        fs::write(app.join("Frameworks/Kit.framework/Kit"), "bin").expect("can write binary");
+
+        assert_eq!(
+            app_binaries(&app),
+            vec![
+                app.join("VisionApp"),
+                app.join("Frameworks/Kit.framework/Kit")
+            ]
+        );
+    }
+
+    #[test]
+    fn job_dsyms_include_split_and_archived_bundles() {
+        let temp = tempdir().expect("can create temp directory");
+        let staging = temp.path().join("staging");
+        let built = staging.join("VisionApp.app.dSYM");
+        let split = temp.path().join("dSYMs/Kit.framework.dSYM");
+        let archived = staging.join("VisionApp.xcarchive/dSYMs/VisionApp.app.dSYM");
+        for dsym in [&built, &split, &archived] {
+            fs::create_dir_all(dsym.join("Contents/Resources/DWARF")).expect("can create dSYM");
+        }
+        fs::create_dir_all(staging.join("VisionApp.app")).expect("can create app");
+        fs::write(built.join("Contents/Resources/DWARF/VisionApp"), "dwarf")
+            .expect("can write DWARF");
+
+        assert_eq!(job_dsyms(temp.path()), vec![built.clone(), split, archived]);
+        assert_eq!(
+            dwarf_files(&built),
+            vec![(
+                "VisionApp".to_string(),
+                built.join("Contents/Resources/DWARF/VisionApp")
+            )]
+        );
+    }
+
+    #[test]
+    fn split_dsyms_moves_bundles_out_of_staging() {
+        let temp = tempdir().expect("can create temp directory");
+        let staging = temp.path().join("staging");
+        fs::create_dir_all(staging.join("VisionApp.dSYM")).expect("can create dSYM");
+        fs::write(staging.join("VisionApp.dSYM/Contents"), "dwarf").expect("can write dSYM");
+
+        let zip = split_dsyms(&staging, temp.path(), ZipOptions::default())
+            .expect("can split")
+            .expect("dSYM zip is written");
+        assert!(zip.is_file());
+        assert!(!staging.join("VisionApp.dSYM").exists());
+        assert!(temp.path().join("dSYMs/VisionApp.dSYM/Contents").is_file());
+        assert_eq!(
+            split_dsyms(&staging, temp.path(), ZipOptions::default()).expect("can split"),
+            None
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Retrying `xcodebuild` runs that failed for reasons unrelated to the project, such as a
+//! simulator that did not boot or a package download that dropped.
+
+use std::{collections::BTreeSet, time::Duration};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Default for `visionos.retry.backoff_secs`.
+pub const DEFAULT_RETRY_BACKOFF_SECS: u64 = 10;
+/// Largest accepted `visionos.retry.max_attempts`.
+pub const MAX_RETRY_ATTEMPTS: u8 = 5;
+
+/// Transient failure class recognised in `xcodebuild` output.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryReason {
+    /// CoreSimulator could not boot or reach the destination simulator.
+    SimulatorBoot,
+    /// Swift Package Manager could not fetch a dependency over the network.
+    SpmNetwork,
+    /// Xcode's build service crashed or lost its connection.
+    BuildService,
+}
+
+impl RetryReason {
+    pub const ALL: [RetryReason; 3] = [
+        RetryReason::SimulatorBoot,
+        RetryReason::SpmNetwork,
+        RetryReason::BuildService,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RetryReason::SimulatorBoot => "simulator_boot",
+            RetryReason::SpmNetwork => "spm_network",
+            RetryReason::BuildService => "build_service",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|reason| reason.as_str() == value)
+    }
+
+    /// Output fragments that identify this failure.
+    fn markers(self) -> &'static [&'static str] {
+        match self {
+            RetryReason::SimulatorBoot => &[
+                "Unable to boot",
+                "Failed to boot",
+                "CoreSimulatorService connection became invalid",
+                "Simulator device failed to launch",
+            ],
+            RetryReason::SpmNetwork => &[
+                "Could not resolve host",
+                "The network connection was lost",
+                "The request timed out",
+                "Failed to clone repository",
+                "unable to access 'https://",
+            ],
+            RetryReason::BuildService => &[
+                "unexpected service error",
+                "The build service has encountered an internal inconsistency",
+                "Lost connection to the build service",
+            ],
+        }
+    }
+
+    /// First failure class whose markers appear in `log`.
+    pub fn classify(log: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|reason| reason.markers().iter().any(|marker| log.contains(marker)))
+    }
+}
+
+/// `[visionos.retry]`: how often a failed `xcodebuild` run is repeated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Runs per job including the first; `1` disables retries.
+    pub max_attempts: u8,
+    /// Wait before the first retry; doubled before each later one.
+    pub backoff: Duration,
+    /// Failure classes worth another run. Compile errors never match.
+    pub retry_on: BTreeSet<RetryReason>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff: Duration::from_secs(DEFAULT_RETRY_BACKOFF_SECS),
+            retry_on: [RetryReason::SimulatorBoot, RetryReason::SpmNetwork].into(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Why failed attempt number `attempt` (1-based) should be retried, if it should.
+    pub fn retry_reason(&self, attempt: u8, log: &str) -> Option<RetryReason> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        RetryReason::classify(log).filter(|reason| self.retry_on.contains(reason))
+    }
+
+    /// Wait after failed attempt number `attempt` before the next one.
+    pub fn backoff_after(&self, attempt: u8) -> Duration {
+        self.backoff
+            .saturating_mul(1u32 << u32::from(attempt.saturating_sub(1)).min(16))
+    }
+}
+
+/// One `xcodebuild` run of a job.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct BuildAttempt {
+    /// 1-based.
+    pub attempt: u8,
+    pub exit_code: Option<i32>,
+    /// Failure class that caused another run; `None` for the final attempt.
+    pub retry_reason: Option<RetryReason>,
+    pub duration_ms: u128,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_transient_failures_only() {
+        assert_eq!(
+            RetryReason::classify("xcodebuild: error: Unable to boot the Simulator."),
+            Some(RetryReason::SimulatorBoot)
+        );
+        assert_eq!(
+            RetryReason::classify(
+                "fatal: unable to access 'https://github.com/apple/swift-collections.git/': Could not resolve host: github.com"
+            ),
+            Some(RetryReason::SpmNetwork)
+        );
+        assert_eq!(
+            RetryReason::classify("ContentView.swift:11:8: error: cannot find 'x' in scope"),
+            None
+        );
+    }
+
+    #[test]
+    fn retries_listed_reasons_until_attempts_run_out() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            ..RetryPolicy::default()
+        };
+        let log = "Unable to boot device in current state: Booted";
+
+        assert_eq!(
+            policy.retry_reason(1, log),
+            Some(RetryReason::SimulatorBoot)
+        );
+        assert_eq!(policy.retry_reason(3, log), None);
+        assert_eq!(policy.retry_reason(1, "unexpected service error"), None);
+        assert_eq!(policy.backoff_after(1), Duration::from_secs(10));
+        assert_eq!(policy.backoff_after(2), Duration::from_secs(20));
+    }
+}
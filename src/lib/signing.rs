@@ -0,0 +1,87 @@
+//! Code signing settings shared by the config, build requests, and the sandbox check.
+
+/// `CODE_SIGN_IDENTITY` value for ad-hoc signing ("Sign to Run Locally"), which needs no
+/// certificate in the keychain.
+pub const AD_HOC_IDENTITY: &str = "-";
+
+/// Code signing certificate found in the keychain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningIdentity {
+    /// SHA-1 fingerprint of the certificate.
+    pub hash: String,
+    /// Common name, e.g. `Apple Development: Jane Doe (A1B2C3D4E5)`.
+    pub name: String,
+}
+
+/// Apple Developer team IDs are 10 uppercase letters and digits.
+pub fn is_team_id(value: &str) -> bool {
+    value.len() == 10
+        && value
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// True when `requested` (as passed to `CODE_SIGN_IDENTITY`) selects `identity`: its SHA-1
+/// hash, its full name, or a prefix such as `Apple Development` that Xcode resolves to it.
+pub fn identity_matches(requested: &str, identity: &SigningIdentity) -> bool {
+    let requested = requested.trim();
+    !requested.is_empty()
+        && (identity.hash.eq_ignore_ascii_case(requested)
+            || identity.name == requested
+            || identity
+                .name
+                .strip_prefix(requested)
+                .is_some_and(|rest| rest.starts_with(':')))
+}
+
+/// Parse `security find-identity -v -p codesigning` output. Lines look like
+/// `  1) 0123…CDEF "Apple Development: Jane Doe (A1B2C3D4E5)"`.
+pub fn parse_find_identity_output(stdout: &str) -> Vec<SigningIdentity> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.trim_start().split_once(") ")?;
+            let (hash, name) = rest.split_once(' ')?;
+            let name = name.trim().strip_prefix('"')?.strip_suffix('"')?;
+            Some(SigningIdentity {
+                hash: hash.to_string(),
+                name: name.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_find_identity_output_and_matches_prefixes() {
+        let stdout = r#"  1) 4F3A0C9E6B1D2A7E8F9C0B1A2D3E4F5A6B7C8D9E "Apple Development: Jane Doe (Q1W2E3R4T5)"
+  2) 9E8D7C6B5A4F3E2D1C0B9A8F7E6D5C4B3A2F1E0D "Apple Distribution: Example Inc. (A1B2C3D4E5)"
+     2 valid identities found
+"#;
+
+        let identities = parse_find_identity_output(stdout);
+
+        assert_eq!(identities.len(), 2);
+        assert_eq!(
+            identities[1].name,
+            "Apple Distribution: Example Inc. (A1B2C3D4E5)"
+        );
+        assert!(identity_matches("Apple Development", &identities[0]));
+        assert!(identity_matches(
+            "9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e0d",
+            &identities[1]
+        ));
+        assert!(!identity_matches("Apple Distribution", &identities[0]));
+        assert!(!identity_matches("Apple", &identities[0]));
+    }
+
+    #[test]
+    fn team_ids_are_ten_uppercase_alphanumerics() {
+        assert!(is_team_id("A1B2C3D4E5"));
+        assert!(!is_team_id("a1b2c3d4e5"));
+        assert!(!is_team_id("A1B2C3D4E"));
+    }
+}
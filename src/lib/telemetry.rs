@@ -1,13 +1,29 @@
-//! Telemetry initialization and visionOS job span helpers.
+//! Telemetry initialization, redaction of sensitive values, and visionOS job span helpers.
 
-use std::time::Instant;
+use std::{
+    io::{self, Write},
+    sync::{LazyLock, RwLock},
+    time::Instant,
+};
 
 use anyhow::Result;
+use regex::Regex;
 use serde::Serialize;
+use serde_json::Value;
 use tracing::{info, info_span, Span};
 use tracing_subscriber::{fmt, EnvFilter};
 use uuid::Uuid;
 
+/// Keys whose values are masked in logs and error data. A key matches when it contains one of
+/// these, ignoring case.
+pub const DEFAULT_SENSITIVE_KEYS: &[&str] =
+    &["token", "secret", "password", "credential", "env_overrides"];
+/// Replacement for masked values.
+pub const REDACTED: &str = "[redacted]";
+
+static REDACTION: LazyLock<RwLock<Redaction>> =
+    LazyLock::new(|| RwLock::new(Redaction::new(&[], false)));
+
 /// Initialize `tracing` and format developer logs.
 pub fn init_tracing() -> Result<()> {
     if tracing::dispatcher::has_been_set() {
@@ -20,11 +36,134 @@ pub fn init_tracing() -> Result<()> {
         .with_target(true)
         .with_thread_ids(true)
         .with_file(true)
-        .with_writer(std::io::stderr)
+        .with_writer(|| RedactingWriter(io::stderr()))
         .try_init()
         .map_err(|err| anyhow::anyhow!("failed to initialize tracing: {err}"))
 }
 
+/// What to mask before logs and error data leave the process.
+#[derive(Debug, Clone)]
+pub struct Redaction {
+    /// Lowercased sensitive key fragments.
+    keys: Vec<String>,
+    /// Home directory replaced by `~` in strings, when enabled.
+    home: Option<String>,
+    /// `key=value`, `key: value`, and `"key": value` pairs with a sensitive key.
+    pairs: Regex,
+}
+
+impl Redaction {
+    /// Mask [`DEFAULT_SENSITIVE_KEYS`] plus `extra_keys`, and the home directory when
+    /// `home_paths` is set.
+    pub fn new(extra_keys: &[String], home_paths: bool) -> Self {
+        let keys: Vec<String> = DEFAULT_SENSITIVE_KEYS
+            .iter()
+            .map(|key| key.to_string())
+            .chain(extra_keys.iter().map(|key| key.trim().to_ascii_lowercase()))
+            .filter(|key| !key.is_empty())
+            .collect();
+        let alternatives: Vec<String> = keys.iter().map(|key| regex::escape(key)).collect();
+        let pairs = Regex::new(&format!(
+            r#"(?i)("?\w*(?:{})\w*"?\s*[=:]\s*)("(?:[^"\\]|\\.)*"|\{{[^}}]*\}}|[^\s,}}\]]+)"#,
+            alternatives.join("|")
+        ))
+        .expect("escaped key alternatives form a valid regex");
+        let home = home_paths
+            .then(|| std::env::var("HOME").ok())
+            .flatten()
+            .map(|home| home.trim_end_matches('/').to_string())
+            .filter(|home| !home.is_empty());
+        Self { keys, home, pairs }
+    }
+
+    fn is_sensitive(&self, key: &str) -> bool {
+        let key = key.to_ascii_lowercase();
+        self.keys.iter().any(|fragment| key.contains(fragment))
+    }
+
+    /// Mask the values of sensitive keys in `value`, keeping the keys of masked maps so
+    /// `env_overrides` still shows which variables were set.
+    pub fn mask_json(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, entry) in map.iter_mut() {
+                    if self.is_sensitive(key) {
+                        mask_all(entry);
+                    } else {
+                        self.mask_json(entry);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.mask_json(item)),
+            Value::String(text) => {
+                if let Some(home) = self.replace_home(text) {
+                    *text = home;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Mask sensitive `key=value` pairs and the home directory in a formatted log line.
+    pub fn mask_text(&self, text: &str) -> String {
+        let masked = self.pairs.replace_all(text, format!("${{1}}{REDACTED}"));
+        self.replace_home(&masked)
+            .unwrap_or_else(|| masked.into_owned())
+    }
+
+    fn replace_home(&self, text: &str) -> Option<String> {
+        let home = self.home.as_deref()?;
+        text.contains(home).then(|| text.replace(home, "~"))
+    }
+}
+
+fn mask_all(value: &mut Value) {
+    match value {
+        Value::Object(map) => map.values_mut().for_each(mask_all),
+        Value::Array(items) => items.iter_mut().for_each(mask_all),
+        Value::Null => {}
+        other => *other = Value::String(REDACTED.into()),
+    }
+}
+
+/// Replace the process-wide redaction policy, e.g. after the config is loaded or reloaded.
+pub fn set_redaction(redaction: Redaction) {
+    *REDACTION
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = redaction;
+}
+
+/// Mask `value` with the process-wide policy.
+pub fn redact_json(value: &mut Value) {
+    REDACTION
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .mask_json(value);
+}
+
+/// Mask `text` with the process-wide policy.
+pub fn redact_text(text: &str) -> String {
+    REDACTION
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .mask_text(text)
+}
+
+/// Log writer that masks each formatted event with the process-wide policy.
+struct RedactingWriter<W>(W);
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let masked = redact_text(&String::from_utf8_lossy(buf));
+        self.0.write_all(masked.as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
 /// Span helper to record start and finish of a visionOS job.
 pub struct JobSpan {
     span: Span,
@@ -63,6 +202,26 @@ impl JobSpan {
     }
 }
 
+/// Payload for logging a job the artifact store evicted to stay within its retention limits.
+#[derive(Debug, Serialize)]
+pub struct ArtifactEvictionTelemetry {
+    pub job_id: Uuid,
+    pub bytes: u64,
+    /// Limit that was exceeded: `max_jobs` or `max_total_bytes`.
+    pub limit: &'static str,
+}
+
+/// Emit an artifact eviction to `tracing`.
+pub fn emit_artifact_eviction(telemetry: &ArtifactEvictionTelemetry) {
+    info!(
+        target: "rmcp_sample::visionos",
+        job_id = %telemetry.job_id,
+        bytes = telemetry.bytes,
+        limit = telemetry.limit,
+        "Evicted artifacts over the retention limit"
+    );
+}
+
 /// Payload for logging MCP runtime state as structured telemetry.
 #[derive(Debug, Serialize)]
 pub struct RuntimeModeTelemetry<'a> {
@@ -85,3 +244,48 @@ pub fn emit_runtime_mode(telemetry: &RuntimeModeTelemetry<'_>) {
         "Started MCP server"
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn json_masks_sensitive_keys_and_keeps_env_override_names() {
+        let redaction = Redaction::new(&["signing_identity".into()], false);
+        let mut value = json!({
+            "auth_token": "abc123",
+            "env_overrides": { "API_KEY": "s3cr3t" },
+            "details": [{ "Signing_Identity": "Apple Development: Jane" }],
+            "scheme": "VisionApp",
+        });
+
+        redaction.mask_json(&mut value);
+
+        assert_eq!(
+            value,
+            json!({
+                "auth_token": REDACTED,
+                "env_overrides": { "API_KEY": REDACTED },
+                "details": [{ "Signing_Identity": REDACTED }],
+                "scheme": "VisionApp",
+            })
+        );
+    }
+
+    #[test]
+    fn text_masks_sensitive_pairs_and_home_directory() {
+        let mut redaction = Redaction::new(&[], false);
+        redaction.home = Some("/Users/jane".into());
+
+        let masked = redaction.mask_text(
+            "token=abc123 password: \"p w\" env_overrides: {\"A\": \"b\"} path=/Users/jane/App",
+        );
+
+        assert_eq!(
+            masked,
+            "token=[redacted] password: [redacted] env_overrides: [redacted] path=~/App"
+        );
+    }
+}
@@ -1,23 +1,160 @@
 //! Shared helpers for visionOS tools.
-use std::{fs, path::Path, path::PathBuf};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
 
-/// Returns true if `path` is under any of the allowed base paths.
+use glob::{MatchOptions, Pattern, PatternError};
+use regex::Regex;
+
+use crate::lib::xcodebuild::{format_log, LogFormat};
+
+/// `*` and `?` stay within one path component; `**` spans any number of them.
+const PATTERN_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Returns true if `path` is under any of the allowed base paths, or under a directory matching
+/// an allowed glob pattern.
 pub fn is_allowed_path(path: &Path, allowed: &[PathBuf]) -> bool {
     let Ok(path) = fs::canonicalize(path) else {
         return false;
     };
-    allowed
+    allowed.iter().any(|entry| {
+        if is_path_pattern(entry) {
+            return resolve_path_pattern(entry).is_some_and(|pattern| {
+                path.ancestors()
+                    .any(|dir| pattern.matches_path_with(dir, PATTERN_OPTIONS))
+            });
+        }
+        fs::canonicalize(entry).is_ok_and(|base| path.starts_with(base))
+    })
+}
+
+/// Returns true if an allowlist entry contains glob syntax (`*`, `?`, `[`).
+pub fn is_path_pattern(entry: &Path) -> bool {
+    entry.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// Check that `entry` parses as a glob pattern.
+pub fn validate_path_pattern(entry: &Path) -> Result<(), PatternError> {
+    Pattern::new(&entry.to_string_lossy()).map(|_| ())
+}
+
+/// Compile `entry` with its literal leading directories canonicalized, so patterns match the
+/// canonical paths checked by [`is_allowed_path`].
+fn resolve_path_pattern(entry: &Path) -> Option<Pattern> {
+    let components: Vec<_> = entry.components().collect();
+    let literal_len = components
         .iter()
-        .filter_map(|base| fs::canonicalize(base).ok())
-        .any(|base| path.starts_with(base))
+        .position(|component| is_path_pattern(Path::new(component.as_os_str())))
+        .unwrap_or(components.len());
+    let literal: PathBuf = components[..literal_len].iter().collect();
+    let base = fs::canonicalize(literal).ok()?;
+    let mut pattern = Pattern::escape(base.to_str()?);
+    for component in &components[literal_len..] {
+        if let Component::Normal(part) = component {
+            pattern.push('/');
+            pattern.push_str(part.to_str()?);
+        } else {
+            return None;
+        }
+    }
+    Pattern::new(&pattern).ok()
+}
+
+/// One `allowed_schemes` entry, compiled when the config is loaded. Entries wrapped in `^...$`
+/// are regexes; entries containing `*`, `?`, or `[` are globs; anything else is an exact name.
+#[derive(Debug, Clone)]
+pub struct SchemePattern {
+    entry: String,
+    matcher: SchemeMatcher,
+}
+
+#[derive(Debug, Clone)]
+enum SchemeMatcher {
+    Exact,
+    Glob(Pattern),
+    Regex(Regex),
+}
+
+impl SchemePattern {
+    /// Compile `entry`, or describe why its regex or glob does not compile.
+    pub fn new(entry: &str) -> Result<Self, String> {
+        let matcher = if is_scheme_regex(entry) {
+            SchemeMatcher::Regex(scheme_regex(entry).map_err(|err| err.to_string())?)
+        } else if is_scheme_glob(entry) {
+            SchemeMatcher::Glob(Pattern::new(entry).map_err(|err| err.to_string())?)
+        } else {
+            SchemeMatcher::Exact
+        };
+        Ok(Self {
+            entry: entry.to_string(),
+            matcher,
+        })
+    }
+
+    /// The entry as written in the config.
+    pub fn as_str(&self) -> &str {
+        &self.entry
+    }
+
+    pub fn matches(&self, scheme: &str) -> bool {
+        match &self.matcher {
+            SchemeMatcher::Exact => self.entry == scheme,
+            SchemeMatcher::Glob(pattern) => pattern.matches(scheme),
+            SchemeMatcher::Regex(regex) => regex.is_match(scheme),
+        }
+    }
+}
+
+/// Compares the entry as written, so configs and tests can check which entries were loaded.
+impl<T: AsRef<str> + ?Sized> PartialEq<T> for SchemePattern {
+    fn eq(&self, other: &T) -> bool {
+        self.entry == other.as_ref()
+    }
+}
+
+/// Returns true if `scheme` equals an allowed entry or matches an allowed pattern.
+pub fn is_allowed_scheme(scheme: &str, allowed: &[SchemePattern]) -> bool {
+    allowed.iter().any(|pattern| pattern.matches(scheme))
+}
+
+fn is_scheme_regex(entry: &str) -> bool {
+    entry.len() > 1 && entry.starts_with('^') && entry.ends_with('$')
+}
+
+/// Compile a `^...$` entry with the anchors around the whole body, so an alternation such as
+/// `^A|B$` cannot match `A` or `B` as a prefix or suffix of a longer scheme. The body must
+/// compile on its own, which keeps it from closing the group early (`^A)|(B$`).
+fn scheme_regex(entry: &str) -> Result<Regex, regex::Error> {
+    let body = &entry[1..entry.len() - 1];
+    Regex::new(body)?;
+    Regex::new(&format!("^(?:{body})$"))
+}
+
+fn is_scheme_glob(entry: &str) -> bool {
+    entry.contains(['*', '?', '['])
 }
 
 /// Merge stdout/stderr and take at most `limit` characters from the end.
 pub fn collect_log_excerpt(stdout: &[u8], stderr: &[u8], limit: usize) -> String {
+    collect_formatted_log_excerpt(stdout, stderr, LogFormat::Raw, limit)
+}
+
+/// [`collect_log_excerpt`] after rewriting the merged output with [`format_log`].
+pub fn collect_formatted_log_excerpt(
+    stdout: &[u8],
+    stderr: &[u8],
+    format: LogFormat,
+    limit: usize,
+) -> String {
     let mut combined = Vec::with_capacity(stdout.len() + stderr.len());
     combined.extend_from_slice(stdout);
     combined.extend_from_slice(stderr);
-    let text = String::from_utf8_lossy(&combined);
+    let text = format_log(&String::from_utf8_lossy(&combined), format);
     if text.chars().count() <= limit {
         return text.to_string();
     }
@@ -69,6 +206,78 @@ mod tests {
         assert!(!is_allowed_path(&link, &[allowed]));
     }
 
+    #[test]
+    fn allowed_path_pattern_matches_each_user_workspace() {
+        let temp = tempdir().expect("can create temp dir");
+        let inside = temp
+            .path()
+            .join("alice")
+            .join("workspaces")
+            .join("VisionApp");
+        let outside = temp.path().join("bob").join("scratch").join("VisionApp");
+        fs::create_dir_all(&inside).expect("can create workspace dir");
+        fs::create_dir_all(&outside).expect("can create scratch dir");
+        let pattern = temp.path().join("*").join("workspaces").join("**");
+
+        assert!(is_allowed_path(&inside, std::slice::from_ref(&pattern)));
+        assert!(!is_allowed_path(&outside, std::slice::from_ref(&pattern)));
+        assert!(!is_allowed_path(
+            &inside.join("..").join("..").join(".."),
+            &[pattern]
+        ));
+    }
+
+    #[test]
+    fn allowed_path_pattern_without_double_star_acts_as_prefix() {
+        let temp = tempdir().expect("can create temp dir");
+        let child = temp
+            .path()
+            .join("alice")
+            .join("workspaces")
+            .join("VisionApp");
+        fs::create_dir_all(&child).expect("can create child dir");
+
+        assert!(is_allowed_path(
+            &child,
+            &[temp.path().join("a*").join("workspaces")]
+        ));
+        assert!(!is_allowed_path(
+            &child,
+            &[temp.path().join("b*").join("workspaces")]
+        ));
+    }
+
+    fn schemes(entries: &[&str]) -> Vec<SchemePattern> {
+        entries
+            .iter()
+            .map(|entry| SchemePattern::new(entry).expect("pattern compiles"))
+            .collect()
+    }
+
+    #[test]
+    fn allowed_scheme_matches_exact_glob_and_anchored_regex_entries() {
+        let allowed = schemes(&["VisionApp", "Generated*", "^Feature[0-9]+Tests$"]);
+
+        assert!(is_allowed_scheme("VisionApp", &allowed));
+        assert!(is_allowed_scheme("GeneratedKit", &allowed));
+        assert!(is_allowed_scheme("Feature12Tests", &allowed));
+        assert!(!is_allowed_scheme("VisionAppTests", &allowed));
+        assert!(!is_allowed_scheme("FeatureTests", &allowed));
+        assert!(SchemePattern::new("^Feature(+$").is_err());
+        assert!(SchemePattern::new("Generated[").is_err());
+    }
+
+    #[test]
+    fn anchored_regex_entries_anchor_every_alternative() {
+        let allowed = schemes(&["^VisionApp|Toolbox$"]);
+
+        assert!(is_allowed_scheme("VisionApp", &allowed));
+        assert!(is_allowed_scheme("Toolbox", &allowed));
+        assert!(!is_allowed_scheme("VisionAppEvil", &allowed));
+        assert!(!is_allowed_scheme("EvilToolbox", &allowed));
+        assert!(SchemePattern::new("^VisionApp)|(Toolbox$").is_err());
+    }
+
     #[test]
     fn allowed_path_accepts_child_after_canonicalization() {
         let temp = tempdir().expect("can create temp dir");
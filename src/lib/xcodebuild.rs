@@ -1,13 +1,60 @@
 //! Shared helpers for building `xcodebuild` commands.
 
-use std::{collections::BTreeMap, path::Path};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::{Output, Stdio},
+};
 
-use tokio::process::Command;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, BufReader},
+    process::Command,
+};
+
+use crate::lib::compilation_cache::CompilationCache;
+
+/// File name of a Swift package manifest.
+pub const PACKAGE_MANIFEST: &str = "Package.swift";
+/// Errors parsed from the output that build responses and `build_failed` errors carry.
+pub const MAX_LOG_ERRORS: usize = 20;
 
 pub struct VisionOsXcodebuildCommandConfig<'a> {
     pub xcodebuild_path: &'a Path,
     pub xcode_path: &'a Path,
     pub staging_dir: &'a Path,
+    /// Passed as `-resultBundlePath` when set.
+    pub result_bundle_path: Option<&'a Path>,
+    /// Passed as `-derivedDataPath` when set, so incremental builds can reuse earlier output.
+    pub derived_data_path: Option<&'a Path>,
+    /// Compiler cache whose environment and build settings are added to the run.
+    pub compilation_cache: Option<&'a CompilationCache>,
+    /// Exported as `LANG` and `LC_ALL`; requests may override either through `env_overrides`.
+    pub locale: &'a str,
+}
+
+/// `xcodebuild` action run after the optional `clean`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XcodebuildAction {
+    Build,
+    Test,
+    /// `archive` writing `<staging_dir>/<scheme>.xcarchive`.
+    Archive,
+    /// `clean` on its own, removing the scheme's build products.
+    Clean,
+}
+
+impl XcodebuildAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Build => "build",
+            Self::Test => "test",
+            Self::Archive => "archive",
+            Self::Clean => "clean",
+        }
+    }
 }
 
 pub struct VisionOsXcodebuildRequest<'a> {
@@ -17,23 +64,37 @@ pub struct VisionOsXcodebuildRequest<'a> {
     pub configuration: &'a str,
     pub destination: &'a str,
     pub clean: bool,
+    pub action: XcodebuildAction,
     pub extra_args: &'a [String],
+    /// `KEY=VALUE` build settings such as the signing settings, passed before `extra_args`.
+    pub build_settings: &'a [String],
     pub env_overrides: &'a BTreeMap<String, String>,
 }
 
-/// Build an `xcodebuild` command for a visionOS build.
+/// Build an `xcodebuild` command for a visionOS build or test run.
 pub fn build_visionos_xcodebuild_command(
     config: VisionOsXcodebuildCommandConfig<'_>,
     request: VisionOsXcodebuildRequest<'_>,
 ) -> Command {
     let mut command = Command::new(config.xcodebuild_path);
     command.kill_on_drop(true);
-    command.current_dir(request.project_path);
+    // `xcodebuild` builds the package in its working directory when given a manifest path.
+    let working_dir = match request.project_path.file_name() {
+        Some(name) if name == PACKAGE_MANIFEST => request
+            .project_path
+            .parent()
+            .unwrap_or(request.project_path),
+        _ => request.project_path,
+    };
+    command.current_dir(working_dir);
     command.env_clear();
     command.env("NSUnbufferedIO", "YES");
     command.env("DEVELOPER_DIR", config.xcode_path);
     command.env("VISIONOS_BUILD_ARTIFACT_DIR", config.staging_dir);
-    for (key, value) in request.env_overrides {
+    command.env("LANG", config.locale);
+    command.env("LC_ALL", config.locale);
+    let cache_env = config.compilation_cache.map(|cache| &cache.env);
+    for (key, value) in cache_env.into_iter().flatten().chain(request.env_overrides) {
         if key == "DEVELOPER_DIR" {
             continue;
         }
@@ -54,11 +115,28 @@ pub fn build_visionos_xcodebuild_command(
     command.arg("-scheme").arg(request.scheme);
     command.arg("-configuration").arg(request.configuration);
     command.arg("-destination").arg(request.destination);
+    if let Some(derived_data_path) = config.derived_data_path {
+        command.arg("-derivedDataPath").arg(derived_data_path);
+    }
 
-    if request.clean {
+    if request.clean && request.action != XcodebuildAction::Clean {
         command.arg("clean");
     }
-    command.arg("build");
+    command.arg(request.action.as_str());
+    if request.action == XcodebuildAction::Archive {
+        command
+            .arg("-archivePath")
+            .arg(archive_path(config.staging_dir, request.scheme));
+    }
+
+    if let Some(result_bundle_path) = config.result_bundle_path {
+        command.arg("-resultBundlePath").arg(result_bundle_path);
+    }
+
+    if let Some(cache) = config.compilation_cache {
+        command.args(cache.build_settings());
+    }
+    command.args(request.build_settings);
 
     for arg in request.extra_args {
         command.arg(arg);
@@ -67,12 +145,281 @@ pub fn build_visionos_xcodebuild_command(
     command
 }
 
+/// Stable DerivedData directory for `project_root` under `root`: the project name plus a hash of
+/// its full path, so same-named projects in different checkouts do not share build output.
+pub fn derived_data_path(root: &Path, project_root: &Path) -> PathBuf {
+    let digest = Sha256::digest(project_root.as_os_str().as_encoded_bytes());
+    let hash: String = digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let name = project_root
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "project".into());
+    root.join(format!("{name}-{hash}"))
+}
+
+/// Location of the `.xcarchive` produced by an archive run.
+pub fn archive_path(staging_dir: &Path, scheme: &str) -> PathBuf {
+    staging_dir.join(format!("{scheme}.xcarchive"))
+}
+
+/// Build phase announced by an `xcodebuild` log line, if any.
+pub fn build_phase(line: &str) -> Option<&'static str> {
+    const PHASES: &[(&str, &str)] = &[
+        ("Resolve Package Graph", "resolve_packages"),
+        (
+            "ComputePackagePrebuildTargetDependencyGraph",
+            "resolve_packages",
+        ),
+        ("CompileSwift", "compile"),
+        ("SwiftCompile", "compile"),
+        ("SwiftDriver", "compile"),
+        ("CompileC ", "compile"),
+        ("CompileAssetCatalog", "process_resources"),
+        ("CompileStoryboard", "process_resources"),
+        ("ProcessInfoPlistFile", "process_resources"),
+        ("CopySwiftLibs", "process_resources"),
+        ("Ld ", "link"),
+        ("CodeSign ", "code_sign"),
+        ("Test Suite ", "test"),
+        ("Test Case ", "test"),
+    ];
+    let line = line.trim_start();
+    PHASES
+        .iter()
+        .find(|(prefix, _)| line.starts_with(prefix))
+        .map(|(_, phase)| *phase)
+}
+
+/// Run `command` like [`Command::output`], passing each stdout line to `on_line` as it arrives.
+pub async fn output_with_lines(
+    mut command: Command,
+    mut on_line: impl FnMut(&str),
+) -> std::io::Result<Output> {
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = command.spawn()?;
+    let mut stdout = BufReader::new(child.stdout.take().expect("piped stdout"));
+    let mut stderr = child.stderr.take().expect("piped stderr");
+
+    let read_stdout = async {
+        let mut collected = Vec::new();
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            if stdout.read_until(b'\n', &mut line).await? == 0 {
+                break;
+            }
+            on_line(&String::from_utf8_lossy(&line));
+            collected.extend_from_slice(&line);
+        }
+        Ok::<_, std::io::Error>(collected)
+    };
+    let read_stderr = async {
+        let mut collected = Vec::new();
+        stderr.read_to_end(&mut collected).await?;
+        Ok::<_, std::io::Error>(collected)
+    };
+    let (stdout, stderr) = tokio::try_join!(read_stdout, read_stderr)?;
+    let status = child.wait().await?;
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// Diagnostic severity reported by the compiler or `xcodebuild`.
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// Warning or error line extracted from a build log.
+#[derive(Debug, Clone, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct BuildLogDiagnostic {
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+}
+
+impl BuildLogDiagnostic {
+    /// Identity used for diffing; line/column are excluded because edits shift them.
+    pub fn key(&self) -> (DiagnosticSeverity, Option<&str>, &str) {
+        (self.severity, self.file.as_deref(), self.message.as_str())
+    }
+}
+
+/// Parse unique `error:` / `warning:` diagnostics from `xcodebuild` output, in log order.
+pub fn parse_diagnostics(log: &str) -> Vec<BuildLogDiagnostic> {
+    let mut diagnostics: Vec<BuildLogDiagnostic> = Vec::new();
+    for raw_line in log.lines() {
+        let Some(diagnostic) = parse_diagnostic_line(raw_line.trim()) else {
+            continue;
+        };
+        if !diagnostics
+            .iter()
+            .any(|existing| existing.key() == diagnostic.key())
+        {
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+fn parse_diagnostic_line(line: &str) -> Option<BuildLogDiagnostic> {
+    for (marker, severity) in [
+        ("error: ", DiagnosticSeverity::Error),
+        ("warning: ", DiagnosticSeverity::Warning),
+    ] {
+        if let Some(message) = line.strip_prefix(marker) {
+            return Some(BuildLogDiagnostic {
+                severity,
+                message: message.trim().to_string(),
+                file: None,
+                line: None,
+                column: None,
+            });
+        }
+
+        let Some((left, message)) = line.split_once(&format!(": {marker}")) else {
+            continue;
+        };
+        let message = message.trim().to_string();
+        let mut parts = left.rsplitn(3, ':');
+        let column = parts
+            .next()
+            .and_then(|text| text.trim().parse::<u32>().ok());
+        let line_number = parts
+            .next()
+            .and_then(|text| text.trim().parse::<u32>().ok());
+        let file = parts.next();
+        return Some(match (file, line_number, column) {
+            (Some(file), Some(line_number), Some(column)) => BuildLogDiagnostic {
+                severity,
+                message,
+                file: Some(file.to_string()),
+                line: Some(line_number),
+                column: Some(column),
+            },
+            _ => BuildLogDiagnostic {
+                severity,
+                message,
+                file: left.contains('/').then(|| left.to_string()),
+                line: None,
+                column: None,
+            },
+        });
+    }
+    None
+}
+
+/// How `log_excerpt` presents the `xcodebuild` output. `build.log` always keeps the raw log.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Output as printed.
+    #[default]
+    Raw,
+    /// Step headers, diagnostics with their source snippets, and results; the indented
+    /// compiler invocations and environment exports under each step are dropped.
+    Compact,
+    /// Error diagnostics and `** ... **` result lines only; falls back to `compact` when the
+    /// log has neither, so launch failures still explain themselves.
+    ErrorsOnly,
+}
+
+/// Rewrite `log` for `format`.
+pub fn format_log(log: &str, format: LogFormat) -> String {
+    match format {
+        LogFormat::Raw => log.to_string(),
+        LogFormat::Compact => compact_log(log),
+        LogFormat::ErrorsOnly => {
+            let lines: Vec<&str> = log
+                .lines()
+                .filter(|line| {
+                    let trimmed = line.trim();
+                    trimmed.starts_with("** ")
+                        || parse_diagnostic_line(trimmed).is_some_and(|diagnostic| {
+                            diagnostic.severity == DiagnosticSeverity::Error
+                        })
+                })
+                .collect();
+            if lines.is_empty() {
+                compact_log(log)
+            } else {
+                lines.join("\n")
+            }
+        }
+    }
+}
+
+fn compact_log(log: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_diagnostic = false;
+    for line in log.lines() {
+        if line.trim().is_empty() {
+            in_diagnostic = false;
+            continue;
+        }
+        if parse_diagnostic_line(line.trim()).is_some() {
+            in_diagnostic = true;
+            lines.push(line);
+        } else if in_diagnostic || !line.starts_with(char::is_whitespace) {
+            lines.push(line);
+        }
+    }
+    lines.join("\n")
+}
+
+/// First `limit` errors in the combined `xcodebuild` stdout and stderr.
+pub fn collect_log_errors(stdout: &[u8], stderr: &[u8], limit: usize) -> Vec<BuildLogDiagnostic> {
+    let mut log = String::from_utf8_lossy(stdout).into_owned();
+    log.push('\n');
+    log.push_str(&String::from_utf8_lossy(stderr));
+    parse_diagnostics(&log)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == DiagnosticSeverity::Error)
+        .take(limit)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::{collections::BTreeMap, path::PathBuf};
 
     use super::*;
 
+    #[test]
+    fn log_formats_drop_compiler_noise() {
+        let log = "CompileSwift normal arm64 /p/App.swift (in target 'App')\n    cd /p\n    export LANG=en_US.UTF-8\n    /usr/bin/swiftc -c /p/App.swift\n\n/p/App.swift:3:5: error: cannot find 'x' in scope\n    x += 1\n    ^\n\n/p/App.swift:9:1: warning: unused variable\n** BUILD FAILED **\n";
+
+        assert_eq!(format_log(log, LogFormat::Raw), log);
+        assert_eq!(
+            format_log(log, LogFormat::Compact),
+            "CompileSwift normal arm64 /p/App.swift (in target 'App')\n/p/App.swift:3:5: error: cannot find 'x' in scope\n    x += 1\n    ^\n/p/App.swift:9:1: warning: unused variable\n** BUILD FAILED **"
+        );
+        assert_eq!(
+            format_log(log, LogFormat::ErrorsOnly),
+            "/p/App.swift:3:5: error: cannot find 'x' in scope\n** BUILD FAILED **"
+        );
+        assert_eq!(
+            format_log(
+                "xcodebuild: unknown option\n    usage\n",
+                LogFormat::ErrorsOnly
+            ),
+            "xcodebuild: unknown option"
+        );
+    }
+
     #[test]
     fn env_overrides_cannot_replace_configured_developer_dir() {
         let xcodebuild_path = PathBuf::from("/usr/bin/xcodebuild");
@@ -93,6 +440,10 @@ mod tests {
                 xcodebuild_path: &xcodebuild_path,
                 xcode_path: &xcode_path,
                 staging_dir: &staging_dir,
+                result_bundle_path: None,
+                derived_data_path: None,
+                compilation_cache: None,
+                locale: "en_US.UTF-8",
             },
             VisionOsXcodebuildRequest {
                 project_path: &project_path,
@@ -101,7 +452,9 @@ mod tests {
                 configuration: "Debug",
                 destination: "platform=visionOS Simulator,name=Apple Vision Pro",
                 clean: false,
+                action: XcodebuildAction::Build,
                 extra_args: &extra_args,
+                build_settings: &[],
                 env_overrides: &env_overrides,
             },
         );
@@ -125,4 +478,272 @@ mod tests {
             Some(std::ffi::OsStr::new("true"))
         );
     }
+
+    #[test]
+    fn locale_is_exported_unless_overridden() {
+        let xcodebuild_path = PathBuf::from("/usr/bin/xcodebuild");
+        let xcode_path = PathBuf::from("/Applications/Xcode.app/Contents/Developer");
+        let staging_dir = PathBuf::from("/tmp/staging");
+        let project_path = PathBuf::from("/tmp/project");
+        let extra_args = Vec::new();
+        let env_overrides = BTreeMap::from([("LC_ALL".to_string(), "ja_JP.UTF-8".to_string())]);
+
+        let command = build_visionos_xcodebuild_command(
+            VisionOsXcodebuildCommandConfig {
+                xcodebuild_path: &xcodebuild_path,
+                xcode_path: &xcode_path,
+                staging_dir: &staging_dir,
+                result_bundle_path: None,
+                derived_data_path: None,
+                compilation_cache: None,
+                locale: "en_US.UTF-8",
+            },
+            VisionOsXcodebuildRequest {
+                project_path: &project_path,
+                workspace: None,
+                scheme: "VisionApp",
+                configuration: "Debug",
+                destination: "platform=visionOS Simulator,name=Apple Vision Pro",
+                clean: false,
+                action: XcodebuildAction::Build,
+                extra_args: &extra_args,
+                build_settings: &[],
+                env_overrides: &env_overrides,
+            },
+        );
+
+        let envs: BTreeMap<_, _> = command
+            .as_std()
+            .get_envs()
+            .filter_map(|(key, value)| {
+                value.map(|value| (key.to_os_string(), value.to_os_string()))
+            })
+            .collect();
+
+        assert_eq!(
+            envs.get(std::ffi::OsStr::new("LANG"))
+                .map(|value| value.as_os_str()),
+            Some(std::ffi::OsStr::new("en_US.UTF-8"))
+        );
+        assert_eq!(
+            envs.get(std::ffi::OsStr::new("LC_ALL"))
+                .map(|value| value.as_os_str()),
+            Some(std::ffi::OsStr::new("ja_JP.UTF-8"))
+        );
+    }
+
+    #[test]
+    fn archive_action_writes_xcarchive_into_staging_dir() {
+        let xcodebuild_path = PathBuf::from("/usr/bin/xcodebuild");
+        let xcode_path = PathBuf::from("/Applications/Xcode.app/Contents/Developer");
+        let staging_dir = PathBuf::from("/tmp/staging");
+        let project_path = PathBuf::from("/tmp/project");
+        let extra_args = Vec::new();
+        let env_overrides = BTreeMap::new();
+
+        let command = build_visionos_xcodebuild_command(
+            VisionOsXcodebuildCommandConfig {
+                xcodebuild_path: &xcodebuild_path,
+                xcode_path: &xcode_path,
+                staging_dir: &staging_dir,
+                result_bundle_path: None,
+                derived_data_path: None,
+                compilation_cache: None,
+                locale: "en_US.UTF-8",
+            },
+            VisionOsXcodebuildRequest {
+                project_path: &project_path,
+                workspace: None,
+                scheme: "VisionApp",
+                configuration: "Release",
+                destination: "generic/platform=visionOS",
+                clean: false,
+                action: XcodebuildAction::Archive,
+                extra_args: &extra_args,
+                build_settings: &[],
+                env_overrides: &env_overrides,
+            },
+        );
+
+        let args: Vec<_> = command
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        let tail = &args[args.len() - 3..];
+        assert_eq!(
+            tail,
+            [
+                "archive",
+                "-archivePath",
+                "/tmp/staging/VisionApp.xcarchive"
+            ]
+        );
+    }
+
+    #[test]
+    fn package_manifest_builds_from_package_directory() {
+        let xcodebuild_path = PathBuf::from("/usr/bin/xcodebuild");
+        let xcode_path = PathBuf::from("/Applications/Xcode.app/Contents/Developer");
+        let staging_dir = PathBuf::from("/tmp/staging");
+        let project_path = PathBuf::from("/tmp/VisionKit/Package.swift");
+        let extra_args = Vec::new();
+        let env_overrides = BTreeMap::new();
+
+        let command = build_visionos_xcodebuild_command(
+            VisionOsXcodebuildCommandConfig {
+                xcodebuild_path: &xcodebuild_path,
+                xcode_path: &xcode_path,
+                staging_dir: &staging_dir,
+                result_bundle_path: None,
+                derived_data_path: None,
+                compilation_cache: None,
+                locale: "en_US.UTF-8",
+            },
+            VisionOsXcodebuildRequest {
+                project_path: &project_path,
+                workspace: None,
+                scheme: "VisionKit",
+                configuration: "Debug",
+                destination: "generic/platform=visionOS",
+                clean: false,
+                action: XcodebuildAction::Build,
+                extra_args: &extra_args,
+                build_settings: &[],
+                env_overrides: &env_overrides,
+            },
+        );
+
+        let command = command.as_std();
+        assert_eq!(command.get_current_dir(), Some(Path::new("/tmp/VisionKit")));
+        let args: Vec<_> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(args[..2], ["-scheme", "VisionKit"]);
+    }
+
+    #[test]
+    fn compilation_cache_adds_environment_and_build_settings() {
+        let xcodebuild_path = PathBuf::from("/usr/bin/xcodebuild");
+        let xcode_path = PathBuf::from("/Applications/Xcode.app/Contents/Developer");
+        let staging_dir = PathBuf::from("/tmp/staging");
+        let project_path = PathBuf::from("/tmp/project");
+        let extra_args = vec!["-quiet".to_string()];
+        let env_overrides = BTreeMap::new();
+        let cache = CompilationCache {
+            mode: crate::lib::compilation_cache::CompilationCacheMode::Xcode,
+            env: BTreeMap::from([("SCCACHE_DIR".to_string(), "/tmp/sccache".to_string())]),
+        };
+
+        let command = build_visionos_xcodebuild_command(
+            VisionOsXcodebuildCommandConfig {
+                xcodebuild_path: &xcodebuild_path,
+                xcode_path: &xcode_path,
+                staging_dir: &staging_dir,
+                result_bundle_path: None,
+                derived_data_path: None,
+                compilation_cache: Some(&cache),
+                locale: "en_US.UTF-8",
+            },
+            VisionOsXcodebuildRequest {
+                project_path: &project_path,
+                workspace: None,
+                scheme: "VisionApp",
+                configuration: "Debug",
+                destination: "generic/platform=visionOS",
+                clean: false,
+                action: XcodebuildAction::Build,
+                extra_args: &extra_args,
+                build_settings: &[],
+                env_overrides: &env_overrides,
+            },
+        );
+
+        let command = command.as_std();
+        assert!(command.get_envs().any(|(key, value)| key == "SCCACHE_DIR"
+            && value == Some(std::ffi::OsStr::new("/tmp/sccache"))));
+        let args: Vec<_> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            args[args.len() - 3..],
+            ["build", "COMPILATION_CACHE_ENABLE_CACHING=YES", "-quiet"]
+        );
+    }
+
+    #[test]
+    fn derived_data_path_is_stable_per_project() {
+        let root = Path::new("/tmp/DerivedData");
+        let app = derived_data_path(root, Path::new("/work/a/VisionApp.xcodeproj"));
+
+        assert_eq!(app.parent(), Some(root));
+        assert!(app
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("VisionApp-") && name.len() == 26));
+        assert_eq!(
+            app,
+            derived_data_path(root, Path::new("/work/a/VisionApp.xcodeproj"))
+        );
+        assert_ne!(
+            app,
+            derived_data_path(root, Path::new("/work/b/VisionApp.xcodeproj"))
+        );
+    }
+
+    #[test]
+    fn build_phase_recognizes_common_xcodebuild_steps() {
+        assert_eq!(
+            build_phase("CompileSwift normal arm64 /tmp/VisionApp/ContentView.swift"),
+            Some("compile")
+        );
+        assert_eq!(
+            build_phase("Ld /tmp/Build/VisionApp.app/VisionApp normal"),
+            Some("link")
+        );
+        assert_eq!(
+            build_phase("CodeSign /tmp/Build/VisionApp.app"),
+            Some("code_sign")
+        );
+        assert_eq!(build_phase("note: Using new build system"), None);
+    }
+
+    #[tokio::test]
+    async fn output_with_lines_streams_stdout_and_keeps_stderr() {
+        let mut command = Command::new("/bin/sh");
+        command
+            .arg("-c")
+            .arg("echo 'CompileSwift a.swift'; echo oops >&2; echo 'Ld app'");
+        let mut phases = Vec::new();
+
+        let output = output_with_lines(command, |line| phases.extend(build_phase(line)))
+            .await
+            .expect("command runs");
+
+        assert!(output.status.success());
+        assert_eq!(phases, vec!["compile", "link"]);
+        assert_eq!(output.stdout, b"CompileSwift a.swift\nLd app\n");
+        assert_eq!(output.stderr, b"oops\n");
+    }
+
+    #[test]
+    fn parse_diagnostics_reads_locations_and_deduplicates() {
+        let log = "/tmp/A.swift:10:5: error: cannot find 'foo' in scope\n\
+                   /tmp/A.swift:10:5: error: cannot find 'foo' in scope\n\
+                   /tmp/B.swift:3:1: warning: variable 'x' was never used\n\
+                   error: linker command failed with exit code 1\n\
+                   ** BUILD FAILED **";
+        let diagnostics = parse_diagnostics(log);
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].file.as_deref(), Some("/tmp/A.swift"));
+        assert_eq!(diagnostics[0].line, Some(10));
+        assert_eq!(diagnostics[1].severity, DiagnosticSeverity::Warning);
+        assert_eq!(
+            diagnostics[2].message,
+            "linker command failed with exit code 1"
+        );
+        assert!(diagnostics[2].file.is_none());
+    }
 }
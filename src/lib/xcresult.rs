@@ -0,0 +1,241 @@
+//! Helpers for reading `.xcresult` bundles via `xcrun xcresulttool`.
+
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::process::Command;
+
+use crate::lib::errors::XcresultError;
+
+/// Severity of an issue recorded in a result bundle.
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum XcresultSeverity {
+    Error,
+    Warning,
+}
+
+/// Coarse grouping of `issueType` values reported by Xcode.
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum XcresultIssueCategory {
+    Compiler,
+    CodeSigning,
+    Analyzer,
+    Other,
+}
+
+/// Warning, error, or code-signing issue read from an `.xcresult` bundle.
+#[derive(Debug, Clone, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct XcresultDiagnostic {
+    pub severity: XcresultSeverity,
+    pub category: XcresultIssueCategory,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// 1-based line number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// 1-based column number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+}
+
+/// Location of the result bundle written for a job (kept outside the zipped staging directory).
+pub fn result_bundle_path(job_dir: &Path) -> PathBuf {
+    job_dir.join("result.xcresult")
+}
+
+/// Run `xcrun xcresulttool get --format json` and extract the bundle's issues.
+///
+/// Xcode 16 moved the JSON object graph behind `--legacy`; the call is retried with the flag when
+/// `xcresulttool` asks for it.
+pub async fn load_diagnostics(
+    xcrun_path: &Path,
+    developer_dir: &Path,
+    bundle: &Path,
+) -> Result<Vec<XcresultDiagnostic>, XcresultError> {
+    let mut output = run_xcresulttool(xcrun_path, developer_dir, bundle, false).await?;
+    if !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("--legacy") {
+        output = run_xcresulttool(xcrun_path, developer_dir, bundle, true).await?;
+    }
+    if !output.status.success() {
+        return Err(XcresultError::CommandFailed {
+            exit_code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    let parsed: Value = serde_json::from_slice(&output.stdout)?;
+    Ok(parse_diagnostics(&parsed))
+}
+
+async fn run_xcresulttool(
+    xcrun_path: &Path,
+    developer_dir: &Path,
+    bundle: &Path,
+    legacy: bool,
+) -> Result<std::process::Output, XcresultError> {
+    let mut command = Command::new(xcrun_path);
+    command.kill_on_drop(true);
+    command.env("DEVELOPER_DIR", developer_dir);
+    command
+        .arg("xcresulttool")
+        .arg("get")
+        .arg("--format")
+        .arg("json")
+        .arg("--path")
+        .arg(bundle);
+    if legacy {
+        command.arg("--legacy");
+    }
+    command.output().await.map_err(XcresultError::Spawn)
+}
+
+/// Extract error, warning, and analyzer summaries from `xcresulttool` JSON.
+pub fn parse_diagnostics(root: &Value) -> Vec<XcresultDiagnostic> {
+    let Some(issues) = root.get("issues") else {
+        return Vec::new();
+    };
+    let mut diagnostics = Vec::new();
+    for (key, severity) in [
+        ("errorSummaries", XcresultSeverity::Error),
+        ("warningSummaries", XcresultSeverity::Warning),
+        ("analyzerWarningSummaries", XcresultSeverity::Warning),
+    ] {
+        let Some(values) = issues
+            .get(key)
+            .and_then(|summaries| summaries.get("_values"))
+            .and_then(Value::as_array)
+        else {
+            continue;
+        };
+        for summary in values {
+            let Some(message) = string_value(summary.get("message")) else {
+                continue;
+            };
+            let issue_type = string_value(summary.get("issueType"));
+            let location = string_value(
+                summary
+                    .get("documentLocationInMetadata")
+                    .and_then(|location| location.get("url")),
+            )
+            .map(|url| parse_document_url(&url))
+            .unwrap_or_default();
+            diagnostics.push(XcresultDiagnostic {
+                severity,
+                category: categorize(key, issue_type.as_deref(), &message),
+                message,
+                issue_type,
+                file: location.0,
+                line: location.1,
+                column: location.2,
+            });
+        }
+    }
+    diagnostics
+}
+
+fn string_value(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(|value| value.get("_value"))
+        .and_then(Value::as_str)
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+fn categorize(key: &str, issue_type: Option<&str>, message: &str) -> XcresultIssueCategory {
+    let issue_type = issue_type.unwrap_or_default().to_ascii_lowercase();
+    let message = message.to_ascii_lowercase();
+    if issue_type.contains("signing")
+        || message.contains("provisioning profile")
+        || message.contains("signing certificate")
+    {
+        XcresultIssueCategory::CodeSigning
+    } else if key == "analyzerWarningSummaries" || issue_type.contains("analyzer") {
+        XcresultIssueCategory::Analyzer
+    } else if issue_type.contains("compiler") {
+        XcresultIssueCategory::Compiler
+    } else {
+        XcresultIssueCategory::Other
+    }
+}
+
+/// Split `file:///path#StartingLineNumber=9&StartingColumnNumber=4` into path and 1-based position.
+fn parse_document_url(url: &str) -> (Option<String>, Option<u32>, Option<u32>) {
+    let (path, fragment) = url.split_once('#').unwrap_or((url, ""));
+    let path = path.strip_prefix("file://").unwrap_or(path);
+    let mut line = None;
+    let mut column = None;
+    for pair in fragment.split('&') {
+        let Some((key, value)) = pair.split_once('=') else {
+            continue;
+        };
+        let Ok(value) = value.parse::<u32>() else {
+            continue;
+        };
+        match key {
+            "StartingLineNumber" => line = Some(value + 1),
+            "StartingColumnNumber" => column = Some(value + 1),
+            _ => {}
+        }
+    }
+    let file = (!path.is_empty()).then(|| path.to_string());
+    (file, line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parse_diagnostics_reads_errors_warnings_and_code_signing() {
+        let root = json!({
+            "issues": {
+                "errorSummaries": { "_values": [
+                    {
+                        "issueType": { "_value": "Swift Compiler Error" },
+                        "message": { "_value": "cannot find 'foo' in scope" },
+                        "documentLocationInMetadata": { "url": {
+                            "_value": "file:///tmp/VisionApp/ContentView.swift#EndingLineNumber=9&StartingColumnNumber=4&StartingLineNumber=9"
+                        } }
+                    },
+                    {
+                        "issueType": { "_value": "Code Signing Error" },
+                        "message": { "_value": "No signing certificate \"Apple Development\" found" }
+                    }
+                ] },
+                "warningSummaries": { "_values": [
+                    {
+                        "issueType": { "_value": "Deprecation" },
+                        "message": { "_value": "'foo' is deprecated" }
+                    }
+                ] }
+            }
+        });
+
+        let diagnostics = parse_diagnostics(&root);
+
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].category, XcresultIssueCategory::Compiler);
+        assert_eq!(
+            diagnostics[0].file.as_deref(),
+            Some("/tmp/VisionApp/ContentView.swift")
+        );
+        assert_eq!(diagnostics[0].line, Some(10));
+        assert_eq!(diagnostics[0].column, Some(5));
+        assert_eq!(diagnostics[1].category, XcresultIssueCategory::CodeSigning);
+        assert_eq!(diagnostics[2].severity, XcresultSeverity::Warning);
+        assert_eq!(diagnostics[2].category, XcresultIssueCategory::Other);
+    }
+
+    #[test]
+    fn parse_diagnostics_returns_empty_without_issues() {
+        assert!(parse_diagnostics(&json!({ "actions": {} })).is_empty());
+    }
+}
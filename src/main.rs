@@ -29,14 +29,18 @@ async fn bootstrap() -> Result<(), RuntimeExit> {
 
     match command {
         ParsedCommand::RunServer(profile) => run_server(profile).await,
+        ParsedCommand::SelfCheck(profile) => runtime::run_self_check(profile).await,
         ParsedCommand::Cli(command) => handle_cli_command(command),
     }
 }
 
 /// Start MCP server mode using the resolved launch profile.
 async fn run_server(profile: seiro_mcp::cli::LaunchProfile) -> Result<(), RuntimeExit> {
-    let config = ServerConfig::load_from_path(profile.config_path.clone())
-        .map_err(|err| RuntimeExit::from_error(Error::new(err)))?;
+    let config = ServerConfig::load_with_profile(
+        profile.config_path.clone(),
+        profile.config_profile.clone(),
+    )
+    .map_err(|err| RuntimeExit::from_error(Error::new(err)))?;
     runtime::run_server(profile, config).await
 }
 
@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::lib::errors::ConfigError;
+
+pub const DEFAULT_AWS_CLI_PATH: &str = "/usr/local/bin/aws";
+pub const DEFAULT_GCLOUD_CLI_PATH: &str = "/usr/local/bin/gcloud";
+
+/// Where build artifacts are published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactStorageKind {
+    /// Keep artifacts under the local artifact root only.
+    Local,
+    /// Upload to Amazon S3 with the `aws` CLI.
+    S3,
+    /// Upload to Google Cloud Storage with the `gcloud` CLI.
+    Gcs,
+}
+
+/// `[artifacts]` configuration section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactsConfig {
+    pub backend: ArtifactStorageKind,
+    pub bucket: Option<String>,
+    pub prefix: String,
+    pub cli_path: PathBuf,
+}
+
+impl Default for ArtifactsConfig {
+    fn default() -> Self {
+        Self {
+            backend: ArtifactStorageKind::Local,
+            bucket: None,
+            prefix: String::new(),
+            cli_path: PathBuf::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RawArtifactsSection {
+    pub backend: Option<String>,
+    pub bucket: Option<String>,
+    pub prefix: Option<String>,
+    pub cli_path: Option<PathBuf>,
+}
+
+pub fn parse_artifacts_section(
+    raw: Option<RawArtifactsSection>,
+    path: &Path,
+) -> Result<ArtifactsConfig, ConfigError> {
+    let raw = raw.unwrap_or_default();
+    let backend = match raw.backend.as_deref().map(str::trim) {
+        None | Some("local") => ArtifactStorageKind::Local,
+        Some("s3") => ArtifactStorageKind::S3,
+        Some("gcs") => ArtifactStorageKind::Gcs,
+        Some(other) => {
+            return Err(ConfigError::InvalidField {
+                path: path.to_path_buf(),
+                field: "artifacts.backend",
+                message: format!("Use one of local, s3, gcs (got `{other}`)"),
+            })
+        }
+    };
+    if backend == ArtifactStorageKind::Local {
+        return Ok(ArtifactsConfig::default());
+    }
+
+    let bucket = raw
+        .bucket
+        .filter(|bucket| !bucket.trim().is_empty())
+        .ok_or(ConfigError::MissingField {
+            path: path.to_path_buf(),
+            field: "artifacts.bucket",
+        })?;
+    validate_bucket(path, &bucket)?;
+
+    let prefix = raw.prefix.unwrap_or_default().trim_matches('/').to_string();
+
+    let cli_path = raw.cli_path.unwrap_or_else(|| {
+        PathBuf::from(match backend {
+            ArtifactStorageKind::Gcs => DEFAULT_GCLOUD_CLI_PATH,
+            _ => DEFAULT_AWS_CLI_PATH,
+        })
+    });
+    validate_cli_path(path, &cli_path)?;
+
+    Ok(ArtifactsConfig {
+        backend,
+        bucket: Some(bucket),
+        prefix,
+        cli_path,
+    })
+}
+
+fn validate_bucket(path: &Path, bucket: &str) -> Result<(), ConfigError> {
+    let valid = (3..=63).contains(&bucket.len())
+        && bucket.chars().all(|ch| {
+            ch.is_ascii_lowercase() || ch.is_ascii_digit() || matches!(ch, '-' | '.' | '_')
+        });
+    if !valid {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "artifacts.bucket",
+            message: "Use a 3-63 character bucket name (lowercase letters, digits, '-', '.', '_')"
+                .into(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_cli_path(path: &Path, cli_path: &Path) -> Result<(), ConfigError> {
+    if cli_path.as_os_str().is_empty() || !cli_path.is_absolute() {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "artifacts.cli_path",
+            message: "Provide an absolute path to the aws or gcloud executable".into(),
+        });
+    }
+    Ok(())
+}
@@ -1,35 +1,371 @@
-use std::path::Path;
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
 
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 use crate::lib::errors::ConfigError;
 
-/// Authentication settings.
-#[derive(Debug, Clone)]
+/// Shortest `auth.token` accepted.
+pub const MIN_TOKEN_LEN: usize = 16;
+/// Binary that reads `auth.token_source = "keychain"` tokens.
+pub const SECURITY_PATH: &str = "/usr/bin/security";
+/// Time a keychain lookup or `auth.token_command` gets before loading the config fails.
+const TOKEN_SOURCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Authentication settings. stdio clients need none; remote transports require `token` or at
+/// least one entry in `tokens`.
+#[derive(Debug, Clone, Default)]
 pub struct AuthSection {
+    /// Token with every scope and no path restriction beyond `visionos.allowed_paths`.
+    pub token: Option<String>,
+    /// Token being rotated out: accepted like `token` until `previous_token_valid_until`, so
+    /// clients can move to the new one without a coordinated restart.
+    pub previous_token: Option<String>,
+    /// When `previous_token` stops being accepted; set whenever `previous_token` is.
+    pub previous_token_valid_until: Option<DateTime<Utc>>,
+    /// `[[auth.tokens]]`: tokens limited to some scopes and, optionally, some paths.
+    pub tokens: Vec<ScopedToken>,
+}
+
+impl AuthSection {
+    /// Whether any token is configured, i.e. whether a remote transport can be opened.
+    pub fn has_tokens(&self) -> bool {
+        self.token.is_some() || !self.tokens.is_empty()
+    }
+}
+
+/// One `[[auth.tokens]]` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopedToken {
+    /// Label used in logs and errors instead of the token itself.
+    pub name: String,
     pub token: String,
+    pub scopes: Vec<TokenScope>,
+    /// Project paths calls may name, on top of `visionos.allowed_paths`; empty for no extra
+    /// restriction.
+    pub allowed_paths: Vec<PathBuf>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Group of tools a token may call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenScope {
+    /// Start, cancel, and clean builds, archives, tests, pipelines, and simulators.
+    Build,
+    /// Read job results, logs, traces, diagnostics, and the queue.
+    Fetch,
+    /// Inspect the toolchain, projects, and sandbox policy without building.
+    Sandbox,
+    /// Every tool, including the admin tools.
+    Admin,
+}
+
+impl TokenScope {
+    pub const ALL: [TokenScope; 4] = [
+        TokenScope::Build,
+        TokenScope::Fetch,
+        TokenScope::Sandbox,
+        TokenScope::Admin,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TokenScope::Build => "build",
+            TokenScope::Fetch => "fetch",
+            TokenScope::Sandbox => "sandbox",
+            TokenScope::Admin => "admin",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
 pub struct RawAuthSection {
     pub token: Option<String>,
+    pub token_source: Option<String>,
+    pub keychain_service: Option<String>,
+    pub keychain_account: Option<String>,
+    pub token_command: Option<Vec<String>>,
+    pub previous_token: Option<String>,
+    pub previous_token_valid_until: Option<String>,
+    pub tokens: Option<Vec<RawScopedToken>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawScopedToken {
+    pub name: Option<String>,
+    pub token: Option<String>,
+    pub scopes: Option<Vec<String>>,
+    pub allowed_paths: Option<Vec<PathBuf>>,
 }
 
 pub fn parse_auth_section(
     raw: Option<RawAuthSection>,
     path: &Path,
 ) -> Result<AuthSection, ConfigError> {
-    let auth_raw = raw.ok_or(ConfigError::MissingField {
+    let invalid = |field: &'static str, message: String| ConfigError::InvalidField {
         path: path.to_path_buf(),
-        field: "auth",
-    })?;
-    let token = auth_raw
-        .token
-        .filter(|value| !value.trim().is_empty())
-        .ok_or(ConfigError::MissingField {
-            path: path.to_path_buf(),
-            field: "auth.token",
-        })?;
-
-    Ok(AuthSection { token })
+        field,
+        message,
+    };
+    let raw = raw.unwrap_or_default();
+    let raw_tokens = raw.tokens.unwrap_or_default();
+    let token = raw.token.filter(|value| !value.trim().is_empty());
+    let source_command = match raw.token_source.as_deref().unwrap_or("config") {
+        "config" => {
+            if raw.keychain_service.is_some() || raw.token_command.is_some() {
+                return Err(invalid(
+                    "auth.token_source",
+                    "Set token_source = \"keychain\" or \"command\" to use keychain_service or token_command".into(),
+                ));
+            }
+            None
+        }
+        "keychain" => {
+            let service = raw
+                .keychain_service
+                .filter(|service| !service.trim().is_empty())
+                .ok_or_else(|| {
+                    invalid(
+                        "auth.keychain_service",
+                        "Name the keychain item to read the token from".into(),
+                    )
+                })?;
+            Some(keychain_command(&service, raw.keychain_account.as_deref()))
+        }
+        "command" => Some(
+            raw.token_command
+                .filter(|argv| argv.first().is_some_and(|program| !program.trim().is_empty()))
+                .ok_or_else(|| {
+                    invalid(
+                        "auth.token_command",
+                        "Give the command that prints the token, e.g. [\"op\", \"read\", \"op://ci/seiro/token\"]".into(),
+                    )
+                })?,
+        ),
+        other => {
+            return Err(invalid(
+                "auth.token_source",
+                format!("Unknown source `{other}`; use config, keychain, or command"),
+            ))
+        }
+    };
+    let token = match source_command {
+        None => token,
+        Some(_) if token.is_some() => {
+            return Err(invalid(
+                "auth.token_source",
+                "Remove auth.token; the token is read from token_source".into(),
+            ))
+        }
+        Some(argv) => {
+            Some(read_token(&argv).map_err(|message| invalid("auth.token_source", message))?)
+        }
+    };
+    if token
+        .as_ref()
+        .is_some_and(|token| token.trim().len() < MIN_TOKEN_LEN)
+    {
+        return Err(invalid(
+            "auth.token",
+            format!("Use a token of at least {MIN_TOKEN_LEN} characters"),
+        ));
+    }
+
+    let previous_token = raw.previous_token.filter(|value| !value.trim().is_empty());
+    let previous_token_valid_until = match (&previous_token, raw.previous_token_valid_until) {
+        (None, None) => None,
+        (None, Some(_)) => {
+            return Err(invalid(
+                "auth.previous_token_valid_until",
+                "Set auth.previous_token together with its expiry, or remove both".into(),
+            ))
+        }
+        (Some(_), None) => {
+            return Err(invalid(
+                "auth.previous_token_valid_until",
+                "Give auth.previous_token an RFC 3339 expiry, e.g. 2026-11-01T00:00:00Z".into(),
+            ))
+        }
+        (Some(previous), Some(valid_until)) => {
+            if token.is_none() {
+                return Err(invalid(
+                    "auth.previous_token",
+                    "Rotate to a new auth.token before keeping the old one as previous_token"
+                        .into(),
+                ));
+            }
+            if previous.trim().len() < MIN_TOKEN_LEN || token.as_deref() == Some(previous.as_str())
+            {
+                return Err(invalid(
+                    "auth.previous_token",
+                    format!(
+                        "Use the old token, at least {MIN_TOKEN_LEN} characters and different from auth.token"
+                    ),
+                ));
+            }
+            let valid_until =
+                DateTime::parse_from_rfc3339(valid_until.trim()).map_err(|error| {
+                    invalid(
+                        "auth.previous_token_valid_until",
+                        format!("`{valid_until}` is not an RFC 3339 timestamp: {error}"),
+                    )
+                })?;
+            Some(valid_until.with_timezone(&Utc))
+        }
+    };
+
+    let mut tokens: Vec<ScopedToken> = Vec::new();
+    for raw in raw_tokens {
+        let name = raw
+            .name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| invalid("auth.tokens", "Every entry needs a `name`".into()))?;
+        if tokens.iter().any(|existing| existing.name == name) {
+            return Err(invalid(
+                "auth.tokens",
+                format!("Token name `{name}` is listed twice"),
+            ));
+        }
+        let secret = raw
+            .token
+            .filter(|secret| secret.trim().len() >= MIN_TOKEN_LEN)
+            .ok_or_else(|| {
+                invalid(
+                    "auth.tokens",
+                    format!("`{name}` needs a token of at least {MIN_TOKEN_LEN} characters"),
+                )
+            })?;
+        if token.as_deref() == Some(secret.as_str())
+            || previous_token.as_deref() == Some(secret.as_str())
+            || tokens.iter().any(|existing| existing.token == secret)
+        {
+            return Err(invalid(
+                "auth.tokens",
+                format!("`{name}` reuses a token that is already configured"),
+            ));
+        }
+        let mut scopes = Vec::new();
+        for scope in raw.scopes.unwrap_or_default() {
+            let scope = TokenScope::ALL
+                .into_iter()
+                .find(|known| known.as_str() == scope)
+                .ok_or_else(|| {
+                    invalid(
+                        "auth.tokens",
+                        format!(
+                            "`{name}` lists unknown scope `{scope}`; use build, fetch, sandbox, or admin"
+                        ),
+                    )
+                })?;
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+        if scopes.is_empty() {
+            return Err(invalid(
+                "auth.tokens",
+                format!("`{name}` needs at least one scope"),
+            ));
+        }
+        let allowed_paths = raw.allowed_paths.unwrap_or_default();
+        if let Some(relative) = allowed_paths.iter().find(|path| !path.is_absolute()) {
+            return Err(invalid(
+                "auth.tokens",
+                format!(
+                    "`{name}` allowed path `{}` must be absolute",
+                    relative.display()
+                ),
+            ));
+        }
+        tokens.push(ScopedToken {
+            name,
+            token: secret,
+            scopes,
+            allowed_paths,
+        });
+    }
+
+    Ok(AuthSection {
+        token,
+        previous_token,
+        previous_token_valid_until,
+        tokens,
+    })
+}
+
+/// `security` invocation that prints the password of the generic keychain item `service`.
+pub fn keychain_command(service: &str, account: Option<&str>) -> Vec<String> {
+    let mut argv = vec![
+        SECURITY_PATH.to_string(),
+        "find-generic-password".into(),
+        "-s".into(),
+        service.to_string(),
+    ];
+    if let Some(account) = account.filter(|account| !account.trim().is_empty()) {
+        argv.extend(["-a".into(), account.to_string()]);
+    }
+    argv.push("-w".into());
+    argv
+}
+
+/// Run `argv` and take its trimmed stdout as the token. Errors name the command and its stderr,
+/// never the output.
+pub fn read_token(argv: &[String]) -> Result<String, String> {
+    let (program, args) = argv.split_first().ok_or("The token command is empty")?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("Failed to run `{program}`: {error}"))?;
+    let read_all = |mut pipe: Box<dyn Read + Send>| {
+        thread::spawn(move || {
+            let mut bytes = Vec::new();
+            let _ = pipe.read_to_end(&mut bytes);
+            bytes
+        })
+    };
+    let stdout = read_all(Box::new(child.stdout.take().expect("stdout is piped")));
+    let stderr = read_all(Box::new(child.stderr.take().expect("stderr is piped")));
+    let deadline = Instant::now() + TOKEN_SOURCE_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "`{program}` did not finish within {}s",
+                    TOKEN_SOURCE_TIMEOUT.as_secs()
+                ));
+            }
+            Err(error) => return Err(format!("Failed to wait for `{program}`: {error}")),
+        }
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    if !status.success() {
+        return Err(format!(
+            "`{program}` exited with {status}: {}",
+            String::from_utf8_lossy(&stderr).trim()
+        ));
+    }
+    let token = String::from_utf8(stdout)
+        .map_err(|_| format!("`{program}` printed a token that is not UTF-8"))?
+        .trim()
+        .to_string();
+    if token.is_empty() {
+        return Err(format!("`{program}` printed no token"));
+    }
+    Ok(token)
 }
@@ -0,0 +1,88 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::lib::errors::ConfigError;
+
+/// `[federation]` configuration section: other seiro-mcp servers that builds are forwarded to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FederationConfig {
+    pub downstreams: Vec<Downstream>,
+}
+
+impl FederationConfig {
+    /// Whether builds go to downstreams instead of running here.
+    pub fn is_enabled(&self) -> bool {
+        !self.downstreams.is_empty()
+    }
+}
+
+/// One `[[federation.downstreams]]` entry: a seiro-mcp server spoken to over the stdio of
+/// `command`, usually `ssh <mac> seiro-mcp`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Downstream {
+    pub name: String,
+    pub command: PathBuf,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    /// Exported to `command` as `SEIRO_MCP_TOKEN`, for wrappers that authenticate to the host.
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RawFederationSection {
+    pub downstreams: Option<Vec<RawDownstream>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawDownstream {
+    pub name: Option<String>,
+    pub command: Option<String>,
+    pub args: Option<Vec<String>>,
+    pub env: Option<BTreeMap<String, String>>,
+    pub token: Option<String>,
+}
+
+pub fn parse_federation_section(
+    raw: Option<RawFederationSection>,
+    path: &Path,
+) -> Result<FederationConfig, ConfigError> {
+    let raw = raw.unwrap_or_default();
+    let mut names = BTreeSet::new();
+    let mut downstreams = Vec::new();
+    for entry in raw.downstreams.unwrap_or_default() {
+        let name =
+            entry
+                .name
+                .filter(|name| !name.trim().is_empty())
+                .ok_or(ConfigError::MissingField {
+                    path: path.to_path_buf(),
+                    field: "federation.downstreams.name",
+                })?;
+        if !names.insert(name.clone()) {
+            return Err(ConfigError::InvalidField {
+                path: path.to_path_buf(),
+                field: "federation.downstreams.name",
+                message: format!("Downstream `{name}` is listed more than once"),
+            });
+        }
+        let command = entry
+            .command
+            .filter(|command| !command.trim().is_empty())
+            .ok_or(ConfigError::MissingField {
+                path: path.to_path_buf(),
+                field: "federation.downstreams.command",
+            })?;
+        downstreams.push(Downstream {
+            name,
+            command: PathBuf::from(command),
+            args: entry.args.unwrap_or_default(),
+            env: entry.env.unwrap_or_default(),
+            token: entry.token.filter(|token| !token.trim().is_empty()),
+        });
+    }
+    Ok(FederationConfig { downstreams })
+}
@@ -0,0 +1,188 @@
+//! `include = [...]` files and `[profile.<name>]` overlays, merged into one TOML document
+//! before the sections are parsed.
+//!
+//! Merge order: each included file in list order, then the including file, then the selected
+//! profile. Tables merge key by key; any other value, arrays included, replaces the earlier one.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use toml::{Table, Value};
+
+use crate::lib::errors::ConfigError;
+
+const INCLUDE_KEY: &str = "include";
+const PROFILE_KEY: &str = "profile";
+/// Guards against deep include chains; cycles are reported separately.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Read `path` with its includes and apply `profile`, returning TOML without the `include` and
+/// `profile` keys.
+pub fn load_layered(path: &Path, profile: Option<&str>) -> Result<String, ConfigError> {
+    let mut document = load_with_includes(path, &mut Vec::new())?;
+    let profiles = document.remove(PROFILE_KEY);
+    if let Some(name) = profile {
+        let overlay = profiles
+            .as_ref()
+            .and_then(Value::as_table)
+            .and_then(|profiles| profiles.get(name))
+            .ok_or_else(|| ConfigError::InvalidField {
+                path: path.to_path_buf(),
+                field: "profile",
+                message: format!("Profile `{name}` is not defined in [profile.{name}]"),
+            })?;
+        let Value::Table(overlay) = overlay.clone() else {
+            return Err(ConfigError::InvalidField {
+                path: path.to_path_buf(),
+                field: "profile",
+                message: format!("[profile.{name}] must be a table"),
+            });
+        };
+        merge(&mut document, overlay);
+    }
+    toml::to_string(&document).map_err(|err| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "include",
+        message: format!("Failed to combine the config layers: {err}"),
+    })
+}
+
+/// `path` merged over its includes. `chain` holds the files being loaded, to report cycles.
+fn load_with_includes(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Table, ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidField {
+        path: chain.first().cloned().unwrap_or_else(|| path.to_path_buf()),
+        field: "include",
+        message,
+    };
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if chain.contains(&canonical) {
+        return Err(invalid(format!(
+            "{} includes itself through {}",
+            path.display(),
+            chain
+                .last()
+                .map(|file| file.display().to_string())
+                .unwrap_or_default()
+        )));
+    }
+    if chain.len() > MAX_INCLUDE_DEPTH {
+        return Err(invalid(format!(
+            "Includes are nested more than {MAX_INCLUDE_DEPTH} levels deep"
+        )));
+    }
+    // The top-level file keeps the read and parse errors of a config without includes.
+    let top_level = chain.is_empty();
+    let text = fs::read_to_string(path).map_err(|err| {
+        if top_level {
+            ConfigError::from_read_error(
+                path.to_path_buf(),
+                config::ConfigError::Foreign(err.into()),
+            )
+        } else {
+            invalid(format!("Failed to read {}: {err}", path.display()))
+        }
+    })?;
+    let mut document: Table = toml::from_str(&text).map_err(|err| {
+        if top_level {
+            ConfigError::from_parse_error(
+                path.to_path_buf(),
+                config::ConfigError::Foreign(err.into()),
+            )
+        } else {
+            invalid(format!("Failed to parse {}: {err}", path.display()))
+        }
+    })?;
+
+    let includes = match document.remove(INCLUDE_KEY) {
+        None => Vec::new(),
+        Some(Value::Array(entries)) => entries
+            .into_iter()
+            .map(|entry| match entry {
+                Value::String(include) => Ok(PathBuf::from(include)),
+                other => Err(invalid(format!(
+                    "include entries in {} must be strings, got `{other}`",
+                    path.display()
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        Some(other) => {
+            return Err(invalid(format!(
+                "include in {} must be an array of paths, got `{other}`",
+                path.display()
+            )))
+        }
+    };
+    if includes.is_empty() {
+        return Ok(document);
+    }
+
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    chain.push(canonical);
+    let mut merged = Table::new();
+    for include in includes {
+        let include = if include.is_absolute() {
+            include
+        } else {
+            base_dir.join(include)
+        };
+        merge(&mut merged, load_with_includes(&include, chain)?);
+    }
+    chain.pop();
+    merge(&mut merged, document);
+    Ok(merged)
+}
+
+fn merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_table)), Value::Table(overlay_table)) => {
+                merge(base_table, overlay_table)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_merge_and_other_values_replace() {
+        let mut base: Table = toml::from_str(
+            "[visionos]\nallowed_schemes = [\"A\", \"B\"]\nmax_build_minutes = 20\n",
+        )
+        .expect("base parses");
+        let overlay: Table =
+            toml::from_str("[visionos]\nallowed_schemes = [\"C\"]\n").expect("overlay parses");
+
+        merge(&mut base, overlay);
+
+        let visionos = base["visionos"].as_table().expect("table");
+        assert_eq!(visionos["allowed_schemes"], Value::Array(vec!["C".into()]));
+        assert_eq!(visionos["max_build_minutes"], Value::Integer(20));
+    }
+
+    #[test]
+    fn include_cycles_are_rejected() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(dir.path().join("a.toml"), "include = [\"b.toml\"]\n").expect("write a");
+        fs::write(dir.path().join("b.toml"), "include = [\"a.toml\"]\n").expect("write b");
+
+        let err = load_layered(&dir.path().join("a.toml"), None).expect_err("cycle");
+
+        assert!(
+            matches!(
+                err,
+                ConfigError::InvalidField {
+                    field: "include",
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+    }
+}
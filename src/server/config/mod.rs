@@ -6,19 +6,44 @@ use tracing::{error, info};
 
 use crate::lib::errors::ConfigError;
 
+pub mod artifacts;
 pub mod auth;
+pub mod federation;
+pub mod layers;
+pub mod notifications;
+pub mod redaction;
 pub mod server;
 pub mod telemetry;
 pub mod visionos;
 
+pub use artifacts::{
+    parse_artifacts_section, ArtifactStorageKind, ArtifactsConfig, RawArtifactsSection,
+};
+pub use auth::{
+    parse_auth_section, AuthSection, RawAuthSection, RawScopedToken, ScopedToken, TokenScope,
+};
+pub use federation::{
+    parse_federation_section, Downstream, FederationConfig, RawDownstream, RawFederationSection,
+};
+pub use notifications::{
+    parse_notifications_section, NotificationEvent, NotificationsConfig, RawNotificationsSection,
+    DEFAULT_NOTIFICATION_TIMEOUT_SECS,
+};
+pub use redaction::{parse_redaction_section, RawRedactionSection, RedactionConfig};
 pub use server::{
     parse_server_section, parse_tools_section, RawServerSection, RawToolsSection, ServerSection,
-    DEFAULT_HOST, DEFAULT_PORT,
+    ToolLimits, ToolsSection, DEFAULT_AUTH_FAILURE_LIMIT, DEFAULT_AUTH_LOCKOUT_SECS, DEFAULT_HOST,
+    DEFAULT_MAX_CONNECTIONS, DEFAULT_PORT, DEFAULT_SHUTDOWN_GRACE_SECS,
 };
 pub use visionos::{
-    parse_visionos_section, RawVisionOsConfig, VisionOsConfig, DEFAULT_ARTIFACT_TTL_SECS,
-    DEFAULT_CLEANUP_SCHEDULE_SECS, DEFAULT_MAX_BUILD_MINUTES, DEFAULT_VISIONOS_DESTINATION,
-    DEFAULT_XCODEBUILD_PATH,
+    parse_visionos_section, AllowedRoot, ArtifactPersistence, BuildPreset, CompilationCache,
+    CompilationCacheMode, PackageHost, Platform, PlatformDefaults, PolicyConfig, RawAllowedPath,
+    RawAllowedRoot, RawArtifactCompression, RawBuildPreset, RawCompilationCache,
+    RawPlatformDefaults, RawPolicyConfig, RawRetryPolicy, RawSigningConfig, RawSizeBudget,
+    RawVisionOsConfig, RetryPolicy, RetryReason, SchemePattern, SigningConfig, SizeBudget,
+    SizeBudgetEnforcement, SymlinkPolicy, VisionOsConfig, ZipCompression, ZipCompressionMethod,
+    DEFAULT_ARTIFACT_TTL_SECS, DEFAULT_CLEANUP_SCHEDULE_SECS, DEFAULT_MAX_BUILD_MINUTES,
+    DEFAULT_VISIONOS_DESTINATION, DEFAULT_XCODEBUILD_PATH, DEFAULT_XCRUN_PATH,
 };
 
 const CONFIG_ENV_KEY: &str = "MCP_CONFIG_PATH";
@@ -28,16 +53,28 @@ const DEFAULT_CONFIG_PATH: &str = "seiro-mcp.toml";
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub server: ServerSection,
+    pub auth: AuthSection,
     pub visionos: VisionOsConfig,
+    pub artifacts: ArtifactsConfig,
+    pub redaction: RedactionConfig,
+    pub tools: ToolsSection,
+    pub federation: FederationConfig,
+    pub notifications: NotificationsConfig,
     pub source_path: PathBuf,
+    /// `[profile.<name>]` applied on top of the file, from `--profile`.
+    pub profile: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct RawServerConfig {
     server: Option<RawServerSection>,
-    auth: Option<auth::RawAuthSection>,
+    auth: Option<RawAuthSection>,
     tools: Option<RawToolsSection>,
     visionos: Option<RawVisionOsConfig>,
+    artifacts: Option<RawArtifactsSection>,
+    redaction: Option<RawRedactionSection>,
+    federation: Option<RawFederationSection>,
+    notifications: Option<RawNotificationsSection>,
 }
 
 impl ServerConfig {
@@ -54,13 +91,29 @@ impl ServerConfig {
 
     /// Load configuration from a specific path.
     pub fn load_from_path(path: PathBuf) -> Result<Self, ConfigError> {
+        Self::load_with_profile(path, None)
+    }
+
+    /// Load `path` merged over its `include` files, with `[profile.<profile>]` applied on top.
+    pub fn load_with_profile(path: PathBuf, profile: Option<String>) -> Result<Self, ConfigError> {
         info!(
             target: "rmcp_sample::config",
             path = %path.display(),
+            profile = profile.as_deref().unwrap_or("-"),
             "Starting configuration load"
         );
 
-        let builder = config::Config::builder().add_source(config::File::from(path.clone()));
+        let layered = layers::load_layered(&path, profile.as_deref()).map_err(|error| {
+            error!(
+                target: "rmcp_sample::config",
+                path = %path.display(),
+                reason = %error,
+                "Failed to read configuration file"
+            );
+            error
+        })?;
+        let builder = config::Config::builder()
+            .add_source(config::File::from_str(&layered, config::FileFormat::Toml));
         let document = builder.build().map_err(|err| {
             let error = ConfigError::from_read_error(path.clone(), err);
             error!(
@@ -83,7 +136,7 @@ impl ServerConfig {
             error
         })?;
 
-        let config = Self::from_raw(raw, path.clone()).map_err(|err| {
+        let config = Self::from_raw(raw, path.clone(), profile).map_err(|err| {
             error!(
                 target: "rmcp_sample::config",
                 path = %path.display(),
@@ -97,16 +150,40 @@ impl ServerConfig {
         Ok(config)
     }
 
-    fn from_raw(raw: RawServerConfig, path: PathBuf) -> Result<Self, ConfigError> {
+    fn from_raw(
+        raw: RawServerConfig,
+        path: PathBuf,
+        profile: Option<String>,
+    ) -> Result<Self, ConfigError> {
         let server = parse_server_section(raw.server, &path)?;
-        let _auth = raw.auth;
-        parse_tools_section(raw.tools, &path)?;
+        let auth = parse_auth_section(raw.auth, &path)?;
+        let tools = parse_tools_section(raw.tools, &path)?;
         let visionos = parse_visionos_section(path.clone(), raw.visionos)?;
+        let artifacts = parse_artifacts_section(raw.artifacts, &path)?;
+        let redaction = parse_redaction_section(raw.redaction, &path)?;
+        let federation = parse_federation_section(raw.federation, &path)?;
+        let notifications = parse_notifications_section(raw.notifications, &path)?;
+        if visionos.artifact_persistence == ArtifactPersistence::Memory
+            && artifacts.backend != ArtifactStorageKind::Local
+        {
+            return Err(ConfigError::InvalidField {
+                path,
+                field: "visionos.artifact_persistence",
+                message: "`memory` cannot be combined with a remote [artifacts] backend".into(),
+            });
+        }
 
         Ok(Self {
             server,
+            auth,
             visionos,
+            artifacts,
+            redaction,
+            tools,
+            federation,
+            notifications,
             source_path: path,
+            profile,
         })
     }
 }
@@ -120,7 +197,12 @@ mod tests {
 
     use crate::lib::errors::ConfigError;
 
-    use super::ServerConfig;
+    use super::{
+        auth::keychain_command, AllowedRoot, ArtifactPersistence, BuildPreset, NotificationEvent,
+        Platform, RedactionConfig, RetryPolicy, RetryReason, ServerConfig, SigningConfig,
+        SizeBudget, SizeBudgetEnforcement, SymlinkPolicy, TokenScope, ToolLimits, ZipCompression,
+        ZipCompressionMethod,
+    };
 
     fn fixture_path(name: &str) -> PathBuf {
         PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -146,6 +228,11 @@ mod tests {
 
         assert_eq!(config.server.host, "127.0.0.1");
         assert_eq!(config.server.port, 8787);
+        assert_eq!(config.server.shutdown_grace_secs, 30);
+        assert_eq!(config.server.rate_limit_per_minute, 0);
+        assert_eq!(config.server.max_concurrent_calls, 0);
+        assert_eq!(config.server.auth_failure_limit, 5);
+        assert_eq!(config.server.auth_lockout_secs, 60);
         assert_eq!(
             config.visionos.allowed_paths,
             vec![PathBuf::from("/Users/example/codex/workspaces")]
@@ -174,8 +261,44 @@ mod tests {
             PathBuf::from("/usr/bin/xcodebuild")
         );
         assert_eq!(config.visionos.max_build_minutes, 20);
+        assert_eq!(config.visionos.timeout_warning_percent, 80);
+        assert_eq!(config.visionos.heartbeat_interval_secs, 15);
+        assert!(config.visionos.allowed_device_udids.is_empty());
+        assert_eq!(
+            config.visionos.artifact_persistence,
+            ArtifactPersistence::Disk
+        );
+        assert_eq!(config.visionos.max_extended_build_minutes, 0);
+        assert_eq!(config.visionos.max_concurrent_builds, 1);
+        assert_eq!(
+            config.visionos.max_timeout_minutes,
+            config.visionos.max_build_minutes
+        );
+        assert!(config.visionos.size_budgets.is_empty());
+        assert!(config.visionos.presets.is_empty());
+        assert!(config.visionos.xcodes.is_empty());
+        assert_eq!(config.visionos.retry.max_attempts, 1);
+        assert_eq!(
+            config.visionos.artifact_compression,
+            ZipCompression::default()
+        );
+        assert_eq!(config.visionos.artifact_symlinks, SymlinkPolicy::Preserve);
+        assert!(!config.visionos.build_cache);
+        assert!(!config.visionos.deterministic_artifacts);
+        assert!(config.visionos.compilation_cache.is_none());
+        assert!(config.visionos.platforms.is_empty());
+        assert!(config.visionos.allowed_roots.is_empty());
+        assert_eq!(config.visionos.locale, "en_US.UTF-8");
+        assert_eq!(config.visionos.signing, SigningConfig::default());
         assert_eq!(config.visionos.artifact_ttl_secs, 600);
         assert_eq!(config.visionos.cleanup_schedule_secs, 60);
+        assert_eq!(config.visionos.artifact_max_jobs, 0);
+        assert_eq!(config.visionos.artifact_max_total_bytes, 0);
+        assert_eq!(config.visionos.min_free_disk_bytes, 20 * 1024 * 1024 * 1024);
+        assert_eq!(config.visionos.sandbox_probe_ttl_secs, 300);
+        assert!(!config.visionos.allow_remediation);
+        assert!(config.visionos.package_hosts.is_empty());
+        assert_eq!(config.redaction, RedactionConfig::default());
     }
 
     #[test]
@@ -184,6 +307,7 @@ mod tests {
             .expect("auth.token should no longer be required");
 
         assert_eq!(config.server.host, "127.0.0.1");
+        assert!(config.auth.token.is_none());
         assert!(config
             .visionos
             .allowed_schemes
@@ -191,6 +315,109 @@ mod tests {
             .any(|s| s == "VisionApp"));
     }
 
+    #[test]
+    fn scoped_tokens_load_with_their_scopes_and_paths() {
+        let config = ServerConfig::load_from_path(fixture_path("config_auth_tokens.toml"))
+            .expect("config with [[auth.tokens]] should load");
+
+        assert!(config.auth.has_tokens());
+        assert_eq!(config.auth.tokens.len(), 2);
+        let ci = &config.auth.tokens[0];
+        assert_eq!(ci.name, "ci");
+        assert_eq!(ci.scopes, [TokenScope::Build, TokenScope::Fetch]);
+        assert_eq!(
+            ci.allowed_paths,
+            [PathBuf::from("/Users/example/codex/workspaces/VisionApp")]
+        );
+        assert!(config.auth.tokens[1].allowed_paths.is_empty());
+        assert_eq!(
+            config.auth.previous_token.as_deref(),
+            Some("old-full-access-token")
+        );
+        assert_eq!(
+            config
+                .auth
+                .previous_token_valid_until
+                .map(|valid_until| valid_until.to_rfc3339()),
+            Some("2026-11-01T00:00:00+00:00".to_string())
+        );
+
+        let error = ServerConfig::load_from_path(fixture_path("config_invalid_auth_tokens.toml"))
+            .expect_err("unknown scopes are rejected");
+        match error {
+            ConfigError::InvalidField { field, message, .. } => {
+                assert_eq!(field, "auth.tokens");
+                assert!(message.contains("deploy"));
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn token_command_supplies_the_token() {
+        let config = ServerConfig::load_from_path(fixture_path("config_token_command.toml"))
+            .expect("token_command should supply auth.token");
+        assert_eq!(config.auth.token.as_deref(), Some("command-token-000001"));
+
+        let error = ServerConfig::load_from_path(fixture_path("config_failing_token_command.toml"))
+            .expect_err("a failing token_command is rejected");
+        match error {
+            ConfigError::InvalidField { field, message, .. } => {
+                assert_eq!(field, "auth.token_source");
+                assert!(message.contains("locked"), "{message}");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+
+        assert_eq!(
+            keychain_command("seiro-mcp", Some("ci")),
+            [
+                "/usr/bin/security",
+                "find-generic-password",
+                "-s",
+                "seiro-mcp",
+                "-a",
+                "ci",
+                "-w"
+            ]
+        );
+        assert_eq!(
+            keychain_command("seiro-mcp", None),
+            [
+                "/usr/bin/security",
+                "find-generic-password",
+                "-s",
+                "seiro-mcp",
+                "-w"
+            ]
+        );
+    }
+
+    #[test]
+    fn previous_token_without_expiry_returns_error() {
+        let error =
+            ServerConfig::load_from_path(fixture_path("config_invalid_previous_token.toml"))
+                .expect_err("auth.previous_token needs previous_token_valid_until");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "auth.previous_token_valid_until")
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn short_token_returns_error() {
+        let error = ServerConfig::load_from_path(fixture_path("config_short_token.toml"))
+            .expect_err("should error for a token under 16 characters");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => assert_eq!(field, "auth.token"),
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
     #[test]
     fn invalid_port_returns_error() {
         let error = ServerConfig::load_from_path(fixture_path("config_invalid_port.toml"))
@@ -243,6 +470,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn visionos_allowed_path_patterns_are_validated() {
+        let config =
+            ServerConfig::load_from_path(fixture_path("config_allowed_path_patterns.toml"))
+                .expect("glob patterns should load");
+        assert_eq!(
+            config.visionos.allowed_paths,
+            vec![PathBuf::from("/Users/*/workspaces/**")]
+        );
+
+        let error =
+            ServerConfig::load_from_path(fixture_path("config_invalid_allowed_path_pattern.toml"))
+                .expect_err("should error on an unparsable pattern");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => assert_eq!(field, "visionos.allowed_paths"),
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn invalid_scheme_pattern_returns_error() {
+        let error =
+            ServerConfig::load_from_path(fixture_path("config_invalid_scheme_pattern.toml"))
+                .expect_err("should error on a scheme regex that does not compile");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.allowed_schemes")
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
     #[test]
     fn missing_allowed_schemes_returns_error() {
         let error =
@@ -300,6 +561,20 @@ mod tests {
         assert!(!config.visionos.allowed_paths.is_empty());
     }
 
+    #[test]
+    fn artifacts_section_selects_object_storage_backend() {
+        let config = ServerConfig::load_from_path(fixture_path("config_artifacts_s3.toml"))
+            .expect("config_artifacts_s3.toml should load");
+
+        assert_eq!(config.artifacts.backend, super::ArtifactStorageKind::S3);
+        assert_eq!(config.artifacts.bucket.as_deref(), Some("visionos-ci"));
+        assert_eq!(config.artifacts.prefix, "nightly");
+        assert_eq!(
+            config.artifacts.cli_path,
+            PathBuf::from(super::artifacts::DEFAULT_AWS_CLI_PATH)
+        );
+    }
+
     #[test]
     fn relative_default_project_path_returns_error() {
         let error =
@@ -313,4 +588,590 @@ mod tests {
             other => panic!("Unexpected error: {other:?}", other = other),
         }
     }
+
+    #[test]
+    fn extended_build_minutes_below_max_build_minutes_returns_error() {
+        let error = ServerConfig::load_from_path(fixture_path(
+            "config_invalid_extended_build_minutes.toml",
+        ))
+        .expect_err("should error when the extension cap is below max_build_minutes");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.max_extended_build_minutes")
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn timeout_ceiling_below_max_build_minutes_returns_error() {
+        let error =
+            ServerConfig::load_from_path(fixture_path("config_invalid_timeout_minutes.toml"))
+                .expect_err("should error when the timeout ceiling is below max_build_minutes");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.max_timeout_minutes")
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn size_budgets_are_parsed_per_scheme() {
+        let config = ServerConfig::load_from_path(fixture_path("config_size_budgets.toml"))
+            .expect("config with size budgets should load");
+
+        assert_eq!(
+            config.visionos.size_budgets.get("VisionApp"),
+            Some(&SizeBudget {
+                max_app_bytes: Some(200_000_000),
+                max_binary_bytes: Some(50_000_000),
+                enforcement: SizeBudgetEnforcement::Fail,
+            })
+        );
+    }
+
+    #[test]
+    fn artifact_compression_can_store_entries() {
+        let config = ServerConfig::load_from_path(fixture_path("config_artifact_compression.toml"))
+            .expect("config with artifact compression should load");
+
+        assert_eq!(
+            config.visionos.artifact_compression.method,
+            ZipCompressionMethod::Store
+        );
+    }
+
+    #[test]
+    fn artifact_compression_zstd_defaults_to_level_3() {
+        let config =
+            ServerConfig::load_from_path(fixture_path("config_artifact_compression_zstd.toml"))
+                .expect("config with zstd compression should load");
+
+        assert_eq!(
+            config.visionos.artifact_compression,
+            ZipCompression {
+                method: ZipCompressionMethod::Zstd,
+                level: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn artifact_compression_level_out_of_range_returns_error() {
+        let error =
+            ServerConfig::load_from_path(fixture_path("config_invalid_artifact_compression.toml"))
+                .expect_err("should error on compression level above 9");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.artifact_compression")
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn artifact_symlinks_unknown_policy_returns_error() {
+        let error =
+            ServerConfig::load_from_path(fixture_path("config_invalid_artifact_symlinks.toml"))
+                .expect_err("should error on unknown symlink policy");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.artifact_symlinks")
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn relative_derived_data_root_returns_error() {
+        let error =
+            ServerConfig::load_from_path(fixture_path("config_invalid_derived_data_root.toml"))
+                .expect_err("should error on relative derived_data_root");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.derived_data_root")
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn relative_compilation_cache_wrapper_returns_error() {
+        let error =
+            ServerConfig::load_from_path(fixture_path("config_invalid_compilation_cache.toml"))
+                .expect_err("should error on a relative compiler_wrapper");
+
+        match error {
+            ConfigError::InvalidField { field, message, .. } => {
+                assert_eq!(field, "visionos.compilation_cache");
+                assert!(message.contains("compiler_wrapper"), "{message}");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn platform_tables_override_builtin_defaults() {
+        let config = ServerConfig::load_from_path(fixture_path("config_platforms.toml"))
+            .expect("config_platforms.toml should load");
+
+        let ios = config.visionos.platform_defaults(Platform::Ios);
+        assert_eq!(
+            ios.default_destination,
+            "platform=iOS Simulator,name=iPhone 15 Pro"
+        );
+        assert_eq!(ios.required_sdks, vec!["iphoneos", "iphonesimulator"]);
+        let macos = config.visionos.platform_defaults(Platform::MacOs);
+        assert_eq!(macos.default_destination, "platform=macOS");
+        assert_eq!(macos.required_sdks, vec!["macosx"]);
+        let tvos = config.visionos.platform_defaults(Platform::TvOs);
+        assert_eq!(tvos.required_sdks, vec!["appletvos", "appletvsimulator"]);
+        let visionos = config.visionos.platform_defaults(Platform::VisionOs);
+        assert_eq!(
+            visionos.default_destination,
+            config.visionos.default_destination
+        );
+    }
+
+    #[test]
+    fn unknown_platform_table_returns_error() {
+        let error = ServerConfig::load_from_path(fixture_path("config_invalid_platform.toml"))
+            .expect_err("should error on an unknown platform");
+
+        match error {
+            ConfigError::InvalidField { field, message, .. } => {
+                assert_eq!(field, "visionos.platforms");
+                assert!(message.contains("android"), "{message}");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn allowed_paths_accept_labeled_root_tables() {
+        let config = ServerConfig::load_from_path(fixture_path("config_allowed_roots.toml"))
+            .expect("config_allowed_roots.toml should load");
+
+        assert_eq!(
+            config.visionos.allowed_paths,
+            vec![
+                PathBuf::from("/Users/example/scratch"),
+                PathBuf::from("/Users/example/codex/workspaces"),
+                PathBuf::from("/Volumes/ci/checkouts"),
+            ]
+        );
+        assert_eq!(
+            config.visionos.allowed_roots,
+            vec![
+                AllowedRoot {
+                    path: PathBuf::from("/Users/example/codex/workspaces"),
+                    label: "personal".into(),
+                    max_concurrent: None,
+                    artifact_ttl_secs: None,
+                },
+                AllowedRoot {
+                    path: PathBuf::from("/Volumes/ci/checkouts"),
+                    label: "ci".into(),
+                    max_concurrent: Some(1),
+                    artifact_ttl_secs: Some(120),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn allowed_root_limit_above_global_limit_returns_error() {
+        let error = ServerConfig::load_from_path(fixture_path("config_invalid_allowed_root.toml"))
+            .expect_err("should error when a root allows more builds than the server");
+
+        match error {
+            ConfigError::InvalidField { field, message, .. } => {
+                assert_eq!(field, "visionos.allowed_paths");
+                assert!(message.contains("`ci`"), "{message}");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn invalid_locale_returns_error() {
+        let error = ServerConfig::load_from_path(fixture_path("config_invalid_locale.toml"))
+            .expect_err("should error when locale is not a locale name");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.locale");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn heartbeat_interval_above_limit_returns_error() {
+        let error =
+            ServerConfig::load_from_path(fixture_path("config_invalid_heartbeat_interval.toml"))
+                .expect_err("should error when heartbeat_interval_secs exceeds 300");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.heartbeat_interval_secs");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn device_allowlist_rejects_names() {
+        let error = ServerConfig::load_from_path(fixture_path("config_invalid_device_udids.toml"))
+            .expect_err("should error when allowed_device_udids holds a device name");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.allowed_device_udids");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn memory_persistence_rejects_on_disk_caches() {
+        let error =
+            ServerConfig::load_from_path(fixture_path("config_invalid_artifact_persistence.toml"))
+                .expect_err("should error when memory persistence keeps DerivedData on disk");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.artifact_persistence");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn presets_are_parsed_by_name() {
+        let config = ServerConfig::load_from_path(fixture_path("config_presets.toml"))
+            .expect("config with presets should load");
+
+        assert_eq!(
+            config.visionos.presets.get("release-sim"),
+            Some(&BuildPreset {
+                scheme: Some("VisionApp".into()),
+                configuration: Some("release".into()),
+                destination: Some("platform=visionOS Simulator,name=Apple Vision Pro".into()),
+                extra_args: vec!["-quiet".into()],
+            })
+        );
+        assert_eq!(
+            config
+                .visionos
+                .presets
+                .get("quiet")
+                .map(|preset| &preset.scheme),
+            Some(&None)
+        );
+    }
+
+    #[test]
+    fn preset_with_unknown_configuration_returns_error() {
+        let error = ServerConfig::load_from_path(fixture_path("config_invalid_presets.toml"))
+            .expect_err("should error when a preset names an unknown configuration");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.presets");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn xcodes_are_parsed_by_name() {
+        let config = ServerConfig::load_from_path(fixture_path("config_xcodes.toml"))
+            .expect("config with xcodes should load");
+
+        assert_eq!(
+            config.visionos.xcodes.keys().collect::<Vec<_>>(),
+            vec!["16.4", "beta"]
+        );
+        assert_eq!(
+            config.visionos.xcodes.get("beta"),
+            Some(&PathBuf::from(
+                "/Applications/Xcode-beta.app/Contents/Developer"
+            ))
+        );
+    }
+
+    #[test]
+    fn xcode_with_relative_developer_dir_returns_error() {
+        let error = ServerConfig::load_from_path(fixture_path("config_invalid_xcodes.toml"))
+            .expect_err("should error when developer_dir is relative");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.xcodes");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn retry_policy_is_parsed() {
+        let config = ServerConfig::load_from_path(fixture_path("config_retry.toml"))
+            .expect("config with a retry policy should load");
+
+        assert_eq!(
+            config.visionos.retry,
+            RetryPolicy {
+                max_attempts: 3,
+                backoff: std::time::Duration::from_secs(5),
+                retry_on: [RetryReason::SimulatorBoot, RetryReason::BuildService].into(),
+            }
+        );
+    }
+
+    #[test]
+    fn retry_on_unknown_reason_returns_error() {
+        let error = ServerConfig::load_from_path(fixture_path("config_invalid_retry.toml"))
+            .expect_err("should error when retry_on names an unknown reason");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "visionos.retry");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn includes_merge_under_the_including_file() {
+        let config = ServerConfig::load_from_path(fixture_path("config_layered.toml"))
+            .expect("layered config should load");
+
+        assert_eq!(config.visionos.max_build_minutes, 30);
+        assert_eq!(
+            config.visionos.allowed_schemes,
+            vec!["VisionApp", "VisionToolbox"]
+        );
+        assert_eq!(config.server.port, 8787);
+        assert_eq!(config.profile, None);
+    }
+
+    #[test]
+    fn selected_profile_overlays_the_merged_file() {
+        let config =
+            ServerConfig::load_with_profile(fixture_path("config_layered.toml"), Some("ci".into()))
+                .expect("ci profile should load");
+
+        assert_eq!(config.visionos.max_build_minutes, 45);
+        assert_eq!(config.visionos.allowed_schemes, vec!["VisionApp"]);
+        assert_eq!(config.server.port, 8787);
+        assert_eq!(config.profile.as_deref(), Some("ci"));
+    }
+
+    #[test]
+    fn unknown_profile_returns_error() {
+        let error = ServerConfig::load_with_profile(
+            fixture_path("config_layered.toml"),
+            Some("staging".into()),
+        )
+        .expect_err("should error when the profile is not defined");
+
+        match error {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "profile");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn redaction_section_adds_keys() {
+        let config = ServerConfig::load_from_path(fixture_path("config_redaction.toml"))
+            .expect("config with [redaction] should load");
+
+        assert_eq!(
+            config.redaction,
+            RedactionConfig {
+                keys: vec!["signing_identity".into()],
+                home_paths: true,
+            }
+        );
+    }
+
+    #[test]
+    fn tools_section_limits_registered_tools() {
+        let config = ServerConfig::load_from_path(fixture_path("config_tools.toml"))
+            .expect("config with [tools] should load");
+
+        assert!(config.tools.allows("fetch_build_output"));
+        assert!(!config.tools.allows("validate_sandbox_policy"));
+        assert!(!config.tools.allows("build_visionos_app"));
+        assert_eq!(
+            config.tools.limits("fetch_build_output"),
+            ToolLimits {
+                timeout_secs: 120,
+                max_concurrent: 2,
+            }
+        );
+        assert_eq!(
+            config.tools.limits("list_build_jobs"),
+            ToolLimits::default()
+        );
+        assert!(
+            ServerConfig::load_from_path(fixture_path("config_valid.toml"))
+                .expect("config_valid.toml should load")
+                .tools
+                .allows("build_visionos_app")
+        );
+    }
+
+    #[test]
+    fn empty_tools_enabled_returns_error() {
+        let err = ServerConfig::load_from_path(fixture_path("config_invalid_tools.toml"))
+            .expect_err("empty tools.enabled should be rejected");
+        match err {
+            ConfigError::InvalidField { field, .. } => assert_eq!(field, "tools.enabled"),
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn signing_table_sets_defaults_and_profile_allowlist() {
+        let config = ServerConfig::load_from_path(fixture_path("config_signing.toml"))
+            .expect("config_signing.toml should load");
+
+        assert_eq!(
+            config.visionos.signing,
+            SigningConfig {
+                team_id: Some("A1B2C3D4E5".into()),
+                identity: Some("Apple Development".into()),
+                allowed_provisioning_profiles: vec![
+                    "VisionApp Development".into(),
+                    "2f6a1c3e-8b7d-4e2a-9c1f-5d3b7a9e0c42".into(),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_signing_team_id_returns_error() {
+        let error = ServerConfig::load_from_path(fixture_path("config_invalid_signing.toml"))
+            .expect_err("should error when team_id is not a team ID");
+
+        match error {
+            ConfigError::InvalidField { field, message, .. } => {
+                assert_eq!(field, "visionos.signing");
+                assert!(message.contains("abc123"), "{message}");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn package_host_with_scheme_returns_error() {
+        let error = ServerConfig::load_from_path(fixture_path("config_invalid_package_host.toml"))
+            .expect_err("should error when a package host is a URL");
+
+        match error {
+            ConfigError::InvalidField { field, message, .. } => {
+                assert_eq!(field, "visionos.package_hosts");
+                assert!(message.contains("https://github.com"), "{message}");
+            }
+            other => panic!("Unexpected error: {other:?}", other = other),
+        }
+    }
+
+    #[test]
+    fn policy_table_disables_checks_and_sets_thresholds() {
+        let config = ServerConfig::load_from_path(fixture_path("config_policy.toml"))
+            .expect("policy table should load");
+
+        let policy = &config.visionos.policy;
+        assert!(!policy.allows("signing"));
+        assert!(policy.allows("disk_space"));
+        assert_eq!(policy.min_free_disk_bytes, Some(5 * 1024 * 1024 * 1024));
+        assert_eq!(policy.unknown_names(&["signing"]), vec!["package_hosts"]);
+    }
+
+    #[test]
+    fn federation_downstreams_are_parsed_in_order() {
+        let config = ServerConfig::load_from_path(fixture_path("config_federation.toml"))
+            .expect("federation section should load");
+
+        let downstreams = &config.federation.downstreams;
+        assert!(config.federation.is_enabled());
+        assert_eq!(downstreams.len(), 2);
+        assert_eq!(downstreams[0].name, "mac-studio");
+        assert_eq!(downstreams[0].command, PathBuf::from("ssh"));
+        assert_eq!(
+            downstreams[0].args,
+            ["builder@mac-studio.local", "seiro-mcp"]
+        );
+        assert_eq!(downstreams[0].token.as_deref(), Some("downstream-token-1"));
+        assert_eq!(
+            downstreams[1]
+                .env
+                .get("MCP_CONFIG_PATH")
+                .map(String::as_str),
+            Some("/etc/seiro-mcp/mini.toml")
+        );
+        assert!(downstreams[1].token.is_none());
+    }
+
+    #[test]
+    fn duplicate_downstream_name_returns_error() {
+        let err = ServerConfig::load_from_path(fixture_path("config_invalid_federation.toml"))
+            .expect_err("duplicate downstream names must be rejected");
+        match err {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "federation.downstreams.name")
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn notifications_section_lists_webhooks_and_events() {
+        let config = ServerConfig::load_from_path(fixture_path("config_notifications.toml"))
+            .expect("notifications section should load");
+
+        let notifications = &config.notifications;
+        assert_eq!(notifications.webhooks.len(), 2);
+        assert_eq!(
+            notifications.secret.as_deref(),
+            Some("webhook-signing-secret")
+        );
+        assert_eq!(
+            notifications.events,
+            [NotificationEvent::Failed, NotificationEvent::TimedOut]
+        );
+        assert!(notifications.wants(NotificationEvent::TimedOut));
+        assert!(!notifications.wants(NotificationEvent::Succeeded));
+        assert_eq!(notifications.curl_path, PathBuf::from("/usr/bin/curl"));
+        assert_eq!(notifications.timeout_secs, 5);
+
+        let defaults = ServerConfig::load_from_path(fixture_path("config_valid.toml"))
+            .expect("valid config should load")
+            .notifications;
+        assert!(defaults.webhooks.is_empty());
+        assert!(!defaults.wants(NotificationEvent::Failed));
+    }
+
+    #[test]
+    fn notification_webhook_without_scheme_returns_error() {
+        let err = ServerConfig::load_from_path(fixture_path("config_invalid_notifications.toml"))
+            .expect_err("webhooks must be http(s) URLs");
+        match err {
+            ConfigError::InvalidField { field, .. } => {
+                assert_eq!(field, "notifications.webhooks")
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
 }
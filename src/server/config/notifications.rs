@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::lib::errors::ConfigError;
+
+/// Default `notifications.timeout_secs`.
+pub const DEFAULT_NOTIFICATION_TIMEOUT_SECS: u16 = 10;
+const MAX_NOTIFICATION_TIMEOUT_SECS: u16 = 300;
+
+/// `[notifications]` configuration section: webhooks POSTed to when a build job ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationsConfig {
+    pub webhooks: Vec<String>,
+    /// Signs each body with HMAC-SHA256, sent as `X-Seiro-Signature: sha256=<hex>`.
+    pub secret: Option<String>,
+    /// Job outcomes that are posted; every outcome when the key is unset.
+    pub events: Vec<NotificationEvent>,
+    /// `curl` binary used for the POST.
+    pub curl_path: PathBuf,
+    pub timeout_secs: u16,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            webhooks: Vec::new(),
+            secret: None,
+            events: NotificationEvent::ALL.to_vec(),
+            curl_path: PathBuf::from("/usr/bin/curl"),
+            timeout_secs: DEFAULT_NOTIFICATION_TIMEOUT_SECS,
+        }
+    }
+}
+
+impl NotificationsConfig {
+    /// Whether jobs ending with `event` are posted anywhere.
+    pub fn wants(&self, event: NotificationEvent) -> bool {
+        !self.webhooks.is_empty() && self.events.contains(&event)
+    }
+}
+
+/// How a build job ended, as named in `notifications.events` and in the webhook body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    Succeeded,
+    Failed,
+    TimedOut,
+    Cancelled,
+}
+
+impl NotificationEvent {
+    pub const ALL: [Self; 4] = [
+        Self::Succeeded,
+        Self::Failed,
+        Self::TimedOut,
+        Self::Cancelled,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::TimedOut => "timed_out",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RawNotificationsSection {
+    pub webhooks: Option<Vec<String>>,
+    pub secret: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub curl_path: Option<String>,
+    pub timeout_secs: Option<u16>,
+}
+
+pub fn parse_notifications_section(
+    raw: Option<RawNotificationsSection>,
+    path: &Path,
+) -> Result<NotificationsConfig, ConfigError> {
+    let raw = raw.unwrap_or_default();
+    let defaults = NotificationsConfig::default();
+    let invalid = |field: &'static str, message: String| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field,
+        message,
+    };
+
+    let webhooks = raw.webhooks.unwrap_or_default();
+    if let Some(url) = webhooks
+        .iter()
+        .find(|url| !(url.starts_with("https://") || url.starts_with("http://")))
+    {
+        return Err(invalid(
+            "notifications.webhooks",
+            format!("`{url}` is not an http:// or https:// URL"),
+        ));
+    }
+
+    let events = match raw.events {
+        None => defaults.events,
+        Some(names) => {
+            let mut events = Vec::new();
+            for name in names {
+                let event = NotificationEvent::ALL
+                    .into_iter()
+                    .find(|event| event.as_str() == name)
+                    .ok_or_else(|| {
+                        invalid(
+                            "notifications.events",
+                            format!(
+                                "Unknown event `{name}`; use succeeded, failed, timed_out, or cancelled"
+                            ),
+                        )
+                    })?;
+                if !events.contains(&event) {
+                    events.push(event);
+                }
+            }
+            events
+        }
+    };
+
+    let timeout_secs = raw.timeout_secs.unwrap_or(defaults.timeout_secs);
+    if !(1..=MAX_NOTIFICATION_TIMEOUT_SECS).contains(&timeout_secs) {
+        return Err(invalid(
+            "notifications.timeout_secs",
+            format!("Set a value between 1 and {MAX_NOTIFICATION_TIMEOUT_SECS}"),
+        ));
+    }
+
+    Ok(NotificationsConfig {
+        webhooks,
+        secret: raw.secret.filter(|secret| !secret.trim().is_empty()),
+        events,
+        curl_path: raw
+            .curl_path
+            .filter(|curl_path| !curl_path.trim().is_empty())
+            .map_or(defaults.curl_path, PathBuf::from),
+        timeout_secs,
+    })
+}
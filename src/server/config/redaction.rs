@@ -0,0 +1,48 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::lib::{errors::ConfigError, telemetry::Redaction};
+
+/// `[redaction]` configuration section.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RedactionConfig {
+    /// Key fragments masked in addition to the built-in sensitive keys.
+    pub keys: Vec<String>,
+    /// Replace the home directory with `~` in logs and error data.
+    pub home_paths: bool,
+}
+
+impl RedactionConfig {
+    /// Policy to install with [`crate::lib::telemetry::set_redaction`].
+    pub fn policy(&self) -> Redaction {
+        Redaction::new(&self.keys, self.home_paths)
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RawRedactionSection {
+    pub keys: Option<Vec<String>>,
+    pub home_paths: Option<bool>,
+}
+
+pub fn parse_redaction_section(
+    raw: Option<RawRedactionSection>,
+    path: &Path,
+) -> Result<RedactionConfig, ConfigError> {
+    let raw = raw.unwrap_or_default();
+    let keys = raw.keys.unwrap_or_default();
+    for key in &keys {
+        if key.trim().is_empty() {
+            return Err(ConfigError::InvalidField {
+                path: path.to_path_buf(),
+                field: "redaction.keys",
+                message: "Key names cannot be empty".into(),
+            });
+        }
+    }
+    Ok(RedactionConfig {
+        keys,
+        home_paths: raw.home_paths.unwrap_or(false),
+    })
+}
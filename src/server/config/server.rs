@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::{collections::BTreeMap, path::Path};
 
 use serde::Deserialize;
 
@@ -6,22 +6,117 @@ use crate::lib::errors::ConfigError;
 
 pub const DEFAULT_HOST: &str = "127.0.0.1";
 pub const DEFAULT_PORT: u16 = 8787;
+pub const DEFAULT_SHUTDOWN_GRACE_SECS: u32 = 30;
+pub const DEFAULT_MAX_CONNECTIONS: u16 = 16;
+pub const DEFAULT_AUTH_FAILURE_LIMIT: u16 = 5;
+pub const DEFAULT_AUTH_LOCKOUT_SECS: u32 = 60;
 
 /// Server socket settings.
 #[derive(Debug, Clone)]
 pub struct ServerSection {
     pub host: String,
     pub port: u16,
+    /// How long SIGINT/SIGTERM waits for queued and running jobs before exiting.
+    pub shutdown_grace_secs: u32,
+    /// Tool calls one client may start per minute; 0 disables the limit.
+    pub rate_limit_per_minute: u32,
+    /// Tool calls one client may have running at once; 0 disables the limit.
+    pub max_concurrent_calls: u16,
+    /// Let `--transport websocket` listen on a `host` other than loopback.
+    pub allow_external_bind: bool,
+    /// WebSocket sessions open at once; 0 disables the limit.
+    pub max_connections: u16,
+    /// Seconds a WebSocket session may go without a message or running tool call before it is
+    /// closed; 0 keeps idle sessions open.
+    pub idle_timeout_secs: u32,
+    /// Consecutive failed WebSocket upgrades from one address before it is locked out; 0
+    /// disables the lockout.
+    pub auth_failure_limit: u16,
+    /// How long a locked-out address is refused, even with the right token.
+    pub auth_lockout_secs: u32,
 }
 
 #[derive(Debug, Deserialize, Default)]
 pub struct RawServerSection {
     pub host: Option<String>,
     pub port: Option<u16>,
+    pub shutdown_grace_secs: Option<u32>,
+    pub rate_limit_per_minute: Option<u32>,
+    pub max_concurrent_calls: Option<u16>,
+    pub allow_external_bind: Option<bool>,
+    pub max_connections: Option<u16>,
+    pub idle_timeout_secs: Option<u32>,
+    pub auth_failure_limit: Option<u16>,
+    pub auth_lockout_secs: Option<u32>,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct RawToolsSection {}
+/// Longest `timeout_secs` a `[tools.<name>]` table accepts.
+pub const MAX_TOOL_TIMEOUT_SECS: u32 = 86_400;
+
+/// `[tools]`: which tools the server registers, and per-tool limits.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolsSection {
+    /// Only these tools are registered; every tool when `None`.
+    pub enabled: Option<Vec<String>>,
+    /// Tools left out even when `enabled` lists them.
+    pub disabled: Vec<String>,
+    /// Register admin tools such as `purge_artifacts`.
+    pub admin: bool,
+    /// `[tools.<name>]` tables, keyed by tool name.
+    pub limits: BTreeMap<String, ToolLimits>,
+}
+
+/// `[tools.<name>]`: limits on calls to one tool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ToolLimits {
+    /// Seconds a call may run before it fails with `tool_timeout`; 0 disables the limit.
+    pub timeout_secs: u32,
+    /// Calls to the tool one client may have running at once; 0 disables the limit.
+    pub max_concurrent: u16,
+}
+
+impl ToolsSection {
+    /// Whether the tool called `name` is registered.
+    pub fn allows(&self, name: &str) -> bool {
+        let enabled = self
+            .enabled
+            .as_ref()
+            .is_none_or(|enabled| enabled.iter().any(|tool| tool == name));
+        enabled && !self.disabled.iter().any(|tool| tool == name)
+    }
+
+    /// Limits for the tool called `name`; no limits when it has no table.
+    pub fn limits(&self, name: &str) -> ToolLimits {
+        self.limits.get(name).copied().unwrap_or_default()
+    }
+
+    /// Configured names that are not in `known`.
+    pub fn unknown_names<'a>(&'a self, known: &[&str]) -> Vec<&'a str> {
+        self.enabled
+            .iter()
+            .flatten()
+            .chain(&self.disabled)
+            .chain(self.limits.keys())
+            .map(String::as_str)
+            .filter(|name| !known.contains(name))
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RawToolsSection {
+    pub enabled: Option<Vec<String>>,
+    pub disabled: Option<Vec<String>>,
+    pub admin: Option<bool>,
+    #[serde(flatten)]
+    pub limits: BTreeMap<String, RawToolLimits>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RawToolLimits {
+    pub timeout_secs: Option<u32>,
+    pub max_concurrent: Option<u16>,
+}
 
 pub fn parse_server_section(
     raw: Option<RawServerSection>,
@@ -31,11 +126,136 @@ pub fn parse_server_section(
     let host = server_raw.host.unwrap_or_else(|| DEFAULT_HOST.to_string());
     let port = server_raw.port.unwrap_or(DEFAULT_PORT);
     validate_port(port, path)?;
-    Ok(ServerSection { host, port })
+    let shutdown_grace_secs = server_raw
+        .shutdown_grace_secs
+        .unwrap_or(DEFAULT_SHUTDOWN_GRACE_SECS);
+    validate_shutdown_grace(shutdown_grace_secs, path)?;
+    let rate_limit_per_minute = server_raw.rate_limit_per_minute.unwrap_or(0);
+    if rate_limit_per_minute > 10_000 {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "server.rate_limit_per_minute",
+            message: "Use 0 (unlimited) or a limit up to 10000 calls per minute".into(),
+        });
+    }
+    let max_concurrent_calls = server_raw.max_concurrent_calls.unwrap_or(0);
+    if max_concurrent_calls > 256 {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "server.max_concurrent_calls",
+            message: "Use 0 (unlimited) or a limit up to 256 concurrent calls".into(),
+        });
+    }
+    let max_connections = server_raw
+        .max_connections
+        .unwrap_or(DEFAULT_MAX_CONNECTIONS);
+    if max_connections > 1024 {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "server.max_connections",
+            message: "Use 0 (unlimited) or a limit up to 1024 sessions".into(),
+        });
+    }
+    let idle_timeout_secs = server_raw.idle_timeout_secs.unwrap_or(0);
+    if idle_timeout_secs > 86_400 {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "server.idle_timeout_secs",
+            message: "Use 0 (never) or a timeout up to 86400 seconds".into(),
+        });
+    }
+    let auth_failure_limit = server_raw
+        .auth_failure_limit
+        .unwrap_or(DEFAULT_AUTH_FAILURE_LIMIT);
+    if auth_failure_limit > 1000 {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "server.auth_failure_limit",
+            message: "Use 0 (no lockout) or a limit up to 1000 failed attempts".into(),
+        });
+    }
+    let auth_lockout_secs = server_raw
+        .auth_lockout_secs
+        .unwrap_or(DEFAULT_AUTH_LOCKOUT_SECS);
+    if !(1..=86_400).contains(&auth_lockout_secs) {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "server.auth_lockout_secs",
+            message: "Use a lockout between 1 and 86400 seconds".into(),
+        });
+    }
+    Ok(ServerSection {
+        host,
+        port,
+        shutdown_grace_secs,
+        rate_limit_per_minute,
+        max_concurrent_calls,
+        allow_external_bind: server_raw.allow_external_bind.unwrap_or(false),
+        max_connections,
+        idle_timeout_secs,
+        auth_failure_limit,
+        auth_lockout_secs,
+    })
 }
 
-pub fn parse_tools_section(_raw: Option<RawToolsSection>, _path: &Path) -> Result<(), ConfigError> {
-    Ok(())
+pub fn parse_tools_section(
+    raw: Option<RawToolsSection>,
+    path: &Path,
+) -> Result<ToolsSection, ConfigError> {
+    let raw = raw.unwrap_or_default();
+    let invalid = |field: &'static str, message: String| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field,
+        message,
+    };
+    if raw.enabled.as_ref().is_some_and(Vec::is_empty) {
+        return Err(invalid(
+            "tools.enabled",
+            "List at least one tool, or remove `enabled` to register every tool".into(),
+        ));
+    }
+    for (field, names) in [
+        ("tools.enabled", raw.enabled.iter().flatten()),
+        ("tools.disabled", raw.disabled.iter().flatten()),
+    ] {
+        if names.clone().any(|name| name.trim().is_empty()) {
+            return Err(invalid(field, "Tool names cannot be empty".into()));
+        }
+    }
+    let mut limits = BTreeMap::new();
+    for (name, raw_limits) in raw.limits {
+        let timeout_secs = raw_limits.timeout_secs.unwrap_or(0);
+        if timeout_secs > MAX_TOOL_TIMEOUT_SECS {
+            return Err(invalid(
+                "tools.timeout_secs",
+                format!(
+                    "`tools.{name}.timeout_secs` must be 0 (no limit) or at most {MAX_TOOL_TIMEOUT_SECS} seconds"
+                ),
+            ));
+        }
+        let max_concurrent = raw_limits.max_concurrent.unwrap_or(0);
+        if max_concurrent > 256 {
+            return Err(invalid(
+                "tools.max_concurrent",
+                format!(
+                    "`tools.{name}.max_concurrent` must be 0 (unlimited) or at most 256 concurrent calls"
+                ),
+            ));
+        }
+        limits.insert(
+            name,
+            ToolLimits {
+                timeout_secs,
+                max_concurrent,
+            },
+        );
+    }
+    Ok(ToolsSection {
+        enabled: raw.enabled,
+        disabled: raw.disabled.unwrap_or_default(),
+        admin: raw.admin.unwrap_or(false),
+        limits,
+    })
 }
 
 fn validate_port(port: u16, path: &Path) -> Result<(), ConfigError> {
@@ -49,3 +269,15 @@ fn validate_port(port: u16, path: &Path) -> Result<(), ConfigError> {
         message: "Use a port in the range 1024-65535".into(),
     })
 }
+
+fn validate_shutdown_grace(secs: u32, path: &Path) -> Result<(), ConfigError> {
+    if secs <= 3600 {
+        return Ok(());
+    }
+
+    Err(ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "server.shutdown_grace_secs",
+        message: "Use a grace period between 0 and 3600 seconds".into(),
+    })
+}
@@ -1,43 +1,431 @@
-use std::path::{Path, PathBuf};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use serde::Deserialize;
 
+pub use crate::lib::compilation_cache::{CompilationCache, CompilationCacheMode};
+use crate::lib::devices::is_device_udid;
 use crate::lib::errors::ConfigError;
+pub use crate::lib::fs::{SymlinkPolicy, ZipCompression, ZipCompressionMethod};
+pub use crate::lib::platform::Platform;
+use crate::lib::retry::MAX_RETRY_ATTEMPTS;
+pub use crate::lib::retry::{RetryPolicy, RetryReason};
+use crate::lib::signing::is_team_id;
+pub use crate::lib::visionos::SchemePattern;
+use crate::lib::visionos::{is_allowed_scheme, is_path_pattern, validate_path_pattern};
 
 pub const DEFAULT_VISIONOS_DESTINATION: &str = "platform=visionOS Simulator,name=Apple Vision Pro";
 pub const DEFAULT_MAX_BUILD_MINUTES: u16 = 20;
+pub const DEFAULT_TIMEOUT_WARNING_PERCENT: u8 = 80;
+pub const DEFAULT_HEARTBEAT_INTERVAL_SECS: u16 = 15;
+pub const DEFAULT_MAX_EXTENDED_BUILD_MINUTES: u16 = 0;
+pub const DEFAULT_MAX_CONCURRENT_BUILDS: u16 = 1;
 pub const DEFAULT_ARTIFACT_TTL_SECS: u32 = 600;
 pub const DEFAULT_CLEANUP_SCHEDULE_SECS: u32 = 60;
+pub const DEFAULT_MIN_FREE_DISK_GB: u64 = 20;
+pub const DEFAULT_SANDBOX_PROBE_TTL_SECS: u32 = 300;
 pub const DEFAULT_REQUIRED_SDKS: &[&str] = &["visionOS", "visionOS Simulator"];
 pub const DEFAULT_XCODEBUILD_PATH: &str = "/usr/bin/xcodebuild";
+pub const DEFAULT_XCRUN_PATH: &str = "/usr/bin/xcrun";
+pub const DEFAULT_LOCALE: &str = "en_US.UTF-8";
 
 /// visionOS configuration section.
 #[derive(Debug, Clone)]
 pub struct VisionOsConfig {
     pub allowed_paths: Vec<PathBuf>,
-    pub allowed_schemes: Vec<String>,
+    /// `allowed_paths` entries given as tables, with a label and their own limits. Their paths
+    /// are also in `allowed_paths`.
+    pub allowed_roots: Vec<AllowedRoot>,
+    /// Compiled at load, so an invalid regex or glob fails the config instead of a build.
+    pub allowed_schemes: Vec<SchemePattern>,
     pub default_project_path: Option<PathBuf>,
     pub default_destination: String,
     pub required_sdks: Vec<String>,
     pub xcode_path: PathBuf,
     pub xcodebuild_path: PathBuf,
+    pub xcrun_path: PathBuf,
     pub max_build_minutes: u16,
+    /// Ceiling for a request's `timeout_minutes`; defaults to `max_build_minutes`.
+    pub max_timeout_minutes: u16,
+    /// Share of the build deadline after which a timeout warning is sent; `0` disables it.
+    pub timeout_warning_percent: u8,
+    /// Seconds between heartbeat notifications while a job runs; `0` disables them.
+    pub heartbeat_interval_secs: u16,
+    /// Absolute cap on a job deadline after `extend_build_deadline`; `0` disables extensions.
+    pub max_extended_build_minutes: u16,
+    /// Build, archive, and test jobs allowed to run at once (different projects only).
+    pub max_concurrent_builds: u16,
     pub artifact_ttl_secs: u32,
     pub cleanup_schedule_secs: u32,
+    /// Recorded jobs kept before cleanup evicts the oldest; `0` disables the limit.
+    pub artifact_max_jobs: u32,
+    /// Bytes of job directories kept before cleanup evicts the oldest jobs; `0` disables the limit.
+    pub artifact_max_total_bytes: u64,
+    /// Free space a build or archive needs on the project's volume before it starts; `0`
+    /// disables the check. Set in GB as `visionos.min_free_disk_gb`.
+    pub min_free_disk_bytes: u64,
+    /// Seconds `validate_sandbox_policy` reuses SDK, DevToolsSecurity, and license probes of a
+    /// developer directory; `0` probes on every call.
+    pub sandbox_probe_ttl_secs: u32,
+    /// Let `validate_sandbox_policy` run fixes such as `DevToolsSecurity -enable` when a request
+    /// sets `remediate`.
+    pub allow_remediation: bool,
+    /// Package hosts `validate_sandbox_policy` must reach for SwiftPM dependencies; empty skips
+    /// the check.
+    pub package_hosts: Vec<PackageHost>,
+    /// Which `validate_sandbox_policy` checks run, and their thresholds, from `[visionos.policy]`.
+    pub policy: PolicyConfig,
+    pub export_paths: Vec<PathBuf>,
+    /// App size limits keyed by scheme.
+    pub size_budgets: BTreeMap<String, SizeBudget>,
+    /// Compression for `artifact.zip` / `archive.zip` and split dSYMs.
+    pub artifact_compression: ZipCompression,
+    /// How symlinks inside bundles are stored in artifact zips.
+    pub artifact_symlinks: SymlinkPolicy,
+    /// Zip artifacts without timestamps or exact permissions so equal trees hash the same.
+    pub deterministic_artifacts: bool,
+    /// Parent of the per-project DerivedData directories reused by `use_cached_derived_data`;
+    /// `None` disables the option.
+    pub derived_data_root: Option<PathBuf>,
+    /// Return the artifact of an earlier job with the same sources and parameters instead of
+    /// rebuilding.
+    pub build_cache: bool,
+    /// Read build defaults from a `.seiro.toml` in the project directory.
+    pub project_config: bool,
+    /// Compiler cache used by every `xcodebuild` run; `None` leaves caching to the project.
+    pub compilation_cache: Option<CompilationCache>,
+    /// Destination and SDK defaults for platforms other than visionOS, from
+    /// `[visionos.platforms.<name>]`. Platforms without a table use [`Platform`]'s defaults.
+    pub platforms: BTreeMap<Platform, PlatformDefaults>,
+    /// `LANG` / `LC_ALL` for `xcodebuild`, whose environment is otherwise cleared.
+    pub locale: String,
+    /// Signing defaults and the provisioning profile allowlist from `[visionos.signing]`.
+    pub signing: SigningConfig,
+    /// Physical devices that `destination` may target by `id`; empty rejects every device
+    /// destination except `generic/platform=...`.
+    pub allowed_device_udids: Vec<String>,
+    /// Whether artifacts and job records outlive the server process.
+    pub artifact_persistence: ArtifactPersistence,
+    /// Named build settings from `[[visionos.presets]]`, selected with a request's `preset`.
+    pub presets: BTreeMap<String, BuildPreset>,
+    /// Named Developer directories from `[[visionos.xcodes]]`, selected with a request's `xcode`.
+    pub xcodes: BTreeMap<String, PathBuf>,
+    /// Retries of `xcodebuild` runs that failed for transient reasons, from `[visionos.retry]`.
+    pub retry: RetryPolicy,
+}
+
+impl VisionOsConfig {
+    /// Most specific labeled root containing `path`, if any.
+    pub fn root_for(&self, path: &Path) -> Option<&AllowedRoot> {
+        let path = fs::canonicalize(path).ok()?;
+        self.allowed_roots
+            .iter()
+            .filter_map(|root| fs::canonicalize(&root.path).ok().map(|base| (root, base)))
+            .filter(|(_, base)| path.starts_with(base))
+            .max_by_key(|(_, base)| base.components().count())
+            .map(|(root, _)| root)
+    }
+
+    /// Whether `allowed_schemes` accepts `scheme` (every scheme when the list is empty).
+    pub fn allows_scheme(&self, scheme: &str) -> bool {
+        self.allowed_schemes.is_empty() || is_allowed_scheme(scheme, &self.allowed_schemes)
+    }
+
+    /// Labels of the labeled roots, for allowlist errors.
+    pub fn root_labels(&self) -> Vec<String> {
+        self.allowed_roots
+            .iter()
+            .map(|root| root.label.clone())
+            .collect()
+    }
+
+    /// Destination and required SDKs for `platform`. visionOS uses `default_destination` and
+    /// `required_sdks`.
+    pub fn platform_defaults(&self, platform: Platform) -> PlatformDefaults {
+        if platform == Platform::VisionOs {
+            return PlatformDefaults {
+                default_destination: self.default_destination.clone(),
+                required_sdks: self.required_sdks.clone(),
+            };
+        }
+        self.platforms
+            .get(&platform)
+            .cloned()
+            .unwrap_or_else(|| PlatformDefaults::builtin(platform))
+    }
+
+    /// This config with `xcode_path` pointing at the named installation; `None` keeps
+    /// `xcode_path`. Callers validate the name first.
+    pub fn with_xcode(&self, name: Option<&str>) -> Cow<'_, VisionOsConfig> {
+        match name.and_then(|name| self.xcodes.get(name)) {
+            Some(developer_dir) => Cow::Owned(VisionOsConfig {
+                xcode_path: developer_dir.clone(),
+                ..self.clone()
+            }),
+            None => Cow::Borrowed(self),
+        }
+    }
+}
+
+/// Workspace root with its own policy, from a table entry in `allowed_paths`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowedRoot {
+    pub path: PathBuf,
+    /// Name shown in errors, e.g. `personal` or `ci`.
+    pub label: String,
+    /// Jobs for projects under this root allowed to run at once; `None` only applies the global
+    /// `max_concurrent_builds`.
+    pub max_concurrent: Option<u16>,
+    /// Artifact TTL for jobs under this root; `None` uses `artifact_ttl_secs`.
+    pub artifact_ttl_secs: Option<u32>,
+}
+
+/// Code signing defaults applied to build, archive, and test requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SigningConfig {
+    /// `DEVELOPMENT_TEAM` used when a request does not set `development_team`.
+    pub team_id: Option<String>,
+    /// `CODE_SIGN_IDENTITY` used when a request does not set `code_sign_identity`; also the
+    /// identity `validate_sandbox_policy` looks for in the keychain.
+    pub identity: Option<String>,
+    /// Names or UUIDs accepted as a request's `provisioning_profile`; empty allows any.
+    pub allowed_provisioning_profiles: Vec<String>,
+}
+
+/// Default destination and required SDKs for one platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlatformDefaults {
+    pub default_destination: String,
+    pub required_sdks: Vec<String>,
+}
+
+impl PlatformDefaults {
+    pub fn builtin(platform: Platform) -> Self {
+        Self {
+            default_destination: platform.default_destination().to_string(),
+            required_sdks: platform
+                .default_required_sdks()
+                .iter()
+                .map(|sdk| sdk.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Where artifacts and job records are kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArtifactPersistence {
+    /// Under the artifact root, with `job.json` records reloaded after a restart.
+    #[default]
+    Disk,
+    /// In a private temporary directory removed at shutdown; records stay in memory.
+    Memory,
+}
+
+/// What happens when a build exceeds its size budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SizeBudgetEnforcement {
+    /// Flag the response and log a warning.
+    #[default]
+    Warn,
+    /// Fail the build with `size_budget_exceeded`.
+    Fail,
+}
+
+/// Sandbox check selection and thresholds from `[visionos.policy]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyConfig {
+    /// Run only these checks; `None` runs every check.
+    pub enabled: Option<Vec<String>>,
+    /// Checks skipped even when `enabled` lists them.
+    pub disabled: Vec<String>,
+    /// Free bytes the `disk_space` check requires; `None` uses `min_free_disk_gb`. Builds and
+    /// archives keep using `min_free_disk_gb`.
+    pub min_free_disk_bytes: Option<u64>,
+}
+
+impl PolicyConfig {
+    /// Whether the check called `name` runs.
+    pub fn allows(&self, name: &str) -> bool {
+        let enabled = self
+            .enabled
+            .as_ref()
+            .is_none_or(|enabled| enabled.iter().any(|check| check == name));
+        enabled && !self.disabled.iter().any(|check| check == name)
+    }
+
+    /// Names in `enabled` or `disabled` that are not in `known`.
+    pub fn unknown_names<'a>(&'a self, known: &[&str]) -> Vec<&'a str> {
+        self.enabled
+            .iter()
+            .flatten()
+            .chain(&self.disabled)
+            .map(String::as_str)
+            .filter(|name| !known.contains(name))
+            .collect()
+    }
+}
+
+/// Host and port a package dependency is fetched from, written `host` or `host:port` in
+/// `visionos.package_hosts`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageHost {
+    pub host: String,
+    pub port: u16,
+}
+
+impl std::fmt::Display for PackageHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.host, self.port)
+    }
+}
+
+/// App size limits for one scheme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeBudget {
+    pub max_app_bytes: Option<u64>,
+    pub max_binary_bytes: Option<u64>,
+    pub enforcement: SizeBudgetEnforcement,
+}
+
+/// Build settings a request expands by naming the preset; the request's own values win.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildPreset {
+    pub scheme: Option<String>,
+    /// `debug` or `release`.
+    pub configuration: Option<String>,
+    pub destination: Option<String>,
+    /// Placed before the request's own `extra_args`.
+    pub extra_args: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawSizeBudget {
+    pub scheme: Option<String>,
+    pub max_app_bytes: Option<u64>,
+    pub max_binary_bytes: Option<u64>,
+    pub enforcement: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawBuildPreset {
+    pub name: Option<String>,
+    pub scheme: Option<String>,
+    pub configuration: Option<String>,
+    pub destination: Option<String>,
+    pub extra_args: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawXcodeInstallation {
+    pub name: Option<String>,
+    pub developer_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawRetryPolicy {
+    pub max_attempts: Option<u8>,
+    pub backoff_secs: Option<u64>,
+    pub retry_on: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawArtifactCompression {
+    pub method: Option<String>,
+    pub level: Option<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawCompilationCache {
+    pub mode: Option<String>,
+    pub sccache_path: Option<PathBuf>,
+    pub compiler_wrapper: Option<PathBuf>,
+    pub env: Option<BTreeMap<String, String>>,
+}
+
+/// `allowed_paths` entry: a bare path or a labeled root table.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RawAllowedPath {
+    Path(PathBuf),
+    Root(RawAllowedRoot),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawAllowedRoot {
+    pub path: Option<PathBuf>,
+    pub label: Option<String>,
+    pub max_concurrent: Option<u16>,
+    pub artifact_ttl_secs: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawPlatformDefaults {
+    pub default_destination: Option<String>,
+    pub required_sdks: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawPolicyConfig {
+    pub enabled: Option<Vec<String>>,
+    pub disabled: Option<Vec<String>>,
+    pub min_free_disk_bytes: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawSigningConfig {
+    pub team_id: Option<String>,
+    pub identity: Option<String>,
+    pub allowed_provisioning_profiles: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct RawVisionOsConfig {
-    pub allowed_paths: Option<Vec<PathBuf>>,
+    pub allowed_paths: Option<Vec<RawAllowedPath>>,
     pub allowed_schemes: Option<Vec<String>>,
     pub default_project_path: Option<PathBuf>,
     pub default_destination: Option<String>,
     pub required_sdks: Option<Vec<String>>,
     pub xcode_path: Option<PathBuf>,
     pub xcodebuild_path: Option<PathBuf>,
+    pub xcrun_path: Option<PathBuf>,
     pub max_build_minutes: Option<u16>,
+    pub max_timeout_minutes: Option<u16>,
+    pub timeout_warning_percent: Option<u8>,
+    pub heartbeat_interval_secs: Option<u16>,
+    pub max_extended_build_minutes: Option<u16>,
+    pub max_concurrent_builds: Option<u16>,
     pub artifact_ttl_secs: Option<u32>,
     pub cleanup_schedule_secs: Option<u32>,
+    pub artifact_max_jobs: Option<u32>,
+    pub artifact_max_total_bytes: Option<u64>,
+    pub min_free_disk_gb: Option<u64>,
+    pub sandbox_probe_ttl_secs: Option<u32>,
+    pub allow_remediation: Option<bool>,
+    pub package_hosts: Option<Vec<String>>,
+    pub policy: Option<RawPolicyConfig>,
+    pub export_paths: Option<Vec<PathBuf>>,
+    pub size_budgets: Option<Vec<RawSizeBudget>>,
+    pub artifact_compression: Option<RawArtifactCompression>,
+    pub artifact_symlinks: Option<String>,
+    pub deterministic_artifacts: Option<bool>,
+    pub derived_data_root: Option<PathBuf>,
+    pub build_cache: Option<bool>,
+    pub project_config: Option<bool>,
+    pub compilation_cache: Option<RawCompilationCache>,
+    pub platforms: Option<BTreeMap<String, RawPlatformDefaults>>,
+    pub locale: Option<String>,
+    pub signing: Option<RawSigningConfig>,
+    pub allowed_device_udids: Option<Vec<String>>,
+    pub artifact_persistence: Option<String>,
+    pub presets: Option<Vec<RawBuildPreset>>,
+    pub xcodes: Option<Vec<RawXcodeInstallation>>,
+    pub retry: Option<RawRetryPolicy>,
 }
 
 pub fn parse_visionos_section(
@@ -49,12 +437,24 @@ pub fn parse_visionos_section(
         field: "visionos",
     })?;
 
-    let allowed_paths = visionos_raw
+    let raw_allowed_paths = visionos_raw
         .allowed_paths
         .ok_or(ConfigError::MissingField {
             path: path.clone(),
             field: "visionos.allowed_paths",
         })?;
+    let mut allowed_paths = Vec::with_capacity(raw_allowed_paths.len());
+    let mut raw_roots = Vec::new();
+    for entry in raw_allowed_paths {
+        match entry {
+            RawAllowedPath::Path(entry) => allowed_paths.push(entry),
+            RawAllowedPath::Root(raw) => {
+                let entry = raw.path.clone().unwrap_or_default();
+                allowed_paths.push(entry);
+                raw_roots.push(raw);
+            }
+        }
+    }
     validate_allowed_paths(path.as_path(), &allowed_paths)?;
 
     let allowed_schemes = visionos_raw
@@ -63,7 +463,7 @@ pub fn parse_visionos_section(
             path: path.clone(),
             field: "visionos.allowed_schemes",
         })?;
-    validate_allowed_schemes(path.as_path(), &allowed_schemes)?;
+    let allowed_schemes = parse_allowed_schemes(path.as_path(), &allowed_schemes)?;
 
     if let Some(default_project_path) = visionos_raw.default_project_path.as_deref() {
         validate_default_project_path(path.as_path(), default_project_path)?;
@@ -72,7 +472,11 @@ pub fn parse_visionos_section(
     let default_destination = visionos_raw
         .default_destination
         .unwrap_or_else(|| DEFAULT_VISIONOS_DESTINATION.to_string());
-    validate_destination(path.as_path(), &default_destination)?;
+    validate_destination(
+        path.as_path(),
+        "visionos.default_destination",
+        &default_destination,
+    )?;
 
     let required_sdks = visionos_raw.required_sdks.unwrap_or_else(|| {
         DEFAULT_REQUIRED_SDKS
@@ -80,7 +484,7 @@ pub fn parse_visionos_section(
             .map(|sdk| sdk.to_string())
             .collect()
     });
-    validate_required_sdks(path.as_path(), &required_sdks)?;
+    validate_required_sdks(path.as_path(), "visionos.required_sdks", &required_sdks)?;
 
     let xcode_path = visionos_raw.xcode_path.ok_or(ConfigError::MissingField {
         path: path.clone(),
@@ -93,11 +497,45 @@ pub fn parse_visionos_section(
         .unwrap_or_else(|| PathBuf::from(DEFAULT_XCODEBUILD_PATH));
     validate_xcodebuild_path(path.as_path(), &xcodebuild_path)?;
 
+    let xcrun_path = visionos_raw
+        .xcrun_path
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_XCRUN_PATH));
+    validate_xcrun_path(path.as_path(), &xcrun_path)?;
+
     let max_build_minutes = visionos_raw
         .max_build_minutes
         .unwrap_or(DEFAULT_MAX_BUILD_MINUTES);
     validate_build_minutes(path.as_path(), max_build_minutes)?;
 
+    let max_timeout_minutes = visionos_raw
+        .max_timeout_minutes
+        .unwrap_or(max_build_minutes);
+    validate_timeout_minutes(path.as_path(), max_build_minutes, max_timeout_minutes)?;
+
+    let timeout_warning_percent = visionos_raw
+        .timeout_warning_percent
+        .unwrap_or(DEFAULT_TIMEOUT_WARNING_PERCENT);
+    validate_timeout_warning_percent(path.as_path(), timeout_warning_percent)?;
+
+    let heartbeat_interval_secs = visionos_raw
+        .heartbeat_interval_secs
+        .unwrap_or(DEFAULT_HEARTBEAT_INTERVAL_SECS);
+    validate_heartbeat_interval(path.as_path(), heartbeat_interval_secs)?;
+
+    let max_extended_build_minutes = visionos_raw
+        .max_extended_build_minutes
+        .unwrap_or(DEFAULT_MAX_EXTENDED_BUILD_MINUTES);
+    validate_extended_build_minutes(
+        path.as_path(),
+        max_build_minutes,
+        max_extended_build_minutes,
+    )?;
+
+    let max_concurrent_builds = visionos_raw
+        .max_concurrent_builds
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_BUILDS);
+    validate_concurrent_builds(path.as_path(), max_concurrent_builds)?;
+
     let artifact_ttl_secs = visionos_raw
         .artifact_ttl_secs
         .unwrap_or(DEFAULT_ARTIFACT_TTL_SECS);
@@ -108,20 +546,267 @@ pub fn parse_visionos_section(
         .unwrap_or(DEFAULT_CLEANUP_SCHEDULE_SECS);
     validate_cleanup_interval(path.as_path(), cleanup_schedule_secs)?;
 
+    let mut allowed_roots = Vec::with_capacity(raw_roots.len());
+    let mut labels = BTreeSet::new();
+    for raw in raw_roots {
+        let root = parse_allowed_root(
+            path.as_path(),
+            raw,
+            max_concurrent_builds,
+            artifact_ttl_secs,
+        )?;
+        if !labels.insert(root.label.clone()) {
+            return Err(ConfigError::InvalidField {
+                path: path.clone(),
+                field: "visionos.allowed_paths",
+                message: format!("Label `{}` is used by more than one root", root.label),
+            });
+        }
+        allowed_roots.push(root);
+    }
+
+    let export_paths = visionos_raw.export_paths.unwrap_or_default();
+    validate_export_paths(path.as_path(), &export_paths)?;
+    let package_hosts = visionos_raw
+        .package_hosts
+        .unwrap_or_default()
+        .iter()
+        .map(|entry| parse_package_host(path.as_path(), entry))
+        .collect::<Result<Vec<_>, _>>()?;
+    let policy = match visionos_raw.policy {
+        Some(raw) => parse_policy(path.as_path(), raw)?,
+        None => PolicyConfig::default(),
+    };
+
+    let mut size_budgets = BTreeMap::new();
+    for raw in visionos_raw.size_budgets.unwrap_or_default() {
+        let (scheme, budget) = parse_size_budget(path.as_path(), raw)?;
+        if size_budgets.insert(scheme.clone(), budget).is_some() {
+            return Err(ConfigError::InvalidField {
+                path: path.clone(),
+                field: "visionos.size_budgets",
+                message: format!("Scheme `{scheme}` has more than one size budget"),
+            });
+        }
+    }
+
+    let mut presets = BTreeMap::new();
+    for raw in visionos_raw.presets.unwrap_or_default() {
+        let (name, preset) = parse_build_preset(path.as_path(), raw)?;
+        if presets.insert(name.clone(), preset).is_some() {
+            return Err(ConfigError::InvalidField {
+                path: path.clone(),
+                field: "visionos.presets",
+                message: format!("Preset `{name}` is defined more than once"),
+            });
+        }
+    }
+
+    let mut xcodes = BTreeMap::new();
+    for raw in visionos_raw.xcodes.unwrap_or_default() {
+        let (name, developer_dir) = parse_xcode_installation(path.as_path(), raw)?;
+        if xcodes.insert(name.clone(), developer_dir).is_some() {
+            return Err(ConfigError::InvalidField {
+                path: path.clone(),
+                field: "visionos.xcodes",
+                message: format!("Xcode `{name}` is defined more than once"),
+            });
+        }
+    }
+
+    let artifact_compression = match visionos_raw.artifact_compression {
+        Some(raw) => parse_artifact_compression(path.as_path(), raw)?,
+        None => ZipCompression::default(),
+    };
+    let artifact_symlinks = match visionos_raw.artifact_symlinks.as_deref() {
+        None | Some("preserve") => SymlinkPolicy::Preserve,
+        Some("dereference") => SymlinkPolicy::Dereference,
+        Some(other) => {
+            return Err(ConfigError::InvalidField {
+                path: path.clone(),
+                field: "visionos.artifact_symlinks",
+                message: format!("must be `preserve` or `dereference`, got `{other}`"),
+            })
+        }
+    };
+
+    if let Some(root) = &visionos_raw.derived_data_root {
+        if root.as_os_str().is_empty() || !root.is_absolute() {
+            return Err(ConfigError::InvalidField {
+                path: path.clone(),
+                field: "visionos.derived_data_root",
+                message: "Specify an absolute path".into(),
+            });
+        }
+    }
+
+    let compilation_cache = visionos_raw
+        .compilation_cache
+        .map(|raw| parse_compilation_cache(path.as_path(), raw))
+        .transpose()?;
+    let retry = match visionos_raw.retry {
+        Some(raw) => parse_retry_policy(path.as_path(), raw)?,
+        None => RetryPolicy::default(),
+    };
+
+    let mut platforms = BTreeMap::new();
+    for (name, raw) in visionos_raw.platforms.unwrap_or_default() {
+        let platform = parse_platform_name(path.as_path(), &name)?;
+        platforms.insert(
+            platform,
+            parse_platform_defaults(path.as_path(), platform, raw)?,
+        );
+    }
+
+    let locale = visionos_raw
+        .locale
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string());
+    validate_locale(path.as_path(), &locale)?;
+
+    let signing = match visionos_raw.signing {
+        Some(raw) => parse_signing(path.as_path(), raw)?,
+        None => SigningConfig::default(),
+    };
+
+    let allowed_device_udids = visionos_raw.allowed_device_udids.unwrap_or_default();
+    validate_device_udids(path.as_path(), &allowed_device_udids)?;
+
+    let artifact_persistence = match visionos_raw.artifact_persistence.as_deref() {
+        None | Some("disk") => ArtifactPersistence::Disk,
+        Some("memory") => ArtifactPersistence::Memory,
+        Some(other) => {
+            return Err(ConfigError::InvalidField {
+                path: path.clone(),
+                field: "visionos.artifact_persistence",
+                message: format!("must be `disk` or `memory`, got `{other}`"),
+            })
+        }
+    };
+    if artifact_persistence == ArtifactPersistence::Memory
+        && (visionos_raw.derived_data_root.is_some() || compilation_cache.is_some())
+    {
+        return Err(ConfigError::InvalidField {
+            path: path.clone(),
+            field: "visionos.artifact_persistence",
+            message:
+                "`memory` keeps nothing on disk; remove derived_data_root and compilation_cache"
+                    .into(),
+        });
+    }
+
     Ok(VisionOsConfig {
         allowed_paths,
+        allowed_roots,
         allowed_schemes,
         default_project_path: visionos_raw.default_project_path,
         default_destination,
         required_sdks,
         xcode_path,
         xcodebuild_path,
+        xcrun_path,
         max_build_minutes,
+        max_timeout_minutes,
+        timeout_warning_percent,
+        heartbeat_interval_secs,
+        max_extended_build_minutes,
+        max_concurrent_builds,
         artifact_ttl_secs,
         cleanup_schedule_secs,
+        artifact_max_jobs: visionos_raw.artifact_max_jobs.unwrap_or(0),
+        artifact_max_total_bytes: visionos_raw.artifact_max_total_bytes.unwrap_or(0),
+        min_free_disk_bytes: visionos_raw
+            .min_free_disk_gb
+            .unwrap_or(DEFAULT_MIN_FREE_DISK_GB)
+            .saturating_mul(1024 * 1024 * 1024),
+        sandbox_probe_ttl_secs: visionos_raw
+            .sandbox_probe_ttl_secs
+            .unwrap_or(DEFAULT_SANDBOX_PROBE_TTL_SECS),
+        allow_remediation: visionos_raw.allow_remediation.unwrap_or(false),
+        package_hosts,
+        policy,
+        export_paths,
+        size_budgets,
+        artifact_compression,
+        artifact_symlinks,
+        deterministic_artifacts: visionos_raw.deterministic_artifacts.unwrap_or(false),
+        derived_data_root: visionos_raw.derived_data_root,
+        build_cache: visionos_raw.build_cache.unwrap_or(false),
+        project_config: visionos_raw.project_config.unwrap_or(false),
+        compilation_cache,
+        platforms,
+        locale,
+        signing,
+        allowed_device_udids,
+        artifact_persistence,
+        presets,
+        xcodes,
+        retry,
     })
 }
 
+fn parse_signing(path: &Path, raw: RawSigningConfig) -> Result<SigningConfig, ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "visionos.signing",
+        message,
+    };
+    if let Some(team_id) = &raw.team_id {
+        if !is_team_id(team_id) {
+            return Err(invalid(format!(
+                "team_id `{team_id}` must be 10 uppercase letters and digits"
+            )));
+        }
+    }
+    if let Some(identity) = &raw.identity {
+        if identity.trim().is_empty() || identity.chars().any(char::is_control) {
+            return Err(invalid(
+                "identity must be a certificate name, SHA-1 hash, or `-`".into(),
+            ));
+        }
+    }
+    let allowed_provisioning_profiles = raw.allowed_provisioning_profiles.unwrap_or_default();
+    if allowed_provisioning_profiles
+        .iter()
+        .any(|profile| profile.trim().is_empty() || profile.chars().any(char::is_control))
+    {
+        return Err(invalid(
+            "allowed_provisioning_profiles entries must be non-empty profile names or UUIDs".into(),
+        ));
+    }
+    Ok(SigningConfig {
+        team_id: raw.team_id,
+        identity: raw.identity,
+        allowed_provisioning_profiles,
+    })
+}
+
+fn validate_device_udids(path: &Path, udids: &[String]) -> Result<(), ConfigError> {
+    match udids.iter().find(|udid| !is_device_udid(udid)) {
+        Some(udid) => Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "visionos.allowed_device_udids",
+            message: format!("`{udid}` is not a device UDID as reported by list_devices"),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn validate_locale(path: &Path, locale: &str) -> Result<(), ConfigError> {
+    let valid = !locale.is_empty()
+        && locale
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '@'));
+    if valid {
+        Ok(())
+    } else {
+        Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "visionos.locale",
+            message: format!("`{locale}` is not a locale name such as `en_US.UTF-8`"),
+        })
+    }
+}
+
 fn validate_allowed_paths(path: &Path, allowed_paths: &[PathBuf]) -> Result<(), ConfigError> {
     if allowed_paths.is_empty() {
         return Ok(());
@@ -134,14 +819,410 @@ fn validate_allowed_paths(path: &Path, allowed_paths: &[PathBuf]) -> Result<(),
                 message: format!("Only absolute paths are allowed: {}", entry.display()),
             });
         }
+        if is_path_pattern(entry) {
+            validate_path_pattern(entry).map_err(|err| ConfigError::InvalidField {
+                path: path.to_path_buf(),
+                field: "visionos.allowed_paths",
+                message: format!("Invalid pattern {}: {err}", entry.display()),
+            })?;
+        }
     }
     Ok(())
 }
 
-fn validate_allowed_schemes(path: &Path, schemes: &[String]) -> Result<(), ConfigError> {
-    if schemes.is_empty() {
-        return Ok(());
+fn parse_allowed_root(
+    path: &Path,
+    raw: RawAllowedRoot,
+    max_concurrent_builds: u16,
+    artifact_ttl_secs: u32,
+) -> Result<AllowedRoot, ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "visionos.allowed_paths",
+        message,
+    };
+    let root_path = raw
+        .path
+        .ok_or_else(|| invalid("Each root table needs a path".into()))?;
+    let label = raw
+        .label
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty())
+        .ok_or_else(|| invalid(format!("Root {} needs a label", root_path.display())))?;
+    if is_path_pattern(&root_path) {
+        return Err(invalid(format!(
+            "Root `{label}` needs a directory path, not a pattern: {}",
+            root_path.display()
+        )));
+    }
+    if label.chars().count() > 64 {
+        return Err(invalid(format!("Label `{label}` exceeds 64 characters")));
+    }
+    if let Some(max_concurrent) = raw.max_concurrent {
+        if !(1..=max_concurrent_builds).contains(&max_concurrent) {
+            return Err(invalid(format!(
+                "max_concurrent for `{label}` must be between 1 and max_concurrent_builds ({max_concurrent_builds})"
+            )));
+        }
+    }
+    if let Some(ttl) = raw.artifact_ttl_secs {
+        if !(60..=artifact_ttl_secs).contains(&ttl) {
+            return Err(invalid(format!(
+                "artifact_ttl_secs for `{label}` must be between 60 and artifact_ttl_secs ({artifact_ttl_secs})"
+            )));
+        }
+    }
+    Ok(AllowedRoot {
+        path: root_path,
+        label,
+        max_concurrent: raw.max_concurrent,
+        artifact_ttl_secs: raw.artifact_ttl_secs,
+    })
+}
+
+fn validate_export_paths(path: &Path, export_paths: &[PathBuf]) -> Result<(), ConfigError> {
+    for entry in export_paths {
+        if entry.as_os_str().is_empty() || !entry.is_absolute() {
+            return Err(ConfigError::InvalidField {
+                path: path.to_path_buf(),
+                field: "visionos.export_paths",
+                message: format!("Only absolute paths are allowed: {}", entry.display()),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn parse_policy(path: &Path, raw: RawPolicyConfig) -> Result<PolicyConfig, ConfigError> {
+    let invalid = |message: &str| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "visionos.policy",
+        message: message.into(),
+    };
+    if raw.enabled.as_ref().is_some_and(Vec::is_empty) {
+        return Err(invalid(
+            "List at least one check in `enabled`, or remove it to run every check",
+        ));
+    }
+    if raw
+        .enabled
+        .iter()
+        .flatten()
+        .chain(raw.disabled.iter().flatten())
+        .any(|name| name.trim().is_empty())
+    {
+        return Err(invalid("Check names cannot be empty"));
+    }
+    Ok(PolicyConfig {
+        enabled: raw.enabled,
+        disabled: raw.disabled.unwrap_or_default(),
+        min_free_disk_bytes: raw.min_free_disk_bytes,
+    })
+}
+
+/// `host` (port 443) or `host:port`; URLs and paths are rejected.
+fn parse_package_host(path: &Path, entry: &str) -> Result<PackageHost, ConfigError> {
+    let invalid = || ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "visionos.package_hosts",
+        message: format!("Expected `host` or `host:port`, got `{entry}`"),
+    };
+    let (host, port) = match entry.trim().rsplit_once(':') {
+        Some((host, port)) => (host, port.parse::<u16>().map_err(|_| invalid())?),
+        None => (entry.trim(), 443),
+    };
+    if host.is_empty()
+        || port == 0
+        || host.contains(['/', ':'])
+        || host.contains(char::is_whitespace)
+    {
+        return Err(invalid());
+    }
+    Ok(PackageHost {
+        host: host.to_string(),
+        port,
+    })
+}
+
+fn parse_size_budget(path: &Path, raw: RawSizeBudget) -> Result<(String, SizeBudget), ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "visionos.size_budgets",
+        message,
+    };
+    let scheme = raw
+        .scheme
+        .filter(|scheme| !scheme.trim().is_empty())
+        .ok_or_else(|| invalid("Each size budget needs a scheme".into()))?;
+    if raw.max_app_bytes.is_none() && raw.max_binary_bytes.is_none() {
+        return Err(invalid(format!(
+            "Set max_app_bytes and/or max_binary_bytes for scheme `{scheme}`"
+        )));
+    }
+    if raw.max_app_bytes == Some(0) || raw.max_binary_bytes == Some(0) {
+        return Err(invalid(format!(
+            "Size limits must be greater than 0 bytes for scheme `{scheme}`"
+        )));
+    }
+    let enforcement = match raw.enforcement.as_deref() {
+        None | Some("warn") => SizeBudgetEnforcement::Warn,
+        Some("fail") => SizeBudgetEnforcement::Fail,
+        Some(other) => {
+            return Err(invalid(format!(
+                "enforcement must be `warn` or `fail` for scheme `{scheme}`, got `{other}`"
+            )))
+        }
+    };
+    let budget = SizeBudget {
+        max_app_bytes: raw.max_app_bytes,
+        max_binary_bytes: raw.max_binary_bytes,
+        enforcement,
+    };
+    Ok((scheme, budget))
+}
+
+fn parse_build_preset(
+    path: &Path,
+    raw: RawBuildPreset,
+) -> Result<(String, BuildPreset), ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "visionos.presets",
+        message,
+    };
+    let name = raw
+        .name
+        .filter(|name| !name.trim().is_empty())
+        .ok_or_else(|| invalid("Each preset needs a name".into()))?;
+    let non_empty = |field: &str, value: Option<String>| match value {
+        Some(value) if value.trim().is_empty() => Err(invalid(format!(
+            "{field} must not be empty for preset `{name}`"
+        ))),
+        value => Ok(value),
+    };
+    let scheme = non_empty("scheme", raw.scheme)?;
+    let destination = non_empty("destination", raw.destination)?;
+    let configuration = match raw.configuration.as_deref() {
+        None => None,
+        Some("debug" | "Debug") => Some("debug".to_string()),
+        Some("release" | "Release") => Some("release".to_string()),
+        Some(other) => {
+            return Err(invalid(format!(
+                "configuration must be `debug` or `release` for preset `{name}`, got `{other}`"
+            )))
+        }
+    };
+    let preset = BuildPreset {
+        scheme,
+        configuration,
+        destination,
+        extra_args: raw.extra_args.unwrap_or_default(),
+    };
+    Ok((name, preset))
+}
+
+fn parse_xcode_installation(
+    path: &Path,
+    raw: RawXcodeInstallation,
+) -> Result<(String, PathBuf), ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "visionos.xcodes",
+        message,
+    };
+    let name = raw
+        .name
+        .filter(|name| !name.trim().is_empty())
+        .ok_or_else(|| invalid("Each Xcode installation needs a name".into()))?;
+    if name == "default" {
+        return Err(invalid(
+            "`default` is reserved for visionos.xcode_path; pick another name".into(),
+        ));
     }
+    let developer_dir = raw
+        .developer_dir
+        .filter(|dir| dir.is_absolute())
+        .ok_or_else(|| {
+            invalid(format!(
+                "Xcode `{name}` needs an absolute developer_dir, e.g. /Applications/Xcode-beta.app/Contents/Developer"
+            ))
+        })?;
+    Ok((name, developer_dir))
+}
+
+fn parse_artifact_compression(
+    path: &Path,
+    raw: RawArtifactCompression,
+) -> Result<ZipCompression, ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "visionos.artifact_compression",
+        message,
+    };
+    let method = match raw.method.as_deref() {
+        None | Some("deflate") => ZipCompressionMethod::Deflate,
+        Some("store") => ZipCompressionMethod::Store,
+        Some("zstd") => ZipCompressionMethod::Zstd,
+        Some(other) => {
+            return Err(invalid(format!(
+                "method must be `deflate`, `zstd`, or `store`, got `{other}`"
+            )))
+        }
+    };
+    let level = raw.level.unwrap_or(method.default_level());
+    match method.level_range() {
+        Some((min, max)) if !(min..=max).contains(&level) => {
+            return Err(invalid(format!(
+                "level must be between {min} and {max}, got {level}"
+            )));
+        }
+        None if raw.level.is_some() => {
+            return Err(invalid(
+                "level does not apply to `store`; remove it when method = \"store\"".into(),
+            ));
+        }
+        _ => {}
+    }
+    Ok(ZipCompression { method, level })
+}
+
+fn parse_platform_name(path: &Path, name: &str) -> Result<Platform, ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "visionos.platforms",
+        message,
+    };
+    match Platform::parse(name) {
+        Some(Platform::VisionOs) => Err(invalid(
+            "Set visionOS defaults with visionos.default_destination and visionos.required_sdks"
+                .into(),
+        )),
+        Some(platform) => Ok(platform),
+        None => Err(invalid(format!(
+            "Unknown platform `{name}`; use ios, macos, tvos, or watchos"
+        ))),
+    }
+}
+
+fn parse_platform_defaults(
+    path: &Path,
+    platform: Platform,
+    raw: RawPlatformDefaults,
+) -> Result<PlatformDefaults, ConfigError> {
+    let builtin = PlatformDefaults::builtin(platform);
+    let default_destination = raw
+        .default_destination
+        .unwrap_or(builtin.default_destination);
+    validate_destination(path, "visionos.platforms", &default_destination)?;
+    let required_sdks = raw.required_sdks.unwrap_or(builtin.required_sdks);
+    validate_required_sdks(path, "visionos.platforms", &required_sdks)?;
+    Ok(PlatformDefaults {
+        default_destination,
+        required_sdks,
+    })
+}
+
+fn parse_compilation_cache(
+    path: &Path,
+    raw: RawCompilationCache,
+) -> Result<CompilationCache, ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "visionos.compilation_cache",
+        message,
+    };
+    let absolute = |name: &str, value: Option<PathBuf>| match value {
+        Some(value) if value.is_absolute() => Ok(value),
+        Some(value) => Err(invalid(format!(
+            "{name} must be an absolute path, got `{}`",
+            value.display()
+        ))),
+        None => Err(invalid(format!(
+            "{name} is required when mode = \"sccache\""
+        ))),
+    };
+    let mode = match raw.mode.as_deref() {
+        None | Some("xcode") => {
+            if raw.sccache_path.is_some() || raw.compiler_wrapper.is_some() {
+                return Err(invalid(
+                    "sccache_path and compiler_wrapper only apply to mode = \"sccache\"".into(),
+                ));
+            }
+            CompilationCacheMode::Xcode
+        }
+        Some("sccache") => CompilationCacheMode::Sccache {
+            sccache_path: absolute("sccache_path", raw.sccache_path)?,
+            compiler_wrapper: absolute("compiler_wrapper", raw.compiler_wrapper)?,
+        },
+        Some(other) => {
+            return Err(invalid(format!(
+                "mode must be `xcode` or `sccache`, got `{other}`"
+            )))
+        }
+    };
+    let env = raw.env.unwrap_or_default();
+    for key in env.keys() {
+        let valid_name = !key.is_empty()
+            && !key.starts_with(|ch: char| ch.is_ascii_digit())
+            && key
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+        if !valid_name || key == "DEVELOPER_DIR" {
+            return Err(invalid(format!(
+                "env cannot set `{key}`; use environment variable names other than DEVELOPER_DIR"
+            )));
+        }
+    }
+    Ok(CompilationCache { mode, env })
+}
+
+fn parse_retry_policy(path: &Path, raw: RawRetryPolicy) -> Result<RetryPolicy, ConfigError> {
+    let invalid = |message: String| ConfigError::InvalidField {
+        path: path.to_path_buf(),
+        field: "visionos.retry",
+        message,
+    };
+    let defaults = RetryPolicy::default();
+    let max_attempts = raw.max_attempts.unwrap_or(defaults.max_attempts);
+    if !(1..=MAX_RETRY_ATTEMPTS).contains(&max_attempts) {
+        return Err(invalid(format!(
+            "max_attempts must be between 1 and {MAX_RETRY_ATTEMPTS}, got {max_attempts}"
+        )));
+    }
+    let backoff = match raw.backoff_secs {
+        Some(secs) if secs > 300 => {
+            return Err(invalid(format!(
+                "backoff_secs must be at most 300, got {secs}"
+            )))
+        }
+        Some(secs) => Duration::from_secs(secs),
+        None => defaults.backoff,
+    };
+    let retry_on = match raw.retry_on {
+        Some(codes) => codes
+            .iter()
+            .map(|code| {
+                RetryReason::parse(code).ok_or_else(|| {
+                    invalid(format!(
+                        "retry_on must list `simulator_boot`, `spm_network`, or `build_service`, got `{code}`"
+                    ))
+                })
+            })
+            .collect::<Result<_, _>>()?,
+        None => defaults.retry_on,
+    };
+    Ok(RetryPolicy {
+        max_attempts,
+        backoff,
+        retry_on,
+    })
+}
+
+fn parse_allowed_schemes(
+    path: &Path,
+    schemes: &[String],
+) -> Result<Vec<SchemePattern>, ConfigError> {
+    let mut patterns = Vec::with_capacity(schemes.len());
     for scheme in schemes {
         if scheme.trim().is_empty() {
             return Err(ConfigError::InvalidField {
@@ -157,8 +1238,15 @@ fn validate_allowed_schemes(path: &Path, schemes: &[String]) -> Result<(), Confi
                 message: format!("Scheme length exceeds 128 characters: {scheme}"),
             });
         }
+        patterns.push(
+            SchemePattern::new(scheme).map_err(|err| ConfigError::InvalidField {
+                path: path.to_path_buf(),
+                field: "visionos.allowed_schemes",
+                message: format!("Invalid scheme pattern `{scheme}`: {err}"),
+            })?,
+        );
     }
-    Ok(())
+    Ok(patterns)
 }
 
 fn validate_default_project_path(path: &Path, project_path: &Path) -> Result<(), ConfigError> {
@@ -181,23 +1269,31 @@ fn validate_default_project_path(path: &Path, project_path: &Path) -> Result<(),
     Ok(())
 }
 
-fn validate_destination(path: &Path, destination: &str) -> Result<(), ConfigError> {
+fn validate_destination(
+    path: &Path,
+    field: &'static str,
+    destination: &str,
+) -> Result<(), ConfigError> {
     let trimmed = destination.trim();
     if trimmed.is_empty() || trimmed.len() > 256 || !trimmed.contains("platform=") {
         return Err(ConfigError::InvalidField {
             path: path.to_path_buf(),
-            field: "visionos.default_destination",
+            field,
             message: "Provide a 1-256 character string that includes platform=".into(),
         });
     }
     Ok(())
 }
 
-fn validate_required_sdks(path: &Path, sdks: &[String]) -> Result<(), ConfigError> {
+fn validate_required_sdks(
+    path: &Path,
+    field: &'static str,
+    sdks: &[String],
+) -> Result<(), ConfigError> {
     if sdks.is_empty() {
         return Err(ConfigError::InvalidField {
             path: path.to_path_buf(),
-            field: "visionos.required_sdks",
+            field,
             message: "Specify at least one SDK name".into(),
         });
     }
@@ -206,7 +1302,7 @@ fn validate_required_sdks(path: &Path, sdks: &[String]) -> Result<(), ConfigErro
         if sdk.trim().is_empty() {
             return Err(ConfigError::InvalidField {
                 path: path.to_path_buf(),
-                field: "visionos.required_sdks",
+                field,
                 message: "SDK names cannot be empty".into(),
             });
         }
@@ -236,6 +1332,17 @@ fn validate_xcodebuild_path(path: &Path, xcodebuild_path: &Path) -> Result<(), C
     Ok(())
 }
 
+fn validate_xcrun_path(path: &Path, xcrun_path: &Path) -> Result<(), ConfigError> {
+    if xcrun_path.as_os_str().is_empty() || !xcrun_path.is_absolute() {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "visionos.xcrun_path",
+            message: "Provide an absolute path to the xcrun executable".into(),
+        });
+    }
+    Ok(())
+}
+
 fn validate_build_minutes(path: &Path, minutes: u16) -> Result<(), ConfigError> {
     if !(1..=60).contains(&minutes) {
         return Err(ConfigError::InvalidField {
@@ -247,6 +1354,73 @@ fn validate_build_minutes(path: &Path, minutes: u16) -> Result<(), ConfigError>
     Ok(())
 }
 
+fn validate_timeout_minutes(
+    path: &Path,
+    max_build_minutes: u16,
+    minutes: u16,
+) -> Result<(), ConfigError> {
+    if !(max_build_minutes..=120).contains(&minutes) {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "visionos.max_timeout_minutes",
+            message: format!(
+                "Specify a value between max_build_minutes ({max_build_minutes}) and 120 minutes"
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn validate_timeout_warning_percent(path: &Path, percent: u8) -> Result<(), ConfigError> {
+    if percent > 99 {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "visionos.timeout_warning_percent",
+            message: "Specify a value between 1 and 99 percent, or 0 to disable the warning".into(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_heartbeat_interval(path: &Path, secs: u16) -> Result<(), ConfigError> {
+    if secs > 300 {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "visionos.heartbeat_interval_secs",
+            message: "Specify 1 to 300 seconds, or 0 to disable heartbeats".into(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_extended_build_minutes(
+    path: &Path,
+    max_build_minutes: u16,
+    minutes: u16,
+) -> Result<(), ConfigError> {
+    if minutes != 0 && !(max_build_minutes..=120).contains(&minutes) {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "visionos.max_extended_build_minutes",
+            message: format!(
+                "Specify a value between max_build_minutes ({max_build_minutes}) and 120 minutes, or 0 to disable extensions"
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn validate_concurrent_builds(path: &Path, builds: u16) -> Result<(), ConfigError> {
+    if !(1..=8).contains(&builds) {
+        return Err(ConfigError::InvalidField {
+            path: path.to_path_buf(),
+            field: "visionos.max_concurrent_builds",
+            message: "Specify a value between 1 and 8 concurrent builds".into(),
+        });
+    }
+    Ok(())
+}
+
 fn validate_ttl_secs(path: &Path, ttl: u32) -> Result<(), ConfigError> {
     if !(60..=3600).contains(&ttl) {
         return Err(ConfigError::InvalidField {
@@ -0,0 +1,188 @@
+//! `seiro://capabilities`: what this host can build, so an orchestrator running several servers
+//! can pick one for a job.
+//!
+//! Like `seiro://status`, the resource is probed again on every read.
+use std::path::PathBuf;
+
+use rmcp::model::{AnnotateAble, RawResource, Resource, ResourceContents};
+use serde::Serialize;
+use tokio::process::Command;
+
+use crate::{
+    server::config::VisionOsConfig,
+    tools::visionos::{
+        free_disk_bytes, installed_sdks, installed_simulator_runtimes, list_xcode_installations,
+        ListXcodeInstallationsRequest, XcodeInstallation,
+    },
+};
+
+pub const CAPABILITIES_RESOURCE_URI: &str = "seiro://capabilities";
+const CAPABILITIES_MIME_TYPE: &str = "application/json";
+
+/// Body of the `seiro://capabilities` resource.
+#[derive(Debug, Clone, Serialize)]
+pub struct HostCapabilities {
+    pub server_version: &'static str,
+    pub os: HostOs,
+    /// Logical CPUs available to the server.
+    pub cpu_count: usize,
+    /// Physical memory; `None` when the OS does not report it.
+    pub memory_bytes: Option<u64>,
+    /// `visionos.xcode_path` first, then each `[[visionos.xcodes]]` entry.
+    pub xcodes: Vec<XcodeInstallation>,
+    /// SDKs of `visionos.xcode_path`, e.g. `xros26.2`.
+    pub sdks: Vec<String>,
+    /// Available visionOS simulator runtime versions, e.g. `26.2`.
+    pub simulator_runtimes: Vec<String>,
+    /// Free space on the volume of each allowed path, or `/` when `allowed_paths` is empty.
+    pub disks: Vec<DiskCapacity>,
+    pub max_concurrent_builds: u16,
+    /// Probes that failed, such as `sdks: ...`; the matching field is left empty.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Operating system of the host.
+#[derive(Debug, Clone, Serialize)]
+pub struct HostOs {
+    /// `macos` on a build host.
+    pub name: &'static str,
+    /// `sw_vers -productVersion`, e.g. `26.1`; `None` off macOS.
+    pub version: Option<String>,
+    pub arch: &'static str,
+}
+
+/// Free space where projects are built.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskCapacity {
+    pub path: PathBuf,
+    pub free_bytes: u64,
+}
+
+/// Entry for `resources/list`.
+pub fn capabilities_resource() -> Resource {
+    let mut raw = RawResource::new(CAPABILITIES_RESOURCE_URI, "host-capabilities");
+    raw.description = Some(
+        "macOS and Xcode versions, installed SDKs and simulator runtimes, CPU, memory, and free disk of this build host"
+            .into(),
+    );
+    raw.mime_type = Some(CAPABILITIES_MIME_TYPE.into());
+    raw.no_annotation()
+}
+
+/// Probe the host for the builds `config` allows.
+pub async fn collect_capabilities(config: &VisionOsConfig) -> HostCapabilities {
+    let mut errors = Vec::new();
+    let sdks = installed_sdks(&config.xcode_path).unwrap_or_else(|error| {
+        errors.push(format!("sdks: {error}"));
+        Vec::new()
+    });
+    let simulator_runtimes =
+        installed_simulator_runtimes(&config.xcode_path).unwrap_or_else(|error| {
+            errors.push(format!("simulator_runtimes: {error}"));
+            Vec::new()
+        });
+    let disk_paths = if config.allowed_paths.is_empty() {
+        vec![PathBuf::from("/")]
+    } else {
+        config.allowed_paths.clone()
+    };
+    let mut disks = Vec::new();
+    for path in disk_paths {
+        match free_disk_bytes(&path) {
+            Ok(free_bytes) => disks.push(DiskCapacity { path, free_bytes }),
+            Err(error) => errors.push(format!("disks: {}: {error}", path.display())),
+        }
+    }
+    HostCapabilities {
+        server_version: env!("CARGO_PKG_VERSION"),
+        os: HostOs {
+            name: std::env::consts::OS,
+            version: macos_version().await,
+            arch: std::env::consts::ARCH,
+        },
+        cpu_count: std::thread::available_parallelism().map_or(1, usize::from),
+        memory_bytes: physical_memory_bytes(),
+        xcodes: list_xcode_installations(ListXcodeInstallationsRequest::default(), config)
+            .await
+            .installations,
+        sdks,
+        simulator_runtimes,
+        disks,
+        max_concurrent_builds: config.max_concurrent_builds,
+        errors,
+    }
+}
+
+/// `resources/read` contents for `capabilities`.
+pub fn capabilities_contents(capabilities: &HostCapabilities) -> ResourceContents {
+    ResourceContents::TextResourceContents {
+        uri: CAPABILITIES_RESOURCE_URI.into(),
+        mime_type: Some(CAPABILITIES_MIME_TYPE.into()),
+        text: serde_json::to_string_pretty(capabilities).unwrap_or_default(),
+        meta: None,
+    }
+}
+
+async fn macos_version() -> Option<String> {
+    let output = Command::new("sw_vers")
+        .arg("-productVersion")
+        .kill_on_drop(true)
+        .output()
+        .await
+        .ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !version.is_empty()).then_some(version)
+}
+
+fn physical_memory_bytes() -> Option<u64> {
+    let pages = unsafe { libc::sysconf(libc::_SC_PHYS_PAGES) };
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    let pages = u64::try_from(pages).ok()?;
+    let page_size = u64::try_from(page_size).ok()?;
+    pages.checked_mul(page_size).filter(|bytes| *bytes > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    #[test]
+    fn capabilities_serialize_host_fields() {
+        let capabilities = HostCapabilities {
+            server_version: "0.0.0",
+            os: HostOs {
+                name: "macos",
+                version: Some("26.1".into()),
+                arch: "aarch64",
+            },
+            cpu_count: 12,
+            memory_bytes: physical_memory_bytes(),
+            xcodes: Vec::new(),
+            sdks: vec!["xros26.2".into()],
+            simulator_runtimes: vec!["26.2".into()],
+            disks: vec![DiskCapacity {
+                path: PathBuf::from("/"),
+                free_bytes: 1024,
+            }],
+            max_concurrent_builds: 2,
+            errors: Vec::new(),
+        };
+
+        let ResourceContents::TextResourceContents { text, .. } =
+            capabilities_contents(&capabilities)
+        else {
+            panic!("capabilities is a text resource");
+        };
+        let body: Value = serde_json::from_str(&text).expect("capabilities are JSON");
+
+        assert_eq!(body["os"]["version"], "26.1");
+        assert_eq!(body["cpu_count"], 12);
+        assert!(body["memory_bytes"].as_u64().is_some_and(|bytes| bytes > 0));
+        assert_eq!(body["simulator_runtimes"][0], "26.2");
+        assert_eq!(body["disks"][0]["free_bytes"], 1024);
+        assert!(body.get("errors").is_none());
+    }
+}
@@ -0,0 +1,483 @@
+//! What an authenticated client may call: the scopes and paths of the token it presented.
+//!
+//! stdio sessions and WebSocket sessions opened with `auth.token` are unrestricted. Sessions
+//! opened with an `[[auth.tokens]]` entry may only call tools in that entry's scopes, and only
+//! on project paths under its `allowed_paths` when it lists any.
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use rmcp::model::{ErrorData, JsonObject};
+use serde_json::{json, Value};
+
+use crate::{
+    lib::{
+        errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+        visionos::is_allowed_path,
+    },
+    server::config::{AuthSection, TokenScope},
+};
+
+/// Tools any authenticated client may call.
+const UNSCOPED_TOOLS: &[&str] = &["list_error_codes"];
+/// Scope each tool needs. Tools missing here need `admin`.
+const TOOL_SCOPES: &[(&str, TokenScope)] = &[
+    ("build_visionos_app", TokenScope::Build),
+    ("archive_visionos_app", TokenScope::Build),
+    ("test_visionos_app", TokenScope::Build),
+    ("run_pipeline", TokenScope::Build),
+    ("create_visionos_project", TokenScope::Build),
+    ("clean_visionos_project", TokenScope::Build),
+    ("cancel_build_job", TokenScope::Build),
+    ("extend_build_deadline", TokenScope::Build),
+    ("resolve_package_dependencies", TokenScope::Build),
+    ("export_visionos_ipa", TokenScope::Build),
+    ("boot_simulator", TokenScope::Build),
+    ("shutdown_simulator", TokenScope::Build),
+    ("capture_simulator_media", TokenScope::Build),
+    ("get_queue_status", TokenScope::Fetch),
+    ("fetch_build_output", TokenScope::Fetch),
+    ("fetch_build_log", TokenScope::Fetch),
+    ("list_build_jobs", TokenScope::Fetch),
+    ("inspect_build_diagnostics", TokenScope::Fetch),
+    ("diff_build_logs", TokenScope::Fetch),
+    ("export_artifact", TokenScope::Fetch),
+    ("export_job_trace", TokenScope::Fetch),
+    ("symbolicate_crash", TokenScope::Fetch),
+    ("validate_sandbox_policy", TokenScope::Sandbox),
+    ("inspect_xcode_sdks", TokenScope::Sandbox),
+    ("list_xcode_installations", TokenScope::Sandbox),
+    ("inspect_xcode_schemes", TokenScope::Sandbox),
+    ("list_allowed_projects", TokenScope::Sandbox),
+    ("describe_build_constraints", TokenScope::Sandbox),
+    ("list_project_schemes", TokenScope::Sandbox),
+    ("list_destinations", TokenScope::Sandbox),
+    ("show_build_settings", TokenScope::Sandbox),
+    ("list_simulators", TokenScope::Sandbox),
+    ("list_devices", TokenScope::Sandbox),
+    ("purge_artifacts", TokenScope::Admin),
+    ("list_sessions", TokenScope::Admin),
+];
+/// Scope each prompt needs: the scope of the first tool it walks the client through. Prompts
+/// missing here need `admin`.
+const PROMPT_SCOPES: &[(&str, TokenScope)] = &[
+    ("build_and_fetch", TokenScope::Build),
+    ("diagnose_build_failure", TokenScope::Fetch),
+];
+/// JSON pointers to arguments holding a project path that a token's `allowed_paths` must cover:
+/// the build tools' `project_path` and `workspace`, the same under `run_pipeline`'s `build`, and
+/// `create_visionos_project`'s `parent_path`.
+const PATH_ARGUMENTS: &[&str] = &[
+    "/project_path",
+    "/workspace",
+    "/build/project_path",
+    "/build/workspace",
+    "/parent_path",
+];
+
+const INSUFFICIENT_SCOPE_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "insufficient_scope",
+    "The token of this session does not grant the tool",
+    "Connect with a token whose scopes in [[auth.tokens]] include required_scope.",
+);
+const PATH_OUTSIDE_TOKEN_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "insufficient_scope",
+    "The token of this session does not grant the project path",
+    "Use a project under the token's allowed_paths, or connect with a token that covers it.",
+);
+/// Sent in the body of the `401` that refuses an upgrade with an expired `auth.previous_token`.
+pub(crate) const TOKEN_EXPIRED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "token_expired",
+    "The token was rotated out and is no longer accepted",
+    "Reconnect with the current auth.token; the old one stopped working at previous_token_valid_until.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&INSUFFICIENT_SCOPE_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&PATH_OUTSIDE_TOKEN_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&TOKEN_EXPIRED_ERROR, false, SandboxState::NotApplicable),
+];
+
+/// Why a presented token opened no session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenRejection {
+    /// Matches no configured token.
+    Unknown,
+    /// Matches `auth.previous_token`, which stopped being accepted at `valid_until`.
+    Expired { valid_until: DateTime<Utc> },
+}
+
+/// Token a client authenticated with, resolved against `[auth]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientAuthContext {
+    /// `name` of the matched `[[auth.tokens]]` entry, `auth.previous_token` for the token being
+    /// rotated out, `None` for `auth.token`.
+    pub token_name: Option<String>,
+    pub scopes: Vec<TokenScope>,
+    /// Empty when the token adds no path restriction.
+    pub allowed_paths: Vec<PathBuf>,
+}
+
+impl ClientAuthContext {
+    /// Context of the configured token equal to `presented` at `now`. Every token is compared,
+    /// each without stopping at the first differing byte.
+    pub fn resolve(
+        auth: &AuthSection,
+        presented: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Self, TokenRejection> {
+        let mut matched = None;
+        if auth
+            .token
+            .as_deref()
+            .is_some_and(|token| tokens_match(presented, token))
+        {
+            matched = Some(Ok(Self {
+                token_name: None,
+                scopes: vec![TokenScope::Admin],
+                allowed_paths: Vec::new(),
+            }));
+        }
+        if let (Some(previous), Some(valid_until)) =
+            (&auth.previous_token, auth.previous_token_valid_until)
+        {
+            if tokens_match(presented, previous) && matched.is_none() {
+                matched = Some(if now < valid_until {
+                    Ok(Self {
+                        token_name: Some("auth.previous_token".into()),
+                        scopes: vec![TokenScope::Admin],
+                        allowed_paths: Vec::new(),
+                    })
+                } else {
+                    Err(TokenRejection::Expired { valid_until })
+                });
+            }
+        }
+        for scoped in &auth.tokens {
+            if tokens_match(presented, &scoped.token) && matched.is_none() {
+                matched = Some(Ok(Self {
+                    token_name: Some(scoped.name.clone()),
+                    scopes: scoped.scopes.clone(),
+                    allowed_paths: scoped.allowed_paths.clone(),
+                }));
+            }
+        }
+        matched.unwrap_or(Err(TokenRejection::Unknown))
+    }
+
+    /// Refuse a call to `tool` that the token's scopes or paths do not cover.
+    pub fn authorize_call(
+        &self,
+        tool: &str,
+        arguments: Option<&JsonObject>,
+    ) -> Result<(), ErrorData> {
+        self.authorize(("tool", tool), required_scope(tool), arguments)
+    }
+
+    /// Refuse a prompt the token's scopes do not cover, or whose `project_path` lies outside
+    /// its paths.
+    pub fn authorize_prompt(
+        &self,
+        prompt: &str,
+        arguments: Option<&JsonObject>,
+    ) -> Result<(), ErrorData> {
+        let required = PROMPT_SCOPES
+            .iter()
+            .find(|(name, _)| *name == prompt)
+            .map_or(TokenScope::Admin, |(_, scope)| *scope);
+        self.authorize(("prompt", prompt), Some(required), arguments)
+    }
+
+    fn authorize(
+        &self,
+        target: (&str, &str),
+        required: Option<TokenScope>,
+        arguments: Option<&JsonObject>,
+    ) -> Result<(), ErrorData> {
+        if let Some(required) = required {
+            if !self.scopes.contains(&TokenScope::Admin) && !self.scopes.contains(&required) {
+                return Err(self.error(
+                    &INSUFFICIENT_SCOPE_ERROR,
+                    target,
+                    json!({ "required_scope": required.as_str() }),
+                ));
+            }
+        }
+        if self.allowed_paths.is_empty() {
+            return Ok(());
+        }
+        let arguments = arguments.map(|arguments| Value::Object(arguments.clone()));
+        for pointer in PATH_ARGUMENTS {
+            let Some(path) = arguments
+                .as_ref()
+                .and_then(|arguments| arguments.pointer(pointer))
+                .and_then(Value::as_str)
+            else {
+                continue;
+            };
+            if !is_allowed_path(Path::new(path), &self.allowed_paths) {
+                let argument = pointer.trim_start_matches('/').replace('/', ".");
+                return Err(self.error(
+                    &PATH_OUTSIDE_TOKEN_ERROR,
+                    target,
+                    json!({ "argument": argument, "path": path, "allowed_paths": self.allowed_paths }),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn error(
+        &self,
+        descriptor: &'static ToolErrorDescriptor,
+        (kind, name): (&str, &str),
+        details: Value,
+    ) -> ErrorData {
+        descriptor
+            .builder()
+            .details(details)
+            .with_context_field(kind, json!(name))
+            // Every key containing "token" is redacted, so the name goes out as `auth_entry`.
+            .with_context_field("auth_entry", json!(self.token_name))
+            .with_context_field(
+                "scopes",
+                json!(self
+                    .scopes
+                    .iter()
+                    .map(|scope| scope.as_str())
+                    .collect::<Vec<_>>()),
+            )
+            .sandbox_state(SandboxState::Blocked)
+            .retryable(false)
+            .build()
+            .expect("descriptor is valid")
+    }
+}
+
+/// Scope a call to `tool` needs; `None` for tools every client may call.
+pub fn required_scope(tool: &str) -> Option<TokenScope> {
+    if UNSCOPED_TOOLS.contains(&tool) {
+        return None;
+    }
+    Some(
+        TOOL_SCOPES
+            .iter()
+            .find(|(name, _)| *name == tool)
+            .map_or(TokenScope::Admin, |(_, scope)| *scope),
+    )
+}
+
+/// Compare without stopping at the first differing byte.
+pub fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (left, right)| diff | (left ^ right))
+            == 0
+}
+
+/// Whether `tool` has an explicit entry, so a new tool is not silently admin-only.
+#[cfg(test)]
+pub fn has_scope_entry(tool: &str) -> bool {
+    UNSCOPED_TOOLS.contains(&tool) || TOOL_SCOPES.iter().any(|(name, _)| *name == tool)
+}
+
+/// Whether `prompt` has an explicit entry, so a new prompt is not silently admin-only.
+#[cfg(test)]
+pub fn has_prompt_scope_entry(prompt: &str) -> bool {
+    PROMPT_SCOPES.iter().any(|(name, _)| *name == prompt)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::config::ScopedToken;
+
+    use super::*;
+
+    fn auth(allowed: &Path) -> AuthSection {
+        AuthSection {
+            token: Some("full-access-token-0001".into()),
+            previous_token: None,
+            previous_token_valid_until: None,
+            tokens: vec![ScopedToken {
+                name: "ci".into(),
+                token: "ci-fetch-token-000001".into(),
+                scopes: vec![TokenScope::Fetch, TokenScope::Build],
+                allowed_paths: vec![allowed.to_path_buf()],
+            }],
+        }
+    }
+
+    fn arguments(value: Value) -> JsonObject {
+        value.as_object().expect("object").clone()
+    }
+
+    fn code(error: &ErrorData) -> Option<&str> {
+        error.data.as_ref()?.get("code")?.as_str()
+    }
+
+    #[test]
+    fn scoped_tokens_only_reach_their_tools_and_paths() {
+        let work = tempfile::tempdir().expect("tempdir");
+        let allowed = work.path().join("allowed/App.xcodeproj");
+        let other = work.path().join("other/App.xcodeproj");
+        for project in [&allowed, &other] {
+            std::fs::create_dir_all(project).expect("create project");
+        }
+        let auth = auth(&work.path().join("allowed"));
+        assert_eq!(
+            ClientAuthContext::resolve(&auth, "unknown-token-000000001", Utc::now()),
+            Err(TokenRejection::Unknown)
+        );
+
+        let full =
+            ClientAuthContext::resolve(&auth, "full-access-token-0001", Utc::now()).expect("full");
+        assert_eq!(full.token_name, None);
+        assert!(full.authorize_call("purge_artifacts", None).is_ok());
+
+        let ci =
+            ClientAuthContext::resolve(&auth, "ci-fetch-token-000001", Utc::now()).expect("ci");
+        assert_eq!(ci.token_name.as_deref(), Some("ci"));
+        assert!(ci.authorize_call("fetch_build_log", None).is_ok());
+        assert!(ci.authorize_call("list_error_codes", None).is_ok());
+
+        let denied = ci
+            .authorize_call("validate_sandbox_policy", None)
+            .expect_err("sandbox scope is missing");
+        assert_eq!(code(&denied), Some("insufficient_scope"));
+        let data = denied.data.as_ref().expect("data");
+        assert_eq!(data["details"]["required_scope"], "sandbox");
+        assert_eq!(data["auth_entry"], "ci");
+        assert!(ci
+            .authorize_call("list_sessions", None)
+            .is_err_and(|err| code(&err) == Some("insufficient_scope")));
+
+        assert!(ci
+            .authorize_call(
+                "build_visionos_app",
+                Some(&arguments(json!({ "project_path": allowed })))
+            )
+            .is_ok());
+        let outside = ci
+            .authorize_call(
+                "build_visionos_app",
+                Some(&arguments(json!({ "project_path": other }))),
+            )
+            .expect_err("path outside the token's allowed_paths");
+        assert_eq!(code(&outside), Some("insufficient_scope"));
+        assert_eq!(
+            outside.data.as_ref().expect("data")["details"]["argument"],
+            "project_path"
+        );
+
+        let pipeline = ci
+            .authorize_call(
+                "run_pipeline",
+                Some(&arguments(json!({
+                    "steps": ["build"],
+                    "build": { "scheme": "App", "project_path": other },
+                }))),
+            )
+            .expect_err("pipeline project outside the token's allowed_paths");
+        assert_eq!(code(&pipeline), Some("insufficient_scope"));
+        assert_eq!(
+            pipeline.data.as_ref().expect("data")["details"]["argument"],
+            "build.project_path"
+        );
+        assert!(ci
+            .authorize_call(
+                "run_pipeline",
+                Some(&arguments(json!({
+                    "steps": ["build"],
+                    "build": { "scheme": "App", "project_path": allowed },
+                }))),
+            )
+            .is_ok());
+
+        let scaffold = ci
+            .authorize_call(
+                "create_visionos_project",
+                Some(&arguments(
+                    json!({ "parent_path": work.path().join("other"), "name": "New" }),
+                )),
+            )
+            .expect_err("parent outside the token's allowed_paths");
+        assert_eq!(code(&scaffold), Some("insufficient_scope"));
+        assert_eq!(
+            scaffold.data.as_ref().expect("data")["details"]["argument"],
+            "parent_path"
+        );
+    }
+
+    #[test]
+    fn prompts_need_the_scope_of_their_first_tool() {
+        let work = tempfile::tempdir().expect("tempdir");
+        let other = work.path().join("other/App.xcodeproj");
+        std::fs::create_dir_all(&other).expect("create project");
+        let mut auth = auth(&work.path().join("allowed"));
+        auth.tokens[0].scopes = vec![TokenScope::Fetch];
+        let fetch = ClientAuthContext::resolve(&auth, "ci-fetch-token-000001", Utc::now())
+            .expect("fetch token");
+
+        assert!(fetch
+            .authorize_prompt("diagnose_build_failure", None)
+            .is_ok());
+        let denied = fetch
+            .authorize_prompt("build_and_fetch", None)
+            .expect_err("build scope is missing");
+        assert_eq!(
+            denied.data.as_ref().expect("data")["details"]["required_scope"],
+            "build"
+        );
+        assert_eq!(
+            denied.data.as_ref().expect("data")["prompt"],
+            "build_and_fetch"
+        );
+        assert!(fetch.authorize_prompt("future_prompt", None).is_err());
+
+        auth.tokens[0].scopes = vec![TokenScope::Build];
+        let build = ClientAuthContext::resolve(&auth, "ci-fetch-token-000001", Utc::now())
+            .expect("build token");
+        assert!(build
+            .authorize_prompt(
+                "build_and_fetch",
+                Some(&arguments(
+                    json!({ "scheme": "App", "project_path": other })
+                )),
+            )
+            .is_err_and(|err| code(&err) == Some("insufficient_scope")));
+    }
+
+    #[test]
+    fn previous_token_is_accepted_until_it_expires() {
+        let valid_until = DateTime::parse_from_rfc3339("2026-11-01T00:00:00Z")
+            .expect("timestamp")
+            .with_timezone(&Utc);
+        let mut auth = auth(Path::new("/"));
+        auth.previous_token = Some("old-full-access-token".into());
+        auth.previous_token_valid_until = Some(valid_until);
+
+        let before = valid_until - chrono::Duration::seconds(1);
+        let previous = ClientAuthContext::resolve(&auth, "old-full-access-token", before)
+            .expect("previous token before its expiry");
+        assert_eq!(previous.token_name.as_deref(), Some("auth.previous_token"));
+        assert!(previous.authorize_call("purge_artifacts", None).is_ok());
+        assert!(ClientAuthContext::resolve(&auth, "full-access-token-0001", before).is_ok());
+
+        assert_eq!(
+            ClientAuthContext::resolve(&auth, "old-full-access-token", valid_until),
+            Err(TokenRejection::Expired { valid_until })
+        );
+        assert!(ClientAuthContext::resolve(&auth, "full-access-token-0001", valid_until).is_ok());
+    }
+
+    #[test]
+    fn unknown_tools_need_admin() {
+        assert_eq!(required_scope("future_tool"), Some(TokenScope::Admin));
+        assert_eq!(required_scope("list_error_codes"), None);
+        assert_eq!(
+            required_scope("fetch_build_output"),
+            Some(TokenScope::Fetch)
+        );
+    }
+}
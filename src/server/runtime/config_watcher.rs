@@ -0,0 +1,251 @@
+//! Reload of the config file while the server runs.
+//!
+//! Tools read the config through [`SharedConfig`] at the start of each call, so allowlist edits
+//! apply to the next call without dropping the MCP session. Settings baked into the job queue,
+//! artifact store, and tool router at startup keep their startup values until a restart.
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::server::config::ServerConfig;
+
+/// Editors often write a file in several steps; wait for them to settle before reloading.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Config shared by a server and its clones, swapped as a whole on reload.
+#[derive(Debug, Clone)]
+pub struct SharedConfig(Arc<RwLock<Arc<ServerConfig>>>);
+
+impl SharedConfig {
+    pub fn new(config: ServerConfig) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(config))))
+    }
+
+    pub fn current(&self) -> Arc<ServerConfig> {
+        self.0
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Swap in `config`, keeping the startup-only settings of the current one. Returns the
+    /// names of startup-only settings that `config` changed and that need a restart.
+    pub fn replace(&self, mut config: ServerConfig) -> Vec<&'static str> {
+        let mut current = self
+            .0
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let restart_required = keep_startup_settings(&current, &mut config);
+        *current = Arc::new(config);
+        restart_required
+    }
+}
+
+/// Watches the config file and swaps valid edits into a [`SharedConfig`]. Stops when dropped.
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Watch `path` (through its directory, so editors that replace the file are seen too).
+    /// Must be called inside a Tokio runtime.
+    pub fn spawn(shared: SharedConfig, path: PathBuf) -> notify::Result<Self> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let file_name = path.file_name().map(|name| name.to_os_string());
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                let touches_config = event
+                    .paths
+                    .iter()
+                    .any(|changed| changed.file_name() == file_name.as_deref());
+                if touches_config && !matches!(event.kind, EventKind::Access(_)) {
+                    let _ = tx.send(());
+                }
+            })?;
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        tokio::spawn(reload_on_change(shared, path, rx));
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+async fn reload_on_change(
+    shared: SharedConfig,
+    path: PathBuf,
+    mut changes: mpsc::UnboundedReceiver<()>,
+) {
+    while changes.recv().await.is_some() {
+        tokio::time::sleep(RELOAD_DEBOUNCE).await;
+        while changes.try_recv().is_ok() {}
+        if !path.is_file() {
+            // Mid-replace; the write that recreates the file triggers another reload.
+            continue;
+        }
+        match ServerConfig::load_with_profile(path.clone(), shared.current().profile.clone()) {
+            Ok(config) => {
+                crate::lib::telemetry::set_redaction(config.redaction.policy());
+                let restart_required = shared.replace(config);
+                info!(
+                    target: "rmcp_sample::runtime",
+                    path = %path.display(),
+                    "Reloaded configuration"
+                );
+                if !restart_required.is_empty() {
+                    warn!(
+                        target: "rmcp_sample::runtime",
+                        settings = ?restart_required,
+                        "Changed settings take effect after a restart"
+                    );
+                }
+            }
+            Err(err) => warn!(
+                target: "rmcp_sample::runtime",
+                path = %path.display(),
+                reason = %err,
+                "Keeping the previous configuration"
+            ),
+        }
+    }
+}
+
+/// Copy the settings the job queue, artifact store, and tool router read once at startup from `current`
+/// into `next`, returning the ones `next` wanted to change.
+fn keep_startup_settings(current: &ServerConfig, next: &mut ServerConfig) -> Vec<&'static str> {
+    let mut changed = Vec::new();
+    let mut record = |name: &'static str, differs: bool| {
+        if differs {
+            changed.push(name);
+        }
+    };
+    record("server.host", next.server.host != current.server.host);
+    record("server.port", next.server.port != current.server.port);
+    record(
+        "server.shutdown_grace_secs",
+        next.server.shutdown_grace_secs != current.server.shutdown_grace_secs,
+    );
+    record("artifacts", next.artifacts != current.artifacts);
+    record(
+        "visionos.artifact_persistence",
+        next.visionos.artifact_persistence != current.visionos.artifact_persistence,
+    );
+    record(
+        "visionos.max_concurrent_builds",
+        next.visionos.max_concurrent_builds != current.visionos.max_concurrent_builds,
+    );
+    record(
+        "visionos.artifact_ttl_secs",
+        next.visionos.artifact_ttl_secs != current.visionos.artifact_ttl_secs,
+    );
+    record(
+        "visionos.cleanup_schedule_secs",
+        next.visionos.cleanup_schedule_secs != current.visionos.cleanup_schedule_secs,
+    );
+    record(
+        "visionos.artifact_max_jobs",
+        next.visionos.artifact_max_jobs != current.visionos.artifact_max_jobs,
+    );
+    record(
+        "visionos.artifact_max_total_bytes",
+        next.visionos.artifact_max_total_bytes != current.visionos.artifact_max_total_bytes,
+    );
+    record("tools.enabled", next.tools.enabled != current.tools.enabled);
+    record(
+        "tools.disabled",
+        next.tools.disabled != current.tools.disabled,
+    );
+    record("tools.admin", next.tools.admin != current.tools.admin);
+    next.server.host = current.server.host.clone();
+    next.server.port = current.server.port;
+    next.server.shutdown_grace_secs = current.server.shutdown_grace_secs;
+    next.artifacts = current.artifacts.clone();
+    next.tools.enabled = current.tools.enabled.clone();
+    next.tools.disabled = current.tools.disabled.clone();
+    next.tools.admin = current.tools.admin;
+    next.visionos.artifact_persistence = current.visionos.artifact_persistence;
+    next.visionos.max_concurrent_builds = current.visionos.max_concurrent_builds;
+    next.visionos.artifact_ttl_secs = current.visionos.artifact_ttl_secs;
+    next.visionos.cleanup_schedule_secs = current.visionos.cleanup_schedule_secs;
+    next.visionos.artifact_max_jobs = current.visionos.artifact_max_jobs;
+    next.visionos.artifact_max_total_bytes = current.visionos.artifact_max_total_bytes;
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::server::config::SchemePattern;
+
+    use super::*;
+
+    const VALID_CONFIG: &str = include_str!("../../../tests/fixtures/config_valid.toml");
+
+    async fn wait_for(shared: &SharedConfig, check: impl Fn(&ServerConfig) -> bool) -> bool {
+        for _ in 0..100 {
+            if check(&shared.current()) {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        false
+    }
+
+    #[test]
+    fn replace_keeps_startup_only_settings() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("seiro-mcp.toml");
+        fs::write(&path, VALID_CONFIG).expect("write config");
+        let config = ServerConfig::load_from_path(path).expect("config loads");
+        let shared = SharedConfig::new(config.clone());
+
+        let mut next = config;
+        next.visionos.allowed_schemes = vec![SchemePattern::new("OtherApp").expect("valid scheme")];
+        next.visionos.max_concurrent_builds += 1;
+        let restart_required = shared.replace(next);
+
+        let current = shared.current();
+        assert_eq!(current.visionos.allowed_schemes, vec!["OtherApp"]);
+        assert_eq!(current.visionos.max_concurrent_builds, 1);
+        assert_eq!(restart_required, vec!["visionos.max_concurrent_builds"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn watcher_swaps_in_valid_edits_and_ignores_invalid_ones() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("seiro-mcp.toml");
+        fs::write(&path, VALID_CONFIG).expect("write config");
+        let shared = SharedConfig::new(ServerConfig::load_from_path(path.clone()).expect("loads"));
+        let _watcher = ConfigWatcher::spawn(shared.clone(), path.clone()).expect("watcher starts");
+
+        fs::write(&path, "[visionos]\nallowed_paths = \"not a list\"\n").expect("write config");
+        tokio::time::sleep(RELOAD_DEBOUNCE * 3).await;
+        assert!(
+            shared.current().visionos.allowed_schemes.len() > 1,
+            "an invalid edit keeps the previous config"
+        );
+
+        let edited = VALID_CONFIG.replace(
+            "allowed_schemes = [\"VisionApp\", \"VisionToolbox\"]",
+            "allowed_schemes = [\"VisionApp\"]",
+        );
+        assert_ne!(edited, VALID_CONFIG);
+        fs::write(&path, edited).expect("write config");
+        let reloaded = wait_for(&shared, |config| {
+            config.visionos.allowed_schemes == ["VisionApp"]
+        })
+        .await;
+        assert!(reloaded, "the edit is picked up without a restart");
+    }
+}
@@ -0,0 +1,151 @@
+//! `list_error_codes`: every error descriptor the server can return, with the `retryable` and
+//! `sandbox_state` its call sites report, so clients can build error handling against a fixed
+//! catalog instead of the errors they happened to see.
+use std::collections::BTreeSet;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lib::errors::{self, ErrorCatalogEntry, SandboxState},
+    server::runtime::{client_auth, federation, rate_limit, schema_version},
+    tools::visionos::{
+        artifacts, build, dependencies, devices, diagnostics, ipa, pipeline, sandbox, scaffold,
+        schemes, simulator,
+    },
+};
+
+/// Every module that declares error descriptors. A descriptor that is not listed through one of
+/// these fails `catalog_lists_every_descriptor_in_the_source`.
+const MODULE_CATALOGS: &[&[ErrorCatalogEntry]] = &[
+    errors::ERROR_CATALOG,
+    client_auth::ERROR_CATALOG,
+    federation::ERROR_CATALOG,
+    rate_limit::ERROR_CATALOG,
+    schema_version::ERROR_CATALOG,
+    artifacts::ERROR_CATALOG,
+    artifacts::export::ERROR_CATALOG,
+    artifacts::logs::ERROR_CATALOG,
+    artifacts::purge::ERROR_CATALOG,
+    artifacts::symbolicate::ERROR_CATALOG,
+    artifacts::trace::ERROR_CATALOG,
+    build::cancel::ERROR_CATALOG,
+    build::deadline::ERROR_CATALOG,
+    build::executor::ERROR_CATALOG,
+    dependencies::ERROR_CATALOG,
+    devices::ERROR_CATALOG,
+    diagnostics::ERROR_CATALOG,
+    ipa::ERROR_CATALOG,
+    pipeline::ERROR_CATALOG,
+    sandbox::ERROR_CATALOG,
+    scaffold::ERROR_CATALOG,
+    schemes::ERROR_CATALOG,
+    schemes::build_settings::ERROR_CATALOG,
+    schemes::destinations::ERROR_CATALOG,
+    simulator::ERROR_CATALOG,
+    simulator::media::ERROR_CATALOG,
+];
+
+/// Input for `list_error_codes`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListErrorCodesRequest {}
+
+/// One error the server can return. Tools that share a code (e.g. `invalid_request`) may word
+/// the message differently; each wording is listed once.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, JsonSchema)]
+pub struct ErrorCode {
+    /// Value of `code` in the error data.
+    pub code: String,
+    pub message: String,
+    pub remediation: String,
+    /// Value of `retryable` in the error data.
+    pub retryable: bool,
+    /// Value of `sandbox_state` in the error data.
+    pub sandbox_state: SandboxState,
+}
+
+/// Response from `list_error_codes`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ListErrorCodesResponse {
+    /// Sorted by code.
+    pub errors: Vec<ErrorCode>,
+}
+
+pub fn list_error_codes(_request: ListErrorCodesRequest) -> ListErrorCodesResponse {
+    let errors: BTreeSet<ErrorCode> = MODULE_CATALOGS
+        .iter()
+        .flat_map(|catalog| catalog.iter())
+        .map(|entry| ErrorCode {
+            code: entry.descriptor.code.to_string(),
+            message: entry.descriptor.message.to_string(),
+            remediation: entry.descriptor.remediation.to_string(),
+            retryable: entry.retryable,
+            sandbox_state: entry.sandbox_state,
+        })
+        .collect();
+    ListErrorCodesResponse {
+        errors: errors.into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fs, path::Path};
+
+    use regex::Regex;
+
+    use super::*;
+
+    /// Descriptors defined only by the tests in `lib/errors.rs`.
+    const TEST_ONLY_CODES: &[&str] = &["sample_error", "blank"];
+
+    fn collect_descriptors(dir: &Path, pattern: &Regex, found: &mut BTreeSet<(String, String)>) {
+        for entry in fs::read_dir(dir).expect("read src dir").flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                collect_descriptors(&path, pattern, found);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                let source = fs::read_to_string(&path).expect("read source");
+                for captures in pattern.captures_iter(&source) {
+                    found.insert((captures[1].to_string(), captures[2].replace("\\\"", "\"")));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn catalog_lists_every_descriptor_in_the_source() {
+        let pattern =
+            Regex::new(r#"ToolErrorDescriptor::new\(\s*"([^"]*)",\s*"((?:[^"\\]|\\.)*)""#)
+                .expect("descriptor pattern");
+        let mut in_source = BTreeSet::new();
+        collect_descriptors(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &pattern,
+            &mut in_source,
+        );
+        in_source.retain(|(code, _)| !TEST_ONLY_CODES.contains(&code.as_str()));
+        assert!(!in_source.is_empty());
+
+        let errors = list_error_codes(ListErrorCodesRequest {}).errors;
+        let listed: BTreeSet<(String, String)> = errors
+            .iter()
+            .map(|error| (error.code.clone(), error.message.clone()))
+            .collect();
+        let missing: Vec<_> = in_source.difference(&listed).collect();
+        assert!(
+            missing.is_empty(),
+            "descriptors missing from the catalog: {missing:?}"
+        );
+
+        let mut defaults = BTreeMap::new();
+        for error in &errors {
+            let previous = defaults.insert(&error.code, (error.retryable, error.sandbox_state));
+            assert!(
+                previous.is_none_or(|previous| previous == (error.retryable, error.sandbox_state)),
+                "`{}` is listed with different retryable or sandbox_state values",
+                error.code
+            );
+        }
+    }
+}
@@ -0,0 +1,508 @@
+//! Federation: forward builds to the seiro-mcp servers in `[[federation.downstreams]]` and route
+//! the follow-up job tools and artifact fetches back to the server that ran the job, so one MCP
+//! entry point spreads builds over several Macs.
+//!
+//! Each downstream is a child process (`command` and `args`) spoken to over stdio. It is started
+//! on first use and again after it exits. Downstreams are read at startup; a config reload does
+//! not change them.
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    process::Stdio,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rmcp::{
+    model::{
+        CallToolRequest, CallToolRequestParam, CallToolResult, ClientRequest, ErrorData, Meta,
+        ProgressNotificationParam, ProgressToken, ReadResourceRequestParam, ResourceContents,
+        ServerResult,
+    },
+    service::{NotificationContext, PeerRequestOptions, RunningService, ServiceError},
+    ClientHandler, Peer, RoleClient, RoleServer, ServiceExt,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tokio::process::{Child, Command};
+use uuid::Uuid;
+
+use crate::{
+    lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+    server::config::{Downstream, FederationConfig},
+    tools::visionos::{self, FetchBuildOutputRequest, VisionOsArtifactStore},
+};
+
+/// `seiro://artifacts/<job_id>`: the artifact zip of a finished job, base64 encoded.
+pub const ARTIFACT_RESOURCE_PREFIX: &str = "seiro://artifacts/";
+const ARTIFACT_MIME_TYPE: &str = "application/zip";
+/// Variable carrying `token` into the downstream command.
+const TOKEN_ENV: &str = "SEIRO_MCP_TOKEN";
+/// Tools that start a job on the least busy downstream.
+const BUILD_TOOLS: &[&str] = &["build_visionos_app"];
+/// Tools routed to the downstream that ran the job named by their `job_id`.
+const JOB_TOOLS: &[&str] = &[
+    "fetch_build_output",
+    "fetch_build_log",
+    "cancel_build_job",
+    "extend_build_deadline",
+    "inspect_build_diagnostics",
+];
+
+const DOWNSTREAM_UNAVAILABLE_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "downstream_unavailable",
+    "No downstream build server could take the call",
+    "Run the [[federation.downstreams]] command by hand to check that it starts seiro-mcp, then retry.",
+);
+const DOWNSTREAM_ARTIFACT_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "downstream_artifact_failed",
+    "The artifact could not be copied back from the downstream build server",
+    "Retry fetch_build_output; if the checksum keeps differing, rebuild the job.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(
+        &DOWNSTREAM_UNAVAILABLE_ERROR,
+        true,
+        SandboxState::NotApplicable,
+    ),
+    ErrorCatalogEntry::new(
+        &DOWNSTREAM_ARTIFACT_ERROR,
+        true,
+        SandboxState::NotApplicable,
+    ),
+];
+
+/// Downstream connections shared by every call on the server, with the jobs each one ran.
+#[derive(Clone, Default)]
+pub struct Federation(Arc<FederationState>);
+
+#[derive(Default)]
+struct FederationState {
+    downstreams: Vec<DownstreamLink>,
+    /// Index into `downstreams` of every forwarded job.
+    jobs: Mutex<HashMap<String, usize>>,
+    progress: ProgressRelay,
+}
+
+struct DownstreamLink {
+    config: Downstream,
+    connection: tokio::sync::Mutex<Option<Connection>>,
+    in_flight: AtomicUsize,
+}
+
+struct Connection {
+    client: RunningService<RoleClient, ProgressRelay>,
+    _child: Child,
+}
+
+/// Relays downstream `notifications/progress` to the client waiting on the same token.
+#[derive(Clone, Default)]
+struct ProgressRelay(Arc<Mutex<HashMap<ProgressToken, Peer<RoleServer>>>>);
+
+impl ProgressRelay {
+    fn routes(&self) -> std::sync::MutexGuard<'_, HashMap<ProgressToken, Peer<RoleServer>>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl ClientHandler for ProgressRelay {
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let peer = self.routes().get(&params.progress_token).cloned();
+        if let Some(peer) = peer {
+            let _ = peer.notify_progress(params).await;
+        }
+    }
+}
+
+impl Federation {
+    pub fn new(config: &FederationConfig) -> Self {
+        let downstreams = config
+            .downstreams
+            .iter()
+            .map(|downstream| DownstreamLink {
+                config: downstream.clone(),
+                connection: tokio::sync::Mutex::new(None),
+                in_flight: AtomicUsize::new(0),
+            })
+            .collect();
+        Self(Arc::new(FederationState {
+            downstreams,
+            ..FederationState::default()
+        }))
+    }
+
+    /// Run `request` on a downstream, or return `None` when it belongs to this server: every
+    /// tool but the build and job tools, and job tools naming a job that was not forwarded.
+    /// Artifacts of a forwarded `fetch_build_output` are copied under `artifact_root`.
+    pub async fn forward(
+        &self,
+        request: &CallToolRequestParam,
+        meta: &Meta,
+        peer: &Peer<RoleServer>,
+        artifact_root: &Path,
+    ) -> Option<Result<CallToolResult, ErrorData>> {
+        if self.0.downstreams.is_empty() {
+            return None;
+        }
+        let tool = request.name.as_ref();
+        let job_id = request
+            .arguments
+            .as_ref()
+            .and_then(|arguments| arguments.get("job_id"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        if BUILD_TOOLS.contains(&tool) {
+            return Some(self.forward_build(request, job_id, meta, peer).await);
+        }
+        if !JOB_TOOLS.contains(&tool) {
+            return None;
+        }
+        let index = self.jobs().get(&job_id?).copied()?;
+        let link = &self.0.downstreams[index];
+        let result = self.call(link, request, meta, peer).await;
+        Some(match result {
+            Ok(result) if tool == "fetch_build_output" => {
+                self.pull_artifact(link, result, artifact_root).await
+            }
+            other => other,
+        })
+    }
+
+    /// Send a build to the downstream with the fewest forwarded calls running, in config order
+    /// on a tie, skipping downstreams that fail to start.
+    async fn forward_build(
+        &self,
+        request: &CallToolRequestParam,
+        job_id: Option<String>,
+        meta: &Meta,
+        peer: &Peer<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut order: Vec<usize> = (0..self.0.downstreams.len()).collect();
+        order.sort_by_key(|&index| self.0.downstreams[index].in_flight.load(Ordering::SeqCst));
+        let mut failures = Vec::new();
+        for index in order {
+            let link = &self.0.downstreams[index];
+            if let Err(error) = link.peer(&self.0.progress).await {
+                tracing::warn!(
+                    target: "rmcp_sample::federation",
+                    downstream = %link.config.name,
+                    error = %error,
+                    "Skipping downstream that failed to start"
+                );
+                failures.push(json!({ "downstream": link.config.name, "error": error }));
+                continue;
+            }
+            // Known before the build returns, so cancel_build_job can reach it meanwhile.
+            if let Some(job_id) = &job_id {
+                self.jobs().insert(job_id.clone(), index);
+            }
+            let result = self.call(link, request, meta, peer).await;
+            if let Some(job_id) = result
+                .as_ref()
+                .ok()
+                .and_then(|result| result.structured_content.as_ref())
+                .and_then(|output| output.get("job_id"))
+                .and_then(Value::as_str)
+            {
+                self.jobs().insert(job_id.to_string(), index);
+            }
+            return result;
+        }
+        Err(downstream_unavailable(json!({ "downstreams": failures })))
+    }
+
+    /// Call the tool on `link`, relaying progress for the caller's token and tagging the result
+    /// with the downstream's name.
+    async fn call(
+        &self,
+        link: &DownstreamLink,
+        request: &CallToolRequestParam,
+        meta: &Meta,
+        peer: &Peer<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let downstream = link.peer(&self.0.progress).await.map_err(|error| {
+            downstream_unavailable(json!({ "downstream": link.config.name, "error": error }))
+        })?;
+        let _running = InFlight::start(link, &self.0.progress, meta.get_progress_token(), peer);
+        let options = PeerRequestOptions {
+            timeout: None,
+            meta: Some(meta.clone()),
+        };
+        let outcome = async {
+            downstream
+                .send_request_with_option(
+                    ClientRequest::CallToolRequest(CallToolRequest::new(request.clone())),
+                    options,
+                )
+                .await?
+                .await_response()
+                .await
+        }
+        .await;
+
+        match outcome {
+            Ok(ServerResult::CallToolResult(mut result)) => {
+                if let Some(output) = result
+                    .structured_content
+                    .as_mut()
+                    .and_then(Value::as_object_mut)
+                {
+                    output.insert("downstream".into(), json!(link.config.name));
+                }
+                Ok(result)
+            }
+            Ok(_) => Err(downstream_unavailable(json!({
+                "downstream": link.config.name,
+                "error": "unexpected response to tools/call",
+            }))),
+            Err(ServiceError::McpError(error)) => Err(error),
+            Err(error) => {
+                link.disconnect().await;
+                Err(downstream_unavailable(
+                    json!({ "downstream": link.config.name, "error": error.to_string() }),
+                ))
+            }
+        }
+    }
+
+    /// Copy the artifact zip named in a downstream `fetch_build_output` result to
+    /// `<artifact_root>/federation/<job_id>/` and point `artifact_zip` at the copy. Results
+    /// carrying a `download_url` instead are returned as they are.
+    async fn pull_artifact(
+        &self,
+        link: &DownstreamLink,
+        mut result: CallToolResult,
+        artifact_root: &Path,
+    ) -> Result<CallToolResult, ErrorData> {
+        let Some(output) = result
+            .structured_content
+            .as_mut()
+            .and_then(Value::as_object_mut)
+        else {
+            return Ok(result);
+        };
+        let (Some(job_id), Some(remote_zip)) = (
+            output
+                .get("job_id")
+                .and_then(Value::as_str)
+                .and_then(|job_id| Uuid::parse_str(job_id).ok()),
+            output.get("artifact_zip").and_then(Value::as_str),
+        ) else {
+            return Ok(result);
+        };
+        let file_name = Path::new(remote_zip)
+            .file_name()
+            .map(|name| name.to_os_string())
+            .unwrap_or_else(|| "artifact.zip".into());
+        let local_zip = artifact_root
+            .join("federation")
+            .join(job_id.to_string())
+            .join(file_name);
+        let artifact_error = |error: String| {
+            artifact_failed(json!({
+                "downstream": link.config.name,
+                "job_id": job_id,
+                "error": error,
+            }))
+        };
+
+        let downstream = link.peer(&self.0.progress).await.map_err(artifact_error)?;
+        let contents = downstream
+            .read_resource(ReadResourceRequestParam {
+                uri: format!("{ARTIFACT_RESOURCE_PREFIX}{job_id}"),
+            })
+            .await
+            .map_err(|error| artifact_error(error.to_string()))?
+            .contents;
+        let Some(ResourceContents::BlobResourceContents { blob, .. }) = contents.into_iter().next()
+        else {
+            return Err(artifact_error("artifact resource is not a blob".into()));
+        };
+        let bytes = STANDARD
+            .decode(blob)
+            .map_err(|error| artifact_error(format!("invalid base64: {error}")))?;
+        if let Some(expected) = output.get("sha256").and_then(Value::as_str) {
+            let actual = format!("{:x}", Sha256::digest(&bytes));
+            if actual != expected {
+                return Err(artifact_error(format!(
+                    "sha256 {actual} does not match {expected}"
+                )));
+            }
+        }
+        if let Some(parent) = local_zip.parent() {
+            fs::create_dir_all(parent).map_err(|error| artifact_error(error.to_string()))?;
+        }
+        fs::write(&local_zip, bytes).map_err(|error| artifact_error(error.to_string()))?;
+
+        output.insert(
+            "artifact_zip".into(),
+            json!(local_zip.to_string_lossy().to_string()),
+        );
+        Ok(result)
+    }
+
+    fn jobs(&self) -> std::sync::MutexGuard<'_, HashMap<String, usize>> {
+        self.0
+            .jobs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A forwarded call counted against its downstream; undoes the count and the progress route when
+/// the call finishes or is dropped.
+struct InFlight<'a> {
+    link: &'a DownstreamLink,
+    relay: &'a ProgressRelay,
+    progress_token: Option<ProgressToken>,
+}
+
+impl<'a> InFlight<'a> {
+    fn start(
+        link: &'a DownstreamLink,
+        relay: &'a ProgressRelay,
+        progress_token: Option<ProgressToken>,
+        peer: &Peer<RoleServer>,
+    ) -> Self {
+        link.in_flight.fetch_add(1, Ordering::SeqCst);
+        if let Some(token) = &progress_token {
+            relay.routes().insert(token.clone(), peer.clone());
+        }
+        Self {
+            link,
+            relay,
+            progress_token,
+        }
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.link.in_flight.fetch_sub(1, Ordering::SeqCst);
+        if let Some(token) = &self.progress_token {
+            self.relay.routes().remove(token);
+        }
+    }
+}
+
+impl DownstreamLink {
+    /// Peer of the running downstream, starting `command` if it is not running.
+    async fn peer(&self, relay: &ProgressRelay) -> Result<Peer<RoleClient>, String> {
+        let mut connection = self.connection.lock().await;
+        if let Some(running) = connection
+            .as_ref()
+            .filter(|running| !running.client.is_transport_closed())
+        {
+            return Ok(running.client.peer().clone());
+        }
+        let mut command = Command::new(&self.config.command);
+        command
+            .args(&self.config.args)
+            .envs(&self.config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(token) = &self.config.token {
+            command.env(TOKEN_ENV, token);
+        }
+        let mut child = command.spawn().map_err(|error| {
+            format!("failed to start {}: {error}", self.config.command.display())
+        })?;
+        let stdout = child.stdout.take().expect("downstream stdout is piped");
+        let stdin = child.stdin.take().expect("downstream stdin is piped");
+        let client = relay
+            .clone()
+            .serve((stdout, stdin))
+            .await
+            .map_err(|error| format!("MCP handshake failed: {error}"))?;
+        let peer = client.peer().clone();
+        tracing::info!(
+            target: "rmcp_sample::federation",
+            downstream = %self.config.name,
+            "Connected to downstream"
+        );
+        *connection = Some(Connection {
+            client,
+            _child: child,
+        });
+        Ok(peer)
+    }
+
+    /// Drop the connection so the next call starts the downstream again.
+    async fn disconnect(&self) {
+        self.connection.lock().await.take();
+    }
+}
+
+/// `resources/read` contents of `seiro://artifacts/<job_id>`, or `None` for other URIs. This is
+/// how a federating server copies the artifact of a job it forwarded here.
+pub async fn artifact_contents(
+    store: &VisionOsArtifactStore,
+    uri: &str,
+) -> Option<Result<ResourceContents, ErrorData>> {
+    let job_id = uri.strip_prefix(ARTIFACT_RESOURCE_PREFIX)?;
+    Some(read_artifact(store, uri, job_id).await)
+}
+
+async fn read_artifact(
+    store: &VisionOsArtifactStore,
+    uri: &str,
+    job_id: &str,
+) -> Result<ResourceContents, ErrorData> {
+    let output = visionos::fetch_build_output(
+        store,
+        FetchBuildOutputRequest {
+            job_id: job_id.to_string(),
+            scheme: None,
+            include_logs: false,
+        },
+    )
+    .await
+    .map_err(visionos::fetch_error_to_error_data)?;
+    let Some(artifact_zip) = output.artifact_zip else {
+        return Err(ErrorData::resource_not_found(
+            format!("job {job_id} has no local artifact zip; use its download_url"),
+            None,
+        ));
+    };
+    let bytes = fs::read(&artifact_zip).map_err(|error| {
+        ErrorData::internal_error(format!("failed to read {artifact_zip}: {error}"), None)
+    })?;
+    Ok(ResourceContents::BlobResourceContents {
+        uri: uri.to_string(),
+        mime_type: Some(ARTIFACT_MIME_TYPE.into()),
+        blob: STANDARD.encode(bytes),
+        meta: None,
+    })
+}
+
+fn downstream_unavailable(details: Value) -> ErrorData {
+    DOWNSTREAM_UNAVAILABLE_ERROR
+        .builder()
+        .details(details)
+        .sandbox_state(SandboxState::NotApplicable)
+        .retryable(true)
+        .build()
+        .expect("descriptor is valid")
+}
+
+fn artifact_failed(details: Value) -> ErrorData {
+    DOWNSTREAM_ARTIFACT_ERROR
+        .builder()
+        .details(details)
+        .sandbox_state(SandboxState::NotApplicable)
+        .retryable(true)
+        .build()
+        .expect("descriptor is valid")
+}
@@ -1,9 +1,27 @@
 //! MCP server startup and tool registration.
+mod capabilities;
+mod client_auth;
+mod config_watcher;
+mod error_catalog;
+mod federation;
+mod notifications;
+mod prompts;
+mod rate_limit;
+mod schema_version;
+mod self_check;
 mod server_info;
+mod sessions;
 mod startup;
+mod status;
 mod tool_registry;
+pub mod websocket;
 
+pub use client_auth::ClientAuthContext;
+pub use config_watcher::{ConfigWatcher, SharedConfig};
+pub use schema_version::{CURRENT_SCHEMA_VERSION, MIN_SCHEMA_VERSION};
+pub use self_check::{run_self_check, SelfCheck, SelfCheckReport};
 pub use server_info::build_instructions;
-pub use startup::{run_server, RuntimeExit};
+pub use sessions::{Session, SessionRegistry};
+pub use startup::{run_server, RuntimeExit, ShutdownController};
 pub use tool_registry::HelloWorldServer;
 pub use tool_registry::VisionOsServer;
@@ -0,0 +1,123 @@
+//! MCP prompts that walk a client through the multi-tool build workflows.
+//!
+//! Each prompt renders a single user message naming the tools to call in order, with the
+//! arguments filled in from the prompt parameters and the current config defaults.
+use rmcp::{
+    handler::server::{router::prompt::PromptRouter, wrapper::Parameters},
+    model::{GetPromptResult, PromptMessage, PromptMessageRole},
+    prompt, prompt_router,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::VisionOsServer;
+use crate::tools::visionos::artifacts::LATEST_JOB_ID;
+
+/// Arguments of `build_and_fetch`.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BuildAndFetchArgs {
+    /// Scheme to build; must be allowed by `visionos.allowed_schemes`.
+    pub scheme: String,
+    /// `xcodebuild -destination`; the configured default destination when omitted.
+    #[serde(default)]
+    pub destination: Option<String>,
+    /// `.xcodeproj`, `.xcworkspace`, or `Package.swift`; the configured default when omitted.
+    #[serde(default)]
+    pub project_path: Option<String>,
+}
+
+/// Arguments of `diagnose_build_failure`.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DiagnoseBuildFailureArgs {
+    /// Failed job to inspect; the most recent job when omitted.
+    #[serde(default)]
+    pub job_id: Option<String>,
+    /// Restrict the most recent job to this scheme.
+    #[serde(default)]
+    pub scheme: Option<String>,
+}
+
+pub(super) type ServerPromptRouter = PromptRouter<VisionOsServer>;
+
+#[prompt_router(vis = "pub(super)")]
+impl VisionOsServer {
+    #[prompt(
+        name = "build_and_fetch",
+        description = "Validate the sandbox, build a visionOS scheme, and download its artifact"
+    )]
+    async fn build_and_fetch_prompt(
+        &self,
+        Parameters(args): Parameters<BuildAndFetchArgs>,
+    ) -> GetPromptResult {
+        let config = self.config();
+        let destination = args
+            .destination
+            .unwrap_or_else(|| config.visionos.default_destination.clone());
+        let project_path = args.project_path.or_else(|| {
+            config
+                .visionos
+                .default_project_path
+                .as_ref()
+                .map(|path| path.display().to_string())
+        });
+        let mut build = json!({ "scheme": args.scheme, "destination": destination });
+        if let Some(project_path) = &project_path {
+            build["project_path"] = json!(project_path);
+        }
+        let project_hint = match project_path {
+            Some(_) => String::new(),
+            None => "No default project is configured, so call `inspect_xcode_schemes` first and \
+                      add the `project_path` it suggests to the requests below.\n\n"
+                .to_string(),
+        };
+        let text = format!(
+            "Build the visionOS scheme `{scheme}` with the seiro-mcp tools, in this order.\n\n\
+             {project_hint}1. Call `validate_sandbox_policy` with the same `project_path` and `scheme`. Stop and \
+             report the failed checks if `status` is not `ok`.\n\
+             2. Call `build_visionos_app` with {build}. Keep the returned `job_id`.\n\
+             3. On success, call `fetch_build_output` with that `job_id` and report `artifact_zip` \
+             (or `download_url`), `sha256`, and `download_ttl_seconds`.\n\
+             4. On `build_failed`, report `details.log_errors` and call `inspect_build_diagnostics` \
+             with the `job_id` from the error before changing any code.\n\n\
+             Do not run `xcodebuild` directly.",
+            scheme = args.scheme,
+        );
+        GetPromptResult {
+            description: Some(format!("Build and fetch `{}`", args.scheme)),
+            messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+        }
+    }
+
+    #[prompt(
+        name = "diagnose_build_failure",
+        description = "Find the cause of a failed visionOS build from its diagnostics and full log"
+    )]
+    async fn diagnose_build_failure_prompt(
+        &self,
+        Parameters(args): Parameters<DiagnoseBuildFailureArgs>,
+    ) -> GetPromptResult {
+        let job_id = args.job_id.unwrap_or_else(|| LATEST_JOB_ID.to_string());
+        let mut job = json!({ "job_id": job_id });
+        if let Some(scheme) = &args.scheme {
+            job["scheme"] = json!(scheme);
+        }
+        let text = format!(
+            "Diagnose why the visionOS build job `{job_id}` failed, using the seiro-mcp tools:\n\n\
+             1. Call `inspect_build_diagnostics` with {job} and list each error with its file and \
+             line.\n\
+             2. If the errors do not explain the failure, page through the complete output with \
+             `fetch_build_log` ({job} plus `offset`), following `next_offset` until `eof`.\n\
+             3. Call `list_build_jobs` to find the last succeeded job for the same scheme, then \
+             `diff_build_logs` between it and the failed job to see which errors are new.\n\
+             4. Propose a fix for the first new error, and only then rebuild with \
+             `build_visionos_app`.\n\n\
+             If the cause stays unclear, call `export_job_trace` with {job} and attach the bundle \
+             to the bug report."
+        );
+        GetPromptResult {
+            description: Some(format!("Diagnose build job `{job_id}`")),
+            messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! Per-connection limits on tool calls: admission before dispatch, and `[tools.<name>]`
+//! timeouts around it.
+//!
+//! Each MCP connection gets its own server, so the window and the in-flight counts cover one
+//! client. Limits are read from the current config on every call and follow reloads.
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use rmcp::model::ErrorData;
+use serde_json::json;
+
+use crate::lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor};
+use crate::server::config::{ServerSection, ToolLimits};
+
+/// Calls that stay available at the limit, so an agent can always stop its own builds.
+const EXEMPT_TOOLS: &[&str] = &["cancel_build_job"];
+const WINDOW: Duration = Duration::from_secs(60);
+
+const RATE_LIMITED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "rate_limited",
+    "Too many tool calls from this client",
+    "Wait retry_after_secs before calling again, or raise server.rate_limit_per_minute / server.max_concurrent_calls.",
+);
+const TOOL_TIMEOUT_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "tool_timeout",
+    "Tool call exceeded its configured timeout",
+    "Retry with a smaller request, or raise tools.<name>.timeout_secs in the config.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&RATE_LIMITED_ERROR, true, SandboxState::NotApplicable),
+    ErrorCatalogEntry::new(&TOOL_TIMEOUT_ERROR, true, SandboxState::NotApplicable),
+];
+
+/// Sliding one-minute window of call starts and the number of calls still running.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter(Arc<Mutex<RateLimiterState>>);
+
+#[derive(Debug, Default)]
+struct RateLimiterState {
+    started: VecDeque<Instant>,
+    in_flight: usize,
+    in_flight_by_tool: HashMap<String, usize>,
+}
+
+/// Held for the duration of an admitted call; releases its concurrency slots when dropped.
+#[derive(Debug)]
+pub struct CallPermit(Option<(RateLimiter, String)>);
+
+impl Drop for CallPermit {
+    fn drop(&mut self) {
+        if let Some((limiter, tool)) = self.0.take() {
+            let mut state = limiter.state();
+            state.in_flight -= 1;
+            if let Some(count) = state.in_flight_by_tool.get_mut(&tool) {
+                *count -= 1;
+                if *count == 0 {
+                    state.in_flight_by_tool.remove(&tool);
+                }
+            }
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Admit a call to `tool` under the limits in `server` and the tool's own `limits`, or
+    /// return a retryable `rate_limited` error carrying `retry_after_secs`.
+    pub fn try_acquire(
+        &self,
+        tool: &str,
+        server: &ServerSection,
+        limits: ToolLimits,
+    ) -> Result<CallPermit, ErrorData> {
+        self.try_acquire_at(tool, server, limits, Instant::now())
+    }
+
+    fn try_acquire_at(
+        &self,
+        tool: &str,
+        server: &ServerSection,
+        limits: ToolLimits,
+        now: Instant,
+    ) -> Result<CallPermit, ErrorData> {
+        if EXEMPT_TOOLS.contains(&tool) {
+            return Ok(CallPermit(None));
+        }
+        let mut state = self.state();
+        while state
+            .started
+            .front()
+            .is_some_and(|started| now.duration_since(*started) >= WINDOW)
+        {
+            state.started.pop_front();
+        }
+
+        let max_concurrent = usize::from(server.max_concurrent_calls);
+        if max_concurrent > 0 && state.in_flight >= max_concurrent {
+            return Err(rate_limited("max_concurrent_calls", max_concurrent, 1));
+        }
+        let tool_max_concurrent = usize::from(limits.max_concurrent);
+        if tool_max_concurrent > 0
+            && state.in_flight_by_tool.get(tool).copied().unwrap_or(0) >= tool_max_concurrent
+        {
+            return Err(rate_limited(
+                &format!("tools.{tool}.max_concurrent"),
+                tool_max_concurrent,
+                1,
+            ));
+        }
+        let per_minute = server.rate_limit_per_minute as usize;
+        if per_minute > 0 && state.started.len() >= per_minute {
+            let oldest = state.started[state.started.len() - per_minute];
+            let wait = WINDOW.saturating_sub(now.duration_since(oldest));
+            let retry_after_secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            return Err(rate_limited(
+                "rate_limit_per_minute",
+                per_minute,
+                retry_after_secs.max(1),
+            ));
+        }
+
+        state.started.push_back(now);
+        state.in_flight += 1;
+        *state.in_flight_by_tool.entry(tool.to_string()).or_default() += 1;
+        Ok(CallPermit(Some((self.clone(), tool.to_string()))))
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, RateLimiterState> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn rate_limited(limit: &str, value: usize, retry_after_secs: u64) -> ErrorData {
+    RATE_LIMITED_ERROR
+        .builder()
+        .details(json!({ "limit": limit, "value": value }))
+        .with_context_field("retry_after_secs", json!(retry_after_secs))
+        .sandbox_state(SandboxState::NotApplicable)
+        .retryable(true)
+        .build()
+        .expect("descriptor is valid")
+}
+
+/// Run `call` to `tool`, failing with a retryable `tool_timeout` once `limits.timeout_secs`
+/// pass. The call is dropped on timeout; a queued build, archive, or test job then releases its
+/// slot and a build or archive job is recorded as `cancelled_running`.
+pub async fn with_timeout<T>(
+    tool: &str,
+    limits: ToolLimits,
+    call: impl Future<Output = Result<T, ErrorData>>,
+) -> Result<T, ErrorData> {
+    if limits.timeout_secs == 0 {
+        return call.await;
+    }
+    let timeout = Duration::from_secs(limits.timeout_secs.into());
+    tokio::time::timeout(timeout, call)
+        .await
+        .unwrap_or_else(|_| {
+            Err(TOOL_TIMEOUT_ERROR
+                .builder()
+                .details(json!({ "tool": tool, "timeout_secs": limits.timeout_secs }))
+                .sandbox_state(SandboxState::NotApplicable)
+                .retryable(true)
+                .build()
+                .expect("descriptor is valid"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(rate_limit_per_minute: u32, max_concurrent_calls: u16) -> ServerSection {
+        ServerSection {
+            host: "127.0.0.1".into(),
+            port: 8787,
+            shutdown_grace_secs: 30,
+            rate_limit_per_minute,
+            max_concurrent_calls,
+            allow_external_bind: false,
+            max_connections: 16,
+            idle_timeout_secs: 0,
+            auth_failure_limit: 0,
+            auth_lockout_secs: 60,
+        }
+    }
+
+    #[test]
+    fn calls_over_the_minute_budget_get_retry_after() {
+        let limiter = RateLimiter::default();
+        let server = limits(2, 0);
+        let start = Instant::now();
+
+        for offset in [0, 10] {
+            limiter
+                .try_acquire_at(
+                    "get_queue_status",
+                    &server,
+                    ToolLimits::default(),
+                    start + Duration::from_secs(offset),
+                )
+                .expect("within budget");
+        }
+        let error = limiter
+            .try_acquire_at(
+                "get_queue_status",
+                &server,
+                ToolLimits::default(),
+                start + Duration::from_secs(20),
+            )
+            .expect_err("third call in the window is limited");
+        let data = error.data.expect("error data");
+        assert_eq!(data["code"], json!("rate_limited"));
+        assert_eq!(data["retryable"], json!(true));
+        assert_eq!(data["retry_after_secs"], json!(40));
+
+        limiter
+            .try_acquire_at(
+                "get_queue_status",
+                &server,
+                ToolLimits::default(),
+                start + Duration::from_secs(60),
+            )
+            .expect("the first call left the window");
+        limiter
+            .try_acquire_at(
+                "cancel_build_job",
+                &server,
+                ToolLimits::default(),
+                start + Duration::from_secs(61),
+            )
+            .expect("cancellation is never limited");
+    }
+
+    #[test]
+    fn concurrent_calls_release_their_slot_when_done() {
+        let limiter = RateLimiter::default();
+        let server = limits(0, 1);
+
+        let permit = limiter
+            .try_acquire("build_visionos_app", &server, ToolLimits::default())
+            .expect("first call is admitted");
+        let error = limiter
+            .try_acquire("get_queue_status", &server, ToolLimits::default())
+            .expect_err("second concurrent call is limited");
+        assert_eq!(
+            error.data.expect("error data")["details"]["limit"],
+            json!("max_concurrent_calls")
+        );
+
+        drop(permit);
+        limiter
+            .try_acquire("get_queue_status", &server, ToolLimits::default())
+            .expect("slot is free again");
+    }
+
+    #[test]
+    fn tool_limits_cap_concurrent_calls_to_that_tool_only() {
+        let limiter = RateLimiter::default();
+        let server = limits(0, 0);
+        let build = ToolLimits {
+            timeout_secs: 0,
+            max_concurrent: 1,
+        };
+
+        let permit = limiter
+            .try_acquire("build_visionos_app", &server, build)
+            .expect("first build is admitted");
+        let error = limiter
+            .try_acquire("build_visionos_app", &server, build)
+            .expect_err("second concurrent build is limited");
+        assert_eq!(
+            error.data.expect("error data")["details"]["limit"],
+            json!("tools.build_visionos_app.max_concurrent")
+        );
+        limiter
+            .try_acquire("get_queue_status", &server, ToolLimits::default())
+            .expect("other tools are not limited");
+
+        drop(permit);
+        limiter
+            .try_acquire("build_visionos_app", &server, build)
+            .expect("slot is free again");
+    }
+
+    #[tokio::test]
+    async fn calls_past_the_tool_timeout_fail_with_tool_timeout() {
+        let limits = ToolLimits {
+            timeout_secs: 1,
+            max_concurrent: 0,
+        };
+
+        let error = with_timeout("build_visionos_app", limits, async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })
+        .await
+        .expect_err("call is cut off");
+        let data = error.data.expect("error data");
+        assert_eq!(data["code"], json!("tool_timeout"));
+        assert_eq!(data["details"]["timeout_secs"], json!(1));
+
+        let value = with_timeout("build_visionos_app", limits, async { Ok(7) })
+            .await
+            .expect("quick call finishes");
+        assert_eq!(value, 7);
+    }
+}
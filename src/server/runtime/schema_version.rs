@@ -0,0 +1,130 @@
+//! Versioning of tool payloads.
+//!
+//! Every tool response and error data carries `schema_version`. Within a version, responses only
+//! gain fields; removing, renaming, or retyping a field bumps [`CURRENT_SCHEMA_VERSION`], and the
+//! previous shape stays available to clients that declare an older version in the call's
+//! `_meta.schema_version`.
+use rmcp::model::{CallToolResult, Content, ErrorData, JsonObject, Meta};
+use serde_json::{json, Value};
+
+use crate::lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor};
+
+/// Shape of the payloads this server produces by default.
+pub const CURRENT_SCHEMA_VERSION: u64 = 1;
+/// Oldest shape this server can still produce.
+pub const MIN_SCHEMA_VERSION: u64 = 1;
+/// Field added to responses and error data, and read from a call's `_meta`.
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+/// Experimental server capability advertising the supported range at initialization.
+pub const SCHEMA_VERSION_CAPABILITY: &str = "seiro/schema_version";
+
+const UNSUPPORTED_SCHEMA_VERSION_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "unsupported_schema_version",
+    "The declared schema_version is not supported",
+    "Declare a schema_version between min and current from the server's seiro/schema_version capability, or omit it.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[ErrorCatalogEntry::new(
+    &UNSUPPORTED_SCHEMA_VERSION_ERROR,
+    false,
+    SandboxState::NoViolation,
+)];
+
+/// `min` and `current` versions for the experimental capability.
+pub fn capability() -> JsonObject {
+    let mut range = JsonObject::new();
+    range.insert("min".into(), json!(MIN_SCHEMA_VERSION));
+    range.insert("current".into(), json!(CURRENT_SCHEMA_VERSION));
+    range
+}
+
+/// Version to answer a call with: the newest one the client declared support for, capped at
+/// [`CURRENT_SCHEMA_VERSION`]. Clients that declare nothing get the current version.
+pub fn negotiate(meta: &Meta) -> Result<u64, ErrorData> {
+    let Some(declared) = meta.get(SCHEMA_VERSION_FIELD) else {
+        return Ok(CURRENT_SCHEMA_VERSION);
+    };
+    match declared.as_u64() {
+        Some(version) if version >= MIN_SCHEMA_VERSION => Ok(version.min(CURRENT_SCHEMA_VERSION)),
+        _ => Err(stamp_error(
+            UNSUPPORTED_SCHEMA_VERSION_ERROR
+                .builder()
+                .details(json!({
+                    "declared": declared,
+                    "min": MIN_SCHEMA_VERSION,
+                    "current": CURRENT_SCHEMA_VERSION,
+                }))
+                .sandbox_state(SandboxState::NoViolation)
+                .retryable(false)
+                .build()
+                .expect("descriptor is valid"),
+            CURRENT_SCHEMA_VERSION,
+        )),
+    }
+}
+
+/// Add `schema_version` to the structured content and its text rendering.
+pub fn stamp_result(mut result: CallToolResult, version: u64) -> CallToolResult {
+    let Some(Value::Object(content)) = result.structured_content.as_mut() else {
+        return result;
+    };
+    content.insert(SCHEMA_VERSION_FIELD.into(), json!(version));
+    let text = Value::Object(content.clone()).to_string();
+    result.content = vec![Content::text(text)];
+    result
+}
+
+/// Add `schema_version` to the error data, creating it for errors raised without any.
+pub fn stamp_error(mut error: ErrorData, version: u64) -> ErrorData {
+    match error.data.as_mut() {
+        Some(Value::Object(data)) => {
+            data.insert(SCHEMA_VERSION_FIELD.into(), json!(version));
+        }
+        Some(_) => {}
+        None => error.data = Some(json!({ SCHEMA_VERSION_FIELD: version })),
+    }
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(value: Value) -> Meta {
+        let mut meta = Meta::new();
+        meta.insert(SCHEMA_VERSION_FIELD.into(), value);
+        meta
+    }
+
+    #[test]
+    fn negotiation_caps_at_current_and_rejects_older_versions() {
+        assert_eq!(negotiate(&Meta::new()), Ok(CURRENT_SCHEMA_VERSION));
+        assert_eq!(
+            negotiate(&meta(json!(CURRENT_SCHEMA_VERSION + 1))),
+            Ok(CURRENT_SCHEMA_VERSION)
+        );
+
+        for declared in [json!(MIN_SCHEMA_VERSION - 1), json!("1")] {
+            let err = negotiate(&meta(declared)).expect_err("version is unsupported");
+            let data = err.data.expect("error data");
+            assert_eq!(data["code"], json!("unsupported_schema_version"));
+            assert_eq!(data[SCHEMA_VERSION_FIELD], json!(CURRENT_SCHEMA_VERSION));
+        }
+    }
+
+    #[test]
+    fn stamping_updates_structured_content_and_its_text() {
+        let result = stamp_result(CallToolResult::structured(json!({ "status": "ok" })), 1);
+
+        let expected = json!({ "status": "ok", "schema_version": 1 });
+        assert_eq!(result.structured_content, Some(expected.clone()));
+        let text = result.content[0].as_text().expect("text content");
+        assert_eq!(
+            serde_json::from_str::<Value>(&text.text).ok(),
+            Some(expected)
+        );
+
+        let error = stamp_error(ErrorData::invalid_params("bad", None), 1);
+        assert_eq!(error.data, Some(json!({ "schema_version": 1 })));
+    }
+}
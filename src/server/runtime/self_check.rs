@@ -0,0 +1,226 @@
+//! `--self-check`: load the config, build the tool router, and probe the build environment
+//! without serving MCP, then print a JSON report. Meant for `ExecStartPre` in a systemd unit or
+//! a launchd pre-flight script; the exit code is non-zero when any check fails.
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    cli::LaunchProfile,
+    server::{
+        config::ServerConfig,
+        runtime::{build_instructions, RuntimeExit, VisionOsServer},
+    },
+    tools::visionos::{
+        self,
+        sandbox::{SandboxCheckResult, SandboxStatus},
+        ListXcodeInstallationsRequest, SandboxPolicyRequest, SandboxProbeCache,
+    },
+};
+
+/// Printed to stdout by `--self-check`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfCheckReport {
+    /// `ok` when every check passed.
+    pub status: SandboxStatus,
+    pub config_path: PathBuf,
+    pub checks: Vec<SelfCheck>,
+}
+
+/// One step of the self-check.
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfCheck {
+    /// `config`, `tool_router`, `sandbox`, or `xcodebuild`.
+    pub name: &'static str,
+    pub result: SandboxCheckResult,
+    pub details: String,
+    /// What the step produced, such as the `validate_sandbox_policy` response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+}
+
+impl SelfCheck {
+    fn new(name: &'static str, passed: bool, details: String, output: Option<Value>) -> Self {
+        Self {
+            name,
+            result: if passed {
+                SandboxCheckResult::Pass
+            } else {
+                SandboxCheckResult::Fail
+            },
+            details,
+            output,
+        }
+    }
+}
+
+/// Run every check, print the report, and fail when a check failed. Later checks are skipped
+/// when the config does not load.
+pub async fn run_self_check(profile: LaunchProfile) -> Result<(), RuntimeExit> {
+    let report = self_check(&profile).await;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).map_err(RuntimeExit::from_error)?
+    );
+    let failed: Vec<&str> = report
+        .checks
+        .iter()
+        .filter(|check| check.result == SandboxCheckResult::Fail)
+        .map(|check| check.name)
+        .collect();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(RuntimeExit::from_error(anyhow!(
+            "self-check failed: {}",
+            failed.join(", ")
+        )))
+    }
+}
+
+async fn self_check(profile: &LaunchProfile) -> SelfCheckReport {
+    let config = ServerConfig::load_with_profile(
+        profile.config_path.clone(),
+        profile.config_profile.clone(),
+    );
+    let mut checks = Vec::new();
+    match config {
+        Ok(config) => {
+            checks.push(SelfCheck::new(
+                "config",
+                true,
+                format!("{} is valid", config.source_path.display()),
+                None,
+            ));
+            checks.push(check_tool_router(profile, &config));
+            checks.push(check_sandbox(&config).await);
+            checks.push(check_xcodebuild(&config).await);
+        }
+        Err(error) => checks.push(SelfCheck::new("config", false, error.to_string(), None)),
+    }
+    let status = if checks
+        .iter()
+        .all(|check| check.result == SandboxCheckResult::Pass)
+    {
+        SandboxStatus::Ok
+    } else {
+        SandboxStatus::Error
+    };
+    SelfCheckReport {
+        status,
+        config_path: profile.config_path.clone(),
+        checks,
+    }
+}
+
+fn check_tool_router(profile: &LaunchProfile, config: &ServerConfig) -> SelfCheck {
+    let server = VisionOsServer::new(config.clone(), build_instructions(profile, config));
+    let tools = server.tool_names();
+    server.artifact_store().discard();
+    SelfCheck::new(
+        "tool_router",
+        !tools.is_empty(),
+        format!("{} tools registered", tools.len()),
+        Some(json!(tools)),
+    )
+}
+
+/// `validate_sandbox_policy` for the project a build would most likely use:
+/// `default_project_path`, else the first allowed root, else the config's directory.
+async fn check_sandbox(config: &ServerConfig) -> SelfCheck {
+    let visionos = &config.visionos;
+    let project_path = visionos
+        .default_project_path
+        .clone()
+        .or_else(|| visionos.allowed_paths.first().cloned())
+        .or_else(|| config.source_path.parent().map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("/"));
+    let request = SandboxPolicyRequest {
+        project_path,
+        required_sdks: visionos.required_sdks.clone(),
+        xcode_path: None,
+        platform: None,
+        code_sign_identity: None,
+        runtime_version: None,
+        force_refresh: true,
+        strict: false,
+        remediate: false,
+    };
+    match visionos::validate_sandbox_policy(request, visionos, &SandboxProbeCache::new()).await {
+        Ok(response) => {
+            let failed: Vec<&str> = response
+                .checks
+                .iter()
+                .filter(|check| check.result == SandboxCheckResult::Fail)
+                .map(|check| check.name.as_str())
+                .collect();
+            let details = if failed.is_empty() {
+                format!("{} checks passed", response.checks.len())
+            } else {
+                format!("failed: {}", failed.join(", "))
+            };
+            SelfCheck::new(
+                "sandbox",
+                response.status == SandboxStatus::Ok,
+                details,
+                serde_json::to_value(&response).ok(),
+            )
+        }
+        Err(failure) => {
+            let error = visionos::sandbox_error_to_error_data(failure);
+            SelfCheck::new(
+                "sandbox",
+                false,
+                error.message.to_string(),
+                serde_json::to_value(&error).ok(),
+            )
+        }
+    }
+}
+
+/// `xcodebuild -version` through `visionos.xcodebuild_path` for every configured Xcode, which
+/// also runs a mock `xcodebuild` script when the config points at one.
+async fn check_xcodebuild(config: &ServerConfig) -> SelfCheck {
+    let installations = visionos::list_xcode_installations(
+        ListXcodeInstallationsRequest::default(),
+        &config.visionos,
+    )
+    .await
+    .installations;
+    let errors: Vec<String> = installations
+        .iter()
+        .filter_map(|installation| {
+            installation
+                .error
+                .as_ref()
+                .map(|error| format!("{}: {error}", installation.name))
+        })
+        .collect();
+    let details = if errors.is_empty() {
+        let versions: Vec<String> = installations
+            .iter()
+            .map(|installation| {
+                format!(
+                    "{} Xcode {}",
+                    installation.name,
+                    installation.version.as_deref().unwrap_or("?")
+                )
+            })
+            .collect();
+        format!(
+            "{} runs: {}",
+            config.visionos.xcodebuild_path.display(),
+            versions.join(", ")
+        )
+    } else {
+        errors.join("; ")
+    };
+    SelfCheck::new(
+        "xcodebuild",
+        errors.is_empty(),
+        details,
+        serde_json::to_value(&installations).ok(),
+    )
+}
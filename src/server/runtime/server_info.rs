@@ -1,9 +1,20 @@
-use crate::{cli::LaunchProfile, server::config::ServerConfig};
+use crate::{
+    cli::{LaunchProfile, TransportMode},
+    server::config::ServerConfig,
+};
 
 /// Build the `ServerInfo.instructions` string shown to MCP clients.
-pub fn build_instructions(_profile: &LaunchProfile, config: &ServerConfig) -> String {
-    format!(
-        "Loaded config {path}; waiting in stdio mode from a local MCP client.",
-        path = config.source_path.display(),
-    )
+pub fn build_instructions(profile: &LaunchProfile, config: &ServerConfig) -> String {
+    match profile.transport {
+        TransportMode::Stdio => format!(
+            "Loaded config {path}; waiting in stdio mode from a local MCP client.",
+            path = config.source_path.display(),
+        ),
+        TransportMode::Websocket => format!(
+            "Loaded config {path}; serving WebSocket MCP sessions on {host}:{port}.",
+            path = config.source_path.display(),
+            host = config.server.host,
+            port = config.server.port,
+        ),
+    }
 }
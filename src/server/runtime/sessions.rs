@@ -0,0 +1,245 @@
+//! Open MCP sessions: who is connected, since when, and which tools each has called.
+//!
+//! The registry is shared by every session of the process. stdio serves a single session;
+//! the WebSocket transport registers one per upgraded connection and turns clients away
+//! once `server.max_connections` are open.
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Sessions open in this process.
+#[derive(Debug, Clone, Default)]
+pub struct SessionRegistry(Arc<RegistryState>);
+
+#[derive(Debug, Default)]
+struct RegistryState {
+    next_id: AtomicU64,
+    sessions: Mutex<BTreeMap<u64, SessionEntry>>,
+}
+
+#[derive(Debug)]
+struct SessionEntry {
+    info: SessionInfo,
+    /// Last message from the client or tool call finishing.
+    last_activity: Instant,
+    /// Tool calls still running.
+    in_flight: usize,
+}
+
+/// One open session; it leaves the registry when the last clone is dropped.
+#[derive(Debug, Clone)]
+pub struct Session(Arc<SessionSlot>);
+
+#[derive(Debug)]
+struct SessionSlot {
+    registry: SessionRegistry,
+    id: u64,
+}
+
+impl Drop for SessionSlot {
+    fn drop(&mut self) {
+        self.registry.sessions().remove(&self.id);
+    }
+}
+
+/// A tool call in progress; the session is not idle until it is dropped.
+#[derive(Debug)]
+pub struct CallActivity(Session);
+
+impl Drop for CallActivity {
+    fn drop(&mut self) {
+        self.0.update(|entry| {
+            entry.in_flight -= 1;
+            entry.last_activity = Instant::now();
+        });
+    }
+}
+
+/// Input for `list_sessions`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListSessionsRequest {}
+
+/// Response from `list_sessions`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ListSessionsResponse {
+    /// `server.max_connections`; 0 means unlimited.
+    pub max_connections: u16,
+    /// Open sessions, oldest first.
+    pub sessions: Vec<SessionInfo>,
+}
+
+/// An open session as reported by `list_sessions`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SessionInfo {
+    pub session_id: u64,
+    /// `stdio` or `websocket`.
+    pub transport: &'static str,
+    /// Client address; `None` over stdio.
+    pub peer: Option<String>,
+    pub connected_at: DateTime<Utc>,
+    /// Tool calls started in this session, by tool name.
+    pub tools_invoked: BTreeMap<String, u64>,
+    /// Start of the latest tool call; `None` until the first one.
+    pub last_call_at: Option<DateTime<Utc>>,
+}
+
+impl SessionRegistry {
+    /// Register a session unless `max_connections` (0 = unlimited) are already open.
+    pub fn open(
+        &self,
+        transport: &'static str,
+        peer: Option<SocketAddr>,
+        max_connections: u16,
+    ) -> Option<Session> {
+        let mut sessions = self.sessions();
+        if max_connections > 0 && sessions.len() >= usize::from(max_connections) {
+            return None;
+        }
+        let id = self.0.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        sessions.insert(
+            id,
+            SessionEntry {
+                info: SessionInfo {
+                    session_id: id,
+                    transport,
+                    peer: peer.map(|peer| peer.to_string()),
+                    connected_at: Utc::now(),
+                    tools_invoked: BTreeMap::new(),
+                    last_call_at: None,
+                },
+                last_activity: Instant::now(),
+                in_flight: 0,
+            },
+        );
+        Some(Session(Arc::new(SessionSlot {
+            registry: self.clone(),
+            id,
+        })))
+    }
+
+    /// Open sessions, oldest first.
+    pub fn list(&self, max_connections: u16) -> ListSessionsResponse {
+        ListSessionsResponse {
+            max_connections,
+            sessions: self
+                .sessions()
+                .values()
+                .map(|entry| entry.info.clone())
+                .collect(),
+        }
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, SessionEntry>> {
+        self.0
+            .sessions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Session {
+    pub fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    /// Count a call to `tool` against this session, which stays active until the returned
+    /// guard is dropped.
+    pub fn begin_call(&self, tool: &str) -> CallActivity {
+        self.update(|entry| {
+            *entry
+                .info
+                .tools_invoked
+                .entry(tool.to_string())
+                .or_default() += 1;
+            entry.info.last_call_at = Some(Utc::now());
+            entry.in_flight += 1;
+            entry.last_activity = Instant::now();
+        });
+        CallActivity(self.clone())
+    }
+
+    /// Note a message from the client.
+    pub fn touch(&self) {
+        self.update(|entry| entry.last_activity = Instant::now());
+    }
+
+    /// How long the session has gone without a message or a running tool call.
+    pub fn idle_for(&self) -> Duration {
+        self.0
+            .registry
+            .sessions()
+            .get(&self.0.id)
+            .filter(|entry| entry.in_flight == 0)
+            .map_or(Duration::ZERO, |entry| entry.last_activity.elapsed())
+    }
+
+    fn update(&self, change: impl FnOnce(&mut SessionEntry)) {
+        if let Some(entry) = self.0.registry.sessions().get_mut(&self.0.id) {
+            change(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_are_limited_and_leave_when_dropped() {
+        let registry = SessionRegistry::default();
+        let peer: SocketAddr = "127.0.0.1:50000".parse().expect("peer");
+
+        let first = registry
+            .open("websocket", Some(peer), 2)
+            .expect("first session fits");
+        let second = registry
+            .open("websocket", None, 2)
+            .expect("second session fits");
+        assert!(registry.open("websocket", None, 2).is_none());
+        assert!(registry.open("websocket", None, 0).is_some());
+
+        drop(first.begin_call("build_visionos_app"));
+        drop(first.begin_call("build_visionos_app"));
+        let listed = registry.list(2);
+        assert_eq!(listed.sessions.len(), 2);
+        assert_eq!(listed.sessions[0].session_id, first.id());
+        assert_eq!(listed.sessions[0].peer.as_deref(), Some("127.0.0.1:50000"));
+        assert_eq!(listed.sessions[0].tools_invoked["build_visionos_app"], 2);
+        assert!(listed.sessions[0].last_call_at.is_some());
+        assert!(listed.sessions[1].tools_invoked.is_empty());
+
+        drop(first.clone());
+        assert_eq!(registry.list(2).sessions.len(), 2);
+        drop(first);
+        drop(second);
+        assert!(registry.list(2).sessions.is_empty());
+    }
+
+    #[test]
+    fn running_calls_keep_a_session_active() {
+        let registry = SessionRegistry::default();
+        let session = registry.open("websocket", None, 0).expect("session");
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(session.idle_for() >= Duration::from_millis(20));
+
+        let call = session.begin_call("build_visionos_app");
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(session.idle_for(), Duration::ZERO);
+        drop(call);
+        assert!(session.idle_for() < Duration::from_millis(20));
+
+        std::thread::sleep(Duration::from_millis(20));
+        session.touch();
+        assert!(session.idle_for() < Duration::from_millis(20));
+    }
+}
@@ -1,17 +1,25 @@
-use std::process::ExitCode;
+use std::{process::ExitCode, time::Duration};
 
-use anyhow::Error;
+use anyhow::{anyhow, Error};
 use rmcp::ServiceExt;
+use serde_json::json;
+use tokio::{net::TcpListener, task::JoinSet};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
 
 use crate::{
-    cli::LaunchProfile,
+    cli::{LaunchProfile, TransportMode},
+    lib::errors::{SandboxState, SHUTDOWN_GRACE_EXPIRED_ERROR},
     server::{
         auth,
         config::ServerConfig,
-        runtime::{build_instructions, VisionOsServer},
+        runtime::{build_instructions, websocket, ConfigWatcher, VisionOsServer},
     },
+    tools::visionos::VisionOsJobQueue,
 };
 
+const SHUTDOWN_GRACE_EXPIRED_EXIT_CODE: u8 = 75;
+
 /// Bundles a runtime error message with an exit code and optional structured error data.
 #[derive(Debug)]
 pub struct RuntimeExit {
@@ -60,30 +68,290 @@ impl RuntimeExit {
     }
 }
 
-/// Start the MCP server and select stdio/TCP based on the launch profile.
+/// Start the MCP server on the launch profile's transport.
 pub async fn run_server(profile: LaunchProfile, config: ServerConfig) -> Result<(), RuntimeExit> {
-    auth::ensure_invoked_via_mcp_client(&profile)?;
+    if profile.transport == TransportMode::Stdio {
+        auth::ensure_invoked_via_mcp_client(&profile)?;
+    }
+    crate::lib::telemetry::set_redaction(config.redaction.policy());
 
     let instructions = build_instructions(&profile, &config);
     let server = VisionOsServer::new(config.clone(), instructions.clone());
     let pending_jobs = server.pending_jobs().await;
+    let shutdown = ShutdownController::new(
+        server.job_queue(),
+        Duration::from_secs(config.server.shutdown_grace_secs.into()),
+    );
 
     crate::lib::telemetry::emit_runtime_mode(&crate::lib::telemetry::RuntimeModeTelemetry {
-        transport: "stdio",
+        transport: profile.transport.as_str(),
         config_path: config.source_path.to_string_lossy().as_ref(),
         pending_jobs,
         instructions: &instructions,
         launch_args: &profile.launch_args,
     });
 
-    run_stdio(server).await
+    // Allowlist edits apply to later tool calls; the session stays up.
+    let _config_watcher =
+        match ConfigWatcher::spawn(server.shared_config(), config.source_path.clone()) {
+            Ok(watcher) => Some(watcher),
+            Err(err) => {
+                warn!(
+                    target: "rmcp_sample::runtime",
+                    path = %config.source_path.display(),
+                    reason = %err,
+                    "Config hot-reload is disabled"
+                );
+                None
+            }
+        };
+
+    let artifact_store = server.artifact_store();
+    let result = match profile.transport {
+        TransportMode::Stdio => run_stdio(server, shutdown).await,
+        TransportMode::Websocket => run_websocket(server, &config, shutdown).await,
+    };
+    artifact_store.discard();
+    result
 }
 
-async fn run_stdio(server: VisionOsServer) -> Result<(), RuntimeExit> {
+async fn run_stdio(
+    server: VisionOsServer,
+    shutdown: ShutdownController,
+) -> Result<(), RuntimeExit> {
+    let session = server
+        .sessions()
+        .open("stdio", None, 0)
+        .expect("stdio sessions are not limited");
     let running = server
+        .for_session(session)
         .serve(rmcp::transport::stdio())
         .await
         .map_err(RuntimeExit::from_error)?;
-    running.waiting().await.map_err(RuntimeExit::from_error)?;
-    Ok(())
+    let service = running.cancellation_token();
+    let waiting = running.waiting();
+    tokio::pin!(waiting);
+
+    let signal = tokio::select! {
+        quit = &mut waiting => {
+            quit.map_err(RuntimeExit::from_error)?;
+            return Ok(());
+        }
+        signal = wait_for_shutdown_signal() => signal,
+    };
+    // The session keeps serving while jobs drain so their responses still reach the client.
+    let drained = shutdown.drain(signal).await;
+    service.cancel();
+    waiting.await.map_err(RuntimeExit::from_error)?;
+    drained
+}
+
+/// Accept WebSocket sessions on `[server] host:port` until SIGINT/SIGTERM, then drain jobs and
+/// close every session. Sessions share the job queue and artifact store; rate limits stay per
+/// session.
+async fn run_websocket(
+    server: VisionOsServer,
+    config: &ServerConfig,
+    shutdown: ShutdownController,
+) -> Result<(), RuntimeExit> {
+    if !config.auth.has_tokens() {
+        return Err(RuntimeExit::from_error(anyhow!(
+            "--transport websocket requires auth.token or [[auth.tokens]] in {}",
+            config.source_path.display()
+        )));
+    }
+    let addresses = websocket::bind_addresses(&config.server)
+        .await
+        .map_err(RuntimeExit::from_error)?;
+    let listener = TcpListener::bind(addresses.as_slice())
+        .await
+        .map_err(RuntimeExit::from_error)?;
+    info!(
+        target: "rmcp_sample::runtime",
+        address = %listener.local_addr().map_err(RuntimeExit::from_error)?,
+        "Listening for WebSocket MCP sessions"
+    );
+
+    let sessions = CancellationToken::new();
+    let failures = websocket::AuthFailures::default();
+    let mut connections = JoinSet::new();
+    let signal = loop {
+        while connections.try_join_next().is_some() {}
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    connections.spawn(websocket::serve_connection(
+                        server.clone(),
+                        stream,
+                        peer,
+                        failures.clone(),
+                        sessions.child_token(),
+                    ));
+                }
+                Err(err) => warn!(
+                    target: "rmcp_sample::runtime",
+                    reason = %err,
+                    "Failed to accept WebSocket connection"
+                ),
+            },
+            signal = wait_for_shutdown_signal() => break signal,
+        }
+    };
+    drop(listener);
+    // Open sessions keep serving while jobs drain so their responses still reach the clients.
+    let drained = shutdown.drain(signal).await;
+    sessions.cancel();
+    while connections.join_next().await.is_some() {}
+    drained
+}
+
+/// Drains the visionOS job queue when the process is asked to stop.
+pub struct ShutdownController {
+    queue: VisionOsJobQueue,
+    grace_period: Duration,
+}
+
+impl ShutdownController {
+    pub fn new(queue: VisionOsJobQueue, grace_period: Duration) -> Self {
+        Self {
+            queue,
+            grace_period,
+        }
+    }
+
+    /// Stop accepting jobs and wait up to the grace period for queued and running ones.
+    pub async fn drain(&self, signal: &str) -> Result<(), RuntimeExit> {
+        self.queue.close();
+        info!(
+            target: "rmcp_sample::runtime",
+            signal,
+            pending_jobs = self.queue.pending_jobs().await,
+            grace_secs = self.grace_period.as_secs(),
+            "Draining visionOS jobs before shutdown"
+        );
+        if tokio::time::timeout(self.grace_period, self.queue.wait_until_idle())
+            .await
+            .is_ok()
+        {
+            info!(target: "rmcp_sample::runtime", signal, "Shutdown drain complete");
+            return Ok(());
+        }
+
+        let pending_jobs = self.queue.pending_jobs().await;
+        warn!(
+            target: "rmcp_sample::runtime",
+            signal,
+            pending_jobs,
+            "Shutdown grace period ended with jobs still pending"
+        );
+        let data = SHUTDOWN_GRACE_EXPIRED_ERROR
+            .builder()
+            .retryable(true)
+            .sandbox_state(SandboxState::NotApplicable)
+            .details(json!({
+                "signal": signal,
+                "pending_jobs": pending_jobs,
+                "grace_secs": self.grace_period.as_secs()
+            }))
+            .with_exit_code_value(SHUTDOWN_GRACE_EXPIRED_EXIT_CODE)
+            .build()
+            .expect("shutdown builder must succeed");
+        Err(RuntimeExit::structured(
+            data,
+            ExitCode::from(SHUTDOWN_GRACE_EXPIRED_EXIT_CODE),
+        ))
+    }
+}
+
+/// Resolve with the signal name once SIGINT or SIGTERM arrives.
+async fn wait_for_shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut sigterm) = signal(SignalKind::terminate()) {
+            return tokio::select! {
+                _ = wait_for_ctrl_c() => "SIGINT",
+                _ = sigterm.recv() => "SIGTERM",
+            };
+        }
+    }
+    wait_for_ctrl_c().await;
+    "SIGINT"
+}
+
+async fn wait_for_ctrl_c() {
+    if tokio::signal::ctrl_c().await.is_err() {
+        // Without a handler the process simply runs until the client disconnects.
+        std::future::pending::<()>().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::tools::visionos::JobPriority;
+
+    #[tokio::test]
+    async fn drain_waits_for_running_job_and_closes_queue() {
+        let queue = VisionOsJobQueue::new();
+        let job_id = Uuid::new_v4();
+        queue
+            .wait_for_turn(
+                job_id,
+                Path::new("/tmp/VisionApp.xcodeproj"),
+                JobPriority::Interactive,
+            )
+            .await
+            .expect("free slot");
+        let finisher = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                queue.finish_job(job_id).await;
+            })
+        };
+
+        let controller = ShutdownController::new(queue.clone(), Duration::from_secs(2));
+        controller.drain("SIGTERM").await.expect("queue drains");
+
+        assert!(queue.is_closed());
+        assert_eq!(queue.pending_jobs().await, 0);
+        finisher.await.expect("finisher task");
+    }
+
+    #[tokio::test]
+    async fn drain_reports_jobs_left_after_grace_period() {
+        let queue = VisionOsJobQueue::new();
+        queue
+            .wait_for_turn(
+                Uuid::new_v4(),
+                Path::new("/tmp/VisionApp.xcodeproj"),
+                JobPriority::Interactive,
+            )
+            .await
+            .expect("free slot");
+
+        let controller = ShutdownController::new(queue, Duration::from_millis(50));
+        let exit = controller
+            .drain("SIGINT")
+            .await
+            .expect_err("running job outlives the grace period");
+
+        assert_eq!(
+            exit.exit_code(),
+            ExitCode::from(SHUTDOWN_GRACE_EXPIRED_EXIT_CODE)
+        );
+        let details = exit
+            .error_data()
+            .and_then(|data| data.data.as_ref())
+            .and_then(|data| data.get("details"))
+            .cloned()
+            .expect("details");
+        assert_eq!(details["pending_jobs"], json!(1));
+        assert_eq!(details["signal"], json!("SIGINT"));
+    }
 }
@@ -0,0 +1,132 @@
+//! `seiro://status`: server health read through `resources/read` instead of a tool call.
+//!
+//! The resource is listed from startup and rendered on every read, so it never goes stale.
+use chrono::{DateTime, Utc};
+use rmcp::model::{AnnotateAble, RawResource, Resource, ResourceContents};
+use serde::Serialize;
+
+use crate::tools::visionos::{
+    artifacts::{ArtifactBackendInfo, ArtifactStoreUsage},
+    VisionOsArtifactStore, VisionOsJobQueue,
+};
+
+pub const STATUS_RESOURCE_URI: &str = "seiro://status";
+const STATUS_MIME_TYPE: &str = "application/json";
+
+/// Body of the `seiro://status` resource.
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerStatus {
+    pub server_version: &'static str,
+    pub started_at: DateTime<Utc>,
+    pub uptime_secs: u64,
+    pub queue: QueueHealth,
+    pub artifact_store: ArtifactStoreHealth,
+}
+
+/// Queue depth and cancellation counters since startup.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueHealth {
+    pub slots: usize,
+    pub running: usize,
+    pub queued: usize,
+    pub cancelled_queued: u64,
+    pub cancelled_running: u64,
+    /// Mean duration of the last finished jobs; `None` until one has finished.
+    pub average_job_secs: Option<u64>,
+}
+
+/// Where artifacts are kept and how much space they take.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtifactStoreHealth {
+    pub backend: ArtifactBackendInfo,
+    #[serde(flatten)]
+    pub usage: ArtifactStoreUsage,
+}
+
+/// Entry for `resources/list`.
+pub fn status_resource() -> Resource {
+    let mut raw = RawResource::new(STATUS_RESOURCE_URI, "server-status");
+    raw.description =
+        Some("Uptime, build queue depth, artifact store usage, and last cleanup time".into());
+    raw.mime_type = Some(STATUS_MIME_TYPE.into());
+    raw.no_annotation()
+}
+
+/// Current status of the server that started at `started_at`.
+pub async fn collect_status(
+    started_at: DateTime<Utc>,
+    queue: &VisionOsJobQueue,
+    store: &VisionOsArtifactStore,
+) -> ServerStatus {
+    let snapshot = queue.snapshot().await;
+    let metrics = queue.metrics().await;
+    ServerStatus {
+        server_version: env!("CARGO_PKG_VERSION"),
+        started_at,
+        uptime_secs: (Utc::now() - started_at).num_seconds().max(0) as u64,
+        queue: QueueHealth {
+            slots: snapshot.slots,
+            running: snapshot.running.len(),
+            queued: snapshot.queued.len(),
+            cancelled_queued: metrics.cancelled_queued,
+            cancelled_running: metrics.cancelled_running,
+            average_job_secs: snapshot.average_duration.map(|average| average.as_secs()),
+        },
+        artifact_store: ArtifactStoreHealth {
+            backend: store.backend_info(),
+            usage: store.usage().await,
+        },
+    }
+}
+
+/// `resources/read` contents for `status`.
+pub fn status_contents(status: &ServerStatus) -> ResourceContents {
+    ResourceContents::TextResourceContents {
+        uri: STATUS_RESOURCE_URI.into(),
+        mime_type: Some(STATUS_MIME_TYPE.into()),
+        text: serde_json::to_string_pretty(status).unwrap_or_default(),
+        meta: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use serde_json::Value;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::tools::visionos::ArtifactKind;
+
+    #[tokio::test]
+    async fn status_reports_uptime_queue_and_store_usage() {
+        let temp = tempdir().expect("temporary directory");
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+        let job_id = Uuid::new_v4();
+        let job_dir = temp.path().join(job_id.to_string());
+        std::fs::create_dir_all(&job_dir).expect("job dir");
+        std::fs::write(job_dir.join("build.log"), "0123456789").expect("write log");
+        store
+            .record_failure(job_id, ArtifactKind::Build, String::new(), None, Utc::now())
+            .await
+            .expect("record failure");
+        let queue = VisionOsJobQueue::with_slots(2);
+
+        let status = collect_status(Utc::now() - Duration::seconds(90), &queue, &store).await;
+        let ResourceContents::TextResourceContents { text, .. } = status_contents(&status) else {
+            panic!("status is a text resource");
+        };
+        let body: Value = serde_json::from_str(&text).expect("status is JSON");
+
+        assert!(body["uptime_secs"].as_u64().is_some_and(|secs| secs >= 90));
+        assert_eq!(body["queue"]["slots"], 2);
+        assert_eq!(body["queue"]["running"], 0);
+        assert_eq!(body["artifact_store"]["backend"]["kind"], "local");
+        assert_eq!(body["artifact_store"]["jobs"], 1);
+        assert!(body["artifact_store"]["bytes"]
+            .as_u64()
+            .is_some_and(|bytes| bytes >= 10));
+        assert!(body["artifact_store"]["last_cleanup"].is_string());
+    }
+}
@@ -1,25 +1,79 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    future::Future,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rmcp::{
-    handler::server::{wrapper::Parameters, ServerHandler},
-    model::{ErrorData, ServerCapabilities, ServerInfo},
-    tool, tool_handler, tool_router, Json,
+    handler::server::{
+        prompt::PromptContext, tool::ToolCallContext, wrapper::Parameters, ServerHandler,
+    },
+    model::{
+        CallToolRequestParam, CallToolResult, ErrorData, GetPromptRequestParam, GetPromptResult,
+        JsonObject, ListPromptsResult, ListResourcesResult, ListToolsResult, LoggingLevel,
+        LoggingMessageNotificationParam, Meta, PaginatedRequestParam, ProgressNotificationParam,
+        ProgressToken, ReadResourceRequestParam, ReadResourceResult, ServerCapabilities,
+        ServerInfo, SetLevelRequestParam,
+    },
+    service::RequestContext,
+    tool, tool_router, Json, Peer, RoleServer,
 };
+use serde_json::json;
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+use super::{
+    capabilities,
+    client_auth::ClientAuthContext,
+    error_catalog::{self, ListErrorCodesRequest, ListErrorCodesResponse},
+    federation::{self, Federation},
+    notifications,
+    prompts::ServerPromptRouter,
+    rate_limit::{self, RateLimiter},
+    schema_version,
+    sessions::{ListSessionsRequest, ListSessionsResponse, Session, SessionRegistry},
+    status, SharedConfig,
+};
 use crate::{
     lib::errors::VisionOsBuildError,
-    server::config::ServerConfig,
+    server::config::{ServerConfig, VisionOsConfig},
     tools::{
         self,
         visionos::{
-            self, BuildFailureContext, BuildVisionOsAppResponse, FetchBuildOutputRequest,
-            FetchBuildOutputResponse, InspectBuildDiagnosticsRequest,
-            InspectBuildDiagnosticsResponse, InspectXcodeSchemesRequest,
-            InspectXcodeSchemesResponse, InspectXcodeSdksRequest, InspectXcodeSdksResponse,
-            SandboxPolicyRequest, SandboxPolicyResponse, VisionOsArtifactStore,
-            VisionOsBuildRequest, VisionOsJobQueue,
+            self,
+            artifacts::BuildJobStatus,
+            build::{cache as build_cache, RootLimit},
+            pipeline::PipelineStepStatus,
+            sandbox::SandboxStatus,
+            ArtifactFile, ArtifactKind, BuildFailureContext, BuildRequestValidationError,
+            BuildVisionOsAppResponse, CancelBuildJobRequest, CancelBuildJobResponse, CancelOutcome,
+            CaptureSimulatorMediaRequest, CaptureSimulatorMediaResponse,
+            CleanVisionOsProjectRequest, CleanVisionOsProjectResponse,
+            CreateVisionOsProjectRequest, CreateVisionOsProjectResponse,
+            DescribeBuildConstraintsRequest, DescribeBuildConstraintsResponse,
+            DiffBuildLogsRequest, DiffBuildLogsResponse, ExportArtifactRequest,
+            ExportArtifactResponse, ExportJobTraceRequest, ExportJobTraceResponse,
+            ExportVisionOsIpaRequest, ExportVisionOsIpaResponse, ExtendBuildDeadlineRequest,
+            ExtendBuildDeadlineResponse, FetchBuildLogRequest, FetchBuildLogResponse,
+            FetchBuildOutputRequest, FetchBuildOutputResponse, GetQueueStatusRequest,
+            InspectBuildDiagnosticsRequest, InspectBuildDiagnosticsResponse,
+            InspectXcodeSchemesRequest, InspectXcodeSchemesResponse, InspectXcodeSdksRequest,
+            InspectXcodeSdksResponse, JobMonitor, JobRole, JobTicket, JobTrace,
+            ListAllowedProjectsRequest, ListAllowedProjectsResponse, ListBuildJobsRequest,
+            ListBuildJobsResponse, ListDestinationsRequest, ListDestinationsResponse,
+            ListDevicesRequest, ListDevicesResponse, ListProjectSchemesRequest,
+            ListProjectSchemesResponse, ListSimulatorsRequest, ListSimulatorsResponse,
+            ListXcodeInstallationsRequest, ListXcodeInstallationsResponse, MediaKind,
+            PipelineStepKind, PipelineStepOutput, PipelineStepResult, PurgeArtifactsRequest,
+            PurgeArtifactsResponse, QueueStatusResponse, ResolvePackageDependenciesRequest,
+            ResolvePackageDependenciesResponse, RunPipelineRequest, RunPipelineResponse,
+            SandboxPolicyRequest, SandboxPolicyResponse, SandboxProbeCache,
+            ShowBuildSettingsRequest, ShowBuildSettingsResponse, SimulatorDeviceRequest,
+            SimulatorStateResponse, SymbolicateCrashRequest, SymbolicateCrashResponse,
+            TestVisionOsAppResponse, VisionOsArtifactStore, VisionOsBuildRequest, VisionOsJobQueue,
+            VisionOsResourceStore,
         },
         ServerToolRouter,
     },
@@ -27,39 +81,604 @@ use crate::{
 
 #[derive(Clone)]
 pub struct VisionOsServer {
-    config: Arc<ServerConfig>,
+    config: SharedConfig,
     instructions: Arc<String>,
     tool_router: ServerToolRouter<Self>,
+    prompt_router: ServerPromptRouter,
     visionos_queue: VisionOsJobQueue,
     artifact_store: VisionOsArtifactStore,
+    resource_store: VisionOsResourceStore,
+    sandbox_cache: SandboxProbeCache,
+    rate_limiter: RateLimiter,
+    federation: Federation,
+    sessions: SessionRegistry,
+    /// Session this server answers; `None` until [`Self::for_session`] assigns one.
+    session: Option<Session>,
+    /// Token the session authenticated with; `None` for unrestricted sessions such as stdio.
+    client_auth: Option<Arc<ClientAuthContext>>,
+    started_at: DateTime<Utc>,
 }
 
+/// Tools registered only with `tools.admin = true`.
+const ADMIN_TOOLS: &[&str] = &["purge_artifacts", "list_sessions"];
+
 /// Compatibility alias to preserve the legacy `HelloWorldServer` name.
 pub type HelloWorldServer = VisionOsServer;
 
 impl VisionOsServer {
     pub fn new(config: ServerConfig, instructions: String) -> Self {
-        let router = tools::build_router(Self::tool_router);
+        let mut router = tools::build_router(Self::tool_router);
+        let registered: Vec<String> = router
+            .list_all()
+            .into_iter()
+            .map(|tool| tool.name.into_owned())
+            .collect();
+        let known: Vec<&str> = registered.iter().map(String::as_str).collect();
+        for name in config.tools.unknown_names(&known) {
+            tracing::warn!(
+                target: "rmcp_sample::config",
+                tool = name,
+                "Ignoring unknown tool name in [tools]"
+            );
+        }
+        let checks = visionos::PolicyRegistry::builtin().names();
+        for name in config.visionos.policy.unknown_names(&checks) {
+            tracing::warn!(
+                target: "rmcp_sample::config",
+                check = name,
+                "Ignoring unknown check name in [visionos.policy]"
+            );
+        }
+        for name in known.iter().filter(|name| {
+            !config.tools.allows(name) || (ADMIN_TOOLS.contains(name) && !config.tools.admin)
+        }) {
+            router.remove_route(name);
+        }
         let artifact_store = visionos::VisionOsArtifactStore::new(
+            &config.artifacts,
+            config.visionos.artifact_persistence,
             config.visionos.artifact_ttl_secs,
             config.visionos.cleanup_schedule_secs,
+            visionos::ArtifactRetention {
+                max_jobs: config.visionos.artifact_max_jobs as usize,
+                max_total_bytes: config.visionos.artifact_max_total_bytes,
+            },
         );
+        let visionos_queue =
+            VisionOsJobQueue::with_slots(config.visionos.max_concurrent_builds.into());
+        let federation = Federation::new(&config.federation);
         Self {
-            config: Arc::new(config),
+            config: SharedConfig::new(config),
             instructions: Arc::new(instructions),
             tool_router: router,
-            visionos_queue: VisionOsJobQueue::new(),
+            prompt_router: Self::prompt_router(),
+            visionos_queue,
             artifact_store,
+            resource_store: VisionOsResourceStore::new(),
+            sandbox_cache: SandboxProbeCache::new(),
+            rate_limiter: RateLimiter::default(),
+            federation,
+            sessions: SessionRegistry::default(),
+            session: None,
+            client_auth: None,
+            started_at: Utc::now(),
         }
     }
 
+    /// Config in effect now; a reload swaps it for later calls without touching this one.
+    pub fn config(&self) -> Arc<ServerConfig> {
+        self.config.current()
+    }
+
+    /// Handle a [`super::ConfigWatcher`] swaps reloaded configs into.
+    pub fn shared_config(&self) -> SharedConfig {
+        self.config.clone()
+    }
+
     pub async fn pending_jobs(&self) -> usize {
         self.visionos_queue.pending_jobs().await
     }
 
+    /// Store holding job records and artifacts.
+    pub fn artifact_store(&self) -> VisionOsArtifactStore {
+        self.artifact_store.clone()
+    }
+
+    /// Replace the store built from the config. Every tool reads and writes artifacts under
+    /// the new store's root.
+    pub fn with_artifact_store(self, artifact_store: VisionOsArtifactStore) -> Self {
+        Self {
+            artifact_store,
+            ..self
+        }
+    }
+
+    /// Names of the registered tools, after `[tools]` filtering.
+    pub fn tool_names(&self) -> Vec<String> {
+        self.tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| tool.name.into_owned())
+            .collect()
+    }
+
+    /// Sessions open in this process.
+    pub fn sessions(&self) -> SessionRegistry {
+        self.sessions.clone()
+    }
+
+    /// Server for `session`: the same jobs, artifacts, and caches, with rate limits of its own.
+    pub fn for_session(&self, session: Session) -> Self {
+        Self {
+            rate_limiter: RateLimiter::default(),
+            session: Some(session),
+            ..self.clone()
+        }
+    }
+
+    /// Limit this server to the scopes and paths of the token its client presented.
+    pub fn with_client_auth(self, client_auth: ClientAuthContext) -> Self {
+        Self {
+            client_auth: Some(Arc::new(client_auth)),
+            ..self
+        }
+    }
+
+    /// Refuse a call outside the scopes and paths of the session's token.
+    fn authorize_call(&self, tool: &str, arguments: Option<&JsonObject>) -> Result<(), ErrorData> {
+        self.client_auth.as_ref().map_or(Ok(()), |client_auth| {
+            client_auth.authorize_call(tool, arguments)
+        })
+    }
+
+    /// Refuse a prompt outside the scopes and paths of the session's token.
+    fn authorize_prompt(
+        &self,
+        prompt: &str,
+        arguments: Option<&JsonObject>,
+    ) -> Result<(), ErrorData> {
+        self.client_auth.as_ref().map_or(Ok(()), |client_auth| {
+            client_auth.authorize_prompt(prompt, arguments)
+        })
+    }
+
+    /// Queue shared by the build, archive, and test tools.
+    pub fn job_queue(&self) -> VisionOsJobQueue {
+        self.visionos_queue.clone()
+    }
+
+    async fn run_xcodebuild_job(
+        &self,
+        peer: Peer<RoleServer>,
+        progress_token: Option<ProgressToken>,
+        mut request: VisionOsBuildRequest,
+        kind: ArtifactKind,
+    ) -> Result<BuildVisionOsAppResponse, ErrorData> {
+        let server_config = self.config();
+        let config = &server_config.visionos;
+        if let Err(err) = request
+            .apply_project_defaults(config)
+            .and_then(|()| request.validate(config))
+        {
+            return Err(visionos::validation_error_to_error_data(err));
+        }
+        let xcode_config = config.with_xcode(request.xcode.as_deref());
+        let config = &*xcode_config;
+        if request.dry_run {
+            return Ok(visionos::plan_build(
+                &request,
+                config,
+                request.resolve_job_id(),
+                &self.artifact_store.root_dir(),
+                kind,
+            ));
+        }
+
+        let started = Instant::now();
+        let cache_key = if config.build_cache {
+            build_cache::build_cache_key(&request, config, kind).await
+        } else {
+            None
+        };
+        if let Some(cache_key) = cache_key
+            .as_deref()
+            .filter(|_| !request.force_rebuild && !request.clean)
+        {
+            if let Some(record) = self.artifact_store.find_cached(cache_key).await {
+                tracing::info!(
+                    target: "rmcp_sample::visionos",
+                    job_id = %record.job_id,
+                    scheme = %request.scheme,
+                    "Serving visionOS build from the build cache"
+                );
+                return Ok(build_cache::cached_response(&record, started));
+            }
+        }
+
+        let job_id = self.claim_job_id(&request).await?;
+        // Requests that name their own job_id keep it, so only anonymous requests are coalesced.
+        let leader = match request.job_id {
+            Some(_) => None,
+            None => {
+                let fingerprint = request_fingerprint(&request, kind, config);
+                loop {
+                    match self.visionos_queue.join_or_lead(&fingerprint, job_id) {
+                        JobRole::Leader(leader) => break Some(leader),
+                        JobRole::Follower(follower) => {
+                            tracing::info!(
+                                target: "rmcp_sample::visionos",
+                                job_id = %follower.job_id,
+                                scheme = %request.scheme,
+                                "Attaching identical visionOS request to the in-flight job"
+                            );
+                            // A leader that stopped without a result hands the work to the next caller.
+                            if let Some(result) = follower.result().await {
+                                return result;
+                            }
+                        }
+                    }
+                }
+            }
+        };
+
+        let result = async {
+            let artifact_root = self.artifact_store.root_dir();
+            let mut trace = JobTrace::new(job_id, kind, &request, config, &artifact_root);
+            let request_ref = &request;
+            let job = |monitor: JobMonitor| async move {
+                let result = match kind {
+                    ArtifactKind::Build | ArtifactKind::Ipa | ArtifactKind::Media => {
+                        visionos::run_build(request_ref, config, &monitor, job_id, artifact_root)
+                            .await
+                    }
+                    ArtifactKind::Archive => {
+                        visionos::run_archive(request_ref, config, &monitor, job_id, artifact_root)
+                            .await
+                    }
+                };
+                (result, monitor.phase_timings(), monitor.attempts())
+            };
+            let (result, phases, attempts) = match self
+                .run_queued(peer, progress_token, job_id, &request, Some(kind), job)
+                .await
+            {
+                Ok(result) => result,
+                Err(outcome) => {
+                    self.record_cancellation(job_id, kind, &request, outcome)
+                        .await;
+                    let err = visionos::cancelled_error_to_error_data(outcome, job_id);
+                    trace.finish(outcome.into(), &[], Some(&err));
+                    self.save_trace(&trace);
+                    notifications::notify_job_finished(&server_config.notifications, &trace);
+                    return Err(err);
+                }
+            };
+
+            let result = match result {
+                Ok(resp) => {
+                    trace.diagnostics = resp.diagnostics.clone();
+                    if let Err(store_err) = self
+                        .artifact_store
+                        .record_success(
+                            job_id,
+                            kind,
+                            request.scheme.clone(),
+                            ArtifactFile {
+                                path: PathBuf::from(&resp.artifact_path),
+                                sha256: resp.artifact_sha256.clone(),
+                                cache_key,
+                                ttl_secs: config
+                                    .root_for(request.project_root())
+                                    .and_then(|root| root.artifact_ttl_secs),
+                            },
+                            resp.log_excerpt.clone(),
+                            Utc::now(),
+                        )
+                        .await
+                    {
+                        let err = VisionOsBuildError::from(store_err);
+                        Err(visionos::runtime_error_to_error_data(err, job_id))
+                    } else {
+                        self.artifact_store
+                            .record_duration(
+                                kind,
+                                &request.scheme,
+                                Duration::from_millis(
+                                    u64::try_from(resp.duration_ms).unwrap_or(u64::MAX),
+                                ),
+                            )
+                            .await;
+                        Ok(resp)
+                    }
+                }
+                Err(err) => {
+                    self.record_build_failure(job_id, kind, &err, &request)
+                        .await;
+                    if let VisionOsBuildError::CommandFailed { diagnostics, .. } = &err {
+                        trace.diagnostics = diagnostics.clone();
+                    }
+                    Err(visionos::runtime_error_to_error_data(err, job_id))
+                }
+            };
+            if !attempts.is_empty() {
+                if let Err(store_err) = self.artifact_store.record_attempts(job_id, attempts).await
+                {
+                    tracing::warn!(
+                        target: "rmcp_sample::visionos",
+                        job_id = %job_id,
+                        error = %store_err,
+                        "Failed to record build attempts"
+                    );
+                }
+            }
+            match &result {
+                Ok(_) => trace.finish(BuildJobStatus::Succeeded, &phases, None),
+                Err(err) => trace.finish(BuildJobStatus::Failed, &phases, Some(err)),
+            }
+            self.save_trace(&trace);
+            notifications::notify_job_finished(&server_config.notifications, &trace);
+            result
+        }
+        .await;
+        if let Some(leader) = leader {
+            leader.publish(result.clone());
+        }
+        result
+    }
+
+    async fn run_test_job(
+        &self,
+        peer: Peer<RoleServer>,
+        progress_token: Option<ProgressToken>,
+        mut request: VisionOsBuildRequest,
+    ) -> Result<TestVisionOsAppResponse, ErrorData> {
+        let server_config = self.config();
+        let config = &server_config.visionos;
+        if let Err(err) = request
+            .reject_dry_run(visionos::TEST_TOOL_ID)
+            .and_then(|()| request.apply_project_defaults(config))
+            .and_then(|()| request.validate(config))
+        {
+            return Err(visionos::validation_error_to_error_data(err));
+        }
+        let xcode_config = config.with_xcode(request.xcode.as_deref());
+        let config = &*xcode_config;
+
+        let job_id = self.claim_job_id(&request).await?;
+        let request_ref = &request;
+        let result = self
+            .run_queued(
+                peer,
+                progress_token,
+                job_id,
+                &request,
+                None,
+                |monitor| async move {
+                    visionos::run_tests(request_ref, config, &monitor, job_id).await
+                },
+            )
+            .await
+            .map_err(|outcome| visionos::cancelled_error_to_error_data(outcome, job_id))?;
+
+        let response = result.map_err(|err| visionos::runtime_error_to_error_data(err, job_id))?;
+        self.resource_store
+            .publish(response.summary_resource(&request.scheme))
+            .await;
+        Ok(response)
+    }
+
+    /// Run one `run_pipeline` step; `build_job_id` is the job of the pipeline's build step.
+    async fn run_pipeline_step(
+        &self,
+        step: PipelineStepKind,
+        request: &RunPipelineRequest,
+        peer: Peer<RoleServer>,
+        progress_token: Option<ProgressToken>,
+        build_job_id: Option<&str>,
+    ) -> Result<PipelineStepOutput, ErrorData> {
+        let server_config = self.config();
+        let config = &server_config.visionos;
+        let udid = request.simulator_udid.as_deref().unwrap_or_default();
+        match step {
+            PipelineStepKind::SandboxCheck => {
+                let response = visionos::validate_sandbox_policy(
+                    visionos::pipeline::sandbox_request(&request.build),
+                    config,
+                    &self.sandbox_cache,
+                )
+                .await
+                .map_err(visionos::sandbox_error_to_error_data)?;
+                let succeeded = response.status == SandboxStatus::Ok;
+                Ok(PipelineStepOutput::new(None, succeeded, &response))
+            }
+            PipelineStepKind::Build => {
+                let response = self
+                    .run_xcodebuild_job(
+                        peer,
+                        progress_token,
+                        request.build.clone(),
+                        ArtifactKind::Build,
+                    )
+                    .await?;
+                Ok(PipelineStepOutput::new(
+                    Some(response.job_id.clone()),
+                    true,
+                    &response,
+                ))
+            }
+            PipelineStepKind::Test => {
+                let response = self
+                    .run_test_job(peer, progress_token, request.build.clone())
+                    .await?;
+                Ok(PipelineStepOutput::new(
+                    Some(response.job_id.clone()),
+                    response.status == "passed",
+                    &response,
+                ))
+            }
+            PipelineStepKind::Install => {
+                let job_id = build_job_id.unwrap_or_default();
+                let app = visionos::built_app(&self.artifact_store.root_dir(), job_id)?;
+                let response = visionos::install_app(udid, &app, config).await?;
+                Ok(PipelineStepOutput::new(
+                    Some(job_id.to_string()),
+                    true,
+                    &response,
+                ))
+            }
+            PipelineStepKind::Screenshot => {
+                let response = visionos::capture_simulator_media(
+                    &self.artifact_store,
+                    config,
+                    CaptureSimulatorMediaRequest {
+                        udid: udid.to_string(),
+                        kind: MediaKind::Screenshot,
+                        duration_secs: None,
+                        scheme: Some(request.build.scheme.clone()),
+                    },
+                )
+                .await?;
+                Ok(PipelineStepOutput::new(
+                    Some(response.job_id.clone()),
+                    true,
+                    &response,
+                ))
+            }
+        }
+    }
+
+    /// Persist the job trace for `export_job_trace`; a failure only loses the trace.
+    fn save_trace(&self, trace: &JobTrace) {
+        if let Err(err) = visionos::save_job_trace(&self.artifact_store.root_dir(), trace) {
+            tracing::warn!(
+                target: "rmcp_sample::visionos",
+                job_id = %trace.job_id,
+                error = %err,
+                "Failed to record job trace"
+            );
+        }
+    }
+
+    /// Pick the job ID for `request`, rejecting caller-chosen IDs that are still in the queue.
+    async fn claim_job_id(&self, request: &VisionOsBuildRequest) -> Result<Uuid, ErrorData> {
+        let job_id = request.resolve_job_id();
+        if self.visionos_queue.is_closed() {
+            return Err(visionos::shutting_down_error_to_error_data(job_id));
+        }
+        if self.visionos_queue.contains(job_id).await {
+            return Err(visionos::validation_error_to_error_data(
+                BuildRequestValidationError::JobIdInUse {
+                    job_id: job_id.to_string(),
+                },
+            ));
+        }
+        Ok(job_id)
+    }
+
+    /// Wait for the queue, then run `job` until it finishes or `cancel_build_job` stops it.
+    ///
+    /// Dropping `job` on cancellation kills the `xcodebuild` child (`kill_on_drop`). While the
+    /// job runs, heartbeats go to the client every `heartbeat_interval_secs`. When the call itself
+    /// is dropped (e.g. by `[tools.<name>] timeout_secs`), the slot is still released. Build and
+    /// archive jobs pass their `kind`, which picks the duration estimate for `get_queue_status`
+    /// and records a dropped job as cancelled while running.
+    async fn run_queued<T, Fut>(
+        &self,
+        peer: Peer<RoleServer>,
+        progress_token: Option<ProgressToken>,
+        job_id: Uuid,
+        request: &VisionOsBuildRequest,
+        kind: Option<ArtifactKind>,
+        job: impl FnOnce(JobMonitor) -> Fut,
+    ) -> Result<T, CancelOutcome>
+    where
+        Fut: Future<Output = T>,
+    {
+        let estimated_duration = match kind {
+            Some(kind) => {
+                self.artifact_store
+                    .estimated_duration(kind, &request.scheme)
+                    .await
+            }
+            None => None,
+        };
+        let root_limit = self
+            .config()
+            .visionos
+            .root_for(request.project_root())
+            .and_then(|root| {
+                Some(RootLimit {
+                    label: root.label.clone(),
+                    max_concurrent: root.max_concurrent?.into(),
+                })
+            });
+        let Some(ticket) = self
+            .visionos_queue
+            .wait_for_turn_in_root(
+                job_id,
+                request.project_root(),
+                request.priority,
+                root_limit,
+                estimated_duration,
+            )
+            .await
+        else {
+            return Err(CancelOutcome::CancelledQueued);
+        };
+        let mut running = RunningJobGuard {
+            queue: self.visionos_queue.clone(),
+            ticket: ticket.clone(),
+            tasks: Vec::new(),
+            abandoned_record: kind
+                .map(|kind| (self.artifact_store.clone(), kind, request.scheme.clone())),
+            finished: false,
+        };
+        let heartbeat_interval = self.config().visionos.heartbeat_interval_secs;
+        if heartbeat_interval > 0 {
+            running.tasks.push(tokio::spawn(forward_heartbeats(
+                peer.clone(),
+                progress_token,
+                ticket.monitor.clone(),
+                Duration::from_secs(heartbeat_interval.into()),
+            )));
+        }
+        running.tasks.push(tokio::spawn(forward_timeout_warning(
+            peer,
+            ticket.monitor.clone(),
+        )));
+        let result = tokio::select! {
+            output = job(ticket.monitor.clone()) => Ok(output),
+            _ = ticket.cancellation.cancelled() => Err(CancelOutcome::CancelledRunning),
+        };
+        running.finish().await;
+        result
+    }
+
+    async fn record_cancellation(
+        &self,
+        job_id: Uuid,
+        kind: ArtifactKind,
+        request: &VisionOsBuildRequest,
+        outcome: CancelOutcome,
+    ) {
+        if let Err(store_err) = self
+            .artifact_store
+            .record_cancellation(job_id, kind, request.scheme.clone(), outcome, Utc::now())
+            .await
+        {
+            tracing::warn!(
+                target: "rmcp_sample::visionos",
+                job_id = %job_id,
+                error = %store_err,
+                "Failed to record build cancellation"
+            );
+        }
+    }
+
     async fn record_build_failure(
         &self,
         job_id: Uuid,
+        kind: ArtifactKind,
         err: &VisionOsBuildError,
         request: &VisionOsBuildRequest,
     ) {
@@ -71,15 +690,20 @@ impl VisionOsServer {
             project_path: request.project_path.clone(),
             workspace: request.workspace.clone(),
             scheme: request.scheme.clone(),
-            configuration: request.configuration.as_str().to_string(),
+            configuration: request.configuration().as_str().to_string(),
             destination: request.destination.clone(),
-            xcode_path: self.config.visionos.xcode_path.clone(),
+            xcode_path: self
+                .config()
+                .visionos
+                .with_xcode(request.xcode.as_deref())
+                .xcode_path
+                .clone(),
             env_overrides: request.env_overrides.clone(),
             extra_args: request.extra_args.clone(),
         });
         if let Err(store_err) = self
             .artifact_store
-            .record_failure(job_id, log_excerpt, failure_context, Utc::now())
+            .record_failure(job_id, kind, log_excerpt, failure_context, Utc::now())
             .await
         {
             tracing::warn!(
@@ -100,46 +724,259 @@ impl VisionOsServer {
     )]
     async fn build_visionos_app(
         &self,
+        peer: Peer<RoleServer>,
+        meta: Meta,
         Parameters(request): Parameters<VisionOsBuildRequest>,
     ) -> Result<Json<BuildVisionOsAppResponse>, ErrorData> {
-        if let Err(err) = request.validate(&self.config.visionos) {
-            return Err(visionos::validation_error_to_error_data(err));
-        }
+        self.run_xcodebuild_job(
+            peer,
+            meta.get_progress_token(),
+            request,
+            ArtifactKind::Build,
+        )
+        .await
+        .map(Json)
+    }
 
-        let job_id = Uuid::new_v4();
-        let _ticket = self.visionos_queue.wait_for_turn(job_id).await;
-        let result = visionos::run_build(
-            &request,
-            &self.config.visionos,
-            job_id,
-            self.artifact_store.root_dir(),
+    #[tool(
+        name = "archive_visionos_app",
+        description = "Archive a visionOS project and return .xcarchive artifact metadata"
+    )]
+    async fn archive_visionos_app(
+        &self,
+        peer: Peer<RoleServer>,
+        meta: Meta,
+        Parameters(request): Parameters<VisionOsBuildRequest>,
+    ) -> Result<Json<BuildVisionOsAppResponse>, ErrorData> {
+        self.run_xcodebuild_job(
+            peer,
+            meta.get_progress_token(),
+            request,
+            ArtifactKind::Archive,
         )
-        .await;
-        self.visionos_queue.finish_job(job_id).await;
+        .await
+        .map(Json)
+    }
 
-        match result {
-            Ok(resp) => {
-                if let Err(store_err) = self
-                    .artifact_store
-                    .record_success(
-                        job_id,
-                        PathBuf::from(&resp.artifact_path),
-                        resp.artifact_sha256.clone(),
-                        resp.log_excerpt.clone(),
-                        Utc::now(),
-                    )
-                    .await
-                {
-                    let err = VisionOsBuildError::from(store_err);
-                    return Err(visionos::runtime_error_to_error_data(err, job_id));
-                }
-                Ok(Json(resp))
+    #[tool(
+        name = "test_visionos_app",
+        description = "Run visionOS tests and publish a Markdown summary resource"
+    )]
+    async fn test_visionos_app(
+        &self,
+        peer: Peer<RoleServer>,
+        meta: Meta,
+        Parameters(request): Parameters<VisionOsBuildRequest>,
+    ) -> Result<Json<TestVisionOsAppResponse>, ErrorData> {
+        self.run_test_job(peer, meta.get_progress_token(), request)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "run_pipeline",
+        description = "Run sandbox_check, build, test, install, and screenshot steps in order as one job, stopping at the first failure"
+    )]
+    async fn run_pipeline(
+        &self,
+        peer: Peer<RoleServer>,
+        meta: Meta,
+        Parameters(request): Parameters<RunPipelineRequest>,
+    ) -> Result<Json<RunPipelineResponse>, ErrorData> {
+        visionos::validate_pipeline(&request)?;
+        if let Some((step, tool)) = request.steps.iter().find_map(|step| {
+            step.tool()
+                .filter(|tool| !self.tool_router.has_route(tool))
+                .map(|tool| (step, tool))
+        }) {
+            return Err(visionos::pipeline::tool_disabled_error(
+                tool,
+                json!({ "step": step.as_str() }),
+            ));
+        }
+        let pipeline_id = Uuid::new_v4();
+        let progress_token = meta.get_progress_token();
+        let started = Instant::now();
+        let mut build_job_id = None;
+        let mut failed = false;
+        let mut steps = Vec::with_capacity(request.steps.len());
+        for &step in &request.steps {
+            if failed {
+                steps.push(PipelineStepResult::skipped(step));
+                continue;
             }
-            Err(err) => {
-                self.record_build_failure(job_id, &err, &request).await;
-                Err(visionos::runtime_error_to_error_data(err, job_id))
+            let step_started = Instant::now();
+            let outcome = self
+                .run_pipeline_step(
+                    step,
+                    &request,
+                    peer.clone(),
+                    progress_token.clone(),
+                    build_job_id.as_deref(),
+                )
+                .await;
+            let result = PipelineStepResult::finished(step, outcome, step_started.elapsed());
+            tracing::info!(
+                target: "rmcp_sample::visionos",
+                pipeline_id = %pipeline_id,
+                step = step.as_str(),
+                status = ?result.status,
+                job_id = result.job_id.as_deref().unwrap_or("-"),
+                "Pipeline step finished"
+            );
+            failed = result.status != PipelineStepStatus::Succeeded;
+            if step == PipelineStepKind::Build && !failed {
+                build_job_id = result.job_id.clone();
             }
+            steps.push(result);
         }
+
+        let mut response = RunPipelineResponse::new(pipeline_id, steps, started.elapsed());
+        match visionos::save_pipeline_record(
+            &self.artifact_store.root_dir(),
+            pipeline_id,
+            &response,
+        ) {
+            Ok(path) => response.record_path = Some(path.to_string_lossy().to_string()),
+            Err(err) => tracing::warn!(
+                target: "rmcp_sample::visionos",
+                pipeline_id = %pipeline_id,
+                error = %err,
+                "Failed to record pipeline"
+            ),
+        }
+        Ok(Json(response))
+    }
+
+    #[tool(
+        name = "create_visionos_project",
+        description = "Create a minimal visionOS app or Swift package in an allowed directory and optionally build it"
+    )]
+    async fn create_visionos_project(
+        &self,
+        peer: Peer<RoleServer>,
+        meta: Meta,
+        Parameters(request): Parameters<CreateVisionOsProjectRequest>,
+    ) -> Result<Json<CreateVisionOsProjectResponse>, ErrorData> {
+        if request.build && !self.tool_router.has_route(visionos::BUILD_TOOL_ID) {
+            return Err(visionos::pipeline::tool_disabled_error(
+                visionos::BUILD_TOOL_ID,
+                json!({ "field": "build" }),
+            ));
+        }
+        let mut response = visionos::create_visionos_project(&request, &self.config().visionos)?;
+        self.resource_store
+            .publish(response.scheme_resource())
+            .await;
+        if request.build {
+            let build_request = serde_json::from_value(json!({
+                "project_path": response.project_path,
+                "scheme": response.scheme,
+            }))
+            .expect("project_path and scheme form a build request");
+            match self
+                .run_xcodebuild_job(
+                    peer,
+                    meta.get_progress_token(),
+                    build_request,
+                    ArtifactKind::Build,
+                )
+                .await
+            {
+                Ok(build) => response.build = Some(build),
+                Err(err) => {
+                    response.build_error = Some(json!({ "message": err.message, "data": err.data }))
+                }
+            }
+        }
+        Ok(Json(response))
+    }
+
+    #[tool(
+        name = "clean_visionos_project",
+        description = "Run xcodebuild clean for an allowed project and scheme, optionally deleting its cached DerivedData, and report freed bytes"
+    )]
+    async fn clean_visionos_project(
+        &self,
+        peer: Peer<RoleServer>,
+        meta: Meta,
+        Parameters(request): Parameters<CleanVisionOsProjectRequest>,
+    ) -> Result<Json<CleanVisionOsProjectResponse>, ErrorData> {
+        let CleanVisionOsProjectRequest {
+            build: mut request,
+            wipe_derived_data,
+        } = request;
+        request.use_cached_derived_data |= wipe_derived_data;
+        let server_config = self.config();
+        let config = &server_config.visionos;
+        if let Err(err) = request
+            .reject_dry_run(visionos::CLEAN_TOOL_ID)
+            .and_then(|()| request.apply_project_defaults(config))
+            .and_then(|()| request.validate(config))
+        {
+            return Err(visionos::validation_error_to_error_data(err));
+        }
+        let xcode_config = config.with_xcode(request.xcode.as_deref());
+        let config = &*xcode_config;
+
+        let job_id = self.claim_job_id(&request).await?;
+        let request_ref = &request;
+        let result = self
+            .run_queued(
+                peer,
+                meta.get_progress_token(),
+                job_id,
+                &request,
+                None,
+                |monitor| async move {
+                    visionos::run_clean(request_ref, wipe_derived_data, config, &monitor, job_id)
+                        .await
+                },
+            )
+            .await
+            .map_err(|outcome| visionos::cancelled_error_to_error_data(outcome, job_id))?;
+        result
+            .map(Json)
+            .map_err(|err| visionos::runtime_error_to_error_data(err, job_id))
+    }
+
+    #[tool(
+        name = "cancel_build_job",
+        description = "Cancel a queued or running build, archive, or test job by job_id"
+    )]
+    async fn cancel_build_job(
+        &self,
+        Parameters(request): Parameters<CancelBuildJobRequest>,
+    ) -> Result<Json<CancelBuildJobResponse>, ErrorData> {
+        visionos::cancel_build_job(&self.visionos_queue, request)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "extend_build_deadline",
+        description = "Add minutes to the running job's timeout, up to max_extended_build_minutes"
+    )]
+    async fn extend_build_deadline(
+        &self,
+        Parameters(request): Parameters<ExtendBuildDeadlineRequest>,
+    ) -> Result<Json<ExtendBuildDeadlineResponse>, ErrorData> {
+        visionos::extend_build_deadline(&self.visionos_queue, &self.config().visionos, request)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "get_queue_status",
+        description = "List running and waiting build jobs with queue position and estimated wait"
+    )]
+    async fn get_queue_status(
+        &self,
+        Parameters(request): Parameters<GetQueueStatusRequest>,
+    ) -> Result<Json<QueueStatusResponse>, ErrorData> {
+        Ok(Json(
+            visionos::get_queue_status(&self.visionos_queue, request).await,
+        ))
     }
 
     #[tool(
@@ -150,7 +987,10 @@ impl VisionOsServer {
         &self,
         Parameters(request): Parameters<SandboxPolicyRequest>,
     ) -> Result<Json<SandboxPolicyResponse>, ErrorData> {
-        match visionos::validate_sandbox_policy(request, &self.config.visionos).await {
+        let config = self.config();
+        match visionos::validate_sandbox_policy(request, &config.visionos, &self.sandbox_cache)
+            .await
+        {
             Ok(response) => Ok(Json(response)),
             Err(failure) => Err(visionos::sandbox_error_to_error_data(failure)),
         }
@@ -164,12 +1004,25 @@ impl VisionOsServer {
         &self,
         Parameters(request): Parameters<InspectXcodeSdksRequest>,
     ) -> Result<Json<InspectXcodeSdksResponse>, ErrorData> {
-        match visionos::inspect_xcode_sdks(request, &self.config.visionos).await {
+        match visionos::inspect_xcode_sdks(request, &self.config().visionos).await {
             Ok(response) => Ok(Json(response)),
             Err(failure) => Err(visionos::sandbox_error_to_error_data(failure)),
         }
     }
 
+    #[tool(
+        name = "list_xcode_installations",
+        description = "List the configured Xcode installations (visionos.xcode_path and visionos.xcodes) with their version and build number"
+    )]
+    async fn list_xcode_installations(
+        &self,
+        Parameters(request): Parameters<ListXcodeInstallationsRequest>,
+    ) -> Result<Json<ListXcodeInstallationsResponse>, ErrorData> {
+        Ok(Json(
+            visionos::list_xcode_installations(request, &self.config().visionos).await,
+        ))
+    }
+
     #[tool(
         name = "inspect_xcode_schemes",
         description = "Inspect available Xcode schemes for a project path"
@@ -178,7 +1031,87 @@ impl VisionOsServer {
         &self,
         Parameters(request): Parameters<InspectXcodeSchemesRequest>,
     ) -> Result<Json<InspectXcodeSchemesResponse>, ErrorData> {
-        visionos::inspect_xcode_schemes(request, &self.config.visionos)
+        visionos::inspect_xcode_schemes(request, &self.config().visionos)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "list_allowed_projects",
+        description = "List the .xcodeproj, .xcworkspace, and Package.swift projects under visionos.allowed_paths, with their type, to use as project_path"
+    )]
+    async fn list_allowed_projects(
+        &self,
+        Parameters(request): Parameters<ListAllowedProjectsRequest>,
+    ) -> Result<Json<ListAllowedProjectsResponse>, ErrorData> {
+        Ok(Json(visionos::list_allowed_projects(
+            request,
+            &self.config().visionos,
+        )))
+    }
+
+    #[tool(
+        name = "describe_build_constraints",
+        description = "Describe the allowlists and length limits build, archive, and test requests are validated against"
+    )]
+    async fn describe_build_constraints(
+        &self,
+        Parameters(request): Parameters<DescribeBuildConstraintsRequest>,
+    ) -> Result<Json<DescribeBuildConstraintsResponse>, ErrorData> {
+        Ok(Json(visionos::describe_build_constraints(
+            request,
+            &self.config().visionos,
+        )))
+    }
+
+    #[tool(
+        name = "list_project_schemes",
+        description = "List schemes, targets, and configurations for an allowed project path"
+    )]
+    async fn list_project_schemes(
+        &self,
+        Parameters(request): Parameters<ListProjectSchemesRequest>,
+    ) -> Result<Json<ListProjectSchemesResponse>, ErrorData> {
+        visionos::list_project_schemes(request, &self.config().visionos)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "list_destinations",
+        description = "List the run destinations (platform, name, OS version, id) xcodebuild offers for an allowed project and scheme"
+    )]
+    async fn list_destinations(
+        &self,
+        Parameters(request): Parameters<ListDestinationsRequest>,
+    ) -> Result<Json<ListDestinationsResponse>, ErrorData> {
+        visionos::list_destinations(request, &self.config().visionos)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "show_build_settings",
+        description = "Show bundle ID, deployment target, and signing build settings of an allowed project and scheme via xcodebuild -showBuildSettings"
+    )]
+    async fn show_build_settings(
+        &self,
+        Parameters(request): Parameters<ShowBuildSettingsRequest>,
+    ) -> Result<Json<ShowBuildSettingsResponse>, ErrorData> {
+        visionos::show_build_settings(request, &self.config().visionos)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "resolve_package_dependencies",
+        description = "Resolve Swift package dependencies for an allowed project and list the pinned packages"
+    )]
+    async fn resolve_package_dependencies(
+        &self,
+        Parameters(request): Parameters<ResolvePackageDependenciesRequest>,
+    ) -> Result<Json<ResolvePackageDependenciesResponse>, ErrorData> {
+        visionos::resolve_package_dependencies(request, &self.config().visionos)
             .await
             .map(Json)
     }
@@ -209,17 +1142,515 @@ impl VisionOsServer {
             .await
             .map(Json)
     }
+
+    #[tool(
+        name = "diff_build_logs",
+        description = "Compare warnings and errors between two build jobs"
+    )]
+    async fn diff_build_logs(
+        &self,
+        Parameters(request): Parameters<DiffBuildLogsRequest>,
+    ) -> Result<Json<DiffBuildLogsResponse>, ErrorData> {
+        visionos::diff_build_logs(&self.artifact_store, request)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "export_artifact",
+        description = "Copy a build artifact (optionally logs and dSYMs) into an allowlisted export directory"
+    )]
+    async fn export_artifact(
+        &self,
+        Parameters(request): Parameters<ExportArtifactRequest>,
+    ) -> Result<Json<ExportArtifactResponse>, ErrorData> {
+        visionos::export_artifact(&self.artifact_store, &self.config().visionos, request)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "symbolicate_crash",
+        description = "Symbolicate a crash log (text or .ips) with atos against the dSYMs kept by a build job"
+    )]
+    async fn symbolicate_crash(
+        &self,
+        Parameters(request): Parameters<SymbolicateCrashRequest>,
+    ) -> Result<Json<SymbolicateCrashResponse>, ErrorData> {
+        visionos::symbolicate_crash(&self.artifact_store, &self.config().visionos, request)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "export_visionos_ipa",
+        description = "Export a signed .ipa from an archive job with xcodebuild -exportArchive and store it as a new artifact"
+    )]
+    async fn export_visionos_ipa(
+        &self,
+        Parameters(request): Parameters<ExportVisionOsIpaRequest>,
+    ) -> Result<Json<ExportVisionOsIpaResponse>, ErrorData> {
+        visionos::export_visionos_ipa(&self.artifact_store, &self.config().visionos, request)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "export_job_trace",
+        description = "Bundle the request, environment, phase timings, full log, diagnostics, and error of a job into one JSON or zip file for bug reports"
+    )]
+    async fn export_job_trace(
+        &self,
+        Parameters(request): Parameters<ExportJobTraceRequest>,
+    ) -> Result<Json<ExportJobTraceResponse>, ErrorData> {
+        visionos::export_job_trace(&self.artifact_store, request)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "fetch_build_log",
+        description = "Page through the complete xcodebuild output of a job by byte offset and limit"
+    )]
+    async fn fetch_build_log(
+        &self,
+        Parameters(request): Parameters<FetchBuildLogRequest>,
+    ) -> Result<Json<FetchBuildLogResponse>, ErrorData> {
+        visionos::fetch_build_log(&self.artifact_store, request)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "list_build_jobs",
+        description = "List recent build, archive, and test jobs with status, scheme, finish time, and remaining TTL, newest first"
+    )]
+    async fn list_build_jobs(
+        &self,
+        Parameters(request): Parameters<ListBuildJobsRequest>,
+    ) -> Result<Json<ListBuildJobsResponse>, ErrorData> {
+        Ok(Json(
+            visionos::list_build_jobs(&self.artifact_store, request).await,
+        ))
+    }
+
+    #[tool(
+        name = "purge_artifacts",
+        description = "Admin: delete the artifacts of one job, all expired jobs, or all finished jobs, returning the removed job_ids and freed bytes"
+    )]
+    async fn purge_artifacts(
+        &self,
+        Parameters(request): Parameters<PurgeArtifactsRequest>,
+    ) -> Result<Json<PurgeArtifactsResponse>, ErrorData> {
+        visionos::purge_artifacts(&self.artifact_store, request)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "list_sessions",
+        description = "Admin: list open MCP sessions with peer address, connect time, and tool calls made, to find stuck or runaway clients"
+    )]
+    async fn list_sessions(
+        &self,
+        Parameters(_request): Parameters<ListSessionsRequest>,
+    ) -> Result<Json<ListSessionsResponse>, ErrorData> {
+        Ok(Json(
+            self.sessions.list(self.config().server.max_connections),
+        ))
+    }
+
+    #[tool(
+        name = "list_error_codes",
+        description = "List every error code the server can return with its message, remediation, retryable flag, and sandbox_state"
+    )]
+    async fn list_error_codes(
+        &self,
+        Parameters(request): Parameters<ListErrorCodesRequest>,
+    ) -> Result<Json<ListErrorCodesResponse>, ErrorData> {
+        Ok(Json(error_catalog::list_error_codes(request)))
+    }
+
+    #[tool(
+        name = "list_simulators",
+        description = "List Apple Vision Pro simulators and their current state"
+    )]
+    async fn list_simulators(
+        &self,
+        Parameters(request): Parameters<ListSimulatorsRequest>,
+    ) -> Result<Json<ListSimulatorsResponse>, ErrorData> {
+        visionos::list_simulators(request, &self.config().visionos)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "boot_simulator",
+        description = "Boot a visionOS simulator by UDID"
+    )]
+    async fn boot_simulator(
+        &self,
+        Parameters(request): Parameters<SimulatorDeviceRequest>,
+    ) -> Result<Json<SimulatorStateResponse>, ErrorData> {
+        visionos::boot_simulator(request, &self.config().visionos)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "shutdown_simulator",
+        description = "Shut down a visionOS simulator by UDID"
+    )]
+    async fn shutdown_simulator(
+        &self,
+        Parameters(request): Parameters<SimulatorDeviceRequest>,
+    ) -> Result<Json<SimulatorStateResponse>, ErrorData> {
+        visionos::shutdown_simulator(request, &self.config().visionos)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "capture_simulator_media",
+        description = "Capture a screenshot or short recording of a booted visionOS simulator and store it as a fetchable artifact"
+    )]
+    async fn capture_simulator_media(
+        &self,
+        Parameters(request): Parameters<CaptureSimulatorMediaRequest>,
+    ) -> Result<Json<CaptureSimulatorMediaResponse>, ErrorData> {
+        visionos::capture_simulator_media(&self.artifact_store, &self.config().visionos, request)
+            .await
+            .map(Json)
+    }
+
+    #[tool(
+        name = "list_devices",
+        description = "List paired Apple Vision Pro devices and whether builds may target them"
+    )]
+    async fn list_devices(
+        &self,
+        Parameters(request): Parameters<ListDevicesRequest>,
+    ) -> Result<Json<ListDevicesResponse>, ErrorData> {
+        visionos::list_devices(request, &self.config().visionos)
+            .await
+            .map(Json)
+    }
 }
 
-#[tool_handler(router = self.tool_router)]
 impl ServerHandler for VisionOsServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_experimental_with(
+                    [(
+                        schema_version::SCHEMA_VERSION_CAPABILITY.to_string(),
+                        schema_version::capability(),
+                    )]
+                    .into(),
+                )
+                .enable_tools()
+                .enable_prompts()
+                .enable_resources()
+                .enable_logging()
+                .build(),
             instructions: Some((*self.instructions).clone()),
             ..ServerInfo::default()
         }
     }
+
+    /// Check the client's token scopes and rate limits, route the call here or to a federation
+    /// downstream, and stamp the negotiated `schema_version` on its response or error.
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let version = schema_version::negotiate(&context.meta)?;
+        let config = self.config();
+        let tool = request.name.to_string();
+        self.authorize_call(&tool, request.arguments.as_ref())
+            .map_err(|err| schema_version::stamp_error(err, version))?;
+        let limits = config.tools.limits(&tool);
+        let _permit = self
+            .rate_limiter
+            .try_acquire(&tool, &config.server, limits)
+            .map_err(|err| schema_version::stamp_error(err, version))?;
+        let _activity = self
+            .session
+            .as_ref()
+            .map(|session| session.begin_call(&tool));
+        let call = async {
+            if self.tool_router.has_route(&tool) {
+                let artifact_root = self.artifact_store.root_dir();
+                let forwarded = self
+                    .federation
+                    .forward(&request, &context.meta, &context.peer, &artifact_root)
+                    .await;
+                if let Some(result) = forwarded {
+                    return result;
+                }
+            }
+            self.tool_router
+                .call(ToolCallContext::new(self, request, context))
+                .await
+        };
+        match rate_limit::with_timeout(&tool, limits, call).await {
+            Ok(result) => Ok(schema_version::stamp_result(result, version)),
+            Err(err) => Err(schema_version::stamp_error(err, version)),
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
+
+    async fn set_level(
+        &self,
+        _request: SetLevelRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        Ok(())
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, ErrorData> {
+        Ok(ListPromptsResult {
+            prompts: self.prompt_router.list_all(),
+            next_cursor: None,
+        })
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        self.authorize_prompt(&request.name, request.arguments.as_ref())?;
+        let prompt = PromptContext::new(self, request.name, request.arguments, context);
+        self.prompt_router.get_prompt(prompt).await
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        let mut resources = vec![
+            status::status_resource(),
+            capabilities::capabilities_resource(),
+        ];
+        resources.extend(self.resource_store.list().await);
+        Ok(ListResourcesResult {
+            resources,
+            next_cursor: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        if request.uri == status::STATUS_RESOURCE_URI {
+            let status =
+                status::collect_status(self.started_at, &self.visionos_queue, &self.artifact_store)
+                    .await;
+            return Ok(ReadResourceResult {
+                contents: vec![status::status_contents(&status)],
+            });
+        }
+        if request.uri == capabilities::CAPABILITIES_RESOURCE_URI {
+            let capabilities = capabilities::collect_capabilities(&self.config().visionos).await;
+            return Ok(ReadResourceResult {
+                contents: vec![capabilities::capabilities_contents(&capabilities)],
+            });
+        }
+        // Artifact and job resources hand out what `fetch_build_output` does, under its scope.
+        self.authorize_call("fetch_build_output", None)?;
+        if let Some(contents) =
+            federation::artifact_contents(&self.artifact_store, &request.uri).await
+        {
+            return Ok(ReadResourceResult {
+                contents: vec![contents?],
+            });
+        }
+        match self.resource_store.read(&request.uri).await {
+            Some(contents) => Ok(ReadResourceResult {
+                contents: vec![contents],
+            }),
+            None => Err(ErrorData::resource_not_found(
+                format!("resource `{}` was not found", request.uri),
+                None,
+            )),
+        }
+    }
+}
+
+/// Slot and helper tasks of a running job. [`Self::finish`] releases them when the job ends;
+/// dropping the guard instead (the call future was dropped, e.g. by a tool timeout) cancels the
+/// job, releases the slot in the background, and records the job as cancelled while running.
+struct RunningJobGuard {
+    queue: VisionOsJobQueue,
+    ticket: JobTicket,
+    tasks: Vec<tokio::task::JoinHandle<()>>,
+    abandoned_record: Option<(VisionOsArtifactStore, ArtifactKind, String)>,
+    finished: bool,
+}
+
+impl RunningJobGuard {
+    async fn finish(mut self) {
+        self.abort_tasks();
+        self.queue.finish_job(self.ticket.job_id).await;
+        self.finished = true;
+    }
+
+    fn abort_tasks(&self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for RunningJobGuard {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.abort_tasks();
+        self.ticket.cancellation.cancel();
+        let queue = self.queue.clone();
+        let job_id = self.ticket.job_id;
+        let record = self.abandoned_record.take();
+        tokio::spawn(async move {
+            queue.finish_job(job_id).await;
+            if let Some((store, kind, scheme)) = record {
+                if let Err(store_err) = store
+                    .record_cancellation(
+                        job_id,
+                        kind,
+                        scheme,
+                        CancelOutcome::CancelledRunning,
+                        Utc::now(),
+                    )
+                    .await
+                {
+                    tracing::warn!(
+                        target: "rmcp_sample::visionos",
+                        job_id = %job_id,
+                        error = %store_err,
+                        "Failed to record an abandoned job"
+                    );
+                }
+            }
+        });
+    }
+}
+
+/// Send a heartbeat every `interval` until aborted. Clients that passed a progress token get
+/// `notifications/progress` (which also resets their request timeout); others get a
+/// `debug`-level `notifications/message` with `data.event: "heartbeat"`.
+async fn forward_heartbeats(
+    peer: Peer<RoleServer>,
+    progress_token: Option<ProgressToken>,
+    monitor: JobMonitor,
+    interval: Duration,
+) {
+    let started = tokio::time::Instant::now();
+    let mut ticks = tokio::time::interval_at(started + interval, interval);
+    loop {
+        ticks.tick().await;
+        let heartbeat = monitor.heartbeat(started.elapsed());
+        let sent = match &progress_token {
+            Some(token) => {
+                let message = format!(
+                    "{} ({}s elapsed)",
+                    heartbeat.phase.as_deref().unwrap_or("running"),
+                    heartbeat.elapsed_secs
+                );
+                peer.notify_progress(ProgressNotificationParam {
+                    progress_token: token.clone(),
+                    progress: heartbeat.elapsed_secs as f64,
+                    total: None,
+                    message: Some(message),
+                })
+                .await
+            }
+            None => {
+                let mut data = json!(heartbeat);
+                data["event"] = json!("heartbeat");
+                peer.notify_logging_message(LoggingMessageNotificationParam {
+                    level: LoggingLevel::Debug,
+                    logger: Some("seiro_mcp::visionos".into()),
+                    data,
+                })
+                .await
+            }
+        };
+        if let Err(err) = sent {
+            tracing::debug!(
+                target: "rmcp_sample::visionos",
+                job_id = %heartbeat.job_id,
+                error = %err,
+                "Failed to send heartbeat"
+            );
+            return;
+        }
+    }
+}
+
+/// Relay the job's timeout warning to the client as a `notifications/message` event.
+async fn forward_timeout_warning(peer: Peer<RoleServer>, monitor: JobMonitor) {
+    let mut warnings = monitor.subscribe();
+    if warnings.wait_for(Option::is_some).await.is_err() {
+        return;
+    }
+    let Some(warning) = warnings.borrow().clone() else {
+        return;
+    };
+    let mut data = json!(warning);
+    data["event"] = json!("timeout_warning");
+    let notification = LoggingMessageNotificationParam {
+        level: LoggingLevel::Warning,
+        logger: Some("seiro_mcp::visionos".into()),
+        data,
+    };
+    if let Err(err) = peer.notify_logging_message(notification).await {
+        tracing::warn!(
+            target: "rmcp_sample::visionos",
+            job_id = %warning.job_id,
+            error = %err,
+            "Failed to send timeout warning"
+        );
+    }
+}
+
+/// Identity of a build for coalescing in-flight duplicates: every request field except the
+/// ones that only affect scheduling, plus the Xcode the build would run with.
+fn request_fingerprint(
+    request: &VisionOsBuildRequest,
+    kind: ArtifactKind,
+    config: &VisionOsConfig,
+) -> String {
+    let mut parameters = serde_json::to_value(request).unwrap_or_default();
+    if let Some(fields) = parameters.as_object_mut() {
+        for field in ["job_id", "priority", "timeout_minutes"] {
+            fields.remove(field);
+        }
+    }
+    let identity = json!({
+        "kind": kind.as_str(),
+        "request": parameters,
+        "xcode_path": config.xcode_path,
+        "xcodebuild_path": config.xcodebuild_path,
+    });
+    format!("{:x}", Sha256::digest(identity.to_string()))
 }
 
 #[cfg(test)]
@@ -231,9 +1662,12 @@ mod tests {
     use crate::{
         lib::errors::VisionOsBuildError,
         server::config::{
-            ServerConfig, ServerSection, VisionOsConfig, DEFAULT_VISIONOS_DESTINATION,
+            ArtifactsConfig, Platform, ServerConfig, ServerSection, SymlinkPolicy, VisionOsConfig,
+            ZipCompression, DEFAULT_VISIONOS_DESTINATION,
+        },
+        tools::visionos::{
+            build::BuildConfiguration, ArtifactKind, JobPriority, VisionOsBuildRequest,
         },
-        tools::visionos::{build::BuildConfiguration, VisionOsBuildRequest},
     };
 
     use super::VisionOsServer;
@@ -244,20 +1678,66 @@ mod tests {
                 server: ServerSection {
                     host: "127.0.0.1".into(),
                     port: 8787,
+                    shutdown_grace_secs: 30,
+                    rate_limit_per_minute: 0,
+                    max_concurrent_calls: 0,
+                    allow_external_bind: false,
+                    max_connections: 16,
+                    idle_timeout_secs: 0,
+                    auth_failure_limit: 0,
+                    auth_lockout_secs: 60,
                 },
                 visionos: VisionOsConfig {
                     allowed_paths: vec![],
+                    allowed_roots: Vec::new(),
                     allowed_schemes: vec![],
                     default_project_path: None,
                     default_destination: DEFAULT_VISIONOS_DESTINATION.into(),
                     required_sdks: vec!["visionOS".into(), "visionOS Simulator".into()],
                     xcode_path: PathBuf::from("/Applications/Xcode.app/Contents/Developer"),
                     xcodebuild_path: PathBuf::from("/usr/bin/xcodebuild"),
+                    xcrun_path: PathBuf::from("/usr/bin/xcrun"),
                     max_build_minutes: 20,
+                    max_timeout_minutes: 20,
+                    timeout_warning_percent: 80,
+                    heartbeat_interval_secs: 15,
+                    max_extended_build_minutes: 0,
+                    max_concurrent_builds: 1,
                     artifact_ttl_secs: 600,
                     cleanup_schedule_secs: 60,
+                    artifact_max_jobs: 0,
+                    artifact_max_total_bytes: 0,
+                    min_free_disk_bytes: 0,
+                    sandbox_probe_ttl_secs: 0,
+                    allow_remediation: false,
+                    package_hosts: Vec::new(),
+                    policy: Default::default(),
+                    export_paths: vec![],
+                    size_budgets: BTreeMap::new(),
+                    artifact_compression: ZipCompression::default(),
+                    artifact_symlinks: SymlinkPolicy::default(),
+                    deterministic_artifacts: false,
+                    derived_data_root: None,
+                    build_cache: false,
+                    project_config: false,
+                    compilation_cache: None,
+                    platforms: BTreeMap::new(),
+                    locale: "en_US.UTF-8".into(),
+                    signing: Default::default(),
+                    allowed_device_udids: Vec::new(),
+                    artifact_persistence: Default::default(),
+                    presets: Default::default(),
+                    xcodes: Default::default(),
+                    retry: Default::default(),
                 },
+                artifacts: ArtifactsConfig::default(),
+                redaction: Default::default(),
+                auth: Default::default(),
+                federation: Default::default(),
+                notifications: Default::default(),
+                tools: Default::default(),
                 source_path: PathBuf::from("test-config.toml"),
+                profile: None,
             },
             "test instructions".into(),
         )
@@ -268,7 +1748,10 @@ mod tests {
             project_path: PathBuf::from("/tmp/project"),
             workspace: None,
             scheme: "VisionApp".into(),
-            configuration: BuildConfiguration::Debug,
+            configuration: Some(BuildConfiguration::Debug),
+            preset: None,
+            xcode: None,
+            platform: Platform::VisionOs,
             destination: DEFAULT_VISIONOS_DESTINATION.into(),
             clean: false,
             extra_args: Vec::new(),
@@ -276,6 +1759,21 @@ mod tests {
                 "DEVELOPER_DIR".to_string(),
                 "/tmp/untrusted/Contents/Developer".to_string(),
             )]),
+            job_id: None,
+            timeout_minutes: None,
+            priority: JobPriority::Interactive,
+            strip_symbols: false,
+            collect_dsyms: false,
+            architectures: Vec::new(),
+            use_cached_derived_data: false,
+            force_rebuild: false,
+            code_sign_identity: None,
+            development_team: None,
+            provisioning_profile: None,
+            artifact_include: Vec::new(),
+            artifact_exclude: Vec::new(),
+            dry_run: false,
+            log_format: Default::default(),
         }
     }
 
@@ -286,10 +1784,12 @@ mod tests {
         let error = VisionOsBuildError::CommandFailed {
             exit_code: Some(1),
             message: "xcodebuild failed".into(),
+            diagnostics: Vec::new(),
+            log_errors: Vec::new(),
         };
 
         server
-            .record_build_failure(job_id, &error, &sample_request())
+            .record_build_failure(job_id, ArtifactKind::Build, &error, &sample_request())
             .await;
 
         let record = server
@@ -306,4 +1806,30 @@ mod tests {
             PathBuf::from("/Applications/Xcode.app/Contents/Developer")
         );
     }
+
+    #[test]
+    fn every_tool_has_a_token_scope() {
+        let mut config = sample_server().config().as_ref().clone();
+        config.tools.admin = true;
+        let server = VisionOsServer::new(config, "test instructions".into());
+
+        let unscoped: Vec<String> = server
+            .tool_names()
+            .into_iter()
+            .filter(|tool| !crate::server::runtime::client_auth::has_scope_entry(tool))
+            .collect();
+        assert!(unscoped.is_empty(), "tools without a scope: {unscoped:?}");
+
+        let unscoped_prompts: Vec<String> = server
+            .prompt_router
+            .list_all()
+            .into_iter()
+            .map(|prompt| prompt.name)
+            .filter(|prompt| !crate::server::runtime::client_auth::has_prompt_scope_entry(prompt))
+            .collect();
+        assert!(
+            unscoped_prompts.is_empty(),
+            "prompts without a scope: {unscoped_prompts:?}"
+        );
+    }
 }
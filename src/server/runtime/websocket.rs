@@ -0,0 +1,411 @@
+//! WebSocket transport: every connection that upgrades with `Authorization: Bearer <token>`,
+//! where the token is `auth.token` or one of `[[auth.tokens]]`, becomes one MCP session, with
+//! each JSON-RPC message sent as a text frame.
+use std::{
+    collections::HashMap,
+    io,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use futures::{future, Sink, SinkExt, Stream, StreamExt};
+use rmcp::{
+    service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage},
+    RoleServer, ServiceExt,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+};
+use tokio_tungstenite::{
+    accept_hdr_async,
+    tungstenite::{
+        handshake::server::{ErrorResponse, Request, Response},
+        http::{
+            header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER},
+            HeaderValue, StatusCode,
+        },
+        Error as WsError, Message,
+    },
+    WebSocketStream,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use super::{
+    client_auth::{TokenRejection, TOKEN_EXPIRED_ERROR},
+    ClientAuthContext, Session, VisionOsServer,
+};
+use crate::server::config::{AuthSection, ServerSection};
+
+/// Time a connection gets to complete the upgrade before it is dropped.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often an idle session is checked against `server.idle_timeout_secs`.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Resolve `[server] host:port`, refusing addresses off loopback unless
+/// `server.allow_external_bind` opts in.
+pub async fn bind_addresses(server: &ServerSection) -> io::Result<Vec<SocketAddr>> {
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((server.host.as_str(), server.port))
+        .await?
+        .collect();
+    if !server.allow_external_bind && addresses.iter().any(|address| !address.ip().is_loopback()) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "server.host `{}` is not a loopback address; set server.allow_external_bind = true to listen on it",
+                server.host
+            ),
+        ));
+    }
+    Ok(addresses)
+}
+
+/// Failed upgrades per client address, so the token cannot be guessed at connection speed.
+/// After `server.auth_failure_limit` failures in a row the address gets `429` for
+/// `server.auth_lockout_secs`, whatever token it sends.
+#[derive(Debug, Clone, Default)]
+pub struct AuthFailures(Arc<Mutex<HashMap<IpAddr, FailureStreak>>>);
+
+#[derive(Debug)]
+struct FailureStreak {
+    count: u16,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl AuthFailures {
+    /// Time `ip` still has to wait, while it is locked out.
+    fn locked_out(&self, ip: IpAddr) -> Option<Duration> {
+        let locked_until = self.streaks().get(&ip)?.locked_until?;
+        Some(locked_until.saturating_duration_since(Instant::now())).filter(|left| !left.is_zero())
+    }
+
+    fn succeeded(&self, ip: IpAddr) {
+        self.streaks().remove(&ip);
+    }
+
+    /// Count a failure from `ip`; returns `true` when it locks the address out. A streak is
+    /// forgotten once `lockout` passes without another failure.
+    fn failed(&self, ip: IpAddr, limit: u16, lockout: Duration) -> bool {
+        let now = Instant::now();
+        let mut streaks = self.streaks();
+        streaks.retain(|_, streak| {
+            streak.locked_until.is_some_and(|until| until > now)
+                || now.duration_since(streak.last_failure) < lockout
+        });
+        if limit == 0 {
+            return false;
+        }
+        let streak = streaks.entry(ip).or_insert(FailureStreak {
+            count: 0,
+            last_failure: now,
+            locked_until: None,
+        });
+        streak.count = streak.count.saturating_add(1);
+        streak.last_failure = now;
+        if streak.count < limit {
+            return false;
+        }
+        streak.count = 0;
+        streak.locked_until = Some(now + lockout);
+        true
+    }
+
+    fn streaks(&self) -> std::sync::MutexGuard<'_, HashMap<IpAddr, FailureStreak>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Upgrade `stream` and serve one MCP session on it until the client leaves or `shutdown` fires.
+/// Authenticated clients get `503` while `server.max_connections` sessions are open. Tokens are
+/// read from the current config, so a reload applies to the next upgrade.
+pub async fn serve_connection(
+    server: VisionOsServer,
+    stream: TcpStream,
+    peer: SocketAddr,
+    failures: AuthFailures,
+    shutdown: CancellationToken,
+) {
+    let mut session = None;
+    let mut client_auth = None;
+    let handshake = accept_hdr_async(stream, |request: &Request, response: Response| {
+        let config = server.config();
+        let settings = &config.server;
+        if let Some(left) = failures.locked_out(peer.ip()) {
+            return Err(locked_out_response(left));
+        }
+        match authorize(request, &config.auth) {
+            Ok(context) => {
+                failures.succeeded(peer.ip());
+                client_auth = Some(context);
+            }
+            Err(rejection) => {
+                let lockout = Duration::from_secs(settings.auth_lockout_secs.into());
+                if failures.failed(peer.ip(), settings.auth_failure_limit, lockout) {
+                    warn!(
+                        target: "rmcp_sample::runtime",
+                        peer = %peer,
+                        failures = settings.auth_failure_limit,
+                        lockout_secs = settings.auth_lockout_secs,
+                        "Locking out WebSocket client after repeated authentication failures"
+                    );
+                }
+                return Err(rejection);
+            }
+        };
+        let max_connections = settings.max_connections;
+        session = server
+            .sessions()
+            .open("websocket", Some(peer), max_connections);
+        match session {
+            Some(_) => Ok(response),
+            None => Err(error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Too many open MCP sessions; retry later or raise server.max_connections",
+            )),
+        }
+    });
+    let websocket = match tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await {
+        Ok(Ok(websocket)) => websocket,
+        Ok(Err(error)) => {
+            warn!(
+                target: "rmcp_sample::runtime",
+                peer = %peer,
+                error = %error,
+                "Rejected WebSocket connection"
+            );
+            return;
+        }
+        Err(_) => {
+            warn!(
+                target: "rmcp_sample::runtime",
+                peer = %peer,
+                "WebSocket handshake timed out"
+            );
+            return;
+        }
+    };
+    let (Some(session), Some(client_auth)) = (session, client_auth) else {
+        return;
+    };
+    info!(
+        target: "rmcp_sample::runtime",
+        peer = %peer,
+        session_id = session.id(),
+        auth_entry = client_auth.token_name.as_deref().unwrap_or("auth.token"),
+        "WebSocket MCP session started"
+    );
+    let (sink, stream) = transport::<RoleServer, _>(websocket);
+    let activity = session.clone();
+    let stream = stream.inspect(move |_| activity.touch());
+    let running = match server
+        .for_session(session.clone())
+        .with_client_auth(client_auth)
+        .serve_with_ct((sink, stream), shutdown.clone())
+        .await
+    {
+        Ok(running) => running,
+        Err(error) => {
+            warn!(
+                target: "rmcp_sample::runtime",
+                peer = %peer,
+                error = %error,
+                "WebSocket MCP initialization failed"
+            );
+            return;
+        }
+    };
+    let service = running.cancellation_token();
+    let waiting = running.waiting();
+    tokio::pin!(waiting);
+    let reason = tokio::select! {
+        _ = &mut waiting => if shutdown.is_cancelled() { "shutdown" } else { "client_closed" },
+        idle = idle_timeout(&server, &session) => {
+            warn!(
+                target: "rmcp_sample::runtime",
+                peer = %peer,
+                idle_secs = idle.as_secs(),
+                "Closing idle WebSocket MCP session"
+            );
+            service.cancel();
+            let _ = waiting.await;
+            "idle_timeout"
+        }
+    };
+    info!(
+        target: "rmcp_sample::runtime",
+        peer = %peer,
+        session_id = session.id(),
+        reason,
+        "WebSocket MCP session ended"
+    );
+}
+
+/// Resolve once `session` has been idle for `server.idle_timeout_secs`, read again on every
+/// check so reloads apply; never while the timeout is 0.
+async fn idle_timeout(server: &VisionOsServer, session: &Session) -> Duration {
+    loop {
+        let timeout = Duration::from_secs(server.config().server.idle_timeout_secs.into());
+        if timeout.is_zero() {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+            continue;
+        }
+        let idle = session.idle_for();
+        if idle >= timeout {
+            return idle;
+        }
+        tokio::time::sleep((timeout - idle).min(IDLE_CHECK_INTERVAL)).await;
+    }
+}
+
+/// MCP transport over an established WebSocket, for either end of the session. Frames other
+/// than text are ignored, and so is text that is not a JSON-RPC message.
+pub fn transport<R, S>(
+    websocket: WebSocketStream<S>,
+) -> (
+    impl Sink<TxJsonRpcMessage<R>, Error = WsError> + Send + Unpin + 'static,
+    impl Stream<Item = RxJsonRpcMessage<R>> + Send + Unpin + 'static,
+)
+where
+    R: ServiceRole,
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let (sink, stream) = websocket.split();
+    let sink = sink.with(|message: TxJsonRpcMessage<R>| {
+        future::ready(
+            serde_json::to_string(&message)
+                .map(Message::text)
+                .map_err(|error| WsError::Io(io::Error::other(error))),
+        )
+    });
+    let stream = stream
+        .take_while(|frame| future::ready(frame.is_ok()))
+        .filter_map(|frame| {
+            future::ready(match frame {
+                Ok(Message::Text(text)) => serde_json::from_str(text.as_str()).ok(),
+                _ => None,
+            })
+        });
+    (sink, stream)
+}
+
+/// Resolve the bearer token of `request` against `auth`. An expired `auth.previous_token` gets
+/// a JSON body with code `token_expired`, so clients can tell it from a wrong token.
+// The error is returned from tungstenite's handshake callback as is.
+#[allow(clippy::result_large_err)]
+fn authorize(request: &Request, auth: &AuthSection) -> Result<ClientAuthContext, ErrorResponse> {
+    let presented = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(TokenRejection::Unknown);
+    match presented.and_then(|presented| ClientAuthContext::resolve(auth, presented.trim(), Utc::now())) {
+        Ok(context) => Ok(context),
+        Err(TokenRejection::Unknown) => Err(error_response(
+            StatusCode::UNAUTHORIZED,
+            "Send `Authorization: Bearer <token>` with auth.token or an [[auth.tokens]] token to open an MCP session",
+        )),
+        Err(TokenRejection::Expired { valid_until }) => {
+            let body = serde_json::json!({
+                "code": TOKEN_EXPIRED_ERROR.code,
+                "message": TOKEN_EXPIRED_ERROR.message,
+                "remediation": TOKEN_EXPIRED_ERROR.remediation,
+                "valid_until": valid_until,
+            });
+            let mut response = ErrorResponse::new(Some(body.to_string()));
+            *response.status_mut() = StatusCode::UNAUTHORIZED;
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            Err(response)
+        }
+    }
+}
+
+fn locked_out_response(left: Duration) -> ErrorResponse {
+    let mut response = error_response(
+        StatusCode::TOO_MANY_REQUESTS,
+        "Too many failed authentication attempts from this address; retry after the lockout",
+    );
+    let retry_after_secs = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    response
+}
+
+fn error_response(status: StatusCode, message: &str) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(message.into()));
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(authorization: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri("ws://127.0.0.1:8787/");
+        if let Some(value) = authorization {
+            builder = builder.header(AUTHORIZATION, value);
+        }
+        builder.body(()).expect("request")
+    }
+
+    #[test]
+    fn upgrade_requires_the_bearer_token() {
+        let token = "websocket-token-0001";
+        let auth = AuthSection {
+            token: Some(token.into()),
+            previous_token: Some("retired-token-00001".into()),
+            previous_token_valid_until: Some(Utc::now() - chrono::Duration::seconds(1)),
+            tokens: Vec::new(),
+        };
+
+        let context = authorize(&request(Some("Bearer websocket-token-0001")), &auth)
+            .expect("the configured token is accepted");
+        assert_eq!(context.token_name, None);
+        for authorization in [None, Some("Bearer websocket-token-0002"), Some(token)] {
+            let rejection = authorize(&request(authorization), &auth)
+                .expect_err("upgrade without the token is rejected");
+            assert_eq!(rejection.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let expired = authorize(&request(Some("Bearer retired-token-00001")), &auth)
+            .expect_err("an expired previous token is rejected");
+        assert_eq!(expired.status(), StatusCode::UNAUTHORIZED);
+        let body: serde_json::Value =
+            serde_json::from_str(expired.body().as_deref().expect("body")).expect("JSON body");
+        assert_eq!(body["code"], "token_expired");
+    }
+
+    #[test]
+    fn repeated_failures_lock_the_address_out_until_the_lockout_passes() {
+        let failures = AuthFailures::default();
+        let attacker: IpAddr = "192.0.2.7".parse().expect("ip");
+        let neighbour: IpAddr = "192.0.2.8".parse().expect("ip");
+        let lockout = Duration::from_millis(200);
+
+        assert!(!failures.failed(attacker, 3, lockout));
+        assert!(!failures.failed(attacker, 3, lockout));
+        assert!(failures.failed(attacker, 3, lockout));
+        assert!(failures.locked_out(attacker).is_some());
+        assert!(failures.locked_out(neighbour).is_none());
+
+        std::thread::sleep(lockout);
+        assert!(failures.locked_out(attacker).is_none());
+        assert!(!failures.failed(attacker, 3, lockout));
+        failures.succeeded(attacker);
+        assert!(!failures.failed(attacker, 3, lockout));
+        assert!(!failures.failed(attacker, 3, lockout));
+
+        assert!(!failures.failed(neighbour, 0, lockout));
+        assert!(failures.locked_out(neighbour).is_none());
+    }
+}
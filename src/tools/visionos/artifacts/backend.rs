@@ -0,0 +1,275 @@
+//! Storage backends behind `VisionOsArtifactStore`.
+use std::{
+    fmt,
+    fs::{self, DirBuilder},
+    os::unix::fs::DirBuilderExt,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::lib::{errors::ArtifactError, fs as artifact_fs};
+
+use super::store::BuildJobRecord;
+
+/// Per-job record persisted next to the artifacts so job IDs survive restarts.
+pub(crate) const JOB_RECORD_FILE: &str = "job.json";
+
+/// Backend identity reported by status output.
+#[derive(Debug, Clone, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct ArtifactBackendInfo {
+    /// Backend identifier (`local`, `s3`, or `gcs`).
+    pub kind: &'static str,
+    /// Where artifacts and job records are kept.
+    pub location: String,
+}
+
+/// Persistence for job records and artifact directories.
+///
+/// Builds always stage into [`ArtifactBackend::root_dir`] on the local disk; backends decide
+/// where records live and how expired jobs are purged.
+pub trait ArtifactBackend: Send + Sync + fmt::Debug {
+    /// Identity shown in tool responses.
+    fn info(&self) -> ArtifactBackendInfo;
+
+    /// Local directory under which `<job_id>/` working directories are created.
+    fn root_dir(&self) -> &Path;
+
+    /// Durably store `record`, replacing any previous record for the job.
+    fn save_record(&self, record: &BuildJobRecord) -> Result<(), ArtifactError>;
+
+    /// Load every readable record; callers apply TTL filtering.
+    fn load_records(&self) -> Vec<BuildJobRecord>;
+
+    /// Delete job data older than `ttl`, returning the removed locations.
+    fn remove_expired(
+        &self,
+        ttl: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PathBuf>, ArtifactError>;
+
+    /// Copy a finished artifact to remote storage, returning its URI when one was created.
+    fn publish_artifact(
+        &self,
+        _job_id: &Uuid,
+        _artifact: &Path,
+    ) -> Result<Option<String>, ArtifactError> {
+        Ok(None)
+    }
+
+    /// Delete everything the backend keeps on disk; called at shutdown.
+    fn discard(&self) {}
+
+    /// Time-limited download URL for a published artifact.
+    fn download_url(
+        &self,
+        _artifact_uri: &str,
+        _expires_in_secs: u32,
+    ) -> Result<Option<String>, ArtifactError> {
+        Ok(None)
+    }
+}
+
+/// Keeps artifacts and `job.json` records in `<root>/<job_id>/` on the local filesystem.
+#[derive(Debug, Clone)]
+pub struct LocalDirBackend {
+    root: PathBuf,
+}
+
+impl LocalDirBackend {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+}
+
+impl ArtifactBackend for LocalDirBackend {
+    fn info(&self) -> ArtifactBackendInfo {
+        ArtifactBackendInfo {
+            kind: "local",
+            location: self.root.to_string_lossy().to_string(),
+        }
+    }
+
+    fn root_dir(&self) -> &Path {
+        &self.root
+    }
+
+    fn save_record(&self, record: &BuildJobRecord) -> Result<(), ArtifactError> {
+        let job_dir = artifact_fs::ensure_job_dir(&self.root, &record.job_id)?;
+        let path = job_dir.join(JOB_RECORD_FILE);
+        let encoded = serde_json::to_vec_pretty(record).map_err(|err| ArtifactError::Io {
+            path: path.clone(),
+            source: std::io::Error::other(err),
+        })?;
+        let staged = path.with_extension("json.tmp");
+        fs::write(&staged, encoded)
+            .and_then(|_| fs::rename(&staged, &path))
+            .map_err(|source| ArtifactError::Io { path, source })
+    }
+
+    fn load_records(&self) -> Vec<BuildJobRecord> {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return Vec::new();
+        };
+        let mut records = Vec::new();
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path().join(JOB_RECORD_FILE);
+            let Ok(raw) = fs::read(&path) else {
+                continue;
+            };
+            match serde_json::from_slice::<BuildJobRecord>(&raw) {
+                Ok(record) => records.push(record),
+                Err(err) => warn!(
+                    target: "rmcp_sample::visionos",
+                    path = %path.display(),
+                    error = %err,
+                    "Ignoring unreadable job record"
+                ),
+            }
+        }
+        records
+    }
+
+    fn remove_expired(
+        &self,
+        ttl: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PathBuf>, ArtifactError> {
+        artifact_fs::cleanup_expired_entries(&self.root, ttl, now)
+    }
+}
+
+/// Keeps job records in memory only and artifacts in a private temporary directory that is
+/// removed at shutdown, so nothing outlives the server process.
+#[derive(Debug)]
+pub struct MemoryBackend {
+    root: PathBuf,
+}
+
+impl MemoryBackend {
+    /// Create an owner-only directory under the system temp dir.
+    pub fn new() -> Self {
+        let root = std::env::temp_dir().join(format!("seiro-mcp-ephemeral-{}", Uuid::new_v4()));
+        if let Err(err) = DirBuilder::new().mode(0o700).create(&root) {
+            warn!(
+                target: "rmcp_sample::visionos",
+                root = %root.display(),
+                error = %err,
+                "Failed to create ephemeral artifact directory"
+            );
+        }
+        Self { root }
+    }
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for MemoryBackend {
+    fn drop(&mut self) {
+        self.discard();
+    }
+}
+
+impl ArtifactBackend for MemoryBackend {
+    fn info(&self) -> ArtifactBackendInfo {
+        ArtifactBackendInfo {
+            kind: "memory",
+            location: self.root.to_string_lossy().to_string(),
+        }
+    }
+
+    fn root_dir(&self) -> &Path {
+        &self.root
+    }
+
+    fn save_record(&self, _record: &BuildJobRecord) -> Result<(), ArtifactError> {
+        Ok(())
+    }
+
+    fn load_records(&self) -> Vec<BuildJobRecord> {
+        Vec::new()
+    }
+
+    fn remove_expired(
+        &self,
+        ttl: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PathBuf>, ArtifactError> {
+        artifact_fs::cleanup_expired_entries(&self.root, ttl, now)
+    }
+
+    fn discard(&self) {
+        if let Err(err) = fs::remove_dir_all(&self.root) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                warn!(
+                    target: "rmcp_sample::visionos",
+                    root = %self.root.display(),
+                    error = %err,
+                    "Failed to remove ephemeral artifact directory"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::tools::visionos::artifacts::{ArtifactKind, BuildJobStatus};
+
+    #[test]
+    fn local_backend_round_trips_records_and_skips_corrupt_files() {
+        let temp = tempdir().expect("temporary directory");
+        let backend = LocalDirBackend::new(temp.path().to_path_buf());
+        let record = BuildJobRecord {
+            job_id: Uuid::new_v4(),
+            status: BuildJobStatus::Failed,
+            artifact_kind: ArtifactKind::Build,
+            scheme: None,
+            artifact_zip: None,
+            artifact_uri: None,
+            artifact_sha256: None,
+            log_excerpt: "error: boom".into(),
+            finished_at: Utc::now(),
+            failure_context: None,
+            cache_key: None,
+            ttl_secs: None,
+            log_path: None,
+            attempts: Vec::new(),
+        };
+        backend.save_record(&record).expect("save record");
+        let corrupt = temp.path().join(Uuid::new_v4().to_string());
+        fs::create_dir_all(&corrupt).expect("corrupt job dir");
+        fs::write(corrupt.join(JOB_RECORD_FILE), b"{not json").expect("write corrupt");
+
+        let records = backend.load_records();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].job_id, record.job_id);
+        assert_eq!(records[0].log_excerpt, "error: boom");
+        assert_eq!(backend.info().kind, "local");
+    }
+
+    #[test]
+    fn memory_backend_keeps_no_records_and_removes_its_directory() {
+        let backend = MemoryBackend::new();
+        let root = backend.root_dir().to_path_buf();
+        let job_dir = artifact_fs::ensure_job_dir(&root, &Uuid::new_v4()).expect("job dir");
+        fs::write(job_dir.join("artifact.zip"), b"zip").expect("write artifact");
+
+        assert!(backend.load_records().is_empty());
+        assert_eq!(backend.info().kind, "memory");
+        drop(backend);
+        assert!(!root.exists());
+    }
+}
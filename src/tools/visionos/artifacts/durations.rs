@@ -0,0 +1,100 @@
+//! Durations of recent successful jobs per artifact kind and scheme, kept in
+//! `durations.json` under the artifact root so estimates survive restarts and TTL cleanup.
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::Path,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::lib::fs::DURATION_HISTORY_FILE;
+
+use super::ArtifactKind;
+
+/// Durations kept per kind and scheme; the estimate is their mean.
+const DURATION_SAMPLES: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DurationHistory {
+    /// Milliseconds, oldest first, keyed by `<kind>:<scheme>`.
+    #[serde(default)]
+    samples: BTreeMap<String, VecDeque<u64>>,
+}
+
+impl DurationHistory {
+    /// Read the history under `root`; a missing or unreadable file starts an empty one.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(DURATION_HISTORY_FILE);
+        let Ok(raw) = fs::read(&path) else {
+            return Self::default();
+        };
+        serde_json::from_slice(&raw).unwrap_or_else(|err| {
+            warn!(
+                target: "rmcp_sample::visionos",
+                path = %path.display(),
+                error = %err,
+                "Ignoring unreadable build duration history"
+            );
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, root: &Path) -> std::io::Result<()> {
+        fs::create_dir_all(root)?;
+        let path = root.join(DURATION_HISTORY_FILE);
+        let staged = path.with_extension("json.tmp");
+        fs::write(&staged, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&staged, &path)
+    }
+
+    pub fn record(&mut self, kind: ArtifactKind, scheme: &str, duration: Duration) {
+        let samples = self.samples.entry(key(kind, scheme)).or_default();
+        if samples.len() == DURATION_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX));
+    }
+
+    /// Mean of the recorded durations; `None` before the first successful job.
+    pub fn estimate(&self, kind: ArtifactKind, scheme: &str) -> Option<Duration> {
+        let samples = self.samples.get(&key(kind, scheme))?;
+        let total: u64 = samples.iter().sum();
+        (!samples.is_empty()).then(|| Duration::from_millis(total / samples.len() as u64))
+    }
+}
+
+fn key(kind: ArtifactKind, scheme: &str) -> String {
+    format!("{}:{scheme}", kind.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_are_rolling_means_per_kind_and_scheme() {
+        let root = tempfile::tempdir().expect("tempdir");
+        let mut history = DurationHistory::default();
+        for secs in 1..=DURATION_SAMPLES as u64 + 2 {
+            history.record(ArtifactKind::Build, "VisionApp", Duration::from_secs(secs));
+        }
+        history.record(ArtifactKind::Archive, "VisionApp", Duration::from_secs(300));
+        history.save(root.path()).expect("save history");
+
+        let loaded = DurationHistory::load(root.path());
+        assert_eq!(loaded, history);
+        // Samples 1 and 2 fell out of the window, leaving 3..=12.
+        assert_eq!(
+            loaded.estimate(ArtifactKind::Build, "VisionApp"),
+            Some(Duration::from_millis(7_500))
+        );
+        assert_eq!(
+            loaded.estimate(ArtifactKind::Archive, "VisionApp"),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(loaded.estimate(ArtifactKind::Build, "Other"), None);
+    }
+}
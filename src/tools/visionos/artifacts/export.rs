@@ -0,0 +1,364 @@
+//! Copy a job's artifacts into a config-allowlisted export directory.
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    lib::{
+        errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+        fs as artifact_fs, postprocess,
+    },
+    server::config::VisionOsConfig,
+};
+
+use super::{
+    fetch_error_to_error_data, BuildJobStatus, FetchBuildOutputError, VisionOsArtifactStore,
+    BUILD_LOG_FILE,
+};
+
+const EXPORT_DISABLED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "export_disabled",
+    "Artifact export is disabled",
+    "Add destination roots to visionos.export_paths in seiro-mcp.toml; the running server reloads it on save.",
+);
+const EXPORT_PATH_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "export_path_not_allowed",
+    "destination_dir is outside visionos.export_paths",
+    "Use an absolute destination_dir under one of the configured visionos.export_paths.",
+);
+const EXPORT_CONFLICT_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "export_conflict",
+    "An exported file already exists at the destination",
+    "Retry with overwrite=true or choose another destination_dir.",
+);
+const EXPORT_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "export_failed",
+    "Failed to copy artifacts to the destination",
+    "Check destination permissions and free space, then retry.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&EXPORT_DISABLED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&EXPORT_PATH_NOT_ALLOWED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&EXPORT_CONFLICT_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&EXPORT_FAILED_ERROR, true, SandboxState::NoViolation),
+];
+
+/// Input for `export_artifact`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportArtifactRequest {
+    /// Job UUID, or `"latest"` for the most recent job.
+    pub job_id: String,
+    /// Restrict `"latest"` to jobs built from this scheme.
+    #[serde(default)]
+    pub scheme: Option<String>,
+    pub destination_dir: PathBuf,
+    #[serde(default)]
+    pub include_logs: bool,
+    #[serde(default)]
+    pub include_dsyms: bool,
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// File or directory written by `export_artifact`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ExportedFile {
+    /// `artifact`, `log`, or `dsym`.
+    pub kind: String,
+    pub path: String,
+}
+
+/// Response from `export_artifact`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportArtifactResponse {
+    pub job_id: String,
+    pub status: &'static str,
+    pub destination_dir: String,
+    pub exported: Vec<ExportedFile>,
+    pub notes: Vec<String>,
+}
+
+/// Copy the artifact zip (and optionally the full build log and dSYMs) of a succeeded job.
+pub async fn export_artifact(
+    store: &VisionOsArtifactStore,
+    config: &VisionOsConfig,
+    request: ExportArtifactRequest,
+) -> Result<ExportArtifactResponse, ErrorData> {
+    let job_id = store
+        .resolve_job_id(&request.job_id, request.scheme.as_deref())
+        .await
+        .map_err(fetch_error_to_error_data)?;
+
+    if config.export_paths.is_empty() {
+        return Err(build_error_data(
+            &EXPORT_DISABLED_ERROR,
+            json!({}),
+            SandboxState::Blocked,
+            false,
+        ));
+    }
+    let destination_dir = resolve_destination(&request.destination_dir, &config.export_paths)?;
+
+    let record = store
+        .fetch_record(&job_id)
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    let artifact_zip = match (record.status, record.artifact_zip.as_ref()) {
+        (BuildJobStatus::Succeeded, Some(path)) => path.clone(),
+        (status @ (BuildJobStatus::CancelledQueued | BuildJobStatus::CancelledRunning), _) => {
+            return Err(fetch_error_to_error_data(
+                FetchBuildOutputError::JobCancelled { job_id, status },
+            ))
+        }
+        _ => {
+            return Err(fetch_error_to_error_data(
+                FetchBuildOutputError::BuildFailedNoArtifact { job_id },
+            ))
+        }
+    };
+
+    let file_name = artifact_zip
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "artifact.zip".into());
+    let mut plan = vec![(
+        "artifact",
+        destination_dir.join(format!("{job_id}-{file_name}")),
+    )];
+    if request.include_logs {
+        plan.push(("log", destination_dir.join(format!("{job_id}-build.log"))));
+    }
+    let dsyms = if request.include_dsyms {
+        artifact_zip
+            .parent()
+            .map(|job_dir| {
+                let mut dsyms = postprocess::find_dsyms(&job_dir.join("staging"));
+                dsyms.extend(postprocess::find_dsyms(
+                    &job_dir.join(postprocess::DSYM_DIR_NAME),
+                ));
+                dsyms
+            })
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let dsym_dir = destination_dir.join(format!("{job_id}-dSYMs"));
+    if !dsyms.is_empty() {
+        plan.push(("dsym", dsym_dir.clone()));
+    }
+
+    // Writing through a symlink would put the file wherever it points, even outside the export
+    // roots; `exists` follows links, so dangling ones are caught here as well.
+    if let Some((_, linked)) = plan.iter().find(|(_, path)| {
+        fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+    }) {
+        return Err(build_error_data(
+            &EXPORT_PATH_NOT_ALLOWED_ERROR,
+            json!({
+                "destination_dir": destination_dir.to_string_lossy(),
+                "path": linked.to_string_lossy(),
+                "reason": "the destination file is a symlink",
+            }),
+            SandboxState::Blocked,
+            false,
+        ));
+    }
+    if !request.overwrite {
+        if let Some((_, existing)) = plan.iter().find(|(_, path)| path.exists()) {
+            return Err(build_error_data(
+                &EXPORT_CONFLICT_ERROR,
+                json!({ "path": existing.to_string_lossy() }),
+                SandboxState::NoViolation,
+                false,
+            ));
+        }
+    }
+
+    let mut exported = Vec::new();
+    let mut notes = Vec::new();
+    for (kind, target) in &plan {
+        match *kind {
+            "artifact" => fs::copy(&artifact_zip, target)
+                .map(|_| ())
+                .map_err(|err| export_failed(target, err.to_string()))?,
+            "log" => {
+                // Records written before logs were registered still find the log in the job
+                // directory.
+                let full_log = record.log_path.clone().unwrap_or_else(|| {
+                    store
+                        .root_dir()
+                        .join(job_id.to_string())
+                        .join(BUILD_LOG_FILE)
+                });
+                if full_log.is_file() {
+                    fs::copy(&full_log, target).map(|_| ())
+                } else {
+                    notes.push(
+                        "xcodebuild produced no full log for this job; the stored log excerpt was exported instead"
+                            .into(),
+                    );
+                    fs::write(target, &record.log_excerpt)
+                }
+                .map_err(|err| export_failed(target, err.to_string()))?;
+            }
+            _ => {
+                if target.exists() {
+                    fs::remove_dir_all(target)
+                        .map_err(|err| export_failed(target, err.to_string()))?;
+                }
+                for dsym in &dsyms {
+                    let name = dsym.file_name().unwrap_or_default();
+                    artifact_fs::copy_directory(dsym, &target.join(name))
+                        .map_err(|err| export_failed(target, err.to_string()))?;
+                }
+            }
+        }
+        exported.push(ExportedFile {
+            kind: (*kind).to_string(),
+            path: target.to_string_lossy().to_string(),
+        });
+    }
+    if request.include_dsyms && dsyms.is_empty() {
+        notes.push("no dSYM bundles were found for this job".into());
+    }
+
+    Ok(ExportArtifactResponse {
+        job_id: job_id.to_string(),
+        status: "exported",
+        destination_dir: destination_dir.to_string_lossy().to_string(),
+        exported,
+        notes,
+    })
+}
+
+/// Create `destination` and ensure it resolves inside one of the export roots.
+fn resolve_destination(destination: &Path, export_paths: &[PathBuf]) -> Result<PathBuf, ErrorData> {
+    let not_allowed = || {
+        build_error_data(
+            &EXPORT_PATH_NOT_ALLOWED_ERROR,
+            json!({ "destination_dir": destination.to_string_lossy() }),
+            SandboxState::Blocked,
+            false,
+        )
+    };
+    if !destination.is_absolute()
+        || destination
+            .components()
+            .any(|component| matches!(component, Component::ParentDir))
+        || !export_paths
+            .iter()
+            .any(|root| destination.starts_with(root))
+    {
+        return Err(not_allowed());
+    }
+
+    // Check the deepest directory that already exists before creating anything, so a symlink
+    // inside an export root cannot be used to create directories outside it. Ancestors of a
+    // root that does not exist yet are fine: only the root itself gets created below them.
+    let existing = destination
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .unwrap_or(destination);
+    let creates_root = export_paths.iter().any(|root| {
+        destination.starts_with(root) && root.starts_with(existing) && root != existing
+    });
+    if !creates_root && !crate::lib::visionos::is_allowed_path(existing, export_paths) {
+        return Err(not_allowed());
+    }
+    fs::create_dir_all(destination).map_err(|err| export_failed(destination, err.to_string()))?;
+    if !crate::lib::visionos::is_allowed_path(destination, export_paths) {
+        return Err(not_allowed());
+    }
+    fs::canonicalize(destination).map_err(|err| export_failed(destination, err.to_string()))
+}
+
+fn export_failed(path: &Path, details: String) -> ErrorData {
+    build_error_data(
+        &EXPORT_FAILED_ERROR,
+        json!({ "path": path.to_string_lossy(), "details": details }),
+        SandboxState::NoViolation,
+        true,
+    )
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    sandbox_state: SandboxState,
+    retryable: bool,
+) -> ErrorData {
+    desc.builder()
+        .details(details)
+        .sandbox_state(sandbox_state)
+        .retryable(retryable)
+        .build()
+        .expect("descriptor is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn resolve_destination_rejects_parent_traversal() {
+        let root = tempdir().expect("tempdir");
+        let escaped = root.path().join("exports").join("..").join("..");
+        let err = resolve_destination(&escaped, &[root.path().to_path_buf()])
+            .expect_err("parent traversal must be rejected");
+        assert_eq!(
+            err.data
+                .as_ref()
+                .and_then(|data| data.get("code"))
+                .and_then(Value::as_str),
+            Some("export_path_not_allowed")
+        );
+    }
+
+    #[test]
+    fn resolve_destination_creates_nested_directory_under_root() {
+        let root = tempdir().expect("tempdir");
+        let nested = root.path().join("site").join("downloads");
+        let resolved = resolve_destination(&nested, &[root.path().to_path_buf()])
+            .expect("nested destination is allowed");
+        assert!(resolved.is_dir());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_destination_rejects_symlink_escape_before_creating_directories() {
+        let root = tempdir().expect("tempdir");
+        let outside = tempdir().expect("tempdir");
+        std::os::unix::fs::symlink(outside.path(), root.path().join("link")).expect("symlink");
+
+        let escaped = root.path().join("link").join("new").join("dir");
+        let err = resolve_destination(&escaped, &[root.path().to_path_buf()])
+            .expect_err("symlink escape must be rejected");
+
+        assert_eq!(
+            err.data
+                .as_ref()
+                .and_then(|data| data.get("code"))
+                .and_then(Value::as_str),
+            Some("export_path_not_allowed")
+        );
+        assert!(!outside.path().join("new").exists());
+    }
+
+    #[test]
+    fn resolve_destination_creates_missing_export_root() {
+        let parent = tempdir().expect("tempdir");
+        let root = parent.path().join("exports");
+        let resolved = resolve_destination(&root.join("job"), std::slice::from_ref(&root))
+            .expect("destination under a missing root is allowed");
+        assert!(resolved.is_dir());
+    }
+}
@@ -0,0 +1,141 @@
+//! `list_build_jobs`: recent job records, so a lost `job_id` can be recovered.
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{ArtifactKind, VisionOsArtifactStore};
+
+/// Jobs returned when the request sets no `limit`.
+pub const DEFAULT_JOB_LIST_LIMIT: usize = 20;
+/// Most jobs a single call returns.
+pub const MAX_JOB_LIST_LIMIT: usize = 100;
+
+/// Input for `list_build_jobs`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListBuildJobsRequest {
+    /// Only list jobs built from this scheme.
+    #[serde(default)]
+    pub scheme: Option<String>,
+    /// Jobs to return, newest first (default 20, at most 100).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Response from `list_build_jobs`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ListBuildJobsResponse {
+    /// Unexpired jobs, newest first.
+    pub jobs: Vec<BuildJobSummary>,
+}
+
+/// One recorded job.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BuildJobSummary {
+    pub job_id: String,
+    pub status: &'static str,
+    pub artifact_kind: ArtifactKind,
+    pub scheme: Option<String>,
+    pub finished_at: DateTime<Utc>,
+    /// Seconds until the job's artifact and record expire.
+    pub ttl_seconds_remaining: u32,
+    /// `xcodebuild` runs of a build or archive job; above 1 when `visionos.retry` repeated a run.
+    pub attempts: usize,
+}
+
+/// List the newest unexpired jobs in `store`.
+pub async fn list_build_jobs(
+    store: &VisionOsArtifactStore,
+    request: ListBuildJobsRequest,
+) -> ListBuildJobsResponse {
+    let limit = request
+        .limit
+        .unwrap_or(DEFAULT_JOB_LIST_LIMIT)
+        .clamp(1, MAX_JOB_LIST_LIMIT);
+    let jobs = store
+        .recent_records(request.scheme.as_deref(), limit)
+        .await
+        .into_iter()
+        .map(|record| BuildJobSummary {
+            job_id: record.job_id.to_string(),
+            status: record.status.as_str(),
+            artifact_kind: record.artifact_kind,
+            ttl_seconds_remaining: store.ttl_seconds_remaining(&record),
+            attempts: record.attempts.len(),
+            scheme: record.scheme,
+            finished_at: record.finished_at,
+        })
+        .collect();
+    ListBuildJobsResponse { jobs }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::tools::visionos::artifacts::BuildFailureContext;
+
+    fn context(scheme: &str) -> BuildFailureContext {
+        BuildFailureContext {
+            project_path: "/tmp/VisionApp.xcodeproj".into(),
+            workspace: None,
+            scheme: scheme.into(),
+            configuration: "Debug".into(),
+            destination: "platform=visionOS Simulator,name=Apple Vision Pro".into(),
+            xcode_path: "/Applications/Xcode.app/Contents/Developer".into(),
+            env_overrides: Default::default(),
+            extra_args: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_newest_unexpired_jobs_first_and_filters_by_scheme() {
+        let temp = tempdir().expect("temporary directory");
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+        let now = Utc::now();
+        let mut job_ids = Vec::new();
+        for (scheme, age_secs) in [("VisionApp", 30), ("VisionToolbox", 20), ("VisionApp", 10)] {
+            let job_id = Uuid::new_v4();
+            store
+                .record_failure(
+                    job_id,
+                    ArtifactKind::Build,
+                    String::new(),
+                    Some(context(scheme)),
+                    now - Duration::seconds(age_secs),
+                )
+                .await
+                .expect("record failure");
+            job_ids.push(job_id.to_string());
+        }
+        store
+            .record_failure(
+                Uuid::new_v4(),
+                ArtifactKind::Build,
+                String::new(),
+                Some(context("VisionApp")),
+                now - Duration::seconds(700),
+            )
+            .await
+            .expect("record expired failure");
+
+        let all = list_build_jobs(&store, ListBuildJobsRequest::default()).await;
+        let filtered = list_build_jobs(
+            &store,
+            ListBuildJobsRequest {
+                scheme: Some("VisionApp".into()),
+                limit: Some(1),
+            },
+        )
+        .await;
+
+        let listed: Vec<_> = all.jobs.iter().map(|job| &job.job_id).collect();
+        assert_eq!(listed, vec![&job_ids[2], &job_ids[1], &job_ids[0]]);
+        assert_eq!(all.jobs[0].status, "failed");
+        assert!(all.jobs[0].ttl_seconds_remaining > 500);
+        assert_eq!(filtered.jobs.len(), 1);
+        assert_eq!(filtered.jobs[0].job_id, job_ids[2]);
+    }
+}
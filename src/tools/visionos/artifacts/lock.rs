@@ -0,0 +1,216 @@
+//! PID lockfile that keeps two servers from sharing one artifact root.
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use tracing::warn;
+
+use crate::lib::fs::{INSTANCES_DIR, ROOT_LOCK_FILE};
+
+/// Lock on an artifact root, released when dropped.
+#[derive(Debug)]
+pub struct ArtifactRootLock {
+    path: PathBuf,
+}
+
+impl Drop for ArtifactRootLock {
+    fn drop(&mut self) {
+        // Another server may have taken over a lock it considered stale; leave that one alone.
+        if read_lock_pid(&self.path) == Some(std::process::id()) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Root this process should use, with the lock it acquired.
+#[derive(Debug)]
+pub struct LockedArtifactRoot {
+    pub root: PathBuf,
+    /// `None` when the lock already belonged to this process or could not be written.
+    pub lock: Option<ArtifactRootLock>,
+}
+
+/// Lock `root` for this process. When another live server holds it, switch to
+/// `<root>/instances/<pid>/` so the two never clean up or overwrite each other's jobs.
+pub fn lock_artifact_root(root: &Path) -> LockedArtifactRoot {
+    let pid = std::process::id();
+    let path = root.join(ROOT_LOCK_FILE);
+    loop {
+        match create_lock(&path, pid) {
+            Ok(()) => {
+                return LockedArtifactRoot {
+                    root: root.to_path_buf(),
+                    lock: Some(ArtifactRootLock { path }),
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => {
+                warn!(
+                    target: "rmcp_sample::visionos",
+                    path = %path.display(),
+                    error = %err,
+                    "Failed to write artifact root lock; continuing without it"
+                );
+                return LockedArtifactRoot {
+                    root: root.to_path_buf(),
+                    lock: None,
+                };
+            }
+        }
+
+        match read_lock_pid(&path) {
+            // Several stores in one process share the root.
+            Some(holder) if holder == pid => {
+                return LockedArtifactRoot {
+                    root: root.to_path_buf(),
+                    lock: None,
+                }
+            }
+            Some(holder) if process_alive(holder) => {
+                let instance_root = root.join(INSTANCES_DIR).join(pid.to_string());
+                warn!(
+                    target: "rmcp_sample::visionos",
+                    root = %root.display(),
+                    holder_pid = holder,
+                    instance_root = %instance_root.display(),
+                    "Artifact root is locked by another running server; using a per-instance directory"
+                );
+                remove_stale_instances(&root.join(INSTANCES_DIR));
+                return LockedArtifactRoot {
+                    root: instance_root,
+                    lock: None,
+                };
+            }
+            // The holder exited without releasing the lock; take it over and retry.
+            _ => {
+                if let Err(err) = fs::remove_file(&path) {
+                    if err.kind() != io::ErrorKind::NotFound {
+                        warn!(
+                            target: "rmcp_sample::visionos",
+                            path = %path.display(),
+                            error = %err,
+                            "Failed to remove stale artifact root lock; continuing without it"
+                        );
+                        return LockedArtifactRoot {
+                            root: root.to_path_buf(),
+                            lock: None,
+                        };
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Write the PID to a temporary file and hard-link it into place, so a racing server never
+/// reads the lock before it holds a PID and mistakes it for a stale one.
+fn create_lock(path: &Path, pid: u32) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temp_name = path.as_os_str().to_owned();
+    temp_name.push(format!(".{pid}.tmp"));
+    let temp_path = PathBuf::from(temp_name);
+    let result = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&temp_path)
+        .and_then(|mut file| writeln!(file, "{pid}"))
+        .and_then(|()| fs::hard_link(&temp_path, path));
+    let _ = fs::remove_file(&temp_path);
+    result
+}
+
+fn read_lock_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Delete `instances/<pid>/` directories left behind by servers that are no longer running.
+fn remove_stale_instances(instances: &Path) {
+    let Ok(entries) = fs::read_dir(instances) else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let stale = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u32>().ok())
+            .is_some_and(|pid| pid != std::process::id() && !process_alive(pid));
+        if stale {
+            let _ = fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+/// `kill(pid, 0)` succeeds, or fails with `EPERM`, only for a running process.
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn live_holder_moves_server_to_instance_dir_and_stale_lock_is_taken_over() {
+        let temp = tempdir().expect("temporary directory");
+        let lock_path = temp.path().join(ROOT_LOCK_FILE);
+        let mut holder = Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("spawn sleep");
+        fs::write(&lock_path, format!("{}\n", holder.id())).expect("write lock");
+
+        let locked = lock_artifact_root(temp.path());
+
+        assert_eq!(
+            locked.root,
+            temp.path()
+                .join(INSTANCES_DIR)
+                .join(std::process::id().to_string())
+        );
+        assert!(locked.lock.is_none());
+
+        holder.kill().expect("kill holder");
+        holder.wait().expect("reap holder");
+        let locked = lock_artifact_root(temp.path());
+
+        assert_eq!(locked.root, temp.path());
+        assert_eq!(read_lock_pid(&lock_path), Some(std::process::id()));
+        drop(locked);
+        assert!(!lock_path.exists());
+    }
+
+    #[test]
+    fn lock_is_never_visible_without_its_pid() {
+        let temp = tempdir().expect("temporary directory");
+        let lock_path = temp.path().join(ROOT_LOCK_FILE);
+
+        create_lock(&lock_path, 4242).expect("create lock");
+        let err = create_lock(&lock_path, 4343).expect_err("lock already held");
+
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(read_lock_pid(&lock_path), Some(4242));
+        let leftovers: Vec<_> = fs::read_dir(temp.path())
+            .expect("read root")
+            .flatten()
+            .map(|entry| entry.file_name())
+            .filter(|name| name != ROOT_LOCK_FILE)
+            .collect();
+        assert!(leftovers.is_empty(), "temporary files left: {leftovers:?}");
+    }
+}
@@ -0,0 +1,255 @@
+//! `fetch_build_log`: pages through the complete `xcodebuild` output kept for a job.
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor};
+
+use super::{fetch_error_to_error_data, VisionOsArtifactStore, BUILD_LOG_FILE};
+
+/// Bytes returned when the request sets no `limit`.
+pub const DEFAULT_LOG_PAGE_BYTES: u64 = 64 * 1024;
+/// Largest page a single call returns.
+pub const MAX_LOG_PAGE_BYTES: u64 = 1024 * 1024;
+
+const LOG_NOT_FOUND_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "log_not_found",
+    "No full build log was kept for this job",
+    "Full logs are kept for jobs that ran xcodebuild; use log_excerpt from fetch_build_output or rerun the job.",
+);
+const LOG_READ_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "log_read_failed",
+    "Failed to read the build log",
+    "Check permissions of the artifact directory, then retry.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&LOG_NOT_FOUND_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&LOG_READ_FAILED_ERROR, true, SandboxState::NoViolation),
+];
+
+/// Input for `fetch_build_log`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FetchBuildLogRequest {
+    /// Job UUID, or `"latest"` for the most recent job.
+    pub job_id: String,
+    /// Restrict `"latest"` to jobs built from this scheme.
+    #[serde(default)]
+    pub scheme: Option<String>,
+    /// Byte offset to start reading from; pass the previous `next_offset` to continue.
+    #[serde(default)]
+    pub offset: u64,
+    /// Bytes to return (default 65536, at most 1048576).
+    #[serde(default)]
+    pub limit: Option<u64>,
+}
+
+/// Response from `fetch_build_log`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FetchBuildLogResponse {
+    pub job_id: String,
+    pub path: String,
+    pub total_bytes: u64,
+    pub offset: u64,
+    /// Bytes of the log covered by `text`.
+    pub returned_bytes: u64,
+    /// Offset of the next page; `None` once the end of the log is reached.
+    pub next_offset: Option<u64>,
+    pub eof: bool,
+    pub text: String,
+}
+
+/// Read one page of the job's full build log.
+pub async fn fetch_build_log(
+    store: &VisionOsArtifactStore,
+    request: FetchBuildLogRequest,
+) -> Result<FetchBuildLogResponse, ErrorData> {
+    let job_id = store
+        .resolve_job_id(&request.job_id, request.scheme.as_deref())
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    let record = store
+        .fetch_record(&job_id)
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    // Records written before logs were registered still find the log in the job directory.
+    let path = record.log_path.clone().unwrap_or_else(|| {
+        store
+            .root_dir()
+            .join(job_id.to_string())
+            .join(BUILD_LOG_FILE)
+    });
+    if !path.is_file() {
+        return Err(build_error_data(
+            &LOG_NOT_FOUND_ERROR,
+            json!({ "job_id": job_id.to_string() }),
+            false,
+        ));
+    }
+
+    let limit = request
+        .limit
+        .unwrap_or(DEFAULT_LOG_PAGE_BYTES)
+        .clamp(1, MAX_LOG_PAGE_BYTES);
+    let (total_bytes, page) =
+        read_page(&path, request.offset, limit).map_err(|err| read_failed(&path, err))?;
+    let offset = request.offset.min(total_bytes);
+    let returned_bytes = page.len() as u64;
+    let end = offset + returned_bytes;
+    let eof = end >= total_bytes;
+
+    Ok(FetchBuildLogResponse {
+        job_id: job_id.to_string(),
+        path: path.to_string_lossy().to_string(),
+        total_bytes,
+        offset,
+        returned_bytes,
+        next_offset: (!eof).then_some(end),
+        eof,
+        text: String::from_utf8_lossy(&page).into_owned(),
+    })
+}
+
+/// Up to `limit` bytes from `offset`, shortened so a UTF-8 character is not split across pages,
+/// along with the file size.
+fn read_page(path: &Path, offset: u64, limit: u64) -> std::io::Result<(u64, Vec<u8>)> {
+    let mut file = File::open(path)?;
+    let total_bytes = file.metadata()?.len();
+    let offset = offset.min(total_bytes);
+    file.seek(SeekFrom::Start(offset))?;
+    let mut page = Vec::new();
+    file.take(limit).read_to_end(&mut page)?;
+    if offset + (page.len() as u64) < total_bytes {
+        if let Err(err) = std::str::from_utf8(&page) {
+            if err.error_len().is_none() && err.valid_up_to() > 0 {
+                page.truncate(err.valid_up_to());
+            }
+        }
+    }
+    Ok((total_bytes, page))
+}
+
+fn read_failed(path: &Path, err: std::io::Error) -> ErrorData {
+    build_error_data(
+        &LOG_READ_FAILED_ERROR,
+        json!({ "path": path.to_string_lossy(), "details": err.to_string() }),
+        true,
+    )
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    retryable: bool,
+) -> ErrorData {
+    desc.builder()
+        .details(details)
+        .sandbox_state(SandboxState::NoViolation)
+        .retryable(retryable)
+        .build()
+        .expect("descriptor is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::Utc;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::tools::visionos::artifacts::ArtifactKind;
+
+    async fn failed_job_with_log(store: &VisionOsArtifactStore, log: &str) -> Uuid {
+        let job_id = Uuid::new_v4();
+        let job_dir = store.root_dir().join(job_id.to_string());
+        fs::create_dir_all(&job_dir).expect("job dir");
+        fs::write(job_dir.join(BUILD_LOG_FILE), log).expect("write log");
+        store
+            .record_failure(
+                job_id,
+                ArtifactKind::Build,
+                "error: boom".into(),
+                None,
+                Utc::now(),
+            )
+            .await
+            .expect("record failure");
+        job_id
+    }
+
+    fn request(job_id: Uuid, offset: u64, limit: u64) -> FetchBuildLogRequest {
+        FetchBuildLogRequest {
+            job_id: job_id.to_string(),
+            scheme: None,
+            offset,
+            limit: Some(limit),
+        }
+    }
+
+    #[tokio::test]
+    async fn pages_cover_the_whole_log() {
+        let temp = tempdir().expect("temporary directory");
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+        let job_id = failed_job_with_log(&store, "line one\nline two\n").await;
+        let record = store.fetch_record(&job_id).await.expect("record");
+        assert!(record.log_path.is_some_and(|path| path.is_file()));
+
+        let first = fetch_build_log(&store, request(job_id, 0, 9))
+            .await
+            .expect("first page");
+        assert_eq!(first.text, "line one\n");
+        assert_eq!(first.total_bytes, 18);
+        assert_eq!(first.next_offset, Some(9));
+        assert!(!first.eof);
+
+        let second = fetch_build_log(&store, request(job_id, 9, 100))
+            .await
+            .expect("second page");
+        assert_eq!(second.text, "line two\n");
+        assert_eq!(second.next_offset, None);
+        assert!(second.eof);
+    }
+
+    #[tokio::test]
+    async fn pages_do_not_split_multibyte_characters() {
+        let temp = tempdir().expect("temporary directory");
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+        let job_id = failed_job_with_log(&store, "aé b").await;
+
+        let page = fetch_build_log(&store, request(job_id, 0, 2))
+            .await
+            .expect("page");
+
+        assert_eq!(page.text, "a");
+        assert_eq!(page.next_offset, Some(1));
+    }
+
+    #[tokio::test]
+    async fn jobs_without_a_log_return_log_not_found() {
+        let temp = tempdir().expect("temporary directory");
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+        let job_id = Uuid::new_v4();
+        store
+            .record_failure(job_id, ArtifactKind::Build, String::new(), None, Utc::now())
+            .await
+            .expect("record failure");
+
+        let err = fetch_build_log(&store, request(job_id, 0, 10))
+            .await
+            .expect_err("no log");
+
+        assert_eq!(
+            err.data.expect("error data")["code"],
+            json!("log_not_found")
+        );
+    }
+}
@@ -1,5 +1,15 @@
 //! Management and retrieval tools for visionOS build artifacts.
+pub mod backend;
+pub mod durations;
+pub mod export;
+pub mod history;
+pub mod lock;
+pub mod logs;
+pub mod object_storage;
+pub mod purge;
 pub mod store;
+pub mod symbolicate;
+pub mod trace;
 
 use rmcp::model::ErrorData;
 use schemars::JsonSchema;
@@ -8,16 +18,36 @@ use serde_json::json;
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::lib::errors::{ArtifactError, SandboxState, ToolErrorDescriptor};
+use crate::lib::errors::{ArtifactError, ErrorCatalogEntry, SandboxState, ToolErrorDescriptor};
 
+pub use backend::{ArtifactBackend, ArtifactBackendInfo, LocalDirBackend, MemoryBackend};
+pub use durations::DurationHistory;
+pub use export::{export_artifact, ExportArtifactRequest, ExportArtifactResponse, ExportedFile};
+pub use history::{list_build_jobs, BuildJobSummary, ListBuildJobsRequest, ListBuildJobsResponse};
+pub use lock::{lock_artifact_root, ArtifactRootLock, LockedArtifactRoot};
+pub use logs::{fetch_build_log, FetchBuildLogRequest, FetchBuildLogResponse};
+pub use object_storage::{backend_from_config, ObjectStorageBackend, ObjectStorageProvider};
+pub use purge::{purge_artifacts, PurgeArtifactsRequest, PurgeArtifactsResponse, PurgeScope};
 pub use store::{
-    BuildFailureContext, BuildJobRecord, BuildJobStatus, VisionOsArtifactStore, ARTIFACT_ROOT,
+    ArtifactFile, ArtifactKind, ArtifactRetention, ArtifactStoreUsage, BuildFailureContext,
+    BuildJobRecord, BuildJobStatus, VisionOsArtifactStore, LATEST_JOB_ID,
+};
+pub use symbolicate::{
+    symbolicate_crash, SymbolicateCrashRequest, SymbolicateCrashResponse, SymbolicatedFrame,
+};
+pub use trace::{
+    export_job_trace, save_job_trace, ExportJobTraceRequest, ExportJobTraceResponse, JobTrace,
+    TraceFormat, BUILD_LOG_FILE,
 };
 
 /// Input for `fetch_build_output`.
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct FetchBuildOutputRequest {
+    /// Job UUID, or `"latest"` for the most recent job.
     pub job_id: String,
+    /// Restrict `"latest"` to jobs built from this scheme.
+    #[serde(default)]
+    pub scheme: Option<String>,
     #[serde(default = "default_include_logs")]
     pub include_logs: bool,
 }
@@ -31,10 +61,16 @@ fn default_include_logs() -> bool {
 pub struct FetchBuildOutputResponse {
     pub job_id: String,
     pub status: &'static str,
+    pub artifact_kind: ArtifactKind,
     pub artifact_zip: Option<String>,
     pub sha256: Option<String>,
     pub download_ttl_seconds: u32,
     pub log_excerpt: Option<String>,
+    /// Presigned URL replacing `artifact_zip` when an object-storage backend is active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+    /// Backend holding the artifact.
+    pub storage_backend: ArtifactBackendInfo,
 }
 
 /// Error types for `fetch_build_output`.
@@ -44,10 +80,23 @@ pub enum FetchBuildOutputError {
     InvalidJobId { raw: String },
     #[error("Job {job_id} not found")]
     JobNotFound { job_id: Uuid },
+    #[error("No unexpired job matches job_id \"latest\"")]
+    NoRecentJob { scheme: Option<String> },
     #[error("Artifacts for job {job_id} have expired")]
     ArtifactExpired { job_id: Uuid },
     #[error("Job {job_id} did not produce artifacts because the build failed")]
     BuildFailedNoArtifact { job_id: Uuid },
+    #[error("Job {job_id} was cancelled ({})", status.as_str())]
+    JobCancelled {
+        job_id: Uuid,
+        status: BuildJobStatus,
+    },
+    #[error("Could not create a download URL for job {job_id}: {source}")]
+    DownloadUrlFailed {
+        job_id: Uuid,
+        #[source]
+        source: ArtifactError,
+    },
     #[error(transparent)]
     Store(#[from] ArtifactError),
 }
@@ -57,28 +106,40 @@ pub async fn fetch_build_output(
     store: &VisionOsArtifactStore,
     request: FetchBuildOutputRequest,
 ) -> Result<FetchBuildOutputResponse, FetchBuildOutputError> {
-    let job_id = Uuid::parse_str(request.job_id.trim()).map_err(|_| {
-        FetchBuildOutputError::InvalidJobId {
-            raw: request.job_id.clone(),
-        }
-    })?;
+    let job_id = store
+        .resolve_job_id(&request.job_id, request.scheme.as_deref())
+        .await?;
     let record = store.fetch_record(&job_id).await?;
     match record.status {
         BuildJobStatus::Succeeded => {
             let ttl = store.ttl_seconds_remaining(&record);
-            Ok(FetchBuildOutputResponse {
-                job_id: job_id.to_string(),
-                status: "succeeded",
-                artifact_zip: record
+            let download_url = store
+                .download_url(&record)
+                .await
+                .map_err(|source| FetchBuildOutputError::DownloadUrlFailed { job_id, source })?;
+            let artifact_zip = match download_url {
+                Some(_) => None,
+                None => record
                     .artifact_zip
                     .as_ref()
                     .map(|path| path.to_string_lossy().to_string()),
+            };
+            Ok(FetchBuildOutputResponse {
+                job_id: job_id.to_string(),
+                status: "succeeded",
+                artifact_kind: record.artifact_kind,
+                artifact_zip,
                 sha256: record.artifact_sha256.clone(),
                 download_ttl_seconds: ttl,
                 log_excerpt: request.include_logs.then(|| record.log_excerpt.clone()),
+                download_url,
+                storage_backend: store.backend_info(),
             })
         }
         BuildJobStatus::Failed => Err(FetchBuildOutputError::BuildFailedNoArtifact { job_id }),
+        status @ (BuildJobStatus::CancelledQueued | BuildJobStatus::CancelledRunning) => {
+            Err(FetchBuildOutputError::JobCancelled { job_id, status })
+        }
     }
 }
 
@@ -94,12 +155,30 @@ pub fn fetch_error_to_error_data(err: FetchBuildOutputError) -> ErrorData {
         FetchBuildOutputError::JobNotFound { job_id } => {
             fetch_error(&JOB_NOT_FOUND_ERROR, Some(job_id), json!({}), false)
         }
+        FetchBuildOutputError::NoRecentJob { scheme } => fetch_error(
+            &JOB_NOT_FOUND_ERROR,
+            None,
+            json!({ "job_id": LATEST_JOB_ID, "scheme": scheme }),
+            false,
+        ),
         FetchBuildOutputError::ArtifactExpired { job_id } => {
             fetch_error(&ARTIFACT_EXPIRED_ERROR, Some(job_id), json!({}), true)
         }
         FetchBuildOutputError::BuildFailedNoArtifact { job_id } => {
             fetch_error(&BUILD_FAILED_ERROR, Some(job_id), json!({}), false)
         }
+        FetchBuildOutputError::JobCancelled { job_id, status } => fetch_error(
+            &JOB_CANCELLED_ERROR,
+            Some(job_id),
+            json!({ "status": status.as_str() }),
+            false,
+        ),
+        FetchBuildOutputError::DownloadUrlFailed { job_id, source } => fetch_error(
+            &DOWNLOAD_URL_FAILED_ERROR,
+            Some(job_id),
+            json!({ "details": source.to_string() }),
+            true,
+        ),
         FetchBuildOutputError::Store(err) => fetch_error(
             &ARTIFACT_EXPIRED_ERROR,
             None,
@@ -133,6 +212,27 @@ const BUILD_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "Review the logs, fix the issue, and build again.",
 );
 
+const JOB_CANCELLED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "job_cancelled",
+    "The build job was cancelled before producing artifacts",
+    "Run the build again if the artifacts are still needed.",
+);
+
+const DOWNLOAD_URL_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "download_url_failed",
+    "Could not create a presigned download URL",
+    "Check the [artifacts] credentials and cli_path, then retry fetch_build_output.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&INVALID_JOB_ID_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&JOB_NOT_FOUND_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&ARTIFACT_EXPIRED_ERROR, true, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&BUILD_FAILED_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&JOB_CANCELLED_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&DOWNLOAD_URL_FAILED_ERROR, true, SandboxState::NoViolation),
+];
+
 fn fetch_error(
     descriptor: &'static ToolErrorDescriptor,
     job_id: Option<Uuid>,
@@ -173,8 +273,14 @@ mod tests {
         store
             .record_success(
                 job_id,
-                artifact_path.clone(),
-                "deadbeef".into(),
+                ArtifactKind::Build,
+                "VisionApp".into(),
+                ArtifactFile {
+                    path: artifact_path.clone(),
+                    sha256: "deadbeef".into(),
+                    cache_key: None,
+                    ttl_secs: None,
+                },
                 "log excerpt".into(),
                 Utc::now(),
             )
@@ -185,6 +291,7 @@ mod tests {
             &store,
             FetchBuildOutputRequest {
                 job_id: job_id.to_string(),
+                scheme: None,
                 include_logs: true,
             },
         )
@@ -193,6 +300,7 @@ mod tests {
 
         assert_eq!(response.job_id, job_id.to_string());
         assert_eq!(response.status, "succeeded");
+        assert_eq!(response.artifact_kind, ArtifactKind::Build);
         assert_eq!(
             response.artifact_zip,
             Some(artifact_path.to_string_lossy().into())
@@ -214,8 +322,14 @@ mod tests {
         store
             .record_success(
                 job_id,
-                artifact_path,
-                "deadbeef".into(),
+                ArtifactKind::Build,
+                "VisionApp".into(),
+                ArtifactFile {
+                    path: artifact_path,
+                    sha256: "deadbeef".into(),
+                    cache_key: None,
+                    ttl_secs: None,
+                },
                 "log excerpt".into(),
                 Utc::now() - Duration::seconds(70),
             )
@@ -226,6 +340,7 @@ mod tests {
             &store,
             FetchBuildOutputRequest {
                 job_id: job_id.to_string(),
+                scheme: None,
                 include_logs: true,
             },
         )
@@ -242,7 +357,13 @@ mod tests {
         let job_id = Uuid::new_v4();
 
         store
-            .record_failure(job_id, "failed".into(), None, Utc::now())
+            .record_failure(
+                job_id,
+                ArtifactKind::Build,
+                "failed".into(),
+                None,
+                Utc::now(),
+            )
             .await
             .expect("record failure");
 
@@ -250,6 +371,7 @@ mod tests {
             &store,
             FetchBuildOutputRequest {
                 job_id: job_id.to_string(),
+                scheme: None,
                 include_logs: true,
             },
         )
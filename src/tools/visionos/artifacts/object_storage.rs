@@ -0,0 +1,229 @@
+//! S3 / GCS artifact backend driven by the `aws` and `gcloud` CLIs.
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    lib::errors::ArtifactError,
+    server::config::{ArtifactStorageKind, ArtifactsConfig},
+};
+
+use super::{
+    backend::{ArtifactBackend, ArtifactBackendInfo, LocalDirBackend},
+    store::BuildJobRecord,
+};
+
+/// Object storage provider reached through its CLI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectStorageProvider {
+    S3,
+    Gcs,
+}
+
+impl ObjectStorageProvider {
+    fn kind(self) -> &'static str {
+        match self {
+            Self::S3 => "s3",
+            Self::Gcs => "gcs",
+        }
+    }
+
+    fn scheme(self) -> &'static str {
+        match self {
+            Self::S3 => "s3",
+            Self::Gcs => "gs",
+        }
+    }
+}
+
+/// Uploads finished artifacts to a bucket and hands out presigned URLs.
+///
+/// Builds still stage locally and job records stay in the local artifact root; only the zipped
+/// artifact is copied to `<scheme>://<bucket>/<prefix>/<job_id>/<file>`.
+#[derive(Debug, Clone)]
+pub struct ObjectStorageBackend {
+    provider: ObjectStorageProvider,
+    bucket: String,
+    prefix: String,
+    cli_path: PathBuf,
+    local: LocalDirBackend,
+}
+
+impl ObjectStorageBackend {
+    pub fn new(
+        provider: ObjectStorageProvider,
+        bucket: String,
+        prefix: String,
+        cli_path: PathBuf,
+        local_root: PathBuf,
+    ) -> Self {
+        Self {
+            provider,
+            bucket,
+            prefix,
+            cli_path,
+            local: LocalDirBackend::new(local_root),
+        }
+    }
+
+    fn base_uri(&self) -> String {
+        let base = format!("{}://{}", self.provider.scheme(), self.bucket);
+        if self.prefix.is_empty() {
+            base
+        } else {
+            format!("{base}/{}", self.prefix)
+        }
+    }
+
+    fn run_cli(&self, uri: &str, args: &[&str]) -> Result<String, ArtifactError> {
+        let output = Command::new(&self.cli_path)
+            .args(args)
+            .output()
+            .map_err(|err| ArtifactError::ObjectStorage {
+                uri: uri.to_string(),
+                message: format!("failed to launch {}: {err}", self.cli_path.display()),
+            })?;
+        if !output.status.success() {
+            return Err(ArtifactError::ObjectStorage {
+                uri: uri.to_string(),
+                message: format!(
+                    "exit={:?}: {}",
+                    output.status.code(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+impl ArtifactBackend for ObjectStorageBackend {
+    fn info(&self) -> ArtifactBackendInfo {
+        ArtifactBackendInfo {
+            kind: self.provider.kind(),
+            location: self.base_uri(),
+        }
+    }
+
+    fn root_dir(&self) -> &Path {
+        self.local.root_dir()
+    }
+
+    fn save_record(&self, record: &BuildJobRecord) -> Result<(), ArtifactError> {
+        self.local.save_record(record)
+    }
+
+    fn load_records(&self) -> Vec<BuildJobRecord> {
+        self.local.load_records()
+    }
+
+    /// Remote objects are left to the bucket's lifecycle rules.
+    fn remove_expired(
+        &self,
+        ttl: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PathBuf>, ArtifactError> {
+        self.local.remove_expired(ttl, now)
+    }
+
+    fn publish_artifact(
+        &self,
+        job_id: &Uuid,
+        artifact: &Path,
+    ) -> Result<Option<String>, ArtifactError> {
+        let file_name = artifact
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "artifact.zip".into());
+        let uri = format!("{}/{job_id}/{file_name}", self.base_uri());
+        let source = artifact.to_string_lossy();
+        let args: Vec<&str> = match self.provider {
+            ObjectStorageProvider::S3 => vec!["s3", "cp", "--only-show-errors", &source, &uri],
+            ObjectStorageProvider::Gcs => vec!["storage", "cp", &source, &uri],
+        };
+        self.run_cli(&uri, &args)?;
+        Ok(Some(uri))
+    }
+
+    fn download_url(
+        &self,
+        artifact_uri: &str,
+        expires_in_secs: u32,
+    ) -> Result<Option<String>, ArtifactError> {
+        let expires_in = expires_in_secs.max(1).to_string();
+        let duration = format!("{expires_in}s");
+        let args: Vec<&str> = match self.provider {
+            ObjectStorageProvider::S3 => {
+                vec!["s3", "presign", artifact_uri, "--expires-in", &expires_in]
+            }
+            ObjectStorageProvider::Gcs => vec![
+                "storage",
+                "sign-url",
+                artifact_uri,
+                "--duration",
+                &duration,
+                "--format=value(signed_url)",
+            ],
+        };
+        let url = self.run_cli(artifact_uri, &args)?;
+        if url.is_empty() {
+            return Err(ArtifactError::ObjectStorage {
+                uri: artifact_uri.to_string(),
+                message: "presign command returned an empty URL".into(),
+            });
+        }
+        Ok(Some(url))
+    }
+}
+
+/// Build the backend selected by the `[artifacts]` section, staging builds under `local_root`.
+pub fn backend_from_config(
+    config: &ArtifactsConfig,
+    local_root: PathBuf,
+) -> Arc<dyn ArtifactBackend> {
+    let provider = match config.backend {
+        ArtifactStorageKind::Local => return Arc::new(LocalDirBackend::new(local_root)),
+        ArtifactStorageKind::S3 => ObjectStorageProvider::S3,
+        ArtifactStorageKind::Gcs => ObjectStorageProvider::Gcs,
+    };
+    Arc::new(ObjectStorageBackend::new(
+        provider,
+        config.bucket.clone().unwrap_or_default(),
+        config.prefix.clone(),
+        config.cli_path.clone(),
+        local_root,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info_reports_bucket_uri_with_prefix() {
+        let backend = ObjectStorageBackend::new(
+            ObjectStorageProvider::Gcs,
+            "visionos-ci".into(),
+            "nightly".into(),
+            PathBuf::from("/usr/local/bin/gcloud"),
+            PathBuf::from("/tmp/visionos-builds"),
+        );
+        let info = backend.info();
+        assert_eq!(info.kind, "gcs");
+        assert_eq!(info.location, "gs://visionos-ci/nightly");
+    }
+
+    #[test]
+    fn local_config_selects_local_backend() {
+        let backend = backend_from_config(
+            &ArtifactsConfig::default(),
+            PathBuf::from("/tmp/visionos-builds"),
+        );
+        assert_eq!(backend.info().kind, "local");
+    }
+}
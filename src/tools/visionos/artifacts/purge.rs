@@ -0,0 +1,210 @@
+//! `purge_artifacts`: deletes finished jobs to reclaim disk space without shell access.
+//!
+//! Only recorded jobs are purged, so queued and running jobs keep their working directories.
+//! Objects already uploaded to remote storage are left in place.
+use chrono::Utc;
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor};
+
+use super::{fetch_error_to_error_data, FetchBuildOutputError, VisionOsArtifactStore};
+
+const JOB_ID_REQUIRED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "job_id_required",
+    "scope \"job\" needs a job_id",
+    "Pass the job_id to purge, or use scope \"expired\" or \"all\".",
+);
+const PURGE_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "purge_failed",
+    "Failed to delete the job's artifacts",
+    "Check permissions of the artifact directory in the server log, then retry.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&JOB_ID_REQUIRED_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&PURGE_FAILED_ERROR, true, SandboxState::NoViolation),
+];
+
+/// Jobs `purge_artifacts` deletes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PurgeScope {
+    /// The job named by `job_id`.
+    Job,
+    /// Every finished job whose TTL has passed.
+    Expired,
+    /// Every finished job.
+    All,
+}
+
+/// Input for `purge_artifacts`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PurgeArtifactsRequest {
+    pub scope: PurgeScope,
+    /// Job UUID, or `"latest"`, for scope `job`.
+    #[serde(default)]
+    pub job_id: Option<String>,
+}
+
+/// Response from `purge_artifacts`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PurgeArtifactsResponse {
+    /// Removed jobs, oldest first.
+    pub removed_job_ids: Vec<String>,
+    pub freed_bytes: u64,
+}
+
+/// Delete the artifacts and records of the jobs `request` selects.
+pub async fn purge_artifacts(
+    store: &VisionOsArtifactStore,
+    request: PurgeArtifactsRequest,
+) -> Result<PurgeArtifactsResponse, ErrorData> {
+    let now = Utc::now();
+    let finished = store.finished_jobs(now).await;
+    let job_ids = match request.scope {
+        PurgeScope::Job => {
+            let raw = request
+                .job_id
+                .as_deref()
+                .ok_or_else(|| build_error_data(&JOB_ID_REQUIRED_ERROR, json!({}), false))?;
+            let job_id = store
+                .resolve_job_id(raw, None)
+                .await
+                .map_err(fetch_error_to_error_data)?;
+            if !finished.iter().any(|(id, _)| *id == job_id) {
+                return Err(fetch_error_to_error_data(
+                    FetchBuildOutputError::JobNotFound { job_id },
+                ));
+            }
+            vec![job_id]
+        }
+        PurgeScope::Expired => finished
+            .into_iter()
+            .filter(|(_, expired)| *expired)
+            .map(|(job_id, _)| job_id)
+            .collect(),
+        PurgeScope::All => finished.into_iter().map(|(job_id, _)| job_id).collect(),
+    };
+
+    let requested = job_ids.len();
+    let removed = store.remove_jobs(job_ids.clone()).await;
+    if request.scope == PurgeScope::Job && removed.len() < requested {
+        return Err(build_error_data(
+            &PURGE_FAILED_ERROR,
+            json!({ "job_id": job_ids[0].to_string() }),
+            true,
+        ));
+    }
+    Ok(PurgeArtifactsResponse {
+        freed_bytes: removed.iter().map(|(_, bytes)| bytes).sum(),
+        removed_job_ids: removed
+            .into_iter()
+            .map(|(job_id, _)| job_id.to_string())
+            .collect(),
+    })
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: serde_json::Value,
+    retryable: bool,
+) -> ErrorData {
+    desc.builder()
+        .details(details)
+        .sandbox_state(SandboxState::NoViolation)
+        .retryable(retryable)
+        .build()
+        .expect("descriptor is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::Duration;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::tools::visionos::artifacts::ArtifactKind;
+
+    async fn job_with_artifact(store: &VisionOsArtifactStore, age_secs: i64) -> Uuid {
+        let job_id = Uuid::new_v4();
+        let job_dir = store.root_dir().join(job_id.to_string());
+        fs::create_dir_all(&job_dir).expect("job dir");
+        fs::write(job_dir.join("artifact.zip"), vec![0u8; 100]).expect("write artifact");
+        store
+            .record_failure(
+                job_id,
+                ArtifactKind::Build,
+                String::new(),
+                None,
+                Utc::now() - Duration::seconds(age_secs),
+            )
+            .await
+            .expect("record failure");
+        job_id
+    }
+
+    fn request(scope: PurgeScope, job_id: Option<Uuid>) -> PurgeArtifactsRequest {
+        PurgeArtifactsRequest {
+            scope,
+            job_id: job_id.map(|job_id| job_id.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn purges_one_job_then_expired_then_all() {
+        let temp = tempdir().expect("temporary directory");
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 1800);
+        let expired = job_with_artifact(&store, 700).await;
+        let kept = job_with_artifact(&store, 20).await;
+        let single = job_with_artifact(&store, 10).await;
+        let running_dir = temp.path().join(Uuid::new_v4().to_string());
+        fs::create_dir_all(&running_dir).expect("running job dir");
+
+        let one = purge_artifacts(&store, request(PurgeScope::Job, Some(single)))
+            .await
+            .expect("purge one job");
+        assert_eq!(one.removed_job_ids, vec![single.to_string()]);
+        assert!(one.freed_bytes >= 100);
+        assert!(!temp.path().join(single.to_string()).exists());
+
+        let old = purge_artifacts(&store, request(PurgeScope::Expired, None))
+            .await
+            .expect("purge expired jobs");
+        assert_eq!(old.removed_job_ids, vec![expired.to_string()]);
+        assert!(temp.path().join(kept.to_string()).exists());
+
+        let all = purge_artifacts(&store, request(PurgeScope::All, None))
+            .await
+            .expect("purge all jobs");
+        assert_eq!(all.removed_job_ids, vec![kept.to_string()]);
+        assert!(running_dir.exists(), "unrecorded jobs are left alone");
+    }
+
+    #[tokio::test]
+    async fn job_scope_needs_a_recorded_job_id() {
+        let temp = tempdir().expect("temporary directory");
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+
+        let missing_id = purge_artifacts(&store, request(PurgeScope::Job, None))
+            .await
+            .expect_err("job_id is required");
+        let unknown = purge_artifacts(&store, request(PurgeScope::Job, Some(Uuid::new_v4())))
+            .await
+            .expect_err("unknown job");
+
+        assert_eq!(
+            missing_id.data.expect("error data")["code"],
+            json!("job_id_required")
+        );
+        assert_eq!(
+            unknown.data.expect("error data")["code"],
+            json!("job_not_found")
+        );
+    }
+}
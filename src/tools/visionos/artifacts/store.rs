@@ -6,25 +6,110 @@ use std::{
 };
 
 use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use tracing::warn;
 use uuid::Uuid;
 
-use crate::lib::errors::ArtifactError;
-use crate::lib::fs as artifact_fs;
+use crate::{
+    lib::{
+        app_size::directory_size,
+        errors::ArtifactError,
+        retry::BuildAttempt,
+        telemetry::{emit_artifact_eviction, ArtifactEvictionTelemetry},
+    },
+    server::config::{ArtifactPersistence, ArtifactsConfig},
+    tools::visionos::build::CancelOutcome,
+};
+
+use super::{
+    backend::{ArtifactBackend, ArtifactBackendInfo, LocalDirBackend, MemoryBackend},
+    durations::DurationHistory,
+    lock::{lock_artifact_root, ArtifactRootLock},
+    object_storage::backend_from_config,
+    trace::BUILD_LOG_FILE,
+};
 
-pub const ARTIFACT_ROOT: &str = "target/visionos-builds";
+/// Preferred artifact root, relative to the server's working directory. Everything else reads
+/// the resolved root from [`VisionOsArtifactStore::root_dir`].
+const ARTIFACT_ROOT: &str = "target/visionos-builds";
+/// `job_id` alias resolving to the most recent job still inside its TTL.
+pub const LATEST_JOB_ID: &str = "latest";
 const ARTIFACT_FALLBACK_ROOT: &str = "seiro-mcp/visionos-builds";
 
 /// Build job status.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BuildJobStatus {
     Succeeded,
     Failed,
+    /// Cancelled before `xcodebuild` started.
+    #[serde(rename = "cancelled_queued")]
+    CancelledQueued,
+    /// Cancelled while `xcodebuild` was running.
+    #[serde(rename = "cancelled_running")]
+    CancelledRunning,
 }
 
-/// Build context captured on failures to enable deterministic diagnostics.
+impl BuildJobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::CancelledQueued => "cancelled_queued",
+            Self::CancelledRunning => "cancelled_running",
+        }
+    }
+}
+
+impl From<CancelOutcome> for BuildJobStatus {
+    fn from(outcome: CancelOutcome) -> Self {
+        match outcome {
+            CancelOutcome::CancelledQueued => Self::CancelledQueued,
+            CancelOutcome::CancelledRunning => Self::CancelledRunning,
+        }
+    }
+}
+
+/// Kind of artifact a job produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactKind {
+    /// Zipped build products from `xcodebuild build`.
+    Build,
+    /// Zipped `.xcarchive` from `xcodebuild archive`.
+    Archive,
+    /// `.ipa` exported from an archive job with `xcodebuild -exportArchive`.
+    Ipa,
+    /// Simulator screenshot or recording from `capture_simulator_media`.
+    Media,
+}
+
+impl ArtifactKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Build => "build",
+            Self::Archive => "archive",
+            Self::Ipa => "ipa",
+            Self::Media => "media",
+        }
+    }
+}
+
+/// Zipped artifact produced by a successful job.
 #[derive(Debug, Clone)]
+pub struct ArtifactFile {
+    pub path: PathBuf,
+    pub sha256: String,
+    /// Build cache key of the inputs that produced the artifact.
+    pub cache_key: Option<String>,
+    /// TTL of the project's workspace root when shorter than the store's.
+    pub ttl_secs: Option<u32>,
+}
+
+/// Build context captured on failures to enable deterministic diagnostics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildFailureContext {
     pub project_path: PathBuf,
     pub workspace: Option<PathBuf>,
@@ -37,18 +122,69 @@ pub struct BuildFailureContext {
 }
 
 /// Record of a build job.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildJobRecord {
     pub job_id: Uuid,
     pub status: BuildJobStatus,
+    pub artifact_kind: ArtifactKind,
+    /// Scheme that produced the job; used to filter `job_id: "latest"`.
+    #[serde(default)]
+    pub scheme: Option<String>,
     pub artifact_zip: Option<PathBuf>,
+    /// Remote copy of `artifact_zip` when an object-storage backend is active.
+    #[serde(default)]
+    pub artifact_uri: Option<String>,
     pub artifact_sha256: Option<String>,
     pub log_excerpt: String,
     pub finished_at: DateTime<Utc>,
     pub failure_context: Option<BuildFailureContext>,
+    /// Build cache key for successful jobs; see `build::cache`.
+    #[serde(default)]
+    pub cache_key: Option<String>,
+    /// Artifact TTL for this job; the store's TTL when unset.
+    #[serde(default)]
+    pub ttl_secs: Option<u32>,
+    /// Complete `xcodebuild` output of the job, paged by `fetch_build_log`.
+    #[serde(default)]
+    pub log_path: Option<PathBuf>,
+    /// `xcodebuild` runs of a build or archive job, oldest first; more than one when
+    /// `visionos.retry` repeated a run.
+    #[serde(default)]
+    pub attempts: Vec<BuildAttempt>,
+}
+
+/// Disk use and bookkeeping of the artifact store, reported by the `seiro://status` resource.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ArtifactStoreUsage {
+    /// Job records still inside the metadata retention window.
+    pub jobs: usize,
+    /// Bytes below the store's root directory.
+    pub bytes: u64,
+    /// Last run of the TTL cleanup; `None` until the first job was recorded.
+    pub last_cleanup: Option<DateTime<Utc>>,
 }
 
-/// Store that persists visionOS artifacts and enforces TTL.
+/// Caps on the recorded jobs a store keeps, enforced oldest first; `0` disables a limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArtifactRetention {
+    pub max_jobs: usize,
+    pub max_total_bytes: u64,
+}
+
+impl ArtifactRetention {
+    /// Limit that `jobs` recorded jobs taking `bytes` exceed, if any.
+    fn exceeded(&self, jobs: usize, bytes: u64) -> Option<&'static str> {
+        if self.max_jobs > 0 && jobs > self.max_jobs {
+            Some("max_jobs")
+        } else if self.max_total_bytes > 0 && bytes > self.max_total_bytes {
+            Some("max_total_bytes")
+        } else {
+            None
+        }
+    }
+}
+
+/// Store that persists visionOS artifacts and enforces TTL and retention limits.
 #[derive(Clone, Debug)]
 pub struct VisionOsArtifactStore {
     inner: Arc<VisionOsArtifactStoreInner>,
@@ -56,96 +192,436 @@ pub struct VisionOsArtifactStore {
 
 #[derive(Debug)]
 struct VisionOsArtifactStoreInner {
-    root: PathBuf,
+    backend: Arc<dyn ArtifactBackend>,
     ttl: Duration,
     cleanup_interval: Duration,
+    retention: ArtifactRetention,
     state: Mutex<ArtifactStoreState>,
+    /// Held for the store's lifetime so other servers move to their own instance directory.
+    _root_lock: Option<ArtifactRootLock>,
 }
 
 #[derive(Debug)]
 struct ArtifactStoreState {
     jobs: HashMap<Uuid, BuildJobRecord>,
     last_cleanup: Option<DateTime<Utc>>,
+    durations: DurationHistory,
 }
 
 impl VisionOsArtifactStore {
-    /// Build a store using the default artifact directory.
-    pub fn new(ttl_secs: u32, cleanup_schedule_secs: u32) -> Self {
-        let root = resolve_artifact_root();
-        Self::with_root(root, ttl_secs, cleanup_schedule_secs)
+    /// Build a store using the default artifact directory and the configured backend.
+    ///
+    /// The root is locked for this process; when another running server holds it, the store
+    /// uses `<root>/instances/<pid>/` instead. `Memory` persistence skips the root and keeps
+    /// everything in a temporary directory.
+    pub fn new(
+        artifacts: &ArtifactsConfig,
+        persistence: ArtifactPersistence,
+        ttl_secs: u32,
+        cleanup_schedule_secs: u32,
+        retention: ArtifactRetention,
+    ) -> Self {
+        if persistence == ArtifactPersistence::Memory {
+            return Self::from_parts(
+                Arc::new(MemoryBackend::new()),
+                ttl_secs,
+                cleanup_schedule_secs,
+                retention,
+                None,
+            );
+        }
+        let locked = lock_artifact_root(&resolve_artifact_root());
+        Self::from_parts(
+            backend_from_config(artifacts, locked.root),
+            ttl_secs,
+            cleanup_schedule_secs,
+            retention,
+            locked.lock,
+        )
     }
 
     /// Build a store with a custom root directory (useful for tests).
     pub fn with_root(root: PathBuf, ttl_secs: u32, cleanup_schedule_secs: u32) -> Self {
+        Self::with_backend(
+            Arc::new(LocalDirBackend::new(root)),
+            ttl_secs,
+            cleanup_schedule_secs,
+        )
+    }
+
+    /// Build a store on top of `backend`, reloading records persisted by a previous process.
+    pub fn with_backend(
+        backend: Arc<dyn ArtifactBackend>,
+        ttl_secs: u32,
+        cleanup_schedule_secs: u32,
+    ) -> Self {
+        Self::from_parts(
+            backend,
+            ttl_secs,
+            cleanup_schedule_secs,
+            ArtifactRetention::default(),
+            None,
+        )
+    }
+
+    fn from_parts(
+        backend: Arc<dyn ArtifactBackend>,
+        ttl_secs: u32,
+        cleanup_schedule_secs: u32,
+        retention: ArtifactRetention,
+        root_lock: Option<ArtifactRootLock>,
+    ) -> Self {
+        let ttl = Duration::seconds(ttl_secs as i64);
+        let cleanup_interval = Duration::seconds(cleanup_schedule_secs as i64);
+        let jobs = retain_recent(backend.load_records(), ttl + cleanup_interval, Utc::now());
+        let durations = DurationHistory::load(backend.root_dir());
         Self {
             inner: Arc::new(VisionOsArtifactStoreInner {
-                root,
-                ttl: Duration::seconds(ttl_secs as i64),
-                cleanup_interval: Duration::seconds(cleanup_schedule_secs as i64),
+                backend,
+                ttl,
+                cleanup_interval,
+                retention,
                 state: Mutex::new(ArtifactStoreState {
-                    jobs: HashMap::new(),
+                    jobs,
                     last_cleanup: None,
+                    durations,
                 }),
+                _root_lock: root_lock,
             }),
         }
     }
 
     /// Return the artifact root directory currently used by this store.
     pub fn root_dir(&self) -> PathBuf {
-        self.inner.root.clone()
+        self.inner.backend.root_dir().to_path_buf()
+    }
+
+    /// Delete artifacts the backend must not leave behind once the server exits.
+    pub fn discard(&self) {
+        self.inner.backend.discard();
+    }
+
+    /// Describe the active storage backend.
+    pub fn backend_info(&self) -> ArtifactBackendInfo {
+        self.inner.backend.info()
+    }
+
+    /// Recorded jobs, bytes on disk, and the last cleanup time.
+    pub async fn usage(&self) -> ArtifactStoreUsage {
+        let (jobs, last_cleanup) = {
+            let state = self.inner.state.lock().await;
+            (state.jobs.len(), state.last_cleanup)
+        };
+        let root = self.root_dir();
+        let bytes = tokio::task::spawn_blocking(move || {
+            if root.is_dir() {
+                directory_size(&root).unwrap_or(0)
+            } else {
+                0
+            }
+        })
+        .await
+        .unwrap_or(0);
+        ArtifactStoreUsage {
+            jobs,
+            bytes,
+            last_cleanup,
+        }
     }
 
     /// Record a successful job.
     pub async fn record_success(
         &self,
         job_id: Uuid,
-        artifact_zip: PathBuf,
-        artifact_sha256: String,
+        artifact_kind: ArtifactKind,
+        scheme: String,
+        artifact: ArtifactFile,
         log_excerpt: String,
         finished_at: DateTime<Utc>,
     ) -> Result<(), ArtifactError> {
         self.maybe_cleanup(finished_at).await;
-        let mut state = self.inner.state.lock().await;
-        state.jobs.insert(
+        let backend = Arc::clone(&self.inner.backend);
+        let ArtifactFile {
+            path: artifact_zip,
+            sha256: artifact_sha256,
+            cache_key,
+            ttl_secs,
+        } = artifact;
+        let local_zip = artifact_zip.clone();
+        let artifact_uri =
+            tokio::task::spawn_blocking(move || backend.publish_artifact(&job_id, &local_zip))
+                .await
+                .map_err(|err| ArtifactError::ObjectStorage {
+                    uri: artifact_zip.to_string_lossy().to_string(),
+                    message: err.to_string(),
+                })??;
+        self.insert_record(BuildJobRecord {
             job_id,
-            BuildJobRecord {
-                job_id,
-                status: BuildJobStatus::Succeeded,
-                artifact_zip: Some(artifact_zip),
-                artifact_sha256: Some(artifact_sha256),
-                log_excerpt,
-                finished_at,
-                failure_context: None,
-            },
-        );
-        Ok(())
+            status: BuildJobStatus::Succeeded,
+            artifact_kind,
+            scheme: Some(scheme),
+            artifact_zip: Some(artifact_zip),
+            artifact_uri,
+            artifact_sha256: Some(artifact_sha256),
+            log_excerpt,
+            finished_at,
+            failure_context: None,
+            cache_key,
+            ttl_secs,
+            log_path: None,
+            attempts: Vec::new(),
+        })
+        .await
     }
 
     /// Record a failed job.
     pub async fn record_failure(
         &self,
         job_id: Uuid,
+        artifact_kind: ArtifactKind,
         log_excerpt: String,
         failure_context: Option<BuildFailureContext>,
         finished_at: DateTime<Utc>,
     ) -> Result<(), ArtifactError> {
         self.maybe_cleanup(finished_at).await;
-        let mut state = self.inner.state.lock().await;
-        state.jobs.insert(
+        self.insert_record(BuildJobRecord {
             job_id,
-            BuildJobRecord {
-                job_id,
-                status: BuildJobStatus::Failed,
-                artifact_zip: None,
-                artifact_sha256: None,
-                log_excerpt,
-                finished_at,
-                failure_context,
-            },
-        );
+            status: BuildJobStatus::Failed,
+            artifact_kind,
+            scheme: failure_context
+                .as_ref()
+                .map(|context| context.scheme.clone()),
+            artifact_zip: None,
+            artifact_uri: None,
+            artifact_sha256: None,
+            log_excerpt,
+            finished_at,
+            failure_context,
+            cache_key: None,
+            ttl_secs: None,
+            log_path: None,
+            attempts: Vec::new(),
+        })
+        .await
+    }
+
+    /// Record a job stopped by `cancel_build_job`.
+    pub async fn record_cancellation(
+        &self,
+        job_id: Uuid,
+        artifact_kind: ArtifactKind,
+        scheme: String,
+        outcome: CancelOutcome,
+        finished_at: DateTime<Utc>,
+    ) -> Result<(), ArtifactError> {
+        self.maybe_cleanup(finished_at).await;
+        self.insert_record(BuildJobRecord {
+            job_id,
+            status: outcome.into(),
+            artifact_kind,
+            scheme: Some(scheme),
+            artifact_zip: None,
+            artifact_uri: None,
+            artifact_sha256: None,
+            log_excerpt: String::new(),
+            finished_at,
+            failure_context: None,
+            cache_key: None,
+            ttl_secs: None,
+            log_path: None,
+            attempts: Vec::new(),
+        })
+        .await
+    }
+
+    /// Attach the `xcodebuild` runs of a recorded job, once it is recorded.
+    pub async fn record_attempts(
+        &self,
+        job_id: Uuid,
+        attempts: Vec<BuildAttempt>,
+    ) -> Result<(), ArtifactError> {
+        let record = {
+            let mut state = self.inner.state.lock().await;
+            let Some(record) = state.jobs.get_mut(&job_id) else {
+                return Ok(());
+            };
+            record.attempts = attempts;
+            record.clone()
+        };
+        self.inner.backend.save_record(&record)
+    }
+
+    /// Add the run time of a successful job to the duration history of its kind and scheme.
+    pub async fn record_duration(
+        &self,
+        kind: ArtifactKind,
+        scheme: &str,
+        duration: std::time::Duration,
+    ) {
+        let history = {
+            let mut state = self.inner.state.lock().await;
+            state.durations.record(kind, scheme, duration);
+            state.durations.clone()
+        };
+        if let Err(err) = history.save(&self.root_dir()) {
+            warn!(
+                target: "rmcp_sample::visionos",
+                scheme,
+                error = %err,
+                "Failed to save build duration history"
+            );
+        }
+    }
+
+    /// Mean run time of the recent successful jobs of `kind` for `scheme`.
+    pub async fn estimated_duration(
+        &self,
+        kind: ArtifactKind,
+        scheme: &str,
+    ) -> Option<std::time::Duration> {
+        let state = self.inner.state.lock().await;
+        state.durations.estimate(kind, scheme)
+    }
+
+    /// Newest successful job recorded with `cache_key` whose artifact is still inside its TTL.
+    pub(crate) async fn find_cached(&self, cache_key: &str) -> Option<BuildJobRecord> {
+        let now = Utc::now();
+        let state = self.inner.state.lock().await;
+        state
+            .jobs
+            .values()
+            .filter(|record| record.status == BuildJobStatus::Succeeded)
+            .filter(|record| record.cache_key.as_deref() == Some(cache_key))
+            .filter(|record| now - record.finished_at <= self.record_ttl(record))
+            .filter(|record| {
+                record.artifact_uri.is_some()
+                    || record.artifact_zip.as_deref().is_some_and(Path::is_file)
+            })
+            .max_by_key(|record| record.finished_at)
+            .cloned()
+    }
+
+    /// Presigned URL for the job's remote artifact, valid for the rest of its TTL.
+    pub(crate) async fn download_url(
+        &self,
+        record: &BuildJobRecord,
+    ) -> Result<Option<String>, ArtifactError> {
+        let Some(uri) = record.artifact_uri.clone() else {
+            return Ok(None);
+        };
+        let backend = Arc::clone(&self.inner.backend);
+        let expires_in = self.ttl_seconds_remaining(record);
+        let task_uri = uri.clone();
+        tokio::task::spawn_blocking(move || backend.download_url(&task_uri, expires_in))
+            .await
+            .map_err(|err| ArtifactError::ObjectStorage {
+                uri,
+                message: err.to_string(),
+            })?
+    }
+
+    /// Persist `record` through the backend, then make it visible to lookups. Registers the
+    /// job's full build log when the executor wrote one.
+    async fn insert_record(&self, mut record: BuildJobRecord) -> Result<(), ArtifactError> {
+        let log_path = self
+            .root_dir()
+            .join(record.job_id.to_string())
+            .join(BUILD_LOG_FILE);
+        if record.log_path.is_none() && log_path.is_file() {
+            record.log_path = Some(log_path);
+        }
+        self.inner.backend.save_record(&record)?;
+
+        let mut state = self.inner.state.lock().await;
+        state.jobs.insert(record.job_id, record);
         Ok(())
     }
 
+    /// Up to `limit` unexpired records, newest first, optionally restricted to `scheme`.
+    pub(crate) async fn recent_records(
+        &self,
+        scheme: Option<&str>,
+        limit: usize,
+    ) -> Vec<BuildJobRecord> {
+        let now = Utc::now();
+        let state = self.inner.state.lock().await;
+        let mut records: Vec<_> = state
+            .jobs
+            .values()
+            .filter(|record| now - record.finished_at <= self.record_ttl(record))
+            .filter(|record| scheme.is_none_or(|scheme| record.scheme.as_deref() == Some(scheme)))
+            .cloned()
+            .collect();
+        records.sort_by(|a, b| b.finished_at.cmp(&a.finished_at));
+        records.truncate(limit);
+        records
+    }
+
+    /// Recorded jobs, oldest first, each with whether its TTL has passed at `now`.
+    pub(crate) async fn finished_jobs(&self, now: DateTime<Utc>) -> Vec<(Uuid, bool)> {
+        let state = self.inner.state.lock().await;
+        let mut records: Vec<_> = state.jobs.values().collect();
+        records.sort_by_key(|record| record.finished_at);
+        records
+            .into_iter()
+            .map(|record| {
+                let expired = now - record.finished_at > self.record_ttl(record);
+                (record.job_id, expired)
+            })
+            .collect()
+    }
+
+    /// Delete the directories and records of `job_ids`, returning each removed job with the
+    /// bytes it took. Jobs whose directory cannot be removed are logged and skipped.
+    pub(crate) async fn remove_jobs(&self, job_ids: Vec<Uuid>) -> Vec<(Uuid, u64)> {
+        let root = self.root_dir();
+        let removed = tokio::task::spawn_blocking(move || {
+            job_ids
+                .into_iter()
+                .filter_map(|job_id| remove_job_dir(&root, &job_id).map(|bytes| (job_id, bytes)))
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_default();
+        let mut state = self.inner.state.lock().await;
+        for (job_id, _) in &removed {
+            state.jobs.remove(job_id);
+        }
+        removed
+    }
+
+    /// Parse `raw` as a job UUID, or resolve [`LATEST_JOB_ID`] to the newest unexpired job,
+    /// optionally restricted to `scheme`.
+    pub(crate) async fn resolve_job_id(
+        &self,
+        raw: &str,
+        scheme: Option<&str>,
+    ) -> Result<Uuid, crate::tools::visionos::artifacts::FetchBuildOutputError> {
+        let trimmed = raw.trim();
+        if !trimmed.eq_ignore_ascii_case(LATEST_JOB_ID) {
+            return Uuid::parse_str(trimmed).map_err(|_| {
+                crate::tools::visionos::artifacts::FetchBuildOutputError::InvalidJobId {
+                    raw: raw.to_string(),
+                }
+            });
+        }
+
+        let now = Utc::now();
+        let state = self.inner.state.lock().await;
+        state
+            .jobs
+            .values()
+            .filter(|record| now - record.finished_at <= self.record_ttl(record))
+            .filter(|record| scheme.is_none_or(|scheme| record.scheme.as_deref() == Some(scheme)))
+            .max_by_key(|record| record.finished_at)
+            .map(|record| record.job_id)
+            .ok_or_else(
+                || crate::tools::visionos::artifacts::FetchBuildOutputError::NoRecentJob {
+                    scheme: scheme.map(str::to_string),
+                },
+            )
+    }
+
     pub(crate) async fn fetch_record(
         &self,
         job_id: &Uuid,
@@ -158,7 +634,7 @@ impl VisionOsArtifactStore {
                 job_id: *job_id,
             },
         )?;
-        if now - record.finished_at > self.inner.ttl {
+        if now - record.finished_at > self.record_ttl(&record) {
             state.jobs.remove(job_id);
             return Err(
                 crate::tools::visionos::artifacts::FetchBuildOutputError::ArtifactExpired {
@@ -169,9 +645,17 @@ impl VisionOsArtifactStore {
         Ok(record)
     }
 
+    /// TTL of `record`: its own when set, otherwise the store's.
+    fn record_ttl(&self, record: &BuildJobRecord) -> Duration {
+        record
+            .ttl_secs
+            .map(|secs| Duration::seconds(secs as i64))
+            .unwrap_or(self.inner.ttl)
+    }
+
     pub(crate) fn ttl_seconds_remaining(&self, record: &BuildJobRecord) -> u32 {
         let now = Utc::now();
-        let expires_at = record.finished_at + self.inner.ttl;
+        let expires_at = record.finished_at + self.record_ttl(record);
         if expires_at <= now {
             return 0;
         }
@@ -196,27 +680,130 @@ impl VisionOsArtifactStore {
             return;
         }
 
-        if let Err(err) =
-            artifact_fs::cleanup_expired_entries(&self.inner.root, self.inner.ttl, now)
-        {
+        if let Err(err) = self.inner.backend.remove_expired(self.inner.ttl, now) {
             warn!(
                 target: "rmcp_sample::visionos",
                 error = %err,
-                root = %self.inner.root.display(),
+                root = %self.inner.backend.info().location,
                 "Failed to clean artifact directory"
             );
         }
 
         let metadata_window = self.inner.ttl + self.inner.cleanup_interval;
+        {
+            let mut state = self.inner.state.lock().await;
+            state
+                .jobs
+                .retain(|_, record| now - record.finished_at <= metadata_window);
+        }
+        self.evict_over_retention().await;
+    }
+
+    /// Delete the oldest recorded jobs until the rest fit the retention limits.
+    async fn evict_over_retention(&self) {
+        let retention = self.inner.retention;
+        if retention == ArtifactRetention::default() {
+            return;
+        }
+        let mut jobs: Vec<_> = {
+            let state = self.inner.state.lock().await;
+            state
+                .jobs
+                .values()
+                .map(|record| (record.finished_at, record.job_id))
+                .collect()
+        };
+        jobs.sort();
+        let root = self.root_dir();
+        let evicted = tokio::task::spawn_blocking(move || {
+            let jobs: Vec<_> = jobs.into_iter().map(|(_, job_id)| job_id).collect();
+            evict_oldest(&root, &jobs, retention)
+        })
+        .await
+        .unwrap_or_default();
+        if evicted.is_empty() {
+            return;
+        }
         let mut state = self.inner.state.lock().await;
-        state
-            .jobs
-            .retain(|_, record| now - record.finished_at <= metadata_window);
+        for eviction in &evicted {
+            state.jobs.remove(&eviction.job_id);
+            emit_artifact_eviction(eviction);
+        }
+    }
+}
+
+/// Remove the directories of `jobs` (oldest first) until the remaining ones fit `retention`,
+/// returning what was removed.
+fn evict_oldest(
+    root: &Path,
+    jobs: &[Uuid],
+    retention: ArtifactRetention,
+) -> Vec<ArtifactEvictionTelemetry> {
+    let sizes: Vec<_> = jobs
+        .iter()
+        .map(|job_id| {
+            let dir = root.join(job_id.to_string());
+            (*job_id, directory_size(&dir).unwrap_or(0))
+        })
+        .collect();
+    let mut remaining_jobs = sizes.len();
+    let mut remaining_bytes: u64 = sizes.iter().map(|(_, bytes)| bytes).sum();
+    let mut evicted = Vec::new();
+    for (job_id, bytes) in sizes {
+        let Some(limit) = retention.exceeded(remaining_jobs, remaining_bytes) else {
+            break;
+        };
+        if remove_job_dir(root, &job_id).is_none() {
+            continue;
+        }
+        remaining_jobs -= 1;
+        remaining_bytes -= bytes;
+        evicted.push(ArtifactEvictionTelemetry {
+            job_id,
+            bytes,
+            limit,
+        });
     }
+    evicted
 }
 
+/// Delete `<root>/<job_id>/`, returning the bytes it took, or `None` after logging a failure.
+/// A directory that is already gone counts as removed.
+fn remove_job_dir(root: &Path, job_id: &Uuid) -> Option<u64> {
+    let dir = root.join(job_id.to_string());
+    let bytes = directory_size(&dir).unwrap_or(0);
+    match fs::remove_dir_all(&dir) {
+        Ok(()) => Some(bytes),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Some(0),
+        Err(err) => {
+            warn!(
+                target: "rmcp_sample::visionos",
+                path = %dir.display(),
+                error = %err,
+                "Failed to remove artifact directory"
+            );
+            None
+        }
+    }
+}
+
+/// Keep records that are still inside the metadata retention window.
+fn retain_recent(
+    records: Vec<BuildJobRecord>,
+    window: Duration,
+    now: DateTime<Utc>,
+) -> HashMap<Uuid, BuildJobRecord> {
+    records
+        .into_iter()
+        .filter(|record| now - record.finished_at <= window)
+        .map(|record| (record.job_id, record))
+        .collect()
+}
+
+/// Absolute, so the root stays put if the working directory changes after startup.
 fn resolve_artifact_root() -> PathBuf {
-    let preferred = PathBuf::from(ARTIFACT_ROOT);
+    let preferred =
+        std::path::absolute(ARTIFACT_ROOT).unwrap_or_else(|_| PathBuf::from(ARTIFACT_ROOT));
     let fallback = std::env::temp_dir().join(ARTIFACT_FALLBACK_ROOT);
     resolve_artifact_root_with(&preferred, &fallback)
 }
@@ -272,6 +859,227 @@ mod tests {
 
     use super::*;
 
+    #[tokio::test]
+    async fn records_are_reloaded_by_a_new_store() {
+        let temp = tempdir().expect("temporary directory");
+        let job_id = Uuid::new_v4();
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+        store
+            .record_success(
+                job_id,
+                ArtifactKind::Archive,
+                "VisionApp".into(),
+                ArtifactFile {
+                    path: temp.path().join(job_id.to_string()).join("archive.zip"),
+                    sha256: "abc123".into(),
+                    cache_key: None,
+                    ttl_secs: None,
+                },
+                "** ARCHIVE SUCCEEDED **".into(),
+                Utc::now(),
+            )
+            .await
+            .expect("record success");
+        drop(store);
+
+        let restarted = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+        let record = restarted
+            .fetch_record(&job_id)
+            .await
+            .expect("record survives restart");
+        assert_eq!(record.status, BuildJobStatus::Succeeded);
+        assert_eq!(record.artifact_kind, ArtifactKind::Archive);
+        assert_eq!(record.artifact_sha256.as_deref(), Some("abc123"));
+    }
+
+    #[tokio::test]
+    async fn latest_resolves_to_newest_job_for_scheme() {
+        let temp = tempdir().expect("temporary directory");
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+        let older = Uuid::new_v4();
+        let newer = Uuid::new_v4();
+        let other_scheme = Uuid::new_v4();
+        for (job_id, scheme, age) in [
+            (older, "VisionApp", 30),
+            (newer, "VisionApp", 20),
+            (other_scheme, "Widgets", 10),
+        ] {
+            store
+                .record_success(
+                    job_id,
+                    ArtifactKind::Build,
+                    scheme.into(),
+                    ArtifactFile {
+                        path: temp.path().join(job_id.to_string()).join("build.zip"),
+                        sha256: "abc123".into(),
+                        cache_key: None,
+                        ttl_secs: None,
+                    },
+                    String::new(),
+                    Utc::now() - Duration::seconds(age),
+                )
+                .await
+                .expect("record success");
+        }
+
+        let latest = store
+            .resolve_job_id("latest", None)
+            .await
+            .expect("latest job");
+        let latest_for_scheme = store
+            .resolve_job_id(" LATEST ", Some("VisionApp"))
+            .await
+            .expect("latest VisionApp job");
+        let missing = store
+            .resolve_job_id("latest", Some("Unknown"))
+            .await
+            .expect_err("no job for scheme");
+
+        assert_eq!(latest, other_scheme);
+        assert_eq!(latest_for_scheme, newer);
+        assert!(matches!(
+            missing,
+            crate::tools::visionos::artifacts::FetchBuildOutputError::NoRecentJob { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn record_ttl_overrides_the_store_ttl() {
+        let temp = tempdir().expect("temporary directory");
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+        let short = Uuid::new_v4();
+        let default = Uuid::new_v4();
+        for (job_id, ttl_secs) in [(short, Some(60)), (default, None)] {
+            store
+                .record_success(
+                    job_id,
+                    ArtifactKind::Build,
+                    "VisionApp".into(),
+                    ArtifactFile {
+                        path: temp.path().join(job_id.to_string()).join("artifact.zip"),
+                        sha256: "abc123".into(),
+                        cache_key: None,
+                        ttl_secs,
+                    },
+                    String::new(),
+                    Utc::now() - Duration::seconds(120),
+                )
+                .await
+                .expect("record success");
+        }
+
+        assert!(matches!(
+            store.fetch_record(&short).await,
+            Err(crate::tools::visionos::artifacts::FetchBuildOutputError::ArtifactExpired { .. })
+        ));
+        let record = store
+            .fetch_record(&default)
+            .await
+            .expect("store TTL still applies");
+        assert!(store.ttl_seconds_remaining(&record) > 400);
+    }
+
+    #[tokio::test]
+    async fn cleanup_evicts_oldest_jobs_over_max_jobs() {
+        let temp = tempdir().expect("temporary directory");
+        let store = VisionOsArtifactStore::from_parts(
+            Arc::new(LocalDirBackend::new(temp.path().to_path_buf())),
+            600,
+            0,
+            ArtifactRetention {
+                max_jobs: 2,
+                max_total_bytes: 0,
+            },
+            None,
+        );
+        let mut job_ids = Vec::new();
+        for age in [40, 30, 20, 10] {
+            let job_id = Uuid::new_v4();
+            let job_dir = temp.path().join(job_id.to_string());
+            fs::create_dir_all(&job_dir).expect("job dir");
+            fs::write(job_dir.join("artifact.zip"), vec![0u8; 1_000]).expect("write artifact");
+            store
+                .record_failure(
+                    job_id,
+                    ArtifactKind::Build,
+                    String::new(),
+                    None,
+                    Utc::now() - Duration::seconds(age),
+                )
+                .await
+                .expect("record failure");
+            job_ids.push(job_id);
+        }
+
+        let newest = store
+            .fetch_record(&job_ids[3])
+            .await
+            .expect("newest job is kept");
+
+        assert_eq!(newest.job_id, job_ids[3]);
+        for evicted in &job_ids[..2] {
+            assert!(!temp.path().join(evicted.to_string()).exists());
+            assert!(matches!(
+                store.fetch_record(evicted).await,
+                Err(crate::tools::visionos::artifacts::FetchBuildOutputError::JobNotFound { .. })
+            ));
+        }
+        assert!(temp.path().join(job_ids[2].to_string()).exists());
+        assert_eq!(store.usage().await.jobs, 2);
+    }
+
+    #[test]
+    fn eviction_frees_the_oldest_jobs_over_max_total_bytes() {
+        let temp = tempdir().expect("temporary directory");
+        let jobs: Vec<_> = (0..3).map(|_| Uuid::new_v4()).collect();
+        for job_id in &jobs {
+            let job_dir = temp.path().join(job_id.to_string());
+            fs::create_dir_all(&job_dir).expect("job dir");
+            fs::write(job_dir.join("artifact.zip"), vec![0u8; 1_000]).expect("write artifact");
+        }
+
+        let evicted = evict_oldest(
+            temp.path(),
+            &jobs,
+            ArtifactRetention {
+                max_jobs: 0,
+                max_total_bytes: 1_500,
+            },
+        );
+
+        assert_eq!(evicted.len(), 2);
+        assert_eq!(evicted[0].job_id, jobs[0]);
+        assert_eq!(evicted[1].job_id, jobs[1]);
+        assert!(evicted
+            .iter()
+            .all(|eviction| eviction.limit == "max_total_bytes" && eviction.bytes >= 1_000));
+        assert!(temp.path().join(jobs[2].to_string()).exists());
+    }
+
+    #[test]
+    fn expired_records_are_not_reloaded() {
+        let job_id = Uuid::new_v4();
+        let record = BuildJobRecord {
+            job_id,
+            status: BuildJobStatus::Failed,
+            artifact_kind: ArtifactKind::Build,
+            scheme: None,
+            artifact_zip: None,
+            artifact_uri: None,
+            artifact_sha256: None,
+            log_excerpt: String::new(),
+            finished_at: Utc::now() - Duration::hours(2),
+            failure_context: None,
+            cache_key: None,
+            ttl_secs: None,
+            log_path: None,
+            attempts: Vec::new(),
+        };
+
+        let jobs = retain_recent(vec![record], Duration::seconds(660), Utc::now());
+        assert!(jobs.is_empty());
+    }
+
     #[test]
     fn resolve_prefers_target_when_writable() {
         let temp = tempdir().expect("temporary directory");
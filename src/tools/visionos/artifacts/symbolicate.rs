@@ -0,0 +1,500 @@
+//! `symbolicate_crash`: resolves crash log frames with `atos` against the dSYMs a job kept.
+//!
+//! Accepts the legacy text format (`3   VisionApp   0x0000000100a1c2d4 0x100a14000 + 33492`) and
+//! the JSON `.ips` format written by visionOS 1 and later.
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    lib::{
+        errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+        postprocess,
+    },
+    server::config::VisionOsConfig,
+};
+
+use super::{
+    fetch_error_to_error_data, BuildJobStatus, FetchBuildOutputError, VisionOsArtifactStore,
+};
+
+/// Largest crash log accepted, in bytes.
+pub const MAX_CRASH_LOG_BYTES: usize = 1024 * 1024;
+/// Architecture assumed when neither the request nor the crash log names one.
+const DEFAULT_ARCH: &str = "arm64";
+
+const CRASH_LOG_TOO_LARGE_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "crash_log_too_large",
+    "crash_log exceeds the size limit",
+    "Pass only the crashed thread and the Binary Images section, or the .ips file, under 1 MiB.",
+);
+const INVALID_ARCH_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "invalid_request",
+    "arch is not a valid architecture name",
+    "Use an architecture such as arm64 or x86_64, or omit arch.",
+);
+const DSYMS_NOT_FOUND_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "dsyms_not_found",
+    "The job kept no dSYM bundles",
+    "Rebuild with collect_dsyms=true (or strip_symbols=true) and DEBUG_INFORMATION_FORMAT=dwarf-with-dsym.",
+);
+const SYMBOLICATION_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "symbolication_failed",
+    "atos failed to read the job's dSYMs",
+    "Check visionos.xcrun_path and that arch matches the crashed device, then retry.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&CRASH_LOG_TOO_LARGE_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&INVALID_ARCH_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&DSYMS_NOT_FOUND_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&SYMBOLICATION_FAILED_ERROR, true, SandboxState::NoViolation),
+];
+
+/// Input for `symbolicate_crash`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SymbolicateCrashRequest {
+    /// Job UUID, or `"latest"`, whose dSYMs match the crashed build.
+    pub job_id: String,
+    /// Restrict `"latest"` to jobs built from this scheme.
+    #[serde(default)]
+    pub scheme: Option<String>,
+    /// Crash report text: the legacy format or a `.ips` file.
+    pub crash_log: String,
+    /// Architecture of the crashed process. Defaults to the one listed in the crash log, then
+    /// `arm64`.
+    #[serde(default)]
+    pub arch: Option<String>,
+}
+
+/// Frame of a binary the job has a dSYM for.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct SymbolicatedFrame {
+    pub binary: String,
+    /// Hex address from the crash log.
+    pub address: String,
+    /// `atos` output such as `ContentView.body.getter (in VisionApp) (ContentView.swift:12)`;
+    /// `None` when the dSYM has no symbol for the address.
+    pub symbol: Option<String>,
+}
+
+/// Response from `symbolicate_crash`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SymbolicateCrashResponse {
+    pub job_id: String,
+    /// The crash log with resolved frames rewritten. `.ips` input is rendered as a text
+    /// backtrace per thread.
+    pub symbolicated_log: String,
+    pub frames: Vec<SymbolicatedFrame>,
+    pub resolved_frames: usize,
+    /// dSYM bundles searched.
+    pub dsyms: Vec<String>,
+}
+
+/// Frame parsed from a crash log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CrashFrame {
+    binary: String,
+    address: u64,
+    load_address: u64,
+    arch: Option<String>,
+}
+
+/// Crash log split into frames plus what is needed to print it again.
+#[derive(Debug)]
+enum ParsedCrashLog {
+    /// Legacy text; `frame_lines[i]` is the line index of `frames[i]`.
+    Text {
+        lines: Vec<String>,
+        frame_lines: Vec<usize>,
+    },
+    /// `.ips`; `threads[t]` lists indexes into `frames` in backtrace order.
+    Ips { threads: Vec<(String, Vec<usize>)> },
+}
+
+/// Resolve the frames of `request.crash_log` with the dSYMs of a succeeded job.
+pub async fn symbolicate_crash(
+    store: &VisionOsArtifactStore,
+    config: &VisionOsConfig,
+    request: SymbolicateCrashRequest,
+) -> Result<SymbolicateCrashResponse, ErrorData> {
+    if request.crash_log.len() > MAX_CRASH_LOG_BYTES {
+        return Err(build_error_data(
+            &CRASH_LOG_TOO_LARGE_ERROR,
+            json!({ "bytes": request.crash_log.len(), "limit": MAX_CRASH_LOG_BYTES }),
+            false,
+        ));
+    }
+    if let Some(arch) = &request.arch {
+        if arch.is_empty() || !arch.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(build_error_data(
+                &INVALID_ARCH_ERROR,
+                json!({ "arch": arch }),
+                false,
+            ));
+        }
+    }
+    let job_id = store
+        .resolve_job_id(&request.job_id, request.scheme.as_deref())
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    let record = store
+        .fetch_record(&job_id)
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    let job_dir = match (record.status, record.artifact_zip.as_ref()) {
+        (BuildJobStatus::Succeeded, Some(path)) => path.parent().map(Path::to_path_buf),
+        (status @ (BuildJobStatus::CancelledQueued | BuildJobStatus::CancelledRunning), _) => {
+            return Err(fetch_error_to_error_data(
+                FetchBuildOutputError::JobCancelled { job_id, status },
+            ))
+        }
+        _ => {
+            return Err(fetch_error_to_error_data(
+                FetchBuildOutputError::BuildFailedNoArtifact { job_id },
+            ))
+        }
+    };
+    let dsyms = job_dir
+        .as_deref()
+        .map(postprocess::job_dsyms)
+        .unwrap_or_default();
+    let dwarf: BTreeMap<String, PathBuf> = dsyms
+        .iter()
+        .flat_map(|dsym| postprocess::dwarf_files(dsym))
+        .collect();
+    if dwarf.is_empty() {
+        return Err(build_error_data(
+            &DSYMS_NOT_FOUND_ERROR,
+            json!({ "job_id": job_id.to_string() }),
+            false,
+        ));
+    }
+
+    let (frames, parsed) = parse_crash_log(&request.crash_log);
+    let mut symbols: Vec<Option<String>> = vec![None; frames.len()];
+    // One atos run per loaded image and architecture.
+    let mut groups: BTreeMap<(&str, u64, &str), Vec<usize>> = BTreeMap::new();
+    for (index, frame) in frames.iter().enumerate() {
+        if !dwarf.contains_key(&frame.binary) {
+            continue;
+        }
+        let arch = request
+            .arch
+            .as_deref()
+            .or(frame.arch.as_deref())
+            .unwrap_or(DEFAULT_ARCH);
+        groups
+            .entry((frame.binary.as_str(), frame.load_address, arch))
+            .or_default()
+            .push(index);
+    }
+    for ((binary, load_address, arch), indexes) in &groups {
+        let addresses: Vec<u64> = indexes.iter().map(|&i| frames[i].address).collect();
+        let resolved = postprocess::atos(
+            &config.xcrun_path,
+            &config.xcode_path,
+            &dwarf[*binary],
+            arch,
+            *load_address,
+            &addresses,
+        )
+        .await
+        .map_err(|err| {
+            build_error_data(
+                &SYMBOLICATION_FAILED_ERROR,
+                json!({ "binary": binary, "details": err.to_string() }),
+                true,
+            )
+        })?;
+        for (&index, symbol) in indexes.iter().zip(resolved) {
+            symbols[index] = symbol;
+        }
+    }
+
+    let symbolicated_log = render(&request.crash_log, &frames, &parsed, &symbols);
+    let frames_out: Vec<SymbolicatedFrame> = frames
+        .iter()
+        .enumerate()
+        .filter(|(_, frame)| dwarf.contains_key(&frame.binary))
+        .map(|(index, frame)| SymbolicatedFrame {
+            binary: frame.binary.clone(),
+            address: format!("{:#x}", frame.address),
+            symbol: symbols[index].clone(),
+        })
+        .collect();
+    Ok(SymbolicateCrashResponse {
+        job_id: job_id.to_string(),
+        symbolicated_log,
+        resolved_frames: frames_out
+            .iter()
+            .filter(|frame| frame.symbol.is_some())
+            .count(),
+        frames: frames_out,
+        dsyms: dsyms
+            .iter()
+            .map(|dsym| dsym.to_string_lossy().to_string())
+            .collect(),
+    })
+}
+
+/// Parse `.ips` when the body after the header line is JSON, otherwise the text format.
+fn parse_crash_log(crash_log: &str) -> (Vec<CrashFrame>, ParsedCrashLog) {
+    if let Some(parsed) = parse_ips(crash_log) {
+        return parsed;
+    }
+    parse_text(crash_log)
+}
+
+fn parse_text(crash_log: &str) -> (Vec<CrashFrame>, ParsedCrashLog) {
+    let frame_pattern =
+        Regex::new(r"^\s*\d+\s+(.+?)\s+0x([0-9a-fA-F]+)\s+0x([0-9a-fA-F]+)\s+\+\s+\d+")
+            .expect("frame pattern is valid");
+    let image_pattern =
+        Regex::new(r"^\s*0x[0-9a-fA-F]+\s+-\s+\S+\s+\+?(.+?)\s+(arm64e?|arm64_32|x86_64)\s+<")
+            .expect("image pattern is valid");
+    let archs: BTreeMap<&str, &str> = crash_log
+        .lines()
+        .filter_map(|line| image_pattern.captures(line))
+        .map(|caps| {
+            let name = caps.get(1).map_or("", |m| m.as_str());
+            let arch = caps.get(2).map_or("", |m| m.as_str());
+            (name, arch)
+        })
+        .collect();
+
+    let lines: Vec<String> = crash_log.lines().map(str::to_string).collect();
+    let mut frames = Vec::new();
+    let mut frame_lines = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let Some(caps) = frame_pattern.captures(line) else {
+            continue;
+        };
+        let (Ok(address), Ok(load_address)) = (
+            u64::from_str_radix(&caps[2], 16),
+            u64::from_str_radix(&caps[3], 16),
+        ) else {
+            continue;
+        };
+        let binary = caps[1].to_string();
+        frames.push(CrashFrame {
+            arch: archs.get(binary.as_str()).map(|arch| arch.to_string()),
+            binary,
+            address,
+            load_address,
+        });
+        frame_lines.push(index);
+    }
+    (frames, ParsedCrashLog::Text { lines, frame_lines })
+}
+
+fn parse_ips(crash_log: &str) -> Option<(Vec<CrashFrame>, ParsedCrashLog)> {
+    let (header, body) = crash_log.trim_start().split_once('\n')?;
+    serde_json::from_str::<Value>(header).ok()?;
+    let body: Value = serde_json::from_str(body).ok()?;
+    let images = body.get("usedImages")?.as_array()?;
+    let mut frames = Vec::new();
+    let mut threads = Vec::new();
+    for (thread_index, thread) in body.get("threads")?.as_array()?.iter().enumerate() {
+        let crashed = thread.get("triggered").and_then(Value::as_bool) == Some(true);
+        let title = format!(
+            "Thread {thread_index}{}:",
+            if crashed { " Crashed" } else { "" }
+        );
+        let mut indexes = Vec::new();
+        for frame in thread
+            .get("frames")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let image = frame
+                .get("imageIndex")
+                .and_then(Value::as_u64)
+                .and_then(|index| images.get(usize::try_from(index).ok()?));
+            let offset = frame.get("imageOffset").and_then(Value::as_u64);
+            let (Some(image), Some(offset)) = (image, offset) else {
+                continue;
+            };
+            let Some(base) = image.get("base").and_then(Value::as_u64) else {
+                continue;
+            };
+            indexes.push(frames.len());
+            frames.push(CrashFrame {
+                binary: image
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or("???")
+                    .to_string(),
+                address: base.saturating_add(offset),
+                load_address: base,
+                arch: image
+                    .get("arch")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            });
+        }
+        threads.push((title, indexes));
+    }
+    Some((frames, ParsedCrashLog::Ips { threads }))
+}
+
+/// Print the crash log again with resolved symbols in place of `0x<load> + <offset>`.
+fn render(
+    crash_log: &str,
+    frames: &[CrashFrame],
+    parsed: &ParsedCrashLog,
+    symbols: &[Option<String>],
+) -> String {
+    match parsed {
+        ParsedCrashLog::Text { lines, frame_lines } => {
+            let mut lines = lines.clone();
+            for (index, &line_index) in frame_lines.iter().enumerate() {
+                let Some(symbol) = &symbols[index] else {
+                    continue;
+                };
+                let line = &lines[line_index];
+                let address = format!("{:x}", frames[index].address);
+                // Keep everything up to and including the frame address.
+                if let Some(end) = line
+                    .to_ascii_lowercase()
+                    .find(&address)
+                    .map(|start| start + address.len())
+                {
+                    lines[line_index] = format!("{} {symbol}", &line[..end]);
+                }
+            }
+            let mut out = lines.join("\n");
+            if crash_log.ends_with('\n') {
+                out.push('\n');
+            }
+            out
+        }
+        ParsedCrashLog::Ips { threads } => {
+            let mut out = String::new();
+            for (title, indexes) in threads {
+                out.push_str(title);
+                out.push('\n');
+                for (position, &index) in indexes.iter().enumerate() {
+                    let frame = &frames[index];
+                    let location = symbols[index].clone().unwrap_or_else(|| {
+                        format!(
+                            "{:#x} + {}",
+                            frame.load_address,
+                            frame.address - frame.load_address
+                        )
+                    });
+                    out.push_str(&format!(
+                        "{position:<4}{:<30}\t{:#018x} {location}\n",
+                        frame.binary, frame.address
+                    ));
+                }
+                out.push('\n');
+            }
+            out
+        }
+    }
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    retryable: bool,
+) -> ErrorData {
+    desc.builder()
+        .details(details)
+        .sandbox_state(SandboxState::NoViolation)
+        .retryable(retryable)
+        .build()
+        .expect("descriptor is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT_LOG: &str = "\
+Thread 0 Crashed:
+0   libswiftCore.dylib            \t0x00000001a2b3c4d4 0x1a2b00000 + 242900
+1   VisionApp                     \t0x0000000100a1c2d4 0x100a14000 + 33492
+2   VisionApp                     \t0x0000000100a1c000 main + 12
+
+Binary Images:
+       0x100a14000 -        0x100a1ffff VisionApp arm64  <0123456789abcdef0123456789abcdef> /private/var/containers/Bundle/Application/VisionApp.app/VisionApp
+";
+
+    #[test]
+    fn text_frames_keep_image_and_architecture() {
+        let (frames, parsed) = parse_crash_log(TEXT_LOG);
+
+        assert_eq!(frames.len(), 2, "already symbolicated frames are skipped");
+        assert_eq!(
+            frames[1],
+            CrashFrame {
+                binary: "VisionApp".into(),
+                address: 0x100a1c2d4,
+                load_address: 0x100a14000,
+                arch: Some("arm64".into()),
+            }
+        );
+        let rendered = render(
+            TEXT_LOG,
+            &frames,
+            &parsed,
+            &[
+                None,
+                Some("ContentView.body.getter (ContentView.swift:12)".into()),
+            ],
+        );
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert!(lines[1].ends_with("0x1a2b00000 + 242900"));
+        assert!(
+            lines[2].ends_with("0x0000000100a1c2d4 ContentView.body.getter (ContentView.swift:12)")
+        );
+        assert!(rendered.ends_with('\n'));
+    }
+
+    #[test]
+    fn ips_frames_add_image_offsets_to_base() {
+        let header = json!({ "app_name": "VisionApp", "bug_type": "309" });
+        let body = json!({
+            "usedImages": [
+                { "base": 4_306_583_552_u64, "name": "VisionApp", "arch": "arm64" },
+                { "base": 7_000_000_000_u64, "name": "libswiftCore.dylib", "arch": "arm64e" }
+            ],
+            "threads": [
+                { "frames": [{ "imageIndex": 1, "imageOffset": 16 }] },
+                {
+                    "triggered": true,
+                    "frames": [
+                        { "imageIndex": 0, "imageOffset": 33_492 },
+                        { "imageIndex": 9, "imageOffset": 1 }
+                    ]
+                }
+            ]
+        });
+        let crash_log = format!("{header}\n{body:#}");
+
+        let (frames, parsed) = parse_crash_log(&crash_log);
+        assert_eq!(frames.len(), 2, "frames of unknown images are skipped");
+        assert_eq!(frames[1].address, 4_306_583_552 + 33_492);
+        assert_eq!(frames[1].arch.as_deref(), Some("arm64"));
+
+        let rendered = render(
+            &crash_log,
+            &frames,
+            &parsed,
+            &[None, Some("main (main.swift:3)".into())],
+        );
+        assert!(rendered.contains("Thread 1 Crashed:\n0   VisionApp"));
+        assert!(rendered.contains("main (main.swift:3)"));
+        assert!(rendered.contains(&format!("{:#x} + 16", 7_000_000_000_u64)));
+    }
+}
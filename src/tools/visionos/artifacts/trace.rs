@@ -0,0 +1,367 @@
+//! Per-job trace recorded by the server and bundled by `export_job_trace` for bug reports.
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    lib::{
+        errors::{ArtifactError, ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+        fs::{self as artifact_fs, ZipOptions},
+        telemetry::{redact_json, redact_text},
+        xcresult::XcresultDiagnostic,
+    },
+    server::config::VisionOsConfig,
+    tools::visionos::build::{plan_build, PhaseTiming, VisionOsBuildRequest},
+};
+
+use super::{
+    backend::JOB_RECORD_FILE, fetch_error_to_error_data, ArtifactKind, BuildJobStatus,
+    VisionOsArtifactStore,
+};
+
+/// Full `xcodebuild` stdout and stderr of a job, written next to the artifact.
+pub const BUILD_LOG_FILE: &str = "build.log";
+/// Trace written once the job finishes.
+pub const JOB_TRACE_FILE: &str = "trace.json";
+const TRACE_BUNDLE_NAME: &str = "job-trace";
+const TRACE_FORMAT_VERSION: u32 = 1;
+
+const TRACE_NOT_FOUND_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "trace_not_found",
+    "No trace was recorded for this job",
+    "Traces are recorded for build and archive jobs; rerun the job and export again.",
+);
+const TRACE_EXPORT_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "trace_export_failed",
+    "Failed to write the job trace bundle",
+    "Check free space and permissions of the artifact directory, then retry.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&TRACE_NOT_FOUND_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&TRACE_EXPORT_FAILED_ERROR, true, SandboxState::NoViolation),
+];
+
+/// Bundle layout produced by `export_job_trace`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceFormat {
+    /// One JSON document with the trace, the job record, and the log inlined.
+    Json,
+    /// `trace.json`, `job.json`, and `build.log` as separate zip entries.
+    #[default]
+    Zip,
+}
+
+/// Input for `export_job_trace`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportJobTraceRequest {
+    /// Job UUID, or `"latest"` for the most recent job.
+    pub job_id: String,
+    /// Restrict `"latest"` to jobs built from this scheme.
+    #[serde(default)]
+    pub scheme: Option<String>,
+    #[serde(default)]
+    pub format: TraceFormat,
+}
+
+/// Response from `export_job_trace`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportJobTraceResponse {
+    pub job_id: String,
+    /// Status of the traced job.
+    pub job_status: String,
+    pub format: TraceFormat,
+    pub path: String,
+    pub sha256: String,
+    pub size_bytes: u64,
+    pub notes: Vec<String>,
+}
+
+/// Toolchain and host details that shape a build but are not part of the request.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceEnvironment {
+    pub server_version: &'static str,
+    pub host_os: &'static str,
+    pub host_arch: &'static str,
+    pub xcode_path: PathBuf,
+    pub xcodebuild_path: PathBuf,
+    pub locale: String,
+    /// Build settings the server appended to the `xcodebuild` invocation.
+    pub build_settings: Vec<String>,
+    pub compilation_cache: bool,
+    /// Environment `xcodebuild` runs with, masked like the `dry_run` plan.
+    pub env: BTreeMap<String, String>,
+}
+
+/// Build phase with the time spent in it.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TracePhase {
+    pub phase: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: i64,
+}
+
+/// Everything the server knows about a finished job, persisted as [`JOB_TRACE_FILE`].
+#[derive(Debug, Clone, Serialize)]
+pub struct JobTrace {
+    pub format_version: u32,
+    pub job_id: Uuid,
+    pub artifact_kind: ArtifactKind,
+    pub status: BuildJobStatus,
+    pub request: VisionOsBuildRequest,
+    pub environment: TraceEnvironment,
+    pub phases: Vec<TracePhase>,
+    pub diagnostics: Vec<XcresultDiagnostic>,
+    /// Error returned to the client; `None` for succeeded jobs.
+    pub error: Option<ErrorData>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+impl JobTrace {
+    /// Start a trace for `request` after config defaults were applied.
+    pub fn new(
+        job_id: Uuid,
+        artifact_kind: ArtifactKind,
+        request: &VisionOsBuildRequest,
+        config: &VisionOsConfig,
+        artifact_root: &Path,
+    ) -> Self {
+        let now = Utc::now();
+        let env = plan_build(request, config, job_id, artifact_root, artifact_kind)
+            .plan
+            .map(|plan| plan.env)
+            .unwrap_or_default();
+        Self {
+            format_version: TRACE_FORMAT_VERSION,
+            job_id,
+            artifact_kind,
+            status: BuildJobStatus::Failed,
+            request: request.clone(),
+            environment: TraceEnvironment {
+                server_version: env!("CARGO_PKG_VERSION"),
+                host_os: std::env::consts::OS,
+                host_arch: std::env::consts::ARCH,
+                xcode_path: config.xcode_path.clone(),
+                xcodebuild_path: config.xcodebuild_path.clone(),
+                locale: config.locale.clone(),
+                build_settings: request.signing_build_settings(),
+                compilation_cache: config.compilation_cache.is_some(),
+                env,
+            },
+            phases: Vec::new(),
+            diagnostics: Vec::new(),
+            error: None,
+            started_at: now,
+            finished_at: now,
+        }
+    }
+
+    /// Close the trace with the job's final status, phase changes, and returned error.
+    pub fn finish(
+        &mut self,
+        status: BuildJobStatus,
+        phases: &[PhaseTiming],
+        error: Option<&ErrorData>,
+    ) {
+        self.finished_at = Utc::now();
+        self.status = status;
+        self.phases = phase_durations(phases, self.finished_at);
+        self.error = error.cloned();
+    }
+}
+
+/// Each phase lasts until the next one starts; the last one until `finished_at`.
+fn phase_durations(phases: &[PhaseTiming], finished_at: DateTime<Utc>) -> Vec<TracePhase> {
+    phases
+        .iter()
+        .enumerate()
+        .map(|(index, timing)| {
+            let ended_at = phases
+                .get(index + 1)
+                .map_or(finished_at, |next| next.started_at);
+            TracePhase {
+                phase: timing.phase.clone(),
+                started_at: timing.started_at,
+                duration_ms: (ended_at - timing.started_at).num_milliseconds().max(0),
+            }
+        })
+        .collect()
+}
+
+/// Write `trace`, masked with the active redaction policy, into the job directory under
+/// `artifact_root`.
+pub fn save_job_trace(artifact_root: &Path, trace: &JobTrace) -> Result<PathBuf, ArtifactError> {
+    let job_dir = artifact_fs::ensure_job_dir(artifact_root, &trace.job_id)?;
+    let path = job_dir.join(JOB_TRACE_FILE);
+    let encode_failed = |err: serde_json::Error| ArtifactError::Io {
+        path: path.clone(),
+        source: std::io::Error::other(err),
+    };
+    let mut value = serde_json::to_value(trace).map_err(encode_failed)?;
+    redact_json(&mut value);
+    let encoded = serde_json::to_vec_pretty(&value).map_err(encode_failed)?;
+    fs::write(&path, encoded).map_err(|source| ArtifactError::Io {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(path)
+}
+
+/// Bundle the trace, job record, and full log of a job into one file inside its job directory,
+/// masked with the active redaction policy.
+pub async fn export_job_trace(
+    store: &VisionOsArtifactStore,
+    request: ExportJobTraceRequest,
+) -> Result<ExportJobTraceResponse, ErrorData> {
+    let job_id = store
+        .resolve_job_id(&request.job_id, request.scheme.as_deref())
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    let record = store
+        .fetch_record(&job_id)
+        .await
+        .map_err(fetch_error_to_error_data)?;
+
+    let job_dir = store.root_dir().join(job_id.to_string());
+    let trace_path = job_dir.join(JOB_TRACE_FILE);
+    let mut trace: Value = fs::read(&trace_path)
+        .ok()
+        .and_then(|raw| serde_json::from_slice(&raw).ok())
+        .ok_or_else(|| {
+            build_error_data(
+                &TRACE_NOT_FOUND_ERROR,
+                json!({ "job_id": job_id.to_string() }),
+                SandboxState::NoViolation,
+                false,
+            )
+        })?;
+
+    let mut notes = Vec::new();
+    let log = match fs::read(job_dir.join(BUILD_LOG_FILE)) {
+        Ok(raw) => redact_text(&String::from_utf8_lossy(&raw)),
+        Err(_) => {
+            notes.push(
+                "xcodebuild produced no full log for this job; the stored log excerpt is included instead"
+                    .into(),
+            );
+            redact_text(&record.log_excerpt)
+        }
+    };
+    let mut record = serde_json::to_value(&record).map_err(|err| export_failed(&job_dir, err))?;
+    // Traces written before a stricter policy was configured are masked again on the way out.
+    redact_json(&mut trace);
+    redact_json(&mut record);
+
+    let path = match request.format {
+        TraceFormat::Json => {
+            let path = job_dir.join(format!("{TRACE_BUNDLE_NAME}.json"));
+            let bundle = json!({ "trace": trace, "record": record, "log": log });
+            let encoded =
+                serde_json::to_vec_pretty(&bundle).map_err(|err| export_failed(&path, err))?;
+            fs::write(&path, encoded).map_err(|err| export_failed(&path, err))?;
+            path
+        }
+        TraceFormat::Zip => {
+            let path = job_dir.join(format!("{TRACE_BUNDLE_NAME}.zip"));
+            let staging = job_dir.join(TRACE_BUNDLE_NAME);
+            write_bundle_dir(&staging, &trace, &record, &log)
+                .map_err(|err| export_failed(&staging, err))?;
+            let zipped = artifact_fs::zip_directory(&staging, &path, ZipOptions::default());
+            let _ = fs::remove_dir_all(&staging);
+            zipped.map_err(|err| export_failed(&path, err))?;
+            path
+        }
+    };
+    let sha256 = artifact_fs::compute_sha256(&path).map_err(|err| export_failed(&path, err))?;
+    let size_bytes = fs::metadata(&path)
+        .map(|metadata| metadata.len())
+        .map_err(|err| export_failed(&path, err))?;
+
+    Ok(ExportJobTraceResponse {
+        job_id: job_id.to_string(),
+        job_status: trace
+            .get("status")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        format: request.format,
+        path: path.to_string_lossy().to_string(),
+        sha256,
+        size_bytes,
+        notes,
+    })
+}
+
+fn write_bundle_dir(dir: &Path, trace: &Value, record: &Value, log: &str) -> std::io::Result<()> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    fs::create_dir_all(dir)?;
+    let encode = |value: &Value| serde_json::to_vec_pretty(value).map_err(std::io::Error::other);
+    fs::write(dir.join(JOB_TRACE_FILE), encode(trace)?)?;
+    fs::write(dir.join(JOB_RECORD_FILE), encode(record)?)?;
+    fs::write(dir.join(BUILD_LOG_FILE), log)
+}
+
+fn export_failed(path: &Path, err: impl std::fmt::Display) -> ErrorData {
+    build_error_data(
+        &TRACE_EXPORT_FAILED_ERROR,
+        json!({ "path": path.to_string_lossy(), "details": err.to_string() }),
+        SandboxState::NoViolation,
+        true,
+    )
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    sandbox_state: SandboxState,
+    retryable: bool,
+) -> ErrorData {
+    desc.builder()
+        .details(details)
+        .sandbox_state(sandbox_state)
+        .retryable(retryable)
+        .build()
+        .expect("descriptor is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn phase_durations_run_until_the_next_phase_or_the_end() {
+        let start = Utc::now();
+        let phases = [
+            PhaseTiming {
+                phase: "compile".into(),
+                started_at: start,
+            },
+            PhaseTiming {
+                phase: "link".into(),
+                started_at: start + Duration::milliseconds(1500),
+            },
+        ];
+
+        let durations = phase_durations(&phases, start + Duration::milliseconds(1750));
+
+        assert_eq!(durations.len(), 2);
+        assert_eq!(durations[0].phase, "compile");
+        assert_eq!(durations[0].duration_ms, 1500);
+        assert_eq!(durations[1].duration_ms, 250);
+    }
+}
@@ -0,0 +1,227 @@
+//! Build result cache keyed by the source tree and the build parameters.
+//!
+//! A successful build or archive records its key with the artifact. A later request with the
+//! same key gets that artifact back without running `xcodebuild`, for as long as the artifact
+//! is inside its TTL.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::{
+    lib::{fs as artifact_fs, xcodebuild::PACKAGE_MANIFEST},
+    server::config::VisionOsConfig,
+    tools::visionos::artifacts::{ArtifactKind, BuildJobRecord},
+};
+
+use super::{BuildVisionOsAppResponse, VisionOsBuildRequest};
+
+/// Directories left out of the hash when the project is not in a git checkout.
+const SKIPPED_DIRS: &[&str] = &[
+    ".git",
+    "target",
+    "build",
+    ".build",
+    "DerivedData",
+    "xcuserdata",
+];
+
+/// Cache key for `request`, or `None` when the source tree could not be read.
+///
+/// Hashes every file git would track under the project directory (so `.gitignore`d build
+/// output is left out) together with the parameters that change what `xcodebuild` produces.
+pub async fn build_cache_key(
+    request: &VisionOsBuildRequest,
+    config: &VisionOsConfig,
+    kind: ArtifactKind,
+) -> Option<String> {
+    let root = source_root(request);
+    let files = match git_source_files(&root).await {
+        Some(files) => files,
+        None => walk_source_files(&root),
+    };
+    let task_root = root.clone();
+    let sources = tokio::task::spawn_blocking(move || hash_sources(&task_root, files))
+        .await
+        .ok()
+        .flatten();
+    let Some(sources) = sources else {
+        warn!(
+            target: "rmcp_sample::visionos",
+            root = %root.display(),
+            "Failed to hash project sources; skipping the build cache"
+        );
+        return None;
+    };
+
+    let parameters = json!({
+        "kind": kind.as_str(),
+        "project_path": request.project_path,
+        "workspace": request.workspace,
+        "scheme": request.scheme,
+        "configuration": request.configuration().as_str(),
+        "platform": request.platform.as_str(),
+        "destination": request.destination,
+        "extra_args": request.extra_args,
+        "env_overrides": request.env_overrides,
+        "strip_symbols": request.strip_symbols,
+        "collect_dsyms": request.collect_dsyms,
+        "architectures": request.architectures,
+        "artifact_include": request.artifact_include,
+        "artifact_exclude": request.artifact_exclude,
+        "signing": request.signing_build_settings(),
+        "xcode_path": config.xcode_path,
+        "xcodebuild_path": config.xcodebuild_path,
+    });
+    let mut hasher = Sha256::new();
+    hasher.update(parameters.to_string());
+    hasher.update([0]);
+    hasher.update(sources);
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Response for a request served from `record` instead of a new build.
+pub fn cached_response(record: &BuildJobRecord, started: Instant) -> BuildVisionOsAppResponse {
+    BuildVisionOsAppResponse {
+        job_id: record.job_id.to_string(),
+        status: "succeeded",
+        artifact_kind: record.artifact_kind,
+        artifact_path: record
+            .artifact_zip
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        artifact_sha256: record.artifact_sha256.clone().unwrap_or_default(),
+        log_excerpt: record.log_excerpt.clone(),
+        log_errors: Vec::new(),
+        diagnostics: Vec::new(),
+        size: None,
+        post_process: None,
+        cache_hit: true,
+        compilation_cache: None,
+        duration_ms: started.elapsed().as_millis(),
+        packaging_duration_ms: 0,
+        attempts: Vec::new(),
+        plan: None,
+    }
+}
+
+/// Directory holding the sources: the parent of an `.xcodeproj` / `.xcworkspace` bundle or a
+/// `Package.swift` manifest, otherwise the project path itself.
+fn source_root(request: &VisionOsBuildRequest) -> PathBuf {
+    let root = request.project_root();
+    let is_bundle = matches!(
+        root.extension().and_then(|ext| ext.to_str()),
+        Some("xcodeproj") | Some("xcworkspace")
+    ) || root.ends_with(PACKAGE_MANIFEST);
+    match root.parent() {
+        Some(parent) if is_bundle => parent.to_path_buf(),
+        _ => root.to_path_buf(),
+    }
+}
+
+/// Tracked and untracked-but-not-ignored files relative to `root`; `None` outside a git checkout.
+async fn git_source_files(root: &Path) -> Option<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args([
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        output
+            .stdout
+            .split(|byte| *byte == 0)
+            .filter(|path| !path.is_empty())
+            .map(|path| PathBuf::from(String::from_utf8_lossy(path).into_owned()))
+            .collect(),
+    )
+}
+
+/// Files relative to `root`, skipping VCS metadata and common build output directories.
+fn walk_source_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                let skipped = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| SKIPPED_DIRS.contains(&name));
+                if !skipped {
+                    stack.push(path);
+                }
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                files.push(relative.to_path_buf());
+            }
+        }
+    }
+    files
+}
+
+/// Combined digest of each file's relative path and contents. Files listed but missing on disk
+/// (deleted but still tracked) are skipped.
+fn hash_sources(root: &Path, mut files: Vec<PathBuf>) -> Option<[u8; 32]> {
+    files.sort();
+    let mut hasher = Sha256::new();
+    for relative in files {
+        let path = root.join(&relative);
+        if !path.is_file() {
+            continue;
+        }
+        let digest = artifact_fs::compute_sha256(&path).ok()?;
+        hasher.update(relative.as_os_str().as_encoded_bytes());
+        hasher.update([0]);
+        hasher.update(digest);
+        hasher.update([0]);
+    }
+    Some(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn source_hash_ignores_build_output_and_tracks_edits() {
+        let temp = tempdir().expect("can create temp directory");
+        let root = temp.path();
+        fs::create_dir_all(root.join("Sources")).expect("can create sources");
+        fs::create_dir_all(root.join("DerivedData/Build")).expect("can create build output");
+        fs::write(root.join("Sources/App.swift"), "let a = 1").expect("can write source");
+        let hash = || hash_sources(root, walk_source_files(root)).expect("can hash sources");
+
+        let initial = hash();
+        fs::write(root.join("DerivedData/Build/App.o"), "object").expect("can write output");
+        assert_eq!(hash(), initial, "build output does not change the key");
+
+        fs::write(root.join("Sources/App.swift"), "let a = 2").expect("can edit source");
+        assert_ne!(hash(), initial, "source edits change the key");
+    }
+}
@@ -0,0 +1,108 @@
+//! `cancel_build_job`: stop a queued or running build.
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor};
+
+use super::{CancelOutcome, VisionOsJobQueue};
+
+const INVALID_JOB_ID_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "invalid_job_id",
+    "Invalid job_id format",
+    "Provide the UUID passed as job_id to the build request.",
+);
+const JOB_NOT_ACTIVE_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "job_not_active",
+    "The job is neither queued nor running",
+    "Only queued or running jobs can be cancelled; use fetch_build_output for finished jobs.",
+);
+const JOB_CANCELLED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "job_cancelled",
+    "The job was cancelled by cancel_build_job",
+    "Run the build again if the artifacts are still needed.",
+);
+const SHUTTING_DOWN_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "server_shutting_down",
+    "The server is draining jobs before shutdown and accepts no new ones",
+    "Retry once the MCP server has restarted.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&INVALID_JOB_ID_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&JOB_NOT_ACTIVE_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&JOB_CANCELLED_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&SHUTTING_DOWN_ERROR, true, SandboxState::NoViolation),
+];
+
+/// Input for `cancel_build_job`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CancelBuildJobRequest {
+    /// UUID supplied as `job_id` when the build was requested.
+    pub job_id: String,
+}
+
+/// Response from `cancel_build_job`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CancelBuildJobResponse {
+    pub job_id: String,
+    /// `cancelled_queued` or `cancelled_running`.
+    pub state: CancelOutcome,
+}
+
+/// Cancel a job that is still in the build queue.
+pub async fn cancel_build_job(
+    queue: &VisionOsJobQueue,
+    request: CancelBuildJobRequest,
+) -> Result<CancelBuildJobResponse, ErrorData> {
+    let job_id = Uuid::parse_str(request.job_id.trim()).map_err(|_| {
+        build_error_data(
+            &INVALID_JOB_ID_ERROR,
+            json!({ "details": request.job_id }),
+            false,
+            None,
+        )
+    })?;
+    let state = queue
+        .cancel(job_id)
+        .await
+        .ok_or_else(|| build_error_data(&JOB_NOT_ACTIVE_ERROR, json!({}), false, Some(job_id)))?;
+    Ok(CancelBuildJobResponse {
+        job_id: job_id.to_string(),
+        state,
+    })
+}
+
+/// Error returned to the original build call after its job was cancelled.
+pub fn cancelled_error_to_error_data(outcome: CancelOutcome, job_id: Uuid) -> ErrorData {
+    build_error_data(
+        &JOB_CANCELLED_ERROR,
+        json!({ "state": outcome.as_str() }),
+        false,
+        Some(job_id),
+    )
+}
+
+/// Error returned for jobs submitted after shutdown started.
+pub fn shutting_down_error_to_error_data(job_id: Uuid) -> ErrorData {
+    build_error_data(&SHUTTING_DOWN_ERROR, json!({}), true, Some(job_id))
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    retryable: bool,
+    job_id: Option<Uuid>,
+) -> ErrorData {
+    let mut builder = desc
+        .builder()
+        .details(details)
+        .sandbox_state(SandboxState::NoViolation)
+        .retryable(retryable);
+    if let Some(job_id) = job_id {
+        builder = builder.with_context_field("job_id", json!(job_id.to_string()));
+    }
+    builder.build().expect("descriptor is valid")
+}
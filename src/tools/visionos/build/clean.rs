@@ -0,0 +1,172 @@
+//! `clean_visionos_project`: `xcodebuild clean`, optionally followed by deleting the project's
+//! cached DerivedData directory.
+use std::{fs, path::Path, time::Instant};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    lib::{
+        app_size::directory_size, errors::VisionOsBuildError, visionos as visionos_helpers,
+        xcodebuild as xcodebuild_helpers,
+    },
+    server::config::VisionOsConfig,
+};
+
+use super::{
+    executor::{build_timeout, cached_derived_data_path},
+    JobMonitor, VisionOsBuildRequest,
+};
+
+pub const CLEAN_TOOL_ID: &str = "clean_visionos_project";
+
+const LOG_EXCERPT_LIMIT: usize = 2_000;
+
+/// Input for `clean_visionos_project`: the build request of the products to clean.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CleanVisionOsProjectRequest {
+    #[serde(flatten)]
+    pub build: VisionOsBuildRequest,
+    /// Also delete the project's directory under `visionos.derived_data_root`, including index
+    /// data and module caches `xcodebuild clean` keeps. Implies `use_cached_derived_data`.
+    #[serde(default)]
+    pub wipe_derived_data: bool,
+}
+
+/// Response from `clean_visionos_project`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CleanVisionOsProjectResponse {
+    pub job_id: String,
+    pub status: &'static str,
+    /// Per-project DerivedData directory that was cleaned; `None` for Xcode's default location.
+    pub derived_data_path: Option<String>,
+    /// Whether that directory was deleted.
+    pub derived_data_wiped: bool,
+    /// Bytes freed in `derived_data_path`; `None` when the products live in Xcode's default
+    /// DerivedData, which is not measured.
+    pub freed_bytes: Option<u64>,
+    pub log_excerpt: String,
+    pub duration_ms: u128,
+}
+
+/// Run `xcodebuild clean` for the request's scheme and report the space it freed.
+pub async fn run_clean(
+    request: &VisionOsBuildRequest,
+    wipe_derived_data: bool,
+    config: &VisionOsConfig,
+    monitor: &JobMonitor,
+    job_id: Uuid,
+) -> Result<CleanVisionOsProjectResponse, VisionOsBuildError> {
+    let staging = tempfile::tempdir().map_err(|err| VisionOsBuildError::ArtifactFailure {
+        message: format!("Failed to create clean staging directory: {err}"),
+    })?;
+    let derived_data_path = cached_derived_data_path(config, request);
+    let size_before = derived_data_path.as_deref().map(measure);
+
+    let signing_settings = request.signing_build_settings();
+    let command = xcodebuild_helpers::build_visionos_xcodebuild_command(
+        xcodebuild_helpers::VisionOsXcodebuildCommandConfig {
+            xcodebuild_path: &config.xcodebuild_path,
+            xcode_path: &config.xcode_path,
+            staging_dir: staging.path(),
+            result_bundle_path: None,
+            derived_data_path: derived_data_path.as_deref(),
+            compilation_cache: None,
+            locale: &config.locale,
+        },
+        xcodebuild_helpers::VisionOsXcodebuildRequest {
+            project_path: &request.project_path,
+            workspace: request.workspace.as_deref(),
+            scheme: &request.scheme,
+            configuration: request.configuration().as_str(),
+            destination: &request.destination,
+            clean: false,
+            action: xcodebuild_helpers::XcodebuildAction::Clean,
+            extra_args: &request.extra_args,
+            build_settings: &signing_settings,
+            env_overrides: &request.env_overrides,
+        },
+    );
+
+    info!(
+        target: "rmcp_sample::visionos",
+        scheme = %request.scheme,
+        job_id = %job_id,
+        wipe_derived_data,
+        "Cleaning visionOS project"
+    );
+
+    let start = Instant::now();
+    let output = monitor
+        .run_with_deadline(
+            build_timeout(config, request),
+            config.timeout_warning_percent,
+            xcodebuild_helpers::output_with_lines(command, |_| {}),
+        )
+        .await
+        .map_err(|deadline| VisionOsBuildError::Timeout {
+            duration_secs: deadline.as_secs(),
+        })?
+        .map_err(|err| VisionOsBuildError::CommandFailed {
+            exit_code: None,
+            message: err.to_string(),
+            diagnostics: Vec::new(),
+            log_errors: Vec::new(),
+        })?;
+    let log_excerpt = visionos_helpers::collect_formatted_log_excerpt(
+        &output.stdout,
+        &output.stderr,
+        request.log_format,
+        LOG_EXCERPT_LIMIT,
+    );
+    if !output.status.success() {
+        return Err(VisionOsBuildError::CommandFailed {
+            exit_code: output.status.code(),
+            message: log_excerpt,
+            diagnostics: Vec::new(),
+            log_errors: xcodebuild_helpers::collect_log_errors(
+                &output.stdout,
+                &output.stderr,
+                xcodebuild_helpers::MAX_LOG_ERRORS,
+            ),
+        });
+    }
+
+    let mut derived_data_wiped = false;
+    if let Some(path) = derived_data_path.as_deref().filter(|_| wipe_derived_data) {
+        match fs::remove_dir_all(path) {
+            Ok(()) => derived_data_wiped = true,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(VisionOsBuildError::ArtifactFailure {
+                    message: format!("Failed to delete {}: {err}", path.display()),
+                })
+            }
+        }
+    }
+    let freed_bytes = derived_data_path
+        .as_deref()
+        .zip(size_before)
+        .map(|(path, before)| before.saturating_sub(measure(path)));
+
+    Ok(CleanVisionOsProjectResponse {
+        job_id: job_id.to_string(),
+        status: "succeeded",
+        derived_data_path: derived_data_path.map(|path| path.to_string_lossy().to_string()),
+        derived_data_wiped,
+        freed_bytes,
+        log_excerpt,
+        duration_ms: start.elapsed().as_millis(),
+    })
+}
+
+/// Size of `dir`, or 0 when it does not exist.
+fn measure(dir: &Path) -> u64 {
+    if dir.is_dir() {
+        directory_size(dir).unwrap_or(0)
+    } else {
+        0
+    }
+}
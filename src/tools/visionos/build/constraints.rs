@@ -0,0 +1,116 @@
+//! `describe_build_constraints`: the allowlists and length limits build, archive, and test
+//! requests are validated against, so clients can shape a request before it is rejected.
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::server::config::VisionOsConfig;
+
+use super::request::{
+    ALLOWED_ENV_OVERRIDES, ALLOWED_EXTRA_ARGS, MAX_ARCHITECTURES, MAX_ARCHITECTURE_LEN,
+    MAX_ARTIFACT_PATTERNS, MAX_ARTIFACT_PATTERN_LEN, MAX_DESTINATION_LEN, MAX_EXTRA_ARGS,
+    MAX_EXTRA_ARG_LEN, MAX_PROJECT_PATH_LEN, MAX_SCHEME_LEN, MAX_SIGNING_VALUE_LEN,
+};
+
+/// Input for `describe_build_constraints`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DescribeBuildConstraintsRequest {}
+
+/// Limits on a list field: entry count and the length of each entry, in characters.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ListLimit {
+    pub max_count: usize,
+    pub max_len: usize,
+}
+
+/// Character limits on single-value fields.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct FieldLengths {
+    pub project_path: usize,
+    pub scheme: usize,
+    pub destination: usize,
+    /// `development_team`, `code_sign_identity`, and `provisioning_profile`.
+    pub signing_value: usize,
+}
+
+/// Response from `describe_build_constraints`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DescribeBuildConstraintsResponse {
+    /// `visionos.allowed_paths`; `project_path` and `workspace` must resolve inside one. Entries
+    /// may be glob patterns.
+    pub allowed_paths: Vec<PathBuf>,
+    /// Labels of the labeled roots among `allowed_paths`.
+    pub allowed_roots: Vec<String>,
+    /// Accepted schemes (or patterns); empty accepts every scheme.
+    pub allowed_schemes: Vec<String>,
+    pub max_length: FieldLengths,
+    /// Flags accepted in `extra_args`.
+    pub allowed_extra_args: Vec<String>,
+    pub extra_args: ListLimit,
+    /// Keys accepted in `env_overrides`.
+    pub allowed_env_overrides: Vec<String>,
+    pub architectures: ListLimit,
+    /// Applies to `artifact_include` and `artifact_exclude` separately.
+    pub artifact_patterns: ListLimit,
+    /// Timeout used when a request sets no `timeout_minutes`.
+    pub default_timeout_minutes: u16,
+    /// Largest accepted `timeout_minutes`.
+    pub max_timeout_minutes: u16,
+    /// Names accepted as `preset`.
+    pub presets: Vec<String>,
+    /// Names accepted as `xcode`.
+    pub xcodes: Vec<String>,
+    /// Accepted `provisioning_profile` values; empty accepts any.
+    pub allowed_provisioning_profiles: Vec<String>,
+    /// Device ids a `destination` may target; empty allows only `generic/platform=...`.
+    pub allowed_device_udids: Vec<String>,
+    /// Whether `use_cached_derived_data` is available (`visionos.derived_data_root` is set).
+    pub cached_derived_data: bool,
+    /// Whether `.seiro.toml` build defaults in the project directory are applied.
+    pub project_config: bool,
+}
+
+pub fn describe_build_constraints(
+    _request: DescribeBuildConstraintsRequest,
+    config: &VisionOsConfig,
+) -> DescribeBuildConstraintsResponse {
+    let names = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+    DescribeBuildConstraintsResponse {
+        allowed_paths: config.allowed_paths.clone(),
+        allowed_roots: config.root_labels(),
+        allowed_schemes: config
+            .allowed_schemes
+            .iter()
+            .map(|pattern| pattern.as_str().to_string())
+            .collect(),
+        max_length: FieldLengths {
+            project_path: MAX_PROJECT_PATH_LEN,
+            scheme: MAX_SCHEME_LEN,
+            destination: MAX_DESTINATION_LEN,
+            signing_value: MAX_SIGNING_VALUE_LEN,
+        },
+        allowed_extra_args: names(ALLOWED_EXTRA_ARGS),
+        extra_args: ListLimit {
+            max_count: MAX_EXTRA_ARGS,
+            max_len: MAX_EXTRA_ARG_LEN,
+        },
+        allowed_env_overrides: names(ALLOWED_ENV_OVERRIDES),
+        architectures: ListLimit {
+            max_count: MAX_ARCHITECTURES,
+            max_len: MAX_ARCHITECTURE_LEN,
+        },
+        artifact_patterns: ListLimit {
+            max_count: MAX_ARTIFACT_PATTERNS,
+            max_len: MAX_ARTIFACT_PATTERN_LEN,
+        },
+        default_timeout_minutes: config.max_build_minutes,
+        max_timeout_minutes: config.max_timeout_minutes,
+        presets: config.presets.keys().cloned().collect(),
+        xcodes: config.xcodes.keys().cloned().collect(),
+        allowed_provisioning_profiles: config.signing.allowed_provisioning_profiles.clone(),
+        allowed_device_udids: config.allowed_device_udids.clone(),
+        cached_derived_data: config.derived_data_root.is_some(),
+        project_config: config.project_config,
+    }
+}
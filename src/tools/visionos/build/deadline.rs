@@ -0,0 +1,143 @@
+//! `extend_build_deadline`: give a running job more time.
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+    server::config::VisionOsConfig,
+};
+
+use super::{executor::minutes_to_duration, monitor::DeadlineExtensionError, VisionOsJobQueue};
+
+const INVALID_JOB_ID_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "invalid_job_id",
+    "Invalid job_id format",
+    "Provide the UUID passed as job_id to the build request.",
+);
+const INVALID_MINUTES_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "invalid_request",
+    "minutes must be at least 1",
+    "Request a positive number of minutes.",
+);
+const EXTENSION_DISABLED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "deadline_extension_disabled",
+    "Deadline extensions are disabled on this server",
+    "Set visionos.max_extended_build_minutes in seiro-mcp.toml to allow extensions.",
+);
+const JOB_NOT_RUNNING_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "job_not_running",
+    "The job is not currently running",
+    "Only the running job can be extended; queued jobs start with the full deadline.",
+);
+const LIMIT_EXCEEDED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "deadline_limit_exceeded",
+    "The extended deadline would exceed max_extended_build_minutes",
+    "Request fewer minutes or cancel the job.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&INVALID_JOB_ID_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&INVALID_MINUTES_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&EXTENSION_DISABLED_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&JOB_NOT_RUNNING_ERROR, true, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&LIMIT_EXCEEDED_ERROR, false, SandboxState::NoViolation),
+];
+
+/// Input for `extend_build_deadline`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExtendBuildDeadlineRequest {
+    /// UUID supplied as `job_id` when the build was requested.
+    pub job_id: String,
+    /// Minutes to add to the current deadline.
+    pub minutes: u16,
+}
+
+/// Response from `extend_build_deadline`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExtendBuildDeadlineResponse {
+    pub job_id: String,
+    /// Total deadline after the extension, measured from the job start.
+    pub deadline_secs: u64,
+    /// Upper bound from `visionos.max_extended_build_minutes`.
+    pub max_deadline_secs: u64,
+}
+
+/// Extend the deadline of the running job, bounded by `max_extended_build_minutes`.
+pub async fn extend_build_deadline(
+    queue: &VisionOsJobQueue,
+    config: &VisionOsConfig,
+    request: ExtendBuildDeadlineRequest,
+) -> Result<ExtendBuildDeadlineResponse, ErrorData> {
+    if config.max_extended_build_minutes == 0 {
+        return Err(build_error_data(
+            &EXTENSION_DISABLED_ERROR,
+            json!({}),
+            false,
+            None,
+        ));
+    }
+    let job_id = Uuid::parse_str(request.job_id.trim()).map_err(|_| {
+        build_error_data(
+            &INVALID_JOB_ID_ERROR,
+            json!({ "details": request.job_id }),
+            false,
+            None,
+        )
+    })?;
+    if request.minutes == 0 {
+        return Err(build_error_data(
+            &INVALID_MINUTES_ERROR,
+            json!({ "minutes": request.minutes }),
+            false,
+            Some(job_id),
+        ));
+    }
+
+    let not_running = || build_error_data(&JOB_NOT_RUNNING_ERROR, json!({}), true, Some(job_id));
+    let monitor = queue
+        .running_monitor(job_id)
+        .await
+        .ok_or_else(not_running)?;
+    let max = minutes_to_duration(config.max_extended_build_minutes);
+    let deadline = monitor
+        .extend_deadline(minutes_to_duration(request.minutes), max)
+        .map_err(|err| match err {
+            DeadlineExtensionError::NotRunning => not_running(),
+            DeadlineExtensionError::LimitExceeded { current, max } => build_error_data(
+                &LIMIT_EXCEEDED_ERROR,
+                json!({
+                    "requested_minutes": request.minutes,
+                    "deadline_secs": current.as_secs(),
+                    "max_deadline_secs": max.as_secs(),
+                }),
+                false,
+                Some(job_id),
+            ),
+        })?;
+
+    Ok(ExtendBuildDeadlineResponse {
+        job_id: job_id.to_string(),
+        deadline_secs: deadline.as_secs(),
+        max_deadline_secs: max.as_secs(),
+    })
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    retryable: bool,
+    job_id: Option<Uuid>,
+) -> ErrorData {
+    let mut builder = desc
+        .builder()
+        .details(details)
+        .sandbox_state(SandboxState::NoViolation)
+        .retryable(retryable);
+    if let Some(job_id) = job_id {
+        builder = builder.with_context_field("job_id", json!(job_id.to_string()));
+    }
+    builder.build().expect("descriptor is valid")
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     env, fs,
     path::{Path, PathBuf},
     time::{Duration, Instant},
@@ -6,26 +7,47 @@ use std::{
 
 use rmcp::model::ErrorData;
 use serde_json::{json, Value};
-use tokio::time;
-use tracing::info;
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::{
     lib::{
-        errors::{SandboxState, ToolErrorDescriptor, VisionOsBuildError},
-        fs as artifact_fs, visionos as visionos_helpers, xcodebuild as xcodebuild_helpers,
+        app_size::{self, AppSizeReport},
+        compilation_cache::CompilationCacheStats,
+        errors::{
+            ErrorCatalogEntry, SandboxPolicyError, SandboxState, ToolErrorDescriptor,
+            VisionOsBuildError,
+        },
+        fs::{self as artifact_fs, ZipFilter, ZipOptions},
+        postprocess::{self, PostProcessOptions, PostProcessReport},
+        retry::BuildAttempt,
+        telemetry::{redact_json, REDACTED},
+        visionos as visionos_helpers,
+        xcodebuild::{self as xcodebuild_helpers, BuildLogDiagnostic, LogFormat, MAX_LOG_ERRORS},
+        xcresult::{self, XcresultDiagnostic},
+    },
+    server::config::{ArtifactPersistence, SizeBudgetEnforcement, VisionOsConfig},
+    tools::visionos::{
+        artifacts::{ArtifactKind, BUILD_LOG_FILE},
+        sandbox,
     },
-    server::config::VisionOsConfig,
 };
 
-use super::{BuildRequestValidationError, VisionOsBuildRequest};
+use super::{
+    BuildRequestValidationError, JobMonitor, VisionOsBuildRequest, ALLOWED_ENV_OVERRIDES,
+    ALLOWED_EXTRA_ARGS,
+};
 
 const LOG_EXCERPT_LIMIT: usize = 5_000;
+/// Phase reported while the artifact is zipped and hashed.
+const PACKAGE_PHASE: &str = "package";
+/// DerivedData directory inside the job directory under `memory` persistence.
+const EPHEMERAL_DERIVED_DATA_DIR: &str = "DerivedData";
 
 const PATH_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "path_not_allowed",
     "project_path is outside the allowlist",
-    "Update visionos.allowed_paths in seiro-mcp.toml and restart the MCP server.",
+    "Update visionos.allowed_paths in seiro-mcp.toml; the running server reloads it on save.",
 );
 const INVALID_INPUT_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "invalid_request",
@@ -37,10 +59,20 @@ const SCHEME_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "scheme is not in the allowlist",
     "Update visionos.allowed_schemes in seiro-mcp.toml or use an allowed scheme.",
 );
+const PROVISIONING_PROFILE_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "provisioning_profile_not_allowed",
+    "provisioning_profile is not in the allowlist",
+    "Add the profile to visionos.signing.allowed_provisioning_profiles in seiro-mcp.toml or use an allowed profile.",
+);
+const DEVICE_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "device_not_allowed",
+    "destination targets a device outside the allowlist",
+    "Add the UDID reported by list_devices to visionos.allowed_device_udids in seiro-mcp.toml or use a simulator destination.",
+);
 const TIMEOUT_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "timeout",
-    "Build was aborted after exceeding max_build_minutes",
-    "Shorten the build time or increase max_build_minutes.",
+    "Build was aborted after exceeding its timeout",
+    "Shorten the build time or raise timeout_minutes (up to max_timeout_minutes) or max_build_minutes.",
 );
 const BUILD_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "build_failed",
@@ -52,21 +84,96 @@ const DESTINATION_AMBIGUOUS_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::ne
     "The requested simulator destination matched multiple devices",
     "Retry build_visionos_app with an id-based destination such as `platform=visionOS Simulator,id:<device-id>`.",
 );
+const SIZE_BUDGET_EXCEEDED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "size_budget_exceeded",
+    "The built app exceeds the size budget for this scheme",
+    "Reduce the app size or raise visionos.size_budgets in seiro-mcp.toml.",
+);
+const POST_PROCESS_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "post_process_failed",
+    "Stripping or thinning the built binaries failed",
+    "Check the requested architectures against the built slices, or retry without strip_symbols.",
+);
 const SANDBOX_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "sandbox_violation_blocked",
     "Build was blocked by the sandbox policy",
     "Verify allowed paths, SDK setup, and DevToolsSecurity.",
 );
 
-/// Response from `build_visionos_app`.
-#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&PATH_NOT_ALLOWED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&INVALID_INPUT_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&SCHEME_NOT_ALLOWED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(
+        &PROVISIONING_PROFILE_NOT_ALLOWED_ERROR,
+        false,
+        SandboxState::Blocked,
+    ),
+    ErrorCatalogEntry::new(&DEVICE_NOT_ALLOWED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&TIMEOUT_ERROR, true, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&BUILD_FAILED_ERROR, true, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(
+        &DESTINATION_AMBIGUOUS_ERROR,
+        true,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(
+        &SIZE_BUDGET_EXCEEDED_ERROR,
+        false,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(&POST_PROCESS_FAILED_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&SANDBOX_ERROR, false, SandboxState::Blocked),
+];
+
+/// Response from `build_visionos_app` and `archive_visionos_app`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct BuildVisionOsAppResponse {
     pub job_id: String,
     pub status: &'static str,
+    pub artifact_kind: ArtifactKind,
     pub artifact_path: String,
     pub artifact_sha256: String,
     pub log_excerpt: String,
+    /// First errors parsed from the `xcodebuild` output, with file and line when reported.
+    pub log_errors: Vec<BuildLogDiagnostic>,
+    /// Warnings, errors, and code-signing issues read from the `.xcresult` bundle.
+    pub diagnostics: Vec<XcresultDiagnostic>,
+    /// Size of the built `.app` and its main binary; `None` when no bundle was found.
+    pub size: Option<AppSizeReport>,
+    /// Result of `strip_symbols` / `architectures` / `collect_dsyms`; `None` when none was
+    /// requested.
+    pub post_process: Option<PostProcessReport>,
+    /// `true` when the artifact of an earlier job with the same sources and parameters was
+    /// returned without running `xcodebuild`; `job_id` is then that job's ID.
+    pub cache_hit: bool,
+    /// Compiler cache hits and misses during the build when `visionos.compilation_cache` uses
+    /// sccache. Other builds sharing the sccache server are counted too.
+    pub compilation_cache: Option<CompilationCacheStats>,
     pub duration_ms: u128,
+    /// Part of `duration_ms` spent zipping and hashing the artifact; `0` for cache hits.
+    pub packaging_duration_ms: u128,
+    /// Each `xcodebuild` run, oldest first. More than one when `visionos.retry` repeated a run
+    /// that failed for a transient reason; empty for cache hits.
+    pub attempts: Vec<BuildAttempt>,
+    /// The command a `dry_run` request would run; `status` is then `dry_run`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<BuildPlan>,
+}
+
+/// `xcodebuild` invocation reported by a `dry_run` request instead of being run.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BuildPlan {
+    /// `visionos.xcodebuild_path` followed by its arguments.
+    pub argv: Vec<String>,
+    pub working_dir: String,
+    /// Complete environment of the run. Values set by `env_overrides` and values of sensitive
+    /// keys are masked.
+    pub env: BTreeMap<String, String>,
+    /// Deadline covering every attempt.
+    pub timeout_secs: u64,
+    /// Where `xcodebuild` would put its products before they are zipped.
+    pub staging_dir: String,
 }
 
 use schemars::JsonSchema;
@@ -76,101 +183,595 @@ use serde::Serialize;
 pub async fn run_build(
     request: &VisionOsBuildRequest,
     config: &VisionOsConfig,
+    monitor: &JobMonitor,
+    job_id: Uuid,
+    artifact_root: PathBuf,
+) -> Result<BuildVisionOsAppResponse, VisionOsBuildError> {
+    run_xcodebuild_job(
+        request,
+        config,
+        monitor,
+        job_id,
+        artifact_root,
+        ArtifactKind::Build,
+    )
+    .await
+}
+
+/// Execute `xcodebuild archive` and package the resulting `.xcarchive`.
+pub async fn run_archive(
+    request: &VisionOsBuildRequest,
+    config: &VisionOsConfig,
+    monitor: &JobMonitor,
+    job_id: Uuid,
+    artifact_root: PathBuf,
+) -> Result<BuildVisionOsAppResponse, VisionOsBuildError> {
+    run_xcodebuild_job(
+        request,
+        config,
+        monitor,
+        job_id,
+        artifact_root,
+        ArtifactKind::Archive,
+    )
+    .await
+}
+
+/// Fail before `xcodebuild` starts when the project's volume has less than
+/// `visionos.min_free_disk_bytes` free. A volume that cannot be measured does not block the build.
+fn check_disk_space(
+    request: &VisionOsBuildRequest,
+    config: &VisionOsConfig,
+) -> Result<(), VisionOsBuildError> {
+    if config.min_free_disk_bytes == 0 {
+        return Ok(());
+    }
+    match sandbox::free_disk_bytes(&request.project_path) {
+        Ok(available_bytes) if available_bytes < config.min_free_disk_bytes => {
+            Err(VisionOsBuildError::DiskInsufficient {
+                available_bytes,
+                required_bytes: config.min_free_disk_bytes,
+            })
+        }
+        Ok(_) => Ok(()),
+        Err(err) => {
+            warn!(
+                target: "rmcp_sample::visionos",
+                project = %request.project_path.display(),
+                error = %err,
+                "Failed to measure free disk space; starting the build anyway"
+            );
+            Ok(())
+        }
+    }
+}
+
+async fn run_xcodebuild_job(
+    request: &VisionOsBuildRequest,
+    config: &VisionOsConfig,
+    monitor: &JobMonitor,
     job_id: Uuid,
     artifact_root: PathBuf,
+    kind: ArtifactKind,
 ) -> Result<BuildVisionOsAppResponse, VisionOsBuildError> {
+    check_disk_space(request, config)?;
     let job_dir = artifact_fs::ensure_job_dir(&artifact_root, &job_id)?;
+    // xcodebuild runs inside the project directory, so hand it absolute output paths.
+    let job_dir =
+        std::path::absolute(&job_dir).map_err(|err| VisionOsBuildError::ArtifactFailure {
+            message: format!("Failed to resolve artifact job directory: {err}"),
+        })?;
     let staging_dir = job_dir.join("staging");
     fs::create_dir_all(&staging_dir).map_err(|err| VisionOsBuildError::ArtifactFailure {
         message: format!("Failed to create artifact staging directory: {err}"),
     })?;
 
-    let time_scale = env::var("VISIONOS_TEST_TIME_SCALE")
-        .ok()
-        .and_then(|value| value.parse::<u64>().ok())
-        .filter(|scale| *scale > 0)
-        .unwrap_or(60);
-    let timeout_duration = Duration::from_secs(config.max_build_minutes as u64 * time_scale);
+    let result_bundle = xcresult::result_bundle_path(&job_dir);
+
+    let timeout_duration = build_timeout(config, request);
     let start = Instant::now();
-    let output = time::timeout(
-        timeout_duration,
-        spawn_xcodebuild(request, config, &staging_dir),
-    )
-    .await
-    .map_err(|_| VisionOsBuildError::Timeout {
-        duration_secs: timeout_duration.as_secs(),
-    })?
-    .map_err(|err| VisionOsBuildError::CommandFailed {
-        exit_code: None,
-        message: err.to_string(),
-    })?;
+    let cache_before = compilation_cache_snapshot(config).await;
+    let output = monitor
+        .run_with_deadline(
+            timeout_duration,
+            config.timeout_warning_percent,
+            run_xcodebuild_attempts(
+                request,
+                config,
+                monitor,
+                job_id,
+                &staging_dir,
+                &result_bundle,
+                kind,
+            ),
+        )
+        .await
+        .map_err(|deadline| VisionOsBuildError::Timeout {
+            duration_secs: deadline.as_secs(),
+        })?
+        .map_err(|err| VisionOsBuildError::CommandFailed {
+            exit_code: None,
+            message: err.to_string(),
+            diagnostics: Vec::new(),
+            log_errors: Vec::new(),
+        })?;
 
-    let log_excerpt = collect_log_excerpt(&output.stdout, &output.stderr);
+    write_build_log(&job_dir, &output, job_id);
+    let log_excerpt = collect_log_excerpt(&output.stdout, &output.stderr, request.log_format);
+    let log_errors =
+        xcodebuild_helpers::collect_log_errors(&output.stdout, &output.stderr, MAX_LOG_ERRORS);
+    let diagnostics = load_result_bundle_diagnostics(config, &result_bundle).await;
+    let compilation_cache = match cache_before {
+        Some(before) => compilation_cache_snapshot(config)
+            .await
+            .map(|after| after.since(before)),
+        None => None,
+    };
+    if let Some(stats) = compilation_cache {
+        info!(
+            target: "rmcp_sample::visionos",
+            job_id = %job_id,
+            hits = stats.hits,
+            misses = stats.misses,
+            "Compilation cache statistics"
+        );
+    }
     if !output.status.success() {
         return Err(VisionOsBuildError::CommandFailed {
             exit_code: output.status.code(),
             message: log_excerpt,
+            diagnostics,
+            log_errors,
         });
     }
 
-    let artifact_zip = job_dir.join("artifact.zip");
-    artifact_fs::zip_directory(&staging_dir, &artifact_zip)?;
-    let artifact_sha256 = artifact_fs::compute_sha256(&artifact_zip)?;
+    let artifact_zip = match kind {
+        ArtifactKind::Build | ArtifactKind::Ipa | ArtifactKind::Media => {
+            job_dir.join("artifact.zip")
+        }
+        ArtifactKind::Archive => {
+            let archive = xcodebuild_helpers::archive_path(&staging_dir, &request.scheme);
+            if !archive.is_dir() {
+                return Err(VisionOsBuildError::ArtifactFailure {
+                    message: format!("xcodebuild archive did not produce {}", archive.display()),
+                });
+            }
+            job_dir.join("archive.zip")
+        }
+    };
+    let post_process =
+        post_process_app(request, config, job_id, kind, &staging_dir, &job_dir).await?;
+    let size = measure_app_size(request, config, job_id, &staging_dir)?;
+    monitor.set_phase(PACKAGE_PHASE);
+    let mut options = zip_options(config);
+    options.filter =
+        ZipFilter::new(&request.artifact_include, &request.artifact_exclude).map_err(|err| {
+            VisionOsBuildError::ArtifactFailure {
+                message: format!("Invalid artifact pattern: {err}"),
+            }
+        })?;
+    let packaging_start = Instant::now();
+    let artifact_sha256 = package_artifact(staging_dir, artifact_zip.clone(), options).await?;
+    let packaging_duration_ms = packaging_start.elapsed().as_millis();
 
     Ok(BuildVisionOsAppResponse {
         job_id: job_id.to_string(),
         status: "succeeded",
+        artifact_kind: kind,
         artifact_path: artifact_zip.to_string_lossy().to_string(),
         artifact_sha256,
         log_excerpt,
+        log_errors,
+        diagnostics,
+        size,
+        post_process,
+        cache_hit: false,
+        compilation_cache,
         duration_ms: start.elapsed().as_millis(),
+        packaging_duration_ms,
+        attempts: monitor.attempts(),
+        plan: None,
+    })
+}
+
+/// Answer a validated `dry_run` request with the command [`run_build`] or [`run_archive`] would
+/// run for `job_id`, without creating the job directory or queueing anything.
+pub fn plan_build(
+    request: &VisionOsBuildRequest,
+    config: &VisionOsConfig,
+    job_id: Uuid,
+    artifact_root: &Path,
+    kind: ArtifactKind,
+) -> BuildVisionOsAppResponse {
+    let job_dir = std::path::absolute(artifact_root.join(job_id.to_string()))
+        .unwrap_or_else(|_| artifact_root.join(job_id.to_string()));
+    let staging_dir = job_dir.join("staging");
+    let result_bundle = xcresult::result_bundle_path(&job_dir);
+    let (command, _) = xcodebuild_command(request, config, &staging_dir, &result_bundle, kind);
+    let command = command.as_std();
+    let argv = std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let mut env = Value::Object(
+        command
+            .get_envs()
+            .filter_map(|(key, value)| {
+                let key = key.to_string_lossy().into_owned();
+                let value = if request.env_overrides.contains_key(&key) {
+                    REDACTED.to_string()
+                } else {
+                    value?.to_string_lossy().into_owned()
+                };
+                Some((key, Value::String(value)))
+            })
+            .collect(),
+    );
+    redact_json(&mut env);
+    let artifact_zip = match kind {
+        ArtifactKind::Archive => job_dir.join("archive.zip"),
+        ArtifactKind::Build | ArtifactKind::Ipa | ArtifactKind::Media => {
+            job_dir.join("artifact.zip")
+        }
+    };
+    BuildVisionOsAppResponse {
+        job_id: job_id.to_string(),
+        status: "dry_run",
+        artifact_kind: kind,
+        artifact_path: artifact_zip.to_string_lossy().to_string(),
+        artifact_sha256: String::new(),
+        log_excerpt: String::new(),
+        log_errors: Vec::new(),
+        diagnostics: Vec::new(),
+        size: None,
+        post_process: None,
+        cache_hit: false,
+        compilation_cache: None,
+        duration_ms: 0,
+        packaging_duration_ms: 0,
+        attempts: Vec::new(),
+        plan: Some(BuildPlan {
+            argv,
+            working_dir: command
+                .get_current_dir()
+                .map(|dir| dir.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            env: serde_json::from_value(env).unwrap_or_default(),
+            timeout_secs: build_timeout(config, request).as_secs(),
+            staging_dir: staging_dir.to_string_lossy().to_string(),
+        }),
+    }
+}
+
+/// Zip and hash the staging directory on the blocking pool so heartbeats keep flowing for
+/// large artifacts.
+async fn package_artifact(
+    staging_dir: PathBuf,
+    artifact_zip: PathBuf,
+    options: ZipOptions,
+) -> Result<String, VisionOsBuildError> {
+    tokio::task::spawn_blocking(move || {
+        artifact_fs::zip_directory(&staging_dir, &artifact_zip, options)?;
+        artifact_fs::compute_sha256(&artifact_zip)
     })
+    .await
+    .map_err(|err| VisionOsBuildError::ArtifactFailure {
+        message: format!("Artifact packaging task failed: {err}"),
+    })?
+    .map_err(VisionOsBuildError::from)
+}
+
+/// Keep the complete `xcodebuild` output for `fetch_build_log` and `export_job_trace`; the
+/// response only carries an excerpt. A write failure only loses the trace log, so it does not fail the job.
+fn write_build_log(job_dir: &Path, output: &std::process::Output, job_id: Uuid) {
+    let path = job_dir.join(BUILD_LOG_FILE);
+    if let Err(err) = fs::write(&path, [output.stdout.as_slice(), &output.stderr].concat()) {
+        warn!(
+            target: "rmcp_sample::visionos",
+            job_id = %job_id,
+            path = %path.display(),
+            error = %err,
+            "Failed to write the full build log"
+        );
+    }
+}
+
+/// Current compiler cache counters; `None` without an sccache-backed `compilation_cache`.
+async fn compilation_cache_snapshot(config: &VisionOsConfig) -> Option<CompilationCacheStats> {
+    config.compilation_cache.as_ref()?.snapshot().await
+}
+
+/// Packaging settings for `artifact.zip`, `archive.zip`, and `dsyms.zip`.
+fn zip_options(config: &VisionOsConfig) -> ZipOptions {
+    ZipOptions {
+        compression: config.artifact_compression,
+        symlinks: config.artifact_symlinks,
+        deterministic: config.deterministic_artifacts,
+        filter: ZipFilter::default(),
+    }
+}
+
+/// Strip and thin the built app when requested. Stripped builds, and builds asking for
+/// `collect_dsyms`, ship their dSYMs as `dsyms.zip`.
+async fn post_process_app(
+    request: &VisionOsBuildRequest,
+    config: &VisionOsConfig,
+    job_id: Uuid,
+    kind: ArtifactKind,
+    staging_dir: &Path,
+    job_dir: &Path,
+) -> Result<Option<PostProcessReport>, VisionOsBuildError> {
+    let options = PostProcessOptions {
+        strip_symbols: request.strip_symbols,
+        architectures: &request.architectures,
+    };
+    if options.is_empty() && !request.collect_dsyms {
+        return Ok(None);
+    }
+    let mut report = if options.is_empty() {
+        PostProcessReport::default()
+    } else {
+        let Some(report) = postprocess::process_app_bundle(
+            &config.xcrun_path,
+            &config.xcode_path,
+            staging_dir,
+            options,
+        )
+        .await?
+        else {
+            return Ok(None);
+        };
+        report
+    };
+    // Archives already keep dSYMs in `<name>.xcarchive/dSYMs`, outside the app bundle.
+    if (request.strip_symbols || request.collect_dsyms) && matches!(kind, ArtifactKind::Build) {
+        report.dsym_artifact_path =
+            postprocess::split_dsyms(staging_dir, job_dir, zip_options(config))?
+                .map(|path| path.to_string_lossy().to_string());
+    }
+    info!(
+        target: "rmcp_sample::visionos",
+        job_id = %job_id,
+        binaries = report.binaries.len(),
+        stripped = report.stripped,
+        removed_architectures = ?report.removed_architectures,
+        "Post-processed visionOS app binaries"
+    );
+    Ok(Some(report))
+}
+
+/// Measure the built `.app` and check it against the scheme's size budget.
+fn measure_app_size(
+    request: &VisionOsBuildRequest,
+    config: &VisionOsConfig,
+    job_id: Uuid,
+    staging_dir: &Path,
+) -> Result<Option<AppSizeReport>, VisionOsBuildError> {
+    let Some(mut report) = app_size::measure_app_bundle(staging_dir)? else {
+        return Ok(None);
+    };
+    let Some(budget) = config.size_budgets.get(&request.scheme) else {
+        return Ok(Some(report));
+    };
+    report.apply_budget(budget.max_app_bytes, budget.max_binary_bytes);
+    if report.budget_exceeded() {
+        warn!(
+            target: "rmcp_sample::visionos",
+            job_id = %job_id,
+            scheme = %request.scheme,
+            app_bundle_bytes = report.app_bundle_bytes,
+            main_binary_bytes = ?report.main_binary_bytes,
+            "visionOS app exceeds its size budget"
+        );
+        if budget.enforcement == SizeBudgetEnforcement::Fail {
+            return Err(VisionOsBuildError::SizeBudgetExceeded { report });
+        }
+    }
+    Ok(Some(report))
+}
+
+/// Read issues from the result bundle; a missing or unreadable bundle yields no diagnostics.
+async fn load_result_bundle_diagnostics(
+    config: &VisionOsConfig,
+    result_bundle: &Path,
+) -> Vec<XcresultDiagnostic> {
+    if !result_bundle.exists() {
+        return Vec::new();
+    }
+    match xcresult::load_diagnostics(&config.xcrun_path, &config.xcode_path, result_bundle).await {
+        Ok(diagnostics) => diagnostics,
+        Err(err) => {
+            warn!(
+                target: "rmcp_sample::visionos",
+                bundle = %result_bundle.display(),
+                error = %err,
+                "Failed to read xcresult bundle"
+            );
+            Vec::new()
+        }
+    }
+}
+
+/// Deadline from the request's `timeout_minutes`, else `max_build_minutes` (scaled by
+/// `VISIONOS_TEST_TIME_SCALE` in tests).
+pub(crate) fn build_timeout(config: &VisionOsConfig, request: &VisionOsBuildRequest) -> Duration {
+    minutes_to_duration(request.timeout_minutes.unwrap_or(config.max_build_minutes))
+}
+
+/// Per-project DerivedData directory when the request opted into `use_cached_derived_data`.
+pub(crate) fn cached_derived_data_path(
+    config: &VisionOsConfig,
+    request: &VisionOsBuildRequest,
+) -> Option<PathBuf> {
+    let root = config.derived_data_root.as_deref()?;
+    request
+        .use_cached_derived_data
+        .then(|| xcodebuild_helpers::derived_data_path(root, request.project_root()))
+}
+
+/// Under `memory` persistence, DerivedData goes to `dir` so it is removed with the job instead
+/// of accumulating in Xcode's default location under `~/Library`.
+pub(crate) fn ephemeral_derived_data_path(config: &VisionOsConfig, dir: &Path) -> Option<PathBuf> {
+    (config.artifact_persistence == ArtifactPersistence::Memory)
+        .then(|| dir.join(EPHEMERAL_DERIVED_DATA_DIR))
+}
+
+/// Convert configured minutes to a duration (scaled by `VISIONOS_TEST_TIME_SCALE` in tests).
+pub(crate) fn minutes_to_duration(minutes: u16) -> Duration {
+    let time_scale = env::var("VISIONOS_TEST_TIME_SCALE")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|scale| *scale > 0)
+        .unwrap_or(60);
+    Duration::from_secs(minutes as u64 * time_scale)
+}
+
+/// Run `xcodebuild`, repeating runs that failed for a reason in `visionos.retry.retry_on`.
+///
+/// Every run is recorded on `monitor`. The staging directory and result bundle are cleared
+/// before a retry so it starts like the first run; the job deadline covers all runs.
+async fn run_xcodebuild_attempts(
+    request: &VisionOsBuildRequest,
+    config: &VisionOsConfig,
+    monitor: &JobMonitor,
+    job_id: Uuid,
+    staging_dir: &Path,
+    result_bundle: &Path,
+    kind: ArtifactKind,
+) -> std::io::Result<std::process::Output> {
+    let mut attempt = 1;
+    loop {
+        let started = Instant::now();
+        let output =
+            spawn_xcodebuild(request, config, monitor, staging_dir, result_bundle, kind).await?;
+        let retry_reason = if output.status.success() {
+            None
+        } else {
+            let log = [output.stdout.as_slice(), &output.stderr].concat();
+            config
+                .retry
+                .retry_reason(attempt, &String::from_utf8_lossy(&log))
+        };
+        monitor.record_attempt(BuildAttempt {
+            attempt,
+            exit_code: output.status.code(),
+            retry_reason,
+            duration_ms: started.elapsed().as_millis(),
+        });
+        let Some(reason) = retry_reason else {
+            return Ok(output);
+        };
+
+        let backoff = config.retry.backoff_after(attempt);
+        warn!(
+            target: "rmcp_sample::visionos",
+            job_id = %job_id,
+            attempt,
+            reason = reason.as_str(),
+            backoff_secs = backoff.as_secs(),
+            "Retrying visionOS build after a transient failure"
+        );
+        if result_bundle.exists() {
+            fs::remove_dir_all(result_bundle)?;
+        }
+        fs::remove_dir_all(staging_dir)?;
+        fs::create_dir_all(staging_dir)?;
+        tokio::time::sleep(backoff).await;
+        attempt += 1;
+    }
 }
 
 async fn spawn_xcodebuild(
     request: &VisionOsBuildRequest,
     config: &VisionOsConfig,
+    monitor: &JobMonitor,
     staging_dir: &Path,
+    result_bundle: &Path,
+    kind: ArtifactKind,
 ) -> std::io::Result<std::process::Output> {
-    let mut command = xcodebuild_helpers::build_visionos_xcodebuild_command(
+    let (command, derived_data_path) =
+        xcodebuild_command(request, config, staging_dir, result_bundle, kind);
+
+    info!(
+        target: "rmcp_sample::visionos",
+        scheme = %request.scheme,
+        platform = request.platform.as_str(),
+        destination = %request.destination,
+        clean = request.clean,
+        project_kind = request.project_kind().as_str(),
+        cached_derived_data = derived_data_path.is_some(),
+        kind = kind.as_str(),
+        "Starting visionOS build"
+    );
+
+    xcodebuild_helpers::output_with_lines(command, |line| {
+        if let Some(phase) = xcodebuild_helpers::build_phase(line) {
+            monitor.set_phase(phase);
+        }
+    })
+    .await
+}
+
+/// The `xcodebuild` command for `request`, and the DerivedData directory it uses when not
+/// Xcode's default.
+fn xcodebuild_command(
+    request: &VisionOsBuildRequest,
+    config: &VisionOsConfig,
+    staging_dir: &Path,
+    result_bundle: &Path,
+    kind: ArtifactKind,
+) -> (tokio::process::Command, Option<PathBuf>) {
+    let job_dir = staging_dir.parent().unwrap_or(staging_dir);
+    let derived_data_path = cached_derived_data_path(config, request)
+        .or_else(|| ephemeral_derived_data_path(config, job_dir));
+    let signing_settings = request.signing_build_settings();
+    let command = xcodebuild_helpers::build_visionos_xcodebuild_command(
         xcodebuild_helpers::VisionOsXcodebuildCommandConfig {
             xcodebuild_path: &config.xcodebuild_path,
             xcode_path: &config.xcode_path,
             staging_dir,
+            result_bundle_path: Some(result_bundle),
+            derived_data_path: derived_data_path.as_deref(),
+            compilation_cache: config.compilation_cache.as_ref(),
+            locale: &config.locale,
         },
         xcodebuild_helpers::VisionOsXcodebuildRequest {
             project_path: &request.project_path,
             workspace: request.workspace.as_deref(),
             scheme: &request.scheme,
-            configuration: request.configuration.as_str(),
+            configuration: request.configuration().as_str(),
             destination: &request.destination,
             clean: request.clean,
+            action: match kind {
+                ArtifactKind::Build | ArtifactKind::Ipa | ArtifactKind::Media => {
+                    xcodebuild_helpers::XcodebuildAction::Build
+                }
+                ArtifactKind::Archive => xcodebuild_helpers::XcodebuildAction::Archive,
+            },
             extra_args: &request.extra_args,
+            build_settings: &signing_settings,
             env_overrides: &request.env_overrides,
         },
     );
-
-    info!(
-        target: "rmcp_sample::visionos",
-        scheme = %request.scheme,
-        destination = %request.destination,
-        clean = request.clean,
-        "Starting visionOS build"
-    );
-
-    command.output().await
+    (command, derived_data_path)
 }
 
-fn collect_log_excerpt(stdout: &[u8], stderr: &[u8]) -> String {
-    visionos_helpers::collect_log_excerpt(stdout, stderr, LOG_EXCERPT_LIMIT)
+fn collect_log_excerpt(stdout: &[u8], stderr: &[u8], format: LogFormat) -> String {
+    visionos_helpers::collect_formatted_log_excerpt(stdout, stderr, format, LOG_EXCERPT_LIMIT)
 }
 
 pub fn validation_error_to_error_data(err: BuildRequestValidationError) -> ErrorData {
     match err {
-        BuildRequestValidationError::ProjectPathNotAllowed { path }
-        | BuildRequestValidationError::WorkspaceNotAllowed { path } => build_error_data(
+        BuildRequestValidationError::ProjectPathNotAllowed {
+            path,
+            allowed_roots,
+        }
+        | BuildRequestValidationError::WorkspaceNotAllowed {
+            path,
+            allowed_roots,
+        } => build_error_data(
             &PATH_NOT_ALLOWED_ERROR,
-            json!({ "path": path.to_string_lossy() }),
+            json!({ "path": path.to_string_lossy(), "allowed_roots": allowed_roots }),
             SandboxState::Blocked,
             false,
         ),
@@ -180,6 +781,30 @@ pub fn validation_error_to_error_data(err: BuildRequestValidationError) -> Error
             SandboxState::Blocked,
             false,
         ),
+        BuildRequestValidationError::ProvisioningProfileNotAllowed { profile } => build_error_data(
+            &PROVISIONING_PROFILE_NOT_ALLOWED_ERROR,
+            json!({ "provisioning_profile": profile }),
+            SandboxState::Blocked,
+            false,
+        ),
+        BuildRequestValidationError::DeviceNotAllowed { udid } => build_error_data(
+            &DEVICE_NOT_ALLOWED_ERROR,
+            json!({ "udid": udid }),
+            SandboxState::Blocked,
+            false,
+        ),
+        BuildRequestValidationError::ExtraArgNotAllowed { .. } => build_error_data(
+            &INVALID_INPUT_ERROR,
+            json!({ "details": err.to_string(), "allowed_extra_args": ALLOWED_EXTRA_ARGS }),
+            SandboxState::NoViolation,
+            false,
+        ),
+        BuildRequestValidationError::EnvOverrideNotAllowed { .. } => build_error_data(
+            &INVALID_INPUT_ERROR,
+            json!({ "details": err.to_string(), "allowed_env_overrides": ALLOWED_ENV_OVERRIDES }),
+            SandboxState::NoViolation,
+            false,
+        ),
         _ => build_error_data(
             &INVALID_INPUT_ERROR,
             json!({ "details": err.to_string() }),
@@ -205,6 +830,35 @@ pub fn runtime_error_to_error_data(err: VisionOsBuildError, job_id: Uuid) -> Err
             true,
             job_id,
         ),
+        VisionOsBuildError::SizeBudgetExceeded { report } => build_error_data_with_job(
+            &SIZE_BUDGET_EXCEEDED_ERROR,
+            json!({ "size": report }),
+            SandboxState::NoViolation,
+            false,
+            job_id,
+        ),
+        VisionOsBuildError::PostProcessFailed(err) => build_error_data_with_job(
+            &POST_PROCESS_FAILED_ERROR,
+            json!({ "details": err.to_string() }),
+            SandboxState::NoViolation,
+            false,
+            job_id,
+        ),
+        VisionOsBuildError::DiskInsufficient {
+            available_bytes,
+            required_bytes,
+        } => build_error_data_with_job(
+            sandbox::sandbox_error_descriptor(&SandboxPolicyError::DiskInsufficient {
+                available_bytes,
+            }),
+            json!({
+                "available_bytes": available_bytes,
+                "required_bytes": required_bytes
+            }),
+            SandboxState::Blocked,
+            false,
+            job_id,
+        ),
         VisionOsBuildError::SandboxViolated { reason } => build_error_data_with_job(
             &SANDBOX_ERROR,
             json!({ "reason": reason }),
@@ -212,7 +866,12 @@ pub fn runtime_error_to_error_data(err: VisionOsBuildError, job_id: Uuid) -> Err
             false,
             job_id,
         ),
-        VisionOsBuildError::CommandFailed { exit_code, message } => {
+        VisionOsBuildError::CommandFailed {
+            exit_code,
+            message,
+            diagnostics,
+            log_errors,
+        } => {
             if let Some(details) = parse_ambiguous_destination_details(&message) {
                 return build_error_data_with_job(
                     &DESTINATION_AMBIGUOUS_ERROR,
@@ -233,6 +892,8 @@ pub fn runtime_error_to_error_data(err: VisionOsBuildError, job_id: Uuid) -> Err
                 &BUILD_FAILED_ERROR,
                 json!({
                     "details": message,
+                    "log_errors": log_errors,
+                    "diagnostics": diagnostics,
                     "diagnostics_hint": "inspect_build_diagnostics"
                 }),
                 SandboxState::NoViolation,
@@ -467,14 +1128,39 @@ mod tests {
         assert_eq!(data.get("retryable").and_then(Value::as_bool), Some(false));
     }
 
+    #[test]
+    fn runtime_disk_insufficient_reuses_sandbox_descriptor() {
+        let err = VisionOsBuildError::DiskInsufficient {
+            available_bytes: 1,
+            required_bytes: 2,
+        };
+        let data = extract_data(&runtime_error_to_error_data(err, Uuid::new_v4()));
+        assert_eq!(
+            data.get("code").and_then(Value::as_str),
+            Some("disk_insufficient")
+        );
+        assert_eq!(data["details"]["required_bytes"], 2);
+        assert_eq!(data.get("retryable").and_then(Value::as_bool), Some(false));
+    }
+
     #[test]
     fn runtime_build_failed_maps_to_retryable_error() {
         let job_id = Uuid::new_v4();
         let err = VisionOsBuildError::CommandFailed {
             exit_code: Some(1),
             message: "fail".into(),
+            diagnostics: Vec::new(),
+            log_errors: xcodebuild_helpers::parse_diagnostics(
+                "/tmp/A.swift:10:5: error: cannot find 'foo' in scope",
+            ),
         };
         let data = extract_data(&runtime_error_to_error_data(err, job_id));
+        assert_eq!(
+            data.get("details")
+                .and_then(|details| details.pointer("/log_errors/0/line"))
+                .and_then(Value::as_u64),
+            Some(10)
+        );
         assert_eq!(
             data.get("code").and_then(Value::as_str),
             Some("build_failed")
@@ -503,6 +1189,8 @@ mod tests {
         { platform:visionOS, id:dvtdevice-DVTiOSDevicePlaceholder-xros:placeholder, name:Any visionOS Device }
         { platform:visionOS Simulator, id:dvtdevice-DVTiOSDeviceSimulatorPlaceholder-xrsimulator:placeholder, name:Any visionOS Simulator Device }
         { platform:visionOS Simulator, arch:arm64, id:F556D53F-412A-4778-AF81-3449D52F5A7F, OS:26.2, name:Apple Vision Pro }"#.into(),
+            diagnostics: Vec::new(),
+            log_errors: Vec::new(),
         };
 
         let data = extract_data(&runtime_error_to_error_data(err, job_id));
@@ -1,16 +1,50 @@
 //! visionOS build tool entrypoint.
+pub mod cache;
+pub mod cancel;
+pub mod clean;
+pub mod constraints;
+pub mod deadline;
 pub mod executor;
+pub mod monitor;
+pub mod project_config;
 pub mod queue;
 pub mod request;
+pub mod status;
 
+pub use cancel::{
+    cancel_build_job, cancelled_error_to_error_data, shutting_down_error_to_error_data,
+    CancelBuildJobRequest, CancelBuildJobResponse,
+};
+pub use clean::{
+    run_clean, CleanVisionOsProjectRequest, CleanVisionOsProjectResponse, CLEAN_TOOL_ID,
+};
+pub use constraints::{
+    describe_build_constraints, DescribeBuildConstraintsRequest, DescribeBuildConstraintsResponse,
+    FieldLengths, ListLimit,
+};
+pub use deadline::{
+    extend_build_deadline, ExtendBuildDeadlineRequest, ExtendBuildDeadlineResponse,
+};
 pub use executor::{
-    run_build, runtime_error_to_error_data, validation_error_to_error_data,
-    BuildVisionOsAppResponse,
+    plan_build, run_archive, run_build, runtime_error_to_error_data,
+    validation_error_to_error_data, BuildPlan, BuildVisionOsAppResponse,
+};
+pub use monitor::{DeadlineExtensionError, Heartbeat, JobMonitor, PhaseTiming, TimeoutWarning};
+pub use project_config::{load_project_config, ProjectConfig, PROJECT_CONFIG_FILE};
+pub use queue::{
+    CancelOutcome, JobFollower, JobLeader, JobPriority, JobRole, JobTicket, QueueMetrics,
+    QueueSnapshot, RootLimit, VisionOsJobQueue,
 };
-pub use queue::{JobTicket, VisionOsJobQueue};
 pub use request::{
-    default_destination, BuildConfiguration, BuildRequestValidationError, VisionOsBuildRequest,
+    BuildConfiguration, BuildRequestValidationError, ProjectKind, VisionOsBuildRequest,
     ALLOWED_ENV_OVERRIDES, ALLOWED_EXTRA_ARGS,
 };
+pub use status::{
+    get_queue_status, GetQueueStatusRequest, QueueStatusResponse, QueuedJobStatus, RunningJobStatus,
+};
 
 pub const BUILD_TOOL_ID: &str = "build_visionos_app";
+pub const ARCHIVE_TOOL_ID: &str = "archive_visionos_app";
+pub const CANCEL_TOOL_ID: &str = "cancel_build_job";
+pub const EXTEND_DEADLINE_TOOL_ID: &str = "extend_build_deadline";
+pub const QUEUE_STATUS_TOOL_ID: &str = "get_queue_status";
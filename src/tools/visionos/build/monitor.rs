@@ -0,0 +1,326 @@
+//! Deadline tracking and timeout warnings for queued visionOS jobs.
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use thiserror::Error;
+use tokio::{sync::watch, time::Instant};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::lib::retry::BuildAttempt;
+
+/// Emitted once a running job has used `percent` of its deadline.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TimeoutWarning {
+    pub job_id: String,
+    pub elapsed_secs: u64,
+    pub deadline_secs: u64,
+    pub percent: u8,
+    /// Last build phase seen in `xcodebuild` output (`compile`, `link`, ...).
+    pub phase: Option<String>,
+}
+
+/// Periodic sign of life for a running job, so clients can tell slow from hung.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Heartbeat {
+    pub job_id: String,
+    pub elapsed_secs: u64,
+    /// Current phase; `package` while the artifact is zipped and hashed.
+    pub phase: Option<String>,
+}
+
+/// Build phase seen in `xcodebuild` output and when it started.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Why a deadline extension was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum DeadlineExtensionError {
+    #[error("job has not started running")]
+    NotRunning,
+    #[error("extended deadline would exceed the configured maximum")]
+    LimitExceeded { current: Duration, max: Duration },
+}
+
+/// Live view of one job, shared between the executor and the server.
+#[derive(Debug, Clone)]
+pub struct JobMonitor {
+    inner: Arc<JobMonitorInner>,
+}
+
+#[derive(Debug)]
+struct JobMonitorInner {
+    job_id: Uuid,
+    phase: Mutex<Option<String>>,
+    /// Every phase change, oldest first; kept for `export_job_trace`.
+    phases: Mutex<Vec<PhaseTiming>>,
+    /// `xcodebuild` runs of the job, oldest first; more than one when `visionos.retry` kicked in.
+    attempts: Mutex<Vec<BuildAttempt>>,
+    warning: watch::Sender<Option<TimeoutWarning>>,
+    /// Current deadline; `None` until [`JobMonitor::run_with_deadline`] starts.
+    deadline: watch::Sender<Option<Duration>>,
+}
+
+impl JobMonitor {
+    pub fn new(job_id: Uuid) -> Self {
+        Self {
+            inner: Arc::new(JobMonitorInner {
+                job_id,
+                phase: Mutex::new(None),
+                phases: Mutex::new(Vec::new()),
+                attempts: Mutex::new(Vec::new()),
+                warning: watch::Sender::new(None),
+                deadline: watch::Sender::new(None),
+            }),
+        }
+    }
+
+    /// Record the build phase currently reported by `xcodebuild`.
+    pub fn set_phase(&self, phase: &str) {
+        let mut current = self.inner.phase.lock().expect("phase lock");
+        if current.as_deref() == Some(phase) {
+            return;
+        }
+        *current = Some(phase.to_string());
+        self.inner
+            .phases
+            .lock()
+            .expect("phases lock")
+            .push(PhaseTiming {
+                phase: phase.to_string(),
+                started_at: Utc::now(),
+            });
+    }
+
+    pub fn phase(&self) -> Option<String> {
+        self.inner.phase.lock().expect("phase lock").clone()
+    }
+
+    /// Phase changes recorded so far, oldest first.
+    pub fn phase_timings(&self) -> Vec<PhaseTiming> {
+        self.inner.phases.lock().expect("phases lock").clone()
+    }
+
+    pub fn record_attempt(&self, attempt: BuildAttempt) {
+        self.inner
+            .attempts
+            .lock()
+            .expect("attempts lock")
+            .push(attempt);
+    }
+
+    /// `xcodebuild` runs recorded so far, oldest first.
+    pub fn attempts(&self) -> Vec<BuildAttempt> {
+        self.inner.attempts.lock().expect("attempts lock").clone()
+    }
+
+    /// Heartbeat for a job that has been running for `elapsed`.
+    pub fn heartbeat(&self, elapsed: Duration) -> Heartbeat {
+        Heartbeat {
+            job_id: self.inner.job_id.to_string(),
+            elapsed_secs: elapsed.as_secs(),
+            phase: self.phase(),
+        }
+    }
+
+    /// Receiver that observes the timeout warning once it is published.
+    pub fn subscribe(&self) -> watch::Receiver<Option<TimeoutWarning>> {
+        self.inner.warning.subscribe()
+    }
+
+    /// Drive `job` until it finishes or `deadline` elapses, warning at `warning_percent`.
+    ///
+    /// The deadline can be pushed back with [`Self::extend_deadline`] while the job runs. Returns
+    /// the deadline in effect when it is reached.
+    pub async fn run_with_deadline<F: Future>(
+        &self,
+        deadline: Duration,
+        warning_percent: u8,
+        job: F,
+    ) -> Result<F::Output, Duration> {
+        let started = Instant::now();
+        let mut deadline_rx = self.inner.deadline.subscribe();
+        self.inner.deadline.send_replace(Some(deadline));
+        deadline_rx.mark_unchanged();
+        let mut deadline = deadline;
+        let mut warned = warning_percent == 0;
+
+        tokio::pin!(job);
+        loop {
+            let deadline_at = started + deadline;
+            let warn_at = started + deadline.mul_f64(f64::from(warning_percent) / 100.0);
+            tokio::select! {
+                output = &mut job => return Ok(output),
+                _ = tokio::time::sleep_until(deadline_at) => return Err(deadline),
+                _ = tokio::time::sleep_until(warn_at), if !warned => {
+                    warned = true;
+                    self.publish_warning(started.elapsed(), deadline, warning_percent);
+                }
+                Ok(()) = deadline_rx.changed() => {
+                    if let Some(extended) = *deadline_rx.borrow_and_update() {
+                        deadline = extended;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add `extra` to the running job's deadline, keeping the total within `max`.
+    ///
+    /// Returns the new deadline.
+    pub fn extend_deadline(
+        &self,
+        extra: Duration,
+        max: Duration,
+    ) -> Result<Duration, DeadlineExtensionError> {
+        let mut result = Err(DeadlineExtensionError::NotRunning);
+        self.inner.deadline.send_if_modified(|deadline| {
+            let Some(current) = *deadline else {
+                return false;
+            };
+            let extended = current + extra;
+            if extended > max {
+                result = Err(DeadlineExtensionError::LimitExceeded { current, max });
+                return false;
+            }
+            *deadline = Some(extended);
+            result = Ok(extended);
+            true
+        });
+        if let Ok(extended) = result {
+            info!(
+                target: "rmcp_sample::visionos",
+                job_id = %self.inner.job_id,
+                deadline_secs = extended.as_secs(),
+                "Extended visionOS job deadline"
+            );
+        }
+        result
+    }
+
+    fn publish_warning(&self, elapsed: Duration, deadline: Duration, percent: u8) {
+        let warning = TimeoutWarning {
+            job_id: self.inner.job_id.to_string(),
+            elapsed_secs: elapsed.as_secs(),
+            deadline_secs: deadline.as_secs(),
+            percent,
+            phase: self.phase(),
+        };
+        warn!(
+            target: "rmcp_sample::visionos",
+            job_id = %warning.job_id,
+            elapsed_secs = warning.elapsed_secs,
+            deadline_secs = warning.deadline_secs,
+            percent = warning.percent,
+            phase = warning.phase.as_deref().unwrap_or("unknown"),
+            "visionOS job is approaching its deadline"
+        );
+        self.inner.warning.send_replace(Some(warning));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn warning_fires_before_deadline_with_current_phase() {
+        let monitor = JobMonitor::new(Uuid::new_v4());
+        let mut warnings = monitor.subscribe();
+        monitor.set_phase("link");
+
+        let output = monitor
+            .run_with_deadline(Duration::from_secs(2), 10, async {
+                tokio::time::sleep(Duration::from_millis(600)).await;
+                "done"
+            })
+            .await;
+
+        assert_eq!(output, Ok("done"));
+        assert!(warnings.has_changed().expect("sender alive"));
+        let warning = warnings.borrow_and_update().clone().expect("warning");
+        assert_eq!(warning.deadline_secs, 2);
+        assert_eq!(warning.percent, 10);
+        assert_eq!(warning.phase.as_deref(), Some("link"));
+    }
+
+    #[test]
+    fn phase_timings_record_each_phase_change_once() {
+        let monitor = JobMonitor::new(Uuid::new_v4());
+        monitor.set_phase("compile");
+        monitor.set_phase("compile");
+        monitor.set_phase("link");
+
+        let phases: Vec<String> = monitor
+            .phase_timings()
+            .into_iter()
+            .map(|timing| timing.phase)
+            .collect();
+
+        assert_eq!(phases, ["compile", "link"]);
+        assert_eq!(monitor.phase().as_deref(), Some("link"));
+    }
+
+    #[tokio::test]
+    async fn deadline_returns_none_and_zero_percent_disables_warning() {
+        let monitor = JobMonitor::new(Uuid::new_v4());
+        let warnings = monitor.subscribe();
+
+        let output = monitor
+            .run_with_deadline(
+                Duration::from_millis(50),
+                0,
+                tokio::time::sleep(Duration::from_secs(60)),
+            )
+            .await;
+
+        assert_eq!(output, Err(Duration::from_millis(50)));
+        assert!(!warnings.has_changed().expect("sender alive"));
+    }
+
+    #[tokio::test]
+    async fn extend_deadline_lets_running_job_finish_within_maximum() {
+        let monitor = JobMonitor::new(Uuid::new_v4());
+        let max = Duration::from_millis(600);
+        assert_eq!(
+            monitor.extend_deadline(Duration::from_millis(100), max),
+            Err(DeadlineExtensionError::NotRunning)
+        );
+
+        let extender = {
+            let monitor = monitor.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                let extended = monitor.extend_deadline(Duration::from_millis(300), max);
+                let refused = monitor.extend_deadline(Duration::from_millis(300), max);
+                (extended, refused)
+            }
+        };
+        let (output, (extended, refused)) = tokio::join!(
+            monitor.run_with_deadline(
+                Duration::from_millis(200),
+                0,
+                tokio::time::sleep(Duration::from_millis(350)),
+            ),
+            extender
+        );
+
+        assert_eq!(output, Ok(()));
+        assert_eq!(extended, Ok(Duration::from_millis(500)));
+        assert_eq!(
+            refused,
+            Err(DeadlineExtensionError::LimitExceeded {
+                current: Duration::from_millis(500),
+                max,
+            })
+        );
+    }
+}
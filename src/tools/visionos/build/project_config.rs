@@ -0,0 +1,119 @@
+//! `.seiro.toml`: build defaults checked in next to the project, read when
+//! `visionos.project_config` is enabled. The request and its preset win over the file, and the
+//! file wins over the server config.
+use std::{fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::{lib::xcodebuild::PACKAGE_MANIFEST, server::config::VisionOsConfig};
+
+use super::{BuildConfiguration, BuildRequestValidationError, ALLOWED_EXTRA_ARGS};
+
+/// File name looked up in the project directory.
+pub const PROJECT_CONFIG_FILE: &str = ".seiro.toml";
+const MAX_PROJECT_CONFIG_BYTES: u64 = 16 * 1024;
+
+/// Contents of `.seiro.toml`. Unknown keys are rejected so a typo does not silently build
+/// with the server defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub scheme: Option<String>,
+    pub configuration: Option<BuildConfiguration>,
+    pub destination: Option<String>,
+    /// Placed before the preset's and the request's own `extra_args`.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    /// Used when the request sets no `artifact_include`.
+    #[serde(default)]
+    pub artifact_include: Vec<String>,
+    /// Used when the request sets no `artifact_exclude`.
+    #[serde(default)]
+    pub artifact_exclude: Vec<String>,
+}
+
+/// Directory holding the project: the parent of an `.xcodeproj`, `.xcworkspace`, or
+/// `Package.swift`, otherwise `project_path` itself.
+pub fn project_directory(project_path: &Path) -> &Path {
+    let is_bundle = matches!(
+        project_path.extension().and_then(|ext| ext.to_str()),
+        Some("xcodeproj") | Some("xcworkspace")
+    );
+    let is_manifest =
+        project_path.file_name().and_then(|name| name.to_str()) == Some(PACKAGE_MANIFEST);
+    if is_bundle || is_manifest {
+        project_path.parent().unwrap_or(project_path)
+    } else {
+        project_path
+    }
+}
+
+/// Read the `.seiro.toml` of `project_path`. `None` when the option is off, the file does not
+/// exist, or the project is outside the allowlist (which [`super::VisionOsBuildRequest::validate`]
+/// reports on its own).
+pub fn load_project_config(
+    project_path: &Path,
+    config: &VisionOsConfig,
+) -> Result<Option<ProjectConfig>, BuildRequestValidationError> {
+    if !config.project_config || !crate::lib::paths::is_nonempty_absolute(project_path) {
+        return Ok(None);
+    }
+    let allows = |path: &Path| {
+        config.allowed_paths.is_empty()
+            || crate::lib::visionos::is_allowed_path(path, &config.allowed_paths)
+    };
+    if !allows(project_path) {
+        return Ok(None);
+    }
+    let path = project_directory(project_path).join(PROJECT_CONFIG_FILE);
+    let Ok(metadata) = fs::metadata(&path) else {
+        return Ok(None);
+    };
+    let invalid = |message: String| BuildRequestValidationError::ProjectConfigInvalid {
+        path: path.clone(),
+        message,
+    };
+    if !allows(&path) {
+        return Err(invalid("the file resolves outside the allowlist".into()));
+    }
+    if !metadata.is_file() || metadata.len() > MAX_PROJECT_CONFIG_BYTES {
+        return Err(invalid(format!(
+            "expected a file of at most {MAX_PROJECT_CONFIG_BYTES} bytes"
+        )));
+    }
+    let contents = fs::read_to_string(&path).map_err(|err| invalid(err.to_string()))?;
+    let project: ProjectConfig =
+        toml::from_str(&contents).map_err(|err| invalid(err.message().to_string()))?;
+    validate(&project).map_err(invalid)?;
+    Ok(Some(project))
+}
+
+fn validate(project: &ProjectConfig) -> Result<(), String> {
+    for (field, value) in [
+        ("scheme", &project.scheme),
+        ("destination", &project.destination),
+    ] {
+        if value.as_ref().is_some_and(|value| value.trim().is_empty()) {
+            return Err(format!("`{field}` must not be empty"));
+        }
+    }
+    if let Some(arg) = project
+        .extra_args
+        .iter()
+        .find(|arg| !ALLOWED_EXTRA_ARGS.contains(&arg.as_str()))
+    {
+        return Err(format!("extra_args contains a disallowed value `{arg}`"));
+    }
+    for (field, patterns) in [
+        ("artifact_include", &project.artifact_include),
+        ("artifact_exclude", &project.artifact_exclude),
+    ] {
+        if let Some(pattern) = patterns
+            .iter()
+            .find(|pattern| pattern.trim().is_empty() || glob::Pattern::new(pattern).is_err())
+        {
+            return Err(format!("{field} `{pattern}` is not a glob pattern"));
+        }
+    }
+    Ok(())
+}
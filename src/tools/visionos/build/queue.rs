@@ -1,25 +1,276 @@
-use std::{collections::VecDeque, sync::Arc};
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 use chrono::{DateTime, Utc};
-use tokio::sync::{Mutex, Notify};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{oneshot, watch, Mutex, Notify};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
 use uuid::Uuid;
 
+use super::JobMonitor;
+
+/// Completed job durations kept for wait estimates.
+const DURATION_HISTORY_LEN: usize = 20;
+
+/// Scheduling class of a job. Interactive jobs are admitted before any waiting batch job.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    /// Someone is waiting on the result.
+    #[default]
+    Interactive,
+    /// Background work such as nightly archives; runs when no interactive job is waiting.
+    Batch,
+}
+
+impl JobPriority {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Interactive => "interactive",
+            Self::Batch => "batch",
+        }
+    }
+
+    fn rank(self) -> u8 {
+        match self {
+            Self::Interactive => 0,
+            Self::Batch => 1,
+        }
+    }
+}
+
 /// Ticket that identifies a build job.
 #[derive(Debug, Clone)]
 pub struct JobTicket {
     pub job_id: Uuid,
+    pub priority: JobPriority,
     pub enqueued_at: DateTime<Utc>,
+    /// Fired by [`VisionOsJobQueue::cancel`].
+    pub cancellation: CancellationToken,
+    /// Deadline and phase tracking for the running job.
+    pub monitor: JobMonitor,
+    /// Expected run time from the duration history of the job's scheme, if it has one.
+    pub estimated_duration: Option<Duration>,
+}
+
+/// Cap on running jobs for projects under one labeled workspace root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootLimit {
+    pub label: String,
+    pub max_concurrent: usize,
+}
+
+/// How a job was stopped by `cancel_build_job`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelOutcome {
+    /// Removed from the queue before it started.
+    CancelledQueued,
+    /// Stopped while `xcodebuild` was running.
+    CancelledRunning,
+}
+
+impl CancelOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::CancelledQueued => "cancelled_queued",
+            Self::CancelledRunning => "cancelled_running",
+        }
+    }
+}
+
+/// Counters describing queue activity since startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QueueMetrics {
+    pub pending_jobs: usize,
+    pub cancelled_queued: u64,
+    pub cancelled_running: u64,
+}
+
+/// Point-in-time view of the queue, in admission order.
+#[derive(Debug, Clone)]
+pub struct QueueSnapshot {
+    pub slots: usize,
+    pub running: Vec<RunningJobSnapshot>,
+    pub queued: Vec<QueuedJobSnapshot>,
+    /// Mean duration of recently finished (not cancelled) jobs.
+    pub average_duration: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RunningJobSnapshot {
+    pub ticket: JobTicket,
+    pub project: PathBuf,
+    pub started_at: DateTime<Utc>,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueuedJobSnapshot {
+    pub ticket: JobTicket,
+    pub project: PathBuf,
+}
+
+/// What a caller submitting a fingerprinted job should do; see
+/// [`VisionOsJobQueue::join_or_lead`].
+pub enum JobRole<T> {
+    /// No identical job is in flight: run it and hand the result to [`JobLeader::publish`].
+    Leader(JobLeader<T>),
+    /// An identical job is already queued or running: wait for its result.
+    Follower(JobFollower<T>),
+}
+
+/// Owner of an in-flight fingerprint. Dropping it without publishing releases the fingerprint
+/// and tells followers that no result is coming.
+pub struct JobLeader<T> {
+    queue: VisionOsJobQueue,
+    fingerprint: String,
+    job_id: Uuid,
+    result: watch::Sender<Option<T>>,
+}
+
+impl<T> JobLeader<T> {
+    /// Hand `result` to every follower and release the fingerprint.
+    pub fn publish(self, result: T) {
+        self.result.send_replace(Some(result));
+    }
 }
 
-/// Single job queue shared by the visionOS build tools.
+impl<T> Drop for JobLeader<T> {
+    fn drop(&mut self) {
+        let mut in_flight = self.queue.inner.in_flight.lock().expect("in-flight lock");
+        if in_flight
+            .get(&self.fingerprint)
+            .is_some_and(|job| job.job_id == self.job_id)
+        {
+            in_flight.remove(&self.fingerprint);
+        }
+    }
+}
+
+/// Caller attached to another caller's job.
+pub struct JobFollower<T> {
+    /// The leader's job, shared by every caller that submitted the same request.
+    pub job_id: Uuid,
+    result: watch::Receiver<Option<T>>,
+}
+
+impl<T: Clone> JobFollower<T> {
+    /// The leader's result; `None` when the leader stopped without one.
+    pub async fn result(mut self) -> Option<T> {
+        self.result
+            .wait_for(Option::is_some)
+            .await
+            .ok()
+            .and_then(|result| result.clone())
+    }
+}
+
+/// Leader of a fingerprint; `result` holds its `watch::Sender<Option<T>>`.
+struct InFlightJob {
+    job_id: Uuid,
+    result: Box<dyn Any + Send + Sync>,
+}
+
+/// Job scheduler shared by the visionOS build tools.
+///
+/// Up to `slots` jobs run at once; jobs for the same project never overlap, since they would share
+/// DerivedData, and jobs under a [`RootLimit`] never exceed its cap. Waiting jobs are admitted by
+/// priority, then in arrival order.
 #[derive(Clone)]
 pub struct VisionOsJobQueue {
     inner: Arc<VisionOsJobQueueInner>,
 }
 
 struct VisionOsJobQueueInner {
-    queue: Mutex<VecDeque<JobTicket>>,
+    jobs: Mutex<JobSet>,
+    slots: usize,
     notify: Notify,
+    cancelled_queued: AtomicU64,
+    cancelled_running: AtomicU64,
+    closed: AtomicBool,
+    /// Jobs that identical requests attach to, keyed by request fingerprint.
+    in_flight: std::sync::Mutex<HashMap<String, InFlightJob>>,
+}
+
+#[derive(Default)]
+struct JobSet {
+    /// Sorted by priority, then arrival.
+    queued: Vec<QueuedJob>,
+    running: Vec<RunningJob>,
+    durations: VecDeque<Duration>,
+}
+
+struct QueuedJob {
+    ticket: JobTicket,
+    project: PathBuf,
+    root: Option<RootLimit>,
+    admit: oneshot::Sender<()>,
+}
+
+struct RunningJob {
+    ticket: JobTicket,
+    project: PathBuf,
+    root: Option<RootLimit>,
+    started_at: DateTime<Utc>,
+    started: Instant,
+}
+
+impl JobSet {
+    fn len(&self) -> usize {
+        self.queued.len() + self.running.len()
+    }
+
+    /// Start waiting jobs while slots are free, skipping jobs whose project is already building
+    /// or whose root is at its limit.
+    fn admit(&mut self, slots: usize) {
+        let mut index = 0;
+        while self.running.len() < slots && index < self.queued.len() {
+            let candidate = &self.queued[index];
+            let project_busy = self
+                .running
+                .iter()
+                .any(|running| running.project == candidate.project);
+            let root_full = candidate.root.as_ref().is_some_and(|root| {
+                self.running
+                    .iter()
+                    .filter(|running| {
+                        running
+                            .root
+                            .as_ref()
+                            .is_some_and(|other| other.label == root.label)
+                    })
+                    .count()
+                    >= root.max_concurrent
+            });
+            if project_busy || root_full {
+                index += 1;
+                continue;
+            }
+            let job = self.queued.remove(index);
+            if job.admit.send(()).is_err() {
+                // The caller stopped waiting (its request was dropped); don't hold a slot for it.
+                continue;
+            }
+            self.running.push(RunningJob {
+                ticket: job.ticket,
+                project: job.project,
+                root: job.root,
+                started_at: Utc::now(),
+                started: Instant::now(),
+            });
+        }
+    }
 }
 
 impl Default for VisionOsJobQueue {
@@ -29,54 +280,527 @@ impl Default for VisionOsJobQueue {
 }
 
 impl VisionOsJobQueue {
-    /// Create an empty job queue.
+    /// Create an empty queue that runs one job at a time.
     pub fn new() -> Self {
+        Self::with_slots(1)
+    }
+
+    /// Create an empty queue that runs up to `slots` jobs at once.
+    pub fn with_slots(slots: usize) -> Self {
         Self {
             inner: Arc::new(VisionOsJobQueueInner {
-                queue: Mutex::new(VecDeque::new()),
+                jobs: Mutex::new(JobSet::default()),
+                slots: slots.max(1),
                 notify: Notify::new(),
+                cancelled_queued: AtomicU64::new(0),
+                cancelled_running: AtomicU64::new(0),
+                closed: AtomicBool::new(false),
+                in_flight: std::sync::Mutex::new(HashMap::new()),
             }),
         }
     }
 
-    /// Enqueue a job and wait until it reaches the front.
-    pub async fn wait_for_turn(&self, job_id: Uuid) -> JobTicket {
+    /// Enqueue a job for `project` and wait until it may run.
+    ///
+    /// Returns `None` when the job was cancelled while still queued.
+    pub async fn wait_for_turn(
+        &self,
+        job_id: Uuid,
+        project: &Path,
+        priority: JobPriority,
+    ) -> Option<JobTicket> {
+        self.wait_for_turn_in_root(job_id, project, priority, None, None)
+            .await
+    }
+
+    /// Like [`Self::wait_for_turn`], also holding the job back while `root` is at its limit.
+    /// `estimated_duration` is reported by `get_queue_status` and used for wait estimates.
+    pub async fn wait_for_turn_in_root(
+        &self,
+        job_id: Uuid,
+        project: &Path,
+        priority: JobPriority,
+        root: Option<RootLimit>,
+        estimated_duration: Option<Duration>,
+    ) -> Option<JobTicket> {
         let ticket = JobTicket {
             job_id,
+            priority,
             enqueued_at: Utc::now(),
+            cancellation: CancellationToken::new(),
+            monitor: JobMonitor::new(job_id),
+            estimated_duration,
         };
+        let (admit, admitted) = oneshot::channel();
         {
-            let mut queue = self.inner.queue.lock().await;
-            queue.push_back(ticket.clone());
+            let mut jobs = self.inner.jobs.lock().await;
+            let position = jobs
+                .queued
+                .iter()
+                .position(|queued| queued.ticket.priority.rank() > priority.rank())
+                .unwrap_or(jobs.queued.len());
+            jobs.queued.insert(
+                position,
+                QueuedJob {
+                    ticket: ticket.clone(),
+                    project: project.to_path_buf(),
+                    root,
+                    admit,
+                },
+            );
+            jobs.admit(self.inner.slots);
         }
 
-        loop {
-            {
-                let queue = self.inner.queue.lock().await;
-                if matches!(queue.front(), Some(front) if front.job_id == job_id) {
-                    break;
-                }
+        tokio::select! {
+            biased;
+            admitted = admitted => admitted.ok().map(|()| ticket),
+            _ = ticket.cancellation.cancelled() => {
+                // `cancel` may have hit the job right after it was admitted.
+                let jobs = self.inner.jobs.lock().await;
+                jobs.running
+                    .iter()
+                    .any(|running| running.ticket.job_id == job_id)
+                    .then_some(ticket)
             }
-            self.inner.notify.notified().await;
         }
+    }
 
-        ticket
+    /// Attach to the in-flight job with `fingerprint`, or register `job_id` as its leader.
+    ///
+    /// Callers that submit the same work while the leader is queued or running become followers
+    /// and receive the leader's result instead of running a second job.
+    pub fn join_or_lead<T>(&self, fingerprint: &str, job_id: Uuid) -> JobRole<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let mut in_flight = self.inner.in_flight.lock().expect("in-flight lock");
+        if let Some(job) = in_flight.get(fingerprint) {
+            if let Some(result) = job.result.downcast_ref::<watch::Sender<Option<T>>>() {
+                return JobRole::Follower(JobFollower {
+                    job_id: job.job_id,
+                    result: result.subscribe(),
+                });
+            }
+        }
+        let result = watch::Sender::new(None);
+        in_flight.insert(
+            fingerprint.to_string(),
+            InFlightJob {
+                job_id,
+                result: Box::new(result.clone()),
+            },
+        );
+        JobRole::Leader(JobLeader {
+            queue: self.clone(),
+            fingerprint: fingerprint.to_string(),
+            job_id,
+            result,
+        })
     }
 
-    /// Notify completion and wake the next job.
+    /// Release the job's slot, admit the next jobs, and wake anyone waiting for the queue to drain.
     pub async fn finish_job(&self, job_id: Uuid) {
         {
-            let mut queue = self.inner.queue.lock().await;
-            if matches!(queue.front(), Some(front) if front.job_id == job_id) {
-                queue.pop_front();
+            let mut jobs = self.inner.jobs.lock().await;
+            if let Some(index) = jobs
+                .running
+                .iter()
+                .position(|running| running.ticket.job_id == job_id)
+            {
+                let finished = jobs.running.remove(index);
+                if !finished.ticket.cancellation.is_cancelled() {
+                    if jobs.durations.len() == DURATION_HISTORY_LEN {
+                        jobs.durations.pop_front();
+                    }
+                    jobs.durations.push_back(finished.started.elapsed());
+                }
             }
+            jobs.admit(self.inner.slots);
         }
         self.inner.notify.notify_waiters();
     }
 
-    /// Return the number of pending jobs (used for telemetry).
+    /// Whether `job_id` is queued or running.
+    pub async fn contains(&self, job_id: Uuid) -> bool {
+        let jobs = self.inner.jobs.lock().await;
+        jobs.queued
+            .iter()
+            .any(|queued| queued.ticket.job_id == job_id)
+            || jobs
+                .running
+                .iter()
+                .any(|running| running.ticket.job_id == job_id)
+    }
+
+    /// Monitor of `job_id` when the job is currently running.
+    pub async fn running_monitor(&self, job_id: Uuid) -> Option<JobMonitor> {
+        let jobs = self.inner.jobs.lock().await;
+        jobs.running
+            .iter()
+            .find(|running| running.ticket.job_id == job_id)
+            .map(|running| running.ticket.monitor.clone())
+    }
+
+    /// Cancel a queued or running job; `None` when the job is not in the queue.
+    ///
+    /// Queued jobs are dropped from the queue immediately. Running jobs keep their slot until the
+    /// owner observes the cancellation and calls [`Self::finish_job`].
+    pub async fn cancel(&self, job_id: Uuid) -> Option<CancelOutcome> {
+        let outcome = {
+            let mut jobs = self.inner.jobs.lock().await;
+            if let Some(running) = jobs
+                .running
+                .iter()
+                .find(|running| running.ticket.job_id == job_id)
+            {
+                running.ticket.cancellation.cancel();
+                CancelOutcome::CancelledRunning
+            } else {
+                let position = jobs
+                    .queued
+                    .iter()
+                    .position(|queued| queued.ticket.job_id == job_id)?;
+                let queued = jobs.queued.remove(position);
+                queued.ticket.cancellation.cancel();
+                CancelOutcome::CancelledQueued
+            }
+        };
+        self.inner.notify.notify_waiters();
+        let counter = match outcome {
+            CancelOutcome::CancelledQueued => &self.inner.cancelled_queued,
+            CancelOutcome::CancelledRunning => &self.inner.cancelled_running,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        let metrics = self.metrics().await;
+        info!(
+            target: "rmcp_sample::visionos",
+            job_id = %job_id,
+            outcome = outcome.as_str(),
+            pending_jobs = metrics.pending_jobs,
+            cancelled_queued = metrics.cancelled_queued,
+            cancelled_running = metrics.cancelled_running,
+            "Cancelled visionOS job"
+        );
+        Some(outcome)
+    }
+
+    /// Stop accepting new jobs; jobs already queued still run.
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(Ordering::SeqCst)
+    }
+
+    /// Wait until no job is queued or running.
+    pub async fn wait_until_idle(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            if self.pending_jobs().await == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Return the number of queued and running jobs (used for telemetry).
     pub async fn pending_jobs(&self) -> usize {
-        let queue = self.inner.queue.lock().await;
-        queue.len()
+        self.inner.jobs.lock().await.len()
+    }
+
+    /// Running and queued jobs plus the recent average job duration.
+    pub async fn snapshot(&self) -> QueueSnapshot {
+        let jobs = self.inner.jobs.lock().await;
+        let average_duration = (!jobs.durations.is_empty())
+            .then(|| jobs.durations.iter().sum::<Duration>() / jobs.durations.len() as u32);
+        QueueSnapshot {
+            slots: self.inner.slots,
+            running: jobs
+                .running
+                .iter()
+                .map(|running| RunningJobSnapshot {
+                    ticket: running.ticket.clone(),
+                    project: running.project.clone(),
+                    started_at: running.started_at,
+                    elapsed: running.started.elapsed(),
+                })
+                .collect(),
+            queued: jobs
+                .queued
+                .iter()
+                .map(|queued| QueuedJobSnapshot {
+                    ticket: queued.ticket.clone(),
+                    project: queued.project.clone(),
+                })
+                .collect(),
+            average_duration,
+        }
+    }
+
+    /// Snapshot of queue counters.
+    pub async fn metrics(&self) -> QueueMetrics {
+        QueueMetrics {
+            pending_jobs: self.pending_jobs().await,
+            cancelled_queued: self.inner.cancelled_queued.load(Ordering::Relaxed),
+            cancelled_running: self.inner.cancelled_running.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn cancel_distinguishes_queued_and_running_jobs() {
+        let queue = VisionOsJobQueue::new();
+        let running = Uuid::new_v4();
+        let queued = Uuid::new_v4();
+
+        let project = Path::new("/tmp/VisionApp.xcodeproj");
+
+        let ticket = queue
+            .wait_for_turn(running, project, JobPriority::Interactive)
+            .await
+            .expect("free slot");
+        let waiter = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                queue
+                    .wait_for_turn(queued, project, JobPriority::Interactive)
+                    .await
+            })
+        };
+        while queue.pending_jobs().await < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(
+            queue.cancel(queued).await,
+            Some(CancelOutcome::CancelledQueued)
+        );
+        assert!(waiter.await.expect("waiter task").is_none());
+        assert_eq!(
+            queue.cancel(running).await,
+            Some(CancelOutcome::CancelledRunning)
+        );
+        assert!(ticket.cancellation.is_cancelled());
+        queue.finish_job(running).await;
+
+        assert_eq!(queue.cancel(Uuid::new_v4()).await, None);
+        tokio::time::timeout(Duration::from_secs(1), queue.wait_until_idle())
+            .await
+            .expect("queue drains once every job finished");
+        assert_eq!(
+            queue.metrics().await,
+            QueueMetrics {
+                pending_jobs: 0,
+                cancelled_queued: 1,
+                cancelled_running: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn slots_run_other_projects_in_parallel_but_serialize_one_project() {
+        let queue = VisionOsJobQueue::with_slots(2);
+        let app = Path::new("/tmp/VisionApp.xcodeproj");
+        let toolbox = Path::new("/tmp/VisionToolbox.xcodeproj");
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+
+        queue
+            .wait_for_turn(first, app, JobPriority::Interactive)
+            .await
+            .expect("free slot");
+        queue
+            .wait_for_turn(second, toolbox, JobPriority::Interactive)
+            .await
+            .expect("second slot for another project");
+        assert!(queue.running_monitor(second).await.is_some());
+
+        let same_project = Uuid::new_v4();
+        let waiter = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                queue
+                    .wait_for_turn(same_project, app, JobPriority::Interactive)
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(queue.running_monitor(same_project).await.is_none());
+
+        queue.finish_job(second).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(
+            queue.running_monitor(same_project).await.is_none(),
+            "a free slot is not enough while the project is still building"
+        );
+
+        queue.finish_job(first).await;
+        let ticket = tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("job starts once its project is free")
+            .expect("waiter task")
+            .expect("not cancelled");
+        assert_eq!(ticket.job_id, same_project);
+    }
+
+    #[tokio::test]
+    async fn root_limit_caps_jobs_under_one_root() {
+        let queue = VisionOsJobQueue::with_slots(3);
+        let ci = || {
+            Some(RootLimit {
+                label: "ci".into(),
+                max_concurrent: 1,
+            })
+        };
+        let first = Uuid::new_v4();
+        queue
+            .wait_for_turn_in_root(
+                first,
+                Path::new("/ci/VisionApp.xcodeproj"),
+                JobPriority::Interactive,
+                ci(),
+                None,
+            )
+            .await
+            .expect("free slot");
+
+        let second = Uuid::new_v4();
+        let waiter = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                queue
+                    .wait_for_turn_in_root(
+                        second,
+                        Path::new("/ci/VisionToolbox.xcodeproj"),
+                        JobPriority::Interactive,
+                        ci(),
+                        None,
+                    )
+                    .await
+            })
+        };
+        let personal = Uuid::new_v4();
+        queue
+            .wait_for_turn(
+                personal,
+                Path::new("/home/VisionApp.xcodeproj"),
+                JobPriority::Interactive,
+            )
+            .await
+            .expect("other roots still get free slots");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(
+            queue.running_monitor(second).await.is_none(),
+            "the ci root is at its limit"
+        );
+
+        queue.finish_job(first).await;
+        let ticket = tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("job starts once the root has room")
+            .expect("waiter task")
+            .expect("not cancelled");
+        assert_eq!(ticket.job_id, second);
+    }
+
+    #[tokio::test]
+    async fn interactive_jobs_overtake_waiting_batch_jobs() {
+        let queue = VisionOsJobQueue::new();
+        let project = Path::new("/tmp/VisionApp.xcodeproj");
+        let running = Uuid::new_v4();
+        let batch = Uuid::new_v4();
+        let interactive = Uuid::new_v4();
+
+        queue
+            .wait_for_turn(running, project, JobPriority::Batch)
+            .await
+            .expect("free slot");
+        let batch_waiter = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                queue
+                    .wait_for_turn(batch, project, JobPriority::Batch)
+                    .await
+            })
+        };
+        while queue.pending_jobs().await < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let interactive_waiter = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                queue
+                    .wait_for_turn(interactive, project, JobPriority::Interactive)
+                    .await
+            })
+        };
+        while queue.pending_jobs().await < 3 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let queued: Vec<Uuid> = queue
+            .snapshot()
+            .await
+            .queued
+            .iter()
+            .map(|queued| queued.ticket.job_id)
+            .collect();
+        assert_eq!(queued, vec![interactive, batch]);
+
+        queue.finish_job(running).await;
+        interactive_waiter
+            .await
+            .expect("waiter task")
+            .expect("interactive job starts first");
+        assert!(queue.running_monitor(batch).await.is_none());
+        queue.finish_job(interactive).await;
+        batch_waiter
+            .await
+            .expect("waiter task")
+            .expect("batch job starts last");
+
+        let snapshot = queue.snapshot().await;
+        assert_eq!(snapshot.running.len(), 1);
+        assert!(snapshot.average_duration.is_some());
+    }
+
+    #[tokio::test]
+    async fn identical_requests_share_the_leaders_result() {
+        let queue = VisionOsJobQueue::new();
+        let leader_id = Uuid::new_v4();
+
+        let JobRole::Leader(leader) = queue.join_or_lead::<u32>("fingerprint", leader_id) else {
+            panic!("first caller leads");
+        };
+        let JobRole::Follower(follower) = queue.join_or_lead::<u32>("fingerprint", Uuid::new_v4())
+        else {
+            panic!("second caller follows");
+        };
+        assert_eq!(follower.job_id, leader_id);
+        assert!(matches!(
+            queue.join_or_lead::<u32>("other", Uuid::new_v4()),
+            JobRole::Leader(_)
+        ));
+
+        leader.publish(7);
+        assert_eq!(follower.result().await, Some(7));
+
+        let JobRole::Leader(leader) = queue.join_or_lead::<u32>("fingerprint", Uuid::new_v4())
+        else {
+            panic!("a published fingerprint is released");
+        };
+        let JobRole::Follower(follower) = queue.join_or_lead::<u32>("fingerprint", Uuid::new_v4())
+        else {
+            panic!("second caller follows");
+        };
+        drop(leader);
+        assert_eq!(follower.result().await, None);
     }
 }
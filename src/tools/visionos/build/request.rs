@@ -1,16 +1,38 @@
-use std::{collections::BTreeMap, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use uuid::Uuid;
 
-use crate::server::config::VisionOsConfig;
+use crate::{
+    lib::{
+        devices::{destination_target, is_allowed_device, DestinationTarget},
+        platform::Platform,
+        signing::is_team_id,
+        xcodebuild::{LogFormat, PACKAGE_MANIFEST},
+    },
+    server::config::{BuildPreset, VisionOsConfig},
+};
 
-const MAX_PROJECT_PATH_LEN: usize = 512;
-const MAX_SCHEME_LEN: usize = 128;
-const MAX_DESTINATION_LEN: usize = 256;
-const MAX_EXTRA_ARGS: usize = 5;
-const MAX_EXTRA_ARG_LEN: usize = 64;
+use super::{
+    project_config::{load_project_config, ProjectConfig},
+    JobPriority,
+};
+
+pub(super) const MAX_PROJECT_PATH_LEN: usize = 512;
+pub(super) const MAX_SCHEME_LEN: usize = 128;
+pub(super) const MAX_DESTINATION_LEN: usize = 256;
+pub(super) const MAX_EXTRA_ARGS: usize = 5;
+pub(super) const MAX_EXTRA_ARG_LEN: usize = 64;
+pub(super) const MAX_ARCHITECTURES: usize = 4;
+pub(super) const MAX_ARCHITECTURE_LEN: usize = 16;
+pub(super) const MAX_SIGNING_VALUE_LEN: usize = 128;
+pub(super) const MAX_ARTIFACT_PATTERNS: usize = 8;
+pub(super) const MAX_ARTIFACT_PATTERN_LEN: usize = 128;
 
 /// `xcodebuild` flags allowed in `extra_args`.
 pub const ALLOWED_EXTRA_ARGS: &[&str] = &[
@@ -21,10 +43,16 @@ pub const ALLOWED_EXTRA_ARGS: &[&str] = &[
 ];
 
 /// Environment variables allowed in `env_overrides`.
-pub const ALLOWED_ENV_OVERRIDES: &[&str] = &["NSUnbufferedIO", "CI", "MOCK_XCODEBUILD_BEHAVIOR"];
+pub const ALLOWED_ENV_OVERRIDES: &[&str] = &[
+    "NSUnbufferedIO",
+    "CI",
+    "LANG",
+    "LC_ALL",
+    "MOCK_XCODEBUILD_BEHAVIOR",
+];
 
 /// Build configuration.
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum BuildConfiguration {
     #[serde(alias = "Debug")]
@@ -43,28 +71,298 @@ impl BuildConfiguration {
     }
 }
 
+/// Layout of the project a request builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectKind {
+    /// `.xcodeproj`, or a directory `xcodebuild` resolves to one.
+    XcodeProject,
+    /// `.xcworkspace`, given as `workspace` or `project_path`.
+    Workspace,
+    /// `Package.swift`, or a directory holding one and no Xcode project. Built with
+    /// `xcodebuild -scheme <package scheme>` from the package directory.
+    SwiftPackage,
+}
+
+impl ProjectKind {
+    /// Detect the layout of `path`. A directory holding both an Xcode project and
+    /// `Package.swift` is treated as an Xcode project, matching `xcodebuild`'s own choice.
+    pub fn detect(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("xcodeproj") => return Self::XcodeProject,
+            Some("xcworkspace") => return Self::Workspace,
+            _ => {}
+        }
+        if path.file_name().and_then(|name| name.to_str()) == Some(PACKAGE_MANIFEST) {
+            return Self::SwiftPackage;
+        }
+        let has_xcode_project = std::fs::read_dir(path).is_ok_and(|entries| {
+            entries.flatten().any(|entry| {
+                matches!(
+                    entry.path().extension().and_then(|ext| ext.to_str()),
+                    Some("xcodeproj") | Some("xcworkspace")
+                )
+            })
+        });
+        if !has_xcode_project && path.join(PACKAGE_MANIFEST).is_file() {
+            return Self::SwiftPackage;
+        }
+        Self::XcodeProject
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::XcodeProject => "xcode_project",
+            Self::Workspace => "workspace",
+            Self::SwiftPackage => "swift_package",
+        }
+    }
+}
+
 /// Input for `build_visionos_app`.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct VisionOsBuildRequest {
     pub project_path: PathBuf,
     #[serde(default)]
     pub workspace: Option<PathBuf>,
+    /// Required unless `preset` supplies it.
+    #[serde(default)]
     pub scheme: String,
+    /// Defaults to the preset's configuration, then `debug`.
+    #[serde(default)]
+    pub configuration: Option<BuildConfiguration>,
+    /// Name of a `[[visionos.presets]]` entry whose scheme, configuration, destination, and
+    /// extra_args fill in what the request leaves unset. Its extra_args come first.
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Name of a `[[visionos.xcodes]]` entry whose Developer directory runs the job. Defaults to
+    /// `visionos.xcode_path`.
+    #[serde(default)]
+    pub xcode: Option<String>,
+    /// Target platform; picks the default destination. Defaults to `visionos`.
+    #[serde(default)]
+    pub platform: Platform,
+    /// `xcodebuild -destination`. Defaults to the platform's configured destination.
     #[serde(default)]
-    pub configuration: BuildConfiguration,
-    #[serde(default = "default_destination")]
     pub destination: String,
     #[serde(default)]
     pub clean: bool,
+    /// Extra `xcodebuild` flags: at most 5 of `-quiet`, `-UseModernBuildSystem=YES`,
+    /// `-skipPackagePluginValidation`, and `-allowProvisioningUpdates`. See
+    /// `describe_build_constraints`.
     #[serde(default)]
     pub extra_args: Vec<String>,
+    /// Environment for `xcodebuild`; keys are limited to `NSUnbufferedIO`, `CI`, `LANG`,
+    /// `LC_ALL`, and `MOCK_XCODEBUILD_BEHAVIOR`.
     #[serde(default)]
     pub env_overrides: BTreeMap<String, String>,
+    /// Caller-chosen UUID for the job, so it can be cancelled with `cancel_build_job` while
+    /// queued or running. A fresh ID is generated when omitted.
+    #[serde(default)]
+    pub job_id: Option<String>,
+    /// Deadline for this job in minutes, up to `visionos.max_timeout_minutes`. Defaults to
+    /// `visionos.max_build_minutes`; lower it for quick smoke builds.
+    #[serde(default)]
+    pub timeout_minutes: Option<u16>,
+    /// `interactive` (default) jobs are started before waiting `batch` jobs.
+    #[serde(default)]
+    pub priority: JobPriority,
+    /// Strip debug and local symbols from the app's binaries after a successful build. dSYMs are
+    /// moved out of the app artifact into a separate `dsyms.zip`.
+    #[serde(default)]
+    pub strip_symbols: bool,
+    /// Move the build's dSYM bundles into a separate `dsyms.zip` without stripping, for
+    /// `symbolicate_crash` and crash reporters. Ignored by archives, which keep dSYMs in the
+    /// `.xcarchive`.
+    #[serde(default)]
+    pub collect_dsyms: bool,
+    /// Architecture slices to keep in the app's binaries (e.g. `["arm64"]`); others are removed
+    /// with `lipo`. Empty keeps every slice.
+    #[serde(default)]
+    pub architectures: Vec<String>,
+    /// Build into a per-project DerivedData directory under `visionos.derived_data_root` that is
+    /// kept between jobs, so unchanged targets are not rebuilt.
+    #[serde(default)]
+    pub use_cached_derived_data: bool,
+    /// Run `xcodebuild` even when `visionos.build_cache` has an artifact from an earlier job with
+    /// the same sources and parameters. `clean` builds always bypass the cache too.
+    #[serde(default)]
+    pub force_rebuild: bool,
+    /// `CODE_SIGN_IDENTITY`, e.g. `Apple Development` or a certificate SHA-1. Defaults to
+    /// `visionos.signing.identity`.
+    #[serde(default)]
+    pub code_sign_identity: Option<String>,
+    /// `DEVELOPMENT_TEAM`. Defaults to `visionos.signing.team_id`.
+    #[serde(default)]
+    pub development_team: Option<String>,
+    /// `PROVISIONING_PROFILE_SPECIFIER` (profile name or UUID); switches the build to manual
+    /// signing. Must be in `visionos.signing.allowed_provisioning_profiles` when that is set.
+    #[serde(default)]
+    pub provisioning_profile: Option<String>,
+    /// Glob patterns of the staging entries to pack into the artifact zip, e.g.
+    /// `["*.app", "*.dSYM"]`. A pattern without `/` matches names at any depth. Empty packs
+    /// everything.
+    #[serde(default)]
+    pub artifact_include: Vec<String>,
+    /// Glob patterns of staging entries to leave out of the artifact zip, even when
+    /// `artifact_include` matches them.
+    #[serde(default)]
+    pub artifact_exclude: Vec<String>,
+    /// Validate the request and return the `xcodebuild` command, environment, deadline, and
+    /// staging path in `plan` without queueing a job. Build and archive only.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// How `log_excerpt` and `build_failed` messages present the output: `raw` (default),
+    /// `compact`, or `errors_only`. `build.log` always keeps the full output.
+    #[serde(default)]
+    pub log_format: LogFormat,
 }
 
 impl VisionOsBuildRequest {
-    /// Validate the input and ensure it complies with the sandbox policy.
-    pub fn validate(&self, policy: &VisionOsConfig) -> Result<(), BuildRequestValidationError> {
+    /// Expand `preset`, then fill in the platform's configured destination and the configured
+    /// signing settings when the request leaves them empty. An unknown preset is left for
+    /// [`Self::validate`] to reject.
+    pub fn apply_config_defaults(&mut self, config: &VisionOsConfig) {
+        self.apply_defaults(config, None);
+    }
+
+    /// [`Self::apply_config_defaults`] with the project's `.seiro.toml` merged between the preset
+    /// and the config, when `visionos.project_config` is on. Fails when the file is invalid.
+    pub fn apply_project_defaults(
+        &mut self,
+        config: &VisionOsConfig,
+    ) -> Result<(), BuildRequestValidationError> {
+        let project = load_project_config(&self.project_path, config)?;
+        self.apply_defaults(config, project.as_ref());
+        Ok(())
+    }
+
+    fn apply_defaults(&mut self, config: &VisionOsConfig, project: Option<&ProjectConfig>) {
+        if let Some(preset) = self
+            .preset
+            .as_deref()
+            .and_then(|name| config.presets.get(name))
+        {
+            self.apply_preset(preset);
+        }
+        if let Some(project) = project {
+            self.apply_project_config(project);
+        }
+        if self.destination.trim().is_empty() {
+            self.destination = config.platform_defaults(self.platform).default_destination;
+        }
+        if self.code_sign_identity.is_none() {
+            self.code_sign_identity = config.signing.identity.clone();
+        }
+        if self.development_team.is_none() {
+            self.development_team = config.signing.team_id.clone();
+        }
+    }
+
+    fn apply_preset(&mut self, preset: &BuildPreset) {
+        if let Some(scheme) = preset
+            .scheme
+            .as_ref()
+            .filter(|_| self.scheme.trim().is_empty())
+        {
+            self.scheme = scheme.clone();
+        }
+        if self.configuration.is_none() {
+            self.configuration = match preset.configuration.as_deref() {
+                Some("release") => Some(BuildConfiguration::Release),
+                Some(_) => Some(BuildConfiguration::Debug),
+                None => None,
+            };
+        }
+        if let Some(destination) = preset
+            .destination
+            .as_ref()
+            .filter(|_| self.destination.trim().is_empty())
+        {
+            self.destination = destination.clone();
+        }
+        if !preset.extra_args.is_empty() {
+            let own = std::mem::take(&mut self.extra_args);
+            self.extra_args = preset.extra_args.clone();
+            for arg in own {
+                if !self.extra_args.contains(&arg) {
+                    self.extra_args.push(arg);
+                }
+            }
+        }
+    }
+
+    fn apply_project_config(&mut self, project: &ProjectConfig) {
+        if let Some(scheme) = project
+            .scheme
+            .as_ref()
+            .filter(|_| self.scheme.trim().is_empty())
+        {
+            self.scheme = scheme.clone();
+        }
+        if self.configuration.is_none() {
+            self.configuration = project.configuration;
+        }
+        if let Some(destination) = project
+            .destination
+            .as_ref()
+            .filter(|_| self.destination.trim().is_empty())
+        {
+            self.destination = destination.clone();
+        }
+        if !project.extra_args.is_empty() {
+            let own = std::mem::take(&mut self.extra_args);
+            self.extra_args = project.extra_args.clone();
+            for arg in own {
+                if !self.extra_args.contains(&arg) {
+                    self.extra_args.push(arg);
+                }
+            }
+        }
+        if self.artifact_include.is_empty() {
+            self.artifact_include = project.artifact_include.clone();
+        }
+        if self.artifact_exclude.is_empty() {
+            self.artifact_exclude = project.artifact_exclude.clone();
+        }
+    }
+
+    /// Configuration to build; `debug` when neither the request nor its preset sets one.
+    pub fn configuration(&self) -> BuildConfiguration {
+        self.configuration.unwrap_or_default()
+    }
+
+    /// `xcodebuild` build settings for the signing fields.
+    pub fn signing_build_settings(&self) -> Vec<String> {
+        let mut settings = Vec::new();
+        if let Some(identity) = &self.code_sign_identity {
+            settings.push(format!("CODE_SIGN_IDENTITY={identity}"));
+        }
+        if let Some(team) = &self.development_team {
+            settings.push(format!("DEVELOPMENT_TEAM={team}"));
+        }
+        if let Some(profile) = &self.provisioning_profile {
+            // Automatic signing rejects an explicit profile.
+            settings.push("CODE_SIGN_STYLE=Manual".to_string());
+            settings.push(format!("PROVISIONING_PROFILE_SPECIFIER={profile}"));
+        }
+        settings
+    }
+
+    /// Validate the input and ensure it complies with the sandbox policy. Allowed `project_path`
+    /// and `workspace` values are replaced by their canonical form, so the build runs on the
+    /// directory that was checked rather than on a symlink that could be repointed.
+    pub fn validate(&mut self, policy: &VisionOsConfig) -> Result<(), BuildRequestValidationError> {
+        if let Some(name) = &self.preset {
+            if !policy.presets.contains_key(name) {
+                return Err(BuildRequestValidationError::UnknownPreset { name: name.clone() });
+            }
+        }
+        if let Some(name) = &self.xcode {
+            if !policy.xcodes.contains_key(name) {
+                return Err(BuildRequestValidationError::UnknownXcode { name: name.clone() });
+            }
+        }
         if self.project_path.as_os_str().is_empty() {
             return Err(BuildRequestValidationError::MissingProjectPath);
         }
@@ -79,13 +377,16 @@ impl VisionOsBuildRequest {
         {
             return Err(BuildRequestValidationError::ProjectPathNotAllowed {
                 path: self.project_path.clone(),
+                allowed_roots: policy.root_labels(),
             });
         }
+        self.project_path = crate::lib::paths::resolve_existing(&self.project_path);
 
         if let Some(workspace) = &self.workspace {
             if !crate::lib::paths::is_nonempty_absolute(workspace) {
                 return Err(BuildRequestValidationError::WorkspaceNotAllowed {
                     path: workspace.clone(),
+                    allowed_roots: policy.root_labels(),
                 });
             }
             if !policy.allowed_paths.is_empty()
@@ -93,8 +394,14 @@ impl VisionOsBuildRequest {
             {
                 return Err(BuildRequestValidationError::WorkspaceNotAllowed {
                     path: workspace.clone(),
+                    allowed_roots: policy.root_labels(),
                 });
             }
+            self.workspace = Some(crate::lib::paths::resolve_existing(workspace));
+        }
+
+        if self.project_kind() == ProjectKind::SwiftPackage {
+            validate_swift_package(self)?;
         }
 
         if self.scheme.trim().is_empty() {
@@ -105,12 +412,7 @@ impl VisionOsBuildRequest {
                 length: self.scheme.chars().count(),
             });
         }
-        if !policy.allowed_schemes.is_empty()
-            && !policy
-                .allowed_schemes
-                .iter()
-                .any(|allowed| allowed == &self.scheme)
-        {
+        if !policy.allows_scheme(&self.scheme) {
             return Err(BuildRequestValidationError::SchemeNotAllowed {
                 scheme: self.scheme.clone(),
             });
@@ -128,6 +430,14 @@ impl VisionOsBuildRequest {
         if !destination.contains("platform=") {
             return Err(BuildRequestValidationError::DestinationMissingPlatform);
         }
+        if let DestinationTarget::Device { platform, id } = destination_target(destination) {
+            let Some(udid) = id else {
+                return Err(BuildRequestValidationError::DeviceIdRequired { platform });
+            };
+            if !is_allowed_device(&udid, &policy.allowed_device_udids) {
+                return Err(BuildRequestValidationError::DeviceNotAllowed { udid });
+            }
+        }
 
         if self.extra_args.len() > MAX_EXTRA_ARGS {
             return Err(BuildRequestValidationError::TooManyExtraArgs {
@@ -154,13 +464,157 @@ impl VisionOsBuildRequest {
             }
         }
 
+        if let Some(job_id) = &self.job_id {
+            if Uuid::parse_str(job_id.trim()).is_err() {
+                return Err(BuildRequestValidationError::InvalidJobId {
+                    job_id: job_id.clone(),
+                });
+            }
+        }
+
+        if let Some(minutes) = self.timeout_minutes {
+            if minutes == 0 || minutes > policy.max_timeout_minutes {
+                return Err(BuildRequestValidationError::TimeoutOutOfRange {
+                    minutes,
+                    max: policy.max_timeout_minutes,
+                });
+            }
+        }
+
+        if self.architectures.len() > MAX_ARCHITECTURES {
+            return Err(BuildRequestValidationError::TooManyArchitectures {
+                count: self.architectures.len(),
+            });
+        }
+        for arch in &self.architectures {
+            if arch.is_empty()
+                || arch.len() > MAX_ARCHITECTURE_LEN
+                || !arch
+                    .chars()
+                    .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_')
+            {
+                return Err(BuildRequestValidationError::InvalidArchitecture {
+                    arch: arch.clone(),
+                });
+            }
+        }
+
+        if self.use_cached_derived_data && policy.derived_data_root.is_none() {
+            return Err(BuildRequestValidationError::DerivedDataCacheDisabled);
+        }
+
+        validate_signing(self, policy)?;
+        validate_artifact_patterns(self)?;
+
         Ok(())
     }
+
+    /// Fail for tools that cannot honour `dry_run`, rather than running the job for real.
+    pub fn reject_dry_run(&self, tool: &'static str) -> Result<(), BuildRequestValidationError> {
+        if self.dry_run {
+            return Err(BuildRequestValidationError::DryRunUnsupported { tool });
+        }
+        Ok(())
+    }
+
+    /// The requested `job_id`, or a new one. Call after [`Self::validate`].
+    pub fn resolve_job_id(&self) -> Uuid {
+        self.job_id
+            .as_deref()
+            .and_then(|job_id| Uuid::parse_str(job_id.trim()).ok())
+            .unwrap_or_else(Uuid::new_v4)
+    }
+
+    /// Detect the project layout; see [`ProjectKind::detect`].
+    pub fn project_kind(&self) -> ProjectKind {
+        if self.workspace.is_some() {
+            return ProjectKind::Workspace;
+        }
+        ProjectKind::detect(&self.project_path)
+    }
+
+    /// Workspace if given, otherwise the project; jobs on the same root never run concurrently.
+    pub fn project_root(&self) -> &Path {
+        self.workspace.as_deref().unwrap_or(&self.project_path)
+    }
+}
+
+fn validate_artifact_patterns(
+    request: &VisionOsBuildRequest,
+) -> Result<(), BuildRequestValidationError> {
+    for (field, patterns) in [
+        ("artifact_include", &request.artifact_include),
+        ("artifact_exclude", &request.artifact_exclude),
+    ] {
+        if patterns.len() > MAX_ARTIFACT_PATTERNS {
+            return Err(BuildRequestValidationError::TooManyArtifactPatterns {
+                field,
+                count: patterns.len(),
+            });
+        }
+        for pattern in patterns {
+            if pattern.trim().is_empty()
+                || pattern.len() > MAX_ARTIFACT_PATTERN_LEN
+                || glob::Pattern::new(pattern).is_err()
+            {
+                return Err(BuildRequestValidationError::InvalidArtifactPattern {
+                    field,
+                    pattern: pattern.clone(),
+                });
+            }
+        }
+    }
+    Ok(())
 }
 
-/// Default destination value.
-pub fn default_destination() -> String {
-    "platform=visionOS Simulator,name=Apple Vision Pro".to_string()
+fn validate_signing(
+    request: &VisionOsBuildRequest,
+    policy: &VisionOsConfig,
+) -> Result<(), BuildRequestValidationError> {
+    let is_invalid = |value: &str| {
+        value.trim().is_empty()
+            || value.chars().count() > MAX_SIGNING_VALUE_LEN
+            || value.chars().any(char::is_control)
+    };
+    if let Some(identity) = &request.code_sign_identity {
+        if is_invalid(identity) {
+            return Err(BuildRequestValidationError::InvalidSigningIdentity {
+                identity: identity.clone(),
+            });
+        }
+    }
+    if let Some(team) = &request.development_team {
+        if !is_team_id(team) {
+            return Err(BuildRequestValidationError::InvalidDevelopmentTeam { team: team.clone() });
+        }
+    }
+    if let Some(profile) = &request.provisioning_profile {
+        let allowed = &policy.signing.allowed_provisioning_profiles;
+        if is_invalid(profile) || (!allowed.is_empty() && !allowed.contains(profile)) {
+            return Err(BuildRequestValidationError::ProvisioningProfileNotAllowed {
+                profile: profile.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that only apply to Swift packages, which build libraries rather than an app bundle.
+fn validate_swift_package(
+    request: &VisionOsBuildRequest,
+) -> Result<(), BuildRequestValidationError> {
+    let manifest = if request.project_path.ends_with(PACKAGE_MANIFEST) {
+        request.project_path.clone()
+    } else {
+        request.project_path.join(PACKAGE_MANIFEST)
+    };
+    if !manifest.is_file() {
+        return Err(BuildRequestValidationError::PackageManifestMissing { path: manifest });
+    }
+    if request.strip_symbols || !request.architectures.is_empty() {
+        return Err(BuildRequestValidationError::PackagePostProcessUnsupported);
+    }
+    Ok(())
 }
 
 /// Input validation errors.
@@ -172,37 +626,89 @@ pub enum BuildRequestValidationError {
     ProjectPathNotAbsolute,
     #[error("project_path is too long (max {MAX_PROJECT_PATH_LEN} characters)")]
     ProjectPathTooLong,
+    /// `allowed_roots` holds the labels of the labeled workspace roots.
     #[error("project_path `{path}` is outside the allowlist")]
-    ProjectPathNotAllowed { path: PathBuf },
+    ProjectPathNotAllowed {
+        path: PathBuf,
+        allowed_roots: Vec<String>,
+    },
     #[error("workspace `{path}` is outside the allowlist")]
-    WorkspaceNotAllowed { path: PathBuf },
+    WorkspaceNotAllowed {
+        path: PathBuf,
+        allowed_roots: Vec<String>,
+    },
+    #[error("preset `{name}` is not defined in visionos.presets")]
+    UnknownPreset { name: String },
+    #[error("xcode `{name}` is not defined in visionos.xcodes")]
+    UnknownXcode { name: String },
     #[error("scheme is required")]
     MissingScheme,
-    #[error("scheme is too long ({length} characters)")]
+    #[error("scheme is too long ({length} characters, max {MAX_SCHEME_LEN})")]
     SchemeTooLong { length: usize },
     #[error("scheme `{scheme}` is not included in the config allowlist")]
     SchemeNotAllowed { scheme: String },
     #[error("destination is required")]
     DestinationEmpty,
-    #[error("destination is too long ({length} characters)")]
+    #[error("destination is too long ({length} characters, max {MAX_DESTINATION_LEN})")]
     DestinationTooLong { length: usize },
     #[error("destination must include `platform=`")]
     DestinationMissingPlatform,
+    #[error("destination for a physical {platform} device must select it with `id=<udid>`")]
+    DeviceIdRequired { platform: String },
+    #[error("device `{udid}` is not in visionos.allowed_device_udids")]
+    DeviceNotAllowed { udid: String },
     #[error("extra_args contains a disallowed value `{arg}`")]
     ExtraArgNotAllowed { arg: String },
-    #[error("extra_args exceeds the allowed count (count={count})")]
+    #[error("extra_args exceeds the allowed count (count={count}, max {MAX_EXTRA_ARGS})")]
     TooManyExtraArgs { count: usize },
-    #[error("extra_args `{arg}` is too long ({length} characters)")]
+    #[error("extra_args `{arg}` is too long ({length} characters, max {MAX_EXTRA_ARG_LEN})")]
     ExtraArgTooLong { arg: String, length: usize },
     #[error("env_overrides `{key}` is not permitted")]
     EnvOverrideNotAllowed { key: String },
+    #[error("job_id `{job_id}` must be a UUID")]
+    InvalidJobId { job_id: String },
+    #[error("job_id `{job_id}` is already queued or running")]
+    JobIdInUse { job_id: String },
+    #[error("timeout_minutes must be between 1 and {max} (got {minutes})")]
+    TimeoutOutOfRange { minutes: u16, max: u16 },
+    #[error("architectures exceeds the allowed count (count={count}, max {MAX_ARCHITECTURES})")]
+    TooManyArchitectures { count: usize },
+    #[error("architecture `{arch}` is not a valid slice name such as `arm64`")]
+    InvalidArchitecture { arch: String },
+    #[error("{field} exceeds the allowed count (count={count}, max {MAX_ARTIFACT_PATTERNS})")]
+    TooManyArtifactPatterns { field: &'static str, count: usize },
+    #[error("{field} `{pattern}` is not a glob pattern of at most {MAX_ARTIFACT_PATTERN_LEN} characters")]
+    InvalidArtifactPattern {
+        field: &'static str,
+        pattern: String,
+    },
+    #[error("Swift package manifest {path} does not exist")]
+    PackageManifestMissing { path: PathBuf },
+    #[error(
+        "strip_symbols and architectures need an app bundle; Swift packages do not produce one"
+    )]
+    PackagePostProcessUnsupported,
+    #[error("use_cached_derived_data requires `visionos.derived_data_root` to be configured")]
+    DerivedDataCacheDisabled,
+    #[error("code_sign_identity `{identity}` is not a certificate name, SHA-1 hash, or `-`")]
+    InvalidSigningIdentity { identity: String },
+    #[error("development_team `{team}` must be 10 uppercase letters and digits")]
+    InvalidDevelopmentTeam { team: String },
+    #[error("provisioning_profile `{profile}` is not included in the config allowlist")]
+    ProvisioningProfileNotAllowed { profile: String },
+    #[error("dry_run is not supported by {tool}; use build_visionos_app or archive_visionos_app")]
+    DryRunUnsupported { tool: &'static str },
+    #[error("project config {path} is invalid: {message}")]
+    ProjectConfigInvalid { path: PathBuf, message: String },
 }
 
 #[cfg(test)]
 mod tests {
     use std::{collections::BTreeMap, fs, path::PathBuf};
 
-    use crate::server::config::VisionOsConfig;
+    use crate::server::config::{
+        AllowedRoot, SchemePattern, SymlinkPolicy, VisionOsConfig, ZipCompression,
+    };
     use serde_json::json;
     use tempfile::tempdir;
 
@@ -212,15 +718,49 @@ mod tests {
         let workspace = absolute_fixtures_path("tests/fixtures/visionos/workspace");
         VisionOsConfig {
             allowed_paths: vec![workspace],
-            allowed_schemes: vec!["VisionApp".into(), "VisionToolbox".into()],
+            allowed_roots: Vec::new(),
+            allowed_schemes: vec![
+                SchemePattern::new("VisionApp").expect("valid scheme"),
+                SchemePattern::new("VisionToolbox").expect("valid scheme"),
+            ],
             default_project_path: None,
             default_destination: "platform=visionOS Simulator,name=Apple Vision Pro".into(),
             required_sdks: vec!["visionOS".into(), "visionOS Simulator".into()],
             xcode_path: PathBuf::from("/Applications/Xcode.app/Contents/Developer"),
             xcodebuild_path: PathBuf::from("/usr/bin/xcodebuild"),
+            xcrun_path: PathBuf::from("/usr/bin/xcrun"),
             max_build_minutes: 20,
+            max_timeout_minutes: 20,
+            timeout_warning_percent: 80,
+            heartbeat_interval_secs: 15,
+            max_extended_build_minutes: 0,
+            max_concurrent_builds: 1,
             artifact_ttl_secs: 600,
             cleanup_schedule_secs: 60,
+            artifact_max_jobs: 0,
+            artifact_max_total_bytes: 0,
+            min_free_disk_bytes: 0,
+            sandbox_probe_ttl_secs: 0,
+            allow_remediation: false,
+            package_hosts: Vec::new(),
+            policy: Default::default(),
+            export_paths: vec![],
+            size_budgets: BTreeMap::new(),
+            artifact_compression: ZipCompression::default(),
+            artifact_symlinks: SymlinkPolicy::default(),
+            deterministic_artifacts: false,
+            derived_data_root: None,
+            build_cache: false,
+            project_config: false,
+            compilation_cache: None,
+            platforms: BTreeMap::new(),
+            locale: "en_US.UTF-8".into(),
+            signing: Default::default(),
+            allowed_device_udids: Vec::new(),
+            artifact_persistence: Default::default(),
+            presets: Default::default(),
+            xcodes: Default::default(),
+            retry: Default::default(),
         }
     }
 
@@ -234,14 +774,252 @@ mod tests {
             project_path: absolute_fixtures_path("tests/fixtures/visionos/workspace/VisionApp"),
             workspace: None,
             scheme: "VisionApp".into(),
-            configuration: BuildConfiguration::Debug,
+            configuration: Some(BuildConfiguration::Debug),
+            preset: None,
+            xcode: None,
+            platform: Platform::VisionOs,
             destination: "platform=visionOS Simulator,name=Apple Vision Pro".into(),
             clean: false,
             extra_args: vec![],
             env_overrides: std::collections::BTreeMap::new(),
+            job_id: None,
+            timeout_minutes: None,
+            priority: JobPriority::Interactive,
+            strip_symbols: false,
+            collect_dsyms: false,
+            architectures: Vec::new(),
+            use_cached_derived_data: false,
+            force_rebuild: false,
+            code_sign_identity: None,
+            development_team: None,
+            provisioning_profile: None,
+            artifact_include: Vec::new(),
+            artifact_exclude: Vec::new(),
+            dry_run: false,
+            log_format: Default::default(),
         }
     }
 
+    #[test]
+    fn missing_destination_uses_the_platform_default() {
+        let mut config = sample_config();
+        config.platforms.insert(
+            Platform::Ios,
+            crate::server::config::PlatformDefaults {
+                default_destination: "platform=iOS Simulator,name=iPhone 15".into(),
+                required_sdks: vec!["iphoneos".into()],
+            },
+        );
+        let mut request: VisionOsBuildRequest = serde_json::from_value(json!({
+            "project_path": absolute_fixtures_path("tests/fixtures/visionos/workspace/VisionApp"),
+            "scheme": "VisionApp",
+            "platform": "ios"
+        }))
+        .expect("platform should deserialize");
+
+        request.apply_config_defaults(&config);
+        assert_eq!(request.destination, "platform=iOS Simulator,name=iPhone 15");
+
+        request.platform = Platform::TvOs;
+        request.apply_config_defaults(&config);
+        assert_eq!(
+            request.destination, "platform=iOS Simulator,name=iPhone 15",
+            "an explicit destination is kept"
+        );
+
+        let mut request = base_request();
+        request.destination.clear();
+        request.apply_config_defaults(&config);
+        assert_eq!(request.destination, config.default_destination);
+    }
+
+    #[test]
+    fn presets_fill_unset_fields_before_validation() {
+        let mut config = sample_config();
+        config.presets.insert(
+            "release-device".into(),
+            crate::server::config::BuildPreset {
+                scheme: Some("VisionApp".into()),
+                configuration: Some("release".into()),
+                destination: Some("generic/platform=visionOS".into()),
+                extra_args: vec!["-quiet".into()],
+            },
+        );
+        let mut request: VisionOsBuildRequest = serde_json::from_value(json!({
+            "project_path": absolute_fixtures_path("tests/fixtures/visionos/workspace/VisionApp"),
+            "preset": "release-device",
+            "extra_args": ["-quiet", "-skipPackagePluginValidation"]
+        }))
+        .expect("a preset request needs no scheme");
+
+        request.apply_config_defaults(&config);
+        assert_eq!(request.scheme, "VisionApp");
+        assert_eq!(request.configuration(), BuildConfiguration::Release);
+        assert_eq!(request.destination, "generic/platform=visionOS");
+        assert_eq!(
+            request.extra_args,
+            vec!["-quiet", "-skipPackagePluginValidation"]
+        );
+        assert!(request.validate(&config).is_ok());
+
+        let mut request = base_request();
+        request.preset = Some("release-device".into());
+        request.apply_config_defaults(&config);
+        assert_eq!(
+            request.configuration(),
+            BuildConfiguration::Debug,
+            "the request's own configuration wins"
+        );
+
+        request.preset = Some("missing".into());
+        assert_eq!(
+            request.validate(&config),
+            Err(BuildRequestValidationError::UnknownPreset {
+                name: "missing".into()
+            })
+        );
+    }
+
+    #[test]
+    fn xcode_must_name_a_configured_installation() {
+        let mut config = sample_config();
+        config.xcodes.insert(
+            "beta".into(),
+            PathBuf::from("/Applications/Xcode-beta.app/Contents/Developer"),
+        );
+        let mut request = base_request();
+        request.xcode = Some("beta".into());
+        assert!(request.validate(&config).is_ok());
+        assert_eq!(
+            config.with_xcode(request.xcode.as_deref()).xcode_path,
+            PathBuf::from("/Applications/Xcode-beta.app/Contents/Developer")
+        );
+        assert_eq!(config.with_xcode(None).xcode_path, config.xcode_path);
+
+        request.xcode = Some("missing".into());
+        assert_eq!(
+            request.validate(&config),
+            Err(BuildRequestValidationError::UnknownXcode {
+                name: "missing".into()
+            })
+        );
+    }
+
+    #[test]
+    fn project_config_fills_what_the_request_and_preset_leave_unset() {
+        let workspace = tempfile::tempdir().expect("tempdir");
+        let project = workspace.path().join("VisionApp");
+        std::fs::create_dir_all(project.join("VisionApp.xcodeproj")).expect("project dir");
+        std::fs::write(
+            project.join(".seiro.toml"),
+            r#"
+scheme = "VisionToolbox"
+configuration = "release"
+destination = "platform=visionOS Simulator,name=Apple Vision Pro (at 2732x2048)"
+extra_args = ["-quiet"]
+artifact_include = ["*.app"]
+"#,
+        )
+        .expect("write .seiro.toml");
+        let mut config = sample_config();
+        config.allowed_paths = vec![workspace.path().to_path_buf()];
+        let request = || {
+            let mut request = base_request();
+            request.project_path = project.join("VisionApp.xcodeproj");
+            request.scheme = String::new();
+            request.configuration = None;
+            request.destination = String::new();
+            request
+        };
+
+        let mut unmerged = request();
+        unmerged
+            .apply_project_defaults(&config)
+            .expect("option is off");
+        assert!(unmerged.scheme.is_empty());
+
+        config.project_config = true;
+        let mut merged = request();
+        merged
+            .apply_project_defaults(&config)
+            .expect(".seiro.toml is valid");
+        assert_eq!(merged.scheme, "VisionToolbox");
+        assert_eq!(merged.configuration(), BuildConfiguration::Release);
+        assert!(merged.destination.ends_with("(at 2732x2048)"));
+        assert_eq!(merged.extra_args, ["-quiet"]);
+        assert_eq!(merged.artifact_include, ["*.app"]);
+
+        let mut own = request();
+        own.scheme = "VisionApp".into();
+        own.artifact_include = vec!["*.dSYM".into()];
+        own.apply_project_defaults(&config)
+            .expect(".seiro.toml is valid");
+        assert_eq!(own.scheme, "VisionApp");
+        assert_eq!(own.artifact_include, ["*.dSYM"]);
+
+        std::fs::write(
+            project.join(".seiro.toml"),
+            "scheme = \"VisionApp\"\ntarget = \"x\"\n",
+        )
+        .expect("rewrite .seiro.toml");
+        assert!(matches!(
+            request().apply_project_defaults(&config),
+            Err(BuildRequestValidationError::ProjectConfigInvalid { .. })
+        ));
+        std::fs::write(
+            project.join(".seiro.toml"),
+            "extra_args = [\"-derivedDataPath\"]\n",
+        )
+        .expect("rewrite .seiro.toml");
+        assert!(matches!(
+            request().apply_project_defaults(&config),
+            Err(BuildRequestValidationError::ProjectConfigInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn signing_settings_default_from_config_and_respect_the_profile_allowlist() {
+        let mut config = sample_config();
+        config.signing = crate::server::config::SigningConfig {
+            team_id: Some("A1B2C3D4E5".into()),
+            identity: Some("Apple Development".into()),
+            allowed_provisioning_profiles: vec!["VisionApp Development".into()],
+        };
+
+        let mut request = base_request();
+        request.apply_config_defaults(&config);
+        assert_eq!(
+            request.signing_build_settings(),
+            vec![
+                "CODE_SIGN_IDENTITY=Apple Development",
+                "DEVELOPMENT_TEAM=A1B2C3D4E5"
+            ]
+        );
+
+        request.provisioning_profile = Some("VisionApp Development".into());
+        assert!(request.validate(&config).is_ok());
+        assert!(request
+            .signing_build_settings()
+            .contains(&"CODE_SIGN_STYLE=Manual".to_string()));
+
+        request.provisioning_profile = Some("Someone Else's Profile".into());
+        assert_eq!(
+            request.validate(&config),
+            Err(BuildRequestValidationError::ProvisioningProfileNotAllowed {
+                profile: "Someone Else's Profile".into()
+            })
+        );
+
+        let mut request = base_request();
+        request.development_team = Some("team-1".into());
+        assert_eq!(
+            request.validate(&config),
+            Err(BuildRequestValidationError::InvalidDevelopmentTeam {
+                team: "team-1".into()
+            })
+        );
+    }
+
     #[test]
     fn build_configuration_accepts_lowercase_values() {
         let parsed: VisionOsBuildRequest = serde_json::from_value(json!({
@@ -251,7 +1029,7 @@ mod tests {
         }))
         .expect("lowercase configuration should deserialize");
 
-        assert_eq!(parsed.configuration, BuildConfiguration::Debug);
+        assert_eq!(parsed.configuration, Some(BuildConfiguration::Debug));
     }
 
     #[test]
@@ -263,7 +1041,7 @@ mod tests {
         }))
         .expect("xcode-style configuration should deserialize");
 
-        assert_eq!(parsed.configuration, BuildConfiguration::Debug);
+        assert_eq!(parsed.configuration, Some(BuildConfiguration::Debug));
     }
 
     #[test]
@@ -321,6 +1099,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn device_destinations_require_an_allowlisted_udid() {
+        let mut config = sample_config();
+        config.allowed_device_udids = vec!["00008112-001A2C3E0123401E".into()];
+        let mut request = base_request();
+
+        request.destination = "platform=visionOS,name=Studio Vision Pro".into();
+        assert_eq!(
+            request.validate(&config),
+            Err(BuildRequestValidationError::DeviceIdRequired {
+                platform: "visionOS".into()
+            })
+        );
+        request.destination = "platform=visionOS,id=00008112-0000000000000001".into();
+        assert_eq!(
+            request.validate(&config),
+            Err(BuildRequestValidationError::DeviceNotAllowed {
+                udid: "00008112-0000000000000001".into()
+            })
+        );
+        request.destination = "platform=visionOS,id=00008112-001a2c3e0123401e".into();
+        assert_eq!(request.validate(&config), Ok(()));
+        request.destination = "generic/platform=visionOS".into();
+        assert_eq!(request.validate(&sample_config()), Ok(()));
+    }
+
     #[test]
     fn destination_longer_than_limit_is_rejected() {
         let mut request = base_request();
@@ -362,6 +1166,12 @@ mod tests {
 
         let mut config = sample_config();
         config.allowed_paths = vec![allowed.clone()];
+        config.allowed_roots = vec![AllowedRoot {
+            path: allowed.clone(),
+            label: "ci".into(),
+            max_concurrent: None,
+            artifact_ttl_secs: None,
+        }];
 
         let mut request = base_request();
         request.project_path = allowed.join("..").join("outside");
@@ -373,7 +1183,8 @@ mod tests {
         assert_eq!(
             error,
             BuildRequestValidationError::ProjectPathNotAllowed {
-                path: request.project_path
+                path: request.project_path,
+                allowed_roots: vec!["ci".into()],
             }
         );
     }
@@ -401,11 +1212,87 @@ mod tests {
         assert_eq!(
             error,
             BuildRequestValidationError::WorkspaceNotAllowed {
-                path: request.workspace.expect("workspace should be present")
+                path: request.workspace.expect("workspace should be present"),
+                allowed_roots: Vec::new(),
             }
         );
     }
 
+    #[test]
+    fn allowed_paths_are_replaced_by_their_canonical_form() {
+        let temp = tempdir().expect("can create temp directory");
+        let allowed = temp.path().join("allowed");
+        let project = allowed.join("project");
+        fs::create_dir_all(&project).expect("can create project directory");
+
+        let mut config = sample_config();
+        config.allowed_paths = vec![allowed.clone()];
+
+        let mut request = base_request();
+        request.project_path = project.join("..").join("project");
+        request.workspace = Some(allowed.join(".").join("project"));
+
+        request
+            .validate(&config)
+            .expect("paths inside the allowlist should pass");
+
+        let canonical = fs::canonicalize(&project).expect("project exists");
+        assert_eq!(request.project_path, canonical);
+        assert_eq!(request.workspace, Some(canonical));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn project_path_symlink_escape_is_rejected() {
+        let temp = tempdir().expect("can create temp directory");
+        let allowed = temp.path().join("allowed");
+        let outside = temp.path().join("outside");
+        fs::create_dir_all(&allowed).expect("can create allowed directory");
+        fs::create_dir_all(&outside).expect("can create outside directory");
+        let link = allowed.join("linked-outside");
+        std::os::unix::fs::symlink(&outside, &link).expect("can create symlink");
+
+        let mut config = sample_config();
+        config.allowed_paths = vec![allowed];
+
+        let mut request = base_request();
+        request.project_path = link.clone();
+
+        let error = request
+            .validate(&config)
+            .expect_err("symlink pointing outside the allowlist should be rejected");
+
+        assert_eq!(
+            error,
+            BuildRequestValidationError::ProjectPathNotAllowed {
+                path: link,
+                allowed_roots: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn root_for_picks_the_most_specific_root() {
+        let temp = tempdir().expect("can create temp directory");
+        let checkouts = temp.path().join("checkouts");
+        let ci = checkouts.join("ci");
+        fs::create_dir_all(ci.join("VisionApp")).expect("can create ci project");
+        fs::create_dir_all(checkouts.join("VisionApp")).expect("can create personal project");
+        let root = |path: &PathBuf, label: &str| AllowedRoot {
+            path: path.clone(),
+            label: label.into(),
+            max_concurrent: Some(1),
+            artifact_ttl_secs: None,
+        };
+        let mut config = sample_config();
+        config.allowed_roots = vec![root(&checkouts, "personal"), root(&ci, "ci")];
+
+        let label = |path: PathBuf| config.root_for(&path).map(|root| root.label.as_str());
+        assert_eq!(label(ci.join("VisionApp")), Some("ci"));
+        assert_eq!(label(checkouts.join("VisionApp")), Some("personal"));
+        assert_eq!(label(temp.path().to_path_buf()), None);
+    }
+
     #[test]
     fn developer_dir_env_override_is_rejected() {
         let mut request = base_request();
@@ -425,4 +1312,121 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn malformed_architecture_is_rejected() {
+        let mut request = base_request();
+        request.architectures = vec!["arm64".into(), "-output".into()];
+
+        let error = request
+            .validate(&sample_config())
+            .expect_err("flag-like architecture should be rejected");
+
+        assert_eq!(
+            error,
+            BuildRequestValidationError::InvalidArchitecture {
+                arch: "-output".into()
+            }
+        );
+    }
+
+    #[test]
+    fn artifact_patterns_are_counted_and_parsed() {
+        let mut request = base_request();
+        request.artifact_include = vec!["*.app".into(), "*.dSYM".into()];
+        request.artifact_exclude = vec!["**/_CodeSignature".into()];
+        request
+            .validate(&sample_config())
+            .expect("glob patterns are accepted");
+
+        request.artifact_exclude = vec!["[".into()];
+        assert_eq!(
+            request.validate(&sample_config()),
+            Err(BuildRequestValidationError::InvalidArtifactPattern {
+                field: "artifact_exclude",
+                pattern: "[".into()
+            })
+        );
+
+        request.artifact_exclude.clear();
+        request.artifact_include = vec!["*.app".into(); MAX_ARTIFACT_PATTERNS + 1];
+        assert_eq!(
+            request.validate(&sample_config()),
+            Err(BuildRequestValidationError::TooManyArtifactPatterns {
+                field: "artifact_include",
+                count: MAX_ARTIFACT_PATTERNS + 1
+            })
+        );
+    }
+
+    #[test]
+    fn timeout_minutes_above_configured_ceiling_is_rejected() {
+        let mut request = base_request();
+        request.timeout_minutes = Some(5);
+        assert_eq!(request.validate(&sample_config()), Ok(()));
+
+        request.timeout_minutes = Some(21);
+        assert_eq!(
+            request.validate(&sample_config()),
+            Err(BuildRequestValidationError::TimeoutOutOfRange {
+                minutes: 21,
+                max: 20
+            })
+        );
+    }
+
+    #[test]
+    fn cached_derived_data_requires_configured_root() {
+        let mut request = base_request();
+        request.use_cached_derived_data = true;
+        assert_eq!(
+            request.validate(&sample_config()),
+            Err(BuildRequestValidationError::DerivedDataCacheDisabled)
+        );
+
+        let mut config = sample_config();
+        config.derived_data_root = Some(PathBuf::from("/tmp/DerivedData"));
+        assert_eq!(request.validate(&config), Ok(()));
+    }
+
+    #[test]
+    fn project_kind_detects_swift_packages() {
+        let mut request = base_request();
+        assert_eq!(request.project_kind(), ProjectKind::XcodeProject);
+
+        let package = absolute_fixtures_path("tests/fixtures/visionos/workspace/VisionKit");
+        request.project_path = package.clone();
+        assert_eq!(request.project_kind(), ProjectKind::SwiftPackage);
+        request.project_path = package.join("Package.swift");
+        assert_eq!(request.project_kind(), ProjectKind::SwiftPackage);
+        assert_eq!(request.validate(&sample_config()), Ok(()));
+
+        let mixed = tempdir().expect("can create temp directory");
+        fs::write(mixed.path().join("Package.swift"), "").expect("can write manifest");
+        fs::create_dir_all(mixed.path().join("App.xcodeproj")).expect("can create project");
+        request.project_path = mixed.path().to_path_buf();
+        assert_eq!(request.project_kind(), ProjectKind::XcodeProject);
+    }
+
+    #[test]
+    fn swift_package_rejects_missing_manifest_and_post_processing() {
+        let mut request = base_request();
+        let manifest =
+            absolute_fixtures_path("tests/fixtures/visionos/workspace/VisionApp/Package.swift");
+        request.project_path = manifest.clone();
+        let mut open_config = sample_config();
+        open_config.allowed_paths.clear();
+        assert_eq!(
+            request.validate(&open_config),
+            Err(BuildRequestValidationError::PackageManifestMissing { path: manifest })
+        );
+
+        request.project_path =
+            absolute_fixtures_path("tests/fixtures/visionos/workspace/VisionKit");
+        request.strip_symbols = true;
+        assert_eq!(
+            request.validate(&sample_config()),
+            Err(BuildRequestValidationError::PackagePostProcessUnsupported)
+        );
+    }
 }
@@ -0,0 +1,255 @@
+//! `get_queue_status`: report running and waiting jobs with estimated waits.
+use std::{path::PathBuf, time::Duration};
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{JobPriority, QueueSnapshot, VisionOsJobQueue};
+
+/// Input for `get_queue_status`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GetQueueStatusRequest {}
+
+/// Response from `get_queue_status`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct QueueStatusResponse {
+    /// `visionos.max_concurrent_builds`.
+    pub slots: usize,
+    pub running: Vec<RunningJobStatus>,
+    /// Waiting jobs in the order they will be considered for a slot.
+    pub queued: Vec<QueuedJobStatus>,
+    /// Mean duration of the last finished jobs; `None` until one has finished.
+    pub average_job_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RunningJobStatus {
+    pub job_id: String,
+    pub priority: JobPriority,
+    pub enqueued_at: DateTime<Utc>,
+    pub started_at: DateTime<Utc>,
+    pub elapsed_secs: u64,
+    /// Last build phase seen in `xcodebuild` output.
+    pub phase: Option<String>,
+    /// Mean run time of recent successful jobs of the same kind and scheme; `None` without
+    /// history. Build and archive jobs only.
+    pub estimated_duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct QueuedJobStatus {
+    pub job_id: String,
+    /// 1-based place among waiting jobs.
+    pub position: usize,
+    pub priority: JobPriority,
+    pub enqueued_at: DateTime<Utc>,
+    /// Seconds until the job is expected to start; `None` until a job has finished.
+    pub estimated_wait_secs: Option<u64>,
+    /// See [`RunningJobStatus::estimated_duration_ms`].
+    pub estimated_duration_ms: Option<u64>,
+}
+
+/// Describe the current queue.
+pub async fn get_queue_status(
+    queue: &VisionOsJobQueue,
+    _request: GetQueueStatusRequest,
+) -> QueueStatusResponse {
+    let snapshot = queue.snapshot().await;
+    let estimates =
+        fallback_duration(&snapshot).map(|fallback| estimate_waits(&snapshot, fallback));
+    QueueStatusResponse {
+        slots: snapshot.slots,
+        running: snapshot
+            .running
+            .iter()
+            .map(|running| RunningJobStatus {
+                job_id: running.ticket.job_id.to_string(),
+                priority: running.ticket.priority,
+                enqueued_at: running.ticket.enqueued_at,
+                started_at: running.started_at,
+                elapsed_secs: running.elapsed.as_secs(),
+                phase: running.ticket.monitor.phase(),
+                estimated_duration_ms: duration_ms(running.ticket.estimated_duration),
+            })
+            .collect(),
+        queued: snapshot
+            .queued
+            .iter()
+            .enumerate()
+            .map(|(index, queued)| QueuedJobStatus {
+                job_id: queued.ticket.job_id.to_string(),
+                position: index + 1,
+                priority: queued.ticket.priority,
+                enqueued_at: queued.ticket.enqueued_at,
+                estimated_wait_secs: estimates
+                    .as_ref()
+                    .map(|estimates| estimates[index].as_secs()),
+                estimated_duration_ms: duration_ms(queued.ticket.estimated_duration),
+            })
+            .collect(),
+        average_job_secs: snapshot.average_duration.map(|average| average.as_secs()),
+    }
+}
+
+fn duration_ms(duration: Option<Duration>) -> Option<u64> {
+    duration.map(|duration| u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// Duration assumed for jobs without a history of their own: the recent average in this
+/// process, else the mean of the estimates the jobs carry.
+fn fallback_duration(snapshot: &QueueSnapshot) -> Option<Duration> {
+    if snapshot.average_duration.is_some() {
+        return snapshot.average_duration;
+    }
+    let estimates: Vec<Duration> = snapshot
+        .running
+        .iter()
+        .map(|running| &running.ticket)
+        .chain(snapshot.queued.iter().map(|queued| &queued.ticket))
+        .filter_map(|ticket| ticket.estimated_duration)
+        .collect();
+    (!estimates.is_empty()).then(|| estimates.iter().sum::<Duration>() / estimates.len() as u32)
+}
+
+/// Replay the scheduler assuming every job takes its scheme's estimate, or `fallback`: each
+/// waiting job starts once a slot and its project are both free. Rough by design.
+fn estimate_waits(snapshot: &QueueSnapshot, fallback: Duration) -> Vec<Duration> {
+    let mut slot_free_at: Vec<Duration> = snapshot
+        .running
+        .iter()
+        .map(|running| {
+            running
+                .ticket
+                .estimated_duration
+                .unwrap_or(fallback)
+                .saturating_sub(running.elapsed)
+        })
+        .collect();
+    slot_free_at.resize(snapshot.slots.max(slot_free_at.len()), Duration::ZERO);
+    let mut project_free_at: Vec<(PathBuf, Duration)> = snapshot
+        .running
+        .iter()
+        .zip(&slot_free_at)
+        .map(|(running, free_at)| (running.project.clone(), *free_at))
+        .collect();
+
+    let mut waits = Vec::with_capacity(snapshot.queued.len());
+    for queued in &snapshot.queued {
+        let earliest_slot = slot_free_at.iter().copied().min().unwrap_or_default();
+        let project_free = project_free_at
+            .iter()
+            .filter(|(project, _)| project == &queued.project)
+            .map(|(_, free_at)| *free_at)
+            .max()
+            .unwrap_or_default();
+        let start = earliest_slot.max(project_free);
+        // Take the slot that frees up last before `start`, leaving earlier ones to later jobs
+        // whose project is idle, as the scheduler would.
+        let slot = slot_free_at
+            .iter()
+            .enumerate()
+            .filter(|(_, free_at)| **free_at <= start)
+            .max_by_key(|(_, free_at)| **free_at)
+            .map(|(slot, _)| slot)
+            .unwrap_or_default();
+        let duration = queued.ticket.estimated_duration.unwrap_or(fallback);
+        slot_free_at[slot] = start + duration;
+        project_free_at.push((queued.project.clone(), start + duration));
+        waits.push(start);
+    }
+    waits
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use tokio_util::sync::CancellationToken;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::tools::visionos::build::{
+        queue::{QueuedJobSnapshot, RunningJobSnapshot},
+        JobMonitor, JobTicket,
+    };
+
+    fn ticket() -> JobTicket {
+        let job_id = Uuid::new_v4();
+        JobTicket {
+            job_id,
+            priority: JobPriority::Interactive,
+            enqueued_at: Utc::now(),
+            cancellation: CancellationToken::new(),
+            monitor: JobMonitor::new(job_id),
+            estimated_duration: None,
+        }
+    }
+
+    #[test]
+    fn estimates_account_for_slots_and_busy_projects() {
+        let app = PathBuf::from("/tmp/VisionApp.xcodeproj");
+        let toolbox = PathBuf::from("/tmp/VisionToolbox.xcodeproj");
+        let snapshot = QueueSnapshot {
+            slots: 2,
+            running: vec![RunningJobSnapshot {
+                ticket: ticket(),
+                project: app.clone(),
+                started_at: Utc::now(),
+                elapsed: Duration::from_secs(40),
+            }],
+            queued: [&app, &toolbox, &toolbox]
+                .into_iter()
+                .map(|project| QueuedJobSnapshot {
+                    ticket: ticket(),
+                    project: project.clone(),
+                })
+                .collect(),
+            average_duration: Some(Duration::from_secs(100)),
+        };
+
+        let waits: Vec<u64> = estimate_waits(&snapshot, Duration::from_secs(100))
+            .iter()
+            .map(Duration::as_secs)
+            .collect();
+        // The queued app job waits for the running one; toolbox jobs take the free slot in turn.
+        assert_eq!(waits, vec![60, 0, 100]);
+    }
+
+    #[test]
+    fn scheme_estimates_replace_the_average() {
+        let app = PathBuf::from("/tmp/VisionApp.xcodeproj");
+        let estimated = |secs| JobTicket {
+            estimated_duration: Some(Duration::from_secs(secs)),
+            ..ticket()
+        };
+        let snapshot = QueueSnapshot {
+            slots: 1,
+            running: vec![RunningJobSnapshot {
+                ticket: estimated(300),
+                project: app.clone(),
+                started_at: Utc::now(),
+                elapsed: Duration::from_secs(100),
+            }],
+            queued: vec![
+                QueuedJobSnapshot {
+                    ticket: estimated(50),
+                    project: app.clone(),
+                },
+                QueuedJobSnapshot {
+                    ticket: ticket(),
+                    project: app,
+                },
+            ],
+            average_duration: None,
+        };
+
+        // Without a process average the jobs' own estimates are averaged for the unknown one.
+        let fallback = fallback_duration(&snapshot).expect("jobs carry estimates");
+        assert_eq!(fallback, Duration::from_secs(175));
+        let waits: Vec<u64> = estimate_waits(&snapshot, fallback)
+            .iter()
+            .map(Duration::as_secs)
+            .collect();
+        assert_eq!(waits, vec![200, 250]);
+    }
+}
@@ -0,0 +1,272 @@
+//! Swift package dependency resolution backed by `xcodebuild -resolvePackageDependencies`.
+mod resolved;
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::process::Command;
+
+use crate::{
+    lib::{
+        errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+        visionos::{collect_log_excerpt, is_allowed_path},
+        xcodebuild::PACKAGE_MANIFEST,
+    },
+    server::config::VisionOsConfig,
+    tools::visionos::build::ProjectKind,
+};
+
+use resolved::{parse_package_resolved, resolved_file_path};
+
+const LOG_EXCERPT_LIMIT: usize = 4000;
+
+const PROJECT_PATH_INVALID_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "project_path_invalid",
+    "project_path is invalid",
+    "Use an absolute .xcodeproj, .xcworkspace, or Package.swift path.",
+);
+const PROJECT_NOT_FOUND_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "project_not_found",
+    "project_path does not exist",
+    "Verify the project path and retry.",
+);
+const PATH_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "path_not_allowed",
+    "project_path is outside the allowlist",
+    "Update visionos.allowed_paths in seiro-mcp.toml; the running server reloads it on save.",
+);
+const PACKAGE_RESOLUTION_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "package_resolution_failed",
+    "xcodebuild -resolvePackageDependencies failed",
+    "Check log_excerpt for the failing package (unreachable URL, missing credentials, or conflicting version requirements), then retry.",
+);
+const PACKAGE_RESOLVED_PARSE_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "package_resolved_parse_failed",
+    "Failed to parse Package.resolved",
+    "Delete the corrupted Package.resolved and resolve again.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(
+        &PROJECT_PATH_INVALID_ERROR,
+        false,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(&PROJECT_NOT_FOUND_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&PATH_NOT_ALLOWED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(
+        &PACKAGE_RESOLUTION_FAILED_ERROR,
+        true,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(
+        &PACKAGE_RESOLVED_PARSE_FAILED_ERROR,
+        false,
+        SandboxState::NoViolation,
+    ),
+];
+
+/// Input for `resolve_package_dependencies`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResolvePackageDependenciesRequest {
+    /// `.xcodeproj`, `.xcworkspace`, `Package.swift`, or a directory holding one of them.
+    pub project_path: PathBuf,
+}
+
+/// A pinned package from `Package.resolved`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ResolvedPackage {
+    pub name: String,
+    /// `remoteSourceControl`, `localSourceControl`, or `registry`; absent in format version 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+}
+
+/// Response from `resolve_package_dependencies`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResolvePackageDependenciesResponse {
+    pub status: &'static str,
+    pub project_path: String,
+    pub project_kind: ProjectKind,
+    /// `Package.resolved` that was read; `None` when the project has no package dependencies.
+    pub resolved_file: Option<String>,
+    pub packages: Vec<ResolvedPackage>,
+    pub invocation: String,
+}
+
+/// Resolve the package graph of an allowlisted project and report the pinned packages.
+pub async fn resolve_package_dependencies(
+    request: ResolvePackageDependenciesRequest,
+    config: &VisionOsConfig,
+) -> Result<ResolvePackageDependenciesResponse, ErrorData> {
+    let project_path = request.project_path;
+    if !project_path.is_absolute() {
+        return Err(build_error_data(
+            &PROJECT_PATH_INVALID_ERROR,
+            json!({ "project_path": project_path.to_string_lossy() }),
+            SandboxState::NoViolation,
+            false,
+        ));
+    }
+    if !project_path.exists() {
+        return Err(build_error_data(
+            &PROJECT_NOT_FOUND_ERROR,
+            json!({ "project_path": project_path.to_string_lossy() }),
+            SandboxState::NoViolation,
+            false,
+        ));
+    }
+    if !config.allowed_paths.is_empty() && !is_allowed_path(&project_path, &config.allowed_paths) {
+        return Err(build_error_data(
+            &PATH_NOT_ALLOWED_ERROR,
+            json!({ "path": project_path.to_string_lossy() }),
+            SandboxState::Blocked,
+            false,
+        ));
+    }
+
+    let kind = ProjectKind::detect(&project_path);
+    let (mut command, invocation) = resolve_command(config, &project_path, kind);
+    let timeout = Duration::from_secs(u64::from(config.max_build_minutes) * 60);
+    let output = match tokio::time::timeout(timeout, command.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(err)) => {
+            return Err(build_error_data(
+                &PACKAGE_RESOLUTION_FAILED_ERROR,
+                json!({ "invocation": invocation, "details": err.to_string() }),
+                SandboxState::NoViolation,
+                true,
+            ))
+        }
+        Err(_) => {
+            return Err(build_error_data(
+                &PACKAGE_RESOLUTION_FAILED_ERROR,
+                json!({
+                    "invocation": invocation,
+                    "details": format!("timed out after {} minutes", config.max_build_minutes),
+                }),
+                SandboxState::NoViolation,
+                true,
+            ))
+        }
+    };
+    if !output.status.success() {
+        return Err(build_error_data(
+            &PACKAGE_RESOLUTION_FAILED_ERROR,
+            json!({
+                "invocation": invocation,
+                "exit_code": output.status.code(),
+                "log_excerpt": collect_log_excerpt(&output.stdout, &output.stderr, LOG_EXCERPT_LIMIT),
+            }),
+            SandboxState::NoViolation,
+            true,
+        ));
+    }
+
+    let resolved_file = resolved_file_path(&project_path, kind).filter(|path| path.is_file());
+    let packages = match &resolved_file {
+        Some(path) => {
+            let contents = std::fs::read(path).map_err(|err| {
+                build_error_data(
+                    &PACKAGE_RESOLVED_PARSE_FAILED_ERROR,
+                    json!({ "resolved_file": path.to_string_lossy(), "details": err.to_string() }),
+                    SandboxState::NoViolation,
+                    false,
+                )
+            })?;
+            parse_package_resolved(&contents).map_err(|err| {
+                build_error_data(
+                    &PACKAGE_RESOLVED_PARSE_FAILED_ERROR,
+                    json!({ "resolved_file": path.to_string_lossy(), "details": err.to_string() }),
+                    SandboxState::NoViolation,
+                    false,
+                )
+            })?
+        }
+        None => Vec::new(),
+    };
+
+    Ok(ResolvePackageDependenciesResponse {
+        status: "ok",
+        project_path: project_path.display().to_string(),
+        project_kind: kind,
+        resolved_file: resolved_file.map(|path| path.display().to_string()),
+        packages,
+        invocation,
+    })
+}
+
+/// Build the `xcodebuild -resolvePackageDependencies` command. Unlike builds, the environment
+/// is inherited so git can reach SSH agents, credential helpers, and `~/.netrc`.
+fn resolve_command(
+    config: &VisionOsConfig,
+    project_path: &Path,
+    kind: ProjectKind,
+) -> (Command, String) {
+    let mut command = Command::new(&config.xcodebuild_path);
+    command.kill_on_drop(true);
+    command.env("DEVELOPER_DIR", &config.xcode_path);
+    command.env("LANG", &config.locale);
+    command.env("LC_ALL", &config.locale);
+
+    let mut args = vec!["-resolvePackageDependencies".to_string()];
+    let working_dir = match kind {
+        ProjectKind::SwiftPackage if project_path.ends_with(PACKAGE_MANIFEST) => {
+            project_path.parent().unwrap_or(project_path)
+        }
+        ProjectKind::Workspace => {
+            args.push("-workspace".into());
+            args.push(project_path.display().to_string());
+            project_path.parent().unwrap_or(project_path)
+        }
+        ProjectKind::XcodeProject
+            if project_path
+                .extension()
+                .is_some_and(|ext| ext == "xcodeproj") =>
+        {
+            args.push("-project".into());
+            args.push(project_path.display().to_string());
+            project_path.parent().unwrap_or(project_path)
+        }
+        _ => project_path,
+    };
+    command.current_dir(working_dir);
+    command.args(&args);
+
+    let invocation = format!(
+        "cd {} && DEVELOPER_DIR={} {} {}",
+        working_dir.display(),
+        config.xcode_path.display(),
+        config.xcodebuild_path.display(),
+        args.join(" ")
+    );
+    (command, invocation)
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    sandbox_state: SandboxState,
+    retryable: bool,
+) -> ErrorData {
+    desc.builder()
+        .details(details)
+        .sandbox_state(sandbox_state)
+        .retryable(retryable)
+        .build()
+        .expect("descriptor is valid")
+}
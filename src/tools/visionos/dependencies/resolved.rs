@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::{ProjectKind, ResolvedPackage};
+use crate::lib::xcodebuild::PACKAGE_MANIFEST;
+
+/// `Package.resolved` written by Xcode 13.3+ / SwiftPM 5.6+ (format versions 2 and 3).
+#[derive(Debug, Deserialize)]
+struct ResolvedFile {
+    #[serde(default)]
+    pins: Option<Vec<Pin>>,
+    /// Format version 1 nests the pins under `object`.
+    #[serde(default)]
+    object: Option<LegacyObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pin {
+    identity: String,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    location: Option<String>,
+    #[serde(default)]
+    state: PinState,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyObject {
+    pins: Vec<LegacyPin>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyPin {
+    package: String,
+    #[serde(rename = "repositoryURL")]
+    repository_url: Option<String>,
+    #[serde(default)]
+    state: PinState,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PinState {
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default)]
+    revision: Option<String>,
+}
+
+/// `Package.resolved` location for a project of the given layout. Xcode keeps it in the shared
+/// data of the (implicit) workspace; packages keep it next to `Package.swift`.
+pub fn resolved_file_path(project_path: &Path, kind: ProjectKind) -> Option<PathBuf> {
+    match kind {
+        ProjectKind::Workspace => Some(project_path.join("xcshareddata/swiftpm/Package.resolved")),
+        ProjectKind::XcodeProject => {
+            let project = if project_path
+                .extension()
+                .is_some_and(|ext| ext == "xcodeproj")
+            {
+                project_path.to_path_buf()
+            } else {
+                let mut candidates: Vec<PathBuf> = std::fs::read_dir(project_path)
+                    .ok()?
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "xcodeproj"))
+                    .collect();
+                candidates.sort();
+                candidates.into_iter().next()?
+            };
+            Some(project.join("project.xcworkspace/xcshareddata/swiftpm/Package.resolved"))
+        }
+        ProjectKind::SwiftPackage => {
+            let dir = if project_path.ends_with(PACKAGE_MANIFEST) {
+                project_path.parent()?
+            } else {
+                project_path
+            };
+            Some(dir.join("Package.resolved"))
+        }
+    }
+}
+
+/// Parse any `Package.resolved` format version into packages sorted by name.
+pub fn parse_package_resolved(contents: &[u8]) -> Result<Vec<ResolvedPackage>, serde_json::Error> {
+    let file: ResolvedFile = serde_json::from_slice(contents)?;
+    let mut packages: Vec<ResolvedPackage> = match (file.pins, file.object) {
+        (Some(pins), _) => pins
+            .into_iter()
+            .map(|pin| ResolvedPackage {
+                name: pin.identity,
+                kind: pin.kind,
+                repository_url: pin.location,
+                version: pin.state.version,
+                branch: pin.state.branch,
+                revision: pin.state.revision,
+            })
+            .collect(),
+        (None, Some(object)) => object
+            .pins
+            .into_iter()
+            .map(|pin| ResolvedPackage {
+                name: pin.package,
+                kind: None,
+                repository_url: pin.repository_url,
+                version: pin.state.version,
+                branch: pin.state.branch,
+                revision: pin.state.revision,
+            })
+            .collect(),
+        (None, None) => Vec::new(),
+    };
+    packages.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_version_two_pins() {
+        let contents = br#"{
+  "originHash" : "9f1c",
+  "pins" : [
+    {
+      "identity" : "swift-collections",
+      "kind" : "remoteSourceControl",
+      "location" : "https://github.com/apple/swift-collections.git",
+      "state" : { "revision" : "671108c", "version" : "1.1.4" }
+    },
+    {
+      "identity" : "RealityKitContent",
+      "kind" : "localSourceControl",
+      "location" : "/Users/example/Packages/RealityKitContent",
+      "state" : { "branch" : "main", "revision" : "0a1b2c3" }
+    }
+  ],
+  "version" : 3
+}"#;
+
+        let packages = parse_package_resolved(contents).expect("version 3 should parse");
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "RealityKitContent");
+        assert_eq!(packages[0].branch.as_deref(), Some("main"));
+        assert_eq!(packages[0].version, None);
+        assert_eq!(packages[1].name, "swift-collections");
+        assert_eq!(packages[1].kind.as_deref(), Some("remoteSourceControl"));
+        assert_eq!(
+            packages[1].repository_url.as_deref(),
+            Some("https://github.com/apple/swift-collections.git")
+        );
+        assert_eq!(packages[1].version.as_deref(), Some("1.1.4"));
+    }
+
+    #[test]
+    fn parses_version_one_pins() {
+        let contents = br#"{
+  "object": {
+    "pins": [
+      {
+        "package": "Alamofire",
+        "repositoryURL": "https://github.com/Alamofire/Alamofire.git",
+        "state": { "branch": null, "revision": "f455c2975872ccd2d9c81594c658af65716e9b9a", "version": "5.9.1" }
+      }
+    ]
+  },
+  "version": 1
+}"#;
+
+        let packages = parse_package_resolved(contents).expect("version 1 should parse");
+
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "Alamofire");
+        assert_eq!(packages[0].kind, None);
+        assert_eq!(packages[0].version.as_deref(), Some("5.9.1"));
+        assert_eq!(packages[0].branch, None);
+    }
+
+    #[test]
+    fn resolved_file_path_follows_project_layout() {
+        assert_eq!(
+            resolved_file_path(Path::new("/src/App.xcodeproj"), ProjectKind::XcodeProject),
+            Some(PathBuf::from(
+                "/src/App.xcodeproj/project.xcworkspace/xcshareddata/swiftpm/Package.resolved"
+            ))
+        );
+        assert_eq!(
+            resolved_file_path(Path::new("/src/App.xcworkspace"), ProjectKind::Workspace),
+            Some(PathBuf::from(
+                "/src/App.xcworkspace/xcshareddata/swiftpm/Package.resolved"
+            ))
+        );
+        assert_eq!(
+            resolved_file_path(
+                Path::new("/src/Kit/Package.swift"),
+                ProjectKind::SwiftPackage
+            ),
+            Some(PathBuf::from("/src/Kit/Package.resolved"))
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use std::{path::Path, process::Output, time::Duration};
+
+use serde_json::Value;
+use tokio::process::Command;
+
+/// `hardwareProperties.platform` values reported for Apple Vision Pro.
+const VISIONOS_PLATFORMS: &[&str] = &["xrOS", "visionOS"];
+/// devicectl can wait indefinitely on a locked or half-paired device.
+const DEVICECTL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Device entry parsed from `devicectl list devices --json-output`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DevicectlDevice {
+    pub udid: String,
+    pub identifier: String,
+    pub name: String,
+    pub model: Option<String>,
+    pub os_version: Option<String>,
+    pub pairing_state: Option<String>,
+    pub tunnel_state: Option<String>,
+    pub transport_type: Option<String>,
+    pub developer_mode: Option<String>,
+}
+
+pub struct DevicectlInvocationResult {
+    pub invocation: String,
+    pub output: Output,
+    /// Contents of the `--json-output` file; empty when devicectl did not write it.
+    pub json: Vec<u8>,
+}
+
+/// Run `xcrun devicectl list devices` with the configured developer directory.
+pub async fn list_devices(
+    xcrun_path: &Path,
+    developer_dir: &Path,
+) -> std::io::Result<DevicectlInvocationResult> {
+    let json_output = tempfile::NamedTempFile::new()?;
+    let mut command = Command::new(xcrun_path);
+    command.kill_on_drop(true);
+    command.env("DEVELOPER_DIR", developer_dir);
+    command.args(["devicectl", "list", "devices", "--json-output"]);
+    command.arg(json_output.path());
+
+    let invocation = format!(
+        "DEVELOPER_DIR={} {} devicectl list devices --json-output {}",
+        developer_dir.display(),
+        xcrun_path.display(),
+        json_output.path().display()
+    );
+    let output = tokio::time::timeout(DEVICECTL_TIMEOUT, command.output())
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!(
+                    "devicectl did not finish within {}s",
+                    DEVICECTL_TIMEOUT.as_secs()
+                ),
+            )
+        })??;
+    let json = std::fs::read(json_output.path()).unwrap_or_default();
+    Ok(DevicectlInvocationResult {
+        invocation,
+        output,
+        json,
+    })
+}
+
+/// Parse the devicectl JSON document, keeping only Apple Vision Pro hardware.
+pub fn parse_visionos_devices(json: &[u8]) -> Result<Vec<DevicectlDevice>, serde_json::Error> {
+    let parsed: Value = serde_json::from_slice(json)?;
+    let Some(entries) = parsed.pointer("/result/devices").and_then(Value::as_array) else {
+        return Ok(Vec::new());
+    };
+
+    let text = |entry: &Value, pointer: &str| {
+        entry
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let mut devices: Vec<DevicectlDevice> = entries
+        .iter()
+        .filter(|entry| {
+            entry
+                .pointer("/hardwareProperties/platform")
+                .and_then(Value::as_str)
+                .is_some_and(|platform| VISIONOS_PLATFORMS.contains(&platform))
+        })
+        .filter_map(|entry| {
+            Some(DevicectlDevice {
+                udid: text(entry, "/hardwareProperties/udid")?,
+                identifier: text(entry, "/identifier")?,
+                name: text(entry, "/deviceProperties/name")
+                    .unwrap_or_else(|| "Apple Vision Pro".into()),
+                model: text(entry, "/hardwareProperties/marketingName"),
+                os_version: text(entry, "/deviceProperties/osVersionNumber"),
+                pairing_state: text(entry, "/connectionProperties/pairingState"),
+                tunnel_state: text(entry, "/connectionProperties/tunnelState"),
+                transport_type: text(entry, "/connectionProperties/transportType"),
+                developer_mode: text(entry, "/deviceProperties/developerModeStatus"),
+            })
+        })
+        .collect();
+    devices.sort_by(|a, b| a.name.cmp(&b.name).then(a.udid.cmp(&b.udid)));
+    Ok(devices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+  "info": { "outcome": "success" },
+  "result": {
+    "devices": [
+      {
+        "identifier": "6A1F2B3C-4D5E-6F70-8192-A3B4C5D6E7F8",
+        "deviceProperties": { "name": "Studio Vision Pro", "osVersionNumber": "26.1", "developerModeStatus": "enabled" },
+        "hardwareProperties": { "platform": "xrOS", "udid": "00008112-001A2C3E0123401E", "marketingName": "Apple Vision Pro" },
+        "connectionProperties": { "pairingState": "paired", "tunnelState": "connected", "transportType": "localNetwork" }
+      },
+      {
+        "identifier": "11111111-2222-3333-4444-555555555555",
+        "deviceProperties": { "name": "iPhone" },
+        "hardwareProperties": { "platform": "iOS", "udid": "00008101-000000000000001E" },
+        "connectionProperties": { "pairingState": "paired", "tunnelState": "disconnected" }
+      }
+    ]
+  }
+}"#;
+
+    #[test]
+    fn parses_visionos_hardware_only() {
+        let devices = parse_visionos_devices(SAMPLE.as_bytes()).expect("sample parses");
+
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].udid, "00008112-001A2C3E0123401E");
+        assert_eq!(devices[0].name, "Studio Vision Pro");
+        assert_eq!(devices[0].tunnel_state.as_deref(), Some("connected"));
+        assert_eq!(devices[0].developer_mode.as_deref(), Some("enabled"));
+    }
+}
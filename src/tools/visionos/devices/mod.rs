@@ -0,0 +1,170 @@
+//! Connected Apple Vision Pro hardware discovered with `xcrun devicectl`.
+mod devicectl;
+
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    lib::{
+        devices::is_allowed_device,
+        errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+    },
+    server::config::VisionOsConfig,
+};
+
+use devicectl::{parse_visionos_devices, DevicectlDevice};
+
+/// devicectl reports this tunnel state for devices it cannot reach.
+const UNAVAILABLE_TUNNEL_STATE: &str = "unavailable";
+
+const DEVICECTL_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "devicectl_failed",
+    "xcrun devicectl failed",
+    "Check visionos.xcrun_path and that Xcode 15 or later is selected, then retry.",
+);
+const DEVICECTL_PARSE_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "devicectl_parse_failed",
+    "Failed to parse devicectl device list output",
+    "Verify `xcrun devicectl list devices --json-output` output format and retry.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&DEVICECTL_FAILED_ERROR, true, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(
+        &DEVICECTL_PARSE_FAILED_ERROR,
+        true,
+        SandboxState::NoViolation,
+    ),
+];
+
+/// Input for `list_devices`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListDevicesRequest {
+    /// Include paired devices that are currently unreachable.
+    #[serde(default)]
+    pub include_unavailable: bool,
+}
+
+/// A paired Apple Vision Pro.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct PhysicalDevice {
+    /// Hardware UDID, used in `destination` and `visionos.allowed_device_udids`.
+    pub udid: String,
+    /// CoreDevice identifier used by `devicectl`.
+    pub identifier: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pairing_state: Option<String>,
+    /// `connected`, `disconnected` (reachable, no tunnel yet), or `unavailable`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_state: Option<String>,
+    /// `wired` or `localNetwork`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transport_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub developer_mode: Option<String>,
+    /// Whether `visionos.allowed_device_udids` lets builds target this device.
+    pub allowed: bool,
+    /// `destination` value selecting this device.
+    pub destination: String,
+}
+
+impl PhysicalDevice {
+    fn new(device: DevicectlDevice, allowed_udids: &[String]) -> Self {
+        let allowed = is_allowed_device(&device.udid, allowed_udids)
+            || is_allowed_device(&device.identifier, allowed_udids);
+        Self {
+            destination: format!("platform=visionOS,id={}", device.udid),
+            udid: device.udid,
+            identifier: device.identifier,
+            name: device.name,
+            model: device.model,
+            os_version: device.os_version,
+            pairing_state: device.pairing_state,
+            connection_state: device.tunnel_state,
+            transport_type: device.transport_type,
+            developer_mode: device.developer_mode,
+            allowed,
+        }
+    }
+}
+
+/// Response from `list_devices`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListDevicesResponse {
+    pub status: &'static str,
+    pub devices: Vec<PhysicalDevice>,
+    pub invocation: String,
+}
+
+/// Enumerate Apple Vision Pro devices paired with this Mac.
+pub async fn list_devices(
+    request: ListDevicesRequest,
+    config: &VisionOsConfig,
+) -> Result<ListDevicesResponse, ErrorData> {
+    let result = devicectl::list_devices(&config.xcrun_path, &config.xcode_path)
+        .await
+        .map_err(|err| {
+            build_error_data(
+                &DEVICECTL_FAILED_ERROR,
+                json!({ "details": err.to_string() }),
+                SandboxState::NoViolation,
+                true,
+            )
+        })?;
+    if !result.output.status.success() {
+        return Err(build_error_data(
+            &DEVICECTL_FAILED_ERROR,
+            json!({
+                "invocation": result.invocation,
+                "exit_code": result.output.status.code(),
+                "stderr": String::from_utf8_lossy(&result.output.stderr)
+            }),
+            SandboxState::NoViolation,
+            true,
+        ));
+    }
+
+    let devices = parse_visionos_devices(&result.json).map_err(|err| {
+        build_error_data(
+            &DEVICECTL_PARSE_FAILED_ERROR,
+            json!({ "details": err.to_string(), "invocation": result.invocation }),
+            SandboxState::NoViolation,
+            true,
+        )
+    })?;
+    let devices = devices
+        .into_iter()
+        .filter(|device| {
+            request.include_unavailable
+                || device.tunnel_state.as_deref() != Some(UNAVAILABLE_TUNNEL_STATE)
+        })
+        .map(|device| PhysicalDevice::new(device, &config.allowed_device_udids))
+        .collect();
+
+    Ok(ListDevicesResponse {
+        status: "ok",
+        devices,
+        invocation: result.invocation,
+    })
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    sandbox_state: SandboxState,
+    retryable: bool,
+) -> ErrorData {
+    desc.builder()
+        .details(details)
+        .sandbox_state(sandbox_state)
+        .retryable(retryable)
+        .build()
+        .expect("descriptor is valid")
+}
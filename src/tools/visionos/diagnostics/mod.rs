@@ -8,10 +8,9 @@ use rmcp::model::ErrorData;
 use schemars::JsonSchema;
 use serde::Serialize;
 use serde_json::{json, Value};
-use uuid::Uuid;
 
 use crate::{
-    lib::errors::{SandboxState, ToolErrorDescriptor},
+    lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
     tools::visionos::{
         artifacts::{BuildJobStatus, FetchBuildOutputError, VisionOsArtifactStore},
         visionos_fetch_error,
@@ -25,7 +24,7 @@ use swift_typecheck::run_typecheck;
 const INVALID_JOB_ID_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "invalid_job_id",
     "Invalid job_id format",
-    "Provide a UUID-formatted job_id and retry.",
+    "Provide a UUID-formatted job_id (or \"latest\") and retry.",
 );
 const JOB_NOT_FOUND_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "job_not_found",
@@ -43,6 +42,17 @@ const DIAGNOSTICS_UNAVAILABLE_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::
     "Re-run build_visionos_app, then inspect diagnostics again with the new job_id.",
 );
 
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&INVALID_JOB_ID_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&JOB_NOT_FOUND_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&DIAGNOSTICS_EXPIRED_ERROR, true, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(
+        &DIAGNOSTICS_UNAVAILABLE_ERROR,
+        true,
+        SandboxState::NoViolation,
+    ),
+];
+
 /// Failure summary payload.
 #[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct BuildFailureSummary {
@@ -78,14 +88,10 @@ pub async fn inspect_build_diagnostics(
     store: &VisionOsArtifactStore,
     request: InspectBuildDiagnosticsRequest,
 ) -> Result<InspectBuildDiagnosticsResponse, ErrorData> {
-    let job_id = Uuid::parse_str(request.job_id.trim()).map_err(|_| {
-        build_error_data(
-            &INVALID_JOB_ID_ERROR,
-            json!({ "details": request.job_id }),
-            SandboxState::NoViolation,
-            false,
-        )
-    })?;
+    let job_id = store
+        .resolve_job_id(&request.job_id, request.scheme.as_deref())
+        .await
+        .map_err(map_fetch_error)?;
 
     let record = store.fetch_record(&job_id).await.map_err(map_fetch_error)?;
     if record.status != BuildJobStatus::Failed {
@@ -104,7 +110,7 @@ pub async fn inspect_build_diagnostics(
             log_excerpt: request
                 .include_log_excerpt
                 .then(|| record.log_excerpt.clone()),
-            notes: vec!["job status is succeeded".into()],
+            notes: vec![format!("job status is {}", record.status.as_str())],
         });
     }
 
@@ -196,6 +202,12 @@ fn build_log_headline(log_excerpt: &str) -> String {
 
 fn map_fetch_error(err: FetchBuildOutputError) -> ErrorData {
     match err {
+        FetchBuildOutputError::InvalidJobId { raw } => build_error_data(
+            &INVALID_JOB_ID_ERROR,
+            json!({ "details": raw }),
+            SandboxState::NoViolation,
+            false,
+        ),
         FetchBuildOutputError::JobNotFound { job_id } => build_error_data(
             &JOB_NOT_FOUND_ERROR,
             json!({ "job_id": job_id.to_string() }),
@@ -230,8 +242,9 @@ fn build_error_data(
 mod tests {
     use chrono::Utc;
     use tempfile::tempdir;
+    use uuid::Uuid;
 
-    use crate::tools::visionos::artifacts::VisionOsArtifactStore;
+    use crate::tools::visionos::artifacts::{ArtifactKind, VisionOsArtifactStore};
 
     use super::*;
 
@@ -247,7 +260,13 @@ mod tests {
         let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
         let job_id = Uuid::new_v4();
         store
-            .record_failure(job_id, "failed".into(), None, Utc::now())
+            .record_failure(
+                job_id,
+                ArtifactKind::Build,
+                "failed".into(),
+                None,
+                Utc::now(),
+            )
             .await
             .expect("record failure");
 
@@ -255,6 +274,7 @@ mod tests {
             &store,
             InspectBuildDiagnosticsRequest {
                 job_id: job_id.to_string(),
+                scheme: None,
                 include_log_excerpt: true,
                 prefer_typecheck: true,
             },
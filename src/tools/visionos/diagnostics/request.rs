@@ -4,7 +4,11 @@ use serde::Deserialize;
 /// Input for `inspect_build_diagnostics`.
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct InspectBuildDiagnosticsRequest {
+    /// Job UUID, or `"latest"` for the most recent job.
     pub job_id: String,
+    /// Restrict `"latest"` to jobs built from this scheme.
+    #[serde(default)]
+    pub scheme: Option<String>,
     #[serde(default = "default_include_log_excerpt")]
     pub include_log_excerpt: bool,
     #[serde(default = "default_prefer_typecheck")]
@@ -0,0 +1,402 @@
+//! Export signed `.ipa` files from archive jobs with `xcodebuild -exportArchive`.
+mod options;
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::Utc;
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::{
+    lib::{
+        errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+        fs as artifact_fs,
+        visionos::{collect_log_excerpt, is_allowed_path},
+        xcodebuild::archive_path,
+    },
+    server::config::VisionOsConfig,
+    tools::visionos::artifacts::{
+        fetch_error_to_error_data, ArtifactFile, ArtifactKind, BuildJobStatus,
+        VisionOsArtifactStore,
+    },
+};
+
+use options::{render_export_options, validate_export_options, MAX_EXPORT_OPTIONS_BYTES};
+pub use options::{ExportMethod, ExportOptionsError};
+
+const LOG_EXCERPT_LIMIT: usize = 4000;
+const EXPORT_OPTIONS_FILE: &str = "ExportOptions.plist";
+
+const ARCHIVE_REQUIRED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "archive_required",
+    "job_id does not refer to a succeeded archive job",
+    "Run archive_visionos_app first and pass its job_id.",
+);
+const ARCHIVE_NOT_FOUND_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "archive_not_found",
+    "The .xcarchive of the archive job is no longer on disk",
+    "Run archive_visionos_app again, then export the new job.",
+);
+const EXPORT_OPTIONS_INVALID_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "export_options_invalid",
+    "The export options are invalid",
+    "Check method, team_id, and provisioning_profiles, or fix the export_options_plist file.",
+);
+const PROVISIONING_PROFILE_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "provisioning_profile_not_allowed",
+    "The provisioning profile is not allowed by the server policy",
+    "Use a profile listed in visionos.signing.allowed_provisioning_profiles.",
+);
+const PATH_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "path_not_allowed",
+    "export_options_plist is outside the allowlist",
+    "Keep the export options plist under visionos.allowed_paths.",
+);
+const IPA_EXPORT_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "ipa_export_failed",
+    "xcodebuild -exportArchive failed",
+    "Check log_excerpt for signing or provisioning errors, then retry.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&ARCHIVE_REQUIRED_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&ARCHIVE_NOT_FOUND_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(
+        &EXPORT_OPTIONS_INVALID_ERROR,
+        false,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(
+        &PROVISIONING_PROFILE_NOT_ALLOWED_ERROR,
+        false,
+        SandboxState::Blocked,
+    ),
+    ErrorCatalogEntry::new(&PATH_NOT_ALLOWED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&IPA_EXPORT_FAILED_ERROR, true, SandboxState::NoViolation),
+];
+
+/// Input for `export_visionos_ipa`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportVisionOsIpaRequest {
+    /// Archive job UUID, or `"latest"` for the most recent job.
+    pub job_id: String,
+    /// Restrict `"latest"` to jobs built from this scheme.
+    #[serde(default)]
+    pub scheme: Option<String>,
+    #[serde(default)]
+    pub method: ExportMethod,
+    /// Defaults to `visionos.signing.team_id`.
+    #[serde(default)]
+    pub team_id: Option<String>,
+    /// Bundle identifier to provisioning profile name; switches to manual signing when set.
+    #[serde(default)]
+    pub provisioning_profiles: BTreeMap<String, String>,
+    /// Existing export options plist under `visionos.allowed_paths`, used instead of `method`,
+    /// `team_id`, and `provisioning_profiles`.
+    #[serde(default)]
+    pub export_options_plist: Option<PathBuf>,
+}
+
+/// Response from `export_visionos_ipa`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportVisionOsIpaResponse {
+    /// Job holding the IPA; pass it to `fetch_build_output`.
+    pub job_id: String,
+    pub archive_job_id: String,
+    pub status: &'static str,
+    pub method: ExportMethod,
+    pub ipa_path: String,
+    pub sha256: String,
+    pub download_ttl_seconds: u32,
+    /// Presigned URL when an object-storage backend is active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+    pub export_options_plist: String,
+    pub log_excerpt: String,
+}
+
+/// Export the archive of a succeeded archive job and store the IPA as a new job.
+pub async fn export_visionos_ipa(
+    store: &VisionOsArtifactStore,
+    config: &VisionOsConfig,
+    request: ExportVisionOsIpaRequest,
+) -> Result<ExportVisionOsIpaResponse, ErrorData> {
+    let archive_job_id = store
+        .resolve_job_id(&request.job_id, request.scheme.as_deref())
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    let archive_record = store
+        .fetch_record(&archive_job_id)
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    let (BuildJobStatus::Succeeded, ArtifactKind::Archive, Some(scheme)) = (
+        archive_record.status,
+        archive_record.artifact_kind,
+        archive_record.scheme.clone(),
+    ) else {
+        return Err(build_error_data(
+            &ARCHIVE_REQUIRED_ERROR,
+            json!({
+                "job_id": archive_job_id.to_string(),
+                "status": archive_record.status.as_str(),
+                "artifact_kind": archive_record.artifact_kind.as_str(),
+            }),
+            SandboxState::NoViolation,
+            false,
+        ));
+    };
+    let archive = archive_path(
+        &store
+            .root_dir()
+            .join(archive_job_id.to_string())
+            .join("staging"),
+        &scheme,
+    );
+    if !archive.is_dir() {
+        return Err(build_error_data(
+            &ARCHIVE_NOT_FOUND_ERROR,
+            json!({ "archive_path": archive.to_string_lossy() }),
+            SandboxState::NoViolation,
+            false,
+        ));
+    }
+
+    let (plist, method) = export_options(&request, config)?;
+    let job_id = Uuid::new_v4();
+    let job_dir = artifact_fs::ensure_job_dir(&store.root_dir(), &job_id)
+        .map_err(|err| export_failed(json!({ "details": err.to_string() })))?;
+    let (ipa, sha256, log_excerpt) = match run_export(config, &archive, &job_dir, &plist).await {
+        Ok(exported) => exported,
+        Err(err) => {
+            let _ = fs::remove_dir_all(&job_dir);
+            return Err(err);
+        }
+    };
+    let options_path = job_dir.join(EXPORT_OPTIONS_FILE);
+
+    store
+        .record_success(
+            job_id,
+            ArtifactKind::Ipa,
+            scheme,
+            ArtifactFile {
+                path: ipa.clone(),
+                sha256: sha256.clone(),
+                cache_key: None,
+                ttl_secs: archive_record.ttl_secs,
+            },
+            log_excerpt.clone(),
+            Utc::now(),
+        )
+        .await
+        .map_err(|err| export_failed(json!({ "details": err.to_string() })))?;
+    let record = store
+        .fetch_record(&job_id)
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    let download_url = store
+        .download_url(&record)
+        .await
+        .map_err(|err| export_failed(json!({ "details": err.to_string() })))?;
+
+    Ok(ExportVisionOsIpaResponse {
+        job_id: job_id.to_string(),
+        archive_job_id: archive_job_id.to_string(),
+        status: "succeeded",
+        method,
+        ipa_path: ipa.to_string_lossy().to_string(),
+        sha256,
+        download_ttl_seconds: store.ttl_seconds_remaining(&record),
+        download_url,
+        export_options_plist: options_path.to_string_lossy().to_string(),
+        log_excerpt,
+    })
+}
+
+/// Write the options plist into `job_dir`, run the export, and return the IPA with its SHA256
+/// and log excerpt.
+async fn run_export(
+    config: &VisionOsConfig,
+    archive: &Path,
+    job_dir: &Path,
+    plist: &str,
+) -> Result<(PathBuf, String, String), ErrorData> {
+    // xcodebuild may resolve relative paths against another directory; pass absolute ones.
+    let job_dir = std::path::absolute(job_dir)
+        .map_err(|err| export_failed(json!({ "details": err.to_string() })))?;
+    let options_path = job_dir.join(EXPORT_OPTIONS_FILE);
+    fs::write(&options_path, plist)
+        .map_err(|err| export_failed(json!({ "details": err.to_string() })))?;
+    let export_dir = job_dir.join("export");
+
+    let mut command = export_command(config, archive, &export_dir, &options_path);
+    let timeout = Duration::from_secs(u64::from(config.max_build_minutes) * 60);
+    let output = match tokio::time::timeout(timeout, command.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(err)) => return Err(export_failed(json!({ "details": err.to_string() }))),
+        Err(_) => {
+            return Err(export_failed(json!({
+                "details": format!("timed out after {} minutes", config.max_build_minutes),
+            })))
+        }
+    };
+    let log_excerpt = collect_log_excerpt(&output.stdout, &output.stderr, LOG_EXCERPT_LIMIT);
+    if !output.status.success() {
+        return Err(export_failed(json!({
+            "exit_code": output.status.code(),
+            "log_excerpt": log_excerpt,
+        })));
+    }
+    let Some(ipa) = find_ipa(&export_dir) else {
+        return Err(export_failed(json!({
+            "details": format!("no .ipa was written to {}", export_dir.display()),
+            "log_excerpt": log_excerpt,
+        })));
+    };
+    let sha256 = artifact_fs::compute_sha256(&ipa)
+        .map_err(|err| export_failed(json!({ "details": err.to_string() })))?;
+    Ok((ipa, sha256, log_excerpt))
+}
+
+/// Validated plist contents and the method they select.
+fn export_options(
+    request: &ExportVisionOsIpaRequest,
+    config: &VisionOsConfig,
+) -> Result<(String, ExportMethod), ErrorData> {
+    let allowed_profiles = &config.signing.allowed_provisioning_profiles;
+    let Some(path) = &request.export_options_plist else {
+        let team_id = request
+            .team_id
+            .as_deref()
+            .or(config.signing.team_id.as_deref());
+        return render_export_options(
+            request.method,
+            team_id,
+            &request.provisioning_profiles,
+            allowed_profiles,
+        )
+        .map(|plist| (plist, request.method))
+        .map_err(options_error_to_error_data);
+    };
+
+    if request.team_id.is_some() || !request.provisioning_profiles.is_empty() {
+        return Err(build_error_data(
+            &EXPORT_OPTIONS_INVALID_ERROR,
+            json!({ "details": "export_options_plist cannot be combined with team_id or provisioning_profiles" }),
+            SandboxState::NoViolation,
+            false,
+        ));
+    }
+    if !path.is_absolute() || !is_allowed_path(path, &config.allowed_paths) {
+        return Err(build_error_data(
+            &PATH_NOT_ALLOWED_ERROR,
+            json!({ "path": path.to_string_lossy() }),
+            SandboxState::Blocked,
+            false,
+        ));
+    }
+    let contents = fs::metadata(path)
+        .ok()
+        .filter(|metadata| metadata.is_file() && metadata.len() <= MAX_EXPORT_OPTIONS_BYTES)
+        .and_then(|_| fs::read_to_string(path).ok())
+        .ok_or_else(|| {
+            build_error_data(
+                &EXPORT_OPTIONS_INVALID_ERROR,
+                json!({
+                    "path": path.to_string_lossy(),
+                    "details": format!("not a readable file of at most {MAX_EXPORT_OPTIONS_BYTES} bytes"),
+                }),
+                SandboxState::NoViolation,
+                false,
+            )
+        })?;
+    let method = validate_export_options(&contents, allowed_profiles)
+        .map_err(options_error_to_error_data)?;
+    Ok((contents, method))
+}
+
+/// Build the `xcodebuild -exportArchive` command. Like package resolution, the environment is
+/// inherited so signing can reach the login keychain and Xcode accounts.
+fn export_command(
+    config: &VisionOsConfig,
+    archive: &Path,
+    export_dir: &Path,
+    options_path: &Path,
+) -> Command {
+    let mut command = Command::new(&config.xcodebuild_path);
+    command.kill_on_drop(true);
+    command.env("DEVELOPER_DIR", &config.xcode_path);
+    command.env("LANG", &config.locale);
+    command.env("LC_ALL", &config.locale);
+    command
+        .arg("-exportArchive")
+        .arg("-archivePath")
+        .arg(archive)
+        .arg("-exportPath")
+        .arg(export_dir)
+        .arg("-exportOptionsPlist")
+        .arg(options_path);
+    command
+}
+
+fn find_ipa(export_dir: &Path) -> Option<PathBuf> {
+    let mut ipas: Vec<PathBuf> = fs::read_dir(export_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "ipa"))
+        .collect();
+    ipas.sort();
+    ipas.into_iter().next()
+}
+
+fn options_error_to_error_data(err: ExportOptionsError) -> ErrorData {
+    let (desc, sandbox_state) = match err {
+        ExportOptionsError::ProvisioningProfileNotAllowed { .. } => (
+            &PROVISIONING_PROFILE_NOT_ALLOWED_ERROR,
+            SandboxState::Blocked,
+        ),
+        ExportOptionsError::UploadNotAllowed => {
+            (&EXPORT_OPTIONS_INVALID_ERROR, SandboxState::Blocked)
+        }
+        _ => (&EXPORT_OPTIONS_INVALID_ERROR, SandboxState::NoViolation),
+    };
+    build_error_data(
+        desc,
+        json!({ "details": err.to_string() }),
+        sandbox_state,
+        false,
+    )
+}
+
+fn export_failed(details: Value) -> ErrorData {
+    build_error_data(
+        &IPA_EXPORT_FAILED_ERROR,
+        details,
+        SandboxState::NoViolation,
+        true,
+    )
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    sandbox_state: SandboxState,
+    retryable: bool,
+) -> ErrorData {
+    desc.builder()
+        .details(details)
+        .sandbox_state(sandbox_state)
+        .retryable(retryable)
+        .build()
+        .expect("descriptor is valid")
+}
@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::lib::signing::is_team_id;
+
+/// Largest export options plist accepted from `export_options_plist`.
+pub const MAX_EXPORT_OPTIONS_BYTES: u64 = 64 * 1024;
+
+/// Distribution method written to the `method` key (Xcode 15.3+ names).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportMethod {
+    AppStoreConnect,
+    /// Ad hoc distribution to registered devices.
+    ReleaseTesting,
+    Enterprise,
+    /// Development-signed build for registered devices.
+    #[default]
+    Debugging,
+}
+
+impl ExportMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::AppStoreConnect => "app-store-connect",
+            Self::ReleaseTesting => "release-testing",
+            Self::Enterprise => "enterprise",
+            Self::Debugging => "debugging",
+        }
+    }
+
+    /// Accept current names and the ones Xcode used before 15.3.
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "app-store-connect" | "app-store" => Some(Self::AppStoreConnect),
+            "release-testing" | "ad-hoc" => Some(Self::ReleaseTesting),
+            "enterprise" => Some(Self::Enterprise),
+            "debugging" | "development" => Some(Self::Debugging),
+            _ => None,
+        }
+    }
+}
+
+/// Why export options were rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ExportOptionsError {
+    #[error("team_id `{team}` is not a 10-character Apple Developer team ID")]
+    InvalidTeamId { team: String },
+    #[error("`{bundle_id}` is not a valid bundle identifier")]
+    InvalidBundleId { bundle_id: String },
+    #[error(
+        "provisioning profile `{profile}` is not in visionos.signing.allowed_provisioning_profiles"
+    )]
+    ProvisioningProfileNotAllowed { profile: String },
+    #[error("export options are not an XML property list")]
+    NotAPlist,
+    #[error("export options method `{method}` is not supported")]
+    UnsupportedMethod { method: String },
+    #[error(
+        "export options must keep destination `export`; uploading from the server is not allowed"
+    )]
+    UploadNotAllowed,
+}
+
+/// Render an `-exportOptionsPlist` file after validating the team and profiles.
+pub fn render_export_options(
+    method: ExportMethod,
+    team_id: Option<&str>,
+    provisioning_profiles: &BTreeMap<String, String>,
+    allowed_profiles: &[String],
+) -> Result<String, ExportOptionsError> {
+    if let Some(team) = team_id.filter(|team| !is_team_id(team)) {
+        return Err(ExportOptionsError::InvalidTeamId {
+            team: team.to_string(),
+        });
+    }
+    for (bundle_id, profile) in provisioning_profiles {
+        if !is_bundle_id(bundle_id) {
+            return Err(ExportOptionsError::InvalidBundleId {
+                bundle_id: bundle_id.clone(),
+            });
+        }
+        if profile.trim().is_empty()
+            || (!allowed_profiles.is_empty() && !allowed_profiles.contains(profile))
+        {
+            return Err(ExportOptionsError::ProvisioningProfileNotAllowed {
+                profile: profile.clone(),
+            });
+        }
+    }
+
+    let mut plist = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" ",
+        "\"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+        "<plist version=\"1.0\">\n<dict>\n",
+        "\t<key>destination</key>\n\t<string>export</string>\n",
+    ));
+    plist.push_str(&format!(
+        "\t<key>method</key>\n\t<string>{}</string>\n",
+        method.as_str()
+    ));
+    let signing_style = if provisioning_profiles.is_empty() {
+        "automatic"
+    } else {
+        "manual"
+    };
+    plist.push_str(&format!(
+        "\t<key>signingStyle</key>\n\t<string>{signing_style}</string>\n"
+    ));
+    if let Some(team) = team_id {
+        plist.push_str(&format!("\t<key>teamID</key>\n\t<string>{team}</string>\n"));
+    }
+    if !provisioning_profiles.is_empty() {
+        plist.push_str("\t<key>provisioningProfiles</key>\n\t<dict>\n");
+        for (bundle_id, profile) in provisioning_profiles {
+            plist.push_str(&format!(
+                "\t\t<key>{bundle_id}</key>\n\t\t<string>{}</string>\n",
+                escape_xml(profile)
+            ));
+        }
+        plist.push_str("\t</dict>\n");
+    }
+    plist.push_str("</dict>\n</plist>\n");
+    Ok(plist)
+}
+
+/// Check a caller-supplied plist: a known `method`, a valid `teamID`, allowlisted profiles, and
+/// no upload.
+pub fn validate_export_options(
+    contents: &str,
+    allowed_profiles: &[String],
+) -> Result<ExportMethod, ExportOptionsError> {
+    let trimmed = contents.trim_start();
+    if !(trimmed.starts_with("<?xml") || trimmed.starts_with("<plist"))
+        || !contents.contains("<dict>")
+    {
+        return Err(ExportOptionsError::NotAPlist);
+    }
+    let method = match plist_string_value(contents, "method") {
+        Some(method) => {
+            ExportMethod::parse(method).ok_or_else(|| ExportOptionsError::UnsupportedMethod {
+                method: method.to_string(),
+            })?
+        }
+        None => ExportMethod::default(),
+    };
+    if let Some(team) = plist_string_value(contents, "teamID").filter(|team| !is_team_id(team)) {
+        return Err(ExportOptionsError::InvalidTeamId {
+            team: team.to_string(),
+        });
+    }
+    if plist_string_value(contents, "destination").is_some_and(|value| value != "export") {
+        return Err(ExportOptionsError::UploadNotAllowed);
+    }
+    if !allowed_profiles.is_empty() {
+        if let Some(profile) = provisioning_profile_names(contents)
+            .find(|profile| !allowed_profiles.iter().any(|allowed| allowed == profile))
+        {
+            return Err(ExportOptionsError::ProvisioningProfileNotAllowed {
+                profile: profile.to_string(),
+            });
+        }
+    }
+    Ok(method)
+}
+
+/// Profile names in the `provisioningProfiles` dictionary.
+fn provisioning_profile_names(contents: &str) -> impl Iterator<Item = &str> {
+    let profiles = contents
+        .split_once("<key>provisioningProfiles</key>")
+        .and_then(|(_, rest)| rest.trim_start().strip_prefix("<dict>"))
+        .and_then(|rest| rest.split_once("</dict>"))
+        .map_or("", |(dict, _)| dict);
+    profiles
+        .split("<string>")
+        .skip(1)
+        .filter_map(|value| value.split_once("</string>"))
+        .map(|(value, _)| value.trim())
+}
+
+/// `<string>` value following `<key>{key}</key>`, ignoring whitespace between the two.
+fn plist_string_value<'a>(contents: &'a str, key: &str) -> Option<&'a str> {
+    let (_, rest) = contents.split_once(&format!("<key>{key}</key>"))?;
+    let rest = rest.trim_start().strip_prefix("<string>")?;
+    let (value, _) = rest.split_once("</string>")?;
+    Some(value.trim())
+}
+
+fn is_bundle_id(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 155
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '*')
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendered_options_use_manual_signing_for_profiles() {
+        let profiles = BTreeMap::from([(
+            "com.example.VisionApp".to_string(),
+            "VisionApp Ad Hoc".to_string(),
+        )]);
+
+        let plist = render_export_options(
+            ExportMethod::ReleaseTesting,
+            Some("A1B2C3D4E5"),
+            &profiles,
+            &["VisionApp Ad Hoc".to_string()],
+        )
+        .expect("options are valid");
+
+        assert_eq!(
+            validate_export_options(&plist, &["VisionApp Ad Hoc".to_string()]),
+            Ok(ExportMethod::ReleaseTesting)
+        );
+        assert_eq!(
+            validate_export_options(&plist, &["Other".to_string()]),
+            Err(ExportOptionsError::ProvisioningProfileNotAllowed {
+                profile: "VisionApp Ad Hoc".into()
+            })
+        );
+        assert_eq!(plist_string_value(&plist, "signingStyle"), Some("manual"));
+        assert_eq!(
+            plist_string_value(&plist, "com.example.VisionApp"),
+            Some("VisionApp Ad Hoc")
+        );
+        assert_eq!(
+            render_export_options(
+                ExportMethod::ReleaseTesting,
+                None,
+                &profiles,
+                &["Other".to_string()]
+            ),
+            Err(ExportOptionsError::ProvisioningProfileNotAllowed {
+                profile: "VisionApp Ad Hoc".into()
+            })
+        );
+    }
+
+    #[test]
+    fn caller_plist_must_not_upload_or_use_unknown_methods() {
+        let upload = "<?xml version=\"1.0\"?><plist><dict><key>method</key><string>app-store</string><key>destination</key><string>upload</string></dict></plist>";
+        let unknown = "<plist><dict><key>method</key> <string>sideload</string></dict></plist>";
+
+        assert_eq!(
+            validate_export_options(upload, &[]),
+            Err(ExportOptionsError::UploadNotAllowed)
+        );
+        assert_eq!(
+            validate_export_options(unknown, &[]),
+            Err(ExportOptionsError::UnsupportedMethod {
+                method: "sideload".into()
+            })
+        );
+        assert_eq!(
+            validate_export_options("method = debugging", &[]),
+            Err(ExportOptionsError::NotAPlist)
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Compare warning/error sets between two recorded build jobs.
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::tools::visionos::artifacts::{fetch_error_to_error_data, VisionOsArtifactStore};
+
+pub use crate::lib::xcodebuild::{parse_diagnostics, BuildLogDiagnostic, DiagnosticSeverity};
+
+/// Input for `diff_build_logs`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DiffBuildLogsRequest {
+    /// Earlier job (typically the failing build before a fix).
+    pub base_job_id: String,
+    /// Later job to compare against the base; `"latest"` selects the most recent job.
+    pub target_job_id: String,
+    /// Restrict `"latest"` to jobs built from this scheme.
+    #[serde(default)]
+    pub scheme: Option<String>,
+}
+
+/// Error/warning counts for one job.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct DiagnosticCounts {
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// Response from `diff_build_logs`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DiffBuildLogsResponse {
+    pub status: &'static str,
+    pub base_job_id: String,
+    pub target_job_id: String,
+    pub base_counts: DiagnosticCounts,
+    pub target_counts: DiagnosticCounts,
+    pub introduced: Vec<BuildLogDiagnostic>,
+    pub resolved: Vec<BuildLogDiagnostic>,
+    pub unchanged_count: usize,
+    pub notes: Vec<String>,
+}
+
+/// Diff the diagnostics recorded for two jobs.
+pub async fn diff_build_logs(
+    store: &VisionOsArtifactStore,
+    request: DiffBuildLogsRequest,
+) -> Result<DiffBuildLogsResponse, ErrorData> {
+    let scheme = request.scheme.as_deref();
+    let base_job_id = store
+        .resolve_job_id(&request.base_job_id, scheme)
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    let target_job_id = store
+        .resolve_job_id(&request.target_job_id, scheme)
+        .await
+        .map_err(fetch_error_to_error_data)?;
+
+    let base = store
+        .fetch_record(&base_job_id)
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    let target = store
+        .fetch_record(&target_job_id)
+        .await
+        .map_err(fetch_error_to_error_data)?;
+
+    let base_diagnostics = parse_diagnostics(&base.log_excerpt);
+    let target_diagnostics = parse_diagnostics(&target.log_excerpt);
+
+    let introduced: Vec<_> = target_diagnostics
+        .iter()
+        .filter(|diagnostic| !contains(&base_diagnostics, diagnostic))
+        .cloned()
+        .collect();
+    let resolved: Vec<_> = base_diagnostics
+        .iter()
+        .filter(|diagnostic| !contains(&target_diagnostics, diagnostic))
+        .cloned()
+        .collect();
+    let unchanged_count = target_diagnostics.len() - introduced.len();
+
+    let mut notes =
+        vec!["diagnostics are compared from stored log excerpts; line numbers are ignored".into()];
+    if base_job_id == target_job_id {
+        notes.push("base_job_id and target_job_id refer to the same job".into());
+    }
+
+    Ok(DiffBuildLogsResponse {
+        status: "ok",
+        base_job_id: base_job_id.to_string(),
+        target_job_id: target_job_id.to_string(),
+        base_counts: count(&base_diagnostics),
+        target_counts: count(&target_diagnostics),
+        introduced,
+        resolved,
+        unchanged_count,
+        notes,
+    })
+}
+
+fn contains(haystack: &[BuildLogDiagnostic], needle: &BuildLogDiagnostic) -> bool {
+    haystack
+        .iter()
+        .any(|candidate| candidate.key() == needle.key())
+}
+
+fn count(diagnostics: &[BuildLogDiagnostic]) -> DiagnosticCounts {
+    DiagnosticCounts {
+        errors: diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == DiagnosticSeverity::Error)
+            .count(),
+        warnings: diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == DiagnosticSeverity::Warning)
+            .count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::Value;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::tools::visionos::artifacts::ArtifactKind;
+
+    #[tokio::test]
+    async fn diff_reports_introduced_and_resolved_diagnostics() {
+        let temp = tempdir().expect("tempdir");
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+        let base = Uuid::new_v4();
+        let target = Uuid::new_v4();
+        store
+            .record_failure(
+                base,
+                ArtifactKind::Build,
+                "/tmp/A.swift:10:5: error: cannot find 'foo' in scope\n\
+                 /tmp/B.swift:3:1: warning: variable 'x' was never used"
+                    .into(),
+                None,
+                Utc::now(),
+            )
+            .await
+            .expect("record base");
+        store
+            .record_failure(
+                target,
+                ArtifactKind::Build,
+                "/tmp/B.swift:4:1: warning: variable 'x' was never used\n\
+                 /tmp/C.swift:1:1: warning: 'bar' is deprecated"
+                    .into(),
+                None,
+                Utc::now(),
+            )
+            .await
+            .expect("record target");
+
+        let response = diff_build_logs(
+            &store,
+            DiffBuildLogsRequest {
+                base_job_id: base.to_string(),
+                target_job_id: "latest".into(),
+                scheme: None,
+            },
+        )
+        .await
+        .expect("diff succeeds");
+
+        assert_eq!(response.target_job_id, target.to_string());
+        assert_eq!(response.resolved.len(), 1);
+        assert_eq!(response.resolved[0].message, "cannot find 'foo' in scope");
+        assert_eq!(response.introduced.len(), 1);
+        assert_eq!(response.introduced[0].message, "'bar' is deprecated");
+        assert_eq!(response.unchanged_count, 1);
+        assert_eq!(
+            response.target_counts,
+            DiagnosticCounts {
+                errors: 0,
+                warnings: 2
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn diff_rejects_invalid_job_id() {
+        let temp = tempdir().expect("tempdir");
+        let store = VisionOsArtifactStore::with_root(temp.path().to_path_buf(), 600, 60);
+        let err = diff_build_logs(
+            &store,
+            DiffBuildLogsRequest {
+                base_job_id: Uuid::new_v4().to_string(),
+                target_job_id: "not-a-uuid".into(),
+                scheme: None,
+            },
+        )
+        .await
+        .expect_err("invalid target job id");
+        assert_eq!(
+            err.data
+                .as_ref()
+                .and_then(|data| data.get("code"))
+                .and_then(Value::as_str),
+            Some("invalid_job_id")
+        );
+    }
+}
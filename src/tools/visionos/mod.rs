@@ -4,21 +4,48 @@
 
 pub mod artifacts;
 pub mod build;
+pub mod dependencies;
+pub mod devices;
 pub mod diagnostics;
 pub mod errors;
+pub mod ipa;
+pub mod log_diff;
+pub mod pipeline;
 pub mod registry;
+pub mod resources;
 pub mod sandbox;
+pub mod scaffold;
 pub mod schemes;
+pub mod simulator;
+pub mod testing;
 
 pub use artifacts::{
-    fetch_build_output, fetch_error_to_error_data, BuildFailureContext, FetchBuildOutputRequest,
-    FetchBuildOutputResponse, VisionOsArtifactStore,
+    export_artifact, export_job_trace, fetch_build_log, fetch_build_output,
+    fetch_error_to_error_data, list_build_jobs, purge_artifacts, save_job_trace, symbolicate_crash,
+    ArtifactFile, ArtifactKind, ArtifactRetention, BuildFailureContext, ExportArtifactRequest,
+    ExportArtifactResponse, ExportJobTraceRequest, ExportJobTraceResponse, ExportedFile,
+    FetchBuildLogRequest, FetchBuildLogResponse, FetchBuildOutputRequest, FetchBuildOutputResponse,
+    JobTrace, ListBuildJobsRequest, ListBuildJobsResponse, PurgeArtifactsRequest,
+    PurgeArtifactsResponse, SymbolicateCrashRequest, SymbolicateCrashResponse, TraceFormat,
+    VisionOsArtifactStore,
 };
 pub use build::{
-    run_build, runtime_error_to_error_data, validation_error_to_error_data,
-    BuildRequestValidationError, BuildVisionOsAppResponse, VisionOsBuildRequest, VisionOsJobQueue,
-    BUILD_TOOL_ID,
+    cancel_build_job, cancelled_error_to_error_data, describe_build_constraints,
+    extend_build_deadline, get_queue_status, plan_build, run_archive, run_build, run_clean,
+    runtime_error_to_error_data, shutting_down_error_to_error_data, validation_error_to_error_data,
+    BuildRequestValidationError, BuildVisionOsAppResponse, CancelBuildJobRequest,
+    CancelBuildJobResponse, CancelOutcome, CleanVisionOsProjectRequest,
+    CleanVisionOsProjectResponse, DescribeBuildConstraintsRequest,
+    DescribeBuildConstraintsResponse, ExtendBuildDeadlineRequest, ExtendBuildDeadlineResponse,
+    GetQueueStatusRequest, JobMonitor, JobPriority, JobRole, JobTicket, QueueStatusResponse,
+    TimeoutWarning, VisionOsBuildRequest, VisionOsJobQueue, ARCHIVE_TOOL_ID, BUILD_TOOL_ID,
+    CANCEL_TOOL_ID, CLEAN_TOOL_ID, EXTEND_DEADLINE_TOOL_ID, QUEUE_STATUS_TOOL_ID,
 };
+pub use dependencies::{
+    resolve_package_dependencies, ResolvePackageDependenciesRequest,
+    ResolvePackageDependenciesResponse, ResolvedPackage,
+};
+pub use devices::{list_devices, ListDevicesRequest, ListDevicesResponse, PhysicalDevice};
 pub use diagnostics::{
     inspect_build_diagnostics, BuildFailureSummary, FailureLocation,
     InspectBuildDiagnosticsRequest, InspectBuildDiagnosticsResponse,
@@ -29,9 +56,43 @@ pub use errors::{
     sandbox_error_to_error_data as visionos_sandbox_error,
     validation_error_to_error_data as visionos_validation_error,
 };
+pub use ipa::{
+    export_visionos_ipa, ExportMethod, ExportVisionOsIpaRequest, ExportVisionOsIpaResponse,
+};
+pub use log_diff::{
+    diff_build_logs, BuildLogDiagnostic, DiagnosticCounts, DiffBuildLogsRequest,
+    DiffBuildLogsResponse,
+};
+pub use pipeline::{
+    built_app, save_pipeline_record, validate_pipeline, PipelineStepKind, PipelineStepOutput,
+    PipelineStepResult, RunPipelineRequest, RunPipelineResponse,
+};
 pub use registry::VisionOsToolRouter;
+pub use resources::{PublishedResource, VisionOsResourceStore};
 pub use sandbox::{
-    inspect_xcode_sdks, sandbox_error_to_error_data, validate_sandbox_policy,
-    InspectXcodeSdksRequest, InspectXcodeSdksResponse, SandboxPolicyRequest, SandboxPolicyResponse,
+    free_disk_bytes, inspect_xcode_sdks, installed_sdks, installed_simulator_runtimes,
+    list_xcode_installations, sandbox_error_to_error_data, validate_sandbox_policy,
+    InspectXcodeSdksRequest, InspectXcodeSdksResponse, ListXcodeInstallationsRequest,
+    ListXcodeInstallationsResponse, PolicyRegistry, SandboxPolicyRequest, SandboxPolicyResponse,
+    SandboxProbeCache, XcodeInstallation,
+};
+pub use scaffold::{
+    create_visionos_project, CreateVisionOsProjectRequest, CreateVisionOsProjectResponse,
+    ProjectTemplate,
+};
+pub use schemes::{
+    inspect_xcode_schemes, list_allowed_projects, list_destinations, list_project_schemes,
+    show_build_settings, AllowedProject, InspectXcodeSchemesRequest, InspectXcodeSchemesResponse,
+    ListAllowedProjectsRequest, ListAllowedProjectsResponse, ListDestinationsRequest,
+    ListDestinationsResponse, ListProjectSchemesRequest, ListProjectSchemesResponse,
+    ShowBuildSettingsRequest, ShowBuildSettingsResponse,
+};
+pub use simulator::{
+    boot_simulator, capture_simulator_media, install_app, list_simulators, shutdown_simulator,
+    CaptureSimulatorMediaRequest, CaptureSimulatorMediaResponse, InstallAppResponse,
+    ListSimulatorsRequest, ListSimulatorsResponse, MediaKind, SimulatorDevice,
+    SimulatorDeviceRequest, SimulatorStateResponse,
+};
+pub use testing::{
+    run_tests, summary_resource_uri, TestRunSummary, TestVisionOsAppResponse, TEST_TOOL_ID,
 };
-pub use schemes::{inspect_xcode_schemes, InspectXcodeSchemesRequest, InspectXcodeSchemesResponse};
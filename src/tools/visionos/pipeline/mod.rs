@@ -0,0 +1,408 @@
+//! `run_pipeline`: sandbox check, build, test, install, and screenshot steps run in order under
+//! one pipeline ID, with later steps using the jobs of earlier ones.
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    lib::{
+        app_size::find_app_bundle,
+        errors::{ArtifactError, ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+        fs as artifact_fs,
+    },
+    tools::visionos::{
+        build::{VisionOsBuildRequest, BUILD_TOOL_ID},
+        sandbox::SandboxPolicyRequest,
+        testing::TEST_TOOL_ID,
+    },
+};
+
+/// Record of the steps, written to the pipeline's own directory in the artifact root.
+pub const PIPELINE_RECORD_FILE: &str = "pipeline.json";
+
+const INVALID_PIPELINE_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "invalid_pipeline",
+    "The pipeline steps are invalid",
+    "List each step once, put build before install, and pass simulator_udid for install and screenshot.",
+);
+const APP_BUNDLE_NOT_FOUND_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "app_bundle_not_found",
+    "The build step produced no .app bundle to install",
+    "Build for a simulator destination so the job's staging directory contains the app.",
+);
+const TOOL_DISABLED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "tool_disabled",
+    "The request needs a tool this server does not register",
+    "Drop the step or option, or register the tool through [tools] enabled / disabled in the config.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&INVALID_PIPELINE_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(
+        &APP_BUNDLE_NOT_FOUND_ERROR,
+        false,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(&TOOL_DISABLED_ERROR, false, SandboxState::Blocked),
+];
+
+/// Steps `run_pipeline` can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineStepKind {
+    /// `validate_sandbox_policy` for the project, platform, and signing identity of `build`.
+    SandboxCheck,
+    /// `build_visionos_app` with `build`.
+    Build,
+    /// `test_visionos_app` with `build`.
+    Test,
+    /// Install the app built by the `build` step on `simulator_udid`, booting it when needed.
+    Install,
+    /// `capture_simulator_media` screenshot of `simulator_udid`.
+    Screenshot,
+}
+
+impl PipelineStepKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SandboxCheck => "sandbox_check",
+            Self::Build => "build",
+            Self::Test => "test",
+            Self::Install => "install",
+            Self::Screenshot => "screenshot",
+        }
+    }
+
+    /// Tool whose work the step does; a server that does not register it refuses the step.
+    /// `install` has no tool of its own.
+    pub fn tool(self) -> Option<&'static str> {
+        match self {
+            Self::SandboxCheck => Some("validate_sandbox_policy"),
+            Self::Build => Some(BUILD_TOOL_ID),
+            Self::Test => Some(TEST_TOOL_ID),
+            Self::Install => None,
+            Self::Screenshot => Some("capture_simulator_media"),
+        }
+    }
+}
+
+/// Input for `run_pipeline`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RunPipelineRequest {
+    /// Steps in the order they run; each kind at most once.
+    pub steps: Vec<PipelineStepKind>,
+    /// Project, scheme, and settings shared by the sandbox, build, and test steps. `job_id` must
+    /// be unset: every step gets its own job.
+    pub build: VisionOsBuildRequest,
+    /// Simulator used by `install` and `screenshot`.
+    #[serde(default)]
+    pub simulator_udid: Option<String>,
+}
+
+/// Outcome of one pipeline step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PipelineStepStatus {
+    Succeeded,
+    Failed,
+    /// Not run because an earlier step failed.
+    Skipped,
+}
+
+/// What a step that ran returned.
+#[derive(Debug, Clone)]
+pub struct PipelineStepOutput {
+    pub job_id: Option<String>,
+    /// `false` when the step finished but reported a failure (failing tests, sandbox errors).
+    pub succeeded: bool,
+    pub output: Value,
+}
+
+impl PipelineStepOutput {
+    pub fn new(job_id: Option<String>, succeeded: bool, output: &impl Serialize) -> Self {
+        Self {
+            job_id,
+            succeeded,
+            output: serde_json::to_value(output).unwrap_or(Value::Null),
+        }
+    }
+}
+
+/// Status of one step in `run_pipeline`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PipelineStepResult {
+    pub step: PipelineStepKind,
+    pub status: PipelineStepStatus,
+    /// Job that ran the step, for `fetch_build_output` and `export_artifact`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    pub duration_ms: u128,
+    /// Response of the matching single-step tool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    /// Error data (`code`, `message`, `hint`, `details`) of a failed step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+}
+
+impl PipelineStepResult {
+    pub fn finished(
+        step: PipelineStepKind,
+        outcome: Result<PipelineStepOutput, ErrorData>,
+        elapsed: Duration,
+    ) -> Self {
+        match outcome {
+            Ok(output) => Self {
+                step,
+                status: if output.succeeded {
+                    PipelineStepStatus::Succeeded
+                } else {
+                    PipelineStepStatus::Failed
+                },
+                job_id: output.job_id,
+                duration_ms: elapsed.as_millis(),
+                output: Some(output.output),
+                error: None,
+            },
+            Err(err) => Self {
+                step,
+                status: PipelineStepStatus::Failed,
+                job_id: err
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.get("job_id"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                duration_ms: elapsed.as_millis(),
+                output: None,
+                error: Some(json!({
+                    "message": err.message,
+                    "data": err.data,
+                })),
+            },
+        }
+    }
+
+    pub fn skipped(step: PipelineStepKind) -> Self {
+        Self {
+            step,
+            status: PipelineStepStatus::Skipped,
+            job_id: None,
+            duration_ms: 0,
+            output: None,
+            error: None,
+        }
+    }
+}
+
+/// Response from `run_pipeline`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RunPipelineResponse {
+    /// Parent ID shared by the steps; names the directory holding `pipeline.json`.
+    pub pipeline_id: String,
+    /// `succeeded` when every step succeeded, `failed` otherwise.
+    pub status: &'static str,
+    pub steps: Vec<PipelineStepResult>,
+    /// Path of `pipeline.json`; `None` when it could not be written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record_path: Option<String>,
+    pub duration_ms: u128,
+}
+
+impl RunPipelineResponse {
+    pub fn new(pipeline_id: Uuid, steps: Vec<PipelineStepResult>, elapsed: Duration) -> Self {
+        let succeeded = steps
+            .iter()
+            .all(|step| step.status == PipelineStepStatus::Succeeded);
+        Self {
+            pipeline_id: pipeline_id.to_string(),
+            status: if succeeded { "succeeded" } else { "failed" },
+            steps,
+            record_path: None,
+            duration_ms: elapsed.as_millis(),
+        }
+    }
+}
+
+/// Reject pipelines whose steps cannot run as listed.
+pub fn validate_pipeline(request: &RunPipelineRequest) -> Result<(), ErrorData> {
+    if request.steps.is_empty() {
+        return Err(invalid_pipeline(
+            json!({ "details": "steps must not be empty" }),
+        ));
+    }
+    let mut seen = HashSet::new();
+    for step in &request.steps {
+        if !seen.insert(*step) {
+            return Err(invalid_pipeline(json!({
+                "step": step.as_str(),
+                "details": "each step may appear only once",
+            })));
+        }
+        match step {
+            PipelineStepKind::Install if !seen.contains(&PipelineStepKind::Build) => {
+                return Err(invalid_pipeline(json!({
+                    "step": step.as_str(),
+                    "details": "install needs an earlier build step",
+                })));
+            }
+            PipelineStepKind::Install | PipelineStepKind::Screenshot
+                if request.simulator_udid.is_none() =>
+            {
+                return Err(invalid_pipeline(json!({
+                    "step": step.as_str(),
+                    "details": "simulator_udid is required",
+                })));
+            }
+            _ => {}
+        }
+    }
+    if request.build.job_id.is_some() {
+        return Err(invalid_pipeline(json!({
+            "field": "build.job_id",
+            "details": "each step gets its own job ID",
+        })));
+    }
+    if request.build.dry_run {
+        return Err(invalid_pipeline(json!({
+            "field": "build.dry_run",
+            "details": "pipelines cannot be dry-run; call build_visionos_app with dry_run instead",
+        })));
+    }
+    Ok(())
+}
+
+/// Sandbox check matching the project, platform, and signing identity of `build`.
+pub fn sandbox_request(build: &VisionOsBuildRequest) -> SandboxPolicyRequest {
+    SandboxPolicyRequest {
+        project_path: build.project_path.clone(),
+        required_sdks: Vec::new(),
+        xcode_path: None,
+        platform: Some(build.platform),
+        code_sign_identity: build.code_sign_identity.clone(),
+        runtime_version: None,
+        force_refresh: false,
+        strict: false,
+        remediate: false,
+    }
+}
+
+/// `.app` bundle left in the staging directory of build job `job_id`.
+pub fn built_app(artifact_root: &Path, job_id: &str) -> Result<PathBuf, ErrorData> {
+    let staging = artifact_root.join(job_id).join("staging");
+    match find_app_bundle(&staging) {
+        Ok(Some(app)) => Ok(app),
+        Ok(None) => Err(app_bundle_not_found(json!({ "job_id": job_id }))),
+        Err(err) => Err(app_bundle_not_found(
+            json!({ "job_id": job_id, "details": err.to_string() }),
+        )),
+    }
+}
+
+/// Write `pipeline.json` for `response` under `<artifact_root>/<pipeline_id>/`.
+pub fn save_pipeline_record(
+    artifact_root: &Path,
+    pipeline_id: Uuid,
+    response: &RunPipelineResponse,
+) -> Result<PathBuf, ArtifactError> {
+    let dir = artifact_fs::ensure_job_dir(artifact_root, &pipeline_id)?;
+    let path = dir.join(PIPELINE_RECORD_FILE);
+    let encoded = serde_json::to_vec_pretty(response).map_err(|err| ArtifactError::Io {
+        path: path.clone(),
+        source: std::io::Error::other(err),
+    })?;
+    fs::write(&path, encoded).map_err(|source| ArtifactError::Io {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(path)
+}
+
+/// `tool_disabled` for work that would bypass `[tools]` by running `tool` on another tool's
+/// behalf, such as a pipeline step or the first build of a new project.
+pub fn tool_disabled_error(tool: &str, details: Value) -> ErrorData {
+    TOOL_DISABLED_ERROR
+        .builder()
+        .details(details)
+        .with_context_field("tool", json!(tool))
+        .sandbox_state(SandboxState::Blocked)
+        .retryable(false)
+        .build()
+        .expect("descriptor is valid")
+}
+
+fn invalid_pipeline(details: Value) -> ErrorData {
+    build_error_data(&INVALID_PIPELINE_ERROR, details, false)
+}
+
+fn app_bundle_not_found(details: Value) -> ErrorData {
+    build_error_data(&APP_BUNDLE_NOT_FOUND_ERROR, details, false)
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    retryable: bool,
+) -> ErrorData {
+    desc.builder()
+        .details(details)
+        .sandbox_state(SandboxState::NoViolation)
+        .retryable(retryable)
+        .build()
+        .expect("descriptor is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(steps: Vec<PipelineStepKind>, simulator_udid: Option<&str>) -> RunPipelineRequest {
+        RunPipelineRequest {
+            steps,
+            build: serde_json::from_value(json!({
+                "project_path": "/tmp/VisionApp",
+                "scheme": "VisionApp",
+            }))
+            .expect("build request parses"),
+            simulator_udid: simulator_udid.map(str::to_string),
+        }
+    }
+
+    fn error_code(err: &ErrorData) -> Option<&str> {
+        err.data
+            .as_ref()
+            .and_then(|data| data.get("code"))
+            .and_then(Value::as_str)
+    }
+
+    #[test]
+    fn pipeline_validation_checks_step_order_and_inputs() {
+        use PipelineStepKind::*;
+
+        let udid = Some("0E3C9A42-7A0B-4C55-9E1F-2B7D6C1A9F10");
+        assert!(validate_pipeline(&request(
+            vec![SandboxCheck, Build, Install, Screenshot],
+            udid
+        ))
+        .is_ok());
+
+        for invalid in [
+            request(Vec::new(), udid),
+            request(vec![Build, Build], udid),
+            request(vec![Install, Build], udid),
+            request(vec![Build, Screenshot], None),
+        ] {
+            let err = validate_pipeline(&invalid).expect_err("pipeline is invalid");
+            assert_eq!(error_code(&err), Some("invalid_pipeline"), "{invalid:?}");
+        }
+    }
+}
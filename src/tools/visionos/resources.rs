@@ -0,0 +1,102 @@
+//! In-memory MCP resources published by visionOS tools.
+use std::{collections::VecDeque, sync::Arc};
+
+use rmcp::model::{AnnotateAble, RawResource, Resource, ResourceContents};
+use tokio::sync::RwLock;
+
+/// Maximum number of resources retained before the oldest ones are dropped.
+const MAX_RESOURCES: usize = 64;
+
+/// Text resource published by a tool run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishedResource {
+    pub uri: String,
+    pub name: String,
+    pub description: String,
+    pub mime_type: String,
+    pub text: String,
+}
+
+/// Store of resources exposed through `resources/list` and `resources/read`.
+#[derive(Clone, Debug, Default)]
+pub struct VisionOsResourceStore {
+    resources: Arc<RwLock<VecDeque<PublishedResource>>>,
+}
+
+impl VisionOsResourceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish (or replace) a resource, evicting the oldest entries beyond the cap.
+    pub async fn publish(&self, resource: PublishedResource) {
+        let mut resources = self.resources.write().await;
+        resources.retain(|existing| existing.uri != resource.uri);
+        resources.push_back(resource);
+        while resources.len() > MAX_RESOURCES {
+            resources.pop_front();
+        }
+    }
+
+    /// List resources, newest first.
+    pub async fn list(&self) -> Vec<Resource> {
+        let resources = self.resources.read().await;
+        resources
+            .iter()
+            .rev()
+            .map(|resource| {
+                let mut raw = RawResource::new(&resource.uri, &resource.name);
+                raw.description = Some(resource.description.clone());
+                raw.mime_type = Some(resource.mime_type.clone());
+                raw.size = u32::try_from(resource.text.len()).ok();
+                raw.no_annotation()
+            })
+            .collect()
+    }
+
+    /// Read a resource by URI.
+    pub async fn read(&self, uri: &str) -> Option<ResourceContents> {
+        let resources = self.resources.read().await;
+        resources
+            .iter()
+            .find(|resource| resource.uri == uri)
+            .map(|resource| ResourceContents::TextResourceContents {
+                uri: resource.uri.clone(),
+                mime_type: Some(resource.mime_type.clone()),
+                text: resource.text.clone(),
+                meta: None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(uri: &str) -> PublishedResource {
+        PublishedResource {
+            uri: uri.into(),
+            name: "sample".into(),
+            description: "sample resource".into(),
+            mime_type: "text/markdown".into(),
+            text: "# sample".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn publish_replaces_existing_uri_and_caps_entries() {
+        let store = VisionOsResourceStore::new();
+        for index in 0..MAX_RESOURCES + 2 {
+            store
+                .publish(sample(&format!("seiro://test/{index}")))
+                .await;
+        }
+        store.publish(sample("seiro://test/10")).await;
+
+        let listed = store.list().await;
+        assert_eq!(listed.len(), MAX_RESOURCES);
+        assert_eq!(listed[0].uri, "seiro://test/10");
+        assert!(store.read("seiro://test/0").await.is_none());
+        assert!(store.read("seiro://test/10").await.is_some());
+    }
+}
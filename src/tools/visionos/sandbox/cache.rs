@@ -0,0 +1,67 @@
+//! Results of the slow sandbox probes (`xcodebuild -showsdks`, `DevToolsSecurity -status`, and the
+//! Xcode license check), reused by `validate_sandbox_policy` for `visionos.sandbox_probe_ttl_secs`.
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use super::probe::SdkInventory;
+
+/// Probes of one developer directory that all passed. DevToolsSecurity and the license are only
+/// cached as enabled/accepted, so a snapshot implies both.
+#[derive(Debug, Clone)]
+pub(crate) struct ProbeSnapshot {
+    pub sdks: SdkInventory,
+    pub probed_at: Instant,
+}
+
+/// Probe results shared by every `validate_sandbox_policy` call on the server, keyed by probe mode
+/// and developer directory.
+#[derive(Clone, Debug, Default)]
+pub struct SandboxProbeCache {
+    entries: Arc<Mutex<HashMap<(String, PathBuf), ProbeSnapshot>>>,
+}
+
+impl SandboxProbeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot of `developer_dir` taken less than `max_age` ago.
+    pub(crate) fn get(
+        &self,
+        probe_mode: &str,
+        developer_dir: &Path,
+        max_age: Duration,
+    ) -> Option<ProbeSnapshot> {
+        let entries = self.entries.lock().expect("sandbox probe cache lock");
+        entries
+            .get(&(probe_mode.to_string(), developer_dir.to_path_buf()))
+            .filter(|snapshot| snapshot.probed_at.elapsed() < max_age)
+            .cloned()
+    }
+
+    /// Record probes of `developer_dir` that just passed.
+    pub(crate) fn insert(&self, probe_mode: &str, developer_dir: &Path, sdks: SdkInventory) {
+        self.entries
+            .lock()
+            .expect("sandbox probe cache lock")
+            .insert(
+                (probe_mode.to_string(), developer_dir.to_path_buf()),
+                ProbeSnapshot {
+                    sdks,
+                    probed_at: Instant::now(),
+                },
+            );
+    }
+
+    /// Forget `developer_dir`, so a probe that fails now is not masked by an older pass.
+    pub(crate) fn invalidate(&self, probe_mode: &str, developer_dir: &Path) {
+        self.entries
+            .lock()
+            .expect("sandbox probe cache lock")
+            .remove(&(probe_mode.to_string(), developer_dir.to_path_buf()));
+    }
+}
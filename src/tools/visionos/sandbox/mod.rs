@@ -1,8 +1,11 @@
 //! Request and response definitions for the sandbox policy validation tool.
 //!
 //! Phase 4 implements the `validate_sandbox_policy` tool to validate allowed paths,
-//! required SDKs, DevToolsSecurity, and disk space.
+//! required SDKs, DevToolsSecurity, simulator runtimes, package hosts, and disk space.
+mod cache;
+mod policy;
 mod probe;
+mod xcodes;
 
 use std::{env, path::Path, path::PathBuf};
 
@@ -13,19 +16,25 @@ use serde_json::json;
 
 use crate::{
     lib::{
-        errors::{SandboxPolicyError, SandboxState, ToolErrorDescriptor},
-        visionos as visionos_helpers,
+        errors::{ErrorCatalogEntry, SandboxPolicyError, SandboxState, ToolErrorDescriptor},
+        platform::Platform,
     },
     server::config::VisionOsConfig,
 };
 
+pub use cache::SandboxProbeCache;
+pub use policy::{PolicyContext, PolicyRegistry, PolicyRule};
 use probe::SdkInventory;
 pub use probe::{EnvSandboxProbe, SandboxProbe, SystemSandboxProbe};
+pub use xcodes::{
+    list_xcode_installations, ListXcodeInstallationsRequest, ListXcodeInstallationsResponse,
+    XcodeInstallation, DEFAULT_XCODE_NAME,
+};
 
 const PATH_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "path_not_allowed",
     "project_path is outside the allowed paths",
-    "Update visionos.allowed_paths in seiro-mcp.toml and restart the MCP server.",
+    "Update visionos.allowed_paths in seiro-mcp.toml; the running server reloads it on save.",
 );
 const SDK_MISSING_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "sdk_missing",
@@ -42,18 +51,73 @@ const DEVTOOLS_DISABLED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "DevToolsSecurity is disabled",
     "Run `DevToolsSecurity -enable` to allow debugging from Xcode.",
 );
+const DEVELOPER_DIR_MISMATCH_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "developer_dir_mismatch",
+    "xcode-select points at a different developer directory than the configured one",
+    "Run `sudo xcode-select -s <visionos.xcode_path>`, or set visionos.xcode_path to the output of `xcode-select -p`.",
+);
+const COMMAND_LINE_TOOLS_MISSING_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "command_line_tools_missing",
+    "Xcode Command Line Tools are not installed",
+    "Run `xcode-select --install`.",
+);
+const RUNTIME_MISSING_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "runtime_missing",
+    "No matching visionOS simulator runtime is installed",
+    "Run `xcodebuild -downloadPlatform visionOS`, or install the runtime via Xcode > Settings > Platforms.",
+);
+const PACKAGE_HOST_UNREACHABLE_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "package_host_unreachable",
+    "Package dependency hosts cannot be reached",
+    "Check the network connection; behind a proxy, make sure HTTPS_PROXY is set for the server and Xcode, or remove the host from visionos.package_hosts.",
+);
 const DISK_INSUFFICIENT_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "disk_insufficient",
     "Insufficient free space for a visionOS build",
     "Remove unnecessary files where the project is stored and ensure enough free space.",
 );
+const SIGNING_IDENTITY_MISSING_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "signing_identity_missing",
+    "Code signing identity not found in the keychain",
+    "Import the certificate and its private key into the login keychain (check with `security find-identity -v -p codesigning`), or fix visionos.signing.identity.",
+);
+const REMEDIATION_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "remediation_not_allowed",
+    "Automatic remediation is disabled on this server",
+    "Set visionos.allow_remediation = true in seiro-mcp.toml, or fix the failing checks by hand.",
+);
 const SANDBOX_INTERNAL_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "sandbox_internal_error",
     "Internal error occurred during sandbox policy validation",
     "Check the logs and contact a developer if retrying does not resolve the issue.",
 );
 
-const MIN_DISK_BYTES: u64 = 20 * 1024 * 1024 * 1024; // 20GB
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&PATH_NOT_ALLOWED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&SDK_MISSING_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&XCODE_UNLICENSED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&DEVTOOLS_DISABLED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&DEVELOPER_DIR_MISMATCH_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(
+        &COMMAND_LINE_TOOLS_MISSING_ERROR,
+        false,
+        SandboxState::Blocked,
+    ),
+    ErrorCatalogEntry::new(&RUNTIME_MISSING_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(
+        &PACKAGE_HOST_UNREACHABLE_ERROR,
+        false,
+        SandboxState::Blocked,
+    ),
+    ErrorCatalogEntry::new(&DISK_INSUFFICIENT_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(
+        &SIGNING_IDENTITY_MISSING_ERROR,
+        false,
+        SandboxState::Blocked,
+    ),
+    ErrorCatalogEntry::new(&REMEDIATION_NOT_ALLOWED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&SANDBOX_INTERNAL_ERROR, false, SandboxState::Blocked),
+];
 
 /// Input for `validate_sandbox_policy`.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -63,6 +127,28 @@ pub struct SandboxPolicyRequest {
     pub required_sdks: Vec<String>,
     #[serde(default)]
     pub xcode_path: Option<PathBuf>,
+    /// Check this platform's SDKs from the config instead of `required_sdks`.
+    #[serde(default)]
+    pub platform: Option<Platform>,
+    /// Identity to look for in the keychain. Defaults to `visionos.signing.identity`; the
+    /// signing check is skipped when neither is set.
+    #[serde(default)]
+    pub code_sign_identity: Option<String>,
+    /// visionOS simulator runtime version that must be installed, e.g. `26.2`; `26` accepts any
+    /// 26.x runtime. Without it any visionOS runtime passes. Skipped for other platforms.
+    #[serde(default)]
+    pub runtime_version: Option<String>,
+    /// Run the SDK, DevToolsSecurity, and license probes even if a recent result is cached.
+    #[serde(default)]
+    pub force_refresh: bool,
+    /// Fail the call with the first failing check's error instead of returning `status: error`
+    /// with every check.
+    #[serde(default)]
+    pub strict: bool,
+    /// Try to fix failing `devtools_security` and `xcode_license` checks before reporting them.
+    /// Requires `visionos.allow_remediation`.
+    #[serde(default)]
+    pub remediate: bool,
 }
 
 fn default_required_sdks() -> Vec<String> {
@@ -91,6 +177,32 @@ pub struct SandboxCheck {
     pub name: String,
     pub result: SandboxCheckResult,
     pub details: String,
+    /// Whether the check reused a probe from an earlier call instead of running it.
+    #[serde(default)]
+    pub cached: bool,
+    /// Seconds since the probe behind the check ran; omitted for checks that are never cached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probe_age_secs: Option<u64>,
+    /// Error code of a failed check, as strict mode would return it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// How to fix a failed check.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+impl SandboxCheck {
+    fn pass(name: &str, details: String) -> Self {
+        Self {
+            name: name.into(),
+            result: SandboxCheckResult::Pass,
+            details,
+            cached: false,
+            probe_age_secs: None,
+            code: None,
+            remediation: None,
+        }
+    }
 }
 
 /// Response from `validate_sandbox_policy`.
@@ -105,6 +217,32 @@ pub struct SandboxPolicyResponse {
     pub checks: Vec<SandboxCheck>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub diagnostics: Option<SandboxDiagnostics>,
+    /// Fixes attempted for `remediate: true`; `checks` shows the state after them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remediations: Vec<SandboxRemediation>,
+}
+
+/// One fix attempted for a failing check.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct SandboxRemediation {
+    pub check: String,
+    /// Command that was run, e.g. `DevToolsSecurity -enable`.
+    pub command: String,
+    /// Whether the command succeeded. The check itself is probed again afterwards.
+    pub applied: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SandboxRemediation {
+    fn new(check: &str, command: &str, outcome: Result<(), SandboxPolicyError>) -> Self {
+        Self {
+            check: check.into(),
+            command: command.into(),
+            applied: outcome.is_ok(),
+            error: outcome.err().map(|error| error.to_string()),
+        }
+    }
 }
 
 /// Diagnostics data captured during sandbox validation.
@@ -128,6 +266,9 @@ pub struct InspectXcodeSdksRequest {
     pub required_sdks: Vec<String>,
     #[serde(default)]
     pub xcode_path: Option<PathBuf>,
+    /// Check this platform's SDKs from the config instead of `required_sdks`.
+    #[serde(default)]
+    pub platform: Option<Platform>,
 }
 
 /// Response from `inspect_xcode_sdks`.
@@ -153,23 +294,70 @@ pub struct SandboxValidationFailure {
     pub diagnostics: Option<SandboxDiagnostics>,
 }
 
-/// Execute sandbox policy validation.
+/// Execute sandbox policy validation, reusing probe results in `cache` that are younger than
+/// `visionos.sandbox_probe_ttl_secs`.
 pub async fn validate_sandbox_policy(
     request: SandboxPolicyRequest,
     config: &VisionOsConfig,
+    cache: &SandboxProbeCache,
 ) -> Result<SandboxPolicyResponse, SandboxValidationFailure> {
     match env::var("VISIONOS_SANDBOX_PROBE").ok().as_deref() {
         Some("env") | Some("mock") => {
             let probe = EnvSandboxProbe;
-            validate_sandbox_policy_with_probe_mode(request, config, &probe, "env").await
+            validate_sandbox_policy_with_probe_mode(
+                request,
+                config,
+                &probe,
+                "env",
+                cache,
+                &PolicyRegistry::builtin(),
+            )
+            .await
         }
         _ => {
             let probe = SystemSandboxProbe;
-            validate_sandbox_policy_with_probe_mode(request, config, &probe, "system").await
+            validate_sandbox_policy_with_probe_mode(
+                request,
+                config,
+                &probe,
+                "system",
+                cache,
+                &PolicyRegistry::builtin(),
+            )
+            .await
         }
     }
 }
 
+/// Free bytes on the volume holding `path`, from the same probe as sandbox validation.
+pub fn free_disk_bytes(path: &Path) -> Result<u64, SandboxPolicyError> {
+    match env::var("VISIONOS_SANDBOX_PROBE").ok().as_deref() {
+        Some("env") | Some("mock") => EnvSandboxProbe.disk_free_bytes(path),
+        _ => SystemSandboxProbe.disk_free_bytes(path),
+    }
+}
+
+/// SDKs `xcodebuild -showsdks` lists for `developer_dir`, from the same probe as sandbox
+/// validation.
+pub fn installed_sdks(developer_dir: &Path) -> Result<Vec<String>, SandboxPolicyError> {
+    let inventory = match env::var("VISIONOS_SANDBOX_PROBE").ok().as_deref() {
+        Some("env") | Some("mock") => EnvSandboxProbe.list_sdks(developer_dir),
+        _ => SystemSandboxProbe.list_sdks(developer_dir),
+    }?;
+    Ok(inventory.raw)
+}
+
+/// Available visionOS simulator runtime versions for `developer_dir`, from the same probe as
+/// sandbox validation.
+pub fn installed_simulator_runtimes(
+    developer_dir: &Path,
+) -> Result<Vec<String>, SandboxPolicyError> {
+    match env::var("VISIONOS_SANDBOX_PROBE").ok().as_deref() {
+        Some("env") | Some("mock") => EnvSandboxProbe.simulator_runtimes(developer_dir),
+        _ => SystemSandboxProbe.simulator_runtimes(developer_dir),
+    }
+}
+
 /// Inspect SDK detection context using the same probe path as sandbox validation.
 pub async fn inspect_xcode_sdks(
     request: InspectXcodeSdksRequest,
@@ -205,11 +393,7 @@ async fn inspect_xcode_sdks_with_probe_mode<P: SandboxProbe>(
             diagnostics: None,
         });
     }
-    let required_sdks = if request.required_sdks.is_empty() {
-        config.required_sdks.clone()
-    } else {
-        request.required_sdks.clone()
-    };
+    let required_sdks = effective_required_sdks(&request.required_sdks, request.platform, config);
     let sdk_inventory =
         probe
             .list_sdks(&developer_dir)
@@ -231,7 +415,11 @@ async fn inspect_xcode_sdks_with_probe_mode<P: SandboxProbe>(
         status,
         probe_mode: probe_mode.to_string(),
         developer_dir: developer_dir.display().to_string(),
-        xcode_select_path: None,
+        xcode_select_path: probe
+            .selected_developer_dir()
+            .ok()
+            .flatten()
+            .map(|path| path.display().to_string()),
         required_sdks,
         detected_sdks_raw: sdk_inventory.raw,
         detected_sdks_normalized: sdk_inventory.normalized,
@@ -246,7 +434,19 @@ pub async fn validate_sandbox_policy_with_probe<P: SandboxProbe>(
     config: &VisionOsConfig,
     probe: &P,
 ) -> Result<SandboxPolicyResponse, SandboxValidationFailure> {
-    validate_sandbox_policy_with_probe_mode(request, config, probe, "system").await
+    validate_sandbox_policy_with_rules(request, config, probe, &PolicyRegistry::builtin()).await
+}
+
+/// Version that runs the rules of `registry` instead of the built-in ones.
+pub async fn validate_sandbox_policy_with_rules<P: SandboxProbe>(
+    request: SandboxPolicyRequest,
+    config: &VisionOsConfig,
+    probe: &P,
+    registry: &PolicyRegistry,
+) -> Result<SandboxPolicyResponse, SandboxValidationFailure> {
+    let cache = SandboxProbeCache::new();
+    validate_sandbox_policy_with_probe_mode(request, config, probe, "system", &cache, registry)
+        .await
 }
 
 async fn validate_sandbox_policy_with_probe_mode<P: SandboxProbe>(
@@ -254,145 +454,93 @@ async fn validate_sandbox_policy_with_probe_mode<P: SandboxProbe>(
     config: &VisionOsConfig,
     probe: &P,
     probe_mode: &str,
+    cache: &SandboxProbeCache,
+    registry: &PolicyRegistry,
 ) -> Result<SandboxPolicyResponse, SandboxValidationFailure> {
-    let project_path = normalize_project_path(&request.project_path).map_err(|error| {
-        SandboxValidationFailure {
-            error,
-            diagnostics: None,
-        }
-    })?;
-    if !config.allowed_paths.is_empty()
-        && !visionos_helpers::is_allowed_path(&project_path, &config.allowed_paths)
-    {
+    if request.remediate && !config.allow_remediation {
         return Err(SandboxValidationFailure {
-            error: SandboxPolicyError::PathNotAllowed { path: project_path },
+            error: SandboxPolicyError::RemediationNotAllowed,
             diagnostics: None,
         });
     }
-
-    let mut checks = Vec::new();
-    checks.push(SandboxCheck {
-        name: "allowed_path".into(),
-        result: SandboxCheckResult::Pass,
-        details: if config.allowed_paths.is_empty() {
-            "allowlist check skipped (visionos.allowed_paths is empty)".into()
-        } else {
-            format!("{} is within the allowlist", project_path.display())
-        },
-    });
-
-    let developer_dir = request
-        .xcode_path
-        .clone()
-        .unwrap_or_else(|| config.xcode_path.clone());
-
-    if probe.requires_developer_dir() && !developer_dir.exists() {
-        return Err(SandboxValidationFailure {
-            error: SandboxPolicyError::XcodePathUnavailable {
-                path: developer_dir,
-            },
-            diagnostics: None,
-        });
+    let mut context = PolicyContext::new(&request, config, probe, probe_mode, cache);
+    let mut log = CheckLog::default();
+    for rule in registry.rules() {
+        if !config.policy.allows(rule.name()) || !rule.applies(&context) {
+            continue;
+        }
+        let outcome = rule.evaluate(&mut context);
+        let check = log.record(rule.name(), outcome);
+        if rule.probed() {
+            check.cached = context.cached();
+            check.probe_age_secs = Some(context.probe_age().as_secs());
+        }
     }
 
-    let sdk_inventory =
-        probe
-            .list_sdks(&developer_dir)
-            .map_err(|error| SandboxValidationFailure {
-                error,
-                diagnostics: None,
-            })?;
-    let required_sdks = if request.required_sdks.is_empty() {
-        config.required_sdks.clone()
-    } else {
-        request.required_sdks.clone()
-    };
-    let diagnostics = build_diagnostics(
-        probe_mode,
-        developer_dir.clone(),
-        required_sdks.clone(),
-        &sdk_inventory,
-    );
-    for sdk in &required_sdks {
-        if !sdk_is_present(&sdk_inventory.normalized, sdk) {
-            return Err(SandboxValidationFailure {
-                error: SandboxPolicyError::MissingSdk { name: sdk.clone() },
-                diagnostics: Some(diagnostics),
-            });
+    // Only a probe where every cached check ran and passed may stand in for them later.
+    if let Some(sdk_inventory) = context.sdk_inventory.take() {
+        if config.sandbox_probe_ttl_secs > 0
+            && log.passed("devtools_security")
+            && log.passed("xcode_license")
+        {
+            cache.insert(probe_mode, &context.developer_dir, sdk_inventory);
         }
     }
-    checks.push(SandboxCheck {
-        name: "sdk".into(),
-        result: SandboxCheckResult::Pass,
-        details: format!("SDK: {}", sdk_inventory.normalized.join(", ")),
-    });
 
-    if !probe
-        .devtools_security_enabled()
-        .map_err(|error| SandboxValidationFailure {
-            error,
-            diagnostics: Some(diagnostics.clone()),
-        })?
-    {
-        return Err(SandboxValidationFailure {
-            error: SandboxPolicyError::DevToolsSecurityDisabled,
-            diagnostics: Some(diagnostics),
-        });
+    let diagnostics = context.diagnostics;
+    match log.first_failure {
+        Some(error) if request.strict => Err(SandboxValidationFailure { error, diagnostics }),
+        first_failure => Ok(SandboxPolicyResponse {
+            status: if first_failure.is_some() {
+                SandboxStatus::Error
+            } else {
+                SandboxStatus::Ok
+            },
+            checks: log.checks,
+            diagnostics,
+            remediations: context.remediations,
+        }),
     }
-    checks.push(SandboxCheck {
-        name: "devtools_security".into(),
-        result: SandboxCheckResult::Pass,
-        details: "DevToolsSecurity is enabled".into(),
-    });
+}
 
-    if !probe
-        .xcode_license_accepted()
-        .map_err(|error| SandboxValidationFailure {
-            error,
-            diagnostics: Some(diagnostics.clone()),
-        })?
-    {
-        return Err(SandboxValidationFailure {
-            error: SandboxPolicyError::LicenseNotAccepted,
-            diagnostics: Some(diagnostics),
-        });
-    }
-    checks.push(SandboxCheck {
-        name: "xcode_license".into(),
-        result: SandboxCheckResult::Pass,
-        details: "Xcode license accepted".into(),
-    });
-
-    let disk_root = project_path
-        .parent()
-        .map(Path::to_path_buf)
-        .unwrap_or_else(|| project_path.clone());
-    let free_bytes =
-        probe
-            .disk_free_bytes(&disk_root)
-            .map_err(|error| SandboxValidationFailure {
-                error,
-                diagnostics: Some(diagnostics.clone()),
-            })?;
-    if free_bytes < MIN_DISK_BYTES {
-        return Err(SandboxValidationFailure {
-            error: SandboxPolicyError::DiskInsufficient {
-                available_bytes: free_bytes,
-            },
-            diagnostics: Some(diagnostics),
-        });
+/// Checks run so far, and the first failure that strict mode returns as the error.
+#[derive(Default)]
+struct CheckLog {
+    checks: Vec<SandboxCheck>,
+    first_failure: Option<SandboxPolicyError>,
+}
+
+impl CheckLog {
+    /// Add check `name` with its pass details or failure.
+    fn record(
+        &mut self,
+        name: &str,
+        outcome: Result<String, SandboxPolicyError>,
+    ) -> &mut SandboxCheck {
+        let check = match outcome {
+            Ok(details) => SandboxCheck::pass(name, details),
+            Err(error) => {
+                let descriptor = sandbox_error_descriptor(&error);
+                let check = SandboxCheck {
+                    result: SandboxCheckResult::Fail,
+                    code: Some(descriptor.code.into()),
+                    remediation: Some(descriptor.remediation.into()),
+                    ..SandboxCheck::pass(name, error.to_string())
+                };
+                self.first_failure.get_or_insert(error);
+                check
+            }
+        };
+        self.checks.push(check);
+        self.checks.last_mut().expect("check was just added")
     }
-    checks.push(SandboxCheck {
-        name: "disk_space".into(),
-        result: SandboxCheckResult::Pass,
-        details: format!("{} bytes free", free_bytes),
-    });
 
-    Ok(SandboxPolicyResponse {
-        status: SandboxStatus::Ok,
-        checks,
-        diagnostics: Some(diagnostics),
-    })
+    /// Whether check `name` ran and passed.
+    fn passed(&self, name: &str) -> bool {
+        self.checks
+            .iter()
+            .any(|check| check.name == name && check.result == SandboxCheckResult::Pass)
+    }
 }
 
 /// Map check results to error codes.
@@ -403,7 +551,13 @@ pub fn sandbox_error_descriptor(error: &SandboxPolicyError) -> &'static ToolErro
         SandboxPolicyError::XcodePathUnavailable { .. } => &XCODE_UNLICENSED_ERROR,
         SandboxPolicyError::LicenseNotAccepted => &XCODE_UNLICENSED_ERROR,
         SandboxPolicyError::DevToolsSecurityDisabled => &DEVTOOLS_DISABLED_ERROR,
+        SandboxPolicyError::DeveloperDirMismatch { .. } => &DEVELOPER_DIR_MISMATCH_ERROR,
+        SandboxPolicyError::CommandLineToolsMissing => &COMMAND_LINE_TOOLS_MISSING_ERROR,
+        SandboxPolicyError::RuntimeMissing { .. } => &RUNTIME_MISSING_ERROR,
+        SandboxPolicyError::PackageHostsUnreachable { .. } => &PACKAGE_HOST_UNREACHABLE_ERROR,
         SandboxPolicyError::DiskInsufficient { .. } => &DISK_INSUFFICIENT_ERROR,
+        SandboxPolicyError::SigningIdentityMissing { .. } => &SIGNING_IDENTITY_MISSING_ERROR,
+        SandboxPolicyError::RemediationNotAllowed => &REMEDIATION_NOT_ALLOWED_ERROR,
         SandboxPolicyError::Internal { .. } => &SANDBOX_INTERNAL_ERROR,
     }
 }
@@ -425,15 +579,6 @@ pub fn sandbox_error_to_error_data(failure: SandboxValidationFailure) -> ErrorDa
         .expect("descriptor is valid")
 }
 
-fn normalize_project_path(path: &Path) -> Result<PathBuf, SandboxPolicyError> {
-    if !crate::lib::paths::is_nonempty_absolute(path) {
-        return Err(SandboxPolicyError::PathNotAllowed {
-            path: path.to_path_buf(),
-        });
-    }
-    Ok(path.to_path_buf())
-}
-
 fn build_diagnostics(
     probe_mode: &str,
     developer_dir: PathBuf,
@@ -451,6 +596,20 @@ fn build_diagnostics(
     }
 }
 
+/// SDKs to check: the platform's configured list when `platform` is set, else the request's
+/// list, falling back to `visionos.required_sdks` when that is empty.
+fn effective_required_sdks(
+    requested: &[String],
+    platform: Option<Platform>,
+    config: &VisionOsConfig,
+) -> Vec<String> {
+    match platform {
+        Some(platform) => config.platform_defaults(platform).required_sdks,
+        None if requested.is_empty() => config.required_sdks.clone(),
+        None => requested.to_vec(),
+    }
+}
+
 fn sdk_is_present(sdks: &[String], required: &str) -> bool {
     let required = required.trim();
     if required.is_empty() {
@@ -483,21 +642,36 @@ fn sdk_identifier_prefix_matches(detected: &str, required: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use std::{fs, path::PathBuf};
+    use std::{collections::BTreeMap, fs, path::PathBuf};
 
     use rmcp::model::ErrorData;
     use serde_json::{Map, Value};
     use tempfile::tempdir;
 
-    use crate::server::config::VisionOsConfig;
+    use crate::{
+        lib::signing::SigningIdentity,
+        server::config::{
+            PackageHost, PolicyConfig, SchemePattern, SigningConfig, SymlinkPolicy, VisionOsConfig,
+            ZipCompression,
+        },
+    };
 
     use super::*;
 
+    const MIN_DISK_BYTES: u64 = 20 * 1024 * 1024 * 1024; // 20GB
+
+    #[derive(Clone)]
     struct FakeProbe {
         sdks: Vec<String>,
         devtools_enabled: bool,
         license_ok: bool,
         disk_bytes: u64,
+        identities: Vec<&'static str>,
+        /// Set by the remediation methods; turns the DevToolsSecurity and license checks on.
+        remediated: std::cell::Cell<bool>,
+        xcode_select: Option<PathBuf>,
+        runtimes: Vec<&'static str>,
+        unreachable_hosts: Vec<&'static str>,
     }
 
     impl SandboxProbe for FakeProbe {
@@ -516,11 +690,47 @@ mod tests {
         fn devtools_security_enabled(
             &self,
         ) -> Result<bool, crate::lib::errors::SandboxPolicyError> {
-            Ok(self.devtools_enabled)
+            Ok(self.devtools_enabled || self.remediated.get())
         }
 
         fn xcode_license_accepted(&self) -> Result<bool, crate::lib::errors::SandboxPolicyError> {
-            Ok(self.license_ok)
+            Ok(self.license_ok || self.remediated.get())
+        }
+
+        fn selected_developer_dir(&self) -> Result<Option<PathBuf>, SandboxPolicyError> {
+            Ok(self.xcode_select.clone())
+        }
+
+        fn command_line_tools_installed(&self) -> Result<bool, SandboxPolicyError> {
+            Ok(true)
+        }
+
+        fn simulator_runtimes(
+            &self,
+            _developer_dir: &std::path::Path,
+        ) -> Result<Vec<String>, SandboxPolicyError> {
+            Ok(self
+                .runtimes
+                .iter()
+                .map(|version| version.to_string())
+                .collect())
+        }
+
+        fn host_reachable(&self, host: &str, _port: u16) -> Result<bool, SandboxPolicyError> {
+            Ok(!self.unreachable_hosts.contains(&host))
+        }
+
+        fn enable_devtools_security(&self) -> Result<(), SandboxPolicyError> {
+            self.remediated.set(true);
+            Ok(())
+        }
+
+        fn run_first_launch(
+            &self,
+            _developer_dir: &std::path::Path,
+        ) -> Result<(), SandboxPolicyError> {
+            self.remediated.set(true);
+            Ok(())
         }
 
         fn disk_free_bytes(
@@ -529,20 +739,64 @@ mod tests {
         ) -> Result<u64, crate::lib::errors::SandboxPolicyError> {
             Ok(self.disk_bytes)
         }
+
+        fn signing_identities(
+            &self,
+        ) -> Result<Vec<SigningIdentity>, crate::lib::errors::SandboxPolicyError> {
+            Ok(self
+                .identities
+                .iter()
+                .map(|name| SigningIdentity {
+                    hash: "0123456789ABCDEF0123456789ABCDEF01234567".into(),
+                    name: name.to_string(),
+                })
+                .collect())
+        }
     }
 
     fn sample_config() -> VisionOsConfig {
         VisionOsConfig {
             allowed_paths: vec![allowed_project_path().parent().unwrap().to_path_buf()],
-            allowed_schemes: vec!["VisionApp".into()],
+            allowed_roots: Vec::new(),
+            allowed_schemes: vec![SchemePattern::new("VisionApp").expect("valid scheme")],
             default_project_path: None,
             default_destination: "platform=visionOS Simulator,name=Apple Vision Pro".into(),
             required_sdks: vec!["visionOS".into(), "visionOS Simulator".into()],
             xcode_path: PathBuf::from("/Applications/Xcode.app/Contents/Developer"),
             xcodebuild_path: PathBuf::from("/usr/bin/xcodebuild"),
+            xcrun_path: PathBuf::from("/usr/bin/xcrun"),
             max_build_minutes: 20,
+            max_timeout_minutes: 20,
+            timeout_warning_percent: 80,
+            heartbeat_interval_secs: 15,
+            max_extended_build_minutes: 0,
+            max_concurrent_builds: 1,
             artifact_ttl_secs: 600,
             cleanup_schedule_secs: 60,
+            artifact_max_jobs: 0,
+            artifact_max_total_bytes: 0,
+            min_free_disk_bytes: MIN_DISK_BYTES,
+            sandbox_probe_ttl_secs: 0,
+            allow_remediation: false,
+            package_hosts: Vec::new(),
+            policy: Default::default(),
+            export_paths: vec![],
+            size_budgets: BTreeMap::new(),
+            artifact_compression: ZipCompression::default(),
+            artifact_symlinks: SymlinkPolicy::default(),
+            deterministic_artifacts: false,
+            derived_data_root: None,
+            build_cache: false,
+            project_config: false,
+            compilation_cache: None,
+            platforms: BTreeMap::new(),
+            locale: "en_US.UTF-8".into(),
+            signing: Default::default(),
+            allowed_device_udids: Vec::new(),
+            artifact_persistence: Default::default(),
+            presets: Default::default(),
+            xcodes: Default::default(),
+            retry: Default::default(),
         }
     }
 
@@ -558,12 +812,23 @@ mod tests {
             project_path: allowed_project_path(),
             required_sdks: vec!["visionOS".into()],
             xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: true,
+            remediate: false,
         };
         let probe = FakeProbe {
             sdks: vec![],
             devtools_enabled: true,
             license_ok: true,
             disk_bytes: 500 * 1024 * 1024,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
         };
 
         let failure = validate_sandbox_policy_with_probe(request, &sample_config(), &probe)
@@ -584,6 +849,550 @@ mod tests {
         assert!(diagnostics.detected_sdks_raw.is_empty());
     }
 
+    #[tokio::test]
+    async fn sandbox_policy_checks_the_requested_platform_sdks() {
+        let temp = tempdir().expect("can create temp directory");
+        let request = SandboxPolicyRequest {
+            project_path: allowed_project_path(),
+            required_sdks: default_required_sdks(),
+            xcode_path: Some(temp.path().to_path_buf()),
+            platform: Some(Platform::Ios),
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: false,
+            remediate: false,
+        };
+        let probe = FakeProbe {
+            sdks: vec!["iphoneos18.0".into(), "iphonesimulator18.0".into()],
+            devtools_enabled: true,
+            license_ok: true,
+            disk_bytes: MIN_DISK_BYTES + 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
+        };
+
+        let response = validate_sandbox_policy_with_probe(request, &sample_config(), &probe)
+            .await
+            .expect("iOS SDKs should satisfy an iOS check");
+
+        let diagnostics = response
+            .diagnostics
+            .expect("successful response should include diagnostics");
+        assert_eq!(
+            diagnostics.effective_required_sdks,
+            vec!["iphoneos", "iphonesimulator"]
+        );
+    }
+
+    #[tokio::test]
+    async fn sandbox_policy_reuses_cached_probes_until_refreshed() {
+        let temp = tempdir().expect("can create temp directory");
+        let mut config = sample_config();
+        config.sandbox_probe_ttl_secs = 300;
+        let request = SandboxPolicyRequest {
+            project_path: allowed_project_path(),
+            required_sdks: default_required_sdks(),
+            xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: false,
+            remediate: false,
+        };
+        let probe = FakeProbe {
+            sdks: default_required_sdks(),
+            devtools_enabled: true,
+            license_ok: true,
+            disk_bytes: MIN_DISK_BYTES + 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
+        };
+        let cache = SandboxProbeCache::new();
+        let validate = |request: SandboxPolicyRequest, probe: FakeProbe| {
+            let (config, cache) = (config.clone(), cache.clone());
+            async move {
+                validate_sandbox_policy_with_probe_mode(
+                    request,
+                    &config,
+                    &probe,
+                    "system",
+                    &cache,
+                    &PolicyRegistry::builtin(),
+                )
+                .await
+            }
+        };
+        let check = |response: &SandboxPolicyResponse, name: &str| {
+            response
+                .checks
+                .iter()
+                .find(|check| check.name == name)
+                .cloned()
+                .expect("check is reported")
+        };
+
+        let fresh = validate(request.clone(), probe.clone())
+            .await
+            .expect("first validation probes and passes");
+        assert!(!check(&fresh, "sdk").cached);
+        assert_eq!(check(&fresh, "sdk").probe_age_secs, Some(0));
+
+        let disabled = FakeProbe {
+            devtools_enabled: false,
+            ..probe
+        };
+        let cached = validate(request.clone(), disabled.clone())
+            .await
+            .expect("cached probes are reused");
+        assert!(check(&cached, "devtools_security").cached);
+        assert!(!check(&cached, "disk_space").cached);
+        assert_eq!(check(&cached, "disk_space").probe_age_secs, None);
+
+        let refreshed = validate(
+            SandboxPolicyRequest {
+                force_refresh: true,
+                ..request
+            },
+            disabled,
+        )
+        .await
+        .expect("non-strict validation reports failures");
+        let devtools = check(&refreshed, "devtools_security");
+        assert_eq!(refreshed.status, SandboxStatus::Error);
+        assert_eq!(devtools.result, SandboxCheckResult::Fail, "probed again");
+        assert!(!devtools.cached);
+    }
+
+    #[tokio::test]
+    async fn sandbox_policy_reports_every_failing_check() {
+        let temp = tempdir().expect("can create temp directory");
+        let request = SandboxPolicyRequest {
+            project_path: allowed_project_path(),
+            required_sdks: default_required_sdks(),
+            xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: false,
+            remediate: false,
+        };
+        let probe = FakeProbe {
+            sdks: vec!["visionOS".into()],
+            devtools_enabled: false,
+            license_ok: true,
+            disk_bytes: 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
+        };
+
+        let response =
+            validate_sandbox_policy_with_probe(request.clone(), &sample_config(), &probe)
+                .await
+                .expect("non-strict validation returns a report");
+
+        assert_eq!(response.status, SandboxStatus::Error);
+        let failed: Vec<(&str, Option<&str>)> = response
+            .checks
+            .iter()
+            .filter(|check| check.result == SandboxCheckResult::Fail)
+            .map(|check| (check.name.as_str(), check.code.as_deref()))
+            .collect();
+        assert_eq!(
+            failed,
+            vec![
+                ("sdk", Some("sdk_missing")),
+                ("devtools_security", Some("devtools_security_disabled")),
+                ("disk_space", Some("disk_insufficient")),
+            ]
+        );
+        assert_eq!(response.checks.len(), 10, "every check runs");
+
+        let failure = validate_sandbox_policy_with_probe(
+            SandboxPolicyRequest {
+                strict: true,
+                remediate: false,
+                ..request
+            },
+            &sample_config(),
+            &probe,
+        )
+        .await
+        .expect_err("strict validation fails");
+        assert!(matches!(
+            failure.error,
+            SandboxPolicyError::MissingSdk { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn sandbox_policy_flags_a_different_xcode_select_directory() {
+        let temp = tempdir().expect("can create temp directory");
+        let xcode = temp.path().join("Xcode.app");
+        let beta = temp.path().join("Xcode-beta.app");
+        fs::create_dir_all(xcode.join("Contents/Developer")).expect("can create Xcode");
+        fs::create_dir_all(&beta).expect("can create Xcode beta");
+        let request = SandboxPolicyRequest {
+            project_path: allowed_project_path(),
+            required_sdks: default_required_sdks(),
+            xcode_path: Some(xcode.join("Contents/Developer")),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: false,
+            remediate: false,
+        };
+        let probe = |selected: &Path| FakeProbe {
+            sdks: default_required_sdks(),
+            devtools_enabled: true,
+            license_ok: true,
+            disk_bytes: MIN_DISK_BYTES + 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: Some(selected.to_path_buf()),
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
+        };
+        let developer_dir_check = |response: SandboxPolicyResponse| {
+            response
+                .checks
+                .into_iter()
+                .find(|check| check.name == "developer_dir")
+                .expect("developer_dir check is reported")
+        };
+
+        let same =
+            validate_sandbox_policy_with_probe(request.clone(), &sample_config(), &probe(&xcode))
+                .await
+                .expect("validation returns a report");
+        assert_eq!(developer_dir_check(same).result, SandboxCheckResult::Pass);
+
+        let other = validate_sandbox_policy_with_probe(request, &sample_config(), &probe(&beta))
+            .await
+            .expect("validation returns a report");
+        assert_eq!(other.status, SandboxStatus::Error);
+        let check = developer_dir_check(other);
+        assert_eq!(check.result, SandboxCheckResult::Fail);
+        assert_eq!(check.code.as_deref(), Some("developer_dir_mismatch"));
+    }
+
+    #[tokio::test]
+    async fn sandbox_policy_requires_a_matching_simulator_runtime() {
+        let temp = tempdir().expect("can create temp directory");
+        let request = |runtime_version: Option<&str>, platform| SandboxPolicyRequest {
+            project_path: allowed_project_path(),
+            required_sdks: default_required_sdks(),
+            xcode_path: Some(temp.path().to_path_buf()),
+            platform,
+            code_sign_identity: None,
+            runtime_version: runtime_version.map(str::to_string),
+            force_refresh: false,
+            strict: false,
+            remediate: false,
+        };
+        let probe = FakeProbe {
+            sdks: default_required_sdks(),
+            devtools_enabled: true,
+            license_ok: true,
+            disk_bytes: MIN_DISK_BYTES + 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["2.5", "26.2"],
+            unreachable_hosts: Vec::new(),
+        };
+        let runtime_check = |response: SandboxPolicyResponse| {
+            response
+                .checks
+                .into_iter()
+                .find(|check| check.name == "simulator_runtime")
+        };
+
+        for version in [None, Some("26"), Some("26.2")] {
+            let response = validate_sandbox_policy_with_probe(
+                request(version, None),
+                &sample_config(),
+                &probe,
+            )
+            .await
+            .expect("validation returns a report");
+            let check = runtime_check(response).expect("simulator_runtime check is reported");
+            assert_eq!(check.result, SandboxCheckResult::Pass, "{version:?}");
+        }
+
+        let response = validate_sandbox_policy_with_probe(
+            request(Some("26.4"), None),
+            &sample_config(),
+            &probe,
+        )
+        .await
+        .expect("validation returns a report");
+        assert_eq!(response.status, SandboxStatus::Error);
+        let check = runtime_check(response).expect("simulator_runtime check is reported");
+        assert_eq!(check.result, SandboxCheckResult::Fail);
+        assert_eq!(check.code.as_deref(), Some("runtime_missing"));
+        assert!(check
+            .remediation
+            .as_deref()
+            .is_some_and(|remediation| remediation.contains("-downloadPlatform visionOS")));
+
+        let response = validate_sandbox_policy_with_probe(
+            request(Some("26.4"), Some(Platform::Ios)),
+            &sample_config(),
+            &probe,
+        )
+        .await
+        .expect("validation returns a report");
+        assert!(runtime_check(response).is_none());
+    }
+
+    #[tokio::test]
+    async fn sandbox_policy_reports_unreachable_package_hosts() {
+        let temp = tempdir().expect("can create temp directory");
+        let request = SandboxPolicyRequest {
+            project_path: allowed_project_path(),
+            required_sdks: default_required_sdks(),
+            xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: false,
+            remediate: false,
+        };
+        let probe = FakeProbe {
+            sdks: default_required_sdks(),
+            devtools_enabled: true,
+            license_ok: true,
+            disk_bytes: MIN_DISK_BYTES + 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: vec!["registry.example.com"],
+        };
+        let mut config = sample_config();
+        config.package_hosts = vec![
+            PackageHost {
+                host: "github.com".into(),
+                port: 443,
+            },
+            PackageHost {
+                host: "registry.example.com".into(),
+                port: 8443,
+            },
+        ];
+
+        let response = validate_sandbox_policy_with_probe(request, &config, &probe)
+            .await
+            .expect("validation returns a report");
+
+        assert_eq!(response.status, SandboxStatus::Error);
+        let check = response
+            .checks
+            .iter()
+            .find(|check| check.name == "package_hosts")
+            .expect("package_hosts check is reported");
+        assert_eq!(check.result, SandboxCheckResult::Fail);
+        assert_eq!(check.code.as_deref(), Some("package_host_unreachable"));
+        assert!(
+            check.details.contains("registry.example.com:8443"),
+            "{}",
+            check.details
+        );
+        assert!(!check.details.contains("github.com"), "{}", check.details);
+    }
+
+    #[tokio::test]
+    async fn sandbox_policy_follows_the_policy_table() {
+        struct ProjectNameRule;
+
+        impl PolicyRule for ProjectNameRule {
+            fn name(&self) -> &'static str {
+                "project_name"
+            }
+
+            fn evaluate(
+                &self,
+                context: &mut PolicyContext<'_>,
+            ) -> Result<String, SandboxPolicyError> {
+                Ok(format!("{}", context.request.project_path.display()))
+            }
+        }
+
+        let temp = tempdir().expect("can create temp directory");
+        let request = SandboxPolicyRequest {
+            project_path: allowed_project_path(),
+            required_sdks: default_required_sdks(),
+            xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: false,
+            remediate: false,
+        };
+        let probe = FakeProbe {
+            sdks: default_required_sdks(),
+            devtools_enabled: false,
+            license_ok: true,
+            disk_bytes: 10 * 1024 * 1024 * 1024,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
+        };
+        let mut config = sample_config();
+        config.policy = PolicyConfig {
+            enabled: None,
+            disabled: vec!["devtools_security".into(), "package_hosts".into()],
+            min_free_disk_bytes: Some(1024 * 1024 * 1024),
+        };
+        let registry = PolicyRegistry::builtin().register(ProjectNameRule);
+
+        let response = validate_sandbox_policy_with_rules(request, &config, &probe, &registry)
+            .await
+            .expect("validation returns a report");
+
+        assert_eq!(response.status, SandboxStatus::Ok, "{:?}", response.checks);
+        let names: Vec<&str> = response
+            .checks
+            .iter()
+            .map(|check| check.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "allowed_path",
+                "developer_dir",
+                "command_line_tools",
+                "sdk",
+                "xcode_license",
+                "simulator_runtime",
+                "signing",
+                "disk_space",
+                "project_name",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn sandbox_policy_remediates_only_when_allowed() {
+        let temp = tempdir().expect("can create temp directory");
+        let request = SandboxPolicyRequest {
+            project_path: allowed_project_path(),
+            required_sdks: default_required_sdks(),
+            xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: false,
+            remediate: true,
+        };
+        let probe = FakeProbe {
+            sdks: default_required_sdks(),
+            devtools_enabled: false,
+            license_ok: true,
+            disk_bytes: MIN_DISK_BYTES + 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
+        };
+
+        let failure = validate_sandbox_policy_with_probe(request.clone(), &sample_config(), &probe)
+            .await
+            .expect_err("remediation is off by default");
+        assert!(matches!(
+            failure.error,
+            SandboxPolicyError::RemediationNotAllowed
+        ));
+        assert!(!probe.remediated.get(), "nothing ran");
+
+        let mut config = sample_config();
+        config.allow_remediation = true;
+        let response = validate_sandbox_policy_with_probe(request, &config, &probe)
+            .await
+            .expect("remediated validation returns a report");
+        assert_eq!(response.status, SandboxStatus::Ok);
+        assert_eq!(
+            response.remediations,
+            vec![SandboxRemediation {
+                check: "devtools_security".into(),
+                command: "DevToolsSecurity -enable".into(),
+                applied: true,
+                error: None,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn sandbox_policy_validates_the_configured_signing_identity() {
+        let temp = tempdir().expect("can create temp directory");
+        let mut config = sample_config();
+        config.signing = SigningConfig {
+            identity: Some("Apple Development".into()),
+            ..SigningConfig::default()
+        };
+        let request = SandboxPolicyRequest {
+            project_path: allowed_project_path(),
+            required_sdks: default_required_sdks(),
+            xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: true,
+            remediate: false,
+        };
+        let mut probe = FakeProbe {
+            sdks: default_required_sdks(),
+            devtools_enabled: true,
+            license_ok: true,
+            disk_bytes: MIN_DISK_BYTES + 1,
+            identities: vec!["Apple Development: Jane Doe (Q1W2E3R4T5)"],
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
+        };
+
+        let response = validate_sandbox_policy_with_probe(request.clone(), &config, &probe)
+            .await
+            .expect("a matching keychain identity should pass");
+        let signing = response
+            .checks
+            .iter()
+            .find(|check| check.name == "signing")
+            .expect("signing check should be reported");
+        assert!(signing.details.contains("Jane Doe"), "{}", signing.details);
+
+        probe.identities = vec!["Apple Distribution: Example Inc. (A1B2C3D4E5)"];
+        let failure = validate_sandbox_policy_with_probe(request, &config, &probe)
+            .await
+            .expect_err("a missing identity should fail");
+        assert!(matches!(
+            failure.error,
+            crate::lib::errors::SandboxPolicyError::SigningIdentityMissing { ref identity }
+                if identity == "Apple Development"
+        ));
+    }
+
     #[tokio::test]
     async fn sandbox_policy_accepts_prefix_sdk_match() {
         let temp = tempdir().expect("can create temp directory");
@@ -591,12 +1400,23 @@ mod tests {
             project_path: allowed_project_path(),
             required_sdks: vec!["xros".into(), "macosx".into()],
             xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: false,
+            remediate: false,
         };
         let probe = FakeProbe {
             sdks: vec!["xros26.0".into(), "macosx15.0".into()],
             devtools_enabled: true,
             license_ok: true,
             disk_bytes: MIN_DISK_BYTES + 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
         };
 
         let response = validate_sandbox_policy_with_probe(request, &sample_config(), &probe)
@@ -622,12 +1442,23 @@ mod tests {
             project_path: allowed_project_path(),
             required_sdks: vec!["visionOS".into()],
             xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: true,
+            remediate: false,
         };
         let probe = FakeProbe {
             sdks: vec!["visionOS Simulator".into()],
             devtools_enabled: true,
             license_ok: true,
             disk_bytes: MIN_DISK_BYTES + 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
         };
 
         let failure = validate_sandbox_policy_with_probe(request, &sample_config(), &probe)
@@ -646,12 +1477,18 @@ mod tests {
         let request = InspectXcodeSdksRequest {
             required_sdks: vec!["visionOS".into()],
             xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
         };
         let probe = FakeProbe {
             sdks: vec!["visionOS Simulator".into()],
             devtools_enabled: true,
             license_ok: true,
             disk_bytes: MIN_DISK_BYTES + 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
         };
 
         let response =
@@ -684,12 +1521,23 @@ mod tests {
             project_path: allowed.join("..").join("outside"),
             required_sdks: vec!["visionOS".into()],
             xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: true,
+            remediate: false,
         };
         let probe = FakeProbe {
             sdks: vec!["visionOS".into()],
             devtools_enabled: true,
             license_ok: true,
             disk_bytes: MIN_DISK_BYTES + 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
         };
 
         let failure = validate_sandbox_policy_with_probe(request, &config, &probe)
@@ -709,12 +1557,23 @@ mod tests {
             project_path: PathBuf::from("/tmp/disallowed-project"),
             required_sdks: vec!["visionOS".into()],
             xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: true,
+            remediate: false,
         };
         let probe = FakeProbe {
             sdks: vec!["visionOS".into()],
             devtools_enabled: true,
             license_ok: true,
             disk_bytes: 500 * 1024 * 1024,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
         };
 
         let failure = validate_sandbox_policy_with_probe(request, &sample_config(), &probe)
@@ -736,12 +1595,23 @@ mod tests {
             project_path: PathBuf::from("/tmp/disallowed-project"),
             required_sdks: vec!["visionOS".into()],
             xcode_path: Some(temp.path().to_path_buf()),
+            platform: None,
+            code_sign_identity: None,
+            runtime_version: None,
+            force_refresh: false,
+            strict: false,
+            remediate: false,
         };
         let probe = FakeProbe {
             sdks: vec!["visionOS".into()],
             devtools_enabled: true,
             license_ok: true,
             disk_bytes: MIN_DISK_BYTES + 1,
+            identities: Vec::new(),
+            remediated: Default::default(),
+            xcode_select: None,
+            runtimes: vec!["26.2"],
+            unreachable_hosts: Vec::new(),
         };
 
         let mut config = sample_config();
@@ -1,11 +1,26 @@
 use std::{
-    collections::BTreeSet, ffi::CString, os::unix::ffi::OsStrExt, path::Path, path::PathBuf,
+    collections::BTreeSet,
+    ffi::CString,
+    net::{TcpStream, ToSocketAddrs},
+    os::unix::ffi::OsStrExt,
+    path::Path,
+    path::PathBuf,
     process::Command,
+    time::Duration,
 };
 
-use crate::lib::errors::SandboxPolicyError;
+use crate::{
+    lib::{
+        errors::SandboxPolicyError,
+        signing::{parse_find_identity_output, SigningIdentity},
+    },
+    tools::visionos::simulator::parse_visionos_runtimes,
+};
 
-use super::MIN_DISK_BYTES;
+/// Install location of the Xcode Command Line Tools.
+const COMMAND_LINE_TOOLS_DIR: &str = "/Library/Developer/CommandLineTools";
+/// How long a package host gets to accept a TCP connection.
+const HOST_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SdkInventory {
@@ -75,6 +90,38 @@ pub trait SandboxProbe {
     fn devtools_security_enabled(&self) -> Result<bool, SandboxPolicyError>;
     fn xcode_license_accepted(&self) -> Result<bool, SandboxPolicyError>;
     fn disk_free_bytes(&self, path: &Path) -> Result<u64, SandboxPolicyError>;
+    /// Valid code signing identities in the keychain search list.
+    fn signing_identities(&self) -> Result<Vec<SigningIdentity>, SandboxPolicyError>;
+    /// Developer directory printed by `xcode-select -p`; `None` when nothing is selected.
+    fn selected_developer_dir(&self) -> Result<Option<PathBuf>, SandboxPolicyError>;
+    /// Whether the Xcode Command Line Tools are installed.
+    fn command_line_tools_installed(&self) -> Result<bool, SandboxPolicyError>;
+    /// Versions of the available visionOS simulator runtimes, e.g. `26.2`.
+    fn simulator_runtimes(&self, developer_dir: &Path) -> Result<Vec<String>, SandboxPolicyError>;
+    /// Whether a TCP connection to `host:port` can be opened directly, ignoring any proxy.
+    fn host_reachable(&self, host: &str, port: u16) -> Result<bool, SandboxPolicyError>;
+    /// Remediation for `devtools_security`: `DevToolsSecurity -enable`.
+    fn enable_devtools_security(&self) -> Result<(), SandboxPolicyError>;
+    /// Remediation for `xcode_license`: `xcodebuild -runFirstLaunch`.
+    fn run_first_launch(&self, developer_dir: &Path) -> Result<(), SandboxPolicyError>;
+}
+
+/// Run a remediation command, turning a non-zero exit into an error with its stderr.
+fn run_remediation(mut command: Command, invocation: &str) -> Result<(), SandboxPolicyError> {
+    let output = command
+        .output()
+        .map_err(|err| SandboxPolicyError::Internal {
+            message: format!("Failed to run {invocation}: {err}"),
+        })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(SandboxPolicyError::Internal {
+        message: format!(
+            "{invocation} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    })
 }
 
 /// Probe that operates against the real environment.
@@ -183,6 +230,100 @@ impl SandboxProbe for SystemSandboxProbe {
                 message: "statfs overflow when computing free bytes".into(),
             })
     }
+
+    fn signing_identities(&self) -> Result<Vec<SigningIdentity>, SandboxPolicyError> {
+        let output = Command::new("security")
+            .args(["find-identity", "-v", "-p", "codesigning"])
+            .output()
+            .map_err(|err| SandboxPolicyError::Internal {
+                message: format!("Failed to run security find-identity: {err}"),
+            })?;
+        if !output.status.success() {
+            return Err(SandboxPolicyError::Internal {
+                message: format!(
+                    "security find-identity failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                ),
+            });
+        }
+        Ok(parse_find_identity_output(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    fn selected_developer_dir(&self) -> Result<Option<PathBuf>, SandboxPolicyError> {
+        let output = Command::new("xcode-select")
+            .arg("-p")
+            .output()
+            .map_err(|err| SandboxPolicyError::Internal {
+                message: format!("Failed to run xcode-select: {err}"),
+            })?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let selected = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((!selected.is_empty()).then(|| PathBuf::from(selected)))
+    }
+
+    fn command_line_tools_installed(&self) -> Result<bool, SandboxPolicyError> {
+        Ok(Path::new(COMMAND_LINE_TOOLS_DIR).join("usr/bin").is_dir())
+    }
+
+    fn simulator_runtimes(&self, developer_dir: &Path) -> Result<Vec<String>, SandboxPolicyError> {
+        let mut command = Command::new("xcrun");
+        command.args(["simctl", "list", "runtimes", "--json"]);
+        if !developer_dir.as_os_str().is_empty() {
+            command.env("DEVELOPER_DIR", developer_dir);
+        }
+        let output = command
+            .output()
+            .map_err(|err| SandboxPolicyError::Internal {
+                message: format!("Failed to run xcrun simctl: {err}"),
+            })?;
+        if !output.status.success() {
+            return Err(SandboxPolicyError::Internal {
+                message: format!(
+                    "xcrun simctl list runtimes failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                ),
+            });
+        }
+        let runtimes = parse_visionos_runtimes(&output.stdout).map_err(|err| {
+            SandboxPolicyError::Internal {
+                message: format!("Failed to parse simctl runtime list: {err}"),
+            }
+        })?;
+        Ok(runtimes
+            .into_iter()
+            .filter(|runtime| runtime.is_available)
+            .map(|runtime| runtime.version)
+            .collect())
+    }
+
+    fn host_reachable(&self, host: &str, port: u16) -> Result<bool, SandboxPolicyError> {
+        // A name that does not resolve is as unreachable as a refused connection.
+        let Ok(addresses) = (host, port).to_socket_addrs() else {
+            return Ok(false);
+        };
+        Ok(addresses
+            .into_iter()
+            .any(|address| TcpStream::connect_timeout(&address, HOST_CONNECT_TIMEOUT).is_ok()))
+    }
+
+    fn enable_devtools_security(&self) -> Result<(), SandboxPolicyError> {
+        let mut command = Command::new("DevToolsSecurity");
+        command.arg("-enable");
+        run_remediation(command, "DevToolsSecurity -enable")
+    }
+
+    fn run_first_launch(&self, developer_dir: &Path) -> Result<(), SandboxPolicyError> {
+        let mut command = Command::new("xcodebuild");
+        command.arg("-runFirstLaunch");
+        if !developer_dir.as_os_str().is_empty() {
+            command.env("DEVELOPER_DIR", developer_dir);
+        }
+        run_remediation(command, "xcodebuild -runFirstLaunch")
+    }
 }
 
 pub struct EnvSandboxProbe;
@@ -240,18 +381,74 @@ impl SandboxProbe for EnvSandboxProbe {
     }
 
     fn disk_free_bytes(&self, _path: &Path) -> Result<u64, SandboxPolicyError> {
-        let bytes = std::env::var("VISIONOS_SANDBOX_DISK_BYTES")
+        Ok(std::env::var("VISIONOS_SANDBOX_DISK_BYTES")
             .ok()
             .and_then(|value| value.parse::<u64>().ok())
-            .unwrap_or(u64::MAX / 2);
+            .unwrap_or(u64::MAX / 2))
+    }
 
-        if bytes < MIN_DISK_BYTES {
-            return Err(SandboxPolicyError::DiskInsufficient {
-                available_bytes: bytes,
-            });
-        }
+    /// Identity names from the comma-separated `VISIONOS_SANDBOX_SIGNING_IDENTITIES`.
+    fn signing_identities(&self) -> Result<Vec<SigningIdentity>, SandboxPolicyError> {
+        Ok(std::env::var("VISIONOS_SANDBOX_SIGNING_IDENTITIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| SigningIdentity {
+                hash: String::new(),
+                name: name.to_string(),
+            })
+            .collect())
+    }
+
+    /// `VISIONOS_SANDBOX_XCODE_SELECT`; unset means nothing is selected.
+    fn selected_developer_dir(&self) -> Result<Option<PathBuf>, SandboxPolicyError> {
+        Ok(std::env::var("VISIONOS_SANDBOX_XCODE_SELECT")
+            .ok()
+            .filter(|path| !path.trim().is_empty())
+            .map(PathBuf::from))
+    }
+
+    fn command_line_tools_installed(&self) -> Result<bool, SandboxPolicyError> {
+        Ok(matches!(
+            std::env::var("VISIONOS_SANDBOX_CLT")
+                .unwrap_or_else(|_| "installed".into())
+                .to_lowercase()
+                .as_str(),
+            "installed" | "true" | "1"
+        ))
+    }
 
-        Ok(bytes)
+    /// Versions from the comma-separated `VISIONOS_SANDBOX_RUNTIMES`.
+    fn simulator_runtimes(&self, _developer_dir: &Path) -> Result<Vec<String>, SandboxPolicyError> {
+        Ok(std::env::var("VISIONOS_SANDBOX_RUNTIMES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|version| !version.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Reachable unless `host` is listed in the comma-separated
+    /// `VISIONOS_SANDBOX_UNREACHABLE_HOSTS`.
+    fn host_reachable(&self, host: &str, _port: u16) -> Result<bool, SandboxPolicyError> {
+        Ok(!std::env::var("VISIONOS_SANDBOX_UNREACHABLE_HOSTS")
+            .unwrap_or_default()
+            .split(',')
+            .any(|unreachable| unreachable.trim() == host))
+    }
+
+    fn enable_devtools_security(&self) -> Result<(), SandboxPolicyError> {
+        Err(SandboxPolicyError::Internal {
+            message: "the env probe cannot enable DevToolsSecurity".into(),
+        })
+    }
+
+    fn run_first_launch(&self, _developer_dir: &Path) -> Result<(), SandboxPolicyError> {
+        Err(SandboxPolicyError::Internal {
+            message: "the env probe cannot run xcodebuild -runFirstLaunch".into(),
+        })
     }
 }
 
@@ -0,0 +1,121 @@
+//! `list_xcode_installations`: the version of `visionos.xcode_path` and each
+//! `[[visionos.xcodes]]` entry, from `xcodebuild -version`.
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::server::config::VisionOsConfig;
+
+/// Name reported for `visionos.xcode_path`, which builds use when `xcode` is omitted.
+pub const DEFAULT_XCODE_NAME: &str = "default";
+
+/// Input for `list_xcode_installations`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListXcodeInstallationsRequest {}
+
+/// One configured Developer directory.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct XcodeInstallation {
+    /// Value to pass as `xcode` on build requests; `default` is `visionos.xcode_path`.
+    pub name: String,
+    pub developer_dir: String,
+    pub default: bool,
+    /// Marketing version, e.g. `26.2`.
+    pub version: Option<String>,
+    /// Build number, e.g. `17C52`.
+    pub build: Option<String>,
+    /// Why `xcodebuild -version` failed for this installation.
+    pub error: Option<String>,
+}
+
+/// Response from `list_xcode_installations`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListXcodeInstallationsResponse {
+    /// The default installation first, then the named ones by name.
+    pub installations: Vec<XcodeInstallation>,
+}
+
+/// Report the version of every configured Xcode. An installation that cannot be queried is
+/// listed with `error` instead of failing the whole call.
+pub async fn list_xcode_installations(
+    _request: ListXcodeInstallationsRequest,
+    config: &VisionOsConfig,
+) -> ListXcodeInstallationsResponse {
+    let mut installations =
+        vec![inspect_installation(DEFAULT_XCODE_NAME, &config.xcode_path, true, config).await];
+    for (name, developer_dir) in &config.xcodes {
+        installations.push(inspect_installation(name, developer_dir, false, config).await);
+    }
+    ListXcodeInstallationsResponse { installations }
+}
+
+async fn inspect_installation(
+    name: &str,
+    developer_dir: &Path,
+    default: bool,
+    config: &VisionOsConfig,
+) -> XcodeInstallation {
+    let mut installation = XcodeInstallation {
+        name: name.to_string(),
+        developer_dir: developer_dir.display().to_string(),
+        default,
+        version: None,
+        build: None,
+        error: None,
+    };
+    let output = Command::new(&config.xcodebuild_path)
+        .env("DEVELOPER_DIR", developer_dir)
+        .arg("-version")
+        .kill_on_drop(true)
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => {
+            let (version, build) = parse_xcode_version(&String::from_utf8_lossy(&output.stdout));
+            if version.is_none() {
+                installation.error = Some("xcodebuild -version printed no Xcode version".into());
+            }
+            installation.version = version;
+            installation.build = build;
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            installation.error = Some(match stderr.trim() {
+                "" => format!("xcodebuild -version exited with {}", output.status),
+                stderr => stderr.to_string(),
+            });
+        }
+        Err(err) => installation.error = Some(err.to_string()),
+    }
+    installation
+}
+
+/// Parse the `Xcode 26.2` and `Build version 17C52` lines.
+fn parse_xcode_version(stdout: &str) -> (Option<String>, Option<String>) {
+    let mut version = None;
+    let mut build = None;
+    for line in stdout.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("Xcode ") {
+            version = Some(rest.trim().to_string());
+        } else if let Some(rest) = line.strip_prefix("Build version ") {
+            build = Some(rest.trim().to_string());
+        }
+    }
+    (version, build)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_version_and_build_number() {
+        assert_eq!(
+            parse_xcode_version("Xcode 26.2\nBuild version 17C52\n"),
+            (Some("26.2".into()), Some("17C52".into()))
+        );
+        assert_eq!(parse_xcode_version("xcode-select: error"), (None, None));
+    }
+}
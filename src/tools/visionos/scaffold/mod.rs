@@ -0,0 +1,338 @@
+//! `create_visionos_project`: minimal visionOS app and Swift package skeletons from templates
+//! embedded in the binary.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    lib::{
+        errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+        visionos::is_allowed_path,
+        xcodebuild::PACKAGE_MANIFEST,
+    },
+    server::config::VisionOsConfig,
+    tools::visionos::{build::BuildVisionOsAppResponse, resources::PublishedResource},
+};
+
+const NAME_PLACEHOLDER: &str = "__NAME__";
+const BUNDLE_ID_PLACEHOLDER: &str = "__BUNDLE_ID__";
+const MAX_NAME_LEN: usize = 64;
+const MAX_BUNDLE_ID_LEN: usize = 155;
+const SCHEME_MIME_TYPE: &str = "application/json";
+
+const APP_TEMPLATE: &[(&str, &str)] = &[
+    (
+        "__NAME__.xcodeproj/project.pbxproj",
+        include_str!("templates/app/project.pbxproj"),
+    ),
+    (
+        "__NAME__.xcodeproj/xcshareddata/xcschemes/__NAME__.xcscheme",
+        include_str!("templates/app/scheme.xcscheme"),
+    ),
+    (
+        "__NAME__/__NAME__App.swift",
+        include_str!("templates/app/App.swift"),
+    ),
+    (
+        "__NAME__/ContentView.swift",
+        include_str!("templates/app/ContentView.swift"),
+    ),
+];
+const PACKAGE_TEMPLATE: &[(&str, &str)] = &[
+    (
+        "Package.swift",
+        include_str!("templates/package/Package.swift"),
+    ),
+    (
+        "Sources/__NAME__/__NAME__.swift",
+        include_str!("templates/package/Library.swift"),
+    ),
+    (
+        "Tests/__NAME__Tests/__NAME__Tests.swift",
+        include_str!("templates/package/LibraryTests.swift"),
+    ),
+];
+
+const INVALID_REQUEST_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "invalid_request",
+    "Input validation failed",
+    "Use a name starting with a letter followed by letters, digits, or underscores, and a reverse-DNS bundle_identifier.",
+);
+const PARENT_NOT_FOUND_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "parent_path_not_found",
+    "parent_path does not exist",
+    "Create the directory first or pick an existing directory inside visionos.allowed_paths.",
+);
+const PATH_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "path_not_allowed",
+    "parent_path is outside the allowlist",
+    "Update visionos.allowed_paths in seiro-mcp.toml; the running server reloads it on save.",
+);
+const PROJECT_EXISTS_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "project_exists",
+    "The project directory already exists and is not empty",
+    "Pick another name or remove the existing directory.",
+);
+const SCAFFOLD_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "scaffold_failed",
+    "Failed to write the project files",
+    "Check free space and permissions of parent_path, then retry.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&INVALID_REQUEST_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&PARENT_NOT_FOUND_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&PATH_NOT_ALLOWED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&PROJECT_EXISTS_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&SCAFFOLD_FAILED_ERROR, true, SandboxState::NoViolation),
+];
+
+/// Skeleton generated by `create_visionos_project`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectTemplate {
+    /// SwiftUI app in an `.xcodeproj` with a shared scheme.
+    #[default]
+    App,
+    /// Swift package with one library and its tests.
+    Package,
+}
+
+/// Input for `create_visionos_project`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateVisionOsProjectRequest {
+    /// Existing directory inside `visionos.allowed_paths`; the project is created in
+    /// `<parent_path>/<name>`.
+    pub parent_path: PathBuf,
+    /// Project, target, and scheme name.
+    pub name: String,
+    #[serde(default)]
+    pub template: ProjectTemplate,
+    /// Bundle ID of an app; defaults to `com.example.<name>`.
+    #[serde(default)]
+    pub bundle_identifier: Option<String>,
+    /// Build the new project with the platform's default destination once it is written.
+    #[serde(default)]
+    pub build: bool,
+}
+
+/// Response from `create_visionos_project`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CreateVisionOsProjectResponse {
+    pub status: &'static str,
+    pub template: ProjectTemplate,
+    pub project_dir: String,
+    /// `project_path` for the build, test, and scheme tools.
+    pub project_path: String,
+    pub scheme: String,
+    /// Whether `visionos.allowed_schemes` accepts `scheme`; otherwise add it there to build.
+    pub scheme_allowed: bool,
+    /// Resource holding the suggested build request for the new project.
+    pub scheme_uri: String,
+    /// Files written, relative to `project_dir`.
+    pub files: Vec<String>,
+    /// First build when `build` was requested and succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<BuildVisionOsAppResponse>,
+    /// Error data of the first build when it failed; the project stays in place.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_error: Option<Value>,
+}
+
+impl CreateVisionOsProjectResponse {
+    /// Resource at `scheme_uri` suggesting how to build the new project.
+    pub fn scheme_resource(&self) -> PublishedResource {
+        let suggestion = json!({
+            "project_path": self.project_path,
+            "scheme": self.scheme,
+            "scheme_allowed": self.scheme_allowed,
+        });
+        PublishedResource {
+            uri: self.scheme_uri.clone(),
+            name: format!("scheme-{}", self.scheme),
+            description: format!(
+                "Build request for the {} project created by create_visionos_project",
+                self.scheme
+            ),
+            mime_type: SCHEME_MIME_TYPE.to_string(),
+            text: serde_json::to_string_pretty(&suggestion).unwrap_or_default(),
+        }
+    }
+}
+
+/// Write the skeleton for `request` and describe it; the registry runs the optional build.
+pub fn create_visionos_project(
+    request: &CreateVisionOsProjectRequest,
+    config: &VisionOsConfig,
+) -> Result<CreateVisionOsProjectResponse, ErrorData> {
+    let name = request.name.trim();
+    if !is_valid_name(name) {
+        return Err(invalid_request(
+            json!({ "field": "name", "value": request.name }),
+        ));
+    }
+    let bundle_identifier = request
+        .bundle_identifier
+        .clone()
+        .unwrap_or_else(|| format!("com.example.{name}"));
+    if !is_valid_bundle_identifier(&bundle_identifier) {
+        return Err(invalid_request(
+            json!({ "field": "bundle_identifier", "value": bundle_identifier }),
+        ));
+    }
+
+    let parent = &request.parent_path;
+    if !parent.is_absolute() || !parent.is_dir() {
+        return Err(build_error_data(
+            &PARENT_NOT_FOUND_ERROR,
+            json!({ "parent_path": parent.to_string_lossy() }),
+            SandboxState::NoViolation,
+            false,
+        ));
+    }
+    if !config.allowed_paths.is_empty() && !is_allowed_path(parent, &config.allowed_paths) {
+        return Err(build_error_data(
+            &PATH_NOT_ALLOWED_ERROR,
+            json!({ "path": parent.to_string_lossy() }),
+            SandboxState::Blocked,
+            false,
+        ));
+    }
+    let project_dir = parent.join(name);
+    let occupied = fs::read_dir(&project_dir).is_ok_and(|mut entries| entries.next().is_some())
+        || project_dir.is_file();
+    if occupied {
+        return Err(build_error_data(
+            &PROJECT_EXISTS_ERROR,
+            json!({ "project_dir": project_dir.to_string_lossy() }),
+            SandboxState::NoViolation,
+            false,
+        ));
+    }
+
+    let (template, project_path) = match request.template {
+        ProjectTemplate::App => (APP_TEMPLATE, project_dir.join(format!("{name}.xcodeproj"))),
+        ProjectTemplate::Package => (PACKAGE_TEMPLATE, project_dir.join(PACKAGE_MANIFEST)),
+    };
+    let files =
+        write_template(&project_dir, template, name, &bundle_identifier).map_err(|err| {
+            let _ = fs::remove_dir_all(&project_dir);
+            build_error_data(
+                &SCAFFOLD_FAILED_ERROR,
+                json!({ "project_dir": project_dir.to_string_lossy(), "details": err.to_string() }),
+                SandboxState::NoViolation,
+                true,
+            )
+        })?;
+
+    Ok(CreateVisionOsProjectResponse {
+        status: "created",
+        template: request.template,
+        project_dir: project_dir.to_string_lossy().to_string(),
+        project_path: project_path.to_string_lossy().to_string(),
+        scheme: name.to_string(),
+        scheme_allowed: config.allows_scheme(name),
+        scheme_uri: format!("seiro://visionos/projects/{name}"),
+        files,
+        build: None,
+        build_error: None,
+    })
+}
+
+fn write_template(
+    project_dir: &Path,
+    template: &[(&str, &str)],
+    name: &str,
+    bundle_identifier: &str,
+) -> std::io::Result<Vec<String>> {
+    let mut files = Vec::with_capacity(template.len());
+    for (path, contents) in template {
+        let relative = path.replace(NAME_PLACEHOLDER, name);
+        let target = project_dir.join(&relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = contents
+            .replace(NAME_PLACEHOLDER, name)
+            .replace(BUNDLE_ID_PLACEHOLDER, bundle_identifier);
+        fs::write(&target, contents)?;
+        files.push(relative);
+    }
+    Ok(files)
+}
+
+/// Names become Swift type names, directory names, and scheme names.
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && name.len() <= MAX_NAME_LEN
+}
+
+fn is_valid_bundle_identifier(value: &str) -> bool {
+    value.len() <= MAX_BUNDLE_ID_LEN
+        && value.contains('.')
+        && value.split('.').all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+fn invalid_request(details: Value) -> ErrorData {
+    build_error_data(
+        &INVALID_REQUEST_ERROR,
+        details,
+        SandboxState::NoViolation,
+        false,
+    )
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    sandbox_state: SandboxState,
+    retryable: bool,
+) -> ErrorData {
+    desc.builder()
+        .details(details)
+        .sandbox_state(sandbox_state)
+        .retryable(retryable)
+        .build()
+        .expect("descriptor is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_and_bundle_identifiers_are_validated() {
+        assert!(is_valid_name("VisionApp_2"));
+        assert!(!is_valid_name("2VisionApp"));
+        assert!(!is_valid_name("../VisionApp"));
+        assert!(!is_valid_name(""));
+        assert!(is_valid_bundle_identifier("com.example.Vision-App"));
+        assert!(!is_valid_bundle_identifier("VisionApp"));
+        assert!(!is_valid_bundle_identifier("com..example"));
+    }
+
+    #[test]
+    fn templates_only_leave_known_placeholders() {
+        for (path, contents) in APP_TEMPLATE.iter().chain(PACKAGE_TEMPLATE) {
+            let rendered = contents
+                .replace(NAME_PLACEHOLDER, "VisionApp")
+                .replace(BUNDLE_ID_PLACEHOLDER, "com.example.VisionApp");
+            assert!(
+                !rendered.contains("__"),
+                "{path} has an unknown placeholder"
+            );
+        }
+    }
+}
@@ -0,0 +1,6 @@
+/// Entry point of the __NAME__ package.
+public enum __NAME__ {
+    public static func greeting() -> String {
+        "Hello from __NAME__"
+    }
+}
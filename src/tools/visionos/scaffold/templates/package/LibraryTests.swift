@@ -0,0 +1,8 @@
+import XCTest
+@testable import __NAME__
+
+final class __NAME__Tests: XCTestCase {
+    func testGreeting() {
+        XCTAssertEqual(__NAME__.greeting(), "Hello from __NAME__")
+    }
+}
@@ -0,0 +1,16 @@
+// swift-tools-version: 5.9
+import PackageDescription
+
+let package = Package(
+    name: "__NAME__",
+    platforms: [
+        .visionOS(.v1),
+    ],
+    products: [
+        .library(name: "__NAME__", targets: ["__NAME__"]),
+    ],
+    targets: [
+        .target(name: "__NAME__"),
+        .testTarget(name: "__NAME__Tests", dependencies: ["__NAME__"]),
+    ]
+)
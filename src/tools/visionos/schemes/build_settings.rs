@@ -0,0 +1,285 @@
+//! `show_build_settings`: the build settings `xcodebuild -showBuildSettings -json` resolves for a
+//! scheme, trimmed to the ones agents check before building.
+use std::{collections::BTreeMap, path::PathBuf};
+
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::process::Command;
+
+use crate::{
+    lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+    server::config::VisionOsConfig,
+};
+
+use super::{build_error_data, validate_allowed_project_path, validate_allowed_scheme};
+
+/// Settings returned for every target.
+pub const DEFAULT_BUILD_SETTING_KEYS: &[&str] = &[
+    "PRODUCT_NAME",
+    "PRODUCT_BUNDLE_IDENTIFIER",
+    "MARKETING_VERSION",
+    "CURRENT_PROJECT_VERSION",
+    "CONFIGURATION",
+    "SDKROOT",
+    "SUPPORTED_PLATFORMS",
+    "XROS_DEPLOYMENT_TARGET",
+    "IPHONEOS_DEPLOYMENT_TARGET",
+    "MACOSX_DEPLOYMENT_TARGET",
+    "ARCHS",
+    "SWIFT_VERSION",
+    "INFOPLIST_FILE",
+    "DEBUG_INFORMATION_FORMAT",
+    "CODE_SIGN_STYLE",
+    "CODE_SIGN_IDENTITY",
+    "DEVELOPMENT_TEAM",
+    "PROVISIONING_PROFILE_SPECIFIER",
+    "CODE_SIGN_ENTITLEMENTS",
+];
+/// Most extra `keys` one request may add.
+pub const MAX_EXTRA_BUILD_SETTING_KEYS: usize = 32;
+
+const INVALID_REQUEST_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "invalid_request",
+    "The build settings request is invalid",
+    "Use at most 32 keys made of A-Z, 0-9, and _, and a configuration name without spaces.",
+);
+const SHOW_BUILD_SETTINGS_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "show_build_settings_failed",
+    "xcodebuild -showBuildSettings -json failed",
+    "Check the scheme and configuration with list_project_schemes, then retry.",
+);
+const BUILD_SETTINGS_PARSE_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "build_settings_parse_failed",
+    "Failed to parse xcodebuild build settings output",
+    "Verify xcodebuild -showBuildSettings -json output format and retry.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&INVALID_REQUEST_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(
+        &SHOW_BUILD_SETTINGS_FAILED_ERROR,
+        true,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(
+        &BUILD_SETTINGS_PARSE_FAILED_ERROR,
+        true,
+        SandboxState::NoViolation,
+    ),
+];
+
+/// Input for `show_build_settings`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ShowBuildSettingsRequest {
+    pub project_path: PathBuf,
+    pub scheme: String,
+    /// Build configuration such as `Debug` or `Release`; defaults to the scheme's.
+    #[serde(default)]
+    pub configuration: Option<String>,
+    /// Settings to return in addition to the defaults, e.g. `["OTHER_SWIFT_FLAGS"]`.
+    #[serde(default)]
+    pub keys: Vec<String>,
+}
+
+/// Settings of one target built by the scheme.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct TargetBuildSettings {
+    pub target: String,
+    /// Requested settings the target defines; unset ones are omitted.
+    pub settings: BTreeMap<String, String>,
+}
+
+/// Response from `show_build_settings`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ShowBuildSettingsResponse {
+    pub status: &'static str,
+    pub project_path: String,
+    pub scheme: String,
+    pub targets: Vec<TargetBuildSettings>,
+    pub invocation: String,
+}
+
+/// Resolve the build settings of an allowlisted project and scheme.
+pub async fn show_build_settings(
+    request: ShowBuildSettingsRequest,
+    config: &VisionOsConfig,
+) -> Result<ShowBuildSettingsResponse, ErrorData> {
+    validate_request(&request)?;
+    let project_path = &request.project_path;
+    validate_allowed_project_path(project_path, config)?;
+    validate_allowed_scheme(&request.scheme, config)?;
+
+    let project_flag = if project_path
+        .extension()
+        .is_some_and(|ext| ext == "xcworkspace")
+    {
+        "-workspace"
+    } else {
+        "-project"
+    };
+    let mut args = vec![
+        "-showBuildSettings".to_string(),
+        "-json".to_string(),
+        project_flag.to_string(),
+        project_path.to_string_lossy().to_string(),
+        "-scheme".to_string(),
+        request.scheme.clone(),
+    ];
+    if let Some(configuration) = &request.configuration {
+        args.push("-configuration".into());
+        args.push(configuration.clone());
+    }
+    let invocation = format!(
+        "DEVELOPER_DIR={} {} {}",
+        config.xcode_path.display(),
+        config.xcodebuild_path.display(),
+        args.join(" ")
+    );
+    let output = Command::new(&config.xcodebuild_path)
+        .env("DEVELOPER_DIR", &config.xcode_path)
+        .args(&args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|err| {
+            build_error_data(
+                &SHOW_BUILD_SETTINGS_FAILED_ERROR,
+                json!({ "invocation": invocation, "details": err.to_string() }),
+                SandboxState::NoViolation,
+                true,
+            )
+        })?;
+    if !output.status.success() {
+        return Err(build_error_data(
+            &SHOW_BUILD_SETTINGS_FAILED_ERROR,
+            json!({
+                "invocation": invocation,
+                "exit_code": output.status.code(),
+                "stderr": String::from_utf8_lossy(&output.stderr),
+            }),
+            SandboxState::NoViolation,
+            true,
+        ));
+    }
+    let parsed: Value = serde_json::from_slice(&output.stdout).map_err(|err| {
+        build_error_data(
+            &BUILD_SETTINGS_PARSE_FAILED_ERROR,
+            json!({ "invocation": invocation, "details": err.to_string() }),
+            SandboxState::NoViolation,
+            true,
+        )
+    })?;
+
+    Ok(ShowBuildSettingsResponse {
+        status: "ok",
+        project_path: project_path.display().to_string(),
+        scheme: request.scheme.clone(),
+        targets: filter_build_settings(&parsed, &request.keys),
+        invocation,
+    })
+}
+
+fn validate_request(request: &ShowBuildSettingsRequest) -> Result<(), ErrorData> {
+    let invalid_key = request.keys.iter().find(|key| {
+        key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+    });
+    let invalid_configuration = request.configuration.as_deref().is_some_and(|name| {
+        name.is_empty() || name.starts_with('-') || name.chars().any(char::is_whitespace)
+    });
+    if request.keys.len() > MAX_EXTRA_BUILD_SETTING_KEYS
+        || invalid_key.is_some()
+        || invalid_configuration
+        || request.scheme.is_empty()
+    {
+        return Err(build_error_data(
+            &INVALID_REQUEST_ERROR,
+            json!({
+                "keys": request.keys.len(),
+                "invalid_key": invalid_key,
+                "configuration": request.configuration,
+            }),
+            SandboxState::NoViolation,
+            false,
+        ));
+    }
+    Ok(())
+}
+
+/// Keep the default and `extra_keys` settings of each `{ "target", "buildSettings" }` entry.
+fn filter_build_settings(parsed: &Value, extra_keys: &[String]) -> Vec<TargetBuildSettings> {
+    let wanted = |key: &str| {
+        DEFAULT_BUILD_SETTING_KEYS.contains(&key) || extra_keys.iter().any(|extra| extra == key)
+    };
+    parsed
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let target = entry.get("target").and_then(Value::as_str)?;
+            let settings = entry.get("buildSettings").and_then(Value::as_object)?;
+            Some(TargetBuildSettings {
+                target: target.to_string(),
+                settings: settings
+                    .iter()
+                    .filter(|(key, _)| wanted(key))
+                    .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_keeps_default_and_requested_settings_per_target() {
+        let parsed = json!([
+            {
+                "action": "build",
+                "target": "VisionApp",
+                "buildSettings": {
+                    "PRODUCT_BUNDLE_IDENTIFIER": "com.example.VisionApp",
+                    "XROS_DEPLOYMENT_TARGET": "2.0",
+                    "OTHER_SWIFT_FLAGS": "-DDEBUG",
+                    "HOME": "/Users/example"
+                }
+            },
+            { "action": "build", "target": "Widgets", "buildSettings": { "SDKROOT": "xros" } }
+        ]);
+
+        let targets = filter_build_settings(&parsed, &["OTHER_SWIFT_FLAGS".to_string()]);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(
+            targets[0].settings.keys().collect::<Vec<_>>(),
+            vec![
+                "OTHER_SWIFT_FLAGS",
+                "PRODUCT_BUNDLE_IDENTIFIER",
+                "XROS_DEPLOYMENT_TARGET"
+            ]
+        );
+        assert_eq!(targets[1].target, "Widgets");
+        assert!(filter_build_settings(&json!({}), &[]).is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_lowercase_keys_and_flag_configurations() {
+        let request = |keys: Vec<&str>, configuration: Option<&str>| ShowBuildSettingsRequest {
+            project_path: PathBuf::from("/tmp/VisionApp.xcodeproj"),
+            scheme: "VisionApp".into(),
+            configuration: configuration.map(str::to_string),
+            keys: keys.into_iter().map(str::to_string).collect(),
+        };
+
+        assert!(validate_request(&request(vec!["SWIFT_VERSION"], Some("Release"))).is_ok());
+        assert!(validate_request(&request(vec!["swift_version"], None)).is_err());
+        assert!(validate_request(&request(Vec::new(), Some("-quiet"))).is_err());
+        assert!(validate_request(&request(vec!["A"; 33], None)).is_err());
+    }
+}
@@ -0,0 +1,256 @@
+//! `list_destinations`: the run destinations `xcodebuild -showdestinations` offers for a scheme.
+use std::path::PathBuf;
+
+use regex::Regex;
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::process::Command;
+
+use crate::{
+    lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+    server::config::VisionOsConfig,
+};
+
+use super::{build_error_data, validate_allowed_project_path, validate_allowed_scheme};
+
+/// Prefix of the `id` xcodebuild gives "Any <platform> Device" placeholders.
+const PLACEHOLDER_ID_PREFIX: &str = "dvtdevice-";
+
+const SHOW_DESTINATIONS_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "show_destinations_failed",
+    "xcodebuild -showdestinations failed",
+    "Check the scheme with list_project_schemes and that the simulator runtimes are installed, then retry.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[ErrorCatalogEntry::new(
+    &SHOW_DESTINATIONS_FAILED_ERROR,
+    true,
+    SandboxState::NoViolation,
+)];
+
+/// Input for `list_destinations`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListDestinationsRequest {
+    pub project_path: PathBuf,
+    pub scheme: String,
+    /// Also return destinations xcodebuild lists as ineligible, with the reason in `error`.
+    #[serde(default)]
+    pub include_ineligible: bool,
+}
+
+/// Destination descriptor printed by `xcodebuild -showdestinations`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct BuildDestination {
+    /// e.g. `visionOS Simulator`, `visionOS`, `macOS`.
+    pub platform: String,
+    pub name: String,
+    /// `OS` field; absent for generic destinations.
+    pub os_version: Option<String>,
+    pub id: Option<String>,
+    pub arch: Option<String>,
+    pub variant: Option<String>,
+    /// `true` for "Any <platform> Device" entries.
+    pub generic: bool,
+    pub eligible: bool,
+    /// Why xcodebuild cannot use an ineligible destination.
+    pub error: Option<String>,
+    /// Value to pass as `destination` to `build_visionos_app`, selecting the device by `id`.
+    pub destination: String,
+}
+
+/// Response from `list_destinations`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListDestinationsResponse {
+    pub status: &'static str,
+    pub project_path: String,
+    pub scheme: String,
+    pub destinations: Vec<BuildDestination>,
+    pub invocation: String,
+}
+
+/// List the destinations of an allowlisted project and scheme.
+pub async fn list_destinations(
+    request: ListDestinationsRequest,
+    config: &VisionOsConfig,
+) -> Result<ListDestinationsResponse, ErrorData> {
+    let project_path = &request.project_path;
+    validate_allowed_project_path(project_path, config)?;
+    validate_allowed_scheme(&request.scheme, config)?;
+
+    let project_flag = if project_path
+        .extension()
+        .is_some_and(|ext| ext == "xcworkspace")
+    {
+        "-workspace"
+    } else {
+        "-project"
+    };
+    let invocation = format!(
+        "DEVELOPER_DIR={} {} -showdestinations {} {} -scheme {}",
+        config.xcode_path.display(),
+        config.xcodebuild_path.display(),
+        project_flag,
+        project_path.display(),
+        request.scheme
+    );
+    let output = Command::new(&config.xcodebuild_path)
+        .env("DEVELOPER_DIR", &config.xcode_path)
+        .arg("-showdestinations")
+        .arg(project_flag)
+        .arg(project_path)
+        .arg("-scheme")
+        .arg(&request.scheme)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|err| {
+            build_error_data(
+                &SHOW_DESTINATIONS_FAILED_ERROR,
+                json!({ "invocation": invocation, "details": err.to_string() }),
+                SandboxState::NoViolation,
+                true,
+            )
+        })?;
+    if !output.status.success() {
+        return Err(build_error_data(
+            &SHOW_DESTINATIONS_FAILED_ERROR,
+            json!({
+                "invocation": invocation,
+                "exit_code": output.status.code(),
+                "stderr": String::from_utf8_lossy(&output.stderr),
+            }),
+            SandboxState::NoViolation,
+            true,
+        ));
+    }
+
+    let destinations = parse_destinations(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .filter(|destination| destination.eligible || request.include_ineligible)
+        .collect();
+    Ok(ListDestinationsResponse {
+        status: "ok",
+        project_path: project_path.display().to_string(),
+        scheme: request.scheme.clone(),
+        destinations,
+        invocation,
+    })
+}
+
+/// Parse the `{ platform:..., id:..., name:... }` lines under the "Available" and "Ineligible"
+/// headings.
+fn parse_destinations(stdout: &str) -> Vec<BuildDestination> {
+    // Values may contain `, ` themselves (`variant:Designed for [iPad,iPhone]`, error text), so
+    // split only before a `key:` pair.
+    let key_pattern = Regex::new(r"(?:^|, )([A-Za-z]+):").expect("key pattern is valid");
+    let mut eligible = true;
+    let mut destinations = Vec::new();
+    for line in stdout.lines().map(str::trim) {
+        if line.starts_with("Available destinations") {
+            eligible = true;
+            continue;
+        }
+        if line.starts_with("Ineligible destinations") {
+            eligible = false;
+            continue;
+        }
+        let Some(body) = line
+            .strip_prefix('{')
+            .and_then(|rest| rest.strip_suffix('}'))
+            .map(str::trim)
+        else {
+            continue;
+        };
+        let keys: Vec<_> = key_pattern.captures_iter(body).collect();
+        let mut fields = Vec::new();
+        for (index, caps) in keys.iter().enumerate() {
+            let whole = caps.get(0).expect("match");
+            let end = keys
+                .get(index + 1)
+                .map_or(body.len(), |next| next.get(0).expect("match").start());
+            fields.push((&caps[1], body[whole.end()..end].trim()));
+        }
+        let field = |key: &str| {
+            fields
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let (Some(platform), Some(name)) = (field("platform"), field("name")) else {
+            continue;
+        };
+        let id = field("id");
+        let generic = id
+            .as_deref()
+            .is_some_and(|id| id.starts_with(PLACEHOLDER_ID_PREFIX));
+        let destination = match (&id, generic) {
+            (Some(id), false) => format!("platform={platform},id={id}"),
+            _ => format!("generic/platform={platform}"),
+        };
+        destinations.push(BuildDestination {
+            os_version: field("OS"),
+            arch: field("arch"),
+            variant: field("variant"),
+            error: field("error"),
+            platform,
+            name,
+            id,
+            generic,
+            eligible,
+            destination,
+        });
+    }
+    destinations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHOW_DESTINATIONS: &str = "
+\tAvailable destinations for the \"VisionApp\" scheme:
+\t\t{ platform:visionOS Simulator, arch:arm64, id:F556D53F-412A-4778-AF81-3449D52F5A7F, OS:26.2, name:Apple Vision Pro }
+\t\t{ platform:macOS, arch:arm64, variant:Designed for [iPad,iPhone], id:00006000-000A1C3E0123401E, name:My Mac }
+\t\t{ platform:visionOS, id:dvtdevice-DVTiOSDevicePlaceholder-xros:placeholder, name:Any visionOS Device }
+
+\tIneligible destinations for the \"VisionApp\" scheme:
+\t\t{ platform:iOS, id:dvtdevice-DVTiOSDevicePlaceholder-iphoneos:placeholder, name:Any iOS Device, error:iOS 26.2 is not installed, please download it }
+";
+
+    #[test]
+    fn parses_available_and_ineligible_destinations() {
+        let destinations = parse_destinations(SHOW_DESTINATIONS);
+
+        assert_eq!(destinations.len(), 4);
+        assert_eq!(
+            destinations[0],
+            BuildDestination {
+                platform: "visionOS Simulator".into(),
+                name: "Apple Vision Pro".into(),
+                os_version: Some("26.2".into()),
+                id: Some("F556D53F-412A-4778-AF81-3449D52F5A7F".into()),
+                arch: Some("arm64".into()),
+                variant: None,
+                generic: false,
+                eligible: true,
+                error: None,
+                destination: "platform=visionOS Simulator,id=F556D53F-412A-4778-AF81-3449D52F5A7F"
+                    .into(),
+            }
+        );
+        assert_eq!(
+            destinations[1].variant.as_deref(),
+            Some("Designed for [iPad,iPhone]")
+        );
+        assert!(destinations[2].generic);
+        assert_eq!(destinations[2].destination, "generic/platform=visionOS");
+        assert!(!destinations[3].eligible);
+        assert_eq!(
+            destinations[3].error.as_deref(),
+            Some("iOS 26.2 is not installed, please download it")
+        );
+    }
+}
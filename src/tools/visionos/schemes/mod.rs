@@ -5,7 +5,7 @@ use std::{
 };
 
 use crate::{
-    lib::errors::{SandboxState, ToolErrorDescriptor},
+    lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
     server::config::VisionOsConfig,
 };
 use rmcp::model::ErrorData;
@@ -13,8 +13,20 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
+pub(crate) mod build_settings;
+pub(crate) mod destinations;
+mod projects;
 mod xcodebuild_list;
 
+pub use build_settings::{
+    show_build_settings, ShowBuildSettingsRequest, ShowBuildSettingsResponse, TargetBuildSettings,
+};
+pub use destinations::{
+    list_destinations, BuildDestination, ListDestinationsRequest, ListDestinationsResponse,
+};
+pub use projects::{
+    list_allowed_projects, AllowedProject, ListAllowedProjectsRequest, ListAllowedProjectsResponse,
+};
 use xcodebuild_list::{run_xcodebuild_list, ProjectKind};
 
 const PROJECT_PATH_MISSING_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
@@ -47,12 +59,46 @@ const SCHEME_PARSE_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "Failed to parse xcodebuild list output",
     "Verify xcodebuild -list -json output format and retry.",
 );
+const PATH_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "path_not_allowed",
+    "project_path is outside the allowlist",
+    "Update visionos.allowed_paths in seiro-mcp.toml; the running server reloads it on save.",
+);
+const SCHEME_NOT_ALLOWED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "scheme_not_allowed",
+    "scheme is not in the allowlist",
+    "Update visionos.allowed_schemes in seiro-mcp.toml or use an allowed scheme.",
+);
 const NO_SCHEMES_FOUND_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
     "no_schemes_found",
     "No schemes found",
     "Confirm that schemes are shared and visible to xcodebuild.",
 );
 
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(
+        &PROJECT_PATH_MISSING_ERROR,
+        false,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(
+        &PROJECT_PATH_INVALID_ERROR,
+        false,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(&PROJECT_NOT_FOUND_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&XCODE_PATH_UNAVAILABLE_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(
+        &XCODEBUILD_LIST_FAILED_ERROR,
+        true,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(&SCHEME_PARSE_FAILED_ERROR, true, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&PATH_NOT_ALLOWED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&SCHEME_NOT_ALLOWED_ERROR, false, SandboxState::Blocked),
+    ErrorCatalogEntry::new(&NO_SCHEMES_FOUND_ERROR, false, SandboxState::NoViolation),
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct InspectXcodeSchemesRequest {
     #[serde(default)]
@@ -110,6 +156,106 @@ pub async fn inspect_xcode_schemes(
         ));
     }
 
+    let (parsed, invocation) =
+        load_xcodebuild_list(config, &effective_xcode_path, &project_path).await?;
+
+    let schemes = collect_list_values(&parsed, "schemes");
+
+    if schemes.is_empty() {
+        return Err(build_error_data(
+            &NO_SCHEMES_FOUND_ERROR,
+            json!({ "invocation": invocation }),
+            SandboxState::NoViolation,
+            false,
+        ));
+    }
+
+    Ok(InspectXcodeSchemesResponse {
+        status: "ok",
+        project_path: project_path.display().to_string(),
+        project_path_source: source.as_str().to_string(),
+        schemes,
+        invocation,
+        notes: Vec::new(),
+    })
+}
+
+/// Input for `list_project_schemes`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListProjectSchemesRequest {
+    pub project_path: PathBuf,
+}
+
+/// Response from `list_project_schemes`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListProjectSchemesResponse {
+    pub status: &'static str,
+    pub project_path: String,
+    pub project_name: Option<String>,
+    pub schemes: Vec<String>,
+    pub targets: Vec<String>,
+    pub configurations: Vec<String>,
+    /// Schemes accepted by `visionos.allowed_schemes` (all schemes when the list is empty).
+    pub buildable_schemes: Vec<String>,
+    pub invocation: String,
+}
+
+/// List schemes, targets, and configurations for an allowlisted project or workspace.
+pub async fn list_project_schemes(
+    request: ListProjectSchemesRequest,
+    config: &VisionOsConfig,
+) -> Result<ListProjectSchemesResponse, ErrorData> {
+    let project_path = request.project_path;
+    validate_allowed_project_path(&project_path, config)?;
+
+    let (parsed, invocation) =
+        load_xcodebuild_list(config, &config.xcode_path, &project_path).await?;
+    let schemes = collect_list_values(&parsed, "schemes");
+    let buildable_schemes = schemes
+        .iter()
+        .filter(|scheme| config.allows_scheme(scheme))
+        .cloned()
+        .collect();
+    let project_name = ["project", "workspace"].iter().find_map(|section| {
+        parsed
+            .get(section)
+            .and_then(|value| value.get("name"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    });
+
+    Ok(ListProjectSchemesResponse {
+        status: "ok",
+        project_path: project_path.display().to_string(),
+        project_name,
+        targets: collect_list_values(&parsed, "targets"),
+        configurations: collect_list_values(&parsed, "configurations"),
+        schemes,
+        buildable_schemes,
+        invocation,
+    })
+}
+
+/// Merge a string array (`schemes`, `targets`, ...) from the project and workspace sections.
+fn collect_list_values(parsed: &Value, key: &str) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    ["project", "workspace"]
+        .iter()
+        .filter_map(|section| parsed.get(section))
+        .filter_map(|section| section.get(key))
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(Value::as_str)
+        .filter(|value| seen.insert(value.to_string()))
+        .map(str::to_string)
+        .collect()
+}
+
+async fn load_xcodebuild_list(
+    config: &VisionOsConfig,
+    xcode_path: &Path,
+    project_path: &Path,
+) -> Result<(Value, String), ErrorData> {
     let project_kind = if project_path
         .extension()
         .and_then(|ext| ext.to_str())
@@ -122,8 +268,8 @@ pub async fn inspect_xcode_schemes(
 
     let invocation_result = run_xcodebuild_list(
         &config.xcodebuild_path,
-        &effective_xcode_path,
-        &project_path,
+        xcode_path,
+        project_path,
         project_kind,
     )
     .await
@@ -160,53 +306,7 @@ pub async fn inspect_xcode_schemes(
             true,
         )
     })?;
-
-    let mut schemes = Vec::new();
-    if let Some(project_schemes) = parsed
-        .get("project")
-        .and_then(|project| project.get("schemes"))
-        .and_then(Value::as_array)
-    {
-        schemes.extend(
-            project_schemes
-                .iter()
-                .filter_map(Value::as_str)
-                .map(str::to_string),
-        );
-    }
-    if let Some(workspace_schemes) = parsed
-        .get("workspace")
-        .and_then(|workspace| workspace.get("schemes"))
-        .and_then(Value::as_array)
-    {
-        schemes.extend(
-            workspace_schemes
-                .iter()
-                .filter_map(Value::as_str)
-                .map(str::to_string),
-        );
-    }
-
-    let mut seen = BTreeSet::new();
-    schemes.retain(|scheme| seen.insert(scheme.clone()));
-
-    if schemes.is_empty() {
-        return Err(build_error_data(
-            &NO_SCHEMES_FOUND_ERROR,
-            json!({ "invocation": invocation }),
-            SandboxState::NoViolation,
-            false,
-        ));
-    }
-
-    Ok(InspectXcodeSchemesResponse {
-        status: "ok",
-        project_path: project_path.display().to_string(),
-        project_path_source: source.as_str().to_string(),
-        schemes,
-        invocation,
-        notes: Vec::new(),
-    })
+    Ok((parsed, invocation))
 }
 
 fn resolve_project_path(
@@ -264,6 +364,34 @@ fn find_xcodeproj_in_dir(base_dir: &Path) -> Option<PathBuf> {
     candidates.into_iter().next()
 }
 
+/// [`validate_project_path`] plus the `visionos.allowed_paths` check.
+fn validate_allowed_project_path(path: &Path, config: &VisionOsConfig) -> Result<(), ErrorData> {
+    validate_project_path(path)?;
+    if !config.allowed_paths.is_empty()
+        && !crate::lib::visionos::is_allowed_path(path, &config.allowed_paths)
+    {
+        return Err(build_error_data(
+            &PATH_NOT_ALLOWED_ERROR,
+            json!({ "path": path.to_string_lossy() }),
+            SandboxState::Blocked,
+            false,
+        ));
+    }
+    Ok(())
+}
+
+fn validate_allowed_scheme(scheme: &str, config: &VisionOsConfig) -> Result<(), ErrorData> {
+    if config.allows_scheme(scheme) {
+        return Ok(());
+    }
+    Err(build_error_data(
+        &SCHEME_NOT_ALLOWED_ERROR,
+        json!({ "scheme": scheme }),
+        SandboxState::Blocked,
+        false,
+    ))
+}
+
 fn validate_project_path(path: &Path) -> Result<(), ErrorData> {
     if !path.is_absolute() {
         return Err(build_error_data(
@@ -319,6 +447,20 @@ mod tests {
         dir
     }
 
+    #[test]
+    fn collect_list_values_merges_project_and_workspace_sections() {
+        let parsed = json!({
+            "project": { "schemes": ["VisionApp", "Shared"], "targets": ["VisionApp"] },
+            "workspace": { "schemes": ["Shared", "VisionToolbox"] }
+        });
+        assert_eq!(
+            collect_list_values(&parsed, "schemes"),
+            vec!["VisionApp", "Shared", "VisionToolbox"]
+        );
+        assert_eq!(collect_list_values(&parsed, "targets"), vec!["VisionApp"]);
+        assert!(collect_list_values(&parsed, "configurations").is_empty());
+    }
+
     fn sample_request(project_path: Option<PathBuf>) -> InspectXcodeSchemesRequest {
         InspectXcodeSchemesRequest {
             project_path,
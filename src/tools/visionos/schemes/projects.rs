@@ -0,0 +1,348 @@
+//! `list_allowed_projects`: find the projects under `visionos.allowed_paths`, so clients can
+//! pick a `project_path` the allowlist accepts instead of guessing one.
+use std::{
+    collections::{HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lib::{
+        visionos::{is_allowed_path, is_path_pattern},
+        xcodebuild::PACKAGE_MANIFEST,
+    },
+    server::config::VisionOsConfig,
+    tools::visionos::build::ProjectKind,
+};
+
+const DEFAULT_MAX_DEPTH: u8 = 3;
+const MAX_DEPTH: u8 = 6;
+/// Projects returned at most; `truncated` is set when the scan stopped there.
+const MAX_PROJECTS: usize = 200;
+/// Build output and dependency checkouts, which hold copies rather than projects to build.
+const SKIPPED_DIRS: &[&str] = &["build", "DerivedData", "Pods", "Carthage", "node_modules"];
+
+/// Input for `list_allowed_projects`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListAllowedProjectsRequest {
+    /// Directory levels searched below each allowed path (default 3, at most 6).
+    #[serde(default)]
+    pub max_depth: Option<u8>,
+}
+
+/// A project found under an allowed path.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct AllowedProject {
+    /// Value to pass as `project_path`: the `.xcodeproj` or `.xcworkspace`, or the directory
+    /// holding `Package.swift`.
+    pub project_path: PathBuf,
+    pub kind: ProjectKind,
+    /// `visionos.allowed_paths` entry the project was found under.
+    pub allowed_path: PathBuf,
+}
+
+/// Response from `list_allowed_projects`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListAllowedProjectsResponse {
+    pub projects: Vec<AllowedProject>,
+    pub max_depth: u8,
+    /// More than the maximum number of projects were found; narrow the allowlist or the depth.
+    pub truncated: bool,
+    /// Allowed paths that do not exist or cannot be read, and glob entries that match nothing.
+    pub unreadable_paths: Vec<PathBuf>,
+}
+
+/// Walk every allowed path (glob entries are expanded first) without following symlinks or
+/// descending into project bundles and hidden directories. A project reachable through several
+/// entries is listed once, under the first.
+pub fn list_allowed_projects(
+    request: ListAllowedProjectsRequest,
+    config: &VisionOsConfig,
+) -> ListAllowedProjectsResponse {
+    let max_depth = request
+        .max_depth
+        .unwrap_or(DEFAULT_MAX_DEPTH)
+        .min(MAX_DEPTH);
+    find_projects(&config.allowed_paths, max_depth)
+}
+
+fn find_projects(allowed_paths: &[PathBuf], max_depth: u8) -> ListAllowedProjectsResponse {
+    let mut scan = Scan {
+        response: ListAllowedProjectsResponse {
+            projects: Vec::new(),
+            max_depth,
+            truncated: false,
+            unreadable_paths: Vec::new(),
+        },
+        seen: HashSet::new(),
+    };
+    'entries: for allowed_path in allowed_paths {
+        let roots = scan_roots(allowed_path);
+        if roots.is_empty() {
+            scan.response.unreadable_paths.push(allowed_path.clone());
+        }
+        for root in roots {
+            if !root.is_dir() {
+                scan.response.unreadable_paths.push(root);
+            } else if is_project_bundle(&root) {
+                let kind = ProjectKind::detect(&root);
+                scan.add(root, kind, allowed_path);
+            } else {
+                scan_root(&root, allowed_path, max_depth, &mut scan);
+            }
+            if scan.response.truncated {
+                break 'entries;
+            }
+        }
+    }
+    let mut response = scan.response;
+    response
+        .projects
+        .sort_by(|a, b| a.project_path.cmp(&b.project_path));
+    response
+}
+
+/// Projects found so far, keyed by canonical path so overlapping entries list each once.
+struct Scan {
+    response: ListAllowedProjectsResponse,
+    seen: HashSet<PathBuf>,
+}
+
+impl Scan {
+    /// Record a project; `false` once the maximum is reached.
+    fn add(&mut self, project_path: PathBuf, kind: ProjectKind, allowed_path: &Path) -> bool {
+        if is_path_pattern(allowed_path)
+            && !is_allowed_path(
+                &project_path,
+                std::slice::from_ref(&allowed_path.to_path_buf()),
+            )
+        {
+            return true;
+        }
+        let key = fs::canonicalize(&project_path).unwrap_or_else(|_| project_path.clone());
+        if self.seen.contains(&key) {
+            return true;
+        }
+        if self.response.projects.len() == MAX_PROJECTS {
+            self.response.truncated = true;
+            return false;
+        }
+        self.seen.insert(key);
+        self.response.projects.push(AllowedProject {
+            project_path,
+            kind,
+            allowed_path: allowed_path.to_path_buf(),
+        });
+        true
+    }
+}
+
+/// Directories to scan for `allowed_path`. A glob is expanded only up to its first `**`, whose
+/// matches are reached by the depth-limited scan instead and then checked against the whole
+/// pattern; matches inside project bundles are dropped.
+fn scan_roots(allowed_path: &Path) -> Vec<PathBuf> {
+    if !is_path_pattern(allowed_path) {
+        return vec![allowed_path.to_path_buf()];
+    }
+    let prefix: PathBuf = allowed_path
+        .components()
+        .take_while(|component| component.as_os_str() != "**")
+        .collect();
+    glob::glob(&prefix.to_string_lossy())
+        .map(|paths| {
+            paths
+                .flatten()
+                .filter(|path| path.is_dir() && !inside_project_bundle(path))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn inside_project_bundle(path: &Path) -> bool {
+    path.ancestors().skip(1).any(is_project_bundle)
+}
+
+fn scan_root(root: &Path, allowed_path: &Path, max_depth: u8, scan: &mut Scan) {
+    let mut pending = VecDeque::from([(root.to_path_buf(), 0u8)]);
+    while let Some((dir, depth)) = pending.pop_front() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            if depth == 0 {
+                scan.response.unreadable_paths.push(dir);
+            }
+            continue;
+        };
+        let mut entries: Vec<_> = entries.flatten().collect();
+        entries.sort_by_key(|entry| entry.file_name());
+        let has_xcode_project = entries.iter().any(|entry| is_project_bundle(&entry.path()));
+        for entry in entries {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let kind = if file_type.is_dir() && is_project_bundle(&path) {
+                Some(ProjectKind::detect(&path))
+            } else if file_type.is_file() && name == PACKAGE_MANIFEST && !has_xcode_project {
+                Some(ProjectKind::SwiftPackage)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                let project_path = if kind == ProjectKind::SwiftPackage {
+                    dir.clone()
+                } else {
+                    path
+                };
+                if !scan.add(project_path, kind, allowed_path) {
+                    return;
+                }
+            } else if file_type.is_dir()
+                && depth < max_depth
+                && !name.starts_with('.')
+                && !SKIPPED_DIRS.contains(&name.as_str())
+            {
+                pending.push_back((path, depth + 1));
+            }
+        }
+    }
+}
+
+fn is_project_bundle(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("xcodeproj") | Some("xcworkspace")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_projects_below_allowed_paths_up_to_max_depth() {
+        let workspace = tempfile::tempdir().expect("tempdir");
+        let root = workspace.path();
+        for dir in [
+            "Apps/Viewer/Viewer.xcodeproj",
+            "Apps/Viewer/Viewer.xcworkspace",
+            "Apps/Deep/a/b/c/Deep.xcodeproj",
+            "Apps/Viewer/build/Copy.xcodeproj",
+            ".hidden/Hidden.xcodeproj",
+            "Packages/Kit",
+            "Mixed",
+        ] {
+            fs::create_dir_all(root.join(dir)).expect("create dir");
+        }
+        fs::write(
+            root.join("Packages/Kit/Package.swift"),
+            "// swift-tools-version:5.9\n",
+        )
+        .expect("write manifest");
+        fs::create_dir_all(root.join("Mixed/Mixed.xcodeproj")).expect("create project");
+        fs::write(
+            root.join("Mixed/Package.swift"),
+            "// swift-tools-version:5.9\n",
+        )
+        .expect("write manifest");
+
+        let allowed_paths = vec![root.to_path_buf(), root.join("missing")];
+        let response = find_projects(&allowed_paths, DEFAULT_MAX_DEPTH);
+
+        let found: Vec<(String, ProjectKind)> = response
+            .projects
+            .iter()
+            .map(|project| {
+                (
+                    project
+                        .project_path
+                        .strip_prefix(root)
+                        .expect("under the root")
+                        .to_string_lossy()
+                        .into_owned(),
+                    project.kind,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "Apps/Viewer/Viewer.xcodeproj".to_string(),
+                    ProjectKind::XcodeProject
+                ),
+                (
+                    "Apps/Viewer/Viewer.xcworkspace".to_string(),
+                    ProjectKind::Workspace
+                ),
+                (
+                    "Mixed/Mixed.xcodeproj".to_string(),
+                    ProjectKind::XcodeProject
+                ),
+                ("Packages/Kit".to_string(), ProjectKind::SwiftPackage),
+            ]
+        );
+        assert_eq!(response.unreadable_paths, [root.join("missing")]);
+        assert!(!response.truncated);
+
+        let deep = find_projects(&allowed_paths, MAX_DEPTH);
+        assert!(deep
+            .projects
+            .iter()
+            .any(|project| project.project_path.ends_with("Deep.xcodeproj")));
+    }
+
+    #[test]
+    fn overlapping_and_glob_entries_list_each_project_once() {
+        let workspace = tempfile::tempdir().expect("tempdir");
+        let root = workspace.path();
+        for dir in [
+            "Apps/Viewer/Viewer.xcodeproj/project.xcworkspace",
+            "Apps/Editor/Editor.xcodeproj",
+        ] {
+            fs::create_dir_all(root.join(dir)).expect("create dir");
+        }
+
+        let allowed_paths = vec![
+            root.to_path_buf(),
+            root.join("Apps"),
+            root.join("**"),
+            root.join("Apps/Viewer/*"),
+            root.join("**/Editor"),
+            root.join("Missing/*"),
+        ];
+        let response = find_projects(&allowed_paths, DEFAULT_MAX_DEPTH);
+
+        let found: Vec<_> = response
+            .projects
+            .iter()
+            .map(|project| {
+                project
+                    .project_path
+                    .strip_prefix(root)
+                    .expect("under the root")
+                    .to_path_buf()
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                PathBuf::from("Apps/Editor/Editor.xcodeproj"),
+                PathBuf::from("Apps/Viewer/Viewer.xcodeproj"),
+            ]
+        );
+        assert!(response
+            .projects
+            .iter()
+            .all(|project| project.allowed_path == root));
+        assert_eq!(response.unreadable_paths, [root.join("Missing/*")]);
+
+        let editor_only = find_projects(&[root.join("**/Editor")], DEFAULT_MAX_DEPTH);
+        assert_eq!(editor_only.projects.len(), 1);
+        assert!(editor_only.projects[0]
+            .project_path
+            .ends_with("Editor.xcodeproj"));
+    }
+}
@@ -0,0 +1,341 @@
+//! Screenshots and screen recordings of a booted simulator via `simctl io`.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use chrono::Utc;
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::{
+    lib::{
+        errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+        fs as artifact_fs,
+    },
+    server::config::VisionOsConfig,
+    tools::visionos::artifacts::{
+        fetch_error_to_error_data, ArtifactFile, ArtifactKind, VisionOsArtifactStore,
+    },
+};
+
+use super::{build_error_data, load_devices, parse_udid, SIMULATOR_NOT_FOUND_ERROR};
+
+const BOOTED_STATE: &str = "Booted";
+const DEFAULT_RECORDING_SECS: u16 = 5;
+const MAX_RECORDING_SECS: u16 = 60;
+/// Time `recordVideo` gets to finalize the movie after SIGINT.
+const RECORDING_FINALIZE_TIMEOUT: Duration = Duration::from_secs(15);
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(30);
+
+const INVALID_MEDIA_REQUEST_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "invalid_request",
+    "Input validation failed",
+    "Use duration_secs between 1 and 60 for recordings.",
+);
+const SIMULATOR_NOT_BOOTED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "simulator_not_booted",
+    "The simulator must be booted to capture its screen",
+    "Boot it with boot_simulator, launch the app, and retry.",
+);
+const MEDIA_CAPTURE_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "media_capture_failed",
+    "xcrun simctl io failed to capture the simulator screen",
+    "Check that the simulator window is rendering and retry; see details.stderr.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(
+        &INVALID_MEDIA_REQUEST_ERROR,
+        false,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(
+        &SIMULATOR_NOT_BOOTED_ERROR,
+        false,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(&MEDIA_CAPTURE_FAILED_ERROR, true, SandboxState::NoViolation),
+];
+
+/// What `capture_simulator_media` records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    /// PNG of the current frame.
+    #[default]
+    Screenshot,
+    /// H.264 `.mov` of the next `duration_secs` seconds.
+    Video,
+}
+
+impl MediaKind {
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Screenshot => "screenshot.png",
+            Self::Video => "recording.mov",
+        }
+    }
+}
+
+/// Input for `capture_simulator_media`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CaptureSimulatorMediaRequest {
+    pub udid: String,
+    #[serde(default)]
+    pub kind: MediaKind,
+    /// Recording length for `video` (1-60, default 5); ignored for screenshots.
+    #[serde(default)]
+    pub duration_secs: Option<u16>,
+    /// Scheme of the app on screen, so `"latest"` lookups for that scheme find the capture.
+    #[serde(default)]
+    pub scheme: Option<String>,
+}
+
+/// Response from `capture_simulator_media`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CaptureSimulatorMediaResponse {
+    /// Job ID for `fetch_build_output` and `export_artifact`.
+    pub job_id: String,
+    pub status: &'static str,
+    pub kind: MediaKind,
+    pub udid: String,
+    pub path: String,
+    pub sha256: String,
+    pub size_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u16>,
+    pub download_ttl_seconds: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+    pub invocation: String,
+}
+
+/// Capture a screenshot or recording of a booted simulator and store it as a new job.
+pub async fn capture_simulator_media(
+    store: &VisionOsArtifactStore,
+    config: &VisionOsConfig,
+    request: CaptureSimulatorMediaRequest,
+) -> Result<CaptureSimulatorMediaResponse, ErrorData> {
+    let udid = parse_udid(&request.udid)?;
+    let duration_secs = match request.kind {
+        MediaKind::Screenshot => None,
+        MediaKind::Video => {
+            let secs = request.duration_secs.unwrap_or(DEFAULT_RECORDING_SECS);
+            if !(1..=MAX_RECORDING_SECS).contains(&secs) {
+                return Err(build_error_data(
+                    &INVALID_MEDIA_REQUEST_ERROR,
+                    json!({ "duration_secs": secs, "max": MAX_RECORDING_SECS }),
+                    SandboxState::NoViolation,
+                    false,
+                ));
+            }
+            Some(secs)
+        }
+    };
+
+    let (devices, _) = load_devices(config).await?;
+    let device = devices
+        .into_iter()
+        .find(|device| device.udid.eq_ignore_ascii_case(&udid))
+        .ok_or_else(|| {
+            build_error_data(
+                &SIMULATOR_NOT_FOUND_ERROR,
+                json!({ "udid": udid }),
+                SandboxState::NoViolation,
+                false,
+            )
+        })?;
+    if device.state != BOOTED_STATE {
+        return Err(build_error_data(
+            &SIMULATOR_NOT_BOOTED_ERROR,
+            json!({ "udid": device.udid, "state": device.state }),
+            SandboxState::NoViolation,
+            false,
+        ));
+    }
+
+    let job_id = Uuid::new_v4();
+    let job_dir = artifact_fs::ensure_job_dir(&store.root_dir(), &job_id)
+        .map_err(|err| capture_failed(json!({ "details": err.to_string() })))?;
+    let output = job_dir.join(request.kind.file_name());
+    let captured = match duration_secs {
+        None => capture_screenshot(config, &device.udid, &output).await,
+        Some(secs) => record_video(config, &device.udid, &output, secs).await,
+    };
+    let invocation = match captured.and_then(|invocation| {
+        if output.is_file() {
+            Ok(invocation)
+        } else {
+            Err(capture_failed(json!({
+                "invocation": invocation,
+                "details": "simctl exited without writing the capture",
+            })))
+        }
+    }) {
+        Ok(invocation) => invocation,
+        Err(err) => {
+            let _ = fs::remove_dir_all(&job_dir);
+            return Err(err);
+        }
+    };
+
+    let sha256 = artifact_fs::compute_sha256(&output)
+        .map_err(|err| capture_failed(json!({ "details": err.to_string() })))?;
+    let size_bytes = fs::metadata(&output)
+        .map(|metadata| metadata.len())
+        .map_err(|err| capture_failed(json!({ "details": err.to_string() })))?;
+    store
+        .record_success(
+            job_id,
+            ArtifactKind::Media,
+            request.scheme.unwrap_or_default(),
+            ArtifactFile {
+                path: output.clone(),
+                sha256: sha256.clone(),
+                cache_key: None,
+                ttl_secs: None,
+            },
+            invocation.clone(),
+            Utc::now(),
+        )
+        .await
+        .map_err(|err| capture_failed(json!({ "details": err.to_string() })))?;
+    let record = store
+        .fetch_record(&job_id)
+        .await
+        .map_err(fetch_error_to_error_data)?;
+    let download_url = store
+        .download_url(&record)
+        .await
+        .map_err(|err| capture_failed(json!({ "details": err.to_string() })))?;
+
+    Ok(CaptureSimulatorMediaResponse {
+        job_id: job_id.to_string(),
+        status: "succeeded",
+        kind: request.kind,
+        udid: device.udid,
+        path: output.to_string_lossy().to_string(),
+        sha256,
+        size_bytes,
+        duration_secs,
+        download_ttl_seconds: store.ttl_seconds_remaining(&record),
+        download_url,
+        invocation,
+    })
+}
+
+async fn capture_screenshot(
+    config: &VisionOsConfig,
+    udid: &str,
+    output: &Path,
+) -> Result<String, ErrorData> {
+    let (mut command, invocation) = simctl_io(
+        config,
+        udid,
+        &["screenshot", "--type=png"],
+        output.to_path_buf(),
+    );
+    let result = tokio::time::timeout(CAPTURE_TIMEOUT, command.output())
+        .await
+        .map_err(|_| {
+            capture_failed(json!({
+                "invocation": invocation,
+                "details": format!("timed out after {}s", CAPTURE_TIMEOUT.as_secs()),
+            }))
+        })?
+        .map_err(|err| {
+            capture_failed(json!({ "invocation": invocation, "details": err.to_string() }))
+        })?;
+    if !result.status.success() {
+        return Err(capture_failed(json!({
+            "invocation": invocation,
+            "exit_code": result.status.code(),
+            "stderr": String::from_utf8_lossy(&result.stderr),
+        })));
+    }
+    Ok(invocation)
+}
+
+/// `recordVideo` runs until interrupted, so stop it with SIGINT after `secs` and let it
+/// finalize the movie.
+async fn record_video(
+    config: &VisionOsConfig,
+    udid: &str,
+    output: &Path,
+    secs: u16,
+) -> Result<String, ErrorData> {
+    let (mut command, invocation) = simctl_io(
+        config,
+        udid,
+        &["recordVideo", "--codec=h264", "--force"],
+        output.to_path_buf(),
+    );
+    command.stdout(Stdio::null()).stderr(Stdio::piped());
+    let child = command.spawn().map_err(|err| {
+        capture_failed(json!({ "invocation": invocation, "details": err.to_string() }))
+    })?;
+
+    tokio::time::sleep(Duration::from_secs(secs.into())).await;
+    if let Some(pid) = child.id().and_then(|pid| libc::pid_t::try_from(pid).ok()) {
+        unsafe {
+            libc::kill(pid, libc::SIGINT);
+        }
+    }
+    let result = tokio::time::timeout(RECORDING_FINALIZE_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            capture_failed(json!({
+                "invocation": invocation,
+                "details": "recordVideo did not stop after SIGINT",
+            }))
+        })?
+        .map_err(|err| {
+            capture_failed(json!({ "invocation": invocation, "details": err.to_string() }))
+        })?;
+    if !result.status.success() {
+        return Err(capture_failed(json!({
+            "invocation": invocation,
+            "exit_code": result.status.code(),
+            "stderr": String::from_utf8_lossy(&result.stderr),
+        })));
+    }
+    Ok(invocation)
+}
+
+fn simctl_io(
+    config: &VisionOsConfig,
+    udid: &str,
+    args: &[&str],
+    output: PathBuf,
+) -> (Command, String) {
+    let mut command = Command::new(&config.xcrun_path);
+    command.kill_on_drop(true);
+    command.env("DEVELOPER_DIR", &config.xcode_path);
+    command.args(["simctl", "io", udid]);
+    command.args(args);
+    command.arg(&output);
+    let invocation = format!(
+        "DEVELOPER_DIR={} {} simctl io {udid} {} {}",
+        config.xcode_path.display(),
+        config.xcrun_path.display(),
+        args.join(" "),
+        output.display()
+    );
+    (command, invocation)
+}
+
+fn capture_failed(details: serde_json::Value) -> ErrorData {
+    build_error_data(
+        &MEDIA_CAPTURE_FAILED_ERROR,
+        details,
+        SandboxState::NoViolation,
+        true,
+    )
+}
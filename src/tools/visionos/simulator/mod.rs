@@ -0,0 +1,370 @@
+//! Apple Vision Pro simulator management backed by `xcrun simctl`.
+pub(crate) mod media;
+mod simctl;
+
+use std::path::Path;
+
+use rmcp::model::ErrorData;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    lib::errors::{ErrorCatalogEntry, SandboxState, ToolErrorDescriptor},
+    server::config::VisionOsConfig,
+};
+
+pub use media::{
+    capture_simulator_media, CaptureSimulatorMediaRequest, CaptureSimulatorMediaResponse, MediaKind,
+};
+pub(crate) use simctl::parse_visionos_runtimes;
+use simctl::{parse_visionos_devices, run_simctl, SimctlDevice};
+
+const INVALID_UDID_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "invalid_udid",
+    "Invalid simulator udid format",
+    "Provide a UUID-formatted udid returned by list_simulators.",
+);
+const SIMULATOR_NOT_FOUND_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "simulator_not_found",
+    "The specified visionOS simulator was not found",
+    "Run list_simulators and retry with one of the returned udid values.",
+);
+const SIMULATOR_UNAVAILABLE_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "simulator_unavailable",
+    "The specified simulator runtime is unavailable",
+    "Install the matching visionOS Simulator runtime via Xcode > Settings > Platforms.",
+);
+const SIMCTL_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "simctl_failed",
+    "xcrun simctl failed",
+    "Check visionos.xcrun_path and the Xcode environment, then retry.",
+);
+const SIMCTL_PARSE_FAILED_ERROR: ToolErrorDescriptor = ToolErrorDescriptor::new(
+    "simctl_parse_failed",
+    "Failed to parse simctl device list output",
+    "Verify `xcrun simctl list devices --json` output format and retry.",
+);
+
+pub(crate) const ERROR_CATALOG: &[ErrorCatalogEntry] = &[
+    ErrorCatalogEntry::new(&INVALID_UDID_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&SIMULATOR_NOT_FOUND_ERROR, false, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(
+        &SIMULATOR_UNAVAILABLE_ERROR,
+        false,
+        SandboxState::NoViolation,
+    ),
+    ErrorCatalogEntry::new(&SIMCTL_FAILED_ERROR, true, SandboxState::NoViolation),
+    ErrorCatalogEntry::new(&SIMCTL_PARSE_FAILED_ERROR, true, SandboxState::NoViolation),
+];
+
+/// Input for `list_simulators`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListSimulatorsRequest {
+    #[serde(default)]
+    pub include_unavailable: bool,
+}
+
+/// Input for `boot_simulator` and `shutdown_simulator`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SimulatorDeviceRequest {
+    pub udid: String,
+}
+
+/// Structured state of a visionOS simulator device.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct SimulatorDevice {
+    pub udid: String,
+    pub name: String,
+    pub state: String,
+    pub runtime: String,
+    pub is_available: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_type: Option<String>,
+}
+
+impl From<SimctlDevice> for SimulatorDevice {
+    fn from(device: SimctlDevice) -> Self {
+        Self {
+            udid: device.udid,
+            name: device.name,
+            state: device.state,
+            runtime: device.runtime,
+            is_available: device.is_available,
+            device_type: device.device_type,
+        }
+    }
+}
+
+/// Response from `list_simulators`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListSimulatorsResponse {
+    pub status: &'static str,
+    pub simulators: Vec<SimulatorDevice>,
+    pub invocation: String,
+}
+
+/// Response from `boot_simulator` and `shutdown_simulator`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SimulatorStateResponse {
+    pub status: &'static str,
+    pub udid: String,
+    pub name: String,
+    pub previous_state: String,
+    pub state: String,
+    pub changed: bool,
+    pub invocation: Option<String>,
+}
+
+/// Result of installing an app bundle on a simulator.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct InstallAppResponse {
+    pub status: &'static str,
+    pub udid: String,
+    pub app_path: String,
+    /// `true` when the simulator had to be booted first.
+    pub booted: bool,
+    pub invocation: String,
+}
+
+/// Enumerate visionOS simulators known to `simctl`.
+pub async fn list_simulators(
+    request: ListSimulatorsRequest,
+    config: &VisionOsConfig,
+) -> Result<ListSimulatorsResponse, ErrorData> {
+    let (devices, invocation) = load_devices(config).await?;
+    let simulators = devices
+        .into_iter()
+        .filter(|device| request.include_unavailable || device.is_available)
+        .map(SimulatorDevice::from)
+        .collect();
+
+    Ok(ListSimulatorsResponse {
+        status: "ok",
+        simulators,
+        invocation,
+    })
+}
+
+/// Boot a visionOS simulator by UDID (no-op when already booted).
+pub async fn boot_simulator(
+    request: SimulatorDeviceRequest,
+    config: &VisionOsConfig,
+) -> Result<SimulatorStateResponse, ErrorData> {
+    transition_simulator(request, config, "boot", "Booted").await
+}
+
+/// Shut down a visionOS simulator by UDID (no-op when already shut down).
+pub async fn shutdown_simulator(
+    request: SimulatorDeviceRequest,
+    config: &VisionOsConfig,
+) -> Result<SimulatorStateResponse, ErrorData> {
+    transition_simulator(request, config, "shutdown", "Shutdown").await
+}
+
+/// Install `app` on a simulator, booting it first when it is shut down.
+pub async fn install_app(
+    udid: &str,
+    app: &Path,
+    config: &VisionOsConfig,
+) -> Result<InstallAppResponse, ErrorData> {
+    let boot = transition_simulator(
+        SimulatorDeviceRequest {
+            udid: udid.to_string(),
+        },
+        config,
+        "boot",
+        "Booted",
+    )
+    .await?;
+
+    let app_path = app.to_string_lossy();
+    let result = run_simctl(
+        &config.xcrun_path,
+        &config.xcode_path,
+        &["install", &boot.udid, &app_path],
+    )
+    .await
+    .map_err(|err| {
+        build_error_data(
+            &SIMCTL_FAILED_ERROR,
+            json!({ "details": err.to_string() }),
+            SandboxState::NoViolation,
+            true,
+        )
+    })?;
+    if !result.output.status.success() {
+        return Err(simctl_failed(&result.invocation, &result.output));
+    }
+
+    Ok(InstallAppResponse {
+        status: "ok",
+        udid: boot.udid,
+        app_path: app_path.to_string(),
+        booted: boot.changed,
+        invocation: result.invocation,
+    })
+}
+
+async fn transition_simulator(
+    request: SimulatorDeviceRequest,
+    config: &VisionOsConfig,
+    subcommand: &str,
+    target_state: &str,
+) -> Result<SimulatorStateResponse, ErrorData> {
+    let udid = parse_udid(&request.udid)?;
+    let (devices, _) = load_devices(config).await?;
+    let device = devices
+        .into_iter()
+        .find(|device| device.udid.eq_ignore_ascii_case(&udid))
+        .ok_or_else(|| {
+            build_error_data(
+                &SIMULATOR_NOT_FOUND_ERROR,
+                json!({ "udid": udid }),
+                SandboxState::NoViolation,
+                false,
+            )
+        })?;
+
+    if device.state == target_state {
+        return Ok(SimulatorStateResponse {
+            status: "ok",
+            udid: device.udid,
+            name: device.name,
+            previous_state: device.state.clone(),
+            state: device.state,
+            changed: false,
+            invocation: None,
+        });
+    }
+    if !device.is_available {
+        return Err(build_error_data(
+            &SIMULATOR_UNAVAILABLE_ERROR,
+            json!({ "udid": device.udid, "runtime": device.runtime }),
+            SandboxState::NoViolation,
+            false,
+        ));
+    }
+
+    let result = run_simctl(
+        &config.xcrun_path,
+        &config.xcode_path,
+        &[subcommand, &device.udid],
+    )
+    .await
+    .map_err(|err| {
+        build_error_data(
+            &SIMCTL_FAILED_ERROR,
+            json!({ "details": err.to_string() }),
+            SandboxState::NoViolation,
+            true,
+        )
+    })?;
+    if !result.output.status.success() {
+        return Err(simctl_failed(&result.invocation, &result.output));
+    }
+
+    Ok(SimulatorStateResponse {
+        status: "ok",
+        udid: device.udid,
+        name: device.name,
+        previous_state: device.state,
+        state: target_state.to_string(),
+        changed: true,
+        invocation: Some(result.invocation),
+    })
+}
+
+async fn load_devices(config: &VisionOsConfig) -> Result<(Vec<SimctlDevice>, String), ErrorData> {
+    let result = run_simctl(
+        &config.xcrun_path,
+        &config.xcode_path,
+        &["list", "devices", "--json"],
+    )
+    .await
+    .map_err(|err| {
+        build_error_data(
+            &SIMCTL_FAILED_ERROR,
+            json!({ "details": err.to_string() }),
+            SandboxState::NoViolation,
+            true,
+        )
+    })?;
+    if !result.output.status.success() {
+        return Err(simctl_failed(&result.invocation, &result.output));
+    }
+
+    let devices = parse_visionos_devices(&result.output.stdout).map_err(|err| {
+        build_error_data(
+            &SIMCTL_PARSE_FAILED_ERROR,
+            json!({ "details": err.to_string(), "invocation": result.invocation }),
+            SandboxState::NoViolation,
+            true,
+        )
+    })?;
+    Ok((devices, result.invocation))
+}
+
+fn parse_udid(raw: &str) -> Result<String, ErrorData> {
+    Uuid::parse_str(raw.trim())
+        .map(|udid| udid.hyphenated().to_string().to_uppercase())
+        .map_err(|_| {
+            build_error_data(
+                &INVALID_UDID_ERROR,
+                json!({ "details": raw }),
+                SandboxState::NoViolation,
+                false,
+            )
+        })
+}
+
+fn simctl_failed(invocation: &str, output: &std::process::Output) -> ErrorData {
+    build_error_data(
+        &SIMCTL_FAILED_ERROR,
+        json!({
+            "invocation": invocation,
+            "exit_code": output.status.code(),
+            "stderr": String::from_utf8_lossy(&output.stderr)
+        }),
+        SandboxState::NoViolation,
+        true,
+    )
+}
+
+fn build_error_data(
+    desc: &'static ToolErrorDescriptor,
+    details: Value,
+    sandbox_state: SandboxState,
+    retryable: bool,
+) -> ErrorData {
+    desc.builder()
+        .details(details)
+        .sandbox_state(sandbox_state)
+        .retryable(retryable)
+        .build()
+        .expect("descriptor is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_udid_normalizes_to_uppercase() {
+        let udid = parse_udid(" f556d53f-412a-4778-af81-3449d52f5a7f ").expect("udid parses");
+        assert_eq!(udid, "F556D53F-412A-4778-AF81-3449D52F5A7F");
+    }
+
+    #[test]
+    fn parse_udid_rejects_device_names() {
+        let err = parse_udid("Apple Vision Pro").expect_err("name is not a udid");
+        assert_eq!(
+            err.data
+                .as_ref()
+                .and_then(|data| data.get("code"))
+                .and_then(Value::as_str),
+            Some("invalid_udid")
+        );
+    }
+}
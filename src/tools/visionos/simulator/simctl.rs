@@ -0,0 +1,203 @@
+use std::{path::Path, process::Output};
+
+use serde_json::Value;
+use tokio::process::Command;
+
+/// Runtime identifier fragments that identify visionOS simulator runtimes.
+const VISIONOS_RUNTIME_MARKERS: &[&str] = &["xros", "visionos"];
+
+/// Device entry parsed from `simctl list devices --json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimctlDevice {
+    pub udid: String,
+    pub name: String,
+    pub state: String,
+    pub runtime: String,
+    pub is_available: bool,
+    pub device_type: Option<String>,
+}
+
+/// Runtime entry parsed from `simctl list runtimes --json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimctlRuntime {
+    pub identifier: String,
+    pub version: String,
+    pub is_available: bool,
+}
+
+pub struct SimctlInvocationResult {
+    pub invocation: String,
+    pub output: Output,
+}
+
+/// Run `xcrun simctl <args>` with the configured developer directory.
+pub async fn run_simctl(
+    xcrun_path: &Path,
+    developer_dir: &Path,
+    args: &[&str],
+) -> std::io::Result<SimctlInvocationResult> {
+    let mut command = Command::new(xcrun_path);
+    command.kill_on_drop(true);
+    command.env("DEVELOPER_DIR", developer_dir);
+    command.arg("simctl");
+    command.args(args);
+
+    let invocation = format!(
+        "DEVELOPER_DIR={} {} simctl {}",
+        developer_dir.display(),
+        xcrun_path.display(),
+        args.join(" ")
+    );
+    let output = command.output().await?;
+    Ok(SimctlInvocationResult { invocation, output })
+}
+
+/// Parse `simctl list devices --json` output, keeping only visionOS runtimes.
+pub fn parse_visionos_devices(stdout: &[u8]) -> Result<Vec<SimctlDevice>, serde_json::Error> {
+    let parsed: Value = serde_json::from_slice(stdout)?;
+    let mut devices = Vec::new();
+    let Some(runtimes) = parsed.get("devices").and_then(Value::as_object) else {
+        return Ok(devices);
+    };
+
+    for (runtime, entries) in runtimes {
+        if !is_visionos_runtime(runtime) {
+            continue;
+        }
+        let Some(entries) = entries.as_array() else {
+            continue;
+        };
+        for entry in entries {
+            let (Some(udid), Some(name), Some(state)) = (
+                entry.get("udid").and_then(Value::as_str),
+                entry.get("name").and_then(Value::as_str),
+                entry.get("state").and_then(Value::as_str),
+            ) else {
+                continue;
+            };
+            devices.push(SimctlDevice {
+                udid: udid.to_string(),
+                name: name.to_string(),
+                state: state.to_string(),
+                runtime: runtime_display_name(runtime),
+                is_available: entry
+                    .get("isAvailable")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true),
+                device_type: entry
+                    .get("deviceTypeIdentifier")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            });
+        }
+    }
+
+    devices.sort_by(|a, b| a.runtime.cmp(&b.runtime).then(a.name.cmp(&b.name)));
+    Ok(devices)
+}
+
+/// Parse `simctl list runtimes --json` output, keeping only visionOS runtimes.
+pub fn parse_visionos_runtimes(stdout: &[u8]) -> Result<Vec<SimctlRuntime>, serde_json::Error> {
+    let parsed: Value = serde_json::from_slice(stdout)?;
+    let Some(entries) = parsed.get("runtimes").and_then(Value::as_array) else {
+        return Ok(Vec::new());
+    };
+    let mut runtimes: Vec<SimctlRuntime> = entries
+        .iter()
+        .filter_map(|entry| {
+            let identifier = entry.get("identifier").and_then(Value::as_str)?;
+            let version = entry.get("version").and_then(Value::as_str)?;
+            is_visionos_runtime(identifier).then(|| SimctlRuntime {
+                identifier: identifier.to_string(),
+                version: version.to_string(),
+                is_available: entry
+                    .get("isAvailable")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true),
+            })
+        })
+        .collect();
+    runtimes.sort_by(|a, b| a.version.cmp(&b.version));
+    Ok(runtimes)
+}
+
+fn is_visionos_runtime(runtime: &str) -> bool {
+    let lower = runtime.to_lowercase();
+    VISIONOS_RUNTIME_MARKERS
+        .iter()
+        .any(|marker| lower.contains(marker))
+}
+
+/// Convert `com.apple.CoreSimulator.SimRuntime.xrOS-26-2` into `visionOS 26.2`.
+fn runtime_display_name(runtime: &str) -> String {
+    let tail = runtime.rsplit('.').next().unwrap_or(runtime);
+    let Some((_, version)) = tail.split_once('-') else {
+        return runtime.to_string();
+    };
+    format!("visionOS {}", version.replace('-', "."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+  "devices": {
+    "com.apple.CoreSimulator.SimRuntime.iOS-18-0": [
+      { "udid": "11111111-1111-1111-1111-111111111111", "name": "iPhone 16", "state": "Shutdown", "isAvailable": true }
+    ],
+    "com.apple.CoreSimulator.SimRuntime.xrOS-26-2": [
+      { "udid": "F556D53F-412A-4778-AF81-3449D52F5A7F", "name": "Apple Vision Pro", "state": "Booted", "isAvailable": true,
+        "deviceTypeIdentifier": "com.apple.CoreSimulator.SimDeviceType.Apple-Vision-Pro" }
+    ],
+    "com.apple.CoreSimulator.SimRuntime.xrOS-2-5": [
+      { "udid": "5BB47C97-BDBA-4DA7-BE30-F659C265F896", "name": "Apple Vision Pro", "state": "Shutdown", "isAvailable": false }
+    ]
+  }
+}"#;
+
+    #[test]
+    fn parse_visionos_devices_filters_non_visionos_runtimes() {
+        let devices = parse_visionos_devices(SAMPLE.as_bytes()).expect("sample should parse");
+        assert_eq!(devices.len(), 2);
+        assert!(devices
+            .iter()
+            .all(|device| device.name == "Apple Vision Pro"));
+    }
+
+    #[test]
+    fn parse_visionos_runtimes_keeps_visionos_entries() {
+        let stdout = r#"{
+  "runtimes": [
+    { "identifier": "com.apple.CoreSimulator.SimRuntime.iOS-18-0", "version": "18.0", "isAvailable": true },
+    { "identifier": "com.apple.CoreSimulator.SimRuntime.xrOS-26-2", "version": "26.2", "isAvailable": true },
+    { "identifier": "com.apple.CoreSimulator.SimRuntime.xrOS-2-5", "version": "2.5", "isAvailable": false }
+  ]
+}"#;
+        let runtimes = parse_visionos_runtimes(stdout.as_bytes()).expect("sample should parse");
+        assert_eq!(runtimes.len(), 2);
+        assert_eq!(runtimes[0].version, "2.5");
+        assert!(!runtimes[0].is_available);
+        assert_eq!(
+            runtimes[1].identifier,
+            "com.apple.CoreSimulator.SimRuntime.xrOS-26-2"
+        );
+        assert!(runtimes[1].is_available);
+    }
+
+    #[test]
+    fn parse_visionos_devices_reports_runtime_and_availability() {
+        let devices = parse_visionos_devices(SAMPLE.as_bytes()).expect("sample should parse");
+        let booted = devices
+            .iter()
+            .find(|device| device.state == "Booted")
+            .expect("booted device should be present");
+        assert_eq!(booted.runtime, "visionOS 26.2");
+        assert!(booted.is_available);
+        let legacy = devices
+            .iter()
+            .find(|device| device.runtime == "visionOS 2.5")
+            .expect("legacy runtime device should be present");
+        assert!(!legacy.is_available);
+    }
+}
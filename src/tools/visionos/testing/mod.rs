@@ -0,0 +1,180 @@
+//! visionOS test tool (`xcodebuild test`) with Markdown summary resources.
+mod summary;
+
+use std::time::Instant;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    lib::{
+        errors::VisionOsBuildError, visionos as visionos_helpers, xcodebuild as xcodebuild_helpers,
+    },
+    server::config::VisionOsConfig,
+};
+
+use super::{
+    build::{
+        executor::{build_timeout, cached_derived_data_path, ephemeral_derived_data_path},
+        JobMonitor,
+    },
+    resources::PublishedResource,
+    VisionOsBuildRequest,
+};
+
+pub use summary::{
+    parse_test_output, render_markdown, FailedTest, TestCaseOutcome, TestRunSummary, TestTiming,
+};
+
+pub const TEST_TOOL_ID: &str = "test_visionos_app";
+
+const LOG_EXCERPT_LIMIT: usize = 5_000;
+const SUMMARY_MIME_TYPE: &str = "text/markdown";
+
+/// Response from `test_visionos_app`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TestVisionOsAppResponse {
+    pub job_id: String,
+    pub status: &'static str,
+    pub summary: TestRunSummary,
+    pub summary_uri: String,
+    pub log_excerpt: String,
+    pub duration_ms: u128,
+    #[serde(skip)]
+    pub summary_markdown: String,
+}
+
+impl TestVisionOsAppResponse {
+    /// Resource carrying the Markdown summary at `summary_uri`.
+    pub fn summary_resource(&self, scheme: &str) -> PublishedResource {
+        PublishedResource {
+            uri: self.summary_uri.clone(),
+            name: format!("test-summary-{}", self.job_id),
+            description: format!("Test summary for scheme {scheme} ({})", self.status),
+            mime_type: SUMMARY_MIME_TYPE.to_string(),
+            text: self.summary_markdown.clone(),
+        }
+    }
+}
+
+/// Resource URI of the Markdown summary for a test job.
+pub fn summary_resource_uri(job_id: Uuid) -> String {
+    format!("seiro://visionos/test-summaries/{job_id}")
+}
+
+/// Run `xcodebuild test` and summarize the results.
+///
+/// Test failures produce a `failed` response; only runs without any parsed
+/// test case and a non-zero exit are reported as errors.
+pub async fn run_tests(
+    request: &VisionOsBuildRequest,
+    config: &VisionOsConfig,
+    monitor: &JobMonitor,
+    job_id: Uuid,
+) -> Result<TestVisionOsAppResponse, VisionOsBuildError> {
+    let staging = tempfile::tempdir().map_err(|err| VisionOsBuildError::ArtifactFailure {
+        message: format!("Failed to create test staging directory: {err}"),
+    })?;
+
+    let derived_data_path = cached_derived_data_path(config, request)
+        .or_else(|| ephemeral_derived_data_path(config, staging.path()));
+    let signing_settings = request.signing_build_settings();
+    let command = xcodebuild_helpers::build_visionos_xcodebuild_command(
+        xcodebuild_helpers::VisionOsXcodebuildCommandConfig {
+            xcodebuild_path: &config.xcodebuild_path,
+            xcode_path: &config.xcode_path,
+            staging_dir: staging.path(),
+            result_bundle_path: None,
+            derived_data_path: derived_data_path.as_deref(),
+            compilation_cache: config.compilation_cache.as_ref(),
+            locale: &config.locale,
+        },
+        xcodebuild_helpers::VisionOsXcodebuildRequest {
+            project_path: &request.project_path,
+            workspace: request.workspace.as_deref(),
+            scheme: &request.scheme,
+            configuration: request.configuration().as_str(),
+            destination: &request.destination,
+            clean: request.clean,
+            action: xcodebuild_helpers::XcodebuildAction::Test,
+            extra_args: &request.extra_args,
+            build_settings: &signing_settings,
+            env_overrides: &request.env_overrides,
+        },
+    );
+
+    info!(
+        target: "rmcp_sample::visionos",
+        scheme = %request.scheme,
+        platform = request.platform.as_str(),
+        destination = %request.destination,
+        "Starting visionOS test run"
+    );
+
+    let timeout_duration = build_timeout(config, request);
+    let start = Instant::now();
+    let output = monitor
+        .run_with_deadline(
+            timeout_duration,
+            config.timeout_warning_percent,
+            xcodebuild_helpers::output_with_lines(command, |line| {
+                if let Some(phase) = xcodebuild_helpers::build_phase(line) {
+                    monitor.set_phase(phase);
+                }
+            }),
+        )
+        .await
+        .map_err(|deadline| VisionOsBuildError::Timeout {
+            duration_secs: deadline.as_secs(),
+        })?
+        .map_err(|err| VisionOsBuildError::CommandFailed {
+            exit_code: None,
+            message: err.to_string(),
+            diagnostics: Vec::new(),
+            log_errors: Vec::new(),
+        })?;
+
+    let log_excerpt = visionos_helpers::collect_formatted_log_excerpt(
+        &output.stdout,
+        &output.stderr,
+        request.log_format,
+        LOG_EXCERPT_LIMIT,
+    );
+    let mut log = String::from_utf8_lossy(&output.stdout).into_owned();
+    log.push('\n');
+    log.push_str(&String::from_utf8_lossy(&output.stderr));
+    let summary = parse_test_output(&log);
+
+    if !output.status.success() && summary.total == 0 {
+        return Err(VisionOsBuildError::CommandFailed {
+            exit_code: output.status.code(),
+            message: log_excerpt,
+            diagnostics: Vec::new(),
+            log_errors: xcodebuild_helpers::collect_log_errors(
+                &output.stdout,
+                &output.stderr,
+                xcodebuild_helpers::MAX_LOG_ERRORS,
+            ),
+        });
+    }
+
+    let status = if output.status.success() && summary.failed == 0 {
+        "passed"
+    } else {
+        "failed"
+    };
+    let job_id_text = job_id.to_string();
+    let summary_markdown = render_markdown(&request.scheme, &job_id_text, status, &summary);
+
+    Ok(TestVisionOsAppResponse {
+        job_id: job_id_text,
+        status,
+        summary,
+        summary_uri: summary_resource_uri(job_id),
+        log_excerpt,
+        duration_ms: start.elapsed().as_millis(),
+        summary_markdown,
+    })
+}
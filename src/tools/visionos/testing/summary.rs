@@ -0,0 +1,255 @@
+//! Parse `xcodebuild test` output and render a compact Markdown report.
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Number of slowest tests listed in the summary.
+const SLOWEST_LIMIT: usize = 5;
+
+/// Outcome of a single test case.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestCaseOutcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// A failed test case with its first reported assertion message.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct FailedTest {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/// Duration of a single test case.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct TestTiming {
+    pub name: String,
+    pub duration_secs: f64,
+}
+
+/// Totals, failures, and slowest tests extracted from an `xcodebuild test` log.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct TestRunSummary {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub duration_secs: f64,
+    pub failures: Vec<FailedTest>,
+    pub slowest: Vec<TestTiming>,
+}
+
+struct ParsedCase {
+    name: String,
+    outcome: TestCaseOutcome,
+    duration_secs: f64,
+}
+
+/// Parse XCTest case lines (`Test Case '-[Suite test]' passed (0.001 seconds).`).
+pub fn parse_test_output(log: &str) -> TestRunSummary {
+    let mut cases: Vec<ParsedCase> = Vec::new();
+    let mut messages: BTreeMap<String, (String, Option<String>)> = BTreeMap::new();
+
+    for line in log.lines() {
+        let line = line.trim();
+        if let Some(case) = parse_case_line(line) {
+            match cases.iter_mut().find(|existing| existing.name == case.name) {
+                Some(existing) => *existing = case,
+                None => cases.push(case),
+            }
+        } else if let Some((name, message, location)) = parse_failure_line(line) {
+            messages.entry(name).or_insert((message, location));
+        }
+    }
+
+    let mut summary = TestRunSummary {
+        total: cases.len(),
+        ..TestRunSummary::default()
+    };
+    for case in &cases {
+        summary.duration_secs += case.duration_secs;
+        match case.outcome {
+            TestCaseOutcome::Passed => summary.passed += 1,
+            TestCaseOutcome::Skipped => summary.skipped += 1,
+            TestCaseOutcome::Failed => {
+                summary.failed += 1;
+                let (message, location) = messages
+                    .get(&case.name)
+                    .cloned()
+                    .map_or((None, None), |(message, location)| {
+                        (Some(message), location)
+                    });
+                summary.failures.push(FailedTest {
+                    name: case.name.clone(),
+                    message,
+                    location,
+                });
+            }
+        }
+    }
+
+    let mut timings: Vec<TestTiming> = cases
+        .iter()
+        .filter(|case| case.outcome != TestCaseOutcome::Skipped)
+        .map(|case| TestTiming {
+            name: case.name.clone(),
+            duration_secs: case.duration_secs,
+        })
+        .collect();
+    timings.sort_by(|a, b| {
+        b.duration_secs
+            .total_cmp(&a.duration_secs)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    timings.truncate(SLOWEST_LIMIT);
+    summary.slowest = timings;
+    summary
+}
+
+/// Render the summary as Markdown suitable for chat clients.
+pub fn render_markdown(
+    title: &str,
+    job_id: &str,
+    status: &str,
+    summary: &TestRunSummary,
+) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Test summary: {title}");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "- Job: `{job_id}`");
+    let _ = writeln!(
+        out,
+        "- Result: **{status}** ({} total, {} passed, {} failed, {} skipped)",
+        summary.total, summary.passed, summary.failed, summary.skipped
+    );
+    let _ = writeln!(out, "- Test time: {:.3}s", summary.duration_secs);
+
+    if !summary.failures.is_empty() {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "## Failed tests");
+        let _ = writeln!(out);
+        for failure in &summary.failures {
+            let _ = write!(out, "- `{}`", failure.name);
+            if let Some(message) = &failure.message {
+                let _ = write!(out, ": {message}");
+            }
+            if let Some(location) = &failure.location {
+                let _ = write!(out, " (`{location}`)");
+            }
+            let _ = writeln!(out);
+        }
+    }
+
+    if !summary.slowest.is_empty() {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "## Slowest tests");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "| Test | Duration |");
+        let _ = writeln!(out, "| --- | --- |");
+        for timing in &summary.slowest {
+            let _ = writeln!(out, "| `{}` | {:.3}s |", timing.name, timing.duration_secs);
+        }
+    }
+
+    out
+}
+
+fn parse_case_line(line: &str) -> Option<ParsedCase> {
+    let rest = line
+        .strip_prefix("Test Case '")
+        .or_else(|| line.strip_prefix("Test case '"))?;
+    let (raw_name, tail) = rest.split_once('\'')?;
+    let tail = tail.trim_start();
+    let outcome = if tail.starts_with("passed") {
+        TestCaseOutcome::Passed
+    } else if tail.starts_with("failed") {
+        TestCaseOutcome::Failed
+    } else if tail.starts_with("skipped") {
+        TestCaseOutcome::Skipped
+    } else {
+        return None;
+    };
+    let duration_secs = tail
+        .rsplit_once('(')
+        .and_then(|(_, seconds)| seconds.split_whitespace().next())
+        .and_then(|seconds| seconds.parse::<f64>().ok())
+        .unwrap_or(0.0);
+
+    Some(ParsedCase {
+        name: normalize_test_name(raw_name),
+        outcome,
+        duration_secs,
+    })
+}
+
+/// Parse `<file>:<line>: error: -[Suite test] : <message>`.
+fn parse_failure_line(line: &str) -> Option<(String, String, Option<String>)> {
+    let (location, rest) = line.split_once(": error: ")?;
+    let rest = rest.strip_prefix("-[")?;
+    let (raw_name, message) = rest.split_once("] : ")?;
+    let location = (!location.is_empty()).then(|| location.to_string());
+    Some((
+        normalize_test_name(&format!("-[{raw_name}]")),
+        message.trim().to_string(),
+        location,
+    ))
+}
+
+/// Convert `-[Suite test]` to `Suite.test`; other formats are kept as-is.
+fn normalize_test_name(raw: &str) -> String {
+    raw.strip_prefix("-[")
+        .and_then(|inner| inner.strip_suffix(']'))
+        .and_then(|inner| inner.split_once(' '))
+        .map(|(suite, test)| format!("{suite}.{test}"))
+        .unwrap_or_else(|| raw.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_LOG: &str = r#"Test Suite 'VisionAppTests' started at 2026-01-01 10:00:00.000.
+Test Case '-[VisionAppTests.VisionAppTests testLaunch]' started.
+Test Case '-[VisionAppTests.VisionAppTests testLaunch]' passed (0.250 seconds).
+Test Case '-[VisionAppTests.VisionAppTests testGesture]' started.
+/tmp/VisionApp/VisionAppTests/VisionAppTests.swift:42: error: -[VisionAppTests.VisionAppTests testGesture] : XCTAssertEqual failed: ("1") is not equal to ("2")
+Test Case '-[VisionAppTests.VisionAppTests testGesture]' failed (0.012 seconds).
+Test Case '-[VisionAppTests.VisionAppTests testImmersive]' skipped (0.001 seconds).
+Test case 'VisionAppTests.testScene()' passed on 'Apple Vision Pro' (1.500 seconds)
+"#;
+
+    #[test]
+    fn parse_test_output_counts_outcomes_and_failure_messages() {
+        let summary = parse_test_output(SAMPLE_LOG);
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.passed, 2);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(
+            summary.failures,
+            vec![FailedTest {
+                name: "VisionAppTests.VisionAppTests.testGesture".into(),
+                message: Some(r#"XCTAssertEqual failed: ("1") is not equal to ("2")"#.into()),
+                location: Some("/tmp/VisionApp/VisionAppTests/VisionAppTests.swift:42".into()),
+            }]
+        );
+        assert_eq!(summary.slowest[0].name, "VisionAppTests.testScene()");
+        assert_eq!(summary.slowest.len(), 3);
+    }
+
+    #[test]
+    fn render_markdown_lists_failures_and_slowest_tests() {
+        let summary = parse_test_output(SAMPLE_LOG);
+        let markdown = render_markdown("VisionApp", "job-1", "failed", &summary);
+        assert!(markdown.starts_with("# Test summary: VisionApp\n"));
+        assert!(markdown.contains("**failed** (4 total, 2 passed, 1 failed, 1 skipped)"));
+        assert!(markdown.contains("## Failed tests"));
+        assert!(markdown.contains("| `VisionAppTests.testScene()` | 1.500s |"));
+    }
+}
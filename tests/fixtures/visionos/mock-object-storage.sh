@@ -0,0 +1,31 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+echo "[mock-object-storage] invoked with args: $*" >&2
+
+case "${1:-} ${2:-}" in
+  "s3 cp"|"storage cp")
+    shift 2
+    if [[ "${1:-}" == "--only-show-errors" ]]; then
+      shift
+    fi
+    if [[ ! -f "${1:-}" ]]; then
+      echo "[mock-object-storage] source not found: ${1:-}" >&2
+      exit 1
+    fi
+    echo "upload: ${1} to ${2:-}"
+    ;;
+  "s3 presign")
+    uri="${3:-}"
+    expires="${5:-3600}"
+    echo "https://mock-bucket.s3.amazonaws.com/${uri#s3://*/}?X-Amz-Expires=${expires}&X-Amz-Signature=mock"
+    ;;
+  "storage sign-url")
+    uri="${3:-}"
+    echo "https://storage.googleapis.com/${uri#gs://}?X-Goog-Signature=mock"
+    ;;
+  *)
+    echo "[mock-object-storage] unsupported command: $*" >&2
+    exit 64
+    ;;
+esac
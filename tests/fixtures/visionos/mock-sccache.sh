@@ -0,0 +1,17 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+# Stands in for both `sccache` and the compiler wrapper. Counters live in MOCK_SCCACHE_STATE.
+STATE="${MOCK_SCCACHE_STATE:?MOCK_SCCACHE_STATE is not set}"
+HITS="$(cat "${STATE}/hits" 2>/dev/null || echo 0)"
+MISSES="$(cat "${STATE}/misses" 2>/dev/null || echo 0)"
+
+if [[ "${1:-}" == "--show-stats" ]]; then
+  cat <<JSON
+{"stats": {"cache_hits": {"counts": {"C/C++": ${HITS}}}, "cache_misses": {"counts": {"C/C++": ${MISSES}}}}}
+JSON
+  exit 0
+fi
+
+echo "[mock-sccache] compiling: $*" >&2
+echo $((HITS + 1)) > "${STATE}/hits"
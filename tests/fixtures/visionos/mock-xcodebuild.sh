@@ -29,7 +29,9 @@ JSON
 {
   "project": {
     "name": "VisionApp",
-    "schemes": ["VisionApp", "VisionAppTests"]
+    "schemes": ["VisionApp", "VisionAppTests"],
+    "targets": ["VisionApp", "VisionAppTests"],
+    "configurations": ["Debug", "Release"]
   }
 }
 JSON
@@ -38,6 +40,99 @@ JSON
   esac
 fi
 
+if [[ "${1:-}" == "-version" ]]; then
+  case "${DEVELOPER_DIR:-}" in
+    *Missing*)
+      echo "xcode-select: error: tool 'xcodebuild' requires Xcode, but active developer directory '${DEVELOPER_DIR}' is not a developer tool directory" >&2
+      exit 1
+      ;;
+    *Xcode-beta*)
+      printf 'Xcode 26.3\nBuild version 17D5044a\n'
+      ;;
+    *)
+      printf 'Xcode 26.2\nBuild version 17C52\n'
+      ;;
+  esac
+  exit 0
+fi
+
+if [[ "${1:-}" == "-showdestinations" ]]; then
+  cat <<'TEXT'
+
+	Available destinations for the "VisionApp" scheme:
+		{ platform:visionOS Simulator, arch:arm64, id:F556D53F-412A-4778-AF81-3449D52F5A7F, OS:26.2, name:Apple Vision Pro }
+		{ platform:visionOS, id:dvtdevice-DVTiOSDevicePlaceholder-xros:placeholder, name:Any visionOS Device }
+
+	Ineligible destinations for the "VisionApp" scheme:
+		{ platform:iOS, id:dvtdevice-DVTiOSDevicePlaceholder-iphoneos:placeholder, name:Any iOS Device, error:iOS 26.2 is not installed. To use with Xcode, first download and install the platform }
+TEXT
+  exit 0
+fi
+
+if [[ "${1:-}" == "-showBuildSettings" ]]; then
+  if [[ "${MOCK_XCODEBUILD_BEHAVIOR:-success}" == "fail" ]]; then
+    echo "xcodebuild: error: The project does not contain a scheme named \"VisionApp\"." >&2
+    exit 65
+  fi
+  cat <<'JSON'
+[
+  {
+    "action": "build",
+    "target": "VisionApp",
+    "buildSettings": {
+      "PRODUCT_NAME": "VisionApp",
+      "PRODUCT_BUNDLE_IDENTIFIER": "com.example.VisionApp",
+      "XROS_DEPLOYMENT_TARGET": "2.0",
+      "CODE_SIGN_STYLE": "Automatic",
+      "DEVELOPMENT_TEAM": "ABCDE12345",
+      "OTHER_SWIFT_FLAGS": "-DDEBUG",
+      "HOME": "/Users/example"
+    }
+  }
+]
+JSON
+  exit 0
+fi
+
+for arg in "$@"; do
+  if [[ "${arg}" == "-resolvePackageDependencies" ]]; then
+    if [[ "${MOCK_XCODEBUILD_BEHAVIOR:-success}" == "fail" ]]; then
+      echo "xcodebuild: error: Could not resolve package dependencies:" >&2
+      echo "  failed downloading 'https://github.com/example/missing.git'" >&2
+      exit 74
+    fi
+    echo "Resolve Package Graph"
+    echo "Resolved source packages:"
+    echo "  swift-collections: https://github.com/apple/swift-collections.git @ 1.1.4"
+    exit 0
+  fi
+done
+
+if [[ "${1:-}" == "-exportArchive" ]]; then
+  EXPORT_PATH=""
+  OPTIONS_PLIST=""
+  prev=""
+  for arg in "$@"; do
+    if [[ "${prev}" == "-exportPath" ]]; then
+      EXPORT_PATH="${arg}"
+    fi
+    if [[ "${prev}" == "-exportOptionsPlist" ]]; then
+      OPTIONS_PLIST="${arg}"
+    fi
+    prev="${arg}"
+  done
+  if [[ "${MOCK_XCODEBUILD_BEHAVIOR:-success}" == "fail" ]]; then
+    echo "error: exportArchive: No profiles for 'com.example.VisionApp' were found" >&2
+    echo "** EXPORT FAILED **" >&2
+    exit 70
+  fi
+  mkdir -p "${EXPORT_PATH}"
+  printf "dummy ipa" > "${EXPORT_PATH}/VisionApp.ipa"
+  cp "${OPTIONS_PLIST}" "${EXPORT_PATH}/ExportOptions.plist"
+  echo "** EXPORT SUCCEEDED **"
+  exit 0
+fi
+
 ARTIFACT_DIR="${VISIONOS_BUILD_ARTIFACT_DIR:-}"
 if [[ -z "${ARTIFACT_DIR}" ]]; then
   echo "[mock-xcodebuild] VISIONOS_BUILD_ARTIFACT_DIR is not set" >&2
@@ -46,9 +141,96 @@ fi
 
 mkdir -p "${ARTIFACT_DIR}"
 
+RESULT_BUNDLE_PATH=""
+DERIVED_DATA_PATH=""
+COMPILER=""
+prev=""
+for arg in "$@"; do
+  if [[ "${arg}" == CC=* ]]; then
+    COMPILER="${arg#CC=}"
+  fi
+  if [[ "${prev}" == "-resultBundlePath" ]]; then
+    RESULT_BUNDLE_PATH="${arg}"
+  fi
+  if [[ "${prev}" == "-derivedDataPath" ]]; then
+    DERIVED_DATA_PATH="${arg}"
+  fi
+  prev="${arg}"
+done
+
+# `clean` without another action deletes the build products and index data.
+if [[ " $* " == *" clean "* && " $* " != *" build "* && " $* " != *" test "* && " $* " != *" archive "* ]]; then
+  if [[ -n "${DERIVED_DATA_PATH}" ]]; then
+    rm -rf "${DERIVED_DATA_PATH}/Build"
+  fi
+  echo "** CLEAN SUCCEEDED **"
+  exit 0
+fi
+
+# `simulator_boot_flaky` fails the first run of a job with a simulator boot error, and
+# `simulator_boot_fail` fails every run. The marker sits in the job directory, which retries keep.
+if [[ "${MOCK_XCODEBUILD_BEHAVIOR:-success}" == simulator_boot_* ]]; then
+  BOOT_MARKER="$(dirname "${ARTIFACT_DIR}")/mock-simulator-boot-failed"
+  if [[ "${MOCK_XCODEBUILD_BEHAVIOR}" == "simulator_boot_fail" || ! -e "${BOOT_MARKER}" ]]; then
+    touch "${BOOT_MARKER}"
+    echo "xcodebuild: error: Unable to boot the Simulator." >&2
+    echo "** BUILD FAILED **"
+    exit 70
+  fi
+fi
+
+# Record each run so tests can check that DerivedData is reused between jobs.
+if [[ -n "${DERIVED_DATA_PATH}" ]]; then
+  mkdir -p "${DERIVED_DATA_PATH}/Build/Products"
+  echo "build" >> "${DERIVED_DATA_PATH}/mock-builds.log"
+  printf "intermediate build products" > "${DERIVED_DATA_PATH}/Build/Products/VisionApp.o"
+fi
+
+# Compile through the configured compiler wrapper, as Xcode would for C sources.
+if [[ -n "${COMPILER}" ]]; then
+  "${COMPILER}" -c VisionApp.c -o VisionApp.o
+fi
+
+# Write the issues JSON that mock-xcrun.sh returns for `xcresulttool get`.
+write_result_bundle() {
+  if [[ -z "${RESULT_BUNDLE_PATH}" ]]; then
+    return
+  fi
+  mkdir -p "${RESULT_BUNDLE_PATH}"
+  cat > "${RESULT_BUNDLE_PATH}/mock-xcresulttool.json"
+}
+
+for arg in "$@"; do
+  if [[ "${arg}" == "test" ]]; then
+    cat <<'EOF'
+Test Suite 'VisionAppTests' started at 2026-01-01 10:00:00.000.
+Test Case '-[VisionAppTests.VisionAppTests testLaunch]' started.
+Test Case '-[VisionAppTests.VisionAppTests testLaunch]' passed (0.250 seconds).
+Test Case '-[VisionAppTests.VisionAppTests testScene]' started.
+Test Case '-[VisionAppTests.VisionAppTests testScene]' passed (0.040 seconds).
+EOF
+    case "${MOCK_XCODEBUILD_BEHAVIOR:-success}" in
+      test_failure)
+        cat <<'EOF'
+Test Case '-[VisionAppTests.VisionAppTests testGesture]' started.
+/tmp/VisionApp/VisionAppTests/VisionAppTests.swift:42: error: -[VisionAppTests.VisionAppTests testGesture] : XCTAssertEqual failed: ("1") is not equal to ("2")
+Test Case '-[VisionAppTests.VisionAppTests testGesture]' failed (0.012 seconds).
+** TEST FAILED **
+EOF
+        exit 65
+        ;;
+      *)
+        echo "** TEST SUCCEEDED **"
+        exit 0
+        ;;
+    esac
+  fi
+done
+
 case "${MOCK_XCODEBUILD_BEHAVIOR:-success}" in
   sleep)
     # Simulate a long-running build that should hit the MCP timeout quickly.
+    echo "CompileSwift normal arm64 /tmp/VisionApp/ContentView.swift"
     sleep 2
     ;;
   ambiguous_destination)
@@ -72,14 +254,62 @@ EOF
     exit 70
     ;;
   fail)
+    write_result_bundle <<'JSON'
+{
+  "issues": {
+    "errorSummaries": { "_values": [
+      {
+        "issueType": { "_value": "Swift Compiler Error" },
+        "message": { "_value": "cannot find 'immersiveSpace' in scope" },
+        "documentLocationInMetadata": { "url": { "_value": "file:///tmp/VisionApp/ContentView.swift#EndingLineNumber=11&StartingColumnNumber=8&StartingLineNumber=11" } }
+      },
+      {
+        "issueType": { "_value": "Code Signing Error" },
+        "message": { "_value": "No signing certificate \"Apple Development\" found" }
+      }
+    ] }
+  }
+}
+JSON
+    echo "/tmp/VisionApp/ContentView.swift:11:8: error: cannot find 'immersiveSpace' in scope"
     echo "[mock-xcodebuild] simulated failure" >&2
     exit 65
     ;;
   *)
+    write_result_bundle <<'JSON'
+{
+  "issues": {
+    "warningSummaries": { "_values": [
+      {
+        "issueType": { "_value": "Deprecation" },
+        "message": { "_value": "'onChange(of:perform:)' was deprecated in visionOS 1.0" },
+        "documentLocationInMetadata": { "url": { "_value": "file:///tmp/VisionApp/ContentView.swift#EndingLineNumber=20&StartingColumnNumber=12&StartingLineNumber=20" } }
+      }
+    ] }
+  }
+}
+JSON
+    ARCHIVE_PATH=""
+    prev=""
+    for arg in "$@"; do
+      if [[ "${prev}" == "-archivePath" ]]; then
+        ARCHIVE_PATH="${arg}"
+      fi
+      prev="${arg}"
+    done
+    if [[ -n "${ARCHIVE_PATH}" ]]; then
+      echo "[mock-xcodebuild] generating dummy archive at ${ARCHIVE_PATH}" >&2
+      mkdir -p "${ARCHIVE_PATH}/Products/Applications/VisionApp.app"
+      printf "dummy app bundle" > "${ARCHIVE_PATH}/Products/Applications/VisionApp.app/Info.plist"
+      printf "dummy archive info" > "${ARCHIVE_PATH}/Info.plist"
+      echo "** ARCHIVE SUCCEEDED **"
+      exit 0
+    fi
     echo "[mock-xcodebuild] generating dummy artifacts in ${ARTIFACT_DIR}" >&2
     mkdir -p "${ARTIFACT_DIR}/VisionApp.app"
     printf "dummy app bundle" > "${ARTIFACT_DIR}/VisionApp.app/Info.plist"
-    mkdir -p "${ARTIFACT_DIR}/VisionApp.dSYM"
-    printf "dummy dSYM" > "${ARTIFACT_DIR}/VisionApp.dSYM/Contents"
+    printf "dummy binary" > "${ARTIFACT_DIR}/VisionApp.app/VisionApp"
+    mkdir -p "${ARTIFACT_DIR}/VisionApp.dSYM/Contents/Resources/DWARF"
+    printf "dummy dSYM" > "${ARTIFACT_DIR}/VisionApp.dSYM/Contents/Resources/DWARF/VisionApp"
     ;;
 esac
@@ -0,0 +1,134 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+echo "[mock-xcrun] invoked with args: $*" >&2
+
+if [[ "${1:-}" == "xcresulttool" ]]; then
+  BUNDLE=""
+  prev=""
+  for arg in "$@"; do
+    if [[ "${prev}" == "--path" ]]; then
+      BUNDLE="${arg}"
+    fi
+    prev="${arg}"
+  done
+  if [[ ! -f "${BUNDLE}/mock-xcresulttool.json" ]]; then
+    echo "[mock-xcrun] no result bundle at ${BUNDLE}" >&2
+    exit 1
+  fi
+  cat "${BUNDLE}/mock-xcresulttool.json"
+  exit 0
+fi
+
+if [[ "${1:-}" == "strip" ]]; then
+  BINARY="${*: -1}"
+  printf "stripped" > "${BINARY}"
+  exit 0
+fi
+
+if [[ "${1:-}" == "lipo" ]]; then
+  if [[ "${2:-}" == "-archs" ]]; then
+    echo "arm64 x86_64"
+    exit 0
+  fi
+  OUTPUT="${*: -1}"
+  printf "thin" > "${OUTPUT}"
+  exit 0
+fi
+
+if [[ "${1:-}" == "atos" ]]; then
+  shift
+  while [[ $# -gt 0 && "${1}" != "-l" ]]; do
+    shift
+  done
+  shift 2
+  for address in "$@"; do
+    echo "mock_symbol_${address} (in VisionApp) (main.swift:1)"
+  done
+  exit 0
+fi
+
+if [[ "${1:-}" == "devicectl" ]]; then
+  OUTPUT="${*: -1}"
+  cat > "${OUTPUT}" <<'JSON'
+{
+  "info": { "outcome": "success" },
+  "result": {
+    "devices": [
+      {
+        "identifier": "6A1F2B3C-4D5E-6F70-8192-A3B4C5D6E7F8",
+        "deviceProperties": { "name": "Studio Vision Pro", "osVersionNumber": "26.1", "developerModeStatus": "enabled" },
+        "hardwareProperties": { "platform": "xrOS", "udid": "00008112-001A2C3E0123401E", "marketingName": "Apple Vision Pro" },
+        "connectionProperties": { "pairingState": "paired", "tunnelState": "connected", "transportType": "wired" }
+      },
+      {
+        "identifier": "7B2E3C4D-5E6F-7081-92A3-B4C5D6E7F809",
+        "deviceProperties": { "name": "Lab Vision Pro", "osVersionNumber": "26.0" },
+        "hardwareProperties": { "platform": "xrOS", "udid": "00008112-0009876543210ABC", "marketingName": "Apple Vision Pro" },
+        "connectionProperties": { "pairingState": "paired", "tunnelState": "unavailable" }
+      },
+      {
+        "identifier": "11111111-2222-3333-4444-555555555555",
+        "deviceProperties": { "name": "iPhone" },
+        "hardwareProperties": { "platform": "iOS", "udid": "00008101-000000000000001E" },
+        "connectionProperties": { "pairingState": "paired", "tunnelState": "connected" }
+      }
+    ]
+  }
+}
+JSON
+  exit 0
+fi
+
+if [[ "${1:-}" != "simctl" ]]; then
+  echo "[mock-xcrun] unsupported tool: ${1:-}" >&2
+  exit 64
+fi
+
+if [[ "${MOCK_SIMCTL_BEHAVIOR:-success}" == "fail" ]]; then
+  echo "[mock-xcrun] simulated simctl failure" >&2
+  exit 72
+fi
+
+case "${2:-}" in
+  list)
+    cat <<'JSON'
+{
+  "devices": {
+    "com.apple.CoreSimulator.SimRuntime.iOS-18-0": [
+      { "udid": "11111111-1111-1111-1111-111111111111", "name": "iPhone 16", "state": "Shutdown", "isAvailable": true }
+    ],
+    "com.apple.CoreSimulator.SimRuntime.xrOS-26-2": [
+      { "udid": "F556D53F-412A-4778-AF81-3449D52F5A7F", "name": "Apple Vision Pro", "state": "Shutdown", "isAvailable": true },
+      { "udid": "0E3C9A42-7A0B-4C55-9E1F-2B7D6C1A9F10", "name": "Apple Vision Pro (Booted)", "state": "Booted", "isAvailable": true }
+    ],
+    "com.apple.CoreSimulator.SimRuntime.xrOS-2-5": [
+      { "udid": "5BB47C97-BDBA-4DA7-BE30-F659C265F896", "name": "Apple Vision Pro", "state": "Shutdown", "isAvailable": false }
+    ]
+  }
+}
+JSON
+    ;;
+  boot|shutdown)
+    echo "[mock-xcrun] ${2} ${3:-}" >&2
+    ;;
+  install)
+    if [[ ! -d "${4:-}" ]]; then
+      echo "[mock-xcrun] app bundle not found: ${4:-}" >&2
+      exit 66
+    fi
+    echo "[mock-xcrun] install ${3:-} ${4}" >&2
+    ;;
+  io)
+    OUTPUT="${*: -1}"
+    if [[ "${4:-}" == "recordVideo" ]]; then
+      trap 'printf "mock-mov" > "${OUTPUT}"; exit 0' INT
+      while true; do sleep 0.1; done
+    fi
+    printf "mock-png" > "${OUTPUT}"
+    ;;
+  *)
+    echo "[mock-xcrun] unsupported simctl subcommand: ${2:-}" >&2
+    exit 64
+    ;;
+esac
@@ -0,0 +1,13 @@
+// swift-tools-version:5.9
+import PackageDescription
+
+let package = Package(
+    name: "VisionKit",
+    platforms: [.visionOS(.v1)],
+    products: [
+        .library(name: "VisionKit", targets: ["VisionKit"]),
+    ],
+    targets: [
+        .target(name: "VisionKit"),
+    ]
+)
@@ -0,0 +1,9 @@
+import SwiftUI
+
+public struct ImmersiveBadge: View {
+    public init() {}
+
+    public var body: some View {
+        Text("VisionKit")
+    }
+}
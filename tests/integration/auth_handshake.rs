@@ -4,11 +4,27 @@ use std::{
 };
 
 use anyhow::Result;
-use rmcp::{model::ClientInfo, serve_client};
-use tokio::time::timeout;
+use rmcp::{
+    model::{CallToolRequestParam, ClientInfo},
+    serve_client, RoleClient, ServiceError,
+};
+use seiro_mcp::server::runtime::websocket;
+use tempfile::TempDir;
+use tokio::{
+    net::TcpStream,
+    process::{Child, Command},
+    time::timeout,
+};
+use tokio_tungstenite::{
+    client_async,
+    tungstenite::{client::IntoClientRequest, http::header::AUTHORIZATION, Error as WsError},
+    WebSocketStream,
+};
 
 use crate::common::{fixture, spawn_server_process, BINARY_PATH};
 
+const WEBSOCKET_TOKEN: &str = "websocket-token-0001";
+
 #[tokio::test]
 async fn token_is_not_required_for_stdio_handshake() -> Result<()> {
     let (mut child, transport, stderr_task) = spawn_server_process().await?;
@@ -31,8 +47,193 @@ async fn token_is_not_required_for_stdio_handshake() -> Result<()> {
     Ok(())
 }
 
+/// Start the binary with `--transport=websocket` on a free port, with `server_settings` added
+/// to `[server]`, and wait until it listens.
+async fn spawn_websocket_server(server_settings: &str) -> Result<(Child, u16, TempDir)> {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port();
+    let state = tempfile::tempdir()?;
+    let config = state.path().join("seiro-mcp.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[server]\nport = {port}\n{server_settings}\n\n[tools]\nadmin = true\n\n[auth]\ntoken = \"{WEBSOCKET_TOKEN}\"\n\n[visionos]\nallowed_paths = []\nallowed_schemes = []\nxcode_path = \"/Applications/Xcode.app/Contents/Developer\"\n"
+        ),
+    )?;
+    let child = Command::new(BINARY_PATH)
+        .arg(format!("--config={}", config.display()))
+        .arg("--transport=websocket")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while TcpStream::connect(("127.0.0.1", port)).await.is_err() {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "server should listen on port {port}"
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    Ok((child, port, state))
+}
+
+async fn connect(
+    port: u16,
+    token: Option<&str>,
+) -> Result<Result<WebSocketStream<TcpStream>, WsError>> {
+    let mut request = format!("ws://127.0.0.1:{port}/").into_client_request()?;
+    if let Some(token) = token {
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, format!("Bearer {token}").parse()?);
+    }
+    let stream = TcpStream::connect(("127.0.0.1", port)).await?;
+    Ok(client_async(request, stream)
+        .await
+        .map(|(websocket, _)| websocket))
+}
+
+#[tokio::test]
+async fn websocket_transport_requires_the_token_and_limits_sessions() -> Result<()> {
+    let (mut child, port, _state) = spawn_websocket_server("max_connections = 1").await?;
+
+    let rejected = connect(port, None).await?;
+    let websocket = connect(port, Some(WEBSOCKET_TOKEN)).await??;
+    let client = serve_client(
+        ClientInfo::default(),
+        websocket::transport::<RoleClient, _>(websocket),
+    )
+    .await?;
+    let list = client.list_tools(None).await;
+    let over_limit = connect(port, Some(WEBSOCKET_TOKEN)).await?;
+    let sessions = client
+        .call_tool(CallToolRequestParam {
+            name: "list_sessions".into(),
+            arguments: None,
+        })
+        .await;
+    let _ = client.cancel().await;
+    child.kill().await?;
+
+    match rejected {
+        Err(WsError::Http(response)) => assert_eq!(response.status(), 401),
+        other => panic!("connection without the token should get 401, got {other:?}"),
+    }
+    assert!(list?
+        .tools
+        .iter()
+        .any(|tool| tool.name.as_ref() == "build_visionos_app"));
+    match over_limit {
+        Err(WsError::Http(response)) => assert_eq!(response.status(), 503),
+        other => panic!("a session over server.max_connections should get 503, got {other:?}"),
+    }
+    let sessions = sessions?.structured_content.expect("structured sessions");
+    assert_eq!(sessions["max_connections"], 1);
+    assert_eq!(sessions["sessions"].as_array().map(Vec::len), Some(1));
+    assert_eq!(sessions["sessions"][0]["transport"], "websocket");
+    assert_eq!(sessions["sessions"][0]["tools_invoked"]["list_sessions"], 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn scoped_tokens_only_reach_the_tools_of_their_scopes() -> Result<()> {
+    let (mut child, port, _state) = spawn_websocket_server(
+        "\n[[auth.tokens]]\nname = \"dashboard\"\ntoken = \"dashboard-token-00001\"\nscopes = [\"fetch\"]",
+    )
+    .await?;
+
+    let websocket = connect(port, Some("dashboard-token-00001")).await??;
+    let client = serve_client(
+        ClientInfo::default(),
+        websocket::transport::<RoleClient, _>(websocket),
+    )
+    .await?;
+    let call = |name: &'static str| {
+        client.call_tool(CallToolRequestParam {
+            name: name.into(),
+            arguments: None,
+        })
+    };
+    let queue = call("get_queue_status").await;
+    let error_codes = call("list_error_codes").await;
+    let sandbox = call("validate_sandbox_policy").await;
+    let sessions = call("list_sessions").await;
+    let _ = client.cancel().await;
+    child.kill().await?;
+
+    assert!(
+        queue.is_ok(),
+        "fetch scope covers get_queue_status: {queue:?}"
+    );
+    assert!(error_codes.is_ok());
+    for (denied, required_scope) in [(sandbox, "sandbox"), (sessions, "admin")] {
+        match denied {
+            Err(ServiceError::McpError(error)) => {
+                let data = error.data.expect("error data");
+                assert_eq!(data["code"], "insufficient_scope");
+                assert_eq!(data["details"]["required_scope"], required_scope);
+                assert_eq!(data["auth_entry"], "dashboard");
+            }
+            other => panic!("a call outside the token's scopes should fail, got {other:?}"),
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn repeated_bad_tokens_lock_the_address_out() -> Result<()> {
+    let (mut child, port, _state) =
+        spawn_websocket_server("auth_failure_limit = 2\nauth_lockout_secs = 60").await?;
+
+    let mut rejections = Vec::new();
+    for token in ["websocket-token-9999", "websocket-token-9998"] {
+        rejections.push(connect(port, Some(token)).await?);
+    }
+    let locked_out = connect(port, Some(WEBSOCKET_TOKEN)).await?;
+    child.kill().await?;
+
+    for rejection in rejections {
+        match rejection {
+            Err(WsError::Http(response)) => assert_eq!(response.status(), 401),
+            other => panic!("a wrong token should get 401, got {other:?}"),
+        }
+    }
+    match locked_out {
+        Err(WsError::Http(response)) => {
+            assert_eq!(response.status(), 429);
+            let retry_after: u64 = response.headers()["retry-after"].to_str()?.parse()?;
+            assert!((1..=60).contains(&retry_after));
+        }
+        other => panic!("a locked-out address should get 429 even with the token, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn idle_websocket_sessions_are_closed() -> Result<()> {
+    let (mut child, port, _state) = spawn_websocket_server("idle_timeout_secs = 1").await?;
+
+    let websocket = connect(port, Some(WEBSOCKET_TOKEN)).await??;
+    let client = serve_client(
+        ClientInfo::default(),
+        websocket::transport::<RoleClient, _>(websocket),
+    )
+    .await?;
+    let closed = timeout(Duration::from_secs(10), client.waiting()).await;
+    child.kill().await?;
+
+    assert!(
+        closed.is_ok(),
+        "the server should close a session idle past server.idle_timeout_secs"
+    );
+    Ok(())
+}
+
 #[test]
-fn transport_argument_is_not_supported() {
+fn tcp_transport_is_not_supported() {
     let output = StdCommand::new(BINARY_PATH)
         .arg("--transport=tcp")
         .env(
@@ -47,11 +248,11 @@ fn transport_argument_is_not_supported() {
 
     assert!(
         !output.status.success(),
-        "--transport should be rejected after TCP removal"
+        "--transport=tcp should be rejected after TCP removal"
     );
     let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(
-        stderr.contains("unexpected argument") || stderr.contains("Usage:"),
-        "stderr should explain unsupported argument, got: {stderr}"
+        stderr.contains("invalid value 'tcp'") && stderr.contains("websocket"),
+        "stderr should list the supported transports, got: {stderr}"
     );
 }
@@ -1,4 +1,4 @@
-use std::{env, fs, path::PathBuf};
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
 
 use anyhow::{Context, Result};
 use rmcp::{
@@ -10,7 +10,10 @@ use rmcp::{
 use serde_json::{json, Value};
 
 use seiro_mcp::server::{
-    config::{ServerConfig, ServerSection, VisionOsConfig},
+    config::{
+        ArtifactsConfig, SchemePattern, ServerConfig, ServerSection, SymlinkPolicy, VisionOsConfig,
+        ZipCompression,
+    },
     runtime::VisionOsServer,
 };
 
@@ -50,6 +53,7 @@ fn enable_fast_timeout() {
 fn configure_sandbox_probe_env() {
     env::set_var("VISIONOS_SANDBOX_PROBE", "env");
     env::set_var("VISIONOS_SANDBOX_SDKS", "visionOS,visionOS Simulator");
+    env::set_var("VISIONOS_SANDBOX_RUNTIMES", "26.2");
     env::set_var("VISIONOS_SANDBOX_DEVTOOLS", "enabled");
     env::set_var("VISIONOS_SANDBOX_LICENSE", "accepted");
     env::set_var("VISIONOS_SANDBOX_DISK_BYTES", "1099511627776");
@@ -70,20 +74,66 @@ fn test_server_config(max_build_minutes: u16) -> ServerConfig {
         server: ServerSection {
             host: "127.0.0.1".into(),
             port: 8787,
+            shutdown_grace_secs: 30,
+            rate_limit_per_minute: 0,
+            max_concurrent_calls: 0,
+            allow_external_bind: false,
+            max_connections: 16,
+            idle_timeout_secs: 0,
+            auth_failure_limit: 0,
+            auth_lockout_secs: 60,
         },
         visionos: VisionOsConfig {
             allowed_paths: vec![workspace],
-            allowed_schemes: vec!["VisionApp".into()],
+            allowed_roots: Vec::new(),
+            allowed_schemes: vec![SchemePattern::new("VisionApp").expect("valid scheme")],
             default_project_path: None,
             default_destination: "platform=visionOS Simulator,name=Apple Vision Pro".into(),
             required_sdks: vec!["visionOS".into(), "visionOS Simulator".into()],
             xcode_path: PathBuf::from("/Applications/Xcode.app/Contents/Developer"),
             xcodebuild_path: mock_xcodebuild_path(),
+            xcrun_path: PathBuf::from("/usr/bin/xcrun"),
             max_build_minutes,
+            max_timeout_minutes: max_build_minutes,
+            timeout_warning_percent: 80,
+            heartbeat_interval_secs: 15,
+            max_extended_build_minutes: 0,
+            max_concurrent_builds: 1,
             artifact_ttl_secs: 600,
             cleanup_schedule_secs: 60,
+            artifact_max_jobs: 0,
+            artifact_max_total_bytes: 0,
+            min_free_disk_bytes: 0,
+            sandbox_probe_ttl_secs: 0,
+            allow_remediation: false,
+            package_hosts: Vec::new(),
+            policy: Default::default(),
+            export_paths: vec![],
+            size_budgets: BTreeMap::new(),
+            artifact_compression: ZipCompression::default(),
+            artifact_symlinks: SymlinkPolicy::default(),
+            deterministic_artifacts: false,
+            derived_data_root: None,
+            build_cache: false,
+            project_config: false,
+            compilation_cache: None,
+            platforms: BTreeMap::new(),
+            locale: "en_US.UTF-8".into(),
+            signing: Default::default(),
+            allowed_device_udids: Vec::new(),
+            artifact_persistence: Default::default(),
+            presets: Default::default(),
+            xcodes: Default::default(),
+            retry: Default::default(),
         },
+        artifacts: ArtifactsConfig::default(),
+        redaction: Default::default(),
+        auth: Default::default(),
+        federation: Default::default(),
+        notifications: Default::default(),
+        tools: Default::default(),
         source_path: PathBuf::from("tests/fixtures/config_valid.toml"),
+        profile: None,
     }
 }
 
@@ -108,8 +158,15 @@ fn normalize_build_success(mut payload: Value) -> Value {
                 Value::String("<artifact_sha256>".into()),
             );
         }
-        if obj.contains_key("duration_ms") {
-            obj.insert("duration_ms".into(), Value::Number(0.into()));
+        for key in ["duration_ms", "packaging_duration_ms"] {
+            if obj.contains_key(key) {
+                obj.insert(key.into(), Value::Number(0.into()));
+            }
+        }
+        if let Some(attempts) = obj.get_mut("attempts").and_then(Value::as_array_mut) {
+            for attempt in attempts.iter_mut().filter_map(Value::as_object_mut) {
+                attempt.insert("duration_ms".into(), Value::Number(0.into()));
+            }
         }
         if obj.contains_key("log_excerpt") {
             obj.insert("log_excerpt".into(), Value::String("<log_excerpt>".into()));
@@ -138,6 +195,12 @@ fn normalize_fetch_success(mut payload: Value) -> Value {
         if obj.contains_key("log_excerpt") {
             obj.insert("log_excerpt".into(), Value::String("<log_excerpt>".into()));
         }
+        if let Some(backend) = obj
+            .get_mut("storage_backend")
+            .and_then(Value::as_object_mut)
+        {
+            backend.insert("location".into(), Value::String("<location>".into()));
+        }
     }
     payload
 }
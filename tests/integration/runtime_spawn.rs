@@ -62,6 +62,50 @@ fn direct_execution_requires_mcp_client() {
     );
 }
 
+#[test]
+fn self_check_reports_each_step_and_sets_the_exit_code() -> Result<()> {
+    let state = tempdir()?;
+    let config = state.path().join("seiro-mcp.toml");
+    fs::write(
+        &config,
+        format!(
+            "[visionos]\nallowed_paths = [\"{workspace}\"]\nallowed_schemes = []\nxcode_path = \"/Applications/Xcode.app/Contents/Developer\"\nxcodebuild_path = \"{xcodebuild}\"\n",
+            workspace = fixture("tests/fixtures/visionos/workspace"),
+            xcodebuild = fixture("tests/fixtures/visionos/mock-xcodebuild.sh"),
+        ),
+    )?;
+    let self_check = |sdks: &str| {
+        StdCommand::new(BINARY_PATH)
+            .arg(format!("--config={}", config.display()))
+            .arg("--self-check")
+            .env("VISIONOS_SANDBOX_PROBE", "env")
+            .env("VISIONOS_SANDBOX_SDKS", sdks)
+            .env("VISIONOS_SANDBOX_RUNTIMES", "26.2")
+            .stdin(Stdio::null())
+            .output()
+    };
+
+    let healthy = self_check("visionOS,visionOS Simulator")?;
+    let report: serde_json::Value = serde_json::from_slice(&healthy.stdout)?;
+    assert!(healthy.status.success(), "report: {report}");
+    assert_eq!(report["status"], "ok");
+    let names: Vec<&str> = report["checks"]
+        .as_array()
+        .expect("checks")
+        .iter()
+        .filter_map(|check| check["name"].as_str())
+        .collect();
+    assert_eq!(names, ["config", "tool_router", "sandbox", "xcodebuild"]);
+    assert_eq!(report["checks"][3]["output"][0]["version"], "26.2");
+
+    let missing_sdk = self_check("")?;
+    let report: serde_json::Value = serde_json::from_slice(&missing_sdk.stdout)?;
+    assert_eq!(missing_sdk.status.code(), Some(1));
+    assert_eq!(report["status"], "error");
+    assert_eq!(report["checks"][2]["result"], "fail");
+    Ok(())
+}
+
 #[test]
 fn cargo_install_path_locked_produces_binary() {
     let temp = tempdir().expect("can create temp directory for cargo install root");
@@ -128,8 +172,8 @@ fn cargo_install_binary_supports_help() {
         "--help output should list config subcommand, got: {stdout}"
     );
     assert!(
-        !stdout.contains("--transport"),
-        "--help output should not expose removed transport flag, got: {stdout}"
+        !stdout.to_lowercase().contains("tcp"),
+        "--help output should not expose the removed TCP transport, got: {stdout}"
     );
 }
 
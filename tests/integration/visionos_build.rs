@@ -1,25 +1,42 @@
-use std::{env, path::PathBuf, sync::Mutex, time::Duration};
+use std::{
+    collections::BTreeMap,
+    env,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use anyhow::Result;
 use rmcp::{
-    model::{CallToolRequestParam, ClientInfo},
+    model::{
+        CallToolRequest, CallToolRequestParam, ClientInfo, ClientRequest, GetPromptRequestParam,
+        LoggingMessageNotificationParam, Meta, ProgressNotificationParam, PromptMessageContent,
+        ReadResourceRequestParam, ResourceContents, ServerResult,
+    },
     serve_client,
-    service::ServiceError,
-    ServiceExt,
+    service::{NotificationContext, PeerRequestOptions, ServiceError},
+    ClientHandler, RoleClient, ServiceExt,
 };
 use serde_json::{json, Value};
-use tokio::time::Instant;
+use sha2::{Digest, Sha256};
+use tokio::{sync::Mutex, time::Instant};
 use uuid::Uuid;
 
 use seiro_mcp::server::{
-    config::{ServerConfig, ServerSection, VisionOsConfig},
+    config::{
+        ArtifactPersistence, ArtifactStorageKind, ArtifactsConfig, BuildPreset, CompilationCache,
+        CompilationCacheMode, Downstream, RetryPolicy, SchemePattern, ServerConfig, ServerSection,
+        SizeBudget, SizeBudgetEnforcement, SymlinkPolicy, ToolLimits, ToolsSection, VisionOsConfig,
+        ZipCompression,
+    },
     runtime::VisionOsServer,
 };
+use seiro_mcp::tools::visionos::VisionOsArtifactStore;
 
-static SANDBOX_ENV_LOCK: Mutex<()> = Mutex::new(());
-static XCODEBUILD_ENV_LOCK: Mutex<()> = Mutex::new(());
-static DIAGNOSTICS_ENV_LOCK: Mutex<()> = Mutex::new(());
-static CWD_LOCK: Mutex<()> = Mutex::new(());
+static SANDBOX_ENV_LOCK: Mutex<()> = Mutex::const_new(());
+static XCODEBUILD_ENV_LOCK: Mutex<()> = Mutex::const_new(());
+static DIAGNOSTICS_ENV_LOCK: Mutex<()> = Mutex::const_new(());
+static CWD_LOCK: Mutex<()> = Mutex::const_new(());
+static SIMCTL_ENV_LOCK: Mutex<()> = Mutex::const_new(());
 
 #[tokio::test]
 async fn build_tool_returns_artifact_metadata() -> Result<()> {
@@ -69,6 +86,16 @@ async fn build_tool_returns_artifact_metadata() -> Result<()> {
         payload.get("status").and_then(|v| v.as_str()),
         Some("succeeded")
     );
+    let diagnostics = payload
+        .get("diagnostics")
+        .and_then(Value::as_array)
+        .expect("diagnostics array");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].get("severity").and_then(Value::as_str),
+        Some("warning")
+    );
+    assert_eq!(diagnostics[0].get("line").and_then(Value::as_u64), Some(21));
     assert!(payload
         .get("artifact_path")
         .and_then(|v| v.as_str())
@@ -78,13 +105,27 @@ async fn build_tool_returns_artifact_metadata() -> Result<()> {
         .and_then(|v| v.as_str())
         .is_some());
     assert!(payload.get("job_id").and_then(|v| v.as_str()).is_some());
+    let size = payload.get("size").expect("size report");
+    assert_eq!(
+        size.get("app_bundle").and_then(Value::as_str),
+        Some("VisionApp.app")
+    );
+    assert_eq!(
+        size.get("app_bundle_bytes").and_then(Value::as_u64),
+        Some(28)
+    );
+    assert_eq!(
+        size.get("main_binary_bytes").and_then(Value::as_u64),
+        Some(12)
+    );
+    assert!(size.get("budget").is_none());
     Ok(())
 }
 
 #[tokio::test]
-async fn build_tool_times_out_when_process_exceeds_deadline() -> Result<()> {
+async fn dry_run_returns_the_command_without_running_it() -> Result<()> {
     enable_fast_timeout();
-    let config = test_server_config(1);
+    let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -94,40 +135,161 @@ async fn build_tool_times_out_when_process_exceeds_deadline() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
-        "scheme": "VisionApp",
-        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
-        "env_overrides": {
-            "MOCK_XCODEBUILD_BEHAVIOR": "sleep"
-        }
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let call_result = client
-        .call_tool(CallToolRequestParam {
-            name: "build_visionos_app".into(),
-            arguments: Some(args),
+    let call = |name: &'static str, args: Value| {
+        client.call_tool(CallToolRequestParam {
+            name: name.into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
         })
-        .await;
+    };
+    let plan = call(
+        "build_visionos_app",
+        json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "extra_args": ["-quiet"],
+            "env_overrides": { "MOCK_XCODEBUILD_BEHAVIOR": "success" },
+            "timeout_minutes": 3,
+            "dry_run": true
+        }),
+    )
+    .await
+    .expect("dry run should succeed")
+    .structured_content
+    .expect("structured_content should exist");
+    let rejected = call(
+        "build_visionos_app",
+        json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "OtherApp",
+            "dry_run": true
+        }),
+    )
+    .await;
+    let test_dry_run = call(
+        "test_visionos_app",
+        json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "dry_run": true
+        }),
+    )
+    .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = call_result.expect_err("should return an error on timeout");
-    match error {
+    assert_eq!(plan["status"], json!("dry_run"));
+    let details = &plan["plan"];
+    let argv: Vec<&str> = details["argv"]
+        .as_array()
+        .expect("argv")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    assert_eq!(argv[0], mock_xcodebuild_path().to_string_lossy());
+    assert!(argv.windows(2).any(|pair| pair == ["-scheme", "VisionApp"]));
+    assert!(argv.contains(&"-quiet"));
+    assert_eq!(
+        details["env"]["MOCK_XCODEBUILD_BEHAVIOR"],
+        json!("[redacted]")
+    );
+    assert_eq!(details["env"]["LANG"], json!("en_US.UTF-8"));
+    // `enable_fast_timeout` scales minutes to seconds.
+    assert_eq!(details["timeout_secs"], json!(3));
+    let staging_dir = PathBuf::from(details["staging_dir"].as_str().expect("staging_dir"));
+    assert!(staging_dir.ends_with(format!(
+        "{}/staging",
+        plan["job_id"].as_str().expect("job_id")
+    )));
+    assert!(!staging_dir.exists(), "a dry run must not create the job");
+
+    match rejected.expect_err("the scheme allowlist still applies") {
         ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "timeout", "no_violation", true);
+            assert_error_metadata(&inner, "scheme_not_allowed", "blocked", false)
         }
-        other => panic!("Unexpected error: {other:?}", other = other),
+        other => panic!("Unexpected error: {other:?}"),
+    }
+    match test_dry_run.expect_err("tests cannot be dry-run") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "invalid_request", "no_violation", false)
+        }
+        other => panic!("Unexpected error: {other:?}"),
     }
     Ok(())
 }
 
 #[tokio::test]
-async fn build_tool_rejects_path_outside_allowlist() -> Result<()> {
+async fn build_tool_applies_scheme_size_budget() -> Result<()> {
+    enable_fast_timeout();
+    for enforcement in [SizeBudgetEnforcement::Warn, SizeBudgetEnforcement::Fail] {
+        let mut config = test_server_config(20);
+        config.visionos.size_budgets.insert(
+            "VisionApp".into(),
+            SizeBudget {
+                max_app_bytes: Some(1_000),
+                max_binary_bytes: Some(8),
+                enforcement,
+            },
+        );
+        let server = build_server(config);
+        let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+        let server_task = tokio::spawn(async move {
+            server.serve(server_transport).await?.waiting().await?;
+            Result::<_, anyhow::Error>::Ok(())
+        });
+        let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+        let args = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+            "env_overrides": {
+                "MOCK_XCODEBUILD_BEHAVIOR": "success"
+            }
+        })
+        .as_object()
+        .expect("JSON object")
+        .clone();
+
+        let call_result = client
+            .call_tool(CallToolRequestParam {
+                name: "build_visionos_app".into(),
+                arguments: Some(args),
+            })
+            .await;
+
+        let _ = client.cancel().await;
+        let _ = server_task.await;
+
+        let size = match enforcement {
+            SizeBudgetEnforcement::Warn => call_result
+                .expect("warn-only budget keeps the build successful")
+                .structured_content
+                .and_then(|payload| payload.get("size").cloned()),
+            SizeBudgetEnforcement::Fail => match call_result
+                .expect_err("fail budget should reject the oversized build")
+            {
+                ServiceError::McpError(inner) => {
+                    assert_error_metadata(&inner, "size_budget_exceeded", "no_violation", false);
+                    error_field(&inner, "details").and_then(|details| details.get("size").cloned())
+                }
+                other => panic!("unexpected error: {other:?}", other = other),
+            },
+        }
+        .expect("size report");
+        let budget = size.get("budget").expect("budget status");
+        assert_eq!(budget.get("exceeded").and_then(Value::as_bool), Some(true));
+        assert_eq!(
+            budget.get("max_binary_bytes").and_then(Value::as_u64),
+            Some(8)
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_strips_and_thins_binaries_and_splits_dsyms() -> Result<()> {
     enable_fast_timeout();
     let config = test_server_config(20);
     let server = build_server(config);
@@ -139,37 +301,76 @@ async fn build_tool_rejects_path_outside_allowlist() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "project_path": PathBuf::from("/tmp/disallowed-project").to_string_lossy(),
-        "scheme": "VisionApp",
-        "destination": "platform=visionOS Simulator,name=Apple Vision Pro"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
+    let build_args = |architectures: Value| {
+        json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+            "strip_symbols": true,
+            "architectures": architectures,
+            "env_overrides": {
+                "MOCK_XCODEBUILD_BEHAVIOR": "success"
+            }
+        })
+        .as_object()
+        .expect("JSON object")
+        .clone()
+    };
 
-    let call_result = client
+    let success = client
         .call_tool(CallToolRequestParam {
             name: "build_visionos_app".into(),
-            arguments: Some(args),
+            arguments: Some(build_args(json!(["arm64"]))),
+        })
+        .await;
+    let mismatch = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(build_args(json!(["arm64e"]))),
         })
         .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = call_result.expect_err("disallowed path should return an error");
-    match error {
+    let payload = success
+        .expect("post-processed build succeeds")
+        .structured_content
+        .expect("structured content");
+    let post_process = payload.get("post_process").expect("post_process report");
+    assert_eq!(post_process.get("stripped"), Some(&json!(true)));
+    assert_eq!(post_process.get("binaries"), Some(&json!(["VisionApp"])));
+    assert_eq!(
+        post_process.get("removed_architectures"),
+        Some(&json!(["x86_64"]))
+    );
+    let dsym_zip = post_process
+        .get("dsym_artifact_path")
+        .and_then(Value::as_str)
+        .map(PathBuf::from)
+        .expect("dSYM artifact path");
+    assert!(dsym_zip.is_file());
+    let job_dir = dsym_zip.parent().expect("job directory");
+    assert!(!job_dir.join("staging/VisionApp.dSYM").exists());
+    assert_eq!(
+        payload
+            .get("size")
+            .and_then(|size| size.get("main_binary_bytes"))
+            .and_then(Value::as_u64),
+        Some("stripped".len() as u64)
+    );
+
+    match mismatch.expect_err("missing slice should fail the build") {
         ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "path_not_allowed", "blocked", false);
+            assert_error_metadata(&inner, "post_process_failed", "no_violation", false);
         }
-        other => panic!("Unexpected error: {other:?}", other = other),
+        other => panic!("unexpected error: {other:?}", other = other),
     }
     Ok(())
 }
 
 #[tokio::test]
-async fn fetch_tool_returns_artifact_metadata() -> Result<()> {
+async fn symbolicate_tool_resolves_app_frames_with_collected_dsyms() -> Result<()> {
     enable_fast_timeout();
     let config = test_server_config(20);
     let server = build_server(config);
@@ -181,89 +382,85 @@ async fn fetch_tool_returns_artifact_metadata() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
-        "scheme": "VisionApp",
-        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
-        "env_overrides": {
-            "MOCK_XCODEBUILD_BEHAVIOR": "success"
-        }
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let build_response = client
+    let build = client
         .call_tool(CallToolRequestParam {
             name: "build_visionos_app".into(),
-            arguments: Some(args),
+            arguments: Some(
+                json!({
+                    "project_path": allowed_project_path().to_string_lossy(),
+                    "scheme": "VisionApp",
+                    "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+                    "collect_dsyms": true,
+                    "env_overrides": {
+                        "MOCK_XCODEBUILD_BEHAVIOR": "success"
+                    }
+                })
+                .as_object()
+                .expect("JSON object")
+                .clone(),
+            ),
         })
         .await
-        .expect("build_visionos_app should succeed");
-    let build_payload = build_response
+        .expect("build with collect_dsyms succeeds")
         .structured_content
-        .expect("structured_content");
-    let job_id = build_payload
+        .expect("structured content");
+    let job_id = build
         .get("job_id")
-        .and_then(|v| v.as_str())
+        .and_then(Value::as_str)
         .expect("job_id")
         .to_string();
-
-    let fetch_args = json!({
-        "job_id": job_id.clone(),
-        "include_logs": true
-    })
-    .as_object()
-    .expect("object")
-    .clone();
-
-    let fetch_response = client
+    let crash_log = "Thread 0 Crashed:\n\
+        0   libsystem_kernel.dylib        \t0x00000001a2b3c4d4 0x1a2b00000 + 242900\n\
+        1   VisionApp                     \t0x0000000100a1c2d4 0x100a14000 + 33492\n";
+    let symbolicated = client
         .call_tool(CallToolRequestParam {
-            name: "fetch_build_output".into(),
-            arguments: Some(fetch_args),
+            name: "symbolicate_crash".into(),
+            arguments: Some(
+                json!({ "job_id": job_id, "crash_log": crash_log })
+                    .as_object()
+                    .expect("JSON object")
+                    .clone(),
+            ),
         })
         .await
-        .expect("fetch_build_output should succeed");
+        .expect("symbolicate_crash succeeds")
+        .structured_content
+        .expect("structured content");
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let payload = fetch_response
-        .structured_content
-        .expect("structured_content");
-    assert_eq!(
-        payload.get("status").and_then(|v| v.as_str()),
-        Some("succeeded")
-    );
+    let post_process = build.get("post_process").expect("post_process report");
+    assert_eq!(post_process.get("stripped"), Some(&json!(false)));
+    assert!(post_process
+        .get("dsym_artifact_path")
+        .and_then(Value::as_str)
+        .is_some_and(|path| Path::new(path).is_file()));
     assert_eq!(
-        payload.get("job_id").and_then(|v| v.as_str()),
-        Some(job_id.as_str())
-    );
-    assert!(
-        payload
-            .get("artifact_zip")
-            .and_then(|v| v.as_str())
-            .is_some(),
-        "artifact_zip should be present"
-    );
-    assert!(
-        payload.get("sha256").and_then(|v| v.as_str()).is_some(),
-        "sha256 should be present"
-    );
-    assert!(
-        payload
-            .get("download_ttl_seconds")
-            .and_then(|v| v.as_u64())
-            .is_some(),
-        "download_ttl_seconds should be present"
+        symbolicated.get("frames"),
+        Some(&json!([{
+            "binary": "VisionApp",
+            "address": "0x100a1c2d4",
+            "symbol": "mock_symbol_0x100a1c2d4 (in VisionApp) (main.swift:1)"
+        }]))
     );
+    assert_eq!(symbolicated.get("resolved_frames"), Some(&json!(1)));
+    let log = symbolicated
+        .get("symbolicated_log")
+        .and_then(Value::as_str)
+        .expect("symbolicated_log");
+    assert!(log.contains("0x0000000100a1c2d4 mock_symbol_0x100a1c2d4"));
+    assert!(log.contains("0x1a2b00000 + 242900"));
     Ok(())
 }
 
 #[tokio::test]
-async fn fetch_tool_reports_expiration_after_ttl() -> Result<()> {
-    enable_fast_timeout();
-    let config = test_server_config_with_ttl(20, 1);
+async fn build_tool_builds_swift_packages_from_manifest_directory() -> Result<()> {
+    let mut config = test_server_config(5);
+    config
+        .visionos
+        .allowed_schemes
+        .push(SchemePattern::new("VisionKit").expect("valid scheme"));
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -273,55 +470,40 @@ async fn fetch_tool_reports_expiration_after_ttl() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
-        "scheme": "VisionApp",
-        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
-        "env_overrides": {
-            "MOCK_XCODEBUILD_BEHAVIOR": "success"
-        }
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let build_response = client
-        .call_tool(CallToolRequestParam {
+    let package = allowed_project_path()
+        .parent()
+        .expect("workspace fixture")
+        .join("VisionKit");
+    let build = |arguments: Value| {
+        client.call_tool(CallToolRequestParam {
             name: "build_visionos_app".into(),
-            arguments: Some(args),
-        })
-        .await
-        .expect("build_visionos_app should succeed");
-    let build_payload = build_response
-        .structured_content
-        .expect("structured_content");
-    let job_id = build_payload
-        .get("job_id")
-        .and_then(|v| v.as_str())
-        .expect("job_id")
-        .to_string();
-
-    tokio::time::sleep(Duration::from_secs(2)).await;
-
-    let fetch_args = json!({ "job_id": job_id.clone() })
-        .as_object()
-        .expect("object")
-        .clone();
-
-    let fetch_result = client
-        .call_tool(CallToolRequestParam {
-            name: "fetch_build_output".into(),
-            arguments: Some(fetch_args),
+            arguments: arguments.as_object().cloned(),
         })
-        .await;
+    };
+    let built = build(json!({
+        "project_path": package.join("Package.swift").to_string_lossy(),
+        "scheme": "VisionKit",
+        "destination": "generic/platform=visionOS"
+    }))
+    .await?;
+    let stripped = build(json!({
+        "project_path": package.to_string_lossy(),
+        "scheme": "VisionKit",
+        "destination": "generic/platform=visionOS",
+        "strip_symbols": true
+    }))
+    .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = fetch_result.expect_err("artifact should expire");
-    match error {
+    let content = built
+        .structured_content
+        .expect("structured_content should exist");
+    assert_eq!(content.get("status"), Some(&json!("succeeded")));
+    match stripped.expect_err("packages have no app bundle to strip") {
         ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "artifact_expired", "no_violation", true);
+            assert_error_metadata(&inner, "invalid_request", "no_violation", false);
         }
         other => panic!("unexpected error: {other:?}", other = other),
     }
@@ -329,9 +511,9 @@ async fn fetch_tool_reports_expiration_after_ttl() -> Result<()> {
 }
 
 #[tokio::test]
-async fn fetch_tool_rejects_unknown_job() -> Result<()> {
-    enable_fast_timeout();
-    let config = test_server_config(20);
+async fn build_tool_serves_unchanged_builds_from_cache() -> Result<()> {
+    let mut config = test_server_config(5);
+    config.visionos.build_cache = true;
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -341,43 +523,56 @@ async fn fetch_tool_rejects_unknown_job() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let fetch_args = json!({ "job_id": Uuid::new_v4().to_string() })
+    // Records outlive the server, so keep this run's key apart from earlier runs.
+    let run_marker = Uuid::new_v4().to_string();
+    let build = |force_rebuild: bool| {
+        let arguments = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+            "force_rebuild": force_rebuild,
+            "env_overrides": { "CI": run_marker.clone() }
+        })
         .as_object()
-        .expect("object")
+        .expect("JSON object")
         .clone();
-
-    let fetch_result = client
-        .call_tool(CallToolRequestParam {
-            name: "fetch_build_output".into(),
-            arguments: Some(fetch_args),
+        client.call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(arguments),
         })
-        .await;
+    };
+    let first = build(false).await?;
+    let cached = build(false).await?;
+    let forced = build(true).await?;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = fetch_result.expect_err("unknown job should be rejected");
-    match error {
-        ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "job_not_found", "no_violation", false);
-        }
-        other => panic!("unexpected error: {other:?}", other = other),
-    }
+    let field = |result: &rmcp::model::CallToolResult, name: &str| {
+        result
+            .structured_content
+            .as_ref()
+            .and_then(|content| content.get(name))
+            .cloned()
+            .unwrap_or(Value::Null)
+    };
+    assert_eq!(field(&first, "cache_hit"), json!(false));
+    assert_eq!(field(&cached, "cache_hit"), json!(true));
+    assert_eq!(field(&cached, "job_id"), field(&first, "job_id"));
+    assert_eq!(
+        field(&cached, "artifact_path"),
+        field(&first, "artifact_path")
+    );
+    assert_eq!(field(&forced, "cache_hit"), json!(false));
+    assert_ne!(field(&forced, "job_id"), field(&first, "job_id"));
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_build_diagnostics_returns_typecheck_location_for_failed_job() -> Result<()> {
-    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
-    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK
-        .lock()
-        .expect("diagnostics env lock should not be poisoned");
-    enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
-    env::set_var("MOCK_DIAGNOSTICS_BEHAVIOR", "typecheck_error");
-    let config = test_server_config(20);
+async fn build_tool_reuses_derived_data_when_requested() -> Result<()> {
+    let derived_data_root = tempfile::tempdir()?;
+    let mut config = test_server_config(5);
+    config.visionos.derived_data_root = Some(derived_data_root.path().to_path_buf());
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -387,103 +582,109 @@ async fn inspect_build_diagnostics_returns_typecheck_location_for_failed_job() -
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let build_args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
-        "scheme": "VisionApp",
-        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
-        "env_overrides": {
-            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
-        }
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let build_result = client
-        .call_tool(CallToolRequestParam {
-            name: "build_visionos_app".into(),
-            arguments: Some(build_args),
+    let build_args = |use_cached_derived_data: bool| {
+        json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+            "use_cached_derived_data": use_cached_derived_data
         })
-        .await;
+        .as_object()
+        .expect("JSON object")
+        .clone()
+    };
+    for use_cached_derived_data in [true, true, false] {
+        client
+            .call_tool(CallToolRequestParam {
+                name: "build_visionos_app".into(),
+                arguments: Some(build_args(use_cached_derived_data)),
+            })
+            .await?;
+    }
 
-    let job_id = match build_result.expect_err("build should fail") {
-        ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "build_failed", "no_violation", true);
-            assert_eq!(
-                error_field(&inner, "details")
-                    .and_then(Value::as_object)
-                    .and_then(|details| details.get("diagnostics_hint"))
-                    .and_then(Value::as_str),
-                Some("inspect_build_diagnostics")
-            );
-            error_field(&inner, "job_id")
-                .and_then(Value::as_str)
-                .expect("job_id should exist")
-                .to_string()
-        }
-        other => panic!("unexpected build error: {other:?}", other = other),
-    };
+    let _ = client.cancel().await;
+    let _ = server_task.await;
 
-    let inspect_args = json!({
-        "job_id": job_id,
-        "include_log_excerpt": true,
-        "prefer_typecheck": true
-    })
-    .as_object()
-    .expect("object")
-    .clone();
+    let project_dirs: Vec<_> =
+        std::fs::read_dir(derived_data_root.path())?.collect::<std::io::Result<_>>()?;
+    assert_eq!(project_dirs.len(), 1, "one directory per project");
+    let log = std::fs::read_to_string(project_dirs[0].path().join("mock-builds.log"))?;
+    assert_eq!(log.lines().count(), 2, "only opted-in builds use the cache");
+    Ok(())
+}
 
-    let inspect_payload = client
-        .call_tool(CallToolRequestParam {
-            name: "inspect_build_diagnostics".into(),
-            arguments: Some(inspect_args),
+#[tokio::test]
+async fn clean_tool_reports_freed_bytes_and_wipes_derived_data() -> Result<()> {
+    let derived_data_root = tempfile::tempdir()?;
+    let mut config = test_server_config(5);
+    config.visionos.derived_data_root = Some(derived_data_root.path().to_path_buf());
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let call = |name: &'static str, extra: Value| {
+        let mut args = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+            "use_cached_derived_data": true
+        });
+        args.as_object_mut()
+            .expect("JSON object")
+            .extend(extra.as_object().expect("JSON object").clone());
+        client.call_tool(CallToolRequestParam {
+            name: name.into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
         })
-        .await
-        .expect("inspect_build_diagnostics should succeed")
+    };
+
+    call("build_visionos_app", json!({})).await?;
+    let cleaned = call("clean_visionos_project", json!({}))
+        .await?
         .structured_content
-        .expect("structured content");
+        .expect("structured_content should exist");
+    let project_dir = PathBuf::from(
+        cleaned["derived_data_path"]
+            .as_str()
+            .expect("derived_data_path"),
+    );
+    let kept_log = project_dir.join("mock-builds.log").is_file();
+    let wiped = call(
+        "clean_visionos_project",
+        json!({ "wipe_derived_data": true }),
+    )
+    .await?
+    .structured_content
+    .expect("structured_content should exist");
 
     let _ = client.cancel().await;
     let _ = server_task.await;
-    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
-    env::remove_var("MOCK_DIAGNOSTICS_BEHAVIOR");
 
+    assert_eq!(cleaned["status"], json!("succeeded"));
+    assert!(project_dir.starts_with(derived_data_root.path()));
     assert_eq!(
-        inspect_payload.get("availability").and_then(Value::as_str),
-        Some("available")
-    );
-    assert_eq!(
-        inspect_payload
-            .get("failure_summary")
-            .and_then(Value::as_object)
-            .and_then(|summary| summary.get("source"))
-            .and_then(Value::as_str),
-        Some("typecheck")
-    );
-    assert_eq!(
-        inspect_payload
-            .get("primary_location")
-            .and_then(Value::as_object)
-            .and_then(|location| location.get("line"))
-            .and_then(Value::as_u64),
-        Some(105)
+        cleaned["freed_bytes"],
+        json!("intermediate build products".len())
     );
+    assert_eq!(cleaned["derived_data_wiped"], json!(false));
+    assert!(kept_log, "clean keeps the rest of DerivedData");
+    assert_eq!(wiped["derived_data_wiped"], json!(true));
+    assert_eq!(wiped["freed_bytes"], json!("build\n".len()));
+    assert!(!project_dir.exists());
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_build_diagnostics_falls_back_when_typecheck_unavailable() -> Result<()> {
-    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
-    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK
-        .lock()
-        .expect("diagnostics env lock should not be poisoned");
+async fn memory_persistence_keeps_artifacts_in_a_temp_dir_removed_at_shutdown() -> Result<()> {
     enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
-    env::set_var("MOCK_DIAGNOSTICS_BEHAVIOR", "typecheck_unavailable");
-    let config = test_server_config(20);
+    let mut config = test_server_config(20);
+    config.visionos.artifact_persistence = ArtifactPersistence::Memory;
     let server = build_server(config);
+    let artifact_store = server.artifact_store();
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
     let server_task = tokio::spawn(async move {
@@ -492,78 +693,67 @@ async fn inspect_build_diagnostics_falls_back_when_typecheck_unavailable() -> Re
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let build_args = json!({
+    let args = json!({
         "project_path": allowed_project_path().to_string_lossy(),
         "scheme": "VisionApp",
-        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
-        "env_overrides": {
-            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
-        }
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro"
     })
     .as_object()
     .expect("JSON object")
     .clone();
 
-    let build_result = client
+    let build = client
         .call_tool(CallToolRequestParam {
             name: "build_visionos_app".into(),
-            arguments: Some(build_args),
+            arguments: Some(args),
         })
-        .await;
-    let job_id = match build_result.expect_err("build should fail") {
-        ServiceError::McpError(inner) => error_field(&inner, "job_id")
-            .and_then(Value::as_str)
-            .expect("job_id")
-            .to_string(),
-        other => panic!("unexpected build error: {other:?}", other = other),
-    };
-
-    let inspect_args = json!({
-        "job_id": job_id,
-        "include_log_excerpt": true,
-        "prefer_typecheck": true
-    })
-    .as_object()
-    .expect("object")
-    .clone();
-    let inspect_payload = client
+        .await
+        .expect("build should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+    let job_id = build["job_id"].as_str().expect("job_id").to_string();
+    let fetch = client
         .call_tool(CallToolRequestParam {
-            name: "inspect_build_diagnostics".into(),
-            arguments: Some(inspect_args),
+            name: "fetch_build_output".into(),
+            arguments: Some(
+                json!({ "job_id": job_id })
+                    .as_object()
+                    .expect("JSON object")
+                    .clone(),
+            ),
         })
         .await
-        .expect("inspect_build_diagnostics should succeed")
+        .expect("fetch should succeed")
         .structured_content
-        .expect("structured content");
+        .expect("structured_content should exist");
 
     let _ = client.cancel().await;
     let _ = server_task.await;
-    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
-    env::remove_var("MOCK_DIAGNOSTICS_BEHAVIOR");
 
-    assert_eq!(
-        inspect_payload.get("availability").and_then(Value::as_str),
-        Some("unavailable")
+    assert_eq!(fetch["storage_backend"]["kind"], json!("memory"));
+    let root = artifact_store.root_dir();
+    assert!(root.starts_with(env::temp_dir()));
+    let job_dir = root.join(&job_id);
+    assert!(
+        PathBuf::from(fetch["artifact_zip"].as_str().expect("artifact_zip")).starts_with(&job_dir)
     );
-    assert_eq!(
-        inspect_payload
-            .get("failure_summary")
-            .and_then(Value::as_object)
-            .and_then(|summary| summary.get("source"))
-            .and_then(Value::as_str),
-        Some("xcodebuild_log")
+    assert!(
+        !job_dir.join("job.json").exists(),
+        "records are never written to disk"
     );
+    artifact_store.discard();
+    assert!(!root.exists());
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_build_diagnostics_rejects_invalid_job_id() -> Result<()> {
-    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK
-        .lock()
-        .expect("diagnostics env lock should not be poisoned");
+async fn builds_write_where_a_fallback_artifact_root_points() -> Result<()> {
     enable_fast_timeout();
-    let config = test_server_config(20);
-    let server = build_server(config);
+    let temp = tempfile::tempdir()?;
+    // Stands in for the temp-dir fallback used when `target/visionos-builds` is not writable.
+    let fallback = temp.path().join("seiro-mcp/visionos-builds");
+    let server = build_server(test_server_config(20))
+        .with_artifact_store(VisionOsArtifactStore::with_root(fallback.clone(), 600, 60));
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
     let server_task = tokio::spawn(async move {
@@ -571,41 +761,76 @@ async fn inspect_build_diagnostics_rejects_invalid_job_id() -> Result<()> {
         Result::<_, anyhow::Error>::Ok(())
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
-
-    let inspect_args = json!({
-        "job_id": "invalid",
-        "include_log_excerpt": true
-    })
-    .as_object()
-    .expect("object")
-    .clone();
-    let result = client
-        .call_tool(CallToolRequestParam {
-            name: "inspect_build_diagnostics".into(),
-            arguments: Some(inspect_args),
+    let call = |name: &str, arguments: Value| {
+        client.call_tool(CallToolRequestParam {
+            name: name.to_string().into(),
+            arguments: arguments.as_object().cloned(),
         })
-        .await;
+    };
+
+    let build = call(
+        "build_visionos_app",
+        json!({
+            "project_path": allowed_project_path(),
+            "scheme": "VisionApp"
+        }),
+    )
+    .await
+    .expect("build should succeed")
+    .structured_content
+    .expect("structured_content should exist");
+    let job_id = build["job_id"].as_str().expect("job_id").to_string();
+    let fetch = call("fetch_build_output", json!({ "job_id": job_id }))
+        .await
+        .expect("fetch should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+    let log = call("fetch_build_log", json!({ "job_id": job_id }))
+        .await
+        .expect("fetch_build_log should succeed")
+        .structured_content
+        .expect("structured_content should exist");
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = result.expect_err("invalid job id should fail");
-    match error {
-        ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "invalid_job_id", "no_violation", false)
-        }
-        other => panic!("unexpected error: {other:?}", other = other),
+    let job_dir = fallback.join(&job_id);
+    for path in [&build["artifact_path"], &fetch["artifact_zip"]] {
+        let path = PathBuf::from(path.as_str().expect("artifact path"));
+        assert!(
+            path.starts_with(&job_dir),
+            "{path:?} is outside {job_dir:?}"
+        );
+        assert!(path.is_file(), "{path:?} was never written");
     }
+    assert!(job_dir.join("job.json").is_file());
+    let log_path = PathBuf::from(log["path"].as_str().expect("log path"));
+    assert!(
+        log_path.starts_with(&job_dir),
+        "{log_path:?} is outside {job_dir:?}"
+    );
+    assert!(!log["text"].as_str().unwrap_or_default().is_empty());
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_build_diagnostics_rejects_unknown_job() -> Result<()> {
-    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK
-        .lock()
-        .expect("diagnostics env lock should not be poisoned");
-    enable_fast_timeout();
-    let config = test_server_config(20);
+async fn build_tool_reports_compilation_cache_statistics() -> Result<()> {
+    let state = tempfile::tempdir()?;
+    std::fs::write(state.path().join("hits"), "5")?;
+    std::fs::write(state.path().join("misses"), "2")?;
+    let mock_sccache =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/visionos/mock-sccache.sh");
+    let mut config = test_server_config(5);
+    config.visionos.compilation_cache = Some(CompilationCache {
+        mode: CompilationCacheMode::Sccache {
+            sccache_path: mock_sccache.clone(),
+            compiler_wrapper: mock_sccache,
+        },
+        env: BTreeMap::from([(
+            "MOCK_SCCACHE_STATE".to_string(),
+            state.path().to_string_lossy().into_owned(),
+        )]),
+    });
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -615,43 +840,38 @@ async fn inspect_build_diagnostics_rejects_unknown_job() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let inspect_args = json!({ "job_id": Uuid::new_v4().to_string() })
-        .as_object()
-        .expect("object")
-        .clone();
-    let result = client
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    let response = client
         .call_tool(CallToolRequestParam {
-            name: "inspect_build_diagnostics".into(),
-            arguments: Some(inspect_args),
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
         })
         .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = result.expect_err("unknown job should fail");
-    match error {
-        ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "job_not_found", "no_violation", false)
-        }
-        other => panic!("unexpected error: {other:?}", other = other),
-    }
+    let payload = response?
+        .structured_content
+        .expect("structured_content should exist");
+    assert_eq!(
+        payload.get("compilation_cache"),
+        Some(&json!({ "hits": 1, "misses": 0 })),
+        "only the counts added during the build are reported"
+    );
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_build_diagnostics_reports_expired_context() -> Result<()> {
-    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
-    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK
-        .lock()
-        .expect("diagnostics env lock should not be poisoned");
-    enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
-    env::set_var("MOCK_DIAGNOSTICS_BEHAVIOR", "typecheck_error");
-    let config = test_server_config_with_ttl(20, 1);
-    let server = build_server(config);
+async fn build_tool_uses_the_platform_default_destination() -> Result<()> {
+    let server = build_server(test_server_config(5));
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
     let server_task = tokio::spawn(async move {
@@ -660,71 +880,50 @@ async fn inspect_build_diagnostics_reports_expired_context() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let build_args = json!({
+    let args = json!({
         "project_path": allowed_project_path().to_string_lossy(),
         "scheme": "VisionApp",
-        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
-        "env_overrides": {
-            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
-        }
+        "platform": "ios"
     })
     .as_object()
     .expect("JSON object")
     .clone();
-    let build_result = client
+    let response = client
         .call_tool(CallToolRequestParam {
             name: "build_visionos_app".into(),
-            arguments: Some(build_args),
-        })
-        .await;
-    let job_id = match build_result.expect_err("build should fail") {
-        ServiceError::McpError(inner) => error_field(&inner, "job_id")
-            .and_then(Value::as_str)
-            .expect("job_id")
-            .to_string(),
-        other => panic!("unexpected build error: {other:?}", other = other),
-    };
-
-    tokio::time::sleep(Duration::from_secs(2)).await;
-
-    let inspect_args = json!({ "job_id": job_id })
-        .as_object()
-        .expect("object")
-        .clone();
-    let inspect_result = client
-        .call_tool(CallToolRequestParam {
-            name: "inspect_build_diagnostics".into(),
-            arguments: Some(inspect_args),
+            arguments: Some(args),
         })
         .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
-    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
-    env::remove_var("MOCK_DIAGNOSTICS_BEHAVIOR");
 
-    let error = inspect_result.expect_err("expired diagnostics should fail");
-    match error {
-        ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "diagnostics_expired", "no_violation", true);
-        }
-        other => panic!("unexpected error: {other:?}", other = other),
-    }
+    let payload = response?
+        .structured_content
+        .expect("structured_content should exist");
+    let log_excerpt = payload
+        .get("log_excerpt")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    assert!(
+        log_excerpt.contains("-destination platform=iOS Simulator,name=iPhone 16"),
+        "{log_excerpt}"
+    );
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_build_diagnostics_is_repeatable_for_same_job_id() -> Result<()> {
-    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
-    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK
-        .lock()
-        .expect("diagnostics env lock should not be poisoned");
-    enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
-    env::set_var("MOCK_DIAGNOSTICS_BEHAVIOR", "typecheck_error");
-    let config = test_server_config(20);
+async fn build_tool_expands_config_presets() -> Result<()> {
+    let mut config = test_server_config(5);
+    config.visionos.presets.insert(
+        "release-sim".into(),
+        BuildPreset {
+            scheme: Some("VisionApp".into()),
+            configuration: Some("release".into()),
+            destination: Some("platform=visionOS Simulator,name=Apple Vision Pro".into()),
+            extra_args: vec!["-quiet".into()],
+        },
+    );
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -733,87 +932,4106 @@ async fn inspect_build_diagnostics_is_repeatable_for_same_job_id() -> Result<()>
         Result::<_, anyhow::Error>::Ok(())
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let call = |preset: &str| {
+        let args = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "preset": preset
+        });
+        client.call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
+        })
+    };
 
-    let build_args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
+    let built = call("release-sim").await;
+    let unknown = call("profile").await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let payload = built?
+        .structured_content
+        .expect("structured_content should exist");
+    assert_eq!(payload["status"], json!("succeeded"));
+    let log_excerpt = payload["log_excerpt"].as_str().unwrap_or_default();
+    assert!(
+        log_excerpt.contains("-scheme VisionApp -configuration Release"),
+        "{log_excerpt}"
+    );
+    assert!(log_excerpt.contains("-quiet"), "{log_excerpt}");
+
+    let ServiceError::McpError(inner) = unknown.expect_err("unknown preset is rejected") else {
+        panic!("expected an MCP error");
+    };
+    assert_error_metadata(&inner, "invalid_request", "no_violation", false);
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_retries_transient_simulator_failures() -> Result<()> {
+    let mut config = test_server_config(5);
+    config.visionos.retry = RetryPolicy {
+        max_attempts: 3,
+        backoff: Duration::ZERO,
+        ..RetryPolicy::default()
+    };
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let call = |behavior: &str| {
+        let args = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "env_overrides": { "MOCK_XCODEBUILD_BEHAVIOR": behavior }
+        });
+        client.call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
+        })
+    };
+
+    let flaky = call("simulator_boot_flaky").await;
+    let broken = call("simulator_boot_fail").await;
+    let jobs = client
+        .call_tool(CallToolRequestParam {
+            name: "list_build_jobs".into(),
+            arguments: Some(json!({}).as_object().expect("JSON object").clone()),
+        })
+        .await?
+        .structured_content
+        .expect("structured_content should exist");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let payload = flaky?
+        .structured_content
+        .expect("structured_content should exist");
+    assert_eq!(payload["status"], json!("succeeded"));
+    let reasons: Vec<&Value> = payload["attempts"]
+        .as_array()
+        .expect("attempts array")
+        .iter()
+        .map(|attempt| &attempt["retry_reason"])
+        .collect();
+    assert_eq!(reasons, vec![&json!("simulator_boot"), &Value::Null]);
+
+    let ServiceError::McpError(inner) = broken.expect_err("every attempt fails") else {
+        panic!("expected an MCP error");
+    };
+    assert_error_metadata(&inner, "build_failed", "no_violation", true);
+    let recorded_attempts = |job_id: &Value| {
+        jobs["jobs"]
+            .as_array()
+            .expect("jobs array")
+            .iter()
+            .find(|job| &job["job_id"] == job_id)
+            .map(|job| job["attempts"].clone())
+    };
+    assert_eq!(recorded_attempts(&payload["job_id"]), Some(json!(2)));
+    assert_eq!(
+        recorded_attempts(error_field(&inner, "job_id").expect("job_id")),
+        Some(json!(3))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_fails_fast_without_enough_free_disk() -> Result<()> {
+    let mut config = test_server_config(5);
+    config.visionos.min_free_disk_bytes = u64::MAX;
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "env_overrides": { "MOCK_XCODEBUILD_BEHAVIOR": "sleep" }
+    });
+    let started = Instant::now();
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let ServiceError::McpError(inner) = result.expect_err("preflight rejects the build") else {
+        panic!("expected an MCP error");
+    };
+    assert_error_metadata(&inner, "disk_insufficient", "blocked", false);
+    assert!(
+        started.elapsed() < Duration::from_secs(2),
+        "xcodebuild never ran"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_coalesces_identical_in_flight_requests() -> Result<()> {
+    let server = build_server(test_server_config(5));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let call = |configuration: &str| {
+        let args = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "configuration": configuration,
+            "env_overrides": { "MOCK_XCODEBUILD_BEHAVIOR": "sleep" }
+        });
+        client.call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
+        })
+    };
+
+    let (first, second, other) = tokio::join!(call("Debug"), call("Debug"), call("Release"));
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let job_id = |result: Result<rmcp::model::CallToolResult, ServiceError>| -> Result<Value> {
+        let payload = result?
+            .structured_content
+            .expect("structured_content should exist");
+        assert_eq!(payload["status"], json!("succeeded"));
+        Ok(payload["job_id"].clone())
+    };
+    let first = job_id(first)?;
+    assert_eq!(job_id(second)?, first, "identical requests share one job");
+    assert_ne!(
+        job_id(other)?,
+        first,
+        "a different configuration runs its own job"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_runs_the_selected_xcode() -> Result<()> {
+    let mut config = test_server_config(5);
+    let beta = PathBuf::from("/Applications/Xcode-beta.app/Contents/Developer");
+    config.visionos.xcodes.insert("beta".into(), beta.clone());
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let call = |xcode: &str| {
+        let args = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "xcode": xcode
+        });
+        client.call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
+        })
+    };
+
+    let built = call("beta")
+        .await?
+        .structured_content
+        .expect("structured_content should exist");
+    let trace = client
+        .call_tool(CallToolRequestParam {
+            name: "export_job_trace".into(),
+            arguments: Some(
+                json!({ "job_id": built["job_id"], "format": "json" })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await
+        .expect("export_job_trace should succeed")
+        .structured_content
+        .expect("structured_content");
+    let unknown = call("Xcode-15").await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(built["status"], json!("succeeded"));
+    let bundle: Value =
+        serde_json::from_slice(&std::fs::read(trace["path"].as_str().expect("trace path"))?)?;
+    assert_eq!(
+        bundle.pointer("/trace/environment/xcode_path"),
+        Some(&json!(beta.to_string_lossy()))
+    );
+
+    let ServiceError::McpError(inner) = unknown.expect_err("unknown xcode is rejected") else {
+        panic!("expected an MCP error");
+    };
+    assert_error_metadata(&inner, "invalid_request", "no_violation", false);
+    Ok(())
+}
+
+#[tokio::test]
+async fn reloaded_allowlist_applies_to_the_open_session() -> Result<()> {
+    let config = test_server_config(5);
+    let mut reloaded = config.clone();
+    reloaded.visionos.allowed_schemes = vec![SchemePattern::new("OtherApp").expect("valid scheme")];
+    let server = build_server(config);
+    let shared_config = server.shared_config();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let build = || {
+        let args = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp"
+        });
+        client.call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
+        })
+    };
+
+    let before = build().await;
+    let restart_required = shared_config.replace(reloaded);
+    let after = build().await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert!(restart_required.is_empty());
+    assert!(before.is_ok(), "{before:?}");
+    let ServiceError::McpError(inner) = after.expect_err("VisionApp left the allowlist") else {
+        panic!("expected an MCP error");
+    };
+    assert_error_metadata(&inner, "scheme_not_allowed", "blocked", false);
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_honours_request_timeout_minutes() -> Result<()> {
+    enable_fast_timeout();
+    let server = build_server(test_server_config(20));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let build_args = |timeout_minutes: u16| {
+        json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+            "timeout_minutes": timeout_minutes,
+            "env_overrides": {
+                "MOCK_XCODEBUILD_BEHAVIOR": "sleep"
+            }
+        })
+        .as_object()
+        .expect("JSON object")
+        .clone()
+    };
+    let short = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(build_args(1)),
+        })
+        .await;
+    let above_ceiling = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(build_args(21)),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    match short.expect_err("1 minute timeout should cut the 2s mock build short") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "timeout", "no_violation", true);
+            assert_eq!(
+                error_field(&inner, "details")
+                    .and_then(|details| details.get("duration_secs").cloned())
+                    .and_then(|value| value.as_u64()),
+                Some(1)
+            );
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    match above_ceiling.expect_err("timeout above max_timeout_minutes should be rejected") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "invalid_request", "no_violation", false);
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_times_out_when_process_exceeds_deadline() -> Result<()> {
+    enable_fast_timeout();
+    let config = test_server_config(1);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
         "scheme": "VisionApp",
         "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
         "env_overrides": {
-            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
+            "MOCK_XCODEBUILD_BEHAVIOR": "sleep"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let error = call_result.expect_err("should return an error on timeout");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "timeout", "no_violation", true);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn timed_out_builds_are_posted_to_notification_webhooks() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    enable_fast_timeout();
+    let capture = tempfile::tempdir()?;
+    let mock_curl = capture.path().join("curl");
+    std::fs::write(
+        &mock_curl,
+        format!(
+            "#!/bin/sh\nprintf '%s\\n' \"$@\" > '{dir}/args'\ncat > '{dir}/config.tmp' && mv '{dir}/config.tmp' '{dir}/config'\n",
+            dir = capture.path().display()
+        ),
+    )?;
+    std::fs::set_permissions(&mock_curl, std::fs::Permissions::from_mode(0o755))?;
+    let mut config = test_server_config(1);
+    config.notifications.webhooks = vec!["https://hooks.example.test/seiro".into()];
+    config.notifications.secret = Some("webhook-signing-secret".into());
+    config.notifications.curl_path = mock_curl;
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "sleep"
         }
     })
     .as_object()
     .expect("JSON object")
     .clone();
-    let build_result = client
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await;
+    assert!(call_result.is_err(), "the build should time out");
+
+    let config_path = capture.path().join("config");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !config_path.exists() && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let curl_config = std::fs::read_to_string(&config_path)?;
+    let quoted_body = curl_config
+        .lines()
+        .find_map(|line| line.strip_prefix("data-binary = "))
+        .expect("body in the curl config");
+    let body: Value = serde_json::from_str(&unquote_curl_config(quoted_body))?;
+    assert_eq!(body["event"], json!("timed_out"));
+    assert_eq!(body["status"], json!("failed"));
+    assert_eq!(body["error_code"], json!("timeout"));
+    assert_eq!(body["scheme"], json!("VisionApp"));
+    assert!(body["job_id"].as_str().is_some());
+    assert!(curl_config.contains("header = \"X-Seiro-Event: timed_out\""));
+    assert!(curl_config.contains("header = \"X-Seiro-Signature: sha256="));
+    assert!(curl_config.starts_with("url = \"https://hooks.example.test/seiro\"\n"));
+    let curl_args = std::fs::read_to_string(capture.path().join("args"))?;
+    assert!(curl_args.ends_with("--config\n-\n"));
+    assert!(!curl_args.contains("hooks.example.test"));
+    assert!(!curl_args.contains("sha256="));
+    Ok(())
+}
+
+/// Value of a double-quoted `curl` config entry.
+fn unquote_curl_config(quoted: &str) -> String {
+    let inner = quoted
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .expect("quoted value");
+    let mut value = String::new();
+    let mut chars = inner.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            value.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some('t') => value.push('\t'),
+            Some(other) => value.push(other),
+            None => {}
+        }
+    }
+    value
+}
+
+#[tokio::test]
+async fn build_tool_warns_before_deadline_with_current_phase() -> Result<()> {
+    enable_fast_timeout();
+    let config = test_server_config(1);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let (sender, mut messages) = tokio::sync::mpsc::unbounded_channel();
+    let client = serve_client(LoggingCollector { sender }, client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "sleep"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert!(call_result.is_err(), "build should still time out");
+    let warning = tokio::time::timeout(Duration::from_secs(1), messages.recv())
+        .await
+        .ok()
+        .flatten()
+        .expect("timeout warning should be sent before the timeout error");
+    assert_eq!(warning.logger.as_deref(), Some("seiro_mcp::visionos"));
+    assert_eq!(
+        warning.data.get("event").and_then(Value::as_str),
+        Some("timeout_warning")
+    );
+    assert_eq!(
+        warning.data.get("percent").and_then(Value::as_u64),
+        Some(80)
+    );
+    assert_eq!(
+        warning.data.get("deadline_secs").and_then(Value::as_u64),
+        Some(1)
+    );
+    assert_eq!(
+        warning.data.get("phase").and_then(Value::as_str),
+        Some("compile")
+    );
+    assert!(warning.data.get("job_id").and_then(Value::as_str).is_some());
+    assert!(messages.try_recv().is_err(), "warning is sent only once");
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_sends_heartbeats_with_elapsed_time_and_phase() -> Result<()> {
+    enable_fast_timeout();
+    let mut config = test_server_config(20);
+    config.visionos.heartbeat_interval_secs = 1;
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let (sender, mut progress) = tokio::sync::mpsc::unbounded_channel();
+    let client = serve_client(ProgressCollector { sender }, client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "sleep"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let _ = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let heartbeat = tokio::time::timeout(Duration::from_secs(1), progress.recv())
+        .await
+        .ok()
+        .flatten()
+        .expect("a heartbeat should be sent while xcodebuild runs");
+    assert!(heartbeat.progress >= 1.0);
+    assert_eq!(heartbeat.total, None);
+    assert!(heartbeat
+        .message
+        .as_deref()
+        .is_some_and(|message| message.starts_with("compile (")));
+    Ok(())
+}
+
+#[tokio::test]
+async fn cancel_tool_stops_queued_and_running_jobs() -> Result<()> {
+    enable_fast_timeout();
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let running_id = Uuid::new_v4();
+    let queued_id = Uuid::new_v4();
+    let spawn_build = |job_id: Uuid| {
+        let peer = client.peer().clone();
+        let args = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+            "env_overrides": {
+                "MOCK_XCODEBUILD_BEHAVIOR": "sleep"
+            },
+            "job_id": job_id.to_string()
+        })
+        .as_object()
+        .expect("JSON object")
+        .clone();
+        tokio::spawn(async move {
+            peer.call_tool(CallToolRequestParam {
+                name: "build_visionos_app".into(),
+                arguments: Some(args),
+            })
+            .await
+        })
+    };
+    let cancel = |job_id: Uuid| {
+        client.call_tool(CallToolRequestParam {
+            name: "cancel_build_job".into(),
+            arguments: Some(
+                json!({ "job_id": job_id.to_string() })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+    };
+
+    let running_build = spawn_build(running_id);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let queued_build = spawn_build(queued_id);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let queued_cancel = cancel(queued_id).await.expect("cancel queued job");
+    let running_cancel = cancel(running_id).await.expect("cancel running job");
+    let running_result = running_build.await?;
+    let queued_result = queued_build.await?;
+    let fetch_result = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: Some(
+                json!({ "job_id": running_id.to_string() })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await;
+    let inactive_result = cancel(running_id).await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let state = |response: rmcp::model::CallToolResult| {
+        response.structured_content.and_then(|payload| {
+            payload
+                .get("state")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+    };
+    assert_eq!(state(queued_cancel).as_deref(), Some("cancelled_queued"));
+    assert_eq!(state(running_cancel).as_deref(), Some("cancelled_running"));
+    for (result, expected) in [
+        (queued_result, "cancelled_queued"),
+        (running_result, "cancelled_running"),
+    ] {
+        match result.expect_err("cancelled build should return an error") {
+            ServiceError::McpError(inner) => {
+                assert_error_metadata(&inner, "job_cancelled", "no_violation", false);
+                assert_eq!(
+                    error_field(&inner, "details")
+                        .and_then(|details| details.get("state"))
+                        .and_then(Value::as_str),
+                    Some(expected)
+                );
+            }
+            other => panic!("unexpected error: {other:?}", other = other),
+        }
+    }
+    match fetch_result.expect_err("cancelled job has no artifacts") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "job_cancelled", "no_violation", false);
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    match inactive_result.expect_err("finished job cannot be cancelled") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "job_not_active", "no_violation", false);
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn extend_deadline_tool_lets_running_build_finish() -> Result<()> {
+    enable_fast_timeout();
+    let mut config = test_server_config(1);
+    config.visionos.max_extended_build_minutes = 3;
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let job_id = Uuid::new_v4();
+    let build = {
+        let peer = client.peer().clone();
+        let args = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+            "env_overrides": {
+                "MOCK_XCODEBUILD_BEHAVIOR": "sleep"
+            },
+            "job_id": job_id.to_string()
+        })
+        .as_object()
+        .expect("JSON object")
+        .clone();
+        tokio::spawn(async move {
+            peer.call_tool(CallToolRequestParam {
+                name: "build_visionos_app".into(),
+                arguments: Some(args),
+            })
+            .await
+        })
+    };
+    let extend = |minutes: u16| {
+        client.call_tool(CallToolRequestParam {
+            name: "extend_build_deadline".into(),
+            arguments: Some(
+                json!({ "job_id": job_id.to_string(), "minutes": minutes })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+    };
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let extended = extend(2).await.expect("extend running job");
+    let over_limit = extend(1).await;
+    let build_result = build.await?;
+    let finished = extend(1).await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let payload = extended
+        .structured_content
+        .expect("structured_content should exist");
+    assert_eq!(
+        payload.get("deadline_secs").and_then(Value::as_u64),
+        Some(3)
+    );
+    assert_eq!(
+        payload.get("max_deadline_secs").and_then(Value::as_u64),
+        Some(3)
+    );
+    build_result.expect("extended build should outlast the original deadline");
+    match over_limit.expect_err("extension beyond the cap must fail") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "deadline_limit_exceeded", "no_violation", false);
+            assert_eq!(
+                error_field(&inner, "details")
+                    .and_then(|details| details.get("deadline_secs"))
+                    .and_then(Value::as_u64),
+                Some(3)
+            );
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    match finished.expect_err("finished job cannot be extended") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "job_not_running", "no_violation", true);
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn extend_deadline_tool_is_disabled_by_default() -> Result<()> {
+    let server = build_server(test_server_config(20));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "extend_build_deadline".into(),
+            arguments: Some(
+                json!({ "job_id": Uuid::new_v4().to_string(), "minutes": 5 })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    match call_result.expect_err("extensions are off without max_extended_build_minutes") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "deadline_extension_disabled", "no_violation", false);
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_slots_run_independent_projects_in_parallel() -> Result<()> {
+    enable_fast_timeout();
+    let mut config = test_server_config(20);
+    config.visionos.max_concurrent_builds = 2;
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let spawn_build = |project_path: PathBuf| {
+        let peer = client.peer().clone();
+        let args = json!({
+            "project_path": project_path.to_string_lossy(),
+            "scheme": "VisionApp",
+            "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+            "env_overrides": {
+                "MOCK_XCODEBUILD_BEHAVIOR": "sleep"
+            }
+        })
+        .as_object()
+        .expect("JSON object")
+        .clone();
+        tokio::spawn(async move {
+            peer.call_tool(CallToolRequestParam {
+                name: "build_visionos_app".into(),
+                arguments: Some(args),
+            })
+            .await
+        })
+    };
+
+    let start = Instant::now();
+    let first = spawn_build(allowed_project_path());
+    let second = spawn_build(allowed_project_xcodeproj_path());
+    let first_result = first.await?;
+    let second_result = second.await?;
+    let elapsed = start.elapsed();
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    first_result.expect("first build succeeds");
+    second_result.expect("second build succeeds");
+    assert!(
+        elapsed < Duration::from_millis(3_500),
+        "two 2s builds on separate roots should overlap, took {elapsed:?}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn queue_status_orders_interactive_jobs_before_batch_jobs() -> Result<()> {
+    enable_fast_timeout();
+    let server = build_server(test_server_config(20));
+    let queue = server.job_queue();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let running = Uuid::new_v4();
+    let batch = Uuid::new_v4();
+    let interactive = Uuid::new_v4();
+    let mut builds = Vec::new();
+    for (job_id, priority) in [
+        (running, "interactive"),
+        (batch, "batch"),
+        (interactive, "interactive"),
+    ] {
+        let peer = client.peer().clone();
+        let args = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+            "job_id": job_id.to_string(),
+            "priority": priority,
+            "env_overrides": {
+                "MOCK_XCODEBUILD_BEHAVIOR": "sleep"
+            }
+        })
+        .as_object()
+        .expect("JSON object")
+        .clone();
+        builds.push(tokio::spawn(async move {
+            peer.call_tool(CallToolRequestParam {
+                name: "build_visionos_app".into(),
+                arguments: Some(args),
+            })
+            .await
+        }));
+        while !queue.contains(job_id).await {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    let status = client
+        .call_tool(CallToolRequestParam {
+            name: "get_queue_status".into(),
+            arguments: Some(serde_json::Map::new()),
+        })
+        .await;
+
+    for job_id in [batch, interactive, running] {
+        queue.cancel(job_id).await;
+    }
+    for build in builds {
+        let _ = build.await?;
+    }
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let payload = status?.structured_content.expect("structured content");
+    assert_eq!(
+        payload.pointer("/running/0/job_id").and_then(Value::as_str),
+        Some(running.to_string().as_str())
+    );
+    let queued: Vec<(String, u64, String)> = payload
+        .get("queued")
+        .and_then(Value::as_array)
+        .expect("queued jobs")
+        .iter()
+        .map(|job| {
+            (
+                job["job_id"].as_str().unwrap_or_default().to_string(),
+                job["position"].as_u64().unwrap_or_default(),
+                job["priority"].as_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        queued,
+        vec![
+            (interactive.to_string(), 1, "interactive".to_string()),
+            (batch.to_string(), 2, "batch".to_string()),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn queue_status_estimates_durations_from_earlier_builds_of_the_scheme() -> Result<()> {
+    enable_fast_timeout();
+    let mut config = test_server_config(20);
+    // A private store, so history left by other tests does not skew the estimate.
+    config.visionos.artifact_persistence = ArtifactPersistence::Memory;
+    let server = build_server(config);
+    let queue = server.job_queue();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let build_args = |behavior: &str, job_id: Uuid| {
+        json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "job_id": job_id.to_string(),
+            "env_overrides": { "MOCK_XCODEBUILD_BEHAVIOR": behavior }
+        })
+        .as_object()
+        .cloned()
+    };
+
+    let finished = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: build_args("success", Uuid::new_v4()),
+        })
+        .await;
+    let running = Uuid::new_v4();
+    let peer = client.peer().clone();
+    let args = build_args("sleep", running);
+    let build = tokio::spawn(async move {
+        peer.call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: args,
+        })
+        .await
+    });
+    while !queue.contains(running).await {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let status = client
+        .call_tool(CallToolRequestParam {
+            name: "get_queue_status".into(),
+            arguments: Some(serde_json::Map::new()),
+        })
+        .await;
+
+    queue.cancel(running).await;
+    let _ = build.await?;
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let finished = finished
+        .expect("first build succeeds")
+        .structured_content
+        .expect("structured content");
+    let payload = status?.structured_content.expect("structured content");
+    let estimate = payload
+        .pointer("/running/0/estimated_duration_ms")
+        .and_then(Value::as_u64)
+        .expect("running job has an estimate");
+    assert_eq!(Some(estimate), finished["duration_ms"].as_u64());
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_refuses_jobs_while_draining_for_shutdown() -> Result<()> {
+    let server = build_server(test_server_config(20));
+    server.job_queue().close();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    match call_result.expect_err("closed queue should refuse new builds") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "server_shutting_down", "no_violation", true);
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_rejects_path_outside_allowlist() -> Result<()> {
+    enable_fast_timeout();
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": PathBuf::from("/tmp/disallowed-project").to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let error = call_result.expect_err("disallowed path should return an error");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "path_not_allowed", "blocked", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_rejects_provisioning_profile_outside_allowlist() -> Result<()> {
+    enable_fast_timeout();
+    let mut config = test_server_config(20);
+    config.visionos.signing.allowed_provisioning_profiles = vec!["VisionApp Development".into()];
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "provisioning_profile": "Enterprise Wildcard"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    match call_result.expect_err("profile outside the allowlist should be rejected") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "provisioning_profile_not_allowed", "blocked", false);
+            assert_eq!(
+                error_field(&inner, "details")
+                    .and_then(|details| details.get("provisioning_profile"))
+                    .and_then(Value::as_str),
+                Some("Enterprise Wildcard")
+            );
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_tool_rejects_device_outside_allowlist() -> Result<()> {
+    enable_fast_timeout();
+    let mut config = test_server_config(20);
+    config.visionos.allowed_device_udids = vec!["00008112-001A2C3E0123401E".into()];
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS,id=00008112-0009876543210ABC"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    match call_result.expect_err("device outside the allowlist should be rejected") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "device_not_allowed", "blocked", false);
+            assert_eq!(
+                error_field(&inner, "details")
+                    .and_then(|details| details.get("udid"))
+                    .and_then(Value::as_str),
+                Some("00008112-0009876543210ABC")
+            );
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn fetch_tool_returns_artifact_metadata() -> Result<()> {
+    enable_fast_timeout();
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "success"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let build_response = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("build_visionos_app should succeed");
+    let build_payload = build_response
+        .structured_content
+        .expect("structured_content");
+    let job_id = build_payload
+        .get("job_id")
+        .and_then(|v| v.as_str())
+        .expect("job_id")
+        .to_string();
+
+    let fetch_args = json!({
+        "job_id": job_id.clone(),
+        "include_logs": true
+    })
+    .as_object()
+    .expect("object")
+    .clone();
+
+    let fetch_response = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: Some(fetch_args),
+        })
+        .await
+        .expect("fetch_build_output should succeed");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let payload = fetch_response
+        .structured_content
+        .expect("structured_content");
+    assert_eq!(
+        payload.get("status").and_then(|v| v.as_str()),
+        Some("succeeded")
+    );
+    assert_eq!(
+        payload.get("job_id").and_then(|v| v.as_str()),
+        Some(job_id.as_str())
+    );
+    assert_eq!(
+        payload.get("artifact_kind").and_then(|v| v.as_str()),
+        Some("build")
+    );
+    assert!(
+        payload
+            .get("artifact_zip")
+            .and_then(|v| v.as_str())
+            .is_some(),
+        "artifact_zip should be present"
+    );
+    assert!(
+        payload.get("sha256").and_then(|v| v.as_str()).is_some(),
+        "sha256 should be present"
+    );
+    assert!(
+        payload
+            .get("download_ttl_seconds")
+            .and_then(|v| v.as_u64())
+            .is_some(),
+        "download_ttl_seconds should be present"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn archive_tool_registers_archive_artifact_kind() -> Result<()> {
+    enable_fast_timeout();
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "configuration": "release",
+        "destination": "generic/platform=visionOS",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "success"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let archive_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "archive_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("archive_visionos_app should succeed")
+        .structured_content
+        .expect("structured_content");
+    let job_id = archive_payload
+        .get("job_id")
+        .and_then(Value::as_str)
+        .expect("job_id")
+        .to_string();
+
+    let fetch_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: Some(
+                json!({ "job_id": job_id })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await
+        .expect("fetch_build_output should succeed")
+        .structured_content
+        .expect("structured_content");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(
+        archive_payload.get("artifact_kind").and_then(Value::as_str),
+        Some("archive")
+    );
+    assert!(archive_payload
+        .get("artifact_path")
+        .and_then(Value::as_str)
+        .is_some_and(|path| path.ends_with("archive.zip")));
+    assert_eq!(
+        fetch_payload.get("artifact_kind").and_then(Value::as_str),
+        Some("archive")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_ipa_tool_exports_archive_jobs_only() -> Result<()> {
+    enable_fast_timeout();
+    let server = build_server(test_server_config(20));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let mut job_ids = Vec::new();
+    for (tool, destination) in [
+        ("archive_visionos_app", "generic/platform=visionOS"),
+        (
+            "build_visionos_app",
+            "platform=visionOS Simulator,name=Apple Vision Pro",
+        ),
+    ] {
+        let payload = client
+            .call_tool(CallToolRequestParam {
+                name: tool.into(),
+                arguments: Some(
+                    json!({
+                        "project_path": allowed_project_path().to_string_lossy(),
+                        "scheme": "VisionApp",
+                        "configuration": "release",
+                        "destination": destination,
+                        "env_overrides": { "MOCK_XCODEBUILD_BEHAVIOR": "success" }
+                    })
+                    .as_object()
+                    .expect("JSON object")
+                    .clone(),
+                ),
+            })
+            .await
+            .expect("job should succeed")
+            .structured_content
+            .expect("structured_content");
+        job_ids.push(
+            payload
+                .get("job_id")
+                .and_then(Value::as_str)
+                .expect("job_id")
+                .to_string(),
+        );
+    }
+
+    let export_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "export_visionos_ipa".into(),
+            arguments: Some(
+                json!({
+                    "job_id": job_ids[0],
+                    "method": "release-testing",
+                    "team_id": "A1B2C3D4E5",
+                    "provisioning_profiles": { "com.example.VisionApp": "VisionApp Ad Hoc" }
+                })
+                .as_object()
+                .expect("object")
+                .clone(),
+            ),
+        })
+        .await
+        .expect("export_visionos_ipa should succeed")
+        .structured_content
+        .expect("structured_content");
+    let ipa_job_id = export_payload
+        .get("job_id")
+        .and_then(Value::as_str)
+        .expect("job_id")
+        .to_string();
+    let fetch_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: Some(
+                json!({ "job_id": ipa_job_id })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await
+        .expect("fetch_build_output should succeed")
+        .structured_content
+        .expect("structured_content");
+    let not_archive = client
+        .call_tool(CallToolRequestParam {
+            name: "export_visionos_ipa".into(),
+            arguments: Some(
+                json!({ "job_id": job_ids[1] })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await;
+    let bad_team = client
+        .call_tool(CallToolRequestParam {
+            name: "export_visionos_ipa".into(),
+            arguments: Some(
+                json!({ "job_id": job_ids[0], "team_id": "not-a-team" })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(
+        export_payload.get("archive_job_id").and_then(Value::as_str),
+        Some(job_ids[0].as_str())
+    );
+    let ipa_path = export_payload
+        .get("ipa_path")
+        .and_then(Value::as_str)
+        .expect("ipa_path");
+    assert!(ipa_path.ends_with("VisionApp.ipa"));
+    assert!(std::path::Path::new(ipa_path).is_file());
+    assert!(export_payload
+        .get("download_ttl_seconds")
+        .and_then(Value::as_u64)
+        .is_some_and(|ttl| ttl > 0));
+    let options = std::fs::read_to_string(
+        export_payload
+            .get("export_options_plist")
+            .and_then(Value::as_str)
+            .expect("export_options_plist"),
+    )?;
+    assert!(options.contains("<string>release-testing</string>"));
+    assert!(options.contains("<string>manual</string>"));
+    assert_eq!(
+        fetch_payload.get("artifact_kind").and_then(Value::as_str),
+        Some("ipa")
+    );
+    assert_eq!(fetch_payload.get("sha256"), export_payload.get("sha256"));
+    match not_archive.expect_err("build jobs cannot be exported") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "archive_required", "no_violation", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    match bad_team.expect_err("invalid team_id") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "export_options_invalid", "no_violation", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_tool_copies_artifact_logs_and_dsyms_into_allowlisted_directory() -> Result<()> {
+    enable_fast_timeout();
+    let export_root = tempfile::tempdir()?;
+    let mut config = test_server_config(20);
+    config.visionos.export_paths = vec![export_root.path().to_path_buf()];
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "success"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    let build_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("build_visionos_app should succeed")
+        .structured_content
+        .expect("structured_content");
+    let job_id = build_payload
+        .get("job_id")
+        .and_then(Value::as_str)
+        .expect("job_id")
+        .to_string();
+
+    let export_args = json!({
+        "job_id": job_id,
+        "destination_dir": export_root.path().join("nightly").to_string_lossy(),
+        "include_logs": true,
+        "include_dsyms": true
+    })
+    .as_object()
+    .expect("object")
+    .clone();
+    let export_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "export_artifact".into(),
+            arguments: Some(export_args.clone()),
+        })
+        .await
+        .expect("export_artifact should succeed")
+        .structured_content
+        .expect("structured_content");
+    let conflict = client
+        .call_tool(CallToolRequestParam {
+            name: "export_artifact".into(),
+            arguments: Some(export_args),
+        })
+        .await;
+    let outside = client
+        .call_tool(CallToolRequestParam {
+            name: "export_artifact".into(),
+            arguments: Some(
+                json!({ "job_id": job_id, "destination_dir": "/tmp/seiro-export-outside" })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await;
+    let victim_dir = tempfile::tempdir()?;
+    let victim = victim_dir.path().join("victim.zip");
+    std::fs::write(&victim, b"untouched")?;
+    let linked_dir = export_root.path().join("linked");
+    std::fs::create_dir_all(&linked_dir)?;
+    std::os::unix::fs::symlink(&victim, linked_dir.join(format!("{job_id}-artifact.zip")))?;
+    let symlinked = client
+        .call_tool(CallToolRequestParam {
+            name: "export_artifact".into(),
+            arguments: Some(
+                json!({
+                    "job_id": job_id,
+                    "destination_dir": linked_dir.to_string_lossy(),
+                    "overwrite": true
+                })
+                .as_object()
+                .expect("object")
+                .clone(),
+            ),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let kinds: Vec<&str> = export_payload
+        .get("exported")
+        .and_then(Value::as_array)
+        .expect("exported array")
+        .iter()
+        .filter_map(|entry| entry.get("kind").and_then(Value::as_str))
+        .collect();
+    assert_eq!(kinds, vec!["artifact", "log", "dsym"]);
+    let nightly = export_root.path().join("nightly");
+    assert!(nightly.join(format!("{job_id}-artifact.zip")).is_file());
+    let artifact_path = build_payload
+        .get("artifact_path")
+        .and_then(Value::as_str)
+        .expect("artifact_path");
+    let full_log = Path::new(artifact_path)
+        .parent()
+        .expect("job dir")
+        .join("build.log");
+    assert_eq!(
+        std::fs::read(nightly.join(format!("{job_id}-build.log")))?,
+        std::fs::read(full_log)?,
+        "the export carries the full build log, not the excerpt"
+    );
+    assert!(nightly
+        .join(format!("{job_id}-dSYMs"))
+        .join("VisionApp.dSYM")
+        .is_dir());
+    match conflict.expect_err("second export should conflict") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "export_conflict", "no_violation", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    match symlinked.expect_err("symlinked destination file") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "export_path_not_allowed", "blocked", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    assert_eq!(std::fs::read(&victim)?, b"untouched");
+    match outside.expect_err("destination outside export_paths") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "export_path_not_allowed", "blocked", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_job_trace_tool_bundles_failed_job_for_bug_reports() -> Result<()> {
+    enable_fast_timeout();
+    let server = build_server(test_server_config(20));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    let build_error = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect_err("build should fail");
+    let job_id = match build_error {
+        ServiceError::McpError(inner) => error_field(&inner, "job_id")
+            .and_then(Value::as_str)
+            .expect("job_id")
+            .to_string(),
+        other => panic!("Unexpected error: {other:?}", other = other),
+    };
+
+    let mut payloads = Vec::new();
+    for format in ["json", "zip"] {
+        payloads.push(
+            client
+                .call_tool(CallToolRequestParam {
+                    name: "export_job_trace".into(),
+                    arguments: Some(
+                        json!({ "job_id": job_id, "format": format })
+                            .as_object()
+                            .expect("object")
+                            .clone(),
+                    ),
+                })
+                .await
+                .expect("export_job_trace should succeed")
+                .structured_content
+                .expect("structured_content"),
+        );
+    }
+    let unknown = client
+        .call_tool(CallToolRequestParam {
+            name: "export_job_trace".into(),
+            arguments: Some(
+                json!({ "job_id": Uuid::new_v4().to_string() })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(
+        payloads[0].get("job_status").and_then(Value::as_str),
+        Some("failed")
+    );
+    let bundle_path = payloads[0]
+        .get("path")
+        .and_then(Value::as_str)
+        .expect("path");
+    let bundle: Value = serde_json::from_slice(&std::fs::read(bundle_path)?)?;
+    let trace = bundle.get("trace").expect("trace");
+    assert_eq!(
+        trace.pointer("/request/scheme").and_then(Value::as_str),
+        Some("VisionApp")
+    );
+    assert_eq!(
+        trace.pointer("/error/data/code").and_then(Value::as_str),
+        Some("build_failed")
+    );
+    assert_eq!(
+        trace.pointer("/environment/locale").and_then(Value::as_str),
+        Some("en_US.UTF-8")
+    );
+    assert_eq!(
+        trace
+            .pointer("/environment/env/LANG")
+            .and_then(Value::as_str),
+        Some("en_US.UTF-8")
+    );
+    for pointer in [
+        "/request/env_overrides/MOCK_XCODEBUILD_BEHAVIOR",
+        "/environment/env/MOCK_XCODEBUILD_BEHAVIOR",
+    ] {
+        assert_eq!(
+            trace.pointer(pointer).and_then(Value::as_str),
+            Some("[redacted]"),
+            "{pointer} should be masked"
+        );
+    }
+    let stored_trace = std::path::Path::new(bundle_path).with_file_name("trace.json");
+    let stored_trace: Value = serde_json::from_slice(&std::fs::read(stored_trace)?)?;
+    assert_eq!(
+        stored_trace
+            .pointer("/request/env_overrides/MOCK_XCODEBUILD_BEHAVIOR")
+            .and_then(Value::as_str),
+        Some("[redacted]")
+    );
+    assert!(trace
+        .get("diagnostics")
+        .and_then(Value::as_array)
+        .is_some_and(|diagnostics| !diagnostics.is_empty()));
+    assert!(bundle
+        .get("log")
+        .and_then(Value::as_str)
+        .is_some_and(|log| log.contains("simulated failure")));
+
+    let zip_path = payloads[1]
+        .get("path")
+        .and_then(Value::as_str)
+        .expect("path");
+    assert!(zip_path.ends_with("job-trace.zip"));
+    assert!(std::path::Path::new(zip_path).is_file());
+    assert_eq!(
+        payloads[1]
+            .get("sha256")
+            .and_then(Value::as_str)
+            .map(str::len),
+        Some(64)
+    );
+    match unknown.expect_err("unknown job should fail") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "job_not_found", "no_violation", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn fetch_build_log_tool_pages_through_the_full_log() -> Result<()> {
+    enable_fast_timeout();
+    let server = build_server(test_server_config(20));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect_err("build should fail");
+
+    let mut pages = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = client
+            .call_tool(CallToolRequestParam {
+                name: "fetch_build_log".into(),
+                arguments: Some(
+                    json!({ "job_id": "latest", "offset": offset, "limit": 16 })
+                        .as_object()
+                        .expect("object")
+                        .clone(),
+                ),
+            })
+            .await
+            .expect("fetch_build_log should succeed")
+            .structured_content
+            .expect("structured_content");
+        let next_offset = page.get("next_offset").and_then(Value::as_u64);
+        pages.push(page);
+        match next_offset {
+            Some(next) => offset = next,
+            None => break,
+        }
+    }
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let last = pages.last().expect("at least one page");
+    assert_eq!(last.get("eof").and_then(Value::as_bool), Some(true));
+    let path = last.get("path").and_then(Value::as_str).expect("path");
+    assert!(path.ends_with("build.log"));
+    let log: String = pages
+        .iter()
+        .filter_map(|page| page.get("text").and_then(Value::as_str))
+        .collect();
+    assert_eq!(log, std::fs::read_to_string(path)?);
+    assert!(log.contains("simulated failure"));
+    assert_eq!(
+        last.get("total_bytes").and_then(Value::as_u64),
+        Some(log.len() as u64)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn fetch_tool_returns_presigned_url_for_object_storage_backend() -> Result<()> {
+    enable_fast_timeout();
+    let mut config = test_server_config(20);
+    config.artifacts = ArtifactsConfig {
+        backend: ArtifactStorageKind::S3,
+        bucket: Some("visionos-ci".into()),
+        prefix: "nightly".into(),
+        cli_path: PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/visionos/mock-object-storage.sh"),
+    };
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "success"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    let build_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("build_visionos_app should succeed")
+        .structured_content
+        .expect("structured_content");
+    let job_id = build_payload
+        .get("job_id")
+        .and_then(Value::as_str)
+        .expect("job_id")
+        .to_string();
+
+    let fetch_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: Some(
+                json!({ "job_id": job_id })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await
+        .expect("fetch_build_output should succeed")
+        .structured_content
+        .expect("structured_content");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert!(fetch_payload.get("artifact_zip").is_none_or(Value::is_null));
+    let download_url = fetch_payload
+        .get("download_url")
+        .and_then(Value::as_str)
+        .expect("download_url");
+    assert!(download_url.starts_with(&format!(
+        "https://mock-bucket.s3.amazonaws.com/nightly/{job_id}/artifact.zip?"
+    )));
+    assert_eq!(
+        fetch_payload.pointer("/storage_backend/kind"),
+        Some(&json!("s3"))
+    );
+    assert_eq!(
+        fetch_payload.pointer("/storage_backend/location"),
+        Some(&json!("s3://visionos-ci/nightly"))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn fetch_tool_reports_expiration_after_ttl() -> Result<()> {
+    enable_fast_timeout();
+    let config = test_server_config_with_ttl(20, 1);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "success"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let build_response = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("build_visionos_app should succeed");
+    let build_payload = build_response
+        .structured_content
+        .expect("structured_content");
+    let job_id = build_payload
+        .get("job_id")
+        .and_then(|v| v.as_str())
+        .expect("job_id")
+        .to_string();
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    let fetch_args = json!({ "job_id": job_id.clone() })
+        .as_object()
+        .expect("object")
+        .clone();
+
+    let fetch_result = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: Some(fetch_args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let error = fetch_result.expect_err("artifact should expire");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "artifact_expired", "no_violation", true);
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn fetch_tool_rejects_unknown_job() -> Result<()> {
+    enable_fast_timeout();
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let fetch_args = json!({ "job_id": Uuid::new_v4().to_string() })
+        .as_object()
+        .expect("object")
+        .clone();
+
+    let fetch_result = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: Some(fetch_args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let error = fetch_result.expect_err("unknown job should be rejected");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "job_not_found", "no_violation", false);
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn fetch_tool_resolves_latest_job_for_scheme() -> Result<()> {
+    enable_fast_timeout();
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "success"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let job_id = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("build_visionos_app should succeed")
+        .structured_content
+        .and_then(|payload| {
+            payload
+                .get("job_id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .expect("job_id");
+
+    let latest_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: Some(
+                json!({ "job_id": "latest", "scheme": "VisionApp" })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await
+        .expect("fetch_build_output should resolve latest")
+        .structured_content
+        .expect("structured_content");
+    let missing_result = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: Some(
+                json!({ "job_id": "latest", "scheme": "NoSuchScheme" })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(
+        latest_payload.get("job_id").and_then(Value::as_str),
+        Some(job_id.as_str())
+    );
+    match missing_result.expect_err("unknown scheme should have no latest job") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "job_not_found", "no_violation", false);
+            assert_eq!(
+                error_field(&inner, "details")
+                    .and_then(|details| details.get("scheme"))
+                    .and_then(Value::as_str),
+                Some("NoSuchScheme")
+            );
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_build_diagnostics_returns_typecheck_location_for_failed_job() -> Result<()> {
+    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK.lock().await;
+    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
+    env::set_var("MOCK_DIAGNOSTICS_BEHAVIOR", "typecheck_error");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let build_args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let build_result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(build_args),
+        })
+        .await;
+
+    let job_id = match build_result.expect_err("build should fail") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "build_failed", "no_violation", true);
+            assert_eq!(
+                error_field(&inner, "details")
+                    .and_then(Value::as_object)
+                    .and_then(|details| details.get("diagnostics_hint"))
+                    .and_then(Value::as_str),
+                Some("inspect_build_diagnostics")
+            );
+            let categories: Vec<&str> = error_field(&inner, "details")
+                .and_then(|details| details.get("diagnostics"))
+                .and_then(Value::as_array)
+                .expect("xcresult diagnostics")
+                .iter()
+                .filter_map(|diagnostic| diagnostic.get("category").and_then(Value::as_str))
+                .collect();
+            assert_eq!(categories, vec!["compiler", "code_signing"]);
+            let log_errors = error_field(&inner, "details")
+                .and_then(|details| details.get("log_errors"))
+                .and_then(Value::as_array)
+                .expect("log errors");
+            assert_eq!(log_errors.len(), 1);
+            assert_eq!(
+                log_errors[0].get("file").and_then(Value::as_str),
+                Some("/tmp/VisionApp/ContentView.swift")
+            );
+            assert_eq!(log_errors[0].get("line").and_then(Value::as_u64), Some(11));
+            error_field(&inner, "job_id")
+                .and_then(Value::as_str)
+                .expect("job_id should exist")
+                .to_string()
+        }
+        other => panic!("unexpected build error: {other:?}", other = other),
+    };
+
+    let inspect_args = json!({
+        "job_id": job_id,
+        "include_log_excerpt": true,
+        "prefer_typecheck": true
+    })
+    .as_object()
+    .expect("object")
+    .clone();
+
+    let inspect_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_build_diagnostics".into(),
+            arguments: Some(inspect_args),
+        })
+        .await
+        .expect("inspect_build_diagnostics should succeed")
+        .structured_content
+        .expect("structured content");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+    env::remove_var("MOCK_DIAGNOSTICS_BEHAVIOR");
+
+    assert_eq!(
+        inspect_payload.get("availability").and_then(Value::as_str),
+        Some("available")
+    );
+    assert_eq!(
+        inspect_payload
+            .get("failure_summary")
+            .and_then(Value::as_object)
+            .and_then(|summary| summary.get("source"))
+            .and_then(Value::as_str),
+        Some("typecheck")
+    );
+    assert_eq!(
+        inspect_payload
+            .get("primary_location")
+            .and_then(Value::as_object)
+            .and_then(|location| location.get("line"))
+            .and_then(Value::as_u64),
+        Some(105)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_build_diagnostics_falls_back_when_typecheck_unavailable() -> Result<()> {
+    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK.lock().await;
+    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
+    env::set_var("MOCK_DIAGNOSTICS_BEHAVIOR", "typecheck_unavailable");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let build_args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let build_result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(build_args),
+        })
+        .await;
+    let job_id = match build_result.expect_err("build should fail") {
+        ServiceError::McpError(inner) => error_field(&inner, "job_id")
+            .and_then(Value::as_str)
+            .expect("job_id")
+            .to_string(),
+        other => panic!("unexpected build error: {other:?}", other = other),
+    };
+
+    let inspect_args = json!({
+        "job_id": job_id,
+        "include_log_excerpt": true,
+        "prefer_typecheck": true
+    })
+    .as_object()
+    .expect("object")
+    .clone();
+    let inspect_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_build_diagnostics".into(),
+            arguments: Some(inspect_args),
+        })
+        .await
+        .expect("inspect_build_diagnostics should succeed")
+        .structured_content
+        .expect("structured content");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+    env::remove_var("MOCK_DIAGNOSTICS_BEHAVIOR");
+
+    assert_eq!(
+        inspect_payload.get("availability").and_then(Value::as_str),
+        Some("unavailable")
+    );
+    assert_eq!(
+        inspect_payload
+            .get("failure_summary")
+            .and_then(Value::as_object)
+            .and_then(|summary| summary.get("source"))
+            .and_then(Value::as_str),
+        Some("xcodebuild_log")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_build_diagnostics_rejects_invalid_job_id() -> Result<()> {
+    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let inspect_args = json!({
+        "job_id": "invalid",
+        "include_log_excerpt": true
+    })
+    .as_object()
+    .expect("object")
+    .clone();
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_build_diagnostics".into(),
+            arguments: Some(inspect_args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let error = result.expect_err("invalid job id should fail");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "invalid_job_id", "no_violation", false)
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_build_diagnostics_rejects_unknown_job() -> Result<()> {
+    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let inspect_args = json!({ "job_id": Uuid::new_v4().to_string() })
+        .as_object()
+        .expect("object")
+        .clone();
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_build_diagnostics".into(),
+            arguments: Some(inspect_args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let error = result.expect_err("unknown job should fail");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "job_not_found", "no_violation", false)
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_build_diagnostics_reports_expired_context() -> Result<()> {
+    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK.lock().await;
+    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
+    env::set_var("MOCK_DIAGNOSTICS_BEHAVIOR", "typecheck_error");
+    let config = test_server_config_with_ttl(20, 1);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let build_args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    let build_result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(build_args),
+        })
+        .await;
+    let job_id = match build_result.expect_err("build should fail") {
+        ServiceError::McpError(inner) => error_field(&inner, "job_id")
+            .and_then(Value::as_str)
+            .expect("job_id")
+            .to_string(),
+        other => panic!("unexpected build error: {other:?}", other = other),
+    };
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    let inspect_args = json!({ "job_id": job_id })
+        .as_object()
+        .expect("object")
+        .clone();
+    let inspect_result = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_build_diagnostics".into(),
+            arguments: Some(inspect_args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+    env::remove_var("MOCK_DIAGNOSTICS_BEHAVIOR");
+
+    let error = inspect_result.expect_err("expired diagnostics should fail");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "diagnostics_expired", "no_violation", true);
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_build_diagnostics_is_repeatable_for_same_job_id() -> Result<()> {
+    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK.lock().await;
+    let _diagnostics_guard = DIAGNOSTICS_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
+    env::set_var("MOCK_DIAGNOSTICS_BEHAVIOR", "typecheck_error");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let build_args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    let build_result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(build_args),
+        })
+        .await;
+    let job_id = match build_result.expect_err("build should fail") {
+        ServiceError::McpError(inner) => error_field(&inner, "job_id")
+            .and_then(Value::as_str)
+            .expect("job_id")
+            .to_string(),
+        other => panic!("unexpected build error: {other:?}", other = other),
+    };
+
+    let inspect_args = json!({
+        "job_id": job_id,
+        "include_log_excerpt": true,
+        "prefer_typecheck": true
+    })
+    .as_object()
+    .expect("object")
+    .clone();
+
+    let first = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_build_diagnostics".into(),
+            arguments: Some(inspect_args.clone()),
+        })
+        .await
+        .expect("first inspect should succeed")
+        .structured_content
+        .expect("structured content");
+    let second = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_build_diagnostics".into(),
+            arguments: Some(inspect_args),
+        })
+        .await
+        .expect("second inspect should succeed")
+        .structured_content
+        .expect("structured content");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+    env::remove_var("MOCK_DIAGNOSTICS_BEHAVIOR");
+
+    assert_eq!(
+        first.get("availability").and_then(Value::as_str),
+        Some("available")
+    );
+    assert_eq!(
+        first.get("failure_summary").and_then(Value::as_object),
+        second.get("failure_summary").and_then(Value::as_object)
+    );
+    assert_eq!(
+        first.get("primary_location").and_then(Value::as_object),
+        second.get("primary_location").and_then(Value::as_object)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn tool_payloads_carry_the_negotiated_schema_version() -> Result<()> {
+    let server = build_server(test_server_config(5));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let capability = client
+        .peer_info()
+        .and_then(|info| info.capabilities.experimental.as_ref())
+        .and_then(|experimental| experimental.get("seiro/schema_version"))
+        .cloned();
+    let call = |declared: Option<Value>| {
+        let mut meta = Meta::new();
+        if let Some(declared) = declared {
+            meta.insert("schema_version".into(), declared);
+        }
+        let request = ClientRequest::CallToolRequest(CallToolRequest::new(CallToolRequestParam {
+            name: "get_queue_status".into(),
+            arguments: Some(serde_json::Map::new()),
+        }));
+        let options = PeerRequestOptions {
+            timeout: None,
+            meta: Some(meta),
+        };
+        let client = &client;
+        async move {
+            client
+                .send_request_with_option(request, options)
+                .await?
+                .await_response()
+                .await
+        }
+    };
+
+    let undeclared = call(None).await?;
+    let newer = call(Some(json!(99))).await?;
+    let unsupported = call(Some(json!(0))).await;
+    let invalid = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(serde_json::Map::new()),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(
+        capability,
+        json!({ "min": 1, "current": 1 }).as_object().cloned()
+    );
+    for result in [undeclared, newer] {
+        let ServerResult::CallToolResult(result) = result else {
+            panic!("expected a tool result");
+        };
+        let content = result.structured_content.expect("structured_content");
+        assert_eq!(content["schema_version"], json!(1));
+    }
+    let ServiceError::McpError(inner) = unsupported.expect_err("version 0 is unsupported") else {
+        panic!("expected an MCP error");
+    };
+    assert_error_metadata(&inner, "unsupported_schema_version", "no_violation", false);
+    let ServiceError::McpError(inner) = invalid.expect_err("arguments are missing") else {
+        panic!("expected an MCP error");
+    };
+    assert_eq!(inner.data.expect("error data")["schema_version"], json!(1));
+    Ok(())
+}
+
+#[tokio::test]
+async fn calls_over_the_rate_limit_are_rejected_before_dispatch() -> Result<()> {
+    let mut config = test_server_config(5);
+    config.server.rate_limit_per_minute = 1;
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let queue_status = || {
+        client.call_tool(CallToolRequestParam {
+            name: "get_queue_status".into(),
+            arguments: Some(serde_json::Map::new()),
+        })
+    };
+
+    let first = queue_status().await;
+    let second = queue_status().await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    first.expect("first call fits the budget");
+    let ServiceError::McpError(inner) = second.expect_err("second call is limited") else {
+        panic!("expected an MCP error");
+    };
+    assert_error_metadata(&inner, "rate_limited", "not_applicable", true);
+    let data = inner.data.expect("error data");
+    assert!(data["retry_after_secs"]
+        .as_u64()
+        .is_some_and(|secs| secs > 0));
+    assert_eq!(data["schema_version"], json!(1));
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_success_response_contract_remains_stable() -> Result<()> {
+    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "success"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    let response = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("build should succeed")
+        .structured_content
+        .expect("structured_content");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+
+    let required = [
+        "job_id",
+        "status",
+        "artifact_path",
+        "artifact_sha256",
+        "log_excerpt",
+        "duration_ms",
+        "packaging_duration_ms",
+        "attempts",
+    ];
+    for key in required {
+        assert!(
+            response.get(key).is_some(),
+            "missing build response key: {key}"
+        );
+    }
+    assert!(
+        response.get("diagnostics_hint").is_none(),
+        "success response must not include diagnostics-specific fields"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn fetch_response_contract_remains_stable_for_success_and_failure() -> Result<()> {
+    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let build_args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "success"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    let build = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(build_args),
+        })
+        .await
+        .expect("build should succeed")
+        .structured_content
+        .expect("structured content");
+    let job_id = build
+        .get("job_id")
+        .and_then(Value::as_str)
+        .expect("job_id")
+        .to_string();
+
+    let fetch_success_args = json!({ "job_id": job_id, "include_logs": true })
+        .as_object()
+        .expect("object")
+        .clone();
+    let fetch_success = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: Some(fetch_success_args),
+        })
+        .await
+        .expect("fetch success")
+        .structured_content
+        .expect("structured content");
+
+    let required = [
+        "job_id",
+        "status",
+        "artifact_zip",
+        "sha256",
+        "download_ttl_seconds",
+        "log_excerpt",
+    ];
+    for key in required {
+        assert!(
+            fetch_success.get(key).is_some(),
+            "missing fetch success key: {key}"
+        );
+    }
+
+    let fetch_fail_args = json!({ "job_id": Uuid::new_v4().to_string() })
+        .as_object()
+        .expect("object")
+        .clone();
+    let fetch_fail = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: Some(fetch_fail_args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+
+    let error = fetch_fail.expect_err("fetch should fail for unknown job");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "job_not_found", "no_violation", false);
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn build_failure_error_keeps_existing_fields_with_additive_hint() -> Result<()> {
+    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+
+    let error = result.expect_err("build should fail");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "build_failed", "no_violation", true);
+            assert!(error_field(&inner, "job_id").is_some(), "job_id must exist");
+            assert!(
+                error_field(&inner, "details")
+                    .and_then(Value::as_object)
+                    .and_then(|details| details.get("details"))
+                    .and_then(Value::as_str)
+                    .is_some(),
+                "existing details field must exist"
+            );
+            assert_eq!(
+                error_field(&inner, "details")
+                    .and_then(Value::as_object)
+                    .and_then(|details| details.get("diagnostics_hint"))
+                    .and_then(Value::as_str),
+                Some("inspect_build_diagnostics")
+            );
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn ambiguous_destination_returns_structured_retry_guidance() -> Result<()> {
+    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "ambiguous_destination");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "ambiguous_destination"
+        }
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+
+    let error = result.expect_err("build should fail");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "destination_ambiguous", "no_violation", true);
+            assert_eq!(
+                error_field(&inner, "details")
+                    .and_then(Value::as_object)
+                    .and_then(|details| details.get("suggested_destination"))
+                    .and_then(Value::as_str),
+                Some("platform=visionOS Simulator,id:F556D53F-412A-4778-AF81-3449D52F5A7F")
+            );
+            assert_eq!(
+                error_field(&inner, "details")
+                    .and_then(Value::as_object)
+                    .and_then(|details| details.get("matched_devices"))
+                    .and_then(Value::as_array)
+                    .map(Vec::len),
+                Some(2)
+            );
+        }
+        other => panic!("unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn sandbox_tool_reports_checks() -> Result<()> {
+    let _guard = SANDBOX_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    configure_sandbox_probe_env();
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "required_sdks": ["visionOS", "visionOS Simulator"],
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "validate_sandbox_policy".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let response = call_result.expect("validate_sandbox_policy should return a success response");
+    let payload = response
+        .structured_content
+        .expect("structured_content should exist");
+    assert_eq!(payload.get("status").and_then(|v| v.as_str()), Some("ok"));
+    assert!(
+        payload
+            .get("checks")
+            .and_then(|v| v.as_array())
+            .map(|checks| !checks.is_empty())
+            .unwrap_or(false),
+        "checks must not be empty"
+    );
+    let diagnostics = payload
+        .get("diagnostics")
+        .and_then(|v| v.as_object())
+        .expect("diagnostics should exist");
+    assert_eq!(
+        diagnostics.get("probe_mode").and_then(Value::as_str),
+        Some("env")
+    );
+    assert_eq!(
+        diagnostics
+            .get("effective_required_sdks")
+            .and_then(Value::as_array)
+            .map(|v| v.len()),
+        Some(2)
+    );
+    assert!(
+        diagnostics
+            .get("detected_sdks_normalized")
+            .and_then(Value::as_array)
+            .map(|v| !v.is_empty())
+            .unwrap_or(false),
+        "detected_sdks_normalized should not be empty"
+    );
+    configure_sandbox_probe_env();
+    Ok(())
+}
+
+#[tokio::test]
+async fn sandbox_tool_returns_diagnostics_for_sdk_missing() -> Result<()> {
+    let _guard = SANDBOX_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    configure_sandbox_probe_env_with_sdks("");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "required_sdks": ["visionOS"],
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer",
+        "strict": true
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "validate_sandbox_policy".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let error = call_result.expect_err("expected sdk_missing error");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "sdk_missing", "blocked", false);
+            let diagnostics = error_field(&inner, "details")
+                .and_then(Value::as_object)
+                .and_then(|details| details.get("diagnostics"))
+                .and_then(Value::as_object)
+                .expect("diagnostics should exist in sdk_missing details");
+            assert_eq!(
+                diagnostics.get("probe_mode").and_then(Value::as_str),
+                Some("env")
+            );
+            assert_eq!(
+                diagnostics
+                    .get("effective_required_sdks")
+                    .and_then(Value::as_array)
+                    .map(|v| v.len()),
+                Some(1)
+            );
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    configure_sandbox_probe_env();
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_tool_reports_sdk_inventory() -> Result<()> {
+    let _guard = SANDBOX_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    configure_sandbox_probe_env();
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "required_sdks": ["visionOS", "visionOS Simulator"],
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let response = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_xcode_sdks".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("inspect_xcode_sdks should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(response.get("status").and_then(|v| v.as_str()), Some("ok"));
+    assert_eq!(
+        response.get("probe_mode").and_then(|v| v.as_str()),
+        Some("env")
+    );
+    assert_eq!(
+        response
+            .get("missing_required_sdks")
+            .and_then(|v| v.as_array())
+            .map(|v| v.len()),
+        Some(0)
+    );
+    assert!(
+        response
+            .get("detected_sdks_normalized")
+            .and_then(|v| v.as_array())
+            .map(|v| !v.is_empty())
+            .unwrap_or(false),
+        "detected_sdks_normalized should not be empty"
+    );
+    configure_sandbox_probe_env();
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_tool_reports_missing_required_sdk_in_env_mode() -> Result<()> {
+    let _guard = SANDBOX_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    configure_sandbox_probe_env_with_sdks("");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "required_sdks": ["visionOS"],
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let response = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_xcode_sdks".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("inspect_xcode_sdks should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(
+        response.get("status").and_then(|v| v.as_str()),
+        Some("error")
+    );
+    assert_eq!(
+        response
+            .get("missing_required_sdks")
+            .and_then(|v| v.as_array())
+            .map(|v| v.len()),
+        Some(1)
+    );
+    configure_sandbox_probe_env();
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_schemes_tool_returns_scheme_list() -> Result<()> {
+    let _guard = XCODEBUILD_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_xcodeproj_path().to_string_lossy(),
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let response = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_xcode_schemes".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("inspect_xcode_schemes should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(response.get("status").and_then(Value::as_str), Some("ok"));
+    assert_eq!(
+        response.get("project_path_source").and_then(Value::as_str),
+        Some("request")
+    );
+    let schemes = response
+        .get("schemes")
+        .and_then(Value::as_array)
+        .expect("schemes should be an array");
+    assert!(
+        !schemes.is_empty(),
+        "inspect_xcode_schemes should return at least one scheme"
+    );
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_allowed_projects_tool_finds_projects_in_the_allowlist() -> Result<()> {
+    let config = test_server_config(20);
+    let workspace = config.visionos.allowed_paths[0].clone();
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let response = client
+        .call_tool(CallToolRequestParam {
+            name: "list_allowed_projects".into(),
+            arguments: Some(serde_json::Map::new()),
+        })
+        .await
+        .expect("list_allowed_projects should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let projects = response
+        .get("projects")
+        .and_then(Value::as_array)
+        .expect("projects array");
+    let kind_of = |path: PathBuf| {
+        projects
+            .iter()
+            .find(|project| project["project_path"] == json!(path.to_string_lossy()))
+            .and_then(|project| project["kind"].as_str())
+    };
+    assert_eq!(
+        kind_of(workspace.join("VisionApp.xcodeproj")),
+        Some("xcode_project")
+    );
+    assert_eq!(kind_of(workspace.join("VisionKit")), Some("swift_package"));
+    assert_eq!(response.get("max_depth").and_then(Value::as_u64), Some(3));
+    assert_eq!(response.get("truncated"), Some(&json!(false)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn describe_build_constraints_matches_what_validation_enforces() -> Result<()> {
+    let mut config = test_server_config(20);
+    config.visionos.max_timeout_minutes = 45;
+    let project_path = allowed_project_path();
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let constraints = client
+        .call_tool(CallToolRequestParam {
+            name: "describe_build_constraints".into(),
+            arguments: Some(serde_json::Map::new()),
+        })
+        .await
+        .expect("describe_build_constraints should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+    let rejected = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: json!({
+                "project_path": project_path,
+                "scheme": "VisionApp",
+                "extra_args": ["-derivedDataPath"]
+            })
+            .as_object()
+            .cloned(),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(constraints["default_timeout_minutes"], json!(20));
+    assert_eq!(constraints["max_timeout_minutes"], json!(45));
+    assert_eq!(constraints["extra_args"]["max_count"], json!(5));
+    assert!(constraints["allowed_env_overrides"]
+        .as_array()
+        .is_some_and(|keys| keys.contains(&json!("CI"))));
+    let ServiceError::McpError(inner) = rejected.expect_err("unlisted flag is rejected") else {
+        panic!("expected an MCP error");
+    };
+    assert_error_metadata(&inner, "invalid_request", "no_violation", false);
+    assert_eq!(
+        error_field(&inner, "details").map(|details| &details["allowed_extra_args"]),
+        Some(&constraints["allowed_extra_args"])
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_error_codes_lists_the_errors_tools_return() -> Result<()> {
+    let project_path = allowed_project_path();
+    let server = build_server(test_server_config(5));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let catalog = client
+        .call_tool(CallToolRequestParam {
+            name: "list_error_codes".into(),
+            arguments: Some(serde_json::Map::new()),
+        })
+        .await
+        .expect("list_error_codes should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+    let invalid_job_id = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: json!({ "job_id": "not-a-job" }).as_object().cloned(),
+        })
+        .await;
+    let invalid_request = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: json!({
+                "project_path": project_path,
+                "scheme": "VisionApp",
+                "extra_args": ["-derivedDataPath"]
+            })
+            .as_object()
+            .cloned(),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let errors = catalog["errors"].as_array().expect("errors array");
+    let codes: Vec<&str> = errors
+        .iter()
+        .filter_map(|error| error["code"].as_str())
+        .collect();
+    assert!(codes.windows(2).all(|pair| pair[0] <= pair[1]));
+    for code in [
+        "timeout",
+        "path_not_allowed",
+        "rate_limited",
+        "MCP_CLIENT_REQUIRED",
+    ] {
+        assert!(codes.contains(&code), "{code} should be listed");
+    }
+    for result in [invalid_job_id, invalid_request] {
+        let ServiceError::McpError(inner) = result.expect_err("request is rejected") else {
+            panic!("expected an MCP error");
+        };
+        let listed = json!({
+            "code": error_field(&inner, "code"),
+            "message": inner.message,
+            "remediation": error_field(&inner, "remediation"),
+            "retryable": error_field(&inner, "retryable"),
+            "sandbox_state": error_field(&inner, "sandbox_state"),
+        });
+        assert!(
+            errors.contains(&listed),
+            "{listed} should be in the catalog"
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_project_schemes_tool_returns_targets_and_configurations() -> Result<()> {
+    let _guard = XCODEBUILD_ENV_LOCK.lock().await;
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({ "project_path": allowed_project_xcodeproj_path().to_string_lossy() })
+        .as_object()
+        .expect("JSON object")
+        .clone();
+
+    let response = client
+        .call_tool(CallToolRequestParam {
+            name: "list_project_schemes".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("list_project_schemes should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(response.get("status").and_then(Value::as_str), Some("ok"));
+    assert_eq!(
+        response.get("project_name").and_then(Value::as_str),
+        Some("VisionApp")
+    );
+    assert_eq!(
+        response.get("targets"),
+        Some(&json!(["VisionApp", "VisionAppTests"]))
+    );
+    assert_eq!(
+        response.get("configurations"),
+        Some(&json!(["Debug", "Release"]))
+    );
+    assert_eq!(
+        response.get("buildable_schemes"),
+        Some(&json!(["VisionApp"]))
+    );
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+    Ok(())
+}
+
+#[tokio::test]
+async fn show_build_settings_tool_returns_filtered_settings() -> Result<()> {
+    let _guard = XCODEBUILD_ENV_LOCK.lock().await;
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let call = |scheme: &str| CallToolRequestParam {
+        name: "show_build_settings".into(),
+        arguments: Some(
+            json!({
+                "project_path": allowed_project_xcodeproj_path().to_string_lossy(),
+                "scheme": scheme,
+                "configuration": "Debug",
+                "keys": ["OTHER_SWIFT_FLAGS"]
+            })
+            .as_object()
+            .expect("JSON object")
+            .clone(),
+        ),
+    };
+    let response = client
+        .call_tool(call("VisionApp"))
+        .await
+        .expect("show_build_settings should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+    let blocked = client.call_tool(call("Internal")).await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+
+    assert_eq!(
+        response.get("targets"),
+        Some(&json!([{
+            "target": "VisionApp",
+            "settings": {
+                "CODE_SIGN_STYLE": "Automatic",
+                "DEVELOPMENT_TEAM": "ABCDE12345",
+                "OTHER_SWIFT_FLAGS": "-DDEBUG",
+                "PRODUCT_BUNDLE_IDENTIFIER": "com.example.VisionApp",
+                "PRODUCT_NAME": "VisionApp",
+                "XROS_DEPLOYMENT_TARGET": "2.0"
+            }
+        }]))
+    );
+    assert!(response
+        .get("invocation")
+        .and_then(Value::as_str)
+        .is_some_and(|invocation| invocation.contains("-showBuildSettings -json -project")));
+    match blocked.expect_err("scheme outside allowlist should be rejected") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "scheme_not_allowed", "blocked", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_destinations_tool_returns_buildable_destinations() -> Result<()> {
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let call = |include_ineligible: bool| CallToolRequestParam {
+        name: "list_destinations".into(),
+        arguments: Some(
+            json!({
+                "project_path": allowed_project_xcodeproj_path().to_string_lossy(),
+                "scheme": "VisionApp",
+                "include_ineligible": include_ineligible
+            })
+            .as_object()
+            .expect("JSON object")
+            .clone(),
+        ),
+    };
+    let eligible = client
+        .call_tool(call(false))
+        .await
+        .expect("list_destinations should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+    let all = client
+        .call_tool(call(true))
+        .await
+        .expect("list_destinations should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let destinations: Vec<&str> = eligible
+        .get("destinations")
+        .and_then(Value::as_array)
+        .expect("destinations array")
+        .iter()
+        .filter_map(|entry| entry.get("destination").and_then(Value::as_str))
+        .collect();
+    assert_eq!(
+        destinations,
+        vec![
+            "platform=visionOS Simulator,id=F556D53F-412A-4778-AF81-3449D52F5A7F",
+            "generic/platform=visionOS"
+        ]
+    );
+    assert_eq!(
+        eligible.pointer("/destinations/0/os_version"),
+        Some(&json!("26.2"))
+    );
+    assert_eq!(all.pointer("/destinations/2/eligible"), Some(&json!(false)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_xcode_installations_tool_reports_versions() -> Result<()> {
+    let mut config = test_server_config(20);
+    config.visionos.xcodes.insert(
+        "beta".into(),
+        PathBuf::from("/Applications/Xcode-beta.app/Contents/Developer"),
+    );
+    config.visionos.xcodes.insert(
+        "removed".into(),
+        PathBuf::from("/Applications/Missing.app/Contents/Developer"),
+    );
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let payload = client
+        .call_tool(CallToolRequestParam {
+            name: "list_xcode_installations".into(),
+            arguments: Some(json!({}).as_object().expect("JSON object").clone()),
+        })
+        .await
+        .expect("list_xcode_installations should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let installations = payload["installations"]
+        .as_array()
+        .expect("installations array");
+    let summary: Vec<(&str, bool, Option<&str>, Option<&str>)> = installations
+        .iter()
+        .map(|entry| {
+            (
+                entry["name"].as_str().expect("name"),
+                entry["default"].as_bool().expect("default"),
+                entry["version"].as_str(),
+                entry["build"].as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("default", true, Some("26.2"), Some("17C52")),
+            ("beta", false, Some("26.3"), Some("17D5044a")),
+            ("removed", false, None, None),
+        ]
+    );
+    assert!(installations[2]["error"]
+        .as_str()
+        .is_some_and(|error| error.contains("xcode-select")));
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_project_schemes_tool_rejects_path_outside_allowlist() -> Result<()> {
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let outside = tempfile::tempdir()?;
+    let project = outside.path().join("Outside.xcodeproj");
+    std::fs::create_dir_all(&project)?;
+    let args = json!({ "project_path": project.to_string_lossy() })
+        .as_object()
+        .expect("JSON object")
+        .clone();
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "list_project_schemes".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    match call_result.expect_err("path outside allowlist should be rejected") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "path_not_allowed", "blocked", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn resolve_package_dependencies_tool_lists_pinned_packages() -> Result<()> {
+    let _guard = XCODEBUILD_ENV_LOCK.lock().await;
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({ "project_path": allowed_project_xcodeproj_path().to_string_lossy() })
+        .as_object()
+        .expect("JSON object")
+        .clone();
+
+    let response = client
+        .call_tool(CallToolRequestParam {
+            name: "resolve_package_dependencies".into(),
+            arguments: Some(args),
+        })
+        .await
+        .expect("resolve_package_dependencies should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+
+    assert_eq!(response.get("status").and_then(Value::as_str), Some("ok"));
+    assert_eq!(
+        response.get("project_kind").and_then(Value::as_str),
+        Some("xcode_project")
+    );
+    assert!(response
+        .get("resolved_file")
+        .and_then(Value::as_str)
+        .is_some_and(|path| path.ends_with("xcshareddata/swiftpm/Package.resolved")));
+    assert!(response
+        .get("invocation")
+        .and_then(Value::as_str)
+        .is_some_and(|invocation| invocation.contains("-resolvePackageDependencies -project")));
+    assert_eq!(
+        response.get("packages"),
+        Some(&json!([
+            {
+                "name": "swift-collections",
+                "kind": "remoteSourceControl",
+                "repository_url": "https://github.com/apple/swift-collections.git",
+                "version": "1.1.4",
+                "revision": "671108c96644956dddcd89dd59c203dcdb36cec7"
+            },
+            {
+                "name": "swift-numerics",
+                "kind": "remoteSourceControl",
+                "repository_url": "https://github.com/apple/swift-numerics.git",
+                "branch": "main",
+                "revision": "e0ec0f5f3af6f3e4d5d8c1f9f8b0a7a2c3d4e5f6"
+            }
+        ]))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn resolve_package_dependencies_tool_maps_resolution_failure() -> Result<()> {
+    let _guard = XCODEBUILD_ENV_LOCK.lock().await;
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({ "project_path": allowed_project_xcodeproj_path().to_string_lossy() })
+        .as_object()
+        .expect("JSON object")
+        .clone();
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "resolve_package_dependencies".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+
+    match call_result.expect_err("failed resolution should be reported") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "package_resolution_failed", "no_violation", true);
+            let excerpt = error_field(&inner, "details")
+                .and_then(|details| details.get("log_excerpt"))
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            assert!(excerpt.contains("missing.git"), "{excerpt}");
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_schemes_tool_maps_xcodebuild_list_failed() -> Result<()> {
+    let _guard = XCODEBUILD_ENV_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_xcodeproj_path().to_string_lossy(),
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_xcode_schemes".into(),
+            arguments: Some(args),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let error = call_result.expect_err("xcodebuild failure should return an error");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "xcodebuild_list_failed", "no_violation", true);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_schemes_tool_uses_cwd_xcodeproj_when_project_path_is_omitted() -> Result<()> {
+    let _env_guard = XCODEBUILD_ENV_LOCK.lock().await;
+    let _cwd_guard = CWD_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
+
+    let workspace_dir =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/visionos/workspace");
+    let _cwd = CwdGuard::change_to(&workspace_dir)?;
+
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let response = client
         .call_tool(CallToolRequestParam {
-            name: "build_visionos_app".into(),
-            arguments: Some(build_args),
+            name: "inspect_xcode_schemes".into(),
+            arguments: Some(args),
         })
-        .await;
-    let job_id = match build_result.expect_err("build should fail") {
-        ServiceError::McpError(inner) => error_field(&inner, "job_id")
-            .and_then(Value::as_str)
-            .expect("job_id")
-            .to_string(),
-        other => panic!("unexpected build error: {other:?}", other = other),
-    };
+        .await
+        .expect("inspect_xcode_schemes should succeed")
+        .structured_content
+        .expect("structured_content should exist");
 
-    let inspect_args = json!({
-        "job_id": job_id,
-        "include_log_excerpt": true,
-        "prefer_typecheck": true
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(
+        response.get("project_path_source").and_then(Value::as_str),
+        Some("cwd")
+    );
+
+    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+    Ok(())
+}
+
+#[tokio::test]
+async fn inspect_schemes_tool_uses_config_default_when_no_request_or_cwd_xcodeproj() -> Result<()> {
+    let _env_guard = XCODEBUILD_ENV_LOCK.lock().await;
+    let _cwd_guard = CWD_LOCK.lock().await;
+    enable_fast_timeout();
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
+
+    let empty_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join(format!("inspect-schemes-config-default-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&empty_dir)?;
+    let _cwd = CwdGuard::change_to(&empty_dir)?;
+
+    let mut config = test_server_config(20);
+    config.visionos.default_project_path = Some(allowed_project_xcodeproj_path());
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
     })
     .as_object()
-    .expect("object")
+    .expect("JSON object")
     .clone();
 
-    let first = client
-        .call_tool(CallToolRequestParam {
-            name: "inspect_build_diagnostics".into(),
-            arguments: Some(inspect_args.clone()),
-        })
-        .await
-        .expect("first inspect should succeed")
-        .structured_content
-        .expect("structured content");
-    let second = client
+    let response = client
         .call_tool(CallToolRequestParam {
-            name: "inspect_build_diagnostics".into(),
-            arguments: Some(inspect_args),
+            name: "inspect_xcode_schemes".into(),
+            arguments: Some(args),
         })
         .await
-        .expect("second inspect should succeed")
+        .expect("inspect_xcode_schemes should succeed")
         .structured_content
-        .expect("structured content");
+        .expect("structured_content should exist");
 
     let _ = client.cancel().await;
     let _ = server_task.await;
     env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
-    env::remove_var("MOCK_DIAGNOSTICS_BEHAVIOR");
 
     assert_eq!(
-        first.get("availability").and_then(Value::as_str),
-        Some("available")
-    );
-    assert_eq!(
-        first.get("failure_summary").and_then(Value::as_object),
-        second.get("failure_summary").and_then(Value::as_object)
-    );
-    assert_eq!(
-        first.get("primary_location").and_then(Value::as_object),
-        second.get("primary_location").and_then(Value::as_object)
+        response.get("project_path_source").and_then(Value::as_str),
+        Some("config")
     );
     Ok(())
 }
 
 #[tokio::test]
-async fn build_success_response_contract_remains_stable() -> Result<()> {
-    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
+async fn inspect_schemes_tool_returns_project_path_missing_when_no_request_cwd_or_config(
+) -> Result<()> {
+    let _cwd_guard = CWD_LOCK.lock().await;
     enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
+
+    let empty_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join(format!("inspect-schemes-empty-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&empty_dir)?;
+    let _cwd = CwdGuard::change_to(&empty_dir)?;
+
     let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
@@ -825,58 +5043,36 @@ async fn build_success_response_contract_remains_stable() -> Result<()> {
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
     let args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
-        "scheme": "VisionApp",
-        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
-        "env_overrides": {
-            "MOCK_XCODEBUILD_BEHAVIOR": "success"
-        }
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
     })
     .as_object()
     .expect("JSON object")
     .clone();
-    let response = client
+
+    let call_result = client
         .call_tool(CallToolRequestParam {
-            name: "build_visionos_app".into(),
+            name: "inspect_xcode_schemes".into(),
             arguments: Some(args),
         })
-        .await
-        .expect("build should succeed")
-        .structured_content
-        .expect("structured_content");
+        .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
-    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
 
-    let required = [
-        "job_id",
-        "status",
-        "artifact_path",
-        "artifact_sha256",
-        "log_excerpt",
-        "duration_ms",
-    ];
-    for key in required {
-        assert!(
-            response.get(key).is_some(),
-            "missing build response key: {key}"
-        );
+    let error = call_result.expect_err("missing project path should be rejected");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "project_path_missing", "no_violation", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
     }
-    assert!(
-        response.get("diagnostics_hint").is_none(),
-        "success response must not include diagnostics-specific fields"
-    );
+
     Ok(())
 }
 
 #[tokio::test]
-async fn fetch_response_contract_remains_stable_for_success_and_failure() -> Result<()> {
-    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
+async fn inspect_schemes_tool_rejects_relative_project_path() -> Result<()> {
     enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
     let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
@@ -887,93 +5083,80 @@ async fn fetch_response_contract_remains_stable_for_success_and_failure() -> Res
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let build_args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
-        "scheme": "VisionApp",
-        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
-        "env_overrides": {
-            "MOCK_XCODEBUILD_BEHAVIOR": "success"
-        }
+    let args = json!({
+        "project_path": "VisionApp.xcodeproj",
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
     })
     .as_object()
     .expect("JSON object")
     .clone();
-    let build = client
-        .call_tool(CallToolRequestParam {
-            name: "build_visionos_app".into(),
-            arguments: Some(build_args),
-        })
-        .await
-        .expect("build should succeed")
-        .structured_content
-        .expect("structured content");
-    let job_id = build
-        .get("job_id")
-        .and_then(Value::as_str)
-        .expect("job_id")
-        .to_string();
 
-    let fetch_success_args = json!({ "job_id": job_id, "include_logs": true })
-        .as_object()
-        .expect("object")
-        .clone();
-    let fetch_success = client
+    let call_result = client
         .call_tool(CallToolRequestParam {
-            name: "fetch_build_output".into(),
-            arguments: Some(fetch_success_args),
+            name: "inspect_xcode_schemes".into(),
+            arguments: Some(args),
         })
-        .await
-        .expect("fetch success")
-        .structured_content
-        .expect("structured content");
+        .await;
 
-    let required = [
-        "job_id",
-        "status",
-        "artifact_zip",
-        "sha256",
-        "download_ttl_seconds",
-        "log_excerpt",
-    ];
-    for key in required {
-        assert!(
-            fetch_success.get(key).is_some(),
-            "missing fetch success key: {key}"
-        );
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let error = call_result.expect_err("relative project_path should be rejected");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "project_path_invalid", "no_violation", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
     }
+    Ok(())
+}
 
-    let fetch_fail_args = json!({ "job_id": Uuid::new_v4().to_string() })
-        .as_object()
-        .expect("object")
-        .clone();
-    let fetch_fail = client
+#[tokio::test]
+async fn inspect_schemes_tool_rejects_nonexistent_project_path() -> Result<()> {
+    enable_fast_timeout();
+    let config = test_server_config(20);
+    let server = build_server(config);
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": "/tmp/does-not-exist/Nope.xcodeproj",
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+
+    let call_result = client
         .call_tool(CallToolRequestParam {
-            name: "fetch_build_output".into(),
-            arguments: Some(fetch_fail_args),
+            name: "inspect_xcode_schemes".into(),
+            arguments: Some(args),
         })
         .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
-    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
 
-    let error = fetch_fail.expect_err("fetch should fail for unknown job");
+    let error = call_result.expect_err("nonexistent project_path should be rejected");
     match error {
         ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "job_not_found", "no_violation", false);
+            assert_error_metadata(&inner, "project_not_found", "no_violation", false);
         }
-        other => panic!("unexpected error: {other:?}", other = other),
+        other => panic!("Unexpected error: {other:?}", other = other),
     }
     Ok(())
 }
 
 #[tokio::test]
-async fn build_failure_error_keeps_existing_fields_with_additive_hint() -> Result<()> {
-    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
+async fn inspect_schemes_tool_maps_scheme_parse_failed() -> Result<()> {
+    let _env_guard = XCODEBUILD_ENV_LOCK.lock().await;
     enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "parse_invalid");
     let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
@@ -985,19 +5168,16 @@ async fn build_failure_error_keeps_existing_fields_with_additive_hint() -> Resul
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
     let args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
-        "scheme": "VisionApp",
-        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
-        "env_overrides": {
-            "MOCK_XCODEBUILD_BEHAVIOR": "fail"
-        }
+        "project_path": allowed_project_xcodeproj_path().to_string_lossy(),
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
     })
     .as_object()
     .expect("JSON object")
     .clone();
-    let result = client
+
+    let call_result = client
         .call_tool(CallToolRequestParam {
-            name: "build_visionos_app".into(),
+            name: "inspect_xcode_schemes".into(),
             arguments: Some(args),
         })
         .await;
@@ -1006,39 +5186,21 @@ async fn build_failure_error_keeps_existing_fields_with_additive_hint() -> Resul
     let _ = server_task.await;
     env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
 
-    let error = result.expect_err("build should fail");
+    let error = call_result.expect_err("invalid JSON should map to scheme_parse_failed");
     match error {
         ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "build_failed", "no_violation", true);
-            assert!(error_field(&inner, "job_id").is_some(), "job_id must exist");
-            assert!(
-                error_field(&inner, "details")
-                    .and_then(Value::as_object)
-                    .and_then(|details| details.get("details"))
-                    .and_then(Value::as_str)
-                    .is_some(),
-                "existing details field must exist"
-            );
-            assert_eq!(
-                error_field(&inner, "details")
-                    .and_then(Value::as_object)
-                    .and_then(|details| details.get("diagnostics_hint"))
-                    .and_then(Value::as_str),
-                Some("inspect_build_diagnostics")
-            );
+            assert_error_metadata(&inner, "scheme_parse_failed", "no_violation", true);
         }
-        other => panic!("unexpected error: {other:?}", other = other),
+        other => panic!("Unexpected error: {other:?}", other = other),
     }
     Ok(())
 }
 
 #[tokio::test]
-async fn ambiguous_destination_returns_structured_retry_guidance() -> Result<()> {
-    let _xcodebuild_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
+async fn inspect_schemes_tool_maps_no_schemes_found() -> Result<()> {
+    let _env_guard = XCODEBUILD_ENV_LOCK.lock().await;
     enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "ambiguous_destination");
+    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "no_schemes");
     let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
@@ -1050,19 +5212,16 @@ async fn ambiguous_destination_returns_structured_retry_guidance() -> Result<()>
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
     let args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
-        "scheme": "VisionApp",
-        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
-        "env_overrides": {
-            "MOCK_XCODEBUILD_BEHAVIOR": "ambiguous_destination"
-        }
+        "project_path": allowed_project_xcodeproj_path().to_string_lossy(),
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
     })
     .as_object()
     .expect("JSON object")
     .clone();
-    let result = client
+
+    let call_result = client
         .call_tool(CallToolRequestParam {
-            name: "build_visionos_app".into(),
+            name: "inspect_xcode_schemes".into(),
             arguments: Some(args),
         })
         .await;
@@ -1071,38 +5230,21 @@ async fn ambiguous_destination_returns_structured_retry_guidance() -> Result<()>
     let _ = server_task.await;
     env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
 
-    let error = result.expect_err("build should fail");
+    let error = call_result.expect_err("no schemes should map to no_schemes_found");
     match error {
         ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "destination_ambiguous", "no_violation", true);
-            assert_eq!(
-                error_field(&inner, "details")
-                    .and_then(Value::as_object)
-                    .and_then(|details| details.get("suggested_destination"))
-                    .and_then(Value::as_str),
-                Some("platform=visionOS Simulator,id:F556D53F-412A-4778-AF81-3449D52F5A7F")
-            );
-            assert_eq!(
-                error_field(&inner, "details")
-                    .and_then(Value::as_object)
-                    .and_then(|details| details.get("matched_devices"))
-                    .and_then(Value::as_array)
-                    .map(Vec::len),
-                Some(2)
-            );
+            assert_error_metadata(&inner, "no_schemes_found", "no_violation", false);
         }
-        other => panic!("unexpected error: {other:?}", other = other),
+        other => panic!("Unexpected error: {other:?}", other = other),
     }
     Ok(())
 }
 
 #[tokio::test]
-async fn sandbox_tool_reports_checks() -> Result<()> {
-    let _guard = SANDBOX_ENV_LOCK
-        .lock()
-        .expect("sandbox env lock should not be poisoned");
+async fn inspect_tool_and_validate_share_required_sdk_view() -> Result<()> {
+    let _guard = SANDBOX_ENV_LOCK.lock().await;
     enable_fast_timeout();
-    configure_sandbox_probe_env();
+    configure_sandbox_probe_env_with_sdks("visionOS");
     let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
@@ -1113,72 +5255,73 @@ async fn sandbox_tool_reports_checks() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
+    let inspect_args = json!({
         "required_sdks": ["visionOS", "visionOS Simulator"],
         "xcode_path": "/Applications/Xcode.app/Contents/Developer"
     })
     .as_object()
     .expect("JSON object")
     .clone();
+    let inspect_payload = client
+        .call_tool(CallToolRequestParam {
+            name: "inspect_xcode_sdks".into(),
+            arguments: Some(inspect_args),
+        })
+        .await
+        .expect("inspect_xcode_sdks should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+    let inspect_missing = inspect_payload
+        .get("missing_required_sdks")
+        .and_then(|v| v.as_array())
+        .map(|v| v.len())
+        .unwrap_or(0);
 
-    let call_result = client
+    let validate_args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "required_sdks": ["visionOS", "visionOS Simulator"],
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer",
+        "strict": true
+    })
+    .as_object()
+    .expect("JSON object")
+    .clone();
+    let validate_result = client
         .call_tool(CallToolRequestParam {
             name: "validate_sandbox_policy".into(),
-            arguments: Some(args),
+            arguments: Some(validate_args),
         })
         .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let response = call_result.expect("validate_sandbox_policy should return a success response");
-    let payload = response
-        .structured_content
-        .expect("structured_content should exist");
-    assert_eq!(payload.get("status").and_then(|v| v.as_str()), Some("ok"));
-    assert!(
-        payload
-            .get("checks")
-            .and_then(|v| v.as_array())
-            .map(|checks| !checks.is_empty())
-            .unwrap_or(false),
-        "checks must not be empty"
-    );
-    let diagnostics = payload
-        .get("diagnostics")
-        .and_then(|v| v.as_object())
-        .expect("diagnostics should exist");
-    assert_eq!(
-        diagnostics.get("probe_mode").and_then(Value::as_str),
-        Some("env")
-    );
-    assert_eq!(
-        diagnostics
-            .get("effective_required_sdks")
-            .and_then(Value::as_array)
-            .map(|v| v.len()),
-        Some(2)
-    );
-    assert!(
-        diagnostics
-            .get("detected_sdks_normalized")
-            .and_then(Value::as_array)
-            .map(|v| !v.is_empty())
-            .unwrap_or(false),
-        "detected_sdks_normalized should not be empty"
-    );
+    assert_eq!(inspect_missing, 1);
+    let validate_error = validate_result.expect_err("validate should fail with missing sdk");
+    match validate_error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "sdk_missing", "blocked", false);
+            let diagnostics_required_len = error_field(&inner, "details")
+                .and_then(Value::as_object)
+                .and_then(|details| details.get("diagnostics"))
+                .and_then(Value::as_object)
+                .and_then(|diag| diag.get("effective_required_sdks"))
+                .and_then(Value::as_array)
+                .map(|v| v.len());
+            assert_eq!(diagnostics_required_len, Some(2));
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
     configure_sandbox_probe_env();
     Ok(())
 }
 
 #[tokio::test]
-async fn sandbox_tool_returns_diagnostics_for_sdk_missing() -> Result<()> {
-    let _guard = SANDBOX_ENV_LOCK
-        .lock()
-        .expect("sandbox env lock should not be poisoned");
+async fn inspect_tool_maps_invalid_xcode_path_error() -> Result<()> {
+    let _guard = SANDBOX_ENV_LOCK.lock().await;
     enable_fast_timeout();
-    configure_sandbox_probe_env_with_sdks("");
+    // Force system probe so xcode_path existence is checked.
+    env::set_var("VISIONOS_SANDBOX_PROBE", "system");
     let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
@@ -1190,9 +5333,8 @@ async fn sandbox_tool_returns_diagnostics_for_sdk_missing() -> Result<()> {
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
     let args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
         "required_sdks": ["visionOS"],
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
+        "xcode_path": "/tmp/does-not-exist"
     })
     .as_object()
     .expect("JSON object")
@@ -1200,7 +5342,7 @@ async fn sandbox_tool_returns_diagnostics_for_sdk_missing() -> Result<()> {
 
     let call_result = client
         .call_tool(CallToolRequestParam {
-            name: "validate_sandbox_policy".into(),
+            name: "inspect_xcode_sdks".into(),
             arguments: Some(args),
         })
         .await;
@@ -1208,26 +5350,10 @@ async fn sandbox_tool_returns_diagnostics_for_sdk_missing() -> Result<()> {
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = call_result.expect_err("expected sdk_missing error");
+    let error = call_result.expect_err("invalid xcode path should return an error");
     match error {
         ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "sdk_missing", "blocked", false);
-            let diagnostics = error_field(&inner, "details")
-                .and_then(Value::as_object)
-                .and_then(|details| details.get("diagnostics"))
-                .and_then(Value::as_object)
-                .expect("diagnostics should exist in sdk_missing details");
-            assert_eq!(
-                diagnostics.get("probe_mode").and_then(Value::as_str),
-                Some("env")
-            );
-            assert_eq!(
-                diagnostics
-                    .get("effective_required_sdks")
-                    .and_then(Value::as_array)
-                    .map(|v| v.len()),
-                Some(1)
-            );
+            assert_error_metadata(&inner, "xcode_unlicensed", "blocked", false);
         }
         other => panic!("Unexpected error: {other:?}", other = other),
     }
@@ -1236,10 +5362,8 @@ async fn sandbox_tool_returns_diagnostics_for_sdk_missing() -> Result<()> {
 }
 
 #[tokio::test]
-async fn inspect_tool_reports_sdk_inventory() -> Result<()> {
-    let _guard = SANDBOX_ENV_LOCK
-        .lock()
-        .expect("sandbox env lock should not be poisoned");
+async fn sandbox_tool_rejects_disallowed_path() -> Result<()> {
+    let _guard = SANDBOX_ENV_LOCK.lock().await;
     enable_fast_timeout();
     configure_sandbox_probe_env();
     let config = test_server_config(20);
@@ -1253,57 +5377,72 @@ async fn inspect_tool_reports_sdk_inventory() -> Result<()> {
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
     let args = json!({
-        "required_sdks": ["visionOS", "visionOS Simulator"],
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
+        "project_path": "/tmp/disallowed-project",
+        "required_sdks": ["visionOS"],
+        "xcode_path": "/Applications/Xcode.app/Contents/Developer",
+        "strict": true
     })
     .as_object()
     .expect("JSON object")
     .clone();
 
-    let response = client
+    let call_result = client
         .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_sdks".into(),
+            name: "validate_sandbox_policy".into(),
             arguments: Some(args),
         })
-        .await
-        .expect("inspect_xcode_sdks should succeed")
-        .structured_content
-        .expect("structured_content should exist");
+        .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    assert_eq!(response.get("status").and_then(|v| v.as_str()), Some("ok"));
-    assert_eq!(
-        response.get("probe_mode").and_then(|v| v.as_str()),
-        Some("env")
-    );
-    assert_eq!(
-        response
-            .get("missing_required_sdks")
-            .and_then(|v| v.as_array())
-            .map(|v| v.len()),
-        Some(0)
-    );
-    assert!(
-        response
-            .get("detected_sdks_normalized")
-            .and_then(|v| v.as_array())
-            .map(|v| !v.is_empty())
-            .unwrap_or(false),
-        "detected_sdks_normalized should not be empty"
-    );
+    let error = call_result.expect_err("expected path_not_allowed error");
+    match error {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "path_not_allowed", "blocked", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
     configure_sandbox_probe_env();
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_tool_reports_missing_required_sdk_in_env_mode() -> Result<()> {
-    let _guard = SANDBOX_ENV_LOCK
-        .lock()
-        .expect("sandbox env lock should not be poisoned");
-    enable_fast_timeout();
-    configure_sandbox_probe_env_with_sdks("");
+async fn sandbox_tool_rejects_remediation_unless_allowed() -> Result<()> {
+    let server = build_server(test_server_config(20));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let args = json!({
+        "project_path": allowed_project_path().to_string_lossy(),
+        "remediate": true
+    });
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "validate_sandbox_policy".into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let ServiceError::McpError(inner) = call_result.expect_err("remediation is not allowed") else {
+        panic!("expected an MCP error");
+    };
+    assert_error_metadata(&inner, "remediation_not_allowed", "blocked", false);
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_simulators_tool_returns_available_visionos_devices() -> Result<()> {
+    let _guard = SIMCTL_ENV_LOCK.lock().await;
+    env::set_var("MOCK_SIMCTL_BEHAVIOR", "success");
     let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
@@ -1314,50 +5453,41 @@ async fn inspect_tool_reports_missing_required_sdk_in_env_mode() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "required_sdks": ["visionOS"],
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
     let response = client
         .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_sdks".into(),
-            arguments: Some(args),
+            name: "list_simulators".into(),
+            arguments: Some(json!({}).as_object().expect("JSON object").clone()),
         })
         .await
-        .expect("inspect_xcode_sdks should succeed")
+        .expect("list_simulators should succeed")
         .structured_content
         .expect("structured_content should exist");
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
+    assert_eq!(response.get("status").and_then(Value::as_str), Some("ok"));
+    let simulators = response
+        .get("simulators")
+        .and_then(Value::as_array)
+        .expect("simulators should be an array");
     assert_eq!(
-        response.get("status").and_then(|v| v.as_str()),
-        Some("error")
-    );
-    assert_eq!(
-        response
-            .get("missing_required_sdks")
-            .and_then(|v| v.as_array())
-            .map(|v| v.len()),
-        Some(1)
+        simulators.len(),
+        2,
+        "unavailable and iOS devices are filtered"
     );
-    configure_sandbox_probe_env();
+    assert!(simulators.iter().all(|device| device
+        .get("runtime")
+        .and_then(Value::as_str)
+        .is_some_and(|runtime| runtime.starts_with("visionOS"))));
+    env::remove_var("MOCK_SIMCTL_BEHAVIOR");
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_schemes_tool_returns_scheme_list() -> Result<()> {
-    let _guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
-    enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
-    let config = test_server_config(20);
+async fn list_devices_tool_reports_reachable_vision_pro_hardware() -> Result<()> {
+    let mut config = test_server_config(20);
+    config.visionos.allowed_device_udids = vec!["00008112-001A2C3E0123401E".into()];
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -1367,51 +5497,42 @@ async fn inspect_schemes_tool_returns_scheme_list() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "project_path": allowed_project_xcodeproj_path().to_string_lossy(),
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
     let response = client
         .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_schemes".into(),
-            arguments: Some(args),
+            name: "list_devices".into(),
+            arguments: Some(json!({}).as_object().expect("JSON object").clone()),
         })
         .await
-        .expect("inspect_xcode_schemes should succeed")
+        .expect("list_devices should succeed")
         .structured_content
         .expect("structured_content should exist");
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    assert_eq!(response.get("status").and_then(Value::as_str), Some("ok"));
+    let devices = response
+        .get("devices")
+        .and_then(Value::as_array)
+        .expect("devices should be an array");
     assert_eq!(
-        response.get("project_path_source").and_then(Value::as_str),
-        Some("request")
+        devices.len(),
+        1,
+        "unreachable and non-visionOS devices are filtered"
     );
-    let schemes = response
-        .get("schemes")
-        .and_then(Value::as_array)
-        .expect("schemes should be an array");
-    assert!(
-        !schemes.is_empty(),
-        "inspect_xcode_schemes should return at least one scheme"
+    assert_eq!(devices[0]["name"], json!("Studio Vision Pro"));
+    assert_eq!(devices[0]["connection_state"], json!("connected"));
+    assert_eq!(devices[0]["allowed"], json!(true));
+    assert_eq!(
+        devices[0]["destination"],
+        json!("platform=visionOS,id=00008112-001A2C3E0123401E")
     );
-    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_schemes_tool_maps_xcodebuild_list_failed() -> Result<()> {
-    let _guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
-    enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "fail");
+async fn capture_simulator_media_tool_stores_screenshots_and_recordings() -> Result<()> {
+    let _guard = SIMCTL_ENV_LOCK.lock().await;
+    env::set_var("MOCK_SIMCTL_BEHAVIOR", "success");
     let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
@@ -1421,49 +5542,72 @@ async fn inspect_schemes_tool_maps_xcodebuild_list_failed() -> Result<()> {
         Result::<_, anyhow::Error>::Ok(())
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let capture = |args: Value| {
+        client.call_tool(CallToolRequestParam {
+            name: "capture_simulator_media".into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
+        })
+    };
 
-    let args = json!({
-        "project_path": allowed_project_xcodeproj_path().to_string_lossy(),
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let call_result = client
+    let screenshot = capture(json!({ "udid": "0E3C9A42-7A0B-4C55-9E1F-2B7D6C1A9F10" }))
+        .await
+        .expect("screenshot should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+    let video = capture(json!({
+        "udid": "0E3C9A42-7A0B-4C55-9E1F-2B7D6C1A9F10",
+        "kind": "video",
+        "duration_secs": 1
+    }))
+    .await
+    .expect("recording should succeed")
+    .structured_content
+    .expect("structured_content should exist");
+    let not_booted = capture(json!({ "udid": "F556D53F-412A-4778-AF81-3449D52F5A7F" })).await;
+    let fetched = client
         .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_schemes".into(),
-            arguments: Some(args),
+            name: "fetch_build_output".into(),
+            arguments: Some(
+                json!({ "job_id": screenshot["job_id"] })
+                    .as_object()
+                    .expect("JSON object")
+                    .clone(),
+            ),
         })
-        .await;
+        .await
+        .expect("fetch should succeed")
+        .structured_content
+        .expect("structured_content should exist");
 
     let _ = client.cancel().await;
     let _ = server_task.await;
-
-    let error = call_result.expect_err("xcodebuild failure should return an error");
-    match error {
+    env::remove_var("MOCK_SIMCTL_BEHAVIOR");
+
+    assert!(screenshot["path"]
+        .as_str()
+        .is_some_and(|path| path.ends_with("screenshot.png")));
+    assert_eq!(screenshot["size_bytes"], json!(8));
+    assert_eq!(video["kind"], json!("video"));
+    assert_eq!(video["duration_secs"], json!(1));
+    assert!(video["path"]
+        .as_str()
+        .is_some_and(|path| path.ends_with("recording.mov")));
+    assert_eq!(fetched["artifact_kind"], json!("media"));
+    assert_eq!(fetched["artifact_zip"], screenshot["path"]);
+    match not_booted.expect_err("shut down simulators cannot be captured") {
         ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "xcodebuild_list_failed", "no_violation", true);
+            assert_error_metadata(&inner, "simulator_not_booted", "no_violation", false);
         }
         other => panic!("Unexpected error: {other:?}", other = other),
     }
-    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_schemes_tool_uses_cwd_xcodeproj_when_project_path_is_omitted() -> Result<()> {
-    let _env_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
-    let _cwd_guard = CWD_LOCK.lock().expect("cwd lock should not be poisoned");
+async fn run_pipeline_tool_runs_steps_in_order_and_skips_after_failure() -> Result<()> {
+    let _guard = SIMCTL_ENV_LOCK.lock().await;
+    env::set_var("MOCK_SIMCTL_BEHAVIOR", "success");
     enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
-
-    let workspace_dir =
-        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/visionos/workspace");
-    let _cwd = CwdGuard::change_to(&workspace_dir)?;
-
     let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
@@ -1473,53 +5617,83 @@ async fn inspect_schemes_tool_uses_cwd_xcodeproj_when_project_path_is_omitted()
         Result::<_, anyhow::Error>::Ok(())
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
-
-    let args = json!({
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let response = client
-        .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_schemes".into(),
-            arguments: Some(args),
+    let pipeline = |steps: Value, behavior: &str| {
+        client.call_tool(CallToolRequestParam {
+            name: "run_pipeline".into(),
+            arguments: Some(
+                json!({
+                    "steps": steps,
+                    "simulator_udid": "0E3C9A42-7A0B-4C55-9E1F-2B7D6C1A9F10",
+                    "build": {
+                        "project_path": allowed_project_path().to_string_lossy(),
+                        "scheme": "VisionApp",
+                        "destination": "platform=visionOS Simulator,name=Apple Vision Pro",
+                        "env_overrides": { "MOCK_XCODEBUILD_BEHAVIOR": behavior }
+                    }
+                })
+                .as_object()
+                .expect("JSON object")
+                .clone(),
+            ),
         })
+    };
+
+    let succeeded = pipeline(json!(["build", "install", "screenshot"]), "success")
         .await
-        .expect("inspect_xcode_schemes should succeed")
+        .expect("pipeline should succeed")
         .structured_content
         .expect("structured_content should exist");
+    let failed = pipeline(json!(["build", "install", "screenshot"]), "fail")
+        .await
+        .expect("a failed step is reported in the response")
+        .structured_content
+        .expect("structured_content should exist");
+    let invalid = pipeline(json!(["install", "build"]), "success").await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
+    env::remove_var("MOCK_SIMCTL_BEHAVIOR");
 
+    assert_eq!(succeeded["status"], json!("succeeded"));
+    let steps = succeeded["steps"].as_array().expect("steps array");
     assert_eq!(
-        response.get("project_path_source").and_then(Value::as_str),
-        Some("cwd")
+        steps.iter().map(|step| &step["step"]).collect::<Vec<_>>(),
+        [&json!("build"), &json!("install"), &json!("screenshot")]
     );
-
-    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
+    assert!(steps
+        .iter()
+        .all(|step| step["status"] == json!("succeeded")));
+    assert_eq!(steps[1]["job_id"], steps[0]["job_id"]);
+    assert!(steps[1]["output"]["app_path"]
+        .as_str()
+        .is_some_and(|path| path.ends_with("VisionApp.app")));
+    assert!(steps[2]["output"]["path"]
+        .as_str()
+        .is_some_and(|path| path.ends_with("screenshot.png")));
+    let record_path = succeeded["record_path"].as_str().expect("record_path");
+    let record: Value = serde_json::from_slice(&std::fs::read(record_path)?)?;
+    assert_eq!(record["pipeline_id"], succeeded["pipeline_id"]);
+
+    assert_eq!(failed["status"], json!("failed"));
+    let steps = failed["steps"].as_array().expect("steps array");
+    assert_eq!(steps[0]["status"], json!("failed"));
+    assert!(steps[0]["error"]["data"]["code"].is_string());
+    assert_eq!(steps[1]["status"], json!("skipped"));
+    assert_eq!(steps[2]["status"], json!("skipped"));
+    match invalid.expect_err("install before build is rejected") {
+        ServiceError::McpError(inner) => {
+            assert_error_metadata(&inner, "invalid_pipeline", "no_violation", false);
+        }
+        other => panic!("Unexpected error: {other:?}", other = other),
+    }
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_schemes_tool_uses_config_default_when_no_request_or_cwd_xcodeproj() -> Result<()> {
-    let _env_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
-    let _cwd_guard = CWD_LOCK.lock().expect("cwd lock should not be poisoned");
-    enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "success");
-
-    let empty_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("target")
-        .join(format!("inspect-schemes-config-default-{}", Uuid::new_v4()));
-    std::fs::create_dir_all(&empty_dir)?;
-    let _cwd = CwdGuard::change_to(&empty_dir)?;
-
-    let mut config = test_server_config(20);
-    config.visionos.default_project_path = Some(allowed_project_xcodeproj_path());
+async fn boot_simulator_tool_reports_state_transition() -> Result<()> {
+    let _guard = SIMCTL_ENV_LOCK.lock().await;
+    env::set_var("MOCK_SIMCTL_BEHAVIOR", "success");
+    let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -1529,46 +5703,60 @@ async fn inspect_schemes_tool_uses_config_default_when_no_request_or_cwd_xcodepr
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let response = client
+    let boot = client
         .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_schemes".into(),
-            arguments: Some(args),
+            name: "boot_simulator".into(),
+            arguments: Some(
+                json!({ "udid": "f556d53f-412a-4778-af81-3449d52f5a7f" })
+                    .as_object()
+                    .expect("JSON object")
+                    .clone(),
+            ),
         })
         .await
-        .expect("inspect_xcode_schemes should succeed")
+        .expect("boot_simulator should succeed")
+        .structured_content
+        .expect("structured_content should exist");
+    let shutdown = client
+        .call_tool(CallToolRequestParam {
+            name: "shutdown_simulator".into(),
+            arguments: Some(
+                json!({ "udid": "F556D53F-412A-4778-AF81-3449D52F5A7F" })
+                    .as_object()
+                    .expect("JSON object")
+                    .clone(),
+            ),
+        })
+        .await
+        .expect("shutdown_simulator should succeed")
         .structured_content
         .expect("structured_content should exist");
 
     let _ = client.cancel().await;
     let _ = server_task.await;
-    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
 
+    assert_eq!(boot.get("state").and_then(Value::as_str), Some("Booted"));
     assert_eq!(
-        response.get("project_path_source").and_then(Value::as_str),
-        Some("config")
+        boot.get("previous_state").and_then(Value::as_str),
+        Some("Shutdown")
+    );
+    assert_eq!(boot.get("changed").and_then(Value::as_bool), Some(true));
+    assert_eq!(
+        shutdown.get("state").and_then(Value::as_str),
+        Some("Shutdown")
+    );
+    assert_eq!(
+        shutdown.get("changed").and_then(Value::as_bool),
+        Some(false)
     );
+    env::remove_var("MOCK_SIMCTL_BEHAVIOR");
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_schemes_tool_returns_project_path_missing_when_no_request_cwd_or_config(
-) -> Result<()> {
-    let _cwd_guard = CWD_LOCK.lock().expect("cwd lock should not be poisoned");
-    enable_fast_timeout();
-
-    let empty_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("target")
-        .join(format!("inspect-schemes-empty-{}", Uuid::new_v4()));
-    std::fs::create_dir_all(&empty_dir)?;
-    let _cwd = CwdGuard::change_to(&empty_dir)?;
-
+async fn boot_simulator_tool_rejects_unknown_udid() -> Result<()> {
+    let _guard = SIMCTL_ENV_LOCK.lock().await;
+    env::set_var("MOCK_SIMCTL_BEHAVIOR", "success");
     let config = test_server_config(20);
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
@@ -1579,36 +5767,34 @@ async fn inspect_schemes_tool_returns_project_path_missing_when_no_request_cwd_o
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
     let call_result = client
         .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_schemes".into(),
-            arguments: Some(args),
+            name: "boot_simulator".into(),
+            arguments: Some(
+                json!({ "udid": Uuid::new_v4().to_string() })
+                    .as_object()
+                    .expect("JSON object")
+                    .clone(),
+            ),
         })
         .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = call_result.expect_err("missing project path should be rejected");
+    let error = call_result.expect_err("unknown udid should return an error");
     match error {
         ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "project_path_missing", "no_violation", false);
+            assert_error_metadata(&inner, "simulator_not_found", "no_violation", false);
         }
         other => panic!("Unexpected error: {other:?}", other = other),
     }
-
+    env::remove_var("MOCK_SIMCTL_BEHAVIOR");
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_schemes_tool_rejects_relative_project_path() -> Result<()> {
+async fn test_tool_publishes_markdown_summary_resource() -> Result<()> {
     enable_fast_timeout();
     let config = test_server_config(20);
     let server = build_server(config);
@@ -1621,38 +5807,215 @@ async fn inspect_schemes_tool_rejects_relative_project_path() -> Result<()> {
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
     let args = json!({
-        "project_path": "VisionApp.xcodeproj",
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
+        "project_path": allowed_project_path().to_string_lossy(),
+        "scheme": "VisionApp",
+        "env_overrides": {
+            "MOCK_XCODEBUILD_BEHAVIOR": "test_failure"
+        }
     })
     .as_object()
     .expect("JSON object")
     .clone();
 
-    let call_result = client
-        .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_schemes".into(),
-            arguments: Some(args),
+    let call_result = client
+        .call_tool(CallToolRequestParam {
+            name: "test_visionos_app".into(),
+            arguments: Some(args),
+        })
+        .await;
+    let payload = call_result
+        .expect("test_visionos_app should return a summary for failing tests")
+        .structured_content
+        .expect("structured_content should exist");
+    let summary_uri = payload
+        .get("summary_uri")
+        .and_then(Value::as_str)
+        .expect("summary_uri should exist")
+        .to_string();
+    let resource = client
+        .read_resource(ReadResourceRequestParam {
+            uri: summary_uri.clone(),
+        })
+        .await;
+    let listed = client.list_resources(None).await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(
+        payload.get("status").and_then(Value::as_str),
+        Some("failed")
+    );
+    let summary = payload.get("summary").expect("summary should exist");
+    assert_eq!(summary.get("total").and_then(Value::as_u64), Some(3));
+    assert_eq!(summary.get("failed").and_then(Value::as_u64), Some(1));
+    assert!(summary_uri.starts_with("seiro://visionos/test-summaries/"));
+
+    let contents = resource?.contents;
+    let text = match contents.first() {
+        Some(ResourceContents::TextResourceContents { text, .. }) => text.clone(),
+        other => panic!("Unexpected resource contents: {other:?}"),
+    };
+    assert!(text.contains("**failed** (3 total, 2 passed, 1 failed, 0 skipped)"));
+    assert!(text.contains("VisionAppTests.VisionAppTests.testGesture"));
+    assert!(listed?
+        .resources
+        .iter()
+        .any(|resource| resource.uri == summary_uri));
+    Ok(())
+}
+
+#[tokio::test]
+async fn federation_forwards_builds_and_copies_artifacts_back() -> Result<()> {
+    enable_fast_timeout();
+    let state = tempfile::tempdir()?;
+    let downstream_config = state.path().join("downstream.toml");
+    std::fs::write(
+        &downstream_config,
+        format!(
+            r#"[visionos]
+allowed_paths = [{workspace:?}]
+allowed_schemes = ["VisionApp"]
+xcode_path = "/Applications/Xcode.app/Contents/Developer"
+xcodebuild_path = {xcodebuild:?}
+xcrun_path = {xcrun:?}
+"#,
+            workspace = allowed_project_path().parent().expect("workspace"),
+            xcodebuild = mock_xcodebuild_path(),
+            xcrun = mock_xcrun_path(),
+        ),
+    )?;
+    let mut config = test_server_config(20);
+    config.federation.downstreams = vec![
+        Downstream {
+            name: "offline".into(),
+            command: state.path().join("missing-seiro-mcp"),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            token: None,
+        },
+        Downstream {
+            name: "mac-a".into(),
+            command: PathBuf::from(env!("CARGO_BIN_EXE_seiro-mcp")),
+            args: Vec::new(),
+            env: BTreeMap::from([(
+                "MCP_CONFIG_PATH".to_string(),
+                downstream_config.to_string_lossy().to_string(),
+            )]),
+            token: Some("downstream-token".into()),
+        },
+    ];
+    let server = build_server(config);
+    let artifact_root = server.artifact_store().root_dir();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let build = client
+        .call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: json!({
+                "project_path": allowed_project_path().to_string_lossy(),
+                "scheme": "VisionApp",
+                "env_overrides": { "MOCK_XCODEBUILD_BEHAVIOR": "success" }
+            })
+            .as_object()
+            .cloned(),
+        })
+        .await;
+    let build = build?.structured_content.expect("structured build result");
+    let job_id = build["job_id"].as_str().expect("job_id").to_string();
+    let output = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: json!({ "job_id": job_id }).as_object().cloned(),
+        })
+        .await;
+
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    assert_eq!(build["status"], json!("succeeded"));
+    assert_eq!(build["downstream"], json!("mac-a"));
+    let output = output?.structured_content.expect("structured fetch result");
+    assert_eq!(output["downstream"], json!("mac-a"));
+    let artifact_zip = PathBuf::from(output["artifact_zip"].as_str().expect("artifact_zip"));
+    assert!(artifact_zip.starts_with(artifact_root.join("federation").join(&job_id)));
+    let bytes = std::fs::read(&artifact_zip)?;
+    assert_eq!(
+        output["sha256"].as_str(),
+        Some(format!("{:x}", Sha256::digest(&bytes)).as_str())
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn capabilities_resource_reports_sdks_runtimes_and_hardware() -> Result<()> {
+    let _guard = SANDBOX_ENV_LOCK.lock().await;
+    configure_sandbox_probe_env();
+    let server = build_server(test_server_config(20));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+    let server_task = tokio::spawn(async move {
+        server.serve(server_transport).await?.waiting().await?;
+        Result::<_, anyhow::Error>::Ok(())
+    });
+    let client = serve_client(ClientInfo::default(), client_transport).await?;
+
+    let listed = client.list_resources(None).await;
+    let resource = client
+        .read_resource(ReadResourceRequestParam {
+            uri: "seiro://capabilities".into(),
         })
         .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = call_result.expect_err("relative project_path should be rejected");
-    match error {
-        ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "project_path_invalid", "no_violation", false);
-        }
-        other => panic!("Unexpected error: {other:?}", other = other),
-    }
+    assert!(listed?
+        .resources
+        .iter()
+        .any(|resource| resource.uri == "seiro://capabilities"));
+    let text = match resource?.contents.first() {
+        Some(ResourceContents::TextResourceContents { text, .. }) => text.clone(),
+        other => panic!("Unexpected resource contents: {other:?}"),
+    };
+    let capabilities: Value = serde_json::from_str(&text)?;
+    assert_eq!(
+        capabilities.get("sdks"),
+        Some(&json!(["visionOS", "visionOS Simulator"]))
+    );
+    assert_eq!(
+        capabilities.get("simulator_runtimes"),
+        Some(&json!(["26.2"]))
+    );
+    assert!(capabilities
+        .get("cpu_count")
+        .and_then(Value::as_u64)
+        .is_some_and(|count| count >= 1));
+    assert_eq!(
+        capabilities
+            .pointer("/xcodes/0/name")
+            .and_then(Value::as_str),
+        Some("default")
+    );
+    assert_eq!(
+        capabilities
+            .pointer("/disks/0/free_bytes")
+            .and_then(Value::as_u64),
+        Some(1099511627776)
+    );
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_schemes_tool_rejects_nonexistent_project_path() -> Result<()> {
+async fn status_resource_reports_queue_and_artifact_store() -> Result<()> {
     enable_fast_timeout();
-    let config = test_server_config(20);
-    let server = build_server(config);
+    let server = build_server(test_server_config(20));
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
     let server_task = tokio::spawn(async move {
@@ -1661,43 +6024,41 @@ async fn inspect_schemes_tool_rejects_nonexistent_project_path() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "project_path": "/tmp/does-not-exist/Nope.xcodeproj",
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let call_result = client
-        .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_schemes".into(),
-            arguments: Some(args),
+    let listed = client.list_resources(None).await;
+    let resource = client
+        .read_resource(ReadResourceRequestParam {
+            uri: "seiro://status".into(),
         })
         .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = call_result.expect_err("nonexistent project_path should be rejected");
-    match error {
-        ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "project_not_found", "no_violation", false);
-        }
-        other => panic!("Unexpected error: {other:?}", other = other),
-    }
+    assert!(listed?
+        .resources
+        .iter()
+        .any(|resource| resource.uri == "seiro://status"));
+    let text = match resource?.contents.first() {
+        Some(ResourceContents::TextResourceContents { text, .. }) => text.clone(),
+        other => panic!("Unexpected resource contents: {other:?}"),
+    };
+    let status: Value = serde_json::from_str(&text)?;
+    assert!(status.get("uptime_secs").and_then(Value::as_u64).is_some());
+    assert_eq!(
+        status.pointer("/queue/running").and_then(Value::as_u64),
+        Some(0)
+    );
+    assert!(status
+        .pointer("/artifact_store/bytes")
+        .and_then(Value::as_u64)
+        .is_some());
+    assert!(status.pointer("/artifact_store/backend/kind").is_some());
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_schemes_tool_maps_scheme_parse_failed() -> Result<()> {
-    let _env_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
-    enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "parse_invalid");
-    let config = test_server_config(20);
-    let server = build_server(config);
+async fn workflow_prompts_fill_in_arguments_and_config_defaults() -> Result<()> {
+    let server = build_server(test_server_config(20));
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
     let server_task = tokio::spawn(async move {
@@ -1706,43 +6067,58 @@ async fn inspect_schemes_tool_maps_scheme_parse_failed() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "project_path": allowed_project_xcodeproj_path().to_string_lossy(),
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let call_result = client
-        .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_schemes".into(),
-            arguments: Some(args),
+    let listed = client.list_prompts(None).await;
+    let prompt = client
+        .get_prompt(GetPromptRequestParam {
+            name: "build_and_fetch".into(),
+            arguments: Some(
+                json!({ "scheme": "VisionApp" })
+                    .as_object()
+                    .expect("object")
+                    .clone(),
+            ),
+        })
+        .await;
+    let missing_scheme = client
+        .get_prompt(GetPromptRequestParam {
+            name: "build_and_fetch".into(),
+            arguments: None,
         })
         .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
-    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
 
-    let error = call_result.expect_err("invalid JSON should map to scheme_parse_failed");
-    match error {
-        ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "scheme_parse_failed", "no_violation", true);
-        }
-        other => panic!("Unexpected error: {other:?}", other = other),
-    }
+    let names: Vec<String> = listed?
+        .prompts
+        .into_iter()
+        .map(|prompt| prompt.name)
+        .collect();
+    assert!(names.contains(&"build_and_fetch".to_string()));
+    assert!(names.contains(&"diagnose_build_failure".to_string()));
+    let text = match &prompt?.messages[0].content {
+        PromptMessageContent::Text { text } => text.clone(),
+        other => panic!("Unexpected prompt content: {other:?}"),
+    };
+    assert!(text.contains("`VisionApp`"));
+    assert!(text.contains("platform=visionOS Simulator,name=Apple Vision Pro"));
+    assert!(text.contains("fetch_build_output"));
+    assert!(missing_scheme.is_err());
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_schemes_tool_maps_no_schemes_found() -> Result<()> {
-    let _env_guard = XCODEBUILD_ENV_LOCK
-        .lock()
-        .expect("xcodebuild env lock should not be poisoned");
-    enable_fast_timeout();
-    env::set_var("MOCK_XCODEBUILD_BEHAVIOR", "no_schemes");
-    let config = test_server_config(20);
+async fn build_that_outlives_tool_timeout_releases_its_slot() -> Result<()> {
+    let mut config = test_server_config(5);
+    config.visionos.max_concurrent_builds = 1;
+    config.visionos.artifact_persistence = ArtifactPersistence::Memory;
+    config.tools.limits.insert(
+        "build_visionos_app".into(),
+        ToolLimits {
+            timeout_secs: 1,
+            max_concurrent: 0,
+        },
+    );
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -1751,44 +6127,74 @@ async fn inspect_schemes_tool_maps_no_schemes_found() -> Result<()> {
         Result::<_, anyhow::Error>::Ok(())
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let build = |behavior: &str, job_id: &str| {
+        let args = json!({
+            "project_path": allowed_project_path().to_string_lossy(),
+            "scheme": "VisionApp",
+            "job_id": job_id,
+            "env_overrides": { "MOCK_XCODEBUILD_BEHAVIOR": behavior }
+        });
+        client.call_tool(CallToolRequestParam {
+            name: "build_visionos_app".into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
+        })
+    };
 
-    let args = json!({
-        "project_path": allowed_project_xcodeproj_path().to_string_lossy(),
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let call_result = client
+    let slow_job = Uuid::new_v4().to_string();
+    let timed_out = build("sleep", &slow_job).await;
+    let next = tokio::time::timeout(
+        Duration::from_secs(20),
+        build("success", &Uuid::new_v4().to_string()),
+    )
+    .await;
+    let status = client
         .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_schemes".into(),
-            arguments: Some(args),
+            name: "get_queue_status".into(),
+            arguments: Some(serde_json::Map::new()),
+        })
+        .await?
+        .structured_content
+        .expect("structured_content should exist");
+    let slow_output = client
+        .call_tool(CallToolRequestParam {
+            name: "fetch_build_output".into(),
+            arguments: json!({ "job_id": slow_job }).as_object().cloned(),
         })
         .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
-    env::remove_var("MOCK_XCODEBUILD_BEHAVIOR");
 
-    let error = call_result.expect_err("no schemes should map to no_schemes_found");
-    match error {
-        ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "no_schemes_found", "no_violation", false);
-        }
-        other => panic!("Unexpected error: {other:?}", other = other),
-    }
+    let ServiceError::McpError(inner) = timed_out.expect_err("the slow build times out") else {
+        panic!("expected an MCP error");
+    };
+    assert_error_metadata(&inner, "tool_timeout", "not_applicable", true);
+    let next = next
+        .expect("the next build gets the slot")?
+        .structured_content
+        .expect("structured_content should exist");
+    assert_eq!(next["status"], json!("succeeded"));
+    assert_eq!(status["running"], json!([]));
+    let ServiceError::McpError(inner) = slow_output.expect_err("the slow job has no artifact")
+    else {
+        panic!("expected an MCP error");
+    };
+    assert_eq!(error_field(&inner, "code"), Some(&json!("job_cancelled")));
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_tool_and_validate_share_required_sdk_view() -> Result<()> {
-    let _guard = SANDBOX_ENV_LOCK
-        .lock()
-        .expect("sandbox env lock should not be poisoned");
-    enable_fast_timeout();
-    configure_sandbox_probe_env_with_sdks("visionOS");
-    let config = test_server_config(20);
+async fn tools_config_limits_listed_and_callable_tools() -> Result<()> {
+    let mut config = test_server_config(20);
+    config.tools = ToolsSection {
+        enabled: Some(vec![
+            "fetch_build_output".into(),
+            "list_build_jobs".into(),
+            "validate_sandbox_policy".into(),
+        ]),
+        disabled: vec!["validate_sandbox_policy".into()],
+        ..Default::default()
+    };
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -1798,75 +6204,38 @@ async fn inspect_tool_and_validate_share_required_sdk_view() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let inspect_args = json!({
-        "required_sdks": ["visionOS", "visionOS Simulator"],
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-    let inspect_payload = client
-        .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_sdks".into(),
-            arguments: Some(inspect_args),
-        })
-        .await
-        .expect("inspect_xcode_sdks should succeed")
-        .structured_content
-        .expect("structured_content should exist");
-    let inspect_missing = inspect_payload
-        .get("missing_required_sdks")
-        .and_then(|v| v.as_array())
-        .map(|v| v.len())
-        .unwrap_or(0);
-
-    let validate_args = json!({
-        "project_path": allowed_project_path().to_string_lossy(),
-        "required_sdks": ["visionOS", "visionOS Simulator"],
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-    let validate_result = client
+    let listed = client.list_tools(None).await;
+    let build = client
         .call_tool(CallToolRequestParam {
-            name: "validate_sandbox_policy".into(),
-            arguments: Some(validate_args),
+            name: "build_visionos_app".into(),
+            arguments: json!({ "scheme": "VisionApp" }).as_object().cloned(),
         })
         .await;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    assert_eq!(inspect_missing, 1);
-    let validate_error = validate_result.expect_err("validate should fail with missing sdk");
-    match validate_error {
-        ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "sdk_missing", "blocked", false);
-            let diagnostics_required_len = error_field(&inner, "details")
-                .and_then(Value::as_object)
-                .and_then(|details| details.get("diagnostics"))
-                .and_then(Value::as_object)
-                .and_then(|diag| diag.get("effective_required_sdks"))
-                .and_then(Value::as_array)
-                .map(|v| v.len());
-            assert_eq!(diagnostics_required_len, Some(2));
-        }
-        other => panic!("Unexpected error: {other:?}", other = other),
-    }
-    configure_sandbox_probe_env();
+    let mut names: Vec<String> = listed?
+        .tools
+        .into_iter()
+        .map(|tool| tool.name.into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["fetch_build_output", "list_build_jobs"]);
+    assert!(build.is_err(), "disabled tools should not be callable");
     Ok(())
 }
 
 #[tokio::test]
-async fn inspect_tool_maps_invalid_xcode_path_error() -> Result<()> {
-    let _guard = SANDBOX_ENV_LOCK
-        .lock()
-        .expect("sandbox env lock should not be poisoned");
-    enable_fast_timeout();
-    // Force system probe so xcode_path existence is checked.
-    env::set_var("VISIONOS_SANDBOX_PROBE", "system");
-    let config = test_server_config(20);
+async fn pipelines_and_scaffold_builds_refuse_disabled_build_tools() -> Result<()> {
+    let parent = tempfile::tempdir()?;
+    let mut config = test_server_config(20);
+    config
+        .visionos
+        .allowed_paths
+        .push(parent.path().to_path_buf());
+    config.tools.disabled = vec!["build_visionos_app".into()];
+    config.visionos.artifact_persistence = ArtifactPersistence::Memory;
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -1876,43 +6245,127 @@ async fn inspect_tool_maps_invalid_xcode_path_error() -> Result<()> {
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
 
-    let args = json!({
-        "required_sdks": ["visionOS"],
-        "xcode_path": "/tmp/does-not-exist"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let call_result = client
+    let pipeline = client
         .call_tool(CallToolRequestParam {
-            name: "inspect_xcode_sdks".into(),
-            arguments: Some(args),
+            name: "run_pipeline".into(),
+            arguments: json!({
+                "steps": ["sandbox_check", "build"],
+                "build": {
+                    "project_path": allowed_project_path().to_string_lossy(),
+                    "scheme": "VisionApp",
+                    "env_overrides": { "MOCK_XCODEBUILD_BEHAVIOR": "success" }
+                }
+            })
+            .as_object()
+            .cloned(),
+        })
+        .await;
+    let scaffold = client
+        .call_tool(CallToolRequestParam {
+            name: "create_visionos_project".into(),
+            arguments: json!({
+                "parent_path": parent.path().to_string_lossy(),
+                "name": "VisionApp",
+                "build": true
+            })
+            .as_object()
+            .cloned(),
         })
         .await;
+    let jobs = client
+        .call_tool(CallToolRequestParam {
+            name: "list_build_jobs".into(),
+            arguments: Some(serde_json::Map::new()),
+        })
+        .await?
+        .structured_content
+        .expect("structured_content should exist");
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = call_result.expect_err("invalid xcode path should return an error");
-    match error {
-        ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "xcode_unlicensed", "blocked", false);
-        }
-        other => panic!("Unexpected error: {other:?}", other = other),
+    for (result, detail) in [
+        (pipeline, ("step", "build")),
+        (scaffold, ("field", "build")),
+    ] {
+        let ServiceError::McpError(inner) = result.expect_err("the build is refused") else {
+            panic!("expected an MCP error");
+        };
+        assert_error_metadata(&inner, "tool_disabled", "blocked", false);
+        assert_eq!(
+            error_field(&inner, "tool"),
+            Some(&json!("build_visionos_app"))
+        );
+        assert_eq!(
+            error_field(&inner, "details").map(|details| &details[detail.0]),
+            Some(&json!(detail.1))
+        );
     }
-    configure_sandbox_probe_env();
+    assert!(
+        !parent.path().join("VisionApp").exists(),
+        "the project is not created when its build is refused"
+    );
+    assert_eq!(jobs["jobs"], json!([]));
     Ok(())
 }
 
 #[tokio::test]
-async fn sandbox_tool_rejects_disallowed_path() -> Result<()> {
-    let _guard = SANDBOX_ENV_LOCK
-        .lock()
-        .expect("sandbox env lock should not be poisoned");
+async fn purge_artifacts_is_registered_only_for_admin_servers() -> Result<()> {
+    let mut names_by_admin = Vec::new();
+    for admin in [false, true] {
+        let mut config = test_server_config(20);
+        config.tools.admin = admin;
+        let server = build_server(config);
+        let (server_transport, client_transport) = tokio::io::duplex(4096);
+
+        let server_task = tokio::spawn(async move {
+            server.serve(server_transport).await?.waiting().await?;
+            Result::<_, anyhow::Error>::Ok(())
+        });
+        let client = serve_client(ClientInfo::default(), client_transport).await?;
+        let listed = client.list_tools(None).await;
+        let purge = client
+            .call_tool(CallToolRequestParam {
+                name: "purge_artifacts".into(),
+                arguments: json!({ "scope": "expired" }).as_object().cloned(),
+            })
+            .await;
+        let _ = client.cancel().await;
+        let _ = server_task.await;
+
+        let names: Vec<String> = listed?
+            .tools
+            .into_iter()
+            .map(|tool| tool.name.into_owned())
+            .collect();
+        names_by_admin.push((names, purge));
+    }
+
+    let (default_names, default_purge) = &names_by_admin[0];
+    assert!(!default_names.contains(&"purge_artifacts".to_string()));
+    assert!(default_purge.is_err());
+    let (admin_names, admin_purge) = &names_by_admin[1];
+    assert!(admin_names.contains(&"purge_artifacts".to_string()));
+    let purged = admin_purge
+        .as_ref()
+        .expect("admin purge succeeds")
+        .structured_content
+        .clone()
+        .expect("structured content");
+    assert!(purged["removed_job_ids"].is_array());
+    assert!(purged["freed_bytes"].is_u64());
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_project_tool_scaffolds_app_and_package_and_builds_the_app() -> Result<()> {
     enable_fast_timeout();
-    configure_sandbox_probe_env();
-    let config = test_server_config(20);
+    let parent = tempfile::tempdir()?;
+    let mut config = test_server_config(20);
+    config
+        .visionos
+        .allowed_paths
+        .push(parent.path().to_path_buf());
     let server = build_server(config);
     let (server_transport, client_transport) = tokio::io::duplex(4096);
 
@@ -1921,34 +6374,74 @@ async fn sandbox_tool_rejects_disallowed_path() -> Result<()> {
         Result::<_, anyhow::Error>::Ok(())
     });
     let client = serve_client(ClientInfo::default(), client_transport).await?;
+    let create = |args: Value| {
+        client.call_tool(CallToolRequestParam {
+            name: "create_visionos_project".into(),
+            arguments: Some(args.as_object().expect("JSON object").clone()),
+        })
+    };
 
-    let args = json!({
-        "project_path": "/tmp/disallowed-project",
-        "required_sdks": ["visionOS"],
-        "xcode_path": "/Applications/Xcode.app/Contents/Developer"
-    })
-    .as_object()
-    .expect("JSON object")
-    .clone();
-
-    let call_result = client
-        .call_tool(CallToolRequestParam {
-            name: "validate_sandbox_policy".into(),
-            arguments: Some(args),
+    let app = create(json!({
+        "parent_path": parent.path().to_string_lossy(),
+        "name": "VisionApp",
+        "build": true
+    }))
+    .await?
+    .structured_content
+    .expect("structured_content should exist");
+    let package = create(json!({
+        "parent_path": parent.path().to_string_lossy(),
+        "name": "VisionKit",
+        "template": "package"
+    }))
+    .await?
+    .structured_content
+    .expect("structured_content should exist");
+    let existing = create(json!({
+        "parent_path": parent.path().to_string_lossy(),
+        "name": "VisionApp"
+    }))
+    .await;
+    let resource = client
+        .read_resource(ReadResourceRequestParam {
+            uri: app["scheme_uri"].as_str().expect("scheme_uri").into(),
         })
-        .await;
+        .await?;
 
     let _ = client.cancel().await;
     let _ = server_task.await;
 
-    let error = call_result.expect_err("expected path_not_allowed error");
-    match error {
+    let project_dir = parent.path().join("VisionApp");
+    assert_eq!(app["status"], json!("created"));
+    assert_eq!(
+        app["project_path"],
+        json!(project_dir.join("VisionApp.xcodeproj").to_string_lossy())
+    );
+    assert_eq!(app["scheme"], json!("VisionApp"));
+    assert_eq!(app["scheme_allowed"], json!(true));
+    let pbxproj = std::fs::read_to_string(project_dir.join("VisionApp.xcodeproj/project.pbxproj"))?;
+    assert!(pbxproj.contains("PRODUCT_BUNDLE_IDENTIFIER = com.example.VisionApp;"));
+    assert!(project_dir.join("VisionApp/VisionAppApp.swift").is_file());
+    assert_eq!(app["build"]["status"], json!("succeeded"));
+
+    assert_eq!(package["scheme_allowed"], json!(false));
+    assert!(package.get("build").is_none());
+    let manifest = std::fs::read_to_string(parent.path().join("VisionKit/Package.swift"))?;
+    assert!(manifest.contains(".library(name: \"VisionKit\", targets: [\"VisionKit\"])"));
+
+    match existing.expect_err("an existing project is not overwritten") {
         ServiceError::McpError(inner) => {
-            assert_error_metadata(&inner, "path_not_allowed", "blocked", false);
+            assert_error_metadata(&inner, "project_exists", "no_violation", false);
         }
         other => panic!("Unexpected error: {other:?}", other = other),
     }
-    configure_sandbox_probe_env();
+    let text = match resource.contents.first() {
+        Some(ResourceContents::TextResourceContents { text, .. }) => text.clone(),
+        other => panic!("Unexpected resource contents: {other:?}"),
+    };
+    let suggestion: Value = serde_json::from_str(&text)?;
+    assert_eq!(suggestion["project_path"], app["project_path"]);
+    assert_eq!(suggestion["scheme"], json!("VisionApp"));
     Ok(())
 }
 
@@ -1968,20 +6461,66 @@ fn test_server_config(max_build_minutes: u16) -> ServerConfig {
         server: ServerSection {
             host: "127.0.0.1".into(),
             port: 8787,
+            shutdown_grace_secs: 30,
+            rate_limit_per_minute: 0,
+            max_concurrent_calls: 0,
+            allow_external_bind: false,
+            max_connections: 16,
+            idle_timeout_secs: 0,
+            auth_failure_limit: 0,
+            auth_lockout_secs: 60,
         },
         visionos: VisionOsConfig {
             allowed_paths: vec![workspace],
-            allowed_schemes: vec!["VisionApp".into()],
+            allowed_roots: Vec::new(),
+            allowed_schemes: vec![SchemePattern::new("VisionApp").expect("valid scheme")],
             default_project_path: None,
             default_destination: "platform=visionOS Simulator,name=Apple Vision Pro".into(),
             required_sdks: vec!["visionOS".into(), "visionOS Simulator".into()],
             xcode_path: PathBuf::from("/Applications/Xcode.app/Contents/Developer"),
             xcodebuild_path: mock_xcodebuild_path(),
+            xcrun_path: mock_xcrun_path(),
             max_build_minutes,
+            max_timeout_minutes: max_build_minutes,
+            timeout_warning_percent: 80,
+            heartbeat_interval_secs: 15,
+            max_extended_build_minutes: 0,
+            max_concurrent_builds: 1,
             artifact_ttl_secs: 600,
             cleanup_schedule_secs: 60,
+            artifact_max_jobs: 0,
+            artifact_max_total_bytes: 0,
+            min_free_disk_bytes: 0,
+            sandbox_probe_ttl_secs: 0,
+            allow_remediation: false,
+            package_hosts: Vec::new(),
+            policy: Default::default(),
+            export_paths: vec![],
+            size_budgets: BTreeMap::new(),
+            artifact_compression: ZipCompression::default(),
+            artifact_symlinks: SymlinkPolicy::default(),
+            deterministic_artifacts: false,
+            derived_data_root: None,
+            build_cache: false,
+            project_config: false,
+            compilation_cache: None,
+            platforms: BTreeMap::new(),
+            locale: "en_US.UTF-8".into(),
+            signing: Default::default(),
+            allowed_device_udids: Vec::new(),
+            artifact_persistence: Default::default(),
+            presets: Default::default(),
+            xcodes: Default::default(),
+            retry: Default::default(),
         },
+        artifacts: ArtifactsConfig::default(),
+        redaction: Default::default(),
+        auth: Default::default(),
+        federation: Default::default(),
+        notifications: Default::default(),
+        tools: Default::default(),
         source_path: PathBuf::from("tests/fixtures/config_valid.toml"),
+        profile: None,
     }
 }
 
@@ -1996,6 +6535,40 @@ fn mock_xcodebuild_path() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/visionos/mock-xcodebuild.sh")
 }
 
+fn mock_xcrun_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/visionos/mock-xcrun.sh")
+}
+
+/// Client that records `notifications/message` events from the server.
+struct LoggingCollector {
+    sender: tokio::sync::mpsc::UnboundedSender<LoggingMessageNotificationParam>,
+}
+
+impl ClientHandler for LoggingCollector {
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let _ = self.sender.send(params);
+    }
+}
+
+/// Client that records `notifications/progress` events from the server.
+struct ProgressCollector {
+    sender: tokio::sync::mpsc::UnboundedSender<ProgressNotificationParam>,
+}
+
+impl ClientHandler for ProgressCollector {
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let _ = self.sender.send(params);
+    }
+}
+
 fn build_server(config: ServerConfig) -> VisionOsServer {
     VisionOsServer::new(config, "visionos-integration".into())
 }
@@ -2032,6 +6605,7 @@ fn enable_fast_timeout() {
 fn configure_sandbox_probe_env() {
     env::set_var("VISIONOS_SANDBOX_PROBE", "env");
     env::set_var("VISIONOS_SANDBOX_SDKS", "visionOS,visionOS Simulator");
+    env::set_var("VISIONOS_SANDBOX_RUNTIMES", "26.2");
     env::set_var("VISIONOS_SANDBOX_DEVTOOLS", "enabled");
     env::set_var("VISIONOS_SANDBOX_LICENSE", "accepted");
     env::set_var("VISIONOS_SANDBOX_DISK_BYTES", "1099511627776");
@@ -2040,6 +6614,7 @@ fn configure_sandbox_probe_env() {
 fn configure_sandbox_probe_env_with_sdks(sdks: &str) {
     env::set_var("VISIONOS_SANDBOX_PROBE", "env");
     env::set_var("VISIONOS_SANDBOX_SDKS", sdks);
+    env::set_var("VISIONOS_SANDBOX_RUNTIMES", "26.2");
     env::set_var("VISIONOS_SANDBOX_DEVTOOLS", "enabled");
     env::set_var("VISIONOS_SANDBOX_LICENSE", "accepted");
     env::set_var("VISIONOS_SANDBOX_DISK_BYTES", "1099511627776");